@@ -0,0 +1,106 @@
+//! 分享卡片（成绩单、每周小结、连续打卡）渲染成 PNG
+//!
+//! 使用内置点阵字体，只支持西文字符，所以卡片文字固定用英文。
+
+use embedded_graphics::{
+    mono_font::{iso_8859_1::{FONT_10X20, FONT_6X10}, MonoTextStyle},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+const WIDTH: u32 = 600;
+const HEIGHT: u32 = 340;
+const BACKGROUND: Rgb888 = Rgb888::new(250, 248, 242);
+const TEXT: Rgb888 = Rgb888::new(40, 40, 48);
+const MUTED: Rgb888 = Rgb888::new(120, 120, 130);
+
+/// 一张分享卡片的内容
+#[derive(Debug, Clone)]
+pub struct ShareCard {
+    pub title: String,
+    pub headline: String,              // 放大显示的主要数字，如 "Level 4" / "12 days"
+    pub lines: Vec<(String, String)>,  // 标签和数值
+    pub footer: String,
+    pub accent: [u8; 3],
+}
+
+struct Canvas {
+    pixels: Vec<u8>,
+    scale: u32,
+    origin: Point,
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = std::convert::Infallible;
+
+    /// 按 `scale` 放大绘制，`origin` 为放大内容的左上角
+    fn draw_iter<I: IntoIterator<Item = Pixel<Rgb888>>>(&mut self, pixels: I) -> Result<(), Self::Error> {
+        for Pixel(point, color) in pixels {
+            let x0 = self.origin.x + point.x * self.scale as i32;
+            let y0 = self.origin.y + point.y * self.scale as i32;
+            for y in y0..y0 + self.scale as i32 {
+                for x in x0..x0 + self.scale as i32 {
+                    if (0..WIDTH as i32).contains(&x) && (0..HEIGHT as i32).contains(&y) {
+                        let i = (y as usize * WIDTH as usize + x as usize) * 3;
+                        self.pixels[i..i + 3].copy_from_slice(&[color.r(), color.g(), color.b()]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Canvas {
+    fn text(&mut self, text: &str, x: i32, y: i32, scale: u32, large: bool, color: Rgb888) {
+        let font = if large { &FONT_10X20 } else { &FONT_6X10 };
+        (self.scale, self.origin) = (scale, Point::new(x, y));
+        let _ = Text::with_baseline(text, Point::zero(), MonoTextStyle::new(font, color), Baseline::Top).draw(self);
+        (self.scale, self.origin) = (1, Point::zero());
+    }
+
+    fn rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: Rgb888) {
+        let _ = Rectangle::new(Point::new(x, y), Size::new(width, height))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(self);
+    }
+}
+
+/// 把卡片渲染成 PNG（600×340）
+pub fn render_png(card: &ShareCard) -> Result<Vec<u8>, String> {
+    let mut canvas = Canvas {
+        pixels: [BACKGROUND.r(), BACKGROUND.g(), BACKGROUND.b()].repeat((WIDTH * HEIGHT) as usize),
+        scale: 1,
+        origin: Point::zero(),
+    };
+    let accent = Rgb888::new(card.accent[0], card.accent[1], card.accent[2]);
+
+    canvas.rect(0, 0, WIDTH, 56, accent);
+    canvas.text(&card.title, 24, 18, 1, true, Rgb888::WHITE);
+    canvas.text(&card.headline, 24, 76, 3, true, accent);
+    for (i, (label, value)) in card.lines.iter().enumerate() {
+        let y = 156 + i as i32 * 30;
+        canvas.text(label, 24, y, 1, true, MUTED);
+        canvas.text(value, 300, y, 1, true, TEXT);
+    }
+    canvas.rect(0, HEIGHT as i32 - 8, WIDTH, 8, accent);
+    canvas.text(&card.footer, 24, HEIGHT as i32 - 26, 1, false, MUTED);
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&canvas.pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(png)
+}
@@ -1,8 +1,11 @@
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, State};
 
-use crate::database::DatabaseManager;
-use crate::models::{Article, CreateArticleRequest, SaveSegmentsRequest, Segment, UpdateArticleRequest};
+use crate::database::{ArticleSnapshot, DatabaseManager};
+use crate::models::{
+    Actor, AlignedParagraph, Article, CreateArticleRequest, FocusWord, ImportedArticle, LanguageCheck, PackImportResult,
+    SaveSegmentsRequest, Segment, SegmentTiming, UpdateArticleRequest,
+};
 
 /// 获取所有文章列表
 #[tauri::command]
@@ -18,32 +21,119 @@ pub fn get_article(id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<Opt
     db.get_article(id).map_err(|e| e.to_string())
 }
 
-/// 创建文章
+/// 创建文章；不支持的语言（英文、西班牙语、法语、中文以外）直接拒绝
 #[tauri::command]
-pub fn create_article(request: CreateArticleRequest, db: State<'_, Mutex<DatabaseManager>>) -> Result<i64, String> {
+pub fn create_article(
+    request: CreateArticleRequest,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i64, String> {
+    let check = crate::text::language::check_article_language(&request.content);
+    if !check.supported {
+        return Err(check.warning.unwrap_or_default());
+    }
+    if let Some(warning) = &check.warning {
+        log::warn!("Creating article {:?}: {}", request.title, warning);
+    }
     let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
     db.create_article(&request.title, &request.content).map_err(|e| e.to_string())
 }
 
+/// 检测文章语言，保存前用于提示非英文内容
+#[tauri::command]
+pub fn detect_article_language(content: String) -> LanguageCheck {
+    crate::text::language::check_article_language(&content)
+}
+
+/// 手动设置文章语言（短单词表可能被识别成英文），分词和熟练度记录随之更新
+#[tauri::command]
+pub fn set_article_language(
+    id: i64,
+    language: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.set_article_language(id, &language).map_err(|e| e.to_string())
+}
+
 /// 更新文章
 #[tauri::command]
-pub fn update_article(id: i64, request: UpdateArticleRequest, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+pub fn update_article(
+    id: i64,
+    request: UpdateArticleRequest,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<bool, String> {
     let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
     db.update_article(id, request.title.as_deref(), request.content.as_deref())
         .map_err(|e| e.to_string())
 }
 
+/// 撤销栈最多保留的已删除文章数
+const MAX_UNDO_DELETES: usize = 20;
+
+/// 本次运行中删除的文章快照（只在内存中，重启后清空）
+#[derive(Default)]
+pub struct DeletedArticles(Vec<ArticleSnapshot>);
+
 /// 删除文章
 #[tauri::command]
-pub fn delete_article(id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+pub fn delete_article(
+    id: i64,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+    deleted_articles: State<'_, Mutex<DeletedArticles>>,
+) -> Result<bool, String> {
     let db = db.lock().map_err(|e| e.to_string())?;
-    db.delete_article(id).map_err(|e| e.to_string())
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let Some(snapshot) = db.snapshot_article(id).map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+    db.delete_article(id).map_err(|e| e.to_string())?;
+    let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+    db.record_audit(user_name, "delete", "article", id, Some(&snapshot.title))
+        .map_err(|e| e.to_string())?;
+
+    let mut deleted_articles = deleted_articles.lock().map_err(|e| e.to_string())?;
+    deleted_articles.0.push(snapshot);
+    if deleted_articles.0.len() > MAX_UNDO_DELETES {
+        deleted_articles.0.remove(0);
+    }
+    Ok(true)
+}
+
+/// 恢复本次运行中最近删除的文章（连同分词和熟练度），返回恢复的文章 id
+#[tauri::command]
+pub fn undo_last_delete(
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+    deleted_articles: State<'_, Mutex<DeletedArticles>>,
+) -> Result<Option<i64>, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let mut deleted_articles = deleted_articles.lock().map_err(|e| e.to_string())?;
+    let Some(snapshot) = deleted_articles.0.pop() else {
+        return Ok(None);
+    };
+    if let Err(e) = db.restore_article(&snapshot) {
+        deleted_articles.0.push(snapshot);
+        return Err(e.to_string());
+    }
+    let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+    db.record_audit(user_name, "restore", "article", snapshot.article_id, Some(&snapshot.title))
+        .map_err(|e| e.to_string())?;
+    Ok(Some(snapshot.article_id))
 }
 
 /// 保存分词结果
 #[tauri::command]
-pub fn save_segments(request: SaveSegmentsRequest, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
+pub fn save_segments(request: SaveSegmentsRequest, actor: Option<Actor>, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
     let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
     db.save_segments(request.article_id, &request.segment_type, &request.segments)
         .map_err(|e| e.to_string())
 }
@@ -54,3 +144,231 @@ pub fn get_segments(article_id: i64, segment_type: String, db: State<'_, Mutex<D
     let db = db.lock().map_err(|e| e.to_string())?;
     db.get_segments(article_id, &segment_type).map_err(|e| e.to_string())
 }
+
+/// 获取分词时间轴（字幕导入的文章才有）
+#[tauri::command]
+pub fn get_segment_timings(article_id: i64, segment_type: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<SegmentTiming>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_segment_timings(article_id, &segment_type).map_err(|e| e.to_string())
+}
+
+/// 设置文章标签
+#[tauri::command]
+pub fn set_article_tags(
+    article_id: i64,
+    tags: Vec<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.set_article_tags(article_id, &tags).map_err(|e| e.to_string())
+}
+
+/// 获取文章标签
+#[tauri::command]
+pub fn get_article_tags(article_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_article_tags(article_id).map_err(|e| e.to_string())
+}
+
+/// 保存文章译文（按段落与原文对齐），`content` 为空时删除该语言的译文
+#[tauri::command]
+pub fn save_article_translation(
+    article_id: i64,
+    language: String,
+    content: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<AlignedParagraph>, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.save_article_translation(article_id, &language, &content).map_err(|e| e.to_string())
+}
+
+/// 获取原文和译文的逐段对照，用于按段切换语言的阅读模式
+#[tauri::command]
+pub fn get_aligned_paragraphs(
+    article_id: i64,
+    language: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<AlignedParagraph>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_aligned_paragraphs(article_id, &language).map_err(|e| e.to_string())
+}
+
+/// 获取文章已有译文的语言
+#[tauri::command]
+pub fn get_translation_languages(article_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_translation_languages(article_id).map_err(|e| e.to_string())
+}
+
+/// 获取所有标签
+#[tauri::command]
+pub fn get_all_tags(db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_all_tags().map_err(|e| e.to_string())
+}
+
+/// 用 TF-IDF（以全部文章为语料）推荐文章的重点词汇
+#[tauri::command]
+pub fn suggest_focus_words(article_id: i64, count: Option<usize>, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<FocusWord>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let article = db.get_article(article_id).map_err(|e| e.to_string())?.ok_or("文章不存在")?;
+    let corpus = db.get_all_article_contents().map_err(|e| e.to_string())?;
+    Ok(crate::text::tf_idf_keywords(&article.content, &corpus, count.unwrap_or(20))
+        .into_iter()
+        .map(|(word, score, frequency)| FocusWord { word, score, frequency: frequency as i32 })
+        .collect())
+}
+
+/// 把选中的重点词汇保存为单词表
+#[tauri::command]
+pub fn save_focus_wordlist(
+    article_id: i64,
+    words: Vec<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ImportedArticle, String> {
+    let words: Vec<String> = words.iter().map(|w| w.trim().to_string()).filter(|w| !w.is_empty()).collect();
+    if words.is_empty() {
+        return Err("单词列表为空".to_string());
+    }
+    let title = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+        db.get_article(article_id).map_err(|e| e.to_string())?.ok_or("文章不存在")?.title
+    };
+
+    let doc = crate::importers::ParsedDocument {
+        title: format!("{} 重点词汇", title),
+        content: words.join("\n"),
+        word_list: Some(words),
+        cues: None,
+    };
+    crate::commands::import::import_document(&db, doc)
+}
+
+/// 生成可打印的练习纸 PDF，返回临时文件路径
+///
+/// 单词表导入后也是一篇文章，所以 `article_id` 既可以是文章也可以是单词表。
+/// `style`：`trace` 描红、`fill_blanks` 选词填空（需要文章有句子分词）、`scrambled` 字母重排。
+#[tauri::command]
+pub fn generate_worksheet_pdf(
+    article_id: i64,
+    style: String,
+    word_limit: Option<usize>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let sheet = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let article = db.get_article(article_id).map_err(|e| e.to_string())?.ok_or("文章不存在")?;
+        let mut seen = std::collections::HashSet::new();
+        let words: Vec<String> = db
+            .get_segments(article_id, "word")
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|s| s.content)
+            .filter(|w| seen.insert(w.to_lowercase()))
+            .take(word_limit.unwrap_or(20))
+            .collect();
+        let sentences = db
+            .get_segments(article_id, "sentence")
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|s| s.content)
+            .collect();
+        crate::worksheet::Worksheet { title: article.title, style: style.clone(), words, sentences }
+    };
+    let pdf = crate::worksheet::render_pdf(&sheet)?;
+
+    let dir = std::env::temp_dir().join("spelling-game-worksheets");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}-{}-{}.pdf", article_id, style, chrono::Local::now().format("%Y%m%d%H%M%S%3f")));
+    std::fs::write(&path, pdf).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 把文章连同分词、标签、根据文章生成的题目和题目音频缓存打包成 `.spelling-pack`，返回临时文件路径
+#[tauri::command]
+pub fn export_pack(
+    app: AppHandle,
+    article_ids: Vec<i64>,
+    title: String,
+    description: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let articles = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        article_ids
+            .iter()
+            .map(|&id| db.export_pack_article(id).map_err(|e| e.to_string())?.ok_or(format!("文章 {} 不存在", id)))
+            .collect::<Result<Vec<_>, String>>()?
+    };
+
+    // 题目音频只在朗读过（已缓存）时打包
+    let cache_dir = crate::commands::tts::audio_cache_dir(&app)?;
+    let mut audio: Vec<(String, Vec<u8>)> = Vec::new();
+    for question in articles.iter().flat_map(|a| &a.listening_questions) {
+        let name = crate::commands::tts::audio_cache_name(&question.audio_text, None);
+        if audio.iter().any(|(n, _)| *n == name) {
+            continue;
+        }
+        if let Ok(data) = std::fs::read(cache_dir.join(&name)) {
+            audio.push((name, data));
+        }
+    }
+    let pack = crate::packs::write_pack(&title, description.as_deref(), &articles, &audio)?;
+
+    let dir = std::env::temp_dir().join("spelling-game-packs");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let file_name: String = title.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect();
+    let path = dir.join(format!("{}-{}.{}", file_name, chrono::Local::now().format("%Y%m%d%H%M%S"), crate::packs::PACK_EXTENSION));
+    std::fs::write(&path, pack).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 导入 `.spelling-pack` 单元包：先校验格式版本和每个文件的校验和，损坏的包不导入任何内容
+#[tauri::command]
+pub fn import_pack(
+    app: AppHandle,
+    path: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<PackImportResult, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    import_pack_bytes(&app, &db, &bytes)
+}
+
+/// 校验并导入单元包内容，题目音频写入朗读缓存（本地文件和内容目录下载共用）
+pub(crate) fn import_pack_bytes(app: &AppHandle, db: &Mutex<DatabaseManager>, bytes: &[u8]) -> Result<PackImportResult, String> {
+    let pack = crate::packs::read_pack(bytes)?;
+    let imported = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.import_pack_articles(&pack.articles).map_err(|e| e.to_string())?
+    };
+
+    let cache_dir = crate::commands::tts::audio_cache_dir(app)?;
+    let mut audio_files = 0;
+    for (name, data) in &pack.audio {
+        let target = cache_dir.join(name);
+        if !target.exists() {
+            std::fs::write(&target, data).map_err(|e| e.to_string())?;
+            audio_files += 1;
+        }
+    }
+
+    Ok(PackImportResult {
+        title: pack.manifest.title,
+        version: pack.manifest.version,
+        app_version: pack.manifest.app_version,
+        article_ids: imported.iter().map(|(id, _)| *id).collect(),
+        questions_imported: imported.iter().map(|(_, count)| count).sum(),
+        audio_files,
+    })
+}
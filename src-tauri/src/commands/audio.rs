@@ -0,0 +1,215 @@
+use tauri::AppHandle;
+
+use crate::models::{AudioInputDevice, AudioSettings, MicrophoneLevel};
+
+/// 默认检测时长（毫秒）
+const DEFAULT_LEVEL_TEST_MS: u64 = 2000;
+/// 检测时长范围（毫秒）
+const LEVEL_TEST_RANGE_MS: (u64, u64) = (500, 10_000);
+/// 平均音量低于该值（dBFS）视为没有声音
+const SILENT_RMS_DB: f64 = -60.0;
+/// 平均音量低于该值（dBFS）视为太小，建议靠近麦克风或调高输入音量
+const LOW_RMS_DB: f64 = -40.0;
+/// 峰值达到该值视为削波（声音太大）
+const CLIPPING_PEAK: f64 = 0.99;
+/// 静音时的分贝值
+const MIN_DB: f64 = -100.0;
+
+/// 列出本机的麦克风，标出系统默认和设置中选择的麦克风
+#[tauri::command]
+pub fn list_audio_input_devices(app: AppHandle) -> Result<Vec<AudioInputDevice>, String> {
+    #[cfg(desktop)]
+    {
+        use cpal::traits::{DeviceTrait, HostTrait};
+
+        let selected = read_audio_settings(&app)?.input_device;
+        let host = cpal::default_host();
+        let default_name = host.default_input_device().and_then(|device| device.name().ok());
+        let devices = host.input_devices().map_err(|e| e.to_string())?;
+        Ok(devices
+            .filter_map(|device| {
+                let name = device.name().ok()?;
+                let config = device.default_input_config().ok();
+                Some(AudioInputDevice {
+                    is_default: default_name.as_deref() == Some(name.as_str()),
+                    selected: selected.as_deref() == Some(name.as_str()),
+                    channels: config.as_ref().map(|c| c.channels()),
+                    sample_rate: config.as_ref().map(|c| c.sample_rate().0),
+                    name,
+                })
+            })
+            .collect())
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = app;
+        Err("移动端由系统选择麦克风".to_string())
+    }
+}
+
+/// 选择麦克风并保存到设置，`device_name` 为空时恢复使用系统默认麦克风
+#[tauri::command]
+pub fn select_audio_input_device(app: AppHandle, device_name: Option<String>) -> Result<(), String> {
+    #[cfg(desktop)]
+    if let Some(name) = &device_name {
+        find_input_device(Some(name))?;
+    }
+    let mut settings = read_audio_settings(&app)?;
+    settings.input_device = device_name;
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "audio_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 录音 `duration_ms` 毫秒（默认 2 秒）检测麦克风音量，供口语测试前确认麦克风可用
+///
+/// `device_name` 为空时使用设置中选择的麦克风，没有选择时使用系统默认麦克风。
+#[tauri::command]
+pub async fn test_microphone_level(
+    app: AppHandle,
+    device_name: Option<String>,
+    duration_ms: Option<u64>,
+) -> Result<MicrophoneLevel, String> {
+    let device_name = match device_name {
+        Some(name) => Some(name),
+        None => read_audio_settings(&app)?.input_device,
+    };
+    let duration = std::time::Duration::from_millis(
+        duration_ms.unwrap_or(DEFAULT_LEVEL_TEST_MS).clamp(LEVEL_TEST_RANGE_MS.0, LEVEL_TEST_RANGE_MS.1),
+    );
+
+    #[cfg(desktop)]
+    {
+        // cpal 的录音流不能跨线程移动，在同一个阻塞线程中创建、录音和关闭
+        tokio::task::spawn_blocking(move || {
+            use cpal::traits::{DeviceTrait, StreamTrait};
+
+            let device = find_input_device(device_name.as_deref())?;
+            let name = device.name().map_err(|e| e.to_string())?;
+            let config = device.default_input_config().map_err(|e| e.to_string())?;
+            let samples = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let stream = match config.sample_format() {
+                cpal::SampleFormat::F32 => build_input_stream::<f32>(&device, &config.config(), samples.clone()),
+                cpal::SampleFormat::I16 => build_input_stream::<i16>(&device, &config.config(), samples.clone()),
+                cpal::SampleFormat::U16 => build_input_stream::<u16>(&device, &config.config(), samples.clone()),
+                cpal::SampleFormat::I32 => build_input_stream::<i32>(&device, &config.config(), samples.clone()),
+                other => Err(format!("不支持的采样格式: {}", other)),
+            }?;
+            stream.play().map_err(|e| e.to_string())?;
+            std::thread::sleep(duration);
+            drop(stream);
+
+            let samples = samples.lock().map_err(|e| e.to_string())?;
+            Ok(microphone_level(&name, &samples))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = (device_name, duration);
+        Err("移动端请在系统设置中检查麦克风".to_string())
+    }
+}
+
+/// 按名称查找麦克风，名称为空时使用系统默认麦克风
+#[cfg(desktop)]
+fn find_input_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| e.to_string())?
+            .find(|device| device.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| format!("找不到麦克风: {}", name)),
+        None => host.default_input_device().ok_or_else(|| "没有可用的麦克风".to_string()),
+    }
+}
+
+/// 创建录音流，采样统一转换为 -1..1 的 f32
+#[cfg(desktop)]
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: std::sync::Arc<std::sync::Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    use cpal::traits::DeviceTrait;
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if let Ok(mut samples) = samples.lock() {
+                    samples.extend(data.iter().map(|sample| sample.to_sample::<f32>()));
+                }
+            },
+            |e| log::warn!("Microphone stream error: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// 按录到的采样计算峰值和平均音量（RMS），并判断音量是否合适
+pub fn microphone_level(device: &str, samples: &[f32]) -> MicrophoneLevel {
+    let peak = samples.iter().fold(0.0f64, |peak, sample| peak.max(sample.abs() as f64));
+    let rms = if samples.is_empty() {
+        0.0
+    } else {
+        (samples.iter().map(|sample| (*sample as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+    };
+    let to_db = |value: f64| if value > 0.0 { (20.0 * value.log10()).max(MIN_DB) } else { MIN_DB };
+    let (peak_db, rms_db) = (to_db(peak), to_db(rms));
+    let status = if rms_db < SILENT_RMS_DB {
+        "silent"
+    } else if peak >= CLIPPING_PEAK {
+        "too_loud"
+    } else if rms_db < LOW_RMS_DB {
+        "low"
+    } else {
+        "ok"
+    };
+    MicrophoneLevel { device: device.to_string(), peak, rms, peak_db, rms_db, status: status.to_string() }
+}
+
+fn read_audio_settings(app: &AppHandle) -> Result<AudioSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "audio_settings.json")?;
+    if !config_path.exists() {
+        return Ok(AudioSettings::default());
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按平均音量和峰值区分无声、太小、合适和太大
+    #[test]
+    fn test_microphone_level_status() {
+        let tone = |amplitude: f32| -> Vec<f32> { (0..4800).map(|i| amplitude * (i as f32 * 0.1).sin()).collect() };
+
+        let silent = microphone_level("mic", &[]);
+        assert_eq!(silent.status, "silent");
+        assert_eq!(silent.rms_db, -100.0);
+        assert_eq!(microphone_level("mic", &tone(0.0005)).status, "silent");
+        assert_eq!(microphone_level("mic", &tone(0.005)).status, "low");
+
+        let ok = microphone_level("mic", &tone(0.3));
+        assert_eq!(ok.status, "ok");
+        assert!((ok.peak - 0.3).abs() < 0.01);
+        // 正弦波的 RMS 约为峰值的 0.707
+        assert!((ok.rms - 0.3 * std::f64::consts::FRAC_1_SQRT_2).abs() < 0.01);
+        assert!(ok.peak_db < 0.0 && ok.rms_db < ok.peak_db);
+
+        assert_eq!(microphone_level("mic", &tone(1.0)).status, "too_loud");
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::tts::{accommodated_rate, speak_and_wait, stop_speaking, tts_language};
+use crate::database::DatabaseManager;
+use crate::models::{AudioReviewMark, AudioReviewProgress, AudioReviewSession, AudioReviewSummary, StartAudioReviewRequest};
+
+/// 单词间隔中检查复习是否已停止的频率
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 开始音频复习：返回本次朗读的错词，并在后台依次朗读
+///
+/// 每个单词朗读前发出 `audio-review-progress`，之后等待 `gap_seconds` 让学生口头或在纸上拼写；
+/// 全部朗读完或被停止后发出 `finished` 为 true 的 `audio-review-progress`。
+#[tauri::command]
+pub fn start_audio_review(
+    app: AppHandle,
+    request: StartAudioReviewRequest,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<AudioReviewSession, String> {
+    let (session, rate) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let session = db.start_audio_review(&request).map_err(|e| e.to_string())?;
+        (session, accommodated_rate(&db, Some(&request.user_name), request.rate)?)
+    };
+    let review = session.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = play_review(&app, &review, rate) {
+            log::warn!("Audio review {} stopped: {}", review.id, e);
+        }
+        let total = review.items.len() as i32;
+        let progress = AudioReviewProgress { session_id: review.id, index: total, total, finished: true };
+        if let Err(e) = app.emit("audio-review-progress", progress) {
+            log::warn!("Failed to emit audio-review-progress event: {}", e);
+        }
+    });
+    Ok(session)
+}
+
+/// 依次朗读错词并留出拼写时间，复习被停止或结束时提前返回
+fn play_review(app: &AppHandle, session: &AudioReviewSession, rate: Option<i32>) -> Result<(), String> {
+    let total = session.items.len() as i32;
+    let gap = Duration::from_secs(session.gap_seconds.max(0) as u64);
+    for item in &session.items {
+        if !is_active(app, session.id)? {
+            return Ok(());
+        }
+        let progress = AudioReviewProgress { session_id: session.id, index: item.index, total, finished: false };
+        app.emit("audio-review-progress", progress).map_err(|e| e.to_string())?;
+        let language = tts_language(item.language.as_deref()).or_else(|_| tts_language(None))?;
+        speak_and_wait(app, &item.content, rate, language)?;
+
+        let until = Instant::now() + gap;
+        while Instant::now() < until {
+            if !is_active(app, session.id)? {
+                return Ok(());
+            }
+            std::thread::sleep(STOP_POLL_INTERVAL);
+        }
+    }
+    Ok(())
+}
+
+fn is_active(app: &AppHandle, session_id: i64) -> Result<bool, String> {
+    let db = app.state::<Mutex<DatabaseManager>>();
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.is_audio_review_active(session_id).map_err(|e| e.to_string())
+}
+
+/// 停止朗读（之后仍可提交自评），返回是否停止了正在进行的复习
+#[tauri::command]
+pub fn stop_audio_review(app: AppHandle, session_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let stopped = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.stop_audio_review(session_id).map_err(|e| e.to_string())?
+    };
+    if stopped {
+        stop_speaking(app)?;
+    }
+    Ok(stopped)
+}
+
+/// 结束音频复习：提交学生对每个单词的自评，记入练习历史（`audio_review` 模式）
+#[tauri::command]
+pub fn finish_audio_review(
+    session_id: i64,
+    marks: Vec<AudioReviewMark>,
+    duration_seconds: i32,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<AudioReviewSummary, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.finish_audio_review(session_id, &marks, duration_seconds).map_err(|e| e.to_string())
+}
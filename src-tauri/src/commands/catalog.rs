@@ -0,0 +1,289 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, PackImportResult};
+
+/// 下载目录和单元包的超时时间（秒）
+const CATALOG_FETCH_TIMEOUT_SECS: u64 = 60;
+/// 单元包的大小上限
+const MAX_PACK_BYTES: usize = 100 * 1024 * 1024;
+
+/// 内容目录设置：目录地址和信任的发布者
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogSettings {
+    pub catalog_url: String,
+    #[serde(default)]
+    pub trusted_publishers: Vec<TrustedPublisher>,
+}
+
+/// 信任的发布者，只安装由这些公钥签名的单元包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedPublisher {
+    pub name: String,
+    pub public_key: String,     // Ed25519 公钥（base64）
+}
+
+/// 远程内容目录（JSON 索引）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentCatalog {
+    pub name: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    pub packs: Vec<CatalogPack>,
+}
+
+/// 目录中可下载的单元包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogPack {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_pack_kind")]
+    pub kind: String,                   // "articles" | "questions"
+    #[serde(default)]
+    pub grade_level: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    pub url: String,                    // 可以是相对目录地址的路径
+    pub sha256: String,
+    #[serde(default)]
+    pub publisher: Option<String>,
+    #[serde(default)]
+    pub signature: Option<String>,      // 发布者对包文件的 Ed25519 签名（base64）
+    #[serde(default)]
+    pub trusted: bool,                  // 本机计算：由信任的发布者签名，可以安装
+}
+
+fn default_pack_kind() -> String {
+    "articles".to_string()
+}
+
+/// 保存内容目录设置
+#[tauri::command]
+pub fn save_catalog_settings(
+    app: AppHandle,
+    settings: CatalogSettings,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let url = settings.catalog_url.trim();
+    if !url.is_empty() && !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err("目录地址必须以 http:// 或 https:// 开头".to_string());
+    }
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "catalog_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 加载内容目录设置
+#[tauri::command]
+pub fn load_catalog_settings(app: AppHandle) -> Result<CatalogSettings, String> {
+    read_catalog_settings(&app)
+}
+
+/// 读取内容目录设置，不存在时返回空设置
+fn read_catalog_settings(app: &AppHandle) -> Result<CatalogSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "catalog_settings.json")?;
+    if !config_path.exists() {
+        return Ok(CatalogSettings::default());
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+/// 获取远程内容目录，`url` 为空时使用设置中的目录地址
+#[tauri::command]
+pub async fn browse_content_catalog(app: AppHandle, url: Option<String>) -> Result<ContentCatalog, String> {
+    let settings = read_catalog_settings(&app)?;
+    fetch_catalog(&settings, url).await
+}
+
+/// 下载目录中的单元包，校验签名和校验和后导入
+#[tauri::command]
+pub async fn install_catalog_pack(
+    app: AppHandle,
+    pack_id: String,
+    url: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<PackImportResult, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let settings = read_catalog_settings(&app)?;
+    let catalog = fetch_catalog(&settings, url).await?;
+    let pack = catalog.packs.into_iter().find(|p| p.id == pack_id).ok_or_else(|| format!("目录中没有单元包 {}", pack_id))?;
+    let public_key = trusted_key(&settings, &pack).ok_or("单元包没有签名，或发布者不在信任列表中")?;
+
+    let bytes = download_limited(&pack.url, MAX_PACK_BYTES).await?;
+    verify_download(&pack, &bytes, public_key)?;
+    crate::commands::article::import_pack_bytes(&app, &db, &bytes)
+}
+
+/// 下载文件，声明的长度或已收到的数据超过 `limit` 字节时立即停止
+async fn download_limited(url: &str, limit: usize) -> Result<Vec<u8>, String> {
+    let mut response = http_client()?
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?;
+    if response.content_length().is_some_and(|length| length > limit as u64) {
+        return Err("单元包过大".to_string());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Network error: {}", e))? {
+        if bytes.len() + chunk.len() > limit {
+            return Err("单元包过大".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(CATALOG_FETCH_TIMEOUT_SECS))
+        .user_agent(concat!("SpellingGame/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+async fn fetch_catalog(settings: &CatalogSettings, url: Option<String>) -> Result<ContentCatalog, String> {
+    let url = url.filter(|u| !u.trim().is_empty()).unwrap_or_else(|| settings.catalog_url.clone());
+    let base = reqwest::Url::parse(url.trim()).map_err(|_| "请先配置内容目录地址".to_string())?;
+    if !matches!(base.scheme(), "http" | "https") {
+        return Err("目录地址必须以 http:// 或 https:// 开头".to_string());
+    }
+    let catalog: ContentCatalog = http_client()?
+        .get(base.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("目录格式无效: {}", e))?;
+    resolve_catalog(settings, &base, catalog)
+}
+
+/// 把单元包地址解析为绝对地址，并标记是否由信任的发布者签名
+pub fn resolve_catalog(settings: &CatalogSettings, base: &reqwest::Url, mut catalog: ContentCatalog) -> Result<ContentCatalog, String> {
+    for pack in &mut catalog.packs {
+        pack.url = base.join(&pack.url).map_err(|e| format!("{}: 地址无效: {}", pack.id, e))?.to_string();
+        pack.trusted = trusted_key(settings, pack).is_some();
+    }
+    Ok(catalog)
+}
+
+/// 签名单元包的发布者在信任列表中时，返回其公钥
+fn trusted_key<'a>(settings: &'a CatalogSettings, pack: &CatalogPack) -> Option<&'a str> {
+    pack.signature.as_ref()?;
+    let publisher = pack.publisher.as_deref()?;
+    settings
+        .trusted_publishers
+        .iter()
+        .find(|p| p.name == publisher)
+        .map(|p| p.public_key.as_str())
+}
+
+/// 校验下载内容的 SHA-256 和发布者签名
+pub fn verify_download(pack: &CatalogPack, bytes: &[u8], public_key: &str) -> Result<(), String> {
+    if !crate::packs::sha256_hex(bytes).eq_ignore_ascii_case(pack.sha256.trim()) {
+        return Err("下载的单元包校验和不符".to_string());
+    }
+    let signature = pack.signature.as_deref().ok_or("单元包没有签名")?;
+    crate::packs::verify_signature(bytes, signature, public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 本地返回固定响应的 HTTP 服务，返回地址
+    async fn serve_once(response: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream.write_all(response).await;
+            }
+        });
+        format!("http://{}/pack", address)
+    }
+
+    /// 下载按声明长度和实际收到的数据限制大小
+    #[tokio::test]
+    async fn test_download_limited() {
+        let url = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello").await;
+        assert_eq!(download_limited(&url, 5).await.unwrap(), b"hello");
+        assert_eq!(download_limited(&url, 4).await.unwrap_err(), "单元包过大");
+
+        // 分块传输没有声明长度，边下载边检查
+        let url = serve_once(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n3\r\nhel\r\n3\r\nlo!\r\n0\r\n\r\n").await;
+        assert_eq!(download_limited(&url, 6).await.unwrap(), b"hello!");
+        assert_eq!(download_limited(&url, 5).await.unwrap_err(), "单元包过大");
+    }
+
+    /// 地址解析、信任的发布者和签名校验
+    #[test]
+    fn test_content_catalog_signature() {
+        use base64::Engine;
+        use ring::signature::KeyPair;
+        let encode = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+
+        let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let key = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let pack_bytes = b"pack contents".to_vec();
+        let settings = CatalogSettings {
+            catalog_url: String::new(),
+            trusted_publishers: vec![TrustedPublisher { name: "District".to_string(), public_key: encode(key.public_key().as_ref()) }],
+        };
+
+        let catalog: ContentCatalog = serde_json::from_value(serde_json::json!({
+            "name": "Shared units",
+            "packs": [
+                {
+                    "id": "unit-1", "title": "Unit 1", "url": "packs/unit-1.spelling-pack",
+                    "sha256": crate::packs::sha256_hex(&pack_bytes),
+                    "publisher": "District", "signature": encode(key.sign(&pack_bytes).as_ref())
+                },
+                { "id": "unit-2", "title": "Unit 2", "url": "https://other.example/unit-2.spelling-pack", "sha256": "00" }
+            ]
+        }))
+        .unwrap();
+        let base = reqwest::Url::parse("https://example.org/catalog/index.json").unwrap();
+        let catalog = resolve_catalog(&settings, &base, catalog).unwrap();
+        assert_eq!(catalog.packs[0].url, "https://example.org/catalog/packs/unit-1.spelling-pack");
+        assert_eq!(catalog.packs[0].kind, "articles");
+        assert!(catalog.packs[0].trusted);
+        assert!(!catalog.packs[1].trusted);
+
+        let public_key = &settings.trusted_publishers[0].public_key;
+        verify_download(&catalog.packs[0], &pack_bytes, public_key).unwrap();
+        assert!(verify_download(&catalog.packs[0], b"pack contents!", public_key).unwrap_err().contains("校验和"));
+
+        // 校验和相符但签名不是该发布者的
+        let mut forged = catalog.packs[0].clone();
+        let other = ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let other = ring::signature::Ed25519KeyPair::from_pkcs8(other.as_ref()).unwrap();
+        forged.signature = Some(encode(other.sign(&pack_bytes).as_ref()));
+        assert!(verify_download(&forged, &pack_bytes, public_key).unwrap_err().contains("签名校验失败"));
+    }
+}
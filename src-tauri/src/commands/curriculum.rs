@@ -0,0 +1,58 @@
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, CreateCurriculumUnitRequest, CurriculumProgress, CurriculumUnit};
+
+/// 在课程计划末尾添加单元
+#[tauri::command]
+pub fn create_curriculum_unit(
+    request: CreateCurriculumUnitRequest,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i64, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.create_curriculum_unit(&request).map_err(|e| e.to_string())
+}
+
+/// 获取用户的课程计划
+#[tauri::command]
+pub fn get_curriculum(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<CurriculumUnit>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_curriculum(&user_name).map_err(|e| e.to_string())
+}
+
+/// 调整单元顺序
+#[tauri::command]
+pub fn reorder_curriculum_units(
+    user_name: String,
+    unit_ids: Vec<i64>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.reorder_curriculum_units(&user_name, &unit_ids).map_err(|e| e.to_string())
+}
+
+/// 删除单元（单元中的文章保留）
+#[tauri::command]
+pub fn delete_curriculum_unit(id: i64, actor: Option<Actor>, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let deleted = db.delete_curriculum_unit(id).map_err(|e| e.to_string())?;
+    if deleted {
+        let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+        db.record_audit(user_name, "delete", "curriculum_unit", id, None)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(deleted)
+}
+
+/// 课程进度：各单元完成情况和与计划相比的快慢
+#[tauri::command]
+pub fn get_curriculum_progress(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<CurriculumProgress, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_curriculum_progress(&user_name).map_err(|e| e.to_string())
+}
@@ -0,0 +1,181 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::database::DatabaseManager;
+use crate::models::{
+    Actor, Assignment, AuditLogEntry, CreateAssignmentRequest, DashboardOverview, GradebookEntry, PracticeRollup, RetentionReport,
+};
+
+/// 教师面板窗口标签
+pub const DASHBOARD_WINDOW_LABEL: &str = "dashboard";
+
+/// 打开教师面板窗口（已打开则聚焦）
+///
+/// 两个窗口共用同一个 `Mutex<DatabaseManager>` 状态，所有读写都经过同一把锁。
+#[tauri::command]
+pub async fn open_dashboard_window(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(DASHBOARD_WINDOW_LABEL) {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(&app, DASHBOARD_WINDOW_LABEL, WebviewUrl::App("index.html#/dashboard".into()))
+        .title("教师面板")
+        .inner_size(1100.0, 760.0)
+        .min_inner_size(800.0, 600.0)
+        .resizable(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 获取教师面板总览（只读）
+#[tauri::command]
+pub fn get_dashboard_overview(db: State<'_, Mutex<DatabaseManager>>) -> Result<DashboardOverview, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_dashboard_overview().map_err(|e| e.to_string())
+}
+
+/// 布置作业
+#[tauri::command]
+pub fn create_assignment(
+    request: CreateAssignmentRequest,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i64, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.create_assignment(&request).map_err(|e| e.to_string())
+}
+
+/// 获取作业列表
+#[tauri::command]
+pub fn get_assignments(
+    user_name: Option<String>,
+    status: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<Assignment>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_assignments(user_name.as_deref(), status.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 删除作业
+#[tauri::command]
+pub fn delete_assignment(
+    id: i64,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let deleted = db.delete_assignment(id).map_err(|e| e.to_string())?;
+    if deleted {
+        let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+        db.record_audit(user_name, "delete", "assignment", id, None)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(deleted)
+}
+
+/// 导出成绩册 CSV（学生、作业、成绩、日期），可直接上传到 Google Classroom 等 LMS，返回临时文件路径
+///
+/// `since` 为 `YYYY-MM-DD`，只导出该日期之后完成的作业和测试。
+#[tauri::command]
+pub fn export_gradebook_csv(
+    user_name: Option<String>,
+    since: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let entries = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+        db.get_gradebook(user_name.as_deref(), since.as_deref()).map_err(|e| e.to_string())?
+    };
+    let dir = std::env::temp_dir().join("spelling-game-exports");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("gradebook-{}.csv", chrono::Local::now().format("%Y%m%d%H%M%S%3f")));
+    std::fs::write(&path, gradebook_csv(&entries)).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 成绩册 CSV 内容：首行为表头，成绩取整，日期只保留年月日
+pub fn gradebook_csv(entries: &[GradebookEntry]) -> String {
+    let mut csv = String::from("Student,Assignment,Score,Date\r\n");
+    for entry in entries {
+        let score = entry.score.map(|s| format!("{:.0}", s)).unwrap_or_default();
+        let date = entry.date.get(..10).unwrap_or(&entry.date);
+        let fields = [csv_field(&entry.student), csv_field(&entry.assignment), score, date.to_string()];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// 按 RFC 4180 转义；以 `= + - @` 开头的文字前加 `'`，避免表格软件当作公式执行
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) { format!("'{}", value) } else { value.to_string() };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// 获取审计日志（删除记录），需要老师或管理员身份
+#[tauri::command]
+pub fn get_audit_log(
+    target_type: Option<String>,
+    limit: Option<i32>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<AuditLogEntry>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.get_audit_log(target_type.as_deref(), limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// 执行只读 SQL 查询（仅 SELECT），结果以 JSON 行返回，需要老师或管理员身份
+#[tauri::command]
+pub fn run_readonly_query(
+    sql: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.run_readonly_query(&sql).map_err(|e| e.to_string())
+}
+
+/// 按保留期限（月）清理旧的练习记录和测试会话，`dry_run` 时只预览将删除的行数
+#[tauri::command]
+pub fn apply_retention_policy(
+    months: u32,
+    dry_run: bool,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<RetentionReport, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let report = db.apply_retention_policy(months, dry_run).map_err(|e| e.to_string())?;
+    if !dry_run {
+        let details = format!(
+            "before {}: {} practice records, {} practice sessions, {} WIDA sessions",
+            report.cutoff, report.practice_history, report.practice_sessions, report.wida_test_sessions
+        );
+        let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+        db.record_audit(user_name, "prune", "retention", months as i64, Some(&details))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
+
+/// 获取按月汇总的练习记录（清理后仍保留）
+#[tauri::command]
+pub fn get_practice_rollups(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<PracticeRollup>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_practice_rollups(&user_name).map_err(|e| e.to_string())
+}
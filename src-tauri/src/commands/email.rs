@@ -0,0 +1,261 @@
+use std::sync::Mutex;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, WeeklySummary};
+
+/// 报告周期及其天数
+pub const REPORT_PERIODS: [(&str, i64); 2] = [("week", 7), ("month", 30)];
+/// 连接和发送的超时时间（秒）
+const SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// SMTP 设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default = "default_smtp_security")]
+    pub security: String,               // "starttls" | "tls" | "none"
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    pub from: String,                   // 发件人，例如 "Spelling Game <noreply@example.com>"
+    #[serde(default)]
+    pub default_recipient: Option<String>,  // 家长或老师的邮箱
+}
+
+impl SmtpSettings {
+    /// 返回给前端的设置不含密码
+    fn redacted(mut self) -> Self {
+        self.password.clear();
+        self
+    }
+
+    /// 前端保存时密码留空表示不修改，沿用已保存的密码
+    fn keep_password(mut self, saved: Option<&SmtpSettings>) -> Self {
+        if self.password.is_empty() {
+            if let Some(saved) = saved {
+                self.password = saved.password.clone();
+            }
+        }
+        self
+    }
+
+    /// 是否为设置中的默认收件人
+    fn is_default_recipient(&self, recipient: &str) -> bool {
+        self.default_recipient.as_deref().is_some_and(|r| r.trim().eq_ignore_ascii_case(recipient.trim()))
+    }
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_smtp_security() -> String {
+    "starttls".to_string()
+}
+
+/// 渲染好的报告邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// 保存 SMTP 设置，`password` 留空时沿用已保存的密码
+#[tauri::command]
+pub fn save_smtp_settings(
+    app: AppHandle,
+    settings: SmtpSettings,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if settings.host.trim().is_empty() {
+        return Err("请填写 SMTP 服务器地址".to_string());
+    }
+    if !["starttls", "tls", "none"].contains(&settings.security.as_str()) {
+        return Err(format!("未知的加密方式: {}，可选 starttls / tls / none", settings.security));
+    }
+    parse_mailbox(&settings.from)?;
+    if let Some(recipient) = settings.default_recipient.as_deref().filter(|r| !r.trim().is_empty()) {
+        parse_mailbox(recipient)?;
+    }
+    let settings = settings.keep_password(read_smtp_settings(&app)?.as_ref());
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "smtp_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 加载 SMTP 设置（不含密码），未配置时返回 None
+#[tauri::command]
+pub fn load_smtp_settings(app: AppHandle) -> Result<Option<SmtpSettings>, String> {
+    Ok(read_smtp_settings(&app)?.map(SmtpSettings::redacted))
+}
+
+/// 生成学习报告并发送到邮箱，`to` 为空时发给设置中的默认收件人，返回收件人
+///
+/// `period`：`week`（最近 7 天）或 `month`（最近 30 天）。发给默认收件人以外的地址需要老师或管理员身份，
+/// 设置了 PIN 的用户需要提供 `pin`。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_report_email(
+    app: AppHandle,
+    user_name: String,
+    period: String,
+    to: Option<String>,
+    locale: Option<String>,
+    pin: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let settings = read_smtp_settings(&app)?.ok_or("请先配置 SMTP 发件设置")?;
+    let recipient = to
+        .or_else(|| settings.default_recipient.clone())
+        .filter(|r| !r.trim().is_empty())
+        .ok_or("请填写收件人邮箱")?;
+    let days = period_days(&period)?;
+    let summary = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        if !settings.is_default_recipient(&recipient) {
+            db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+        }
+        db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+        db.get_practice_summary(&user_name, days).map_err(|e| e.to_string())?
+    };
+    let report = render_report_email(&summary, &period, locale.as_deref());
+
+    let message = Message::builder()
+        .from(parse_mailbox(&settings.from)?)
+        .to(parse_mailbox(&recipient)?)
+        .subject(&report.subject)
+        .multipart(MultiPart::alternative_plain_html(report.text, report.html))
+        .map_err(|e| e.to_string())?;
+    smtp_transport(&settings)?
+        .send(message)
+        .await
+        .map_err(|e| format!("邮件发送失败: {}", e))?;
+    log::info!("Sent {} report for {} to {}", period, user_name, recipient);
+    Ok(recipient)
+}
+
+fn read_smtp_settings(app: &AppHandle) -> Result<Option<SmtpSettings>, String> {
+    let config_path = crate::paths::app_data_file(app, "smtp_settings.json")?;
+    if !config_path.exists() {
+        return Ok(None);
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map(Some).map_err(|e| e.to_string())
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, String> {
+    address.trim().parse().map_err(|e| format!("无效的邮箱地址 {}: {}", address, e))
+}
+
+fn period_days(period: &str) -> Result<i64, String> {
+    REPORT_PERIODS
+        .iter()
+        .find(|(name, _)| *name == period)
+        .map(|(_, days)| *days)
+        .ok_or_else(|| format!("未知的报告周期: {}，可选 week / month", period))
+}
+
+fn smtp_transport(settings: &SmtpSettings) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let host = settings.host.trim();
+    let builder = match settings.security.as_str() {
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+        _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+    }
+    .map_err(|e| e.to_string())?;
+    let mut builder = builder.port(settings.port).timeout(Some(std::time::Duration::from_secs(SMTP_TIMEOUT_SECS)));
+    if !settings.username.is_empty() {
+        builder = builder.credentials(Credentials::new(settings.username.clone(), settings.password.clone()));
+    }
+    Ok(builder.build())
+}
+
+/// 把练习小结渲染成纯文本和 HTML 两种正文
+pub fn render_report_email(summary: &WeeklySummary, period: &str, locale: Option<&str>) -> ReportEmail {
+    let t = |key: &str, vars: &[(&str, String)]| crate::i18n::text(locale, key, vars);
+    let subject = t(&format!("email.subject.{}", period), &[("user", summary.user_name.clone())]);
+    let mut rows = vec![
+        (t("email.practices", &[]), summary.practices.to_string()),
+        (t("email.words_practiced", &[]), summary.words_practiced.to_string()),
+        (t("email.words_mastered", &[]), summary.words_mastered.to_string()),
+        (t("report.accuracy", &[]), format!("{:.0}%", summary.avg_accuracy)),
+        (t("email.time_practiced", &[]), t("email.minutes", &[("count", (summary.duration_seconds / 60).to_string())])),
+        (t("email.streak", &[]), t("email.days", &[("count", summary.streak_days.to_string())])),
+    ];
+    // 放慢速度能明显提高正确率时提醒
+    if let Some(profile) = summary.speed_accuracy.as_ref().filter(|p| p.recommendation == "slow_down") {
+        let percent = |label: &str| {
+            let bucket = profile.buckets.iter().find(|b| b.label == label);
+            format!("{:.0}", bucket.map_or(0.0, |b| b.accuracy * 100.0))
+        };
+        rows.push((t("email.pace", &[]), t("email.slow_down", &[("fast", percent("fast")), ("slow", percent("slow"))])));
+    }
+    let footer = t("email.footer", &[]);
+
+    let mut text = format!("{}\n\n", subject);
+    for (label, value) in &rows {
+        text.push_str(&format!("{}: {}\n", label, value));
+    }
+    text.push_str(&format!("\n{}\n", footer));
+
+    let mut html = format!("<h2>{}</h2>\n<table>\n", escape_html(&subject));
+    for (label, value) in &rows {
+        html.push_str(&format!("<tr><td>{}</td><td><b>{}</b></td></tr>\n", escape_html(label), escape_html(value)));
+    }
+    html.push_str(&format!("</table>\n<p style=\"color:#888\">{}</p>\n", escape_html(&footer)));
+    ReportEmail { subject, text, html }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(password: &str, default_recipient: Option<&str>) -> SmtpSettings {
+        SmtpSettings {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            security: "starttls".to_string(),
+            username: "teacher".to_string(),
+            password: password.to_string(),
+            from: "noreply@example.com".to_string(),
+            default_recipient: default_recipient.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_smtp_password_is_not_returned_or_lost() {
+        let saved = settings("secret", None);
+        assert_eq!(saved.clone().redacted().password, "");
+        assert_eq!(settings("", None).keep_password(Some(&saved)).password, "secret");
+        assert_eq!(settings("new", None).keep_password(Some(&saved)).password, "new");
+        assert_eq!(settings("", None).keep_password(None).password, "");
+    }
+
+    #[test]
+    fn test_is_default_recipient() {
+        let with_default = settings("", Some(" Parent@Example.com "));
+        assert!(with_default.is_default_recipient("parent@example.com"));
+        assert!(!with_default.is_default_recipient("stranger@example.com"));
+        assert!(!settings("", None).is_default_recipient("parent@example.com"));
+    }
+}
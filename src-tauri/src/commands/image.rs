@@ -0,0 +1,226 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, WordImage};
+
+/// 配图文件保存的子目录
+const IMAGE_DIR: &str = "word_images";
+/// 支持的图片格式
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "svg"];
+/// 下载图片的超时时间（秒）
+const IMAGE_FETCH_TIMEOUT_SECS: u64 = 20;
+/// 下载图片的大小上限
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 图片 API 设置
+///
+/// `api_url` 中的 `{word}` 和 `{key}` 会被替换；`image_field` 是响应 JSON 中图片地址的
+/// JSON Pointer（如 Pixabay 的 `/hits/0/webformatURL`、Unsplash 的 `/results/0/urls/small`）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageApiSettings {
+    pub api_url: String,
+    pub api_key: String,
+    pub image_field: String,
+}
+
+/// 为单词附加本地图片（复制到应用数据目录）
+#[tauri::command]
+pub fn attach_word_image(
+    app: AppHandle,
+    word: String,
+    file_path: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<WordImage, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let extension = image_extension(Path::new(&file_path).extension().and_then(|e| e.to_str()))?;
+    let target = image_path(&app, &word, extension)?;
+    std::fs::copy(&file_path, &target).map_err(|e| format!("复制图片失败: {}", e))?;
+    save_image(&db, &word, &target, "local", None)
+}
+
+/// 从配置的图片 API 下载单词配图
+#[tauri::command]
+pub async fn fetch_word_image(
+    app: AppHandle,
+    word: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<WordImage, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let settings = read_image_api_settings(&app)?;
+    if settings.api_key.trim().is_empty() {
+        return Err("请先配置图片 API Key".to_string());
+    }
+    let word_query = url_encode(word.trim());
+    let search_url = settings.api_url.replace("{word}", &word_query).replace("{key}", &settings.api_key);
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(IMAGE_FETCH_TIMEOUT_SECS))
+        .user_agent(concat!("SpellingGame/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response: serde_json::Value = client
+        .get(&search_url)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Parse error: {}", e))?;
+    let image_url = response
+        .pointer(&settings.image_field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("没有找到“{}”的图片", word.trim()))?
+        .to_string();
+
+    let image = client
+        .get(&image_url)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?;
+    let content_type = image
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let bytes = image.bytes().await.map_err(|e| format!("Parse error: {}", e))?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err("图片过大".to_string());
+    }
+    // 优先按 Content-Type 判断格式，其次看地址后缀
+    let extension = content_type
+        .strip_prefix("image/")
+        .map(|subtype| subtype.trim_end_matches("+xml"))
+        .or_else(|| image_url.split(['?', '#']).next().and_then(|path| Path::new(path).extension()?.to_str()));
+    let extension = image_extension(extension)?;
+
+    let target = image_path(&app, &word, extension)?;
+    std::fs::write(&target, &bytes).map_err(|e| e.to_string())?;
+    let db = db.lock().map_err(|e| e.to_string())?;
+    save_image(&db, &word, &target, "api", Some(&image_url))
+}
+
+/// 获取单词配图
+#[tauri::command]
+pub fn get_word_image(word: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Option<WordImage>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_word_image(&word).map_err(|e| e.to_string())
+}
+
+/// 批量获取单词配图（只返回有图的单词）
+#[tauri::command]
+pub fn get_word_images(words: Vec<String>, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<WordImage>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_word_images(&words).map_err(|e| e.to_string())
+}
+
+/// 删除单词配图及其文件
+#[tauri::command]
+pub fn remove_word_image(
+    word: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let path = db.delete_word_image(&word).map_err(|e| e.to_string())?;
+    if let Some(path) = &path {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(path.is_some())
+}
+
+/// 保存图片 API 设置
+#[tauri::command]
+pub fn save_image_api_settings(
+    app: AppHandle,
+    settings: ImageApiSettings,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "image_api_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 加载图片 API 设置
+#[tauri::command]
+pub fn load_image_api_settings(app: AppHandle) -> Result<ImageApiSettings, String> {
+    read_image_api_settings(&app)
+}
+
+/// 读取图片 API 设置，不存在时返回 Pixabay 的默认设置
+fn read_image_api_settings(app: &AppHandle) -> Result<ImageApiSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "image_api_settings.json")?;
+    if !config_path.exists() {
+        return Ok(ImageApiSettings {
+            api_url: "https://pixabay.com/api/?key={key}&q={word}&image_type=illustration&safesearch=true".to_string(),
+            api_key: String::new(),
+            image_field: "/hits/0/webformatURL".to_string(),
+        });
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+/// 校验图片格式，返回小写扩展名
+fn image_extension(extension: Option<&str>) -> Result<&'static str, String> {
+    let extension = extension.unwrap_or_default().to_lowercase();
+    IMAGE_EXTENSIONS
+        .iter()
+        .copied()
+        .find(|e| *e == extension)
+        .ok_or_else(|| format!("不支持的图片格式: {}", extension))
+}
+
+/// 新配图文件的保存路径（每次用新文件名，避免 WebView 缓存旧图）
+fn image_path(app: &AppHandle, word: &str, extension: &str) -> Result<std::path::PathBuf, String> {
+    let stem: String = word.trim().to_lowercase().chars().filter(|c| c.is_alphanumeric() || *c == '-').collect();
+    let file_name = format!("{}-{}.{}", stem, uuid::Uuid::new_v4().simple(), extension);
+    Ok(crate::paths::app_data_subdir(app, IMAGE_DIR)?.join(file_name))
+}
+
+/// 登记配图并删除被替换的旧文件
+fn save_image(
+    db: &DatabaseManager,
+    word: &str,
+    path: &Path,
+    source: &str,
+    source_url: Option<&str>,
+) -> Result<WordImage, String> {
+    let path = path.to_string_lossy();
+    let old = db.set_word_image(word, &path, source, source_url).map_err(|e| e.to_string())?;
+    if let Some(old) = old {
+        let _ = std::fs::remove_file(old);
+    }
+    db.get_word_image(word).map_err(|e| e.to_string())?.ok_or_else(|| "保存配图失败".to_string())
+}
+
+/// 查询参数的百分号编码
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
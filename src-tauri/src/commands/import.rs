@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::commands::segment::request_segments;
+use crate::database::DatabaseManager;
+use crate::importers::{self, ParsedDocument};
+use crate::text::language;
+use crate::models::{Actor, EpubChapter, EpubToc, FilesImportedEvent, ImportedArticle, PageRange};
+
+/// 自动生成标题的最大字符数
+const MAX_TITLE_CHARS: usize = 60;
+
+/// 下载网页的超时时间（秒）
+const URL_FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// 从剪贴板文本创建文章（"粘贴即练习"）
+///
+/// 标题取自第一行非空文本；`segment_modes` 非空时创建后立即调用分词服务。
+#[tauri::command]
+pub async fn create_article_from_clipboard(
+    app: AppHandle,
+    segment_modes: Option<Vec<String>>,
+    server_url: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ImportedArticle, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let text = app.clipboard().read_text().map_err(|e| format!("读取剪贴板失败: {}", e))?;
+    let content = text.trim();
+    if content.is_empty() {
+        return Err("剪贴板中没有文本".to_string());
+    }
+
+    let title = derive_title(content);
+    import_text(&db, &title, content, segment_modes.unwrap_or_default(), server_url).await
+}
+
+/// 从网页地址导入文章：下载页面、提取正文后创建文章
+#[tauri::command]
+pub async fn import_article_from_url(
+    url: String,
+    segment_modes: Option<Vec<String>>,
+    server_url: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ImportedArticle, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("网址必须以 http:// 或 https:// 开头".to_string());
+    }
+
+    let html = fetch_page(url.to_string()).await?;
+    let doc = importers::html::extract_article(&html)?;
+    import_text(&db, &doc.title, &doc.content, segment_modes.unwrap_or_default(), server_url).await
+}
+
+/// 下载网页 HTML
+async fn fetch_page(url: String) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(URL_FETCH_TIMEOUT_SECS))
+        .user_agent(concat!("SpellingGame/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn(async move {
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Server error: {}", response.status()));
+        }
+
+        response.text().await.map_err(|e| format!("Parse error: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 创建文章并按需分词（各导入方式共用）
+///
+/// 不支持的语言直接拒绝；中文文章不按单词和短语分词。
+pub async fn import_text(
+    db: &Mutex<DatabaseManager>,
+    title: &str,
+    content: &str,
+    segment_modes: Vec<String>,
+    server_url: Option<String>,
+) -> Result<ImportedArticle, String> {
+    let check = language::check_article_language(content);
+    if !check.supported {
+        return Err(check.warning.unwrap_or_default());
+    }
+    let article_id = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.create_article(title, content).map_err(|e| e.to_string())?
+    };
+
+    let mut segmented_modes = Vec::new();
+    let mut segment_errors = Vec::new();
+    for mode in segment_modes {
+        if mode != "sentence" && !language::segments_words(check.language.as_deref()) {
+            continue;
+        }
+        // 分词失败不影响文章创建，用户可稍后在编辑页重新分词
+        match request_segments(content.to_string(), mode.clone(), server_url.clone(), None, None).await {
+            Ok(segments) => {
+                let mut db = db.lock().map_err(|e| e.to_string())?;
+                db.save_segments(article_id, &mode, &segments).map_err(|e| e.to_string())?;
+                segmented_modes.push(mode);
+            }
+            Err(e) => segment_errors.push(format!("{}: {}", mode, e)),
+        }
+    }
+
+    Ok(ImportedArticle {
+        article_id,
+        title: title.to_string(),
+        kind: "article".to_string(),
+        segmented_modes,
+        segment_errors,
+        language: check.language,
+        warning: check.warning,
+    })
+}
+
+/// 导入本地文件（txt / md / csv / srt / vtt / pdf）
+#[tauri::command]
+pub fn import_files(
+    paths: Vec<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<FilesImportedEvent, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    Ok(import_paths(&db, &paths))
+}
+
+/// 处理拖放到窗口上的文件，导入完成后发出 `files-imported` 事件
+///
+/// 拖放没有调用者身份，已设置老师或管理员时拒绝导入，需通过导入命令提供身份。
+/// 解析文件（尤其是 PDF）可能较慢，在后台线程中进行，不阻塞窗口事件循环。
+pub fn handle_dropped_files(window: &Window, paths: &[PathBuf]) {
+    let window = window.clone();
+    let paths = paths.to_vec();
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = window.state::<Mutex<DatabaseManager>>();
+        let authorized = db.lock().map_err(|e| e.to_string()).and_then(|db| db.authorize(None).map_err(|e| e.to_string()));
+        let result = match authorized {
+            Ok(()) => import_paths(&db, &paths),
+            Err(e) => FilesImportedEvent { imported: Vec::new(), errors: vec![e] },
+        };
+        if let Err(e) = window.emit("files-imported", result) {
+            log::warn!("Failed to emit files-imported event: {}", e);
+        }
+    });
+}
+
+/// 逐个导入文件，单个文件失败不影响其他文件
+fn import_paths(db: &Mutex<DatabaseManager>, paths: &[PathBuf]) -> FilesImportedEvent {
+    let mut imported = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match importers::parse_file(path).and_then(|doc| import_document(db, doc)) {
+            Ok(article) => imported.push(article),
+            Err(e) => errors.push(e),
+        }
+    }
+    FilesImportedEvent { imported, errors }
+}
+
+/// 从 PDF 导入文章，可只导入部分页面
+#[tauri::command]
+pub fn import_article_from_pdf(
+    path: String,
+    page_range: Option<PageRange>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ImportedArticle, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let range = page_range.map(|r| (r.start, r.end));
+    let doc = importers::pdf::read_pdf(Path::new(&path), range)?;
+    import_document(&db, doc)
+}
+
+/// 列出 EPUB 的章节，供用户勾选要导入的章节
+#[tauri::command]
+pub fn list_epub_chapters(path: String) -> Result<EpubToc, String> {
+    let book = importers::epub::read_epub(Path::new(&path))?;
+    let chapters = book
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| EpubChapter {
+            index,
+            title: chapter.title.clone(),
+            char_count: chapter.content.chars().count(),
+        })
+        .collect();
+    Ok(EpubToc { title: book.title, chapters })
+}
+
+/// 导入选中的 EPUB 章节，每章创建一篇文章并保留章节标题
+#[tauri::command]
+pub fn import_epub_chapters(
+    path: String,
+    chapter_indices: Vec<usize>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<ImportedArticle>, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if chapter_indices.is_empty() {
+        return Err("请至少选择一个章节".to_string());
+    }
+
+    let book = importers::epub::read_epub(Path::new(&path))?;
+    // 先检查所有序号，无效时不导入任何章节
+    let mut chapters: Vec<Option<ParsedDocument>> = book.chapters.into_iter().map(Some).collect();
+    let selected = chapter_indices
+        .iter()
+        .map(|&index| chapters.get_mut(index).and_then(Option::take).ok_or_else(|| format!("章节不存在: {}", index)))
+        .collect::<Result<Vec<_>, _>>()?;
+    selected.into_iter().map(|chapter| import_document(&db, chapter)).collect()
+}
+
+/// 保存解析好的文档；单词表直接保存为 word 分词，不支持的语言直接拒绝
+pub fn import_document(db: &Mutex<DatabaseManager>, doc: ParsedDocument) -> Result<ImportedArticle, String> {
+    let check = language::check_article_language(&doc.content);
+    if !check.supported {
+        return Err(format!("{}: {}", doc.title, check.warning.unwrap_or_default()));
+    }
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    let article_id = db.create_article(&doc.title, &doc.content).map_err(|e| e.to_string())?;
+
+    let mut segmented_modes = Vec::new();
+    let kind = match &doc.word_list {
+        Some(words) => {
+            db.save_segments(article_id, "word", words).map_err(|e| e.to_string())?;
+            segmented_modes.push("word".to_string());
+            "wordlist"
+        }
+        None => "article",
+    };
+
+    // 字幕：每条字幕作为一个 sentence 分词，并保存时间轴
+    if let Some(cues) = &doc.cues {
+        let sentences: Vec<String> = cues.iter().map(|c| c.text.clone()).collect();
+        let timings: Vec<(i64, i64)> = cues.iter().map(|c| (c.start_ms, c.end_ms)).collect();
+        db.save_segments(article_id, "sentence", &sentences).map_err(|e| e.to_string())?;
+        db.save_segment_timings(article_id, "sentence", &timings).map_err(|e| e.to_string())?;
+        segmented_modes.push("sentence".to_string());
+    }
+
+    Ok(ImportedArticle {
+        article_id,
+        title: doc.title,
+        kind: kind.to_string(),
+        segmented_modes,
+        segment_errors: Vec::new(),
+        language: check.language,
+        warning: check.warning,
+    })
+}
+
+/// 用第一行非空文本作为标题，过长时截断
+pub fn derive_title(text: &str) -> String {
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+
+    if first_line.chars().count() <= MAX_TITLE_CHARS {
+        first_line.to_string()
+    } else {
+        let truncated: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+        format!("{}…", truncated.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_title() {
+        assert_eq!(derive_title("\n\n  The Fox  \nJumps over the dog."), "The Fox");
+        assert_eq!(derive_title("   \n"), "");
+        let long = "word ".repeat(20);
+        let title = derive_title(&long);
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.ends_with("word…"));
+        assert_eq!(derive_title(&"字".repeat(MAX_TITLE_CHARS)), "字".repeat(MAX_TITLE_CHARS));
+    }
+}
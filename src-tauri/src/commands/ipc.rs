@@ -0,0 +1,422 @@
+//! 本机 IPC 接口：家长可以用脚本做每晚导出，或接入 Home Assistant 之类的家庭看板
+//!
+//! Unix 上是数据目录中的 `spelling-game.sock`，Windows 上是命名管道 `\\.\pipe\spelling-game`，
+//! 都只允许当前系统用户访问。每行一个 JSON 请求，每行一个 JSON 响应，只提供只读查询：
+//!
+//! `{"method": "statistics", "user_name": "Tom", "pin": "1234"}` → `{"ok": true, "result": {...}}`
+//!
+//! 设置了 PIN 的用户查询统计时需要带 `pin`。请求行超过长度上限时回复错误并断开连接。
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::database::DatabaseManager;
+use crate::models::Actor;
+
+/// 查询方法，`user_name` 必填的在前
+pub const IPC_METHODS: [&str; 5] = ["statistics", "due_counts", "due_words", "users", "ping"];
+/// `due_words` 默认返回的单词数
+const DEFAULT_DUE_WORDS_LIMIT: i32 = 100;
+/// 单行请求的长度上限
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+#[cfg(unix)]
+const SOCKET_FILE: &str = "spelling-game.sock";
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\spelling-game";
+
+/// IPC 接口设置，默认关闭
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpcSettings {
+    pub enabled: bool,
+}
+
+/// IPC 接口状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub endpoint: String,           // socket 路径或命名管道名
+    pub methods: Vec<String>,
+}
+
+/// 运行中的 IPC 监听任务
+#[derive(Default)]
+pub struct IpcServer {
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+/// 一行 IPC 请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct IpcRequest {
+    pub method: String,
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub limit: Option<i32>,
+    #[serde(default)]
+    pub pin: Option<String>,        // statistics：设置了 PIN 的用户需要
+    #[serde(default)]
+    pub compare: bool,              // statistics：附带与上一周期的对比
+    #[serde(default)]
+    pub language: Option<String>,   // due_words：只返回该语言的单词
+}
+
+/// 处理一行请求，返回 `{"ok": true, "result": ...}` 或 `{"ok": false, "error": "..."}`
+pub fn handle_request(db: &DatabaseManager, line: &str) -> serde_json::Value {
+    match dispatch(db, line) {
+        Ok(result) => serde_json::json!({ "ok": true, "result": result }),
+        Err(error) => serde_json::json!({ "ok": false, "error": error }),
+    }
+}
+
+fn dispatch(db: &DatabaseManager, line: &str) -> Result<serde_json::Value, String> {
+    let request: IpcRequest = serde_json::from_str(line).map_err(|e| format!("无效的请求: {}", e))?;
+    let user_name = || {
+        request
+            .user_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| format!("{} 需要 user_name", request.method))
+    };
+    let result = match request.method.as_str() {
+        "ping" => Ok(serde_json::json!({ "version": env!("CARGO_PKG_VERSION") })),
+        "users" => db.get_known_users().map(|users| serde_json::json!(users)),
+        "statistics" => {
+            let user_name = user_name()?;
+            db.require_user_pin(user_name, request.pin.as_deref()).map_err(|e| e.to_string())?;
+            db.get_user_statistics(user_name, request.compare).map(|s| serde_json::json!(s))
+        }
+        "due_counts" => db.get_due_counts(user_name()?).map(|c| serde_json::json!(c)),
+        "due_words" => db
+            .get_due_words(user_name()?, request.limit.unwrap_or(DEFAULT_DUE_WORDS_LIMIT).max(0), request.language.as_deref())
+            .map(|w| serde_json::json!(w)),
+        other => return Err(format!("未知方法: {}，可选 {}", other, IPC_METHODS.join(" / "))),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// 保存 IPC 设置并立即开启或关闭接口
+#[tauri::command]
+pub fn save_ipc_settings(
+    app: AppHandle,
+    settings: IpcSettings,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+    server: State<'_, Mutex<IpcServer>>,
+) -> Result<IpcStatus, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "ipc_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())?;
+
+    let mut server = server.lock().map_err(|e| e.to_string())?;
+    stop(&app, &mut server);
+    if settings.enabled {
+        server.task = Some(start(&app)?);
+    }
+    status(&app, &settings, &server)
+}
+
+/// 获取 IPC 设置和运行状态
+#[tauri::command]
+pub fn get_ipc_status(app: AppHandle, server: State<'_, Mutex<IpcServer>>) -> Result<IpcStatus, String> {
+    let settings = read_ipc_settings(&app)?;
+    let server = server.lock().map_err(|e| e.to_string())?;
+    status(&app, &settings, &server)
+}
+
+/// 启动时按设置开启 IPC 接口
+pub fn start_if_enabled(app: &AppHandle) {
+    let enabled = read_ipc_settings(app).map(|s| s.enabled).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    match start(app) {
+        Ok(task) => {
+            let server = app.state::<Mutex<IpcServer>>();
+            let mut server = server.lock().unwrap_or_else(|e| e.into_inner());
+            server.task = Some(task);
+        }
+        Err(e) => log::warn!("Failed to start IPC endpoint: {}", e),
+    }
+}
+
+fn read_ipc_settings(app: &AppHandle) -> Result<IpcSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "ipc_settings.json")?;
+    if !config_path.exists() {
+        return Ok(IpcSettings::default());
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+fn status(app: &AppHandle, settings: &IpcSettings, server: &IpcServer) -> Result<IpcStatus, String> {
+    Ok(IpcStatus {
+        enabled: settings.enabled,
+        running: server.task.is_some(),
+        endpoint: endpoint(app)?,
+        methods: IPC_METHODS.iter().map(|m| m.to_string()).collect(),
+    })
+}
+
+#[cfg(unix)]
+fn endpoint(app: &AppHandle) -> Result<String, String> {
+    Ok(crate::paths::app_data_file(app, SOCKET_FILE)?.to_string_lossy().to_string())
+}
+
+#[cfg(windows)]
+fn endpoint(_app: &AppHandle) -> Result<String, String> {
+    Ok(PIPE_NAME.to_string())
+}
+
+fn stop(app: &AppHandle, server: &mut IpcServer) {
+    if let Some(task) = server.task.take() {
+        task.abort();
+        #[cfg(unix)]
+        if let Ok(path) = endpoint(app) {
+            let _ = std::fs::remove_file(path);
+        }
+        #[cfg(windows)]
+        let _ = app;
+        log::info!("IPC endpoint stopped");
+    }
+}
+
+#[cfg(unix)]
+fn start(app: &AppHandle) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = endpoint(app)?;
+    // 上次异常退出留下的 socket 文件
+    let _ = std::fs::remove_file(&path);
+    let listener = std::os::unix::net::UnixListener::bind(&path).map_err(|e| format!("无法创建 {}: {}", path, e))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    log::info!("IPC endpoint listening on {}", path);
+
+    let app = app.clone();
+    Ok(tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::UnixListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => return log::warn!("IPC endpoint failed: {}", e),
+        };
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve_connection(app.clone(), stream));
+                }
+                Err(e) => log::warn!("IPC accept failed: {}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(windows)]
+fn start(app: &AppHandle) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let sddl = current_user_sddl().map_err(|e| format!("无法读取当前用户: {}", e))?;
+    let app = app.clone();
+    Ok(tauri::async_runtime::spawn(async move {
+        let mut options = ServerOptions::new();
+        options.reject_remote_clients(true);
+        let mut server = match create_pipe(options.clone().first_pipe_instance(true), &sddl) {
+            Ok(server) => server,
+            Err(e) => return log::warn!("IPC endpoint failed: {}", e),
+        };
+        log::info!("IPC endpoint listening on {}", PIPE_NAME);
+        loop {
+            if let Err(e) = server.connect().await {
+                log::warn!("IPC accept failed: {}", e);
+                continue;
+            }
+            let next = match create_pipe(&options, &sddl) {
+                Ok(next) => next,
+                Err(e) => return log::warn!("IPC endpoint failed: {}", e),
+            };
+            tauri::async_runtime::spawn(serve_connection(app.clone(), std::mem::replace(&mut server, next)));
+        }
+    }))
+}
+
+/// 仅当前用户可访问的安全描述符（SDDL），以 UTF-16 结尾带 0
+#[cfg(windows)]
+fn current_user_sddl() -> std::io::Result<Vec<u16>> {
+    use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, HANDLE};
+    use windows_sys::Win32::Security::Authorization::ConvertSidToStringSidW;
+    use windows_sys::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token: HANDLE = 0;
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut len = 0u32;
+        GetTokenInformation(token, TokenUser, std::ptr::null_mut(), 0, &mut len);
+        // 按 8 字节对齐，TOKEN_USER 中有指针
+        let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+        let ok = GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), len, &mut len);
+        CloseHandle(token);
+        if ok == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+        let mut sid = std::ptr::null_mut();
+        if ConvertSidToStringSidW(user.User.Sid, &mut sid) == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let sid_len = (0..).take_while(|&i| *sid.add(i) != 0).count();
+        let sid_string = String::from_utf16_lossy(std::slice::from_raw_parts(sid, sid_len));
+        LocalFree(sid.cast());
+        // 受保护的 DACL，只有一条授予当前用户全部权限的 ACE
+        Ok(format!("D:P(A;;GA;;;{})", sid_string).encode_utf16().chain(Some(0)).collect())
+    }
+}
+
+/// 用给定的安全描述符创建一个命名管道实例
+#[cfg(windows)]
+fn create_pipe(
+    options: &tokio::net::windows::named_pipe::ServerOptions,
+    sddl: &[u16],
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeServer> {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::{ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1};
+    use windows_sys::Win32::Security::SECURITY_ATTRIBUTES;
+
+    unsafe {
+        let mut descriptor = std::ptr::null_mut();
+        if ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut descriptor, std::ptr::null_mut()) == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: descriptor,
+            bInheritHandle: 0,
+        };
+        let pipe = options.create_with_security_attributes_raw(PIPE_NAME, (&mut attributes as *mut SECURITY_ATTRIBUTES).cast());
+        LocalFree(descriptor);
+        pipe
+    }
+}
+
+/// 逐行读取请求并回复，直到客户端关闭连接；单行超过上限时回复错误并断开
+async fn serve_connection<S: AsyncRead + AsyncWrite + Unpin>(app: AppHandle, stream: S) {
+    serve_lines(stream, |line| {
+        let db = app.state::<Mutex<DatabaseManager>>();
+        let response = match db.lock() {
+            Ok(db) => handle_request(&db, line),
+            Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+        };
+        response
+    })
+    .await
+}
+
+async fn serve_lines<S, F>(stream: S, mut handle: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: FnMut(&str) -> serde_json::Value,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        // 最多读上限加换行符，读不到换行符就不再继续缓存
+        let read = match (&mut reader).take(MAX_REQUEST_BYTES as u64 + 1).read_until(b'\n', &mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        let too_long = read > MAX_REQUEST_BYTES && buffer.last() != Some(&b'\n');
+        let response = if too_long {
+            serde_json::json!({ "ok": false, "error": "请求过长" })
+        } else {
+            match std::str::from_utf8(&buffer) {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => handle(line.trim_end()),
+                Err(_) => serde_json::json!({ "ok": false, "error": "请求不是有效的 UTF-8" }),
+            }
+        };
+        let mut out = response.to_string();
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() || too_long {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipc_requests() {
+        let mut db = DatabaseManager::new(":memory:").unwrap();
+        let article_id = db.create_article("测试文章", "apple banana").unwrap();
+        db.save_segments(article_id, "word", &["apple".to_string(), "banana".to_string()]).unwrap();
+        let segments = db.get_segments(article_id, "word").unwrap();
+        db.update_word_mastery("Tom", segments[0].id, "apple", "word", false).unwrap();
+        db.update_word_mastery("Tom", segments[1].id, "banana", "word", true).unwrap();
+
+        let response = handle_request(&db, r#"{"method": "due_words", "user_name": "Tom"}"#);
+        assert_eq!(response["ok"], true);
+        let words = response["result"].as_array().unwrap();
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0]["segment_content"], "apple");
+
+        let response = handle_request(&db, r#"{"method": "due_counts", "user_name": "Tom"}"#);
+        assert_eq!(response["result"]["due_words"], 1);
+        let response = handle_request(&db, r#"{"method": "users"}"#);
+        assert!(response["result"].as_array().unwrap().iter().any(|u| u == "Tom"));
+        let response = handle_request(&db, r#"{"method": "statistics", "user_name": "Tom"}"#);
+        assert_eq!(response["ok"], true);
+
+        // 缺少用户、未知方法和无效 JSON 都返回错误而不是断开
+        let response = handle_request(&db, r#"{"method": "statistics"}"#);
+        assert_eq!(response["ok"], false);
+        assert!(response["error"].as_str().unwrap().contains("user_name"));
+        assert!(handle_request(&db, r#"{"method": "delete_user"}"#)["error"].as_str().unwrap().contains("未知方法"));
+        assert_eq!(handle_request(&db, "not json")["ok"], false);
+
+        // 设置了 PIN 的用户查询统计需要 PIN
+        db.set_user_pin("Tom", None, Some("1234")).unwrap();
+        assert_eq!(handle_request(&db, r#"{"method": "statistics", "user_name": "Tom"}"#)["ok"], false);
+        assert_eq!(handle_request(&db, r#"{"method": "statistics", "user_name": "Tom", "pin": "0000"}"#)["ok"], false);
+        assert_eq!(handle_request(&db, r#"{"method": "statistics", "user_name": "Tom", "pin": "1234"}"#)["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_request_line_limit() {
+        let (client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve_lines(server, |line| serde_json::json!({ "ok": true, "result": line.len() })));
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut responses = BufReader::new(reader).lines();
+
+        writer.write_all(b"\n{\"method\": \"ping\"}\n").await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&responses.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["result"], 18);
+
+        // 超长的一行不会整行缓存：回复错误后断开连接
+        let writing = tokio::spawn(async move {
+            let chunk = vec![b'x'; 1024];
+            for _ in 0..(MAX_REQUEST_BYTES / chunk.len() + 8) {
+                if writer.write_all(&chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let response: serde_json::Value = serde_json::from_str(&responses.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["error"], "请求过长");
+        assert!(responses.next_line().await.unwrap().is_none());
+        writing.await.unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+use std::collections::HashMap;
+
+/// 获取界面文字资源，未知语言回退到中文
+#[tauri::command]
+pub fn get_locale_strings(locale: Option<String>) -> Result<HashMap<String, String>, String> {
+    Ok(crate::i18n::strings(locale.as_deref()))
+}
@@ -1,5 +1,22 @@
 pub mod article;
+pub mod audio;
+pub mod audio_review;
+pub mod catalog;
+pub mod curriculum;
+pub mod dashboard;
+pub mod email;
+pub mod image;
+pub mod import;
+pub mod ipc;
+pub mod locale;
 pub mod practice;
+pub mod pronunciation;
+pub mod reading;
 pub mod segment;
+pub mod session;
+pub mod share;
+pub mod storage;
+pub mod timer;
 pub mod tts;
+pub mod webhook;
 pub mod wida;
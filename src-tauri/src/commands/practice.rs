@@ -1,10 +1,12 @@
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::webhook::{self, PracticeMilestones};
 use crate::database::DatabaseManager;
+use crate::ordering;
 use crate::models::{
-    LeaderboardRecord, Mistake, PracticeProgress, 
-    SaveProgressRequest, SaveRecordRequest, ScheduledWordsResponse, WordMastery
+    Actor, AddMistakeRequest, ArchivedMistake, ArticleResetPreview, CustomSessionFilters, LeaderboardRecord, LearningStateExport,
+    LearningStateImport, Mistake, PracticeProgress, SaveProgressRequest, SaveRecordRequest, ScheduledWordsResponse, UpdateMasteryRequest, WordMastery
 };
 
 /// 保存练习进度
@@ -20,6 +22,8 @@ pub fn save_progress(request: SaveProgressRequest, db: State<'_, Mutex<DatabaseM
         &words_list_json,
         request.correct_count,
         request.incorrect_count,
+        request.order_mode.as_deref(),
+        request.order_seed,
     ).map_err(|e| e.to_string())
 }
 
@@ -47,6 +51,32 @@ pub fn clear_progress(
     db.clear_progress(&user_name, article_id, &segment_type).map_err(|e| e.to_string())
 }
 
+/// 预览重置某篇文章的全部练习数据，返回将删除的记录数和确认令牌
+#[tauri::command]
+pub fn preview_article_reset(
+    user_name: String,
+    article_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ArticleResetPreview, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.preview_article_reset(&user_name, article_id).map_err(|e| e.to_string())
+}
+
+/// 重置某篇文章的练习进度、错词和熟练度（设置了 PIN 的用户需要验证）
+#[tauri::command]
+pub fn reset_article_progress(
+    user_name: String,
+    article_id: i64,
+    confirm_token: String,
+    pin: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ArticleResetPreview, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+    db.reset_article_progress(&user_name, article_id, &confirm_token)
+        .map_err(|e| e.to_string())
+}
+
 /// 添加错词/错句
 #[tauri::command]
 pub fn add_mistake(
@@ -61,6 +91,13 @@ pub fn add_mistake(
         .map_err(|e| e.to_string())
 }
 
+/// 批量记录错词（练习结束时统一提交）
+#[tauri::command]
+pub fn add_mistakes_batch(mistakes: Vec<AddMistakeRequest>, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.add_mistakes_batch(&mistakes).map_err(|e| e.to_string())
+}
+
 /// 移除错词/错句
 #[tauri::command]
 pub fn remove_mistake(user_name: String, segment_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
@@ -79,6 +116,13 @@ pub fn get_mistakes(
     db.get_mistakes(&user_name, segment_type.as_deref()).map_err(|e| e.to_string())
 }
 
+/// 获取已归档的错词（连续答对或已掌握后自动移出错词本）
+#[tauri::command]
+pub fn get_archived_mistakes(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<ArchivedMistake>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_archived_mistakes(&user_name).map_err(|e| e.to_string())
+}
+
 /// 保存练习记录（排行榜）
 #[tauri::command]
 pub fn save_record(request: SaveRecordRequest, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
@@ -107,20 +151,300 @@ pub fn get_leaderboard(
 }
 
 /// 获取智能调度的单词（基于记忆曲线）
+///
+/// `order_mode` 见 `ordering::apply_order`；传入上次返回的 `seed` 可复现相同顺序。
+/// `avoid_similar` 为 true 时再穿插相似词，避免首字母相同的单词扎堆出现。
+/// 今天已作答的单词默认不再出题，`include_answered` 为 true 时照常安排（如再练一遍）。
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn get_scheduled_words(
     user_name: String,
     article_id: i64,
     segment_type: String,
     limit: i32,
+    order_mode: Option<String>,
+    seed: Option<u64>,
+    avoid_similar: Option<bool>,
+    include_answered: Option<bool>,
     db: State<'_, Mutex<DatabaseManager>>,
 ) -> Result<ScheduledWordsResponse, String> {
     let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_scheduled_words(&user_name, article_id, &segment_type, limit)
+    scheduled_words(
+        &db,
+        &user_name,
+        article_id,
+        &segment_type,
+        limit,
+        order_mode.as_deref(),
+        seed,
+        avoid_similar.unwrap_or(false),
+        include_answered.unwrap_or(false),
+    )
+}
+
+/// 按无障碍设置限制数量后调度单词并排序（Tauri 命令和 HTTP 接口共用）
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn scheduled_words(
+    db: &DatabaseManager,
+    user_name: &str,
+    article_id: i64,
+    segment_type: &str,
+    limit: i32,
+    order_mode: Option<&str>,
+    seed: Option<u64>,
+    avoid_similar: bool,
+    include_answered: bool,
+) -> Result<ScheduledWordsResponse, String> {
+    // 无障碍设置中的每批单词数上限
+    let batch_size = db.get_accommodations(user_name).map_err(|e| e.to_string())?.batch_size;
+    let limit = batch_size.map_or(limit, |size| limit.min(size));
+    let mut response = db.get_scheduled_words(user_name, article_id, segment_type, limit, include_answered)
+        .map_err(|e| e.to_string())?;
+    response.seed = ordering::apply_order(&mut response.words, order_mode, seed)?;
+    if avoid_similar {
+        ordering::spread_similar(&mut response.words);
+    }
+    Ok(response)
+}
+
+/// 设置每日新词上限（`daily_limit` 为空时取消限制）
+#[tauri::command]
+pub fn set_new_word_pacing(
+    user_name: String,
+    daily_limit: Option<i32>,
+    carryover: Option<bool>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_new_word_pacing(&user_name, daily_limit, carryover.unwrap_or(true))
         .map_err(|e| e.to_string())
 }
 
+/// 开启或关闭按内容合并熟练度；开启时先合并已有的重复记录，返回合并掉的行数
+#[tauri::command]
+pub fn set_content_keyed_mastery(
+    user_name: String,
+    enabled: bool,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i32, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    let merged = if enabled {
+        db.merge_duplicate_masteries(&user_name).map_err(|e| e.to_string())?
+    } else {
+        0
+    };
+    db.set_content_keyed_mastery(&user_name, enabled).map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// 设置是否把美式和英式拼写（color / colour）视为同一个词，用于判断答案和按内容合并熟练度；
+/// 按内容合并时开启会合并两种拼写的已有记录，返回合并掉的行数
+#[tauri::command]
+pub fn set_accept_spelling_variants(
+    user_name: String,
+    enabled: bool,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i32, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.set_accept_spelling_variants(&user_name, enabled).map_err(|e| e.to_string())?;
+    if enabled && db.content_keyed_mastery(&user_name).map_err(|e| e.to_string())? {
+        return db.merge_duplicate_masteries(&user_name).map_err(|e| e.to_string());
+    }
+    Ok(0)
+}
+
+/// 获取是否接受美式和英式两种拼写
+#[tauri::command]
+pub fn get_accept_spelling_variants(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.accept_spelling_variants(&user_name).map_err(|e| e.to_string())
+}
+
+/// 合并同一用户相同内容的重复熟练度记录
+#[tauri::command]
+pub fn merge_duplicate_masteries(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<i32, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.merge_duplicate_masteries(&user_name).map_err(|e| e.to_string())
+}
+
+/// 获取每日新词上限及今天剩余名额
+#[tauri::command]
+pub fn get_new_word_pacing(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::NewWordPacing, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_new_word_pacing(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 保存无障碍设置（延长时间、语速上限、放大显示、每批单词数）
+#[tauri::command]
+pub fn set_accommodations(
+    accommodations: crate::models::Accommodations,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_accommodations(&accommodations)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取无障碍设置
+#[tauri::command]
+pub fn get_accommodations(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::Accommodations, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_accommodations(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置键盘布局（qwerty / azerty），用于区分按错相邻键和拼写错误
+#[tauri::command]
+pub fn set_keyboard_layout(user_name: String, layout: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_keyboard_layout(&user_name, &layout).map_err(|e| e.to_string())
+}
+
+/// 获取键盘布局
+#[tauri::command]
+pub fn get_keyboard_layout(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_keyboard_layout(&user_name).map_err(|e| e.to_string())
+}
+
+/// 按字母统计按错次数（易错字母只看拼写错误）
+#[tauri::command]
+pub fn get_letter_errors(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<crate::models::LetterErrorStat>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_letter_errors(&user_name).map_err(|e| e.to_string())
+}
+
+/// 设置、修改或清除用户 PIN（`new_pin` 为空表示清除）
+#[tauri::command]
+pub fn set_user_pin(
+    user_name: String,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_user_pin(&user_name, current_pin.as_deref(), new_pin.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 用户是否设置了 PIN（切换档案前决定是否需要输入）
+#[tauri::command]
+pub fn user_has_pin(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.user_has_pin(&user_name).map_err(|e| e.to_string())
+}
+
+/// 切换到某个档案前校验 PIN
+#[tauri::command]
+pub fn verify_user_pin(user_name: String, pin: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.verify_user_pin(&user_name, &pin).map_err(|e| e.to_string())
+}
+
+/// 获取用户角色（student / teacher / admin）
+#[tauri::command]
+pub fn get_user_role(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_user_role(&user_name).map_err(|e| e.to_string())
+}
+
+/// 设置用户角色，需要管理员身份
+#[tauri::command]
+pub fn set_user_role(
+    user_name: String,
+    role: String,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_user_role(actor.as_ref(), &user_name, &role)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置或清除用户出生年份（用于推荐 WIDA 年级段），设置了 PIN 的用户需提供 PIN
+#[tauri::command]
+pub fn set_user_birth_year(
+    user_name: String,
+    birth_year: Option<i32>,
+    pin: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+    db.set_user_birth_year(&user_name, birth_year).map_err(|e| e.to_string())
+}
+
+/// 获取用户出生年份
+#[tauri::command]
+pub fn get_user_birth_year(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Option<i32>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_user_birth_year(&user_name).map_err(|e| e.to_string())
+}
+
+/// 导出用户的学习状态（熟练度、错词、练习记录、设置）为 JSON 文件，返回临时文件路径
+///
+/// 单词按内容、练习记录按文章标题关联，可以导入到文章 ID 不同的另一台电脑。
+#[tauri::command]
+pub fn export_learning_state(
+    user_name: String,
+    pin: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let export = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+        db.export_learning_state(&user_name).map_err(|e| e.to_string())?
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+
+    let dir = std::env::temp_dir().join("spelling-game-exports");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let file_name: String = user_name.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+    let path = dir.join(format!("{}-learning-{}.json", file_name, chrono::Local::now().format("%Y%m%d%H%M%S%3f")));
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 导入学习状态文件，`user_name` 为空时导入到文件中的用户；目标用户设置了 PIN 时需提供 PIN
+#[tauri::command]
+pub fn import_learning_state(
+    path: String,
+    user_name: Option<String>,
+    pin: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<LearningStateImport, String> {
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let export: LearningStateExport = serde_json::from_str(&json).map_err(|e| format!("不是有效的学习状态文件: {}", e))?;
+    let user_name = user_name.filter(|u| !u.trim().is_empty()).unwrap_or_else(|| export.user_name.clone());
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+    db.import_learning_state(&user_name, &export).map_err(|e| e.to_string())
+}
+
+/// 按自定义条件组合复习列表
+#[tauri::command]
+pub fn build_custom_session(
+    filters: CustomSessionFilters,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ScheduledWordsResponse, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let mut response = db.build_custom_session(&filters).map_err(|e| e.to_string())?;
+    response.seed = ordering::apply_order(&mut response.words, filters.order_mode.as_deref(), filters.seed)?;
+    Ok(response)
+}
+
 /// 更新单词熟练度（SM-2 算法）
+///
+/// 可选的 `quality` 为自评质量等级（0-5，如“难/良好/简单”按钮对应 3/4/5），有值时按完整 SM-2 公式计算间隔。
 #[tauri::command]
 pub fn update_word_mastery(
     user_name: String,
@@ -128,13 +452,24 @@ pub fn update_word_mastery(
     segment_content: String,
     segment_type: String,
     correct: bool,
+    quality: Option<i32>,
     db: State<'_, Mutex<DatabaseManager>>,
 ) -> Result<WordMastery, String> {
     let db = db.lock().map_err(|e| e.to_string())?;
-    db.update_word_mastery(&user_name, segment_id, &segment_content, &segment_type, correct)
+    db.update_word_mastery_graded(&user_name, segment_id, &segment_content, &segment_type, correct, quality)
         .map_err(|e| e.to_string())
 }
 
+/// 批量更新单词熟练度（一次练习结束后统一提交）
+#[tauri::command]
+pub fn update_word_masteries_batch(
+    updates: Vec<UpdateMasteryRequest>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<WordMastery>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.update_word_masteries_batch(&updates).map_err(|e| e.to_string())
+}
+
 /// 获取单词熟练度列表
 #[tauri::command]
 pub fn get_word_masteries(
@@ -150,18 +485,40 @@ pub fn get_word_masteries(
 /// 保存练习历史
 #[tauri::command]
 pub fn save_practice_history(
+    app: AppHandle,
     request: crate::models::SaveHistoryRequest,
     db: State<'_, Mutex<DatabaseManager>>,
 ) -> Result<(), String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.save_practice_history(
-        &request.user_name,
-        request.article_id,
-        &request.segment_type,
-        request.correct_count,
-        request.incorrect_count,
-        request.duration_seconds,
-    ).map_err(|e| e.to_string())
+    let events = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let milestones = PracticeMilestones::before(&db, &request.user_name, request.article_id, &request.segment_type)?;
+        db.save_practice_history(
+            &request.user_name,
+            request.article_id,
+            &request.segment_type,
+            request.correct_count,
+            request.incorrect_count,
+            request.duration_seconds,
+        ).map_err(|e| e.to_string())?;
+        milestones.events(&db)?
+    };
+    webhook::notify(&app, events);
+    Ok(())
+}
+
+/// 构词分析：把单词拆成前缀、词根和后缀，练习界面用于高亮构词规律
+#[tauri::command]
+pub fn analyze_morphology(word: String) -> Result<crate::models::Morphology, String> {
+    if word.trim().is_empty() {
+        return Err("请输入单词".to_string());
+    }
+    Ok(crate::text::morphology::analyze(&word))
+}
+
+/// 按音节切分单词或短语，用于分块拼写练习
+#[tauri::command]
+pub fn syllabify(word: String) -> Vec<String> {
+    crate::text::syllable_chunks(&word)
 }
 
 /// 获取练习历史
@@ -177,12 +534,131 @@ pub fn get_practice_history(
 }
 
 /// 获取用户统计信息
+///
+/// `compare` 为 true 时附带与本机其他用户的匿名百分位对比；设置了 PIN 的用户需提供 `pin`
 #[tauri::command]
 pub fn get_user_statistics(
     user_name: String,
+    compare: Option<bool>,
+    pin: Option<String>,
     db: State<'_, Mutex<DatabaseManager>>,
 ) -> Result<crate::models::UserStatistics, String> {
     let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_user_statistics(&user_name)
+    db.require_user_pin(&user_name, pin.as_deref()).map_err(|e| e.to_string())?;
+    db.get_user_statistics(&user_name, compare.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取词汇量增长曲线（每周累计掌握的单词数）
+#[tauri::command]
+pub fn get_vocabulary_growth(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::VocabularyGrowthPoint>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_vocabulary_growth(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取熟练度变更记录（可按分词筛选）
+#[tauri::command]
+pub fn get_mastery_events(
+    user_name: String,
+    segment_id: Option<i64>,
+    limit: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::MasteryEvent>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_mastery_events(&user_name, segment_id, limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取曾经掌握、现已遗忘的单词
+#[tauri::command]
+pub fn get_relapsed_words(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::MasteryEvent>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_relapsed_words(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取顽固词（多次掌握后又遗忘的单词）
+#[tauri::command]
+pub fn get_leech_words(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::LeechWord>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_leech_words(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 分散未来复习量过大的日子（`max_per_day` 为空时按平均量的 1.5 倍判断）
+#[tauri::command]
+pub fn rebalance_reviews(
+    user_name: String,
+    max_per_day: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::ReviewRebalance, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.rebalance_reviews(&user_name, max_per_day)
+        .map_err(|e| e.to_string())
+}
+
+/// 登记假期（YYYY-MM-DD，含首尾），假期结束后到期的复习自动顺延假期天数
+#[tauri::command]
+pub fn pause_reviews(
+    user_name: String,
+    from: String,
+    to: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::ReviewPause, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.pause_reviews(&user_name, &from, &to).map_err(|e| e.to_string())
+}
+
+/// 获取登记过的假期
+#[tauri::command]
+pub fn get_review_pauses(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<crate::models::ReviewPause>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_review_pauses(&user_name).map_err(|e| e.to_string())
+}
+
+/// 取消还没生效的假期
+#[tauri::command]
+pub fn cancel_review_pause(id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.cancel_review_pause(id).map_err(|e| e.to_string())
+}
+
+/// 按复习间隔统计回忆率，检验复习计划的效果
+#[tauri::command]
+pub fn get_retention_stats(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<crate::models::RetentionBucket>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_retention_stats(&user_name).map_err(|e| e.to_string())
+}
+
+/// 获取包含最薄弱单词的句子，用于语境听写复习
+#[tauri::command]
+pub fn get_context_sentences(
+    user_name: String,
+    word_limit: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::ContextSentence>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_context_sentences(&user_name, word_limit.unwrap_or(10))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取导航栏角标计数（待复习、作业、未完成的 WIDA 测试）
+#[tauri::command]
+pub fn get_due_counts(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::DueCounts, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_due_counts(&user_name)
         .map_err(|e| e.to_string())
 }
@@ -0,0 +1,213 @@
+//! 发音评分：把学生录制的单词发音与参考文本对齐，按音素打分
+//!
+//! 目前通过云端 API（Azure 语音服务的 Pronunciation Assessment）完成强制对齐和评分，
+//! 录音需为 16kHz 单声道 PCM WAV。
+
+use std::sync::Mutex;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{PhonemeScore, PronunciationAssessment};
+
+/// 评分请求的超时时间（秒）
+const ASSESSMENT_TIMEOUT_SECS: u64 = 30;
+
+/// 发音评分服务设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationSettings {
+    pub region: String,     // 服务区域，如 eastus
+    pub api_key: String,
+    pub language: String,   // en-US | en-GB
+}
+
+/// 评估一个单词的发音并保存到熟练度记录
+#[tauri::command]
+pub async fn assess_pronunciation(
+    app: AppHandle,
+    user_name: String,
+    segment_id: i64,
+    audio: Vec<u8>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<PronunciationAssessment, String> {
+    let segment = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_segment(segment_id).map_err(|e| e.to_string())?.ok_or("分词不存在")?
+    };
+    let settings = read_pronunciation_settings(&app)?;
+    if settings.api_key.trim().is_empty() || settings.region.trim().is_empty() {
+        return Err("请先配置发音评分服务的区域和 API Key".to_string());
+    }
+    if audio.is_empty() {
+        return Err("录音为空".to_string());
+    }
+
+    let config = serde_json::json!({
+        "ReferenceText": segment.content,
+        "GradingSystem": "HundredMark",
+        "Granularity": "Phoneme",
+        "Dimension": "Comprehensive",
+    });
+    let url = format!(
+        "https://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1?language={}&format=detailed",
+        settings.region.trim(),
+        settings.language
+    );
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(ASSESSMENT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response: serde_json::Value = client
+        .post(&url)
+        .header("Ocp-Apim-Subscription-Key", settings.api_key.trim())
+        .header("Content-Type", "audio/wav; codecs=audio/pcm; samplerate=16000")
+        .header("Pronunciation-Assessment", base64::engine::general_purpose::STANDARD.encode(config.to_string()))
+        .body(audio)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Parse error: {}", e))?;
+
+    let (overall_score, phonemes) = parse_assessment(&response)?;
+    let assessment = PronunciationAssessment {
+        segment_id,
+        word: segment.content,
+        overall_score,
+        phonemes,
+        assessed_at: None,
+    };
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.save_pronunciation_score(&user_name, &assessment).map_err(|e| e.to_string())?;
+    db.get_pronunciation_score(&user_name, segment_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "保存发音评分失败".to_string())
+}
+
+/// 识别一段录音（16kHz 单声道 PCM WAV）的文字，返回文本和可信度
+pub(crate) async fn recognize_speech(app: &AppHandle, audio: Vec<u8>) -> Result<(String, Option<f64>), String> {
+    let settings = read_pronunciation_settings(app)?;
+    if settings.api_key.trim().is_empty() || settings.region.trim().is_empty() {
+        return Err("请先配置语音服务的区域和 API Key".to_string());
+    }
+    if audio.is_empty() {
+        return Err("录音为空".to_string());
+    }
+    let url = format!(
+        "https://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1?language={}&format=detailed",
+        settings.region.trim(),
+        settings.language
+    );
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(ASSESSMENT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response: serde_json::Value = client
+        .post(&url)
+        .header("Ocp-Apim-Subscription-Key", settings.api_key.trim())
+        .header("Content-Type", "audio/wav; codecs=audio/pcm; samplerate=16000")
+        .body(audio)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Parse error: {}", e))?;
+    parse_recognition(&response)
+}
+
+/// 解析识别结果：没有识别到语音（NoMatch、InitialSilenceTimeout）时为空文本
+fn parse_recognition(response: &serde_json::Value) -> Result<(String, Option<f64>), String> {
+    match response["RecognitionStatus"].as_str() {
+        Some("Success") => {}
+        Some("NoMatch") | Some("InitialSilenceTimeout") | Some("BabbleTimeout") => return Ok((String::new(), None)),
+        Some(status) => return Err(format!("语音识别失败: {}", status)),
+        None => return Err("无法解析语音识别结果".to_string()),
+    }
+    let best = &response["NBest"][0];
+    let text = best["Display"].as_str().or_else(|| response["DisplayText"].as_str()).unwrap_or_default();
+    Ok((text.to_string(), best["Confidence"].as_f64()))
+}
+
+/// 获取最近一次发音评分
+#[tauri::command]
+pub fn get_pronunciation_score(
+    user_name: String,
+    segment_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Option<PronunciationAssessment>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_pronunciation_score(&user_name, segment_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 保存发音评分服务设置
+#[tauri::command]
+pub fn save_pronunciation_settings(
+    app: AppHandle,
+    settings: PronunciationSettings,
+    actor: Option<crate::models::Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "pronunciation_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 加载发音评分服务设置
+#[tauri::command]
+pub fn load_pronunciation_settings(app: AppHandle) -> Result<PronunciationSettings, String> {
+    read_pronunciation_settings(&app)
+}
+
+pub(crate) fn read_pronunciation_settings(app: &AppHandle) -> Result<PronunciationSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "pronunciation_settings.json")?;
+    if !config_path.exists() {
+        return Ok(PronunciationSettings {
+            region: String::new(),
+            api_key: String::new(),
+            language: "en-US".to_string(),
+        });
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+/// 解析评分结果：整体准确度和各音素得分
+///
+/// 分数可能直接在对象上，也可能在 `PronunciationAssessment` 子对象里（不同 API 版本）。
+pub fn parse_assessment(response: &serde_json::Value) -> Result<(f64, Vec<PhonemeScore>), String> {
+    let status = response["RecognitionStatus"].as_str().unwrap_or("Success");
+    if status != "Success" {
+        return Err(format!("没有识别到发音: {}", status));
+    }
+    let best = response["NBest"].get(0).ok_or("评分结果为空")?;
+    let score = |value: &serde_json::Value| {
+        value["PronunciationAssessment"]["AccuracyScore"].as_f64().or_else(|| value["AccuracyScore"].as_f64())
+    };
+    let overall_score = score(best).ok_or("评分结果缺少准确度")?;
+    let phonemes = best["Words"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|word| word["Phonemes"].as_array().into_iter().flatten())
+        .filter_map(|phoneme| {
+            Some(PhonemeScore {
+                phoneme: phoneme["Phoneme"].as_str()?.to_string(),
+                score: score(phoneme)?,
+            })
+        })
+        .collect();
+    Ok((overall_score, phonemes))
+}
@@ -0,0 +1,118 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{FluencyTrend, ReadingRecording, RecordingMetadata};
+
+/// 录音文件保存的子目录
+const RECORDING_DIR: &str = "recordings";
+/// 支持的录音格式（WebView MediaRecorder 通常输出 webm 或 m4a）
+const AUDIO_EXTENSIONS: [&str; 6] = ["webm", "ogg", "wav", "m4a", "mp3", "aac"];
+
+/// 开始朗读一篇文章，返回录音 id（音频由前端录制）
+#[tauri::command]
+pub fn record_reading(
+    user_name: String,
+    article_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i64, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.start_reading_recording(&user_name, article_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 结束朗读：保存录音文件，解码出时长和波形，并计算每分钟单词数
+///
+/// 没有传入 `duration_ms` 时优先使用解码出的时长。
+#[tauri::command]
+pub fn finish_reading(
+    app: AppHandle,
+    recording_id: i64,
+    audio: Vec<u8>,
+    extension: String,
+    duration_ms: Option<i64>,
+    words_read: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ReadingRecording, String> {
+    let extension = extension.trim_start_matches('.').to_lowercase();
+    if !AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("不支持的录音格式: {}", extension));
+    }
+    if audio.is_empty() {
+        return Err("录音为空".to_string());
+    }
+    let path = crate::paths::app_data_subdir(&app, RECORDING_DIR)?
+        .join(format!("reading-{}-{}.{}", recording_id, uuid::Uuid::new_v4().simple(), extension));
+    std::fs::write(&path, &audio).map_err(|e| e.to_string())?;
+    let decoded = crate::waveform::analyze_file(&path)
+        .inspect_err(|e| log::info!("No waveform for recording {}: {}", recording_id, e))
+        .ok();
+
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let duration_ms = duration_ms.or(decoded.as_ref().map(|audio| audio.duration_ms));
+    let recording = db
+        .complete_reading_recording(recording_id, &path.to_string_lossy(), duration_ms, words_read)
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&path);
+        })
+        .map_err(|e| e.to_string())?;
+    if let Some(audio) = &decoded {
+        db.save_recording_audio(recording_id, audio).map_err(|e| e.to_string())?;
+    }
+    Ok(recording)
+}
+
+/// 获取录音的时长和降采样后的波形，用于绘制播放进度条
+///
+/// 旧版本保存的录音在第一次获取时解码并保存。
+#[tauri::command]
+pub fn get_recording_metadata(recording_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<RecordingMetadata, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let metadata = db
+        .get_recording_metadata(recording_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("录音不存在: {}", recording_id))?;
+    if metadata.audio.is_some() {
+        return Ok(metadata);
+    }
+    let path = db.get_reading_recording(recording_id).map_err(|e| e.to_string())?.and_then(|r| r.file_path);
+    let Some(audio) = path.and_then(|path| crate::waveform::analyze_file(std::path::Path::new(&path)).ok()) else {
+        return Ok(metadata);
+    };
+    db.save_recording_audio(recording_id, &audio).map_err(|e| e.to_string())?;
+    Ok(RecordingMetadata { recording_id, duration_ms: Some(audio.duration_ms), audio: Some(audio) })
+}
+
+/// 获取某篇文章的朗读录音
+#[tauri::command]
+pub fn get_reading_recordings(
+    user_name: String,
+    article_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<ReadingRecording>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_reading_recordings(&user_name, article_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取同一篇文章多次朗读的流利度趋势
+#[tauri::command]
+pub fn get_fluency_trend(
+    user_name: String,
+    article_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<FluencyTrend, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_fluency_trend(&user_name, article_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 删除一次朗读录音及其文件
+#[tauri::command]
+pub fn delete_reading_recording(recording_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    if let Some(path) = db.delete_reading_recording(recording_id).map_err(|e| e.to_string())? {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
@@ -4,6 +4,14 @@ use tauri::async_runtime::spawn;
 
 use crate::models::{SegmentRequest, SegmentResponse};
 
+/// 单次请求的默认超时（秒）
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// 临时故障的默认重试次数
+const DEFAULT_MAX_RETRIES: u32 = 3;
+/// 重试等待的初始时长和上限（毫秒），每次翻倍
+const BACKOFF_BASE_MS: u64 = 500;
+const BACKOFF_MAX_MS: u64 = 8_000;
+
 #[derive(Debug, Serialize)]
 struct ServerSegmentRequest {
     text: String,
@@ -19,10 +27,108 @@ struct ServerSegmentResponse {
     metadata: Option<serde_json::Value>,
 }
 
+/// 分词服务器请求失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentError {
+    /// 连不上服务器（未启动、地址错误、DNS 失败）
+    ConnectionRefused(String),
+    /// 超过设置的超时时间
+    Timeout,
+    /// 服务器返回非 2xx 状态码
+    Server(u16),
+    /// 响应不是预期的 JSON
+    Parse(String),
+    /// 其他网络错误
+    Network(String),
+}
+
+impl SegmentError {
+    /// 前端用于区分错误类型的标识
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SegmentError::ConnectionRefused(_) => "connection_refused",
+            SegmentError::Timeout => "timeout",
+            SegmentError::Server(_) => "server_error",
+            SegmentError::Parse(_) => "parse_error",
+            SegmentError::Network(_) => "network_error",
+        }
+    }
+
+    /// 是否为值得重试的临时故障（服务器冷启动、超时、5xx、限流）
+    pub fn is_transient(&self) -> bool {
+        match self {
+            SegmentError::ConnectionRefused(_) | SegmentError::Timeout | SegmentError::Network(_) => true,
+            SegmentError::Server(status) => *status >= 500 || *status == 429,
+            SegmentError::Parse(_) => false,
+        }
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            SegmentError::Timeout
+        } else if e.is_connect() {
+            SegmentError::ConnectionRefused(e.to_string())
+        } else if e.is_decode() {
+            SegmentError::Parse(e.to_string())
+        } else {
+            SegmentError::Network(e.to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentError::ConnectionRefused(e) => write!(f, "无法连接分词服务器，请确认服务器正在运行: {}", e),
+            SegmentError::Timeout => write!(f, "分词服务器响应超时"),
+            SegmentError::Server(status) => write!(f, "Server error: {}", status),
+            SegmentError::Parse(e) => write!(f, "Parse error: {}", e),
+            SegmentError::Network(e) => write!(f, "Network error: {}", e),
+        }
+    }
+}
+
+/// 第 `attempt` 次重试前的等待时间（从 0 开始，指数增长并封顶）
+pub fn backoff_delay(attempt: u32) -> std::time::Duration {
+    let ms = BACKOFF_BASE_MS.saturating_mul(1 << attempt.min(16)).min(BACKOFF_MAX_MS);
+    std::time::Duration::from_millis(ms)
+}
+
 /// 调用服务器进行分词
+///
+/// 失败时返回 `success: false`，`error_kind` 区分连接失败、超时、服务器错误和解析错误。
 #[tauri::command]
 pub async fn segment_text(request: SegmentRequest) -> Result<SegmentResponse, String> {
-    let server_url = request.server_url.unwrap_or_else(|| {
+    let result = request_segments(
+        request.text,
+        request.mode,
+        request.server_url,
+        request.timeout_secs,
+        request.max_retries,
+    )
+    .await;
+    Ok(match result {
+        Ok(segments) => SegmentResponse { segments, success: true, error: None, error_kind: None },
+        Err(e) => SegmentResponse {
+            segments: Vec::new(),
+            success: false,
+            error: Some(e.to_string()),
+            error_kind: Some(e.kind().to_string()),
+        },
+    })
+}
+
+/// 请求分词服务器，返回分词结果（供导入等流程复用）
+///
+/// 临时故障按指数退避自动重试，最多 `max_retries` 次。
+pub async fn request_segments(
+    text: String,
+    mode: String,
+    server_url: Option<String>,
+    timeout_secs: Option<u64>,
+    max_retries: Option<u32>,
+) -> Result<Vec<String>, SegmentError> {
+    let server_url = server_url.unwrap_or_else(|| {
         // 默认使用生产服务器地址
         option_env!("SEGMENT_SERVER_URL")
             .unwrap_or("https://wordsspelling-production.up.railway.app")
@@ -30,40 +136,73 @@ pub async fn segment_text(request: SegmentRequest) -> Result<SegmentResponse, St
     });
     
     let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
+        .timeout(std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).max(1)))
         .build()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| SegmentError::Network(e.to_string()))?;
     
-    let server_request = ServerSegmentRequest {
-        text: request.text,
-        mode: request.mode,
-    };
+    let server_request = ServerSegmentRequest { text, mode };
     
     let url = format!("{}/api/segment", server_url);
+    let max_retries = max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
     
     spawn(async move {
-        let response = client
-            .post(&url)
-            .json(&server_request)
-            .send()
-            .await
-            .map_err(|e| format!("Network error: {}", e))?;
-        
-        if !response.status().is_success() {
-            return Err(format!("Server error: {}", response.status()));
+        let mut attempt = 0;
+        loop {
+            match send_segment_request(&client, &url, &server_request).await {
+                Err(e) if e.is_transient() && attempt < max_retries => {
+                    log::warn!("Segment request failed ({}), retrying: {}", e.kind(), e);
+                    tokio::time::sleep(backoff_delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
         }
-        
-        let result: ServerSegmentResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Parse error: {}", e))?;
-        
-        Ok(SegmentResponse {
-            segments: result.segments,
-            success: true,
-            error: None,
-        })
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|e| SegmentError::Network(e.to_string()))?
+}
+
+async fn send_segment_request(
+    client: &Client,
+    url: &str,
+    request: &ServerSegmentRequest,
+) -> Result<Vec<String>, SegmentError> {
+    let response = client
+        .post(url)
+        .json(request)
+        .send()
+        .await
+        .map_err(SegmentError::from_reqwest)?;
+    
+    if !response.status().is_success() {
+        return Err(SegmentError::Server(response.status().as_u16()));
+    }
+    
+    let result: ServerSegmentResponse = response
+        .json()
+        .await
+        .map_err(|e| SegmentError::Parse(e.to_string()))?;
+    
+    Ok(result.segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 分词服务器错误分类与退避时间
+    #[test]
+    fn test_segment_retry_policy() {
+        assert!(SegmentError::ConnectionRefused("refused".to_string()).is_transient());
+        assert!(SegmentError::Timeout.is_transient());
+        assert!(SegmentError::Server(503).is_transient());
+        assert!(SegmentError::Server(429).is_transient());
+        assert!(!SegmentError::Server(400).is_transient());
+        assert!(!SegmentError::Parse("bad json".to_string()).is_transient());
+        assert_eq!(SegmentError::Server(502).kind(), "server_error");
+
+        assert_eq!(backoff_delay(0).as_millis(), 500);
+        assert_eq!(backoff_delay(2).as_millis(), 2_000);
+        assert_eq!(backoff_delay(10).as_millis(), 8_000);
+    }
 }
@@ -0,0 +1,474 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::webhook::{self, PracticeMilestones};
+use crate::database::DatabaseManager;
+use crate::text::answer_check::check_answer;
+use crate::text::keyboard;
+use crate::models::{
+    InputValidation, KeystrokeFeedback, PracticeSession, SessionAnswerResult, SessionHint, SessionStats,
+    StartPracticeSessionRequest,
+};
+
+/// 每作答该数量的题目（无论对错）自动保存一次检查点
+const CHECKPOINT_INTERVAL: i32 = 5;
+
+/// 听力提速训练：起始语速、每次调整的幅度和语速范围（每分钟单词数）
+const SPEED_START_RATE: i32 = 150;
+const SPEED_STEP: i32 = 10;
+const SPEED_MIN_RATE: i32 = 100;
+const SPEED_MAX_RATE: i32 = 350;
+/// 连续答对该数量的题目后提速
+const SPEED_UP_STREAK: i32 = 3;
+
+/// 根据本题对错计算下一题的语速和新的连续答对数
+///
+/// 连续答对 `SPEED_UP_STREAK` 题提速一档，答错降一档并重新计数；`max_rate` 为无障碍设置中的语速上限。
+pub fn next_speed_rate(rate: i32, streak: i32, correct: bool, max_rate: Option<i32>) -> (i32, i32) {
+    let (rate, streak) = if !correct {
+        (rate - SPEED_STEP, 0)
+    } else if streak + 1 >= SPEED_UP_STREAK {
+        (rate + SPEED_STEP, 0)
+    } else {
+        (rate, streak + 1)
+    };
+    (rate.clamp(SPEED_MIN_RATE, speed_rate_cap(max_rate)), streak)
+}
+
+fn speed_rate_cap(max_rate: Option<i32>) -> i32 {
+    max_rate.map_or(SPEED_MAX_RATE, |m| m.min(SPEED_MAX_RATE)).max(SPEED_MIN_RATE)
+}
+
+/// 使用提示后的题目得分：每次提示扣 `penalty` 分，最低 0 分
+pub fn hinted_item_score(correct: bool, hints_used: i32, penalty: f64) -> f64 {
+    if correct {
+        (1.0 - hints_used as f64 * penalty).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// 练习目标：答对若干题、练满若干分钟或清空本次题目中到期的复习；题目做完时无论是否达成都会结束
+enum SessionGoal {
+    Correct(i32),
+    Minutes(i64),
+    ClearDueQueue,
+}
+
+impl SessionGoal {
+    /// 解析 `until_20_correct`、`10_minutes`、`clear_due_queue`
+    fn parse(goal: &str) -> Result<Self, String> {
+        let invalid = || format!("未知的练习目标: {}", goal);
+        if goal == "clear_due_queue" {
+            return Ok(SessionGoal::ClearDueQueue);
+        }
+        if let Some(count) = goal.strip_prefix("until_").and_then(|g| g.strip_suffix("_correct")) {
+            return count.parse().ok().filter(|&n| n > 0).map(SessionGoal::Correct).ok_or_else(invalid);
+        }
+        if let Some(minutes) = goal.strip_suffix("_minutes") {
+            return minutes.parse().ok().filter(|&n| n > 0).map(SessionGoal::Minutes).ok_or_else(invalid);
+        }
+        Err(invalid())
+    }
+
+    fn is_reached(&self, db: &DatabaseManager, session: &PracticeSession, correct_count: i32) -> Result<bool, String> {
+        match self {
+            SessionGoal::Correct(target) => Ok(correct_count >= *target),
+            SessionGoal::Minutes(minutes) => {
+                // 有延长时间设置的用户按倍数延长
+                let multiplier = db.get_accommodations(&session.user_name).map_err(|e| e.to_string())?.time_multiplier;
+                let target = (*minutes as f64 * 60.0 * multiplier).round() as i64;
+                Ok(db.session_elapsed_seconds(session.id).map_err(|e| e.to_string())? >= target)
+            }
+            // 只看本次会话的题目，其他文章的到期单词不影响
+            SessionGoal::ClearDueQueue => {
+                let segment_ids: Vec<i64> = session.items.iter().map(|item| item.segment_id).collect();
+                Ok(db.count_due_segments(&session.user_name, &segment_ids).map_err(|e| e.to_string())? == 0)
+            }
+        }
+    }
+}
+
+/// 开始练习会话，之后的输入校验和计分都在后端完成
+///
+/// 可选的 `goal` 达成时会话提前结束，结束时后端自动写入练习历史。
+/// 同一文章和类型有未完成的作业且设置了答案检查方式时，以作业的设置为准。
+#[tauri::command]
+pub fn start_practice_session(
+    request: StartPracticeSessionRequest,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<PracticeSession, String> {
+    if request.items.is_empty() {
+        return Err("练习列表为空".to_string());
+    }
+    if let Some(goal) = &request.goal {
+        SessionGoal::parse(goal)?;
+    }
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let mut request = request;
+    if request.speed_training {
+        let max_rate = db.get_accommodations(&request.user_name).map_err(|e| e.to_string())?.max_tts_rate;
+        let rate = request.tts_rate.unwrap_or(SPEED_START_RATE);
+        request.tts_rate = Some(rate.clamp(SPEED_MIN_RATE, speed_rate_cap(max_rate)));
+    }
+    let assignment_options = db
+        .get_assignments(Some(&request.user_name), Some("pending"))
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|a| a.article_id == request.article_id && a.segment_type == request.segment_type)
+        .find_map(|a| a.check_options);
+    if assignment_options.is_some() {
+        request.check_options = assignment_options;
+    }
+    // 没有指定时按用户的美英拼写设置
+    let mut check_options = request.check_options.take().unwrap_or_default();
+    if check_options.accept_spelling_variants.is_none() {
+        let accept = db.accept_spelling_variants(&request.user_name).map_err(|e| e.to_string())?;
+        check_options.accept_spelling_variants = Some(accept);
+    }
+    request.check_options = Some(check_options);
+    let session_id = db.create_practice_session(&request).map_err(|e| e.to_string())?;
+    db.get_practice_session(session_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "创建练习会话失败".to_string())
+}
+
+/// 获取练习会话
+#[tauri::command]
+pub fn get_practice_session(session_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<Option<PracticeSession>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_practice_session(session_id).map_err(|e| e.to_string())
+}
+
+/// 校验当前输入（每次按键调用，`typed` 为输入框的完整内容）
+///
+/// 新输入的每个字符发出一个 `keystroke-feedback` 事件，前端据此播放按键音效；删除不计入按键数。
+/// 分块练习时同时返回音节块和已拼完的块数，前端据此逐块显示下一个音节。
+#[tauri::command]
+pub fn validate_input(
+    app: AppHandle,
+    session_id: i64,
+    typed: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<InputValidation, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let session = active_session(&db, session_id)?;
+    let expected = &session.items[session.current_index as usize].content;
+
+    let layout = db.get_keyboard_layout(&session.user_name).map_err(|e| e.to_string())?;
+    let keystrokes = new_keystrokes(session_id, &session.typed, &typed, expected, &layout);
+    let correct_keystrokes = keystrokes.iter().filter(|k| k.correct).count() as i32;
+    db.record_session_typing(session_id, &typed, keystrokes.len() as i32, correct_keystrokes)
+        .map_err(|e| e.to_string())?;
+    for keystroke in &keystrokes {
+        let (Some(kind), Some(expected_char)) = (&keystroke.error_kind, expected.chars().nth(keystroke.position)) else {
+            continue;
+        };
+        let typed_char = keystroke.key.chars().next().unwrap_or_default();
+        db.record_keystroke_error(session_id, &session.user_name, expected_char, typed_char, kind)
+            .map_err(|e| e.to_string())?;
+    }
+
+    for feedback in &keystrokes {
+        if let Err(e) = app.emit("keystroke-feedback", feedback) {
+            log::warn!("Failed to emit keystroke-feedback event: {}", e);
+        }
+    }
+
+    let first_error_at = first_error(&typed, expected);
+    let chunks = if session.chunked { crate::text::syllable_chunks(expected) } else { Vec::new() };
+    Ok(InputValidation {
+        is_prefix: first_error_at.is_none(),
+        is_complete: check_answer(&typed, expected, &session.check_options).correct,
+        first_error_at,
+        keystrokes,
+        completed_chunks: crate::text::completed_chunks(&chunks, &typed),
+        chunks,
+    })
+}
+
+/// 为当前题目请求下一级提示：长度 → 首字母 → 再读一遍 → 每次多显示一个字母
+///
+/// 提示次数按题记录，提交答案时据此扣分；达到会话的提示阈值后答对也不提升熟练度。
+#[tauri::command]
+pub fn request_hint(session_id: i64, item_index: i32, db: State<'_, Mutex<DatabaseManager>>) -> Result<SessionHint, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let session = active_session(&db, session_id)?;
+    if item_index != session.current_index {
+        return Err("只能为当前题目请求提示".to_string());
+    }
+    let item = &session.items[item_index as usize];
+    let hint_level = db.record_session_hint(session_id, item_index).map_err(|e| e.to_string())?;
+    let (kind, hint, speak) = crate::text::progressive_hint(&item.content, hint_level);
+    Ok(SessionHint {
+        session_id,
+        item_index,
+        hint_level,
+        kind: kind.to_string(),
+        hint,
+        speak,
+    })
+}
+
+/// 提交当前题目的答案
+///
+/// 由后端判定对错并更新熟练度和错词本，前端无需再调用 `update_word_mastery` / `add_mistake`；
+/// 会话结束时也已写入练习历史，无需再调用 `save_practice_history`。
+#[tauri::command]
+pub fn submit_session_answer(
+    app: AppHandle,
+    session_id: i64,
+    typed: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<SessionAnswerResult, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let session = active_session(&db, session_id)?;
+    let item = &session.items[session.current_index as usize];
+
+    let check = check_answer(&typed, &item.content, &session.check_options);
+    let correct = check.correct;
+    // 提示用得太多时，答对也按答错更新熟练度（但不记入错词本）
+    let hints_used = db.session_item_hints(session_id, session.current_index).map_err(|e| e.to_string())?;
+    let mastery_correct = correct && hints_used < session.hint_mastery_threshold;
+    let mastery = db
+        .update_word_mastery(&session.user_name, item.segment_id, &item.content, &session.segment_type, mastery_correct)
+        .map_err(|e| e.to_string())?;
+    // 答错的句子按对上的单词给部分分
+    let item_score = if correct {
+        hinted_item_score(true, hints_used, session.hint_penalty)
+    } else {
+        check.credit * hinted_item_score(true, hints_used, session.hint_penalty)
+    };
+    if item_score > 0.0 {
+        db.add_session_score(session_id, item_score).map_err(|e| e.to_string())?;
+    }
+    if !correct {
+        db.add_mistake(&session.user_name, item.segment_id, &item.content, &session.segment_type)
+            .map_err(|e| e.to_string())?;
+    }
+
+    // 写成了同音词（their → there）：单独归类，两个词一起安排对比练习
+    let homophone = if correct { None } else { crate::text::homophones::homophone_swap(&typed, &item.content) };
+    if let Some((word, confused_with)) = &homophone {
+        db.record_homophone_confusion(&session.user_name, word, confused_with).map_err(|e| e.to_string())?;
+    } else if correct {
+        for word in item.content.split_whitespace() {
+            db.record_homophone_success(&session.user_name, word).map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 提速训练：按对错调整下一题的朗读语速
+    let mut tts_rate = session.tts_rate;
+    if let (true, Some(rate)) = (session.speed_training, session.tts_rate) {
+        let max_rate = db.get_accommodations(&session.user_name).map_err(|e| e.to_string())?.max_tts_rate;
+        let streak = db.session_speed_streak(session_id).map_err(|e| e.to_string())?;
+        let (next_rate, streak) = next_speed_rate(rate, streak, correct, max_rate);
+        db.record_speed_answer(session_id, &session.user_name, rate, correct, next_rate, streak)
+            .map_err(|e| e.to_string())?;
+        tts_rate = Some(next_rate);
+    }
+
+    db.record_practice_event(session_id, &session.user_name, session.current_index, item.segment_id, correct)
+        .map_err(|e| e.to_string())?;
+    let total_items = session.items.len() as i32;
+    let current_index = db.record_session_answer(session_id, correct, total_items).map_err(|e| e.to_string())?;
+
+    let goal_reached = match session.goal.as_deref().map(SessionGoal::parse).transpose()? {
+        Some(goal) => Some(goal.is_reached(&db, &session, session.correct_count + correct as i32)?),
+        None => None,
+    };
+    let finished = current_index >= total_items || goal_reached == Some(true);
+    // 答错不前进到下一题，按作答次数而不是题号计算
+    let answered = session.correct_count + session.incorrect_count + 1;
+    if finished {
+        let milestones =
+            PracticeMilestones::before(&db, &session.user_name, session.article_id, &session.segment_type)?;
+        db.finish_practice_session(session_id, goal_reached).map_err(|e| e.to_string())?;
+        // 会话完成，不再需要恢复
+        db.clear_progress(&session.user_name, session.article_id, &session.segment_type)
+            .map_err(|e| e.to_string())?;
+        webhook::notify(&app, milestones.events(&db)?);
+    } else if answered % CHECKPOINT_INTERVAL == 0 {
+        db.checkpoint_practice_session(session_id).map_err(|e| e.to_string())?;
+    }
+
+    Ok(SessionAnswerResult {
+        correct,
+        expected: item.content.clone(),
+        current_index,
+        finished,
+        goal_met: if finished { goal_reached } else { None },
+        mastery,
+        tts_rate,
+        hints_used,
+        item_score,
+        error_class: homophone.as_ref().map(|_| "homophone".to_string()),
+        confused_with: homophone.map(|(_, confused_with)| confused_with),
+        tokens: check.tokens,
+    })
+}
+
+/// 获取待对比练习的同音词组，前端把两个词放在一起（如例句填空）练习
+#[tauri::command]
+pub fn get_homophone_practice(
+    user_name: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::HomophonePair>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_homophone_practice(&user_name).map_err(|e| e.to_string())
+}
+
+/// 组织混合练习：拼写题中穿插与水平相当的听力小测验，返回统一的题目列表
+#[tauri::command]
+pub fn build_mixed_session(
+    request: crate::models::BuildMixedSessionRequest,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::MixedSession, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.build_mixed_session(&request).map_err(|e| e.to_string())
+}
+
+/// 提交混合练习中一题的答案（拼写题输入单词，听力题为选项下标或简答文字）
+#[tauri::command]
+pub fn submit_mixed_answer(
+    session_id: i64,
+    item_index: i32,
+    answer: String,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::MixedItemResult, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.submit_mixed_answer(session_id, item_index, &answer).map_err(|e| e.to_string())
+}
+
+/// 获取混合练习小结
+#[tauri::command]
+pub fn get_mixed_session_summary(
+    session_id: i64,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::MixedSessionSummary, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_mixed_session_summary(session_id).map_err(|e| e.to_string())
+}
+
+/// 获取听力提速训练的语速记录，用于查看舒适语速的变化
+#[tauri::command]
+pub fn get_listening_speed_history(
+    user_name: String,
+    limit: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Vec<crate::models::ListeningSpeedRecord>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_listening_speed_history(&user_name, limit.unwrap_or(20))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取速度与正确率分析（`days` 为空时分析全部记录），判断放慢速度是否能提高正确率
+#[tauri::command]
+pub fn get_speed_accuracy_profile(
+    user_name: String,
+    days: Option<i64>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<crate::models::SpeedAccuracyProfile, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_speed_accuracy_profile(&user_name, days).map_err(|e| e.to_string())
+}
+
+/// 获取会话的按键准确率等统计
+#[tauri::command]
+pub fn get_session_stats(session_id: i64, db: State<'_, Mutex<DatabaseManager>>) -> Result<SessionStats, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_practice_session(session_id).map_err(|e| e.to_string())?.ok_or("练习会话不存在")?;
+
+    let keystroke_accuracy = if session.keystrokes > 0 {
+        session.correct_keystrokes as f64 / session.keystrokes as f64 * 100.0
+    } else {
+        0.0
+    };
+    Ok(SessionStats {
+        session_id,
+        keystrokes: session.keystrokes,
+        correct_keystrokes: session.correct_keystrokes,
+        keystroke_accuracy,
+        correct_count: session.correct_count,
+        incorrect_count: session.incorrect_count,
+        answered: session.current_index,
+        total_items: session.items.len() as i32,
+        hints_used: db.session_hints_used(session_id).map_err(|e| e.to_string())?,
+        score: if session.current_index > 0 {
+            session.score_points / session.current_index as f64 * 100.0
+        } else {
+            0.0
+        },
+    })
+}
+
+/// 恢复用户最近一次未完成的练习会话（异常退出后调用）
+#[tauri::command]
+pub fn recover_last_session(user_name: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<Option<PracticeSession>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_last_active_session(&user_name).map_err(|e| e.to_string())
+}
+
+/// 为所有进行中的会话保存检查点（窗口关闭时调用）
+pub fn checkpoint_active_sessions(db: &Mutex<DatabaseManager>) {
+    match db.lock() {
+        Ok(db) => {
+            if let Err(e) = db.checkpoint_active_sessions() {
+                log::warn!("Failed to checkpoint practice sessions: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to lock database for checkpoint: {}", e),
+    }
+}
+
+fn active_session(db: &DatabaseManager, session_id: i64) -> Result<PracticeSession, String> {
+    let session = db.get_practice_session(session_id).map_err(|e| e.to_string())?.ok_or("练习会话不存在")?;
+    if session.status != "active" || session.current_index as usize >= session.items.len() {
+        return Err("练习会话已结束".to_string());
+    }
+    Ok(session)
+}
+
+/// 与上次输入比较，找出新输入的字符并逐个判断是否正确，按错的字符按键盘布局分类
+fn new_keystrokes(session_id: i64, previous: &str, typed: &str, expected: &str, layout: &str) -> Vec<KeystrokeFeedback> {
+    let previous: Vec<char> = previous.chars().collect();
+    let typed: Vec<char> = typed.chars().collect();
+    let expected: Vec<char> = expected.chars().collect();
+
+    // 中间修改或粘贴时，从第一个不同的字符开始算新输入
+    let common = previous.iter().zip(typed.iter()).take_while(|(a, b)| a == b).count();
+    typed[common..]
+        .iter()
+        .enumerate()
+        .map(|(offset, &key)| {
+            let position = common + offset;
+            let correct = expected.get(position).is_some_and(|&c| chars_match(c, key));
+            // 只有字母位置按错才分类；超出答案长度的多余输入不计
+            let error_kind = match expected.get(position) {
+                Some(&e) if !correct && e.is_alphabetic() => Some(keyboard::classify_error(layout, e, key).to_string()),
+                _ => None,
+            };
+            KeystrokeFeedback {
+                session_id,
+                position,
+                key: key.to_string(),
+                correct,
+                error_kind,
+            }
+        })
+        .collect()
+}
+
+/// 第一个与答案不符的位置（大小写不敏感）；仍是答案前缀时返回 None
+fn first_error(typed: &str, expected: &str) -> Option<usize> {
+    let expected: Vec<char> = expected.chars().collect();
+    typed
+        .chars()
+        .enumerate()
+        .find(|(i, c)| !expected.get(*i).is_some_and(|&e| chars_match(e, *c)))
+        .map(|(i, _)| i)
+}
+
+fn chars_match(a: char, b: char) -> bool {
+    a.to_lowercase().eq(b.to_lowercase())
+}
@@ -0,0 +1,124 @@
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::cards::{render_png, ShareCard};
+use crate::database::DatabaseManager;
+
+/// 分享卡片保存在系统临时目录下的子目录
+const CARD_DIR: &str = "spelling-game-cards";
+/// 连续练习的里程碑天数
+const STREAK_MILESTONES: [i32; 7] = [3, 7, 14, 30, 50, 100, 365];
+
+/// 生成分享卡片 PNG，返回临时文件路径，前端可用于分享或打印
+///
+/// `kind`：`wida_result`（`id` 为测试历史 ID）、`weekly_summary`、`streak`（需要 `user_name`）。
+/// 卡片所属用户设置了 PIN 时需要提供 `pin`。
+#[tauri::command]
+pub fn render_share_card(
+    kind: String,
+    id: Option<i64>,
+    user_name: Option<String>,
+    pin: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let card = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        build_card(&db, &kind, id, user_name.as_deref(), pin.as_deref())?
+    };
+    let png = render_png(&card)?;
+
+    let dir = std::env::temp_dir().join(CARD_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}-{}.png", kind, chrono::Local::now().format("%Y%m%d%H%M%S%3f")));
+    std::fs::write(&path, png).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn build_card(
+    db: &DatabaseManager,
+    kind: &str,
+    id: Option<i64>,
+    user_name: Option<&str>,
+    pin: Option<&str>,
+) -> Result<ShareCard, String> {
+    let require_pin = |user_name: &str| db.require_user_pin(user_name, pin).map_err(|e| e.to_string());
+    let require_user = || {
+        let user_name = user_name.ok_or_else(|| format!("{} 卡片需要 user_name", kind))?;
+        require_pin(user_name)?;
+        Ok::<_, String>(user_name)
+    };
+    match kind {
+        "wida_result" => {
+            let id = id.ok_or("wida_result 卡片需要测试历史 id")?;
+            let record = db.get_wida_history_record(id).map_err(|e| e.to_string())?.ok_or("测试记录不存在")?;
+            require_pin(&record.user_name)?;
+            let test_type = crate::i18n::text(Some("en"), &format!("test_type.{}", record.test_type), &[]);
+            Ok(ShareCard {
+                title: format!("WIDA {} result", test_type),
+                headline: format!("Level {}", record.proficiency_level),
+                lines: vec![
+                    ("Proficiency".to_string(), crate::i18n::proficiency_level_name(Some("en"), record.proficiency_level)),
+                    ("Scale score".to_string(), format!("{:.0}", record.score)),
+                    ("Accuracy".to_string(), format!("{:.0}%", record.accuracy)),
+                    ("Correct".to_string(), format!("{} / {}", record.correct_count, record.total_questions)),
+                    ("Grade".to_string(), record.grade_level.clone()),
+                ],
+                footer: format!("{} · {}", record.user_name, record.completed_at),
+                accent: [46, 110, 190],
+            })
+        }
+        "weekly_summary" => {
+            let summary = db.get_weekly_summary(require_user()?).map_err(|e| e.to_string())?;
+            Ok(ShareCard {
+                title: "Weekly summary".to_string(),
+                headline: format!("{} words", summary.words_practiced),
+                lines: vec![
+                    ("Practice sessions".to_string(), summary.practices.to_string()),
+                    ("Average accuracy".to_string(), format!("{:.0}%", summary.avg_accuracy)),
+                    ("Time practiced".to_string(), format!("{} min", summary.duration_seconds / 60)),
+                    ("Words mastered".to_string(), summary.words_mastered.to_string()),
+                    ("Streak".to_string(), format!("{} days", summary.streak_days)),
+                ],
+                footer: format!("{} · {}", summary.user_name, chrono::Local::now().format("%Y-%m-%d")),
+                accent: [52, 150, 100],
+            })
+        }
+        "streak" => {
+            let user_name = require_user()?;
+            let streak = db.get_practice_streak(user_name).map_err(|e| e.to_string())?;
+            let reached = STREAK_MILESTONES.iter().rev().find(|&&m| streak >= m);
+            let next = STREAK_MILESTONES.iter().find(|&&m| streak < m);
+            Ok(ShareCard {
+                title: "Practice streak".to_string(),
+                headline: format!("{}-day streak", streak),
+                lines: vec![
+                    ("Milestone".to_string(), reached.map_or("-".to_string(), |m| format!("{} days", m))),
+                    ("Next milestone".to_string(), next.map_or("-".to_string(), |m| format!("{} days", m))),
+                ],
+                footer: format!("{} · {}", user_name, chrono::Local::now().format("%Y-%m-%d")),
+                accent: [220, 120, 40],
+            })
+        }
+        _ => Err(format!("未知的卡片类型: {}", kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_card_requires_pin() {
+        let db = DatabaseManager::new(":memory:").unwrap();
+        let article_id = db.create_article("Fruit", "apple banana").unwrap();
+        db.save_practice_history("alice", article_id, "word", 3, 1, 60).unwrap();
+        assert!(build_card(&db, "weekly_summary", None, Some("alice"), None).is_ok());
+
+        db.set_user_pin("alice", None, Some("1234")).unwrap();
+        for kind in ["weekly_summary", "streak"] {
+            assert!(build_card(&db, kind, None, Some("alice"), None).is_err());
+            assert!(build_card(&db, kind, None, Some("alice"), Some("0000")).is_err());
+            assert!(build_card(&db, kind, None, Some("alice"), Some("1234")).is_ok());
+        }
+    }
+}
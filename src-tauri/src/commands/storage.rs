@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, DataDirectoryInfo, DataDirectoryMigration};
+use crate::paths;
+
+/// 获取当前数据目录及是否为便携模式
+#[tauri::command]
+pub fn get_data_directory(app: AppHandle) -> Result<DataDirectoryInfo, String> {
+    let default_dir = paths::default_data_dir(&app)?;
+    let portable = paths::is_portable(paths::exe_dir().as_deref());
+    Ok(DataDirectoryInfo {
+        path: paths::app_data_dir(&app)?.to_string_lossy().to_string(),
+        default_path: default_dir.to_string_lossy().to_string(),
+        portable,
+        custom: !portable && paths::custom_data_dir(&default_dir).is_some(),
+    })
+}
+
+/// 把数据迁移到指定目录并切换到新目录，`path` 为空时迁回系统默认目录
+///
+/// 数据库在使用中也能安全复制；旧目录中的数据保留不删，确认无误后可手动清理。
+#[tauri::command]
+pub fn set_data_directory(
+    app: AppHandle,
+    path: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<DataDirectoryMigration, String> {
+    if paths::is_portable(paths::exe_dir().as_deref()) {
+        return Err("便携模式下数据固定保存在程序目录，请先关闭便携模式".to_string());
+    }
+    let default_dir = paths::default_data_dir(&app)?;
+    let target = match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) if Path::new(p).is_absolute() => PathBuf::from(p),
+        Some(_) => return Err("数据目录必须是绝对路径".to_string()),
+        None => default_dir.clone(),
+    };
+    migrate(&app, &db, actor.as_ref(), &target, || {
+        paths::set_custom_data_dir(&default_dir, Some(target.as_path()).filter(|t| *t != default_dir))
+    })
+}
+
+/// 开启或关闭便携模式：开启时数据迁移到程序旁的 `data` 目录（适合每晚还原用户目录的学校电脑）
+#[tauri::command]
+pub fn set_portable_mode(
+    app: AppHandle,
+    enabled: bool,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<DataDirectoryMigration, String> {
+    let exe_dir = paths::exe_dir().ok_or("无法确定程序所在目录")?;
+    if paths::is_portable(Some(&exe_dir)) == enabled {
+        return Err(if enabled { "已经是便携模式" } else { "当前不是便携模式" }.to_string());
+    }
+    let marker = exe_dir.join(paths::PORTABLE_MARKER);
+    if enabled {
+        migrate(&app, &db, actor.as_ref(), &paths::portable_data_dir(&exe_dir), || {
+            std::fs::write(&marker, b"").map_err(|e| format!("无法在程序目录写入便携模式标记: {}", e))
+        })
+    } else {
+        let target = paths::resolve_data_dir(&paths::default_data_dir(&app)?, None);
+        migrate(&app, &db, actor.as_ref(), &target, || std::fs::remove_file(&marker).map_err(|e| e.to_string()))
+    }
+}
+
+/// 复制数据库和其他数据文件到 `target`，`switch` 记录新位置后切换到新数据库
+fn migrate(
+    app: &AppHandle,
+    db: &Mutex<DatabaseManager>,
+    actor: Option<&Actor>,
+    target: &Path,
+    switch: impl FnOnce() -> Result<(), String>,
+) -> Result<DataDirectoryMigration, String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor).map_err(|e| e.to_string())?;
+    let current = paths::app_data_dir(app)?;
+    if target == current {
+        return Err("已经在使用该数据目录".to_string());
+    }
+    if target.starts_with(&current) {
+        return Err("新数据目录不能位于当前数据目录之内".to_string());
+    }
+    std::fs::create_dir_all(target).map_err(|e| format!("无法创建数据目录 {}: {}", target.display(), e))?;
+
+    // 新目录中已有的数据库改名保留，不覆盖
+    let db_path = target.join(paths::DB_FILE);
+    let replaced_database = if db_path.exists() {
+        let backup = format!("{}.{}.bak", paths::DB_FILE, chrono::Local::now().format("%Y%m%d%H%M%S"));
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            let file = target.join(format!("{}{}", paths::DB_FILE, suffix));
+            if file.exists() {
+                std::fs::rename(&file, target.join(format!("{}{}", backup, suffix))).map_err(|e| e.to_string())?;
+            }
+        }
+        Some(target.join(backup).to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    db.backup_to(&db_path).map_err(|e| e.to_string())?;
+    let files_copied = paths::copy_data_files(&current, target)?;
+    let new_db = DatabaseManager::new(&db_path).map_err(|e| e.to_string())?;
+    let with_separator = |dir: &Path| format!("{}{}", dir.to_string_lossy(), std::path::MAIN_SEPARATOR);
+    let paths_updated = new_db
+        .relocate_file_paths(&with_separator(&current), &with_separator(target))
+        .map_err(|e| e.to_string())?;
+    switch()?;
+
+    let from = current.to_string_lossy().to_string();
+    let to = target.to_string_lossy().to_string();
+    let details = format!("{} -> {}", from, to);
+    new_db
+        .record_audit(actor.map(|a| a.user_name.as_str()), "relocate", "data_dir", 0, Some(&details))
+        .map_err(|e| e.to_string())?;
+    *db = new_db;
+    log::info!("Data directory moved: {}", details);
+
+    Ok(DataDirectoryMigration { from, to, files_copied, paths_updated, replaced_database })
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::database::DatabaseManager;
+use crate::models::SessionTick;
+
+/// `session-tick` 事件的发送间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 可以计时的会话：练习会话和 WIDA 测试
+const TIMER_KINDS: [&str; 2] = ["practice", "wida"];
+
+struct Timer {
+    limit: Option<Duration>,
+    elapsed: Duration,              // 暂停前累计的时间
+    resumed_at: Option<Instant>,    // 计时中时为最近一次开始/继续的时间
+}
+
+impl Timer {
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.elapsed + self.resumed_at.map_or(Duration::ZERO, |at| now.saturating_duration_since(at))
+    }
+
+    fn tick(&self, kind: &str, session_id: i64, now: Instant) -> SessionTick {
+        let elapsed = self.elapsed(now);
+        let remaining = self.limit.map(|limit| limit.saturating_sub(elapsed));
+        SessionTick {
+            kind: kind.to_string(),
+            session_id,
+            elapsed_seconds: elapsed.as_secs(),
+            remaining_seconds: remaining.map(|r| r.as_secs()),
+            running: self.resumed_at.is_some(),
+            expired: remaining.is_some_and(|r| r.is_zero()),
+        }
+    }
+}
+
+/// 后端计时器：不依赖 WebView 的定时器，窗口在后台被节流时计时也不会变慢
+#[derive(Default)]
+pub struct SessionTimers {
+    timers: HashMap<(String, i64), Timer>,
+}
+
+impl SessionTimers {
+    /// 开始计时（已有计时器时从零重新开始），`limit` 为限时
+    pub fn start(&mut self, kind: &str, session_id: i64, limit: Option<Duration>, now: Instant) -> SessionTick {
+        let timer = Timer { limit, elapsed: Duration::ZERO, resumed_at: Some(now) };
+        let tick = timer.tick(kind, session_id, now);
+        self.timers.insert((kind.to_string(), session_id), timer);
+        tick
+    }
+
+    pub fn pause(&mut self, kind: &str, session_id: i64, now: Instant) -> Option<SessionTick> {
+        let timer = self.timers.get_mut(&(kind.to_string(), session_id))?;
+        timer.elapsed = timer.elapsed(now);
+        timer.resumed_at = None;
+        Some(timer.tick(kind, session_id, now))
+    }
+
+    pub fn resume(&mut self, kind: &str, session_id: i64, now: Instant) -> Option<SessionTick> {
+        let timer = self.timers.get_mut(&(kind.to_string(), session_id))?;
+        timer.resumed_at.get_or_insert(now);
+        Some(timer.tick(kind, session_id, now))
+    }
+
+    /// 停止并移除计时器，返回最终计时
+    pub fn stop(&mut self, kind: &str, session_id: i64, now: Instant) -> Option<SessionTick> {
+        let timer = self.timers.remove(&(kind.to_string(), session_id))?;
+        Some(timer.tick(kind, session_id, now))
+    }
+
+    pub fn get(&self, kind: &str, session_id: i64, now: Instant) -> Option<SessionTick> {
+        self.timers.get(&(kind.to_string(), session_id)).map(|timer| timer.tick(kind, session_id, now))
+    }
+
+    /// 所有计时中的会话的当前计时；到时的计时器在最后一次（`expired`）之后移除
+    pub fn tick(&mut self, now: Instant) -> Vec<SessionTick> {
+        let mut ticks: Vec<SessionTick> = self
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.resumed_at.is_some())
+            .map(|((kind, session_id), timer)| timer.tick(kind, *session_id, now))
+            .collect();
+        for tick in ticks.iter().filter(|tick| tick.expired) {
+            self.timers.remove(&(tick.kind.clone(), tick.session_id));
+        }
+        ticks.sort_by(|a, b| (&a.kind, a.session_id).cmp(&(&b.kind, b.session_id)));
+        ticks
+    }
+}
+
+/// 启动计时线程，每秒为计时中的会话发出 `session-tick` 事件
+pub fn start_tick_loop(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TICK_INTERVAL);
+        let ticks = {
+            let timers = app.state::<Mutex<SessionTimers>>();
+            let ticks = timers.lock().map(|mut timers| timers.tick(Instant::now())).unwrap_or_default();
+            ticks
+        };
+        for tick in ticks {
+            if let Err(e) = app.emit("session-tick", tick) {
+                log::warn!("Failed to emit session-tick event: {}", e);
+            }
+        }
+    });
+}
+
+fn check_session(db: &DatabaseManager, kind: &str, session_id: i64) -> Result<(), String> {
+    let exists = match kind {
+        "practice" => db.get_practice_session(session_id).map_err(|e| e.to_string())?.is_some(),
+        "wida" => db.get_wida_test_session(session_id).map_err(|e| e.to_string())?.is_some(),
+        _ => return Err(format!("不支持计时的会话类型: {}，可选 {}", kind, TIMER_KINDS.join(" / "))),
+    };
+    if exists {
+        Ok(())
+    } else {
+        Err("会话不存在".to_string())
+    }
+}
+
+/// 开始为练习会话（`practice`）或 WIDA 测试（`wida`）计时，`limit_seconds` 为限时
+#[tauri::command]
+pub fn start_session_timer(
+    kind: String,
+    session_id: i64,
+    limit_seconds: Option<u64>,
+    timers: State<'_, Mutex<SessionTimers>>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<SessionTick, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        check_session(&db, &kind, session_id)?;
+    }
+    let mut timers = timers.lock().map_err(|e| e.to_string())?;
+    Ok(timers.start(&kind, session_id, limit_seconds.map(Duration::from_secs), Instant::now()))
+}
+
+/// 暂停计时
+#[tauri::command]
+pub fn pause_session_timer(kind: String, session_id: i64, timers: State<'_, Mutex<SessionTimers>>) -> Result<SessionTick, String> {
+    let mut timers = timers.lock().map_err(|e| e.to_string())?;
+    timers.pause(&kind, session_id, Instant::now()).ok_or_else(|| "该会话没有计时".to_string())
+}
+
+/// 继续计时
+#[tauri::command]
+pub fn resume_session_timer(kind: String, session_id: i64, timers: State<'_, Mutex<SessionTimers>>) -> Result<SessionTick, String> {
+    let mut timers = timers.lock().map_err(|e| e.to_string())?;
+    timers.resume(&kind, session_id, Instant::now()).ok_or_else(|| "该会话没有计时".to_string())
+}
+
+/// 停止计时，返回最终用时
+#[tauri::command]
+pub fn stop_session_timer(kind: String, session_id: i64, timers: State<'_, Mutex<SessionTimers>>) -> Result<Option<SessionTick>, String> {
+    let mut timers = timers.lock().map_err(|e| e.to_string())?;
+    Ok(timers.stop(&kind, session_id, Instant::now()))
+}
+
+/// 获取当前计时（如窗口重新加载后恢复显示）
+#[tauri::command]
+pub fn get_session_timer(kind: String, session_id: i64, timers: State<'_, Mutex<SessionTimers>>) -> Result<Option<SessionTick>, String> {
+    let timers = timers.lock().map_err(|e| e.to_string())?;
+    Ok(timers.get(&kind, session_id, Instant::now()))
+}
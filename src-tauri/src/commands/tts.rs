@@ -1,52 +1,636 @@
-use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
 
-/// 使用系统 TTS 朗读文本 (macOS)
-#[tauri::command]
-pub async fn speak(text: String, rate: Option<i32>) -> Result<(), String> {
-    let rate = rate.unwrap_or(175); // 默认语速
-    
-    // 使用 spawn_blocking 来执行阻塞的 say 命令
-    tokio::task::spawn_blocking(move || {
-        #[cfg(target_os = "macos")]
-        {
-            let rate_str = rate.to_string();
-            let output = Command::new("say")
-                .arg("-r")
-                .arg(&rate_str)
-                .arg(&text)
-                .output();
-            
-            match output {
-                Ok(o) if o.status.success() => Ok(()),
-                Ok(o) => Err(String::from_utf8_lossy(&o.stderr).to_string()),
-                Err(e) => Err(e.to_string()),
+use crate::database::DatabaseManager;
+
+/// `say` 的默认语速（每分钟单词数）
+const DEFAULT_RATE: i32 = 175;
+
+/// 可朗读的语言：(`lang`, BCP 47 语言标签, macOS `say` 的声音，空为系统默认)
+const TTS_LANGUAGES: [(&str, &str, Option<&str>); 4] = [
+    ("en", "en-US", None),
+    ("zh", "zh-CN", Some("Tingting")),
+    ("es", "es-ES", Some("Monica")),
+    ("fr", "fr-FR", Some("Thomas")),
+];
+
+/// 移动端朗读请求（交给 WebView 的 speechSynthesis 播放）
+///
+/// 移动端没有原生朗读插件，`tts-speak` / `tts-stop` 由前端 `utils/ttsBridge.ts` 监听：
+/// 按 `interrupt` 排队或先 cancel，播放结束、被打断或失败时发出带同一 `id` 的 `tts-complete`。
+#[cfg_attr(not(mobile), allow(dead_code))]
+#[derive(Debug, Clone, serde::Serialize)]
+struct MobileSpeakPayload {
+    text: String,
+    rate: f32, // speechSynthesis 语速倍率，1.0 = 默认语速
+    lang: String, // BCP 47 语言标签
+    interrupt: &'static str, // 打断策略，由 WebView 决定排队还是先 cancel
+    id: u64, // 播放编号，WebView 播放结束后发出带此编号的 `tts-complete`
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+impl MobileSpeakPayload {
+    /// `rate` 为每分钟单词数，换算成相对默认语速的倍率
+    fn new(text: String, rate: i32, language: TtsLanguage, policy: InterruptPolicy, id: u64) -> Self {
+        Self { text, rate: rate as f32 / DEFAULT_RATE as f32, lang: language.tag.to_string(), interrupt: policy.name(), id }
+    }
+}
+
+/// `tts-complete` 事件（移动端由 WebView 发出同样格式的事件）
+#[cfg(all(not(mobile), target_os = "macos"))]
+#[derive(Debug, Clone, serde::Serialize)]
+struct TtsComplete {
+    id: u64,
+    completed: bool, // 为 false 表示排队时被替换、播放中被打断或停止，或播放失败
+    error: Option<String>,
+}
+
+/// 新的朗读到来时如何处理正在播放和排队的朗读
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterruptPolicy {
+    Queue,   // 排在后面依次播放（默认）
+    Replace, // 停止当前朗读并清空队列，立即播放
+    Ignore,  // 正在朗读或有排队时忽略
+}
+
+impl InterruptPolicy {
+    /// 解析 `queue` / `replace` / `ignore`，为空时排队
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.unwrap_or("queue") {
+            "queue" => Ok(Self::Queue),
+            "replace" => Ok(Self::Replace),
+            "ignore" => Ok(Self::Ignore),
+            other => Err(format!("不支持的打断策略: {}，可选 queue / replace / ignore", other)),
+        }
+    }
+
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    fn name(self) -> &'static str {
+        match self {
+            Self::Queue => "queue",
+            Self::Replace => "replace",
+            Self::Ignore => "ignore",
+        }
+    }
+}
+
+/// 朗读服务：同一时间只播放一段，新的朗读按打断策略排队、替换当前朗读或被忽略
+///
+/// 桌面端由这里管理 `say` / `afplay` 进程，替换和停止时只结束自己启动的进程。
+#[derive(Default)]
+#[cfg_attr(any(mobile, not(target_os = "macos")), allow(dead_code))]
+pub struct TtsService {
+    state: Mutex<TtsState>,
+    changed: std::sync::Condvar,
+}
+
+#[derive(Default)]
+#[cfg_attr(any(mobile, not(target_os = "macos")), allow(dead_code))]
+struct TtsState {
+    next_id: u64,
+    current: Option<(u64, String)>,
+    queue: std::collections::VecDeque<(u64, String)>,
+    process: Option<(u64, std::process::Child)>,
+}
+
+impl TtsState {
+    #[cfg_attr(any(mobile, not(target_os = "macos")), allow(dead_code))]
+    fn is_playing(&self, id: u64) -> bool {
+        self.current.as_ref().is_some_and(|(current, _)| *current == id)
+    }
+}
+
+#[cfg_attr(any(mobile, not(target_os = "macos")), allow(dead_code))]
+impl TtsService {
+    /// 按策略排队，轮到时调用 `play` 播放（阻塞到播放结束）；被忽略、被替换或停止时返回 false
+    pub fn run(
+        &self,
+        text: &str,
+        policy: InterruptPolicy,
+        play: impl FnOnce(&TtsPlayback) -> Result<(), String>,
+    ) -> Result<bool, String> {
+        match self.submit(text, policy)? {
+            Some(id) => self.play(id, play),
+            None => Ok(false),
+        }
+    }
+
+    /// 按策略加入队列，返回播放编号；被忽略时为空
+    pub fn submit(&self, text: &str, policy: InterruptPolicy) -> Result<Option<u64>, String> {
+        let mut state = self.lock()?;
+        match policy {
+            InterruptPolicy::Ignore if state.current.is_some() || !state.queue.is_empty() => return Ok(None),
+            InterruptPolicy::Replace => Self::clear(&mut state),
+            _ => {}
+        }
+        let id = Self::next_id(&mut state);
+        state.queue.push_back((id, text.to_string()));
+        self.changed.notify_all();
+        Ok(Some(id))
+    }
+
+    /// 分配一个不排队的播放编号（移动端由 WebView 排队播放）
+    pub fn reserve_id(&self) -> Result<u64, String> {
+        let mut state = self.lock()?;
+        Ok(Self::next_id(&mut state))
+    }
+
+    /// 等待已加入队列的朗读轮到后调用 `play` 播放（阻塞到播放结束）；排队或播放时被替换、停止返回 false
+    pub fn play(&self, id: u64, play: impl FnOnce(&TtsPlayback) -> Result<(), String>) -> Result<bool, String> {
+        let mut state = self.lock()?;
+        loop {
+            if !state.queue.iter().any(|(queued, _)| *queued == id) {
+                return Ok(false);
+            }
+            if state.current.is_none() && state.queue.front().is_some_and(|(queued, _)| *queued == id) {
+                state.current = state.queue.pop_front();
+                break;
             }
+            state = self.changed.wait(state).map_err(|e| e.to_string())?;
         }
-        
-        #[cfg(not(target_os = "macos"))]
+        drop(state);
+
+        let result = play(&TtsPlayback { service: self, id });
+        let mut state = self.lock()?;
+        let finished = state.is_playing(id);
+        if finished {
+            state.current = None;
+        }
+        self.changed.notify_all();
+        result.map(|_| finished)
+    }
+
+    /// 停止当前朗读并清空队列
+    pub fn stop(&self) -> Result<(), String> {
+        let mut state = self.lock()?;
+        Self::clear(&mut state);
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// 当前朗读状态
+    pub fn status(&self) -> Result<crate::models::TtsStatus, String> {
+        let state = self.lock()?;
+        Ok(crate::models::TtsStatus {
+            speaking: state.current.is_some(),
+            current_text: state.current.as_ref().map(|(_, text)| text.clone()),
+            queued: state.queue.len() as i32,
+        })
+    }
+
+    fn next_id(state: &mut TtsState) -> u64 {
+        state.next_id += 1;
+        state.next_id
+    }
+
+    fn clear(state: &mut TtsState) {
+        state.queue.clear();
+        state.current = None;
+        if let Some((_, mut child)) = state.process.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, TtsState>, String> {
+        self.state.lock().map_err(|e| e.to_string())
+    }
+}
+
+/// 正在播放的一段朗读，用于启动可被替换和停止的播放进程
+pub struct TtsPlayback<'a> {
+    service: &'a TtsService,
+    id: u64,
+}
+
+#[cfg_attr(any(mobile, not(target_os = "macos")), allow(dead_code))]
+impl TtsPlayback<'_> {
+    /// 朗读是否已被替换或停止
+    pub fn cancelled(&self) -> bool {
+        self.service.lock().map_or(true, |state| !state.is_playing(self.id))
+    }
+
+    /// 启动进程并等待结束；朗读被替换或停止时进程被结束，返回 Ok
+    fn run_command(&self, command: &mut std::process::Command) -> Result<(), String> {
+        use std::io::Read;
+
+        let mut child = command
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
         {
-            // Windows/Linux 使用不同的 TTS 方案
-            Err("TTS not implemented for this platform".to_string())
+            let mut state = self.service.lock()?;
+            if !state.is_playing(self.id) {
+                // 启动期间已被替换或停止：只结束这个进程，不动新朗读的状态
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(());
+            }
+            state.process = Some((self.id, child));
         }
-    })
-    .await
-    .map_err(|e| e.to_string())?
+        loop {
+            {
+                let mut state = self.service.lock()?;
+                let Some((_, child)) = state.process.as_mut().filter(|(id, _)| *id == self.id) else {
+                    return Ok(()); // 被替换或停止
+                };
+                if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+                    let mut stderr = String::new();
+                    if let Some(mut pipe) = child.stderr.take() {
+                        let _ = pipe.read_to_string(&mut stderr);
+                    }
+                    state.process = None;
+                    return if status.success() { Ok(()) } else { Err(stderr) };
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
 }
 
-/// 停止朗读
+/// 使用系统 TTS 朗读文本
+///
+/// - macOS：调用 `say`
+/// - iOS/Android：发出 `tts-speak` 事件，由 WebView 的 speechSynthesis 调用系统引擎
+///   （AVSpeechSynthesizer / Android TextToSpeech）
+///
+/// 传入 `user_name` 时语速不超过该用户无障碍设置中的上限。
+/// `lang` 为 `en`（默认）、`zh`、`es` 或 `fr`，朗读中文释义或西班牙语、法语单词时切换声音。
+/// `interrupt` 为 `queue`（默认，排在正在朗读的内容之后）、`replace`（打断当前朗读）或 `ignore`（正在朗读时忽略）。
+///
+/// 不等待朗读结束，立即返回播放编号（被忽略时为空）；播放结束、被打断或失败后发出 `tts-complete` 事件。
 #[tauri::command]
-pub fn stop_speaking() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
+pub async fn speak(
+    app: AppHandle,
+    text: String,
+    rate: Option<i32>,
+    user_name: Option<String>,
+    lang: Option<String>,
+    interrupt: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<Option<u64>, String> {
+    let policy = InterruptPolicy::parse(interrupt.as_deref())?;
+    // 未指定语言时按文本检测，例如中文释义用中文声音朗读
+    let lang = lang.or_else(|| {
+        crate::text::language::detect_language(&text)
+            .filter(|l| tts_language(Some(l)).is_ok())
+            .map(str::to_string)
+    });
+    let language = tts_language(lang.as_deref())?;
+    let rate = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        accommodated_rate(&db, user_name.as_deref(), rate)?
+    };
+    speak_text(app, text, rate, language, policy)
+}
+
+/// 朗读分词的部分发音作为提示，返回实际朗读的文本（不等待朗读结束）
+///
+/// `hint_level`：1 = 第一个音节，2 = 前一半，3 = 完整发音。`interrupt` 同 [`speak`]。
+#[tauri::command]
+pub async fn speak_hint(
+    app: AppHandle,
+    segment_id: i64,
+    hint_level: i32,
+    rate: Option<i32>,
+    user_name: Option<String>,
+    interrupt: Option<String>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<String, String> {
+    let policy = InterruptPolicy::parse(interrupt.as_deref())?;
+    let (segment, language, rate) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let segment = db.get_segment(segment_id).map_err(|e| e.to_string())?.ok_or("分词不存在")?;
+        let language = segment.language.clone().filter(|l| tts_language(Some(l)).is_ok());
+        (segment, language, accommodated_rate(&db, user_name.as_deref(), rate)?)
+    };
+
+    // 按分词的语言选择朗读声音
+    let hint = crate::text::hint_text(&segment.content, hint_level);
+    speak_text(app, hint.clone(), rate, tts_language(language.as_deref())?, policy)?;
+    Ok(hint)
+}
+
+/// 按用户无障碍设置中的语速上限调整语速
+pub(crate) fn accommodated_rate(db: &DatabaseManager, user_name: Option<&str>, rate: Option<i32>) -> Result<Option<i32>, String> {
+    let Some(user_name) = user_name else {
+        return Ok(rate);
+    };
+    let accommodations = db.get_accommodations(user_name).map_err(|e| e.to_string())?;
+    Ok(match accommodations.max_tts_rate {
+        Some(max_rate) => Some(rate.unwrap_or(DEFAULT_RATE).min(max_rate)),
+        None => rate,
+    })
+}
+
+/// 朗读语言：BCP 47 语言标签和 macOS 声音
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TtsLanguage {
+    pub tag: &'static str,
+    pub voice: Option<&'static str>,
+}
+
+/// 解析请求中的 `lang`（`zh`、`zh-CN`、`en_US` 等），为空时朗读英文
+pub(crate) fn tts_language(lang: Option<&str>) -> Result<TtsLanguage, String> {
+    let code = lang.unwrap_or("en").split(['-', '_']).next().unwrap_or_default().trim().to_lowercase();
+    TTS_LANGUAGES
+        .iter()
+        .find(|(name, _, _)| *name == code)
+        .map(|(_, tag, voice)| TtsLanguage { tag, voice: *voice })
+        .ok_or_else(|| {
+            let names: Vec<&str> = TTS_LANGUAGES.iter().map(|(name, _, _)| *name).collect();
+            format!("不支持朗读的语言: {}，可选 {}", lang.unwrap_or_default(), names.join(" / "))
+        })
+}
+
+/// 开始朗读，返回播放编号；被 `ignore` 策略忽略时为空
+///
+/// 桌面端在后台线程等待 `say` 结束后发出 `tts-complete`。
+fn speak_text(
+    app: AppHandle,
+    text: String,
+    rate: Option<i32>,
+    language: TtsLanguage,
+    policy: InterruptPolicy,
+) -> Result<Option<u64>, String> {
+    let rate = rate.unwrap_or(DEFAULT_RATE); // 默认语速
+
+    #[cfg(mobile)]
     {
-        Command::new("killall")
-            .arg("say")
-            .spawn()
+        use tauri::Emitter;
+
+        let id = tts_service(&app).reserve_id()?;
+        app.emit("tts-speak", MobileSpeakPayload::new(text, rate, language, policy, id)).map_err(|e| e.to_string())?;
+        Ok(Some(id))
+    }
+
+    #[cfg(all(not(mobile), target_os = "macos"))]
+    {
+        use tauri::Emitter;
+
+        let service = tts_service(&app);
+        let Some(id) = service.submit(&text, policy)? else {
+            return Ok(None);
+        };
+        // 在后台线程中等待排队和阻塞的 say 命令
+        tokio::task::spawn_blocking(move || {
+            let result = service.play(id, |playback| playback.run_command(&mut say_command(&text, rate, language)));
+            let payload = TtsComplete { id, completed: result == Ok(true), error: result.err() };
+            let _ = app.emit("tts-complete", payload);
+        });
+        Ok(Some(id))
+    }
+
+    #[cfg(all(not(mobile), not(target_os = "macos")))]
+    {
+        let _ = (app, text, rate, language, policy);
+        // Windows/Linux 使用不同的 TTS 方案
+        Err("TTS not implemented for this platform".to_string())
+    }
+}
+
+/// 排队朗读并等待朗读结束，用于后台线程中连续朗读（移动端交给 WebView 后立即返回）
+pub(crate) fn speak_and_wait(app: &AppHandle, text: &str, rate: Option<i32>, language: TtsLanguage) -> Result<(), String> {
+    #[cfg(any(mobile, not(target_os = "macos")))]
+    {
+        speak_text(app.clone(), text.to_string(), rate, language, InterruptPolicy::Queue).map(|_| ())
+    }
+
+    #[cfg(all(not(mobile), target_os = "macos"))]
+    {
+        let rate = rate.unwrap_or(DEFAULT_RATE);
+        tts_service(app)
+            .run(text, InterruptPolicy::Queue, |playback| playback.run_command(&mut say_command(text, rate, language)))
             .map(|_| ())
-            .map_err(|e| e.to_string())
+    }
+}
+
+/// macOS `say` 朗读命令
+#[cfg(all(not(mobile), target_os = "macos"))]
+fn say_command(text: &str, rate: i32, language: TtsLanguage) -> std::process::Command {
+    let mut command = std::process::Command::new("say");
+    if let Some(voice) = language.voice {
+        command.arg("-v").arg(voice);
+    }
+    command.arg("-r").arg(rate.to_string()).arg(text);
+    command
+}
+
+/// 朗读并缓存合成的音频，同一段文本再次播放时不必重新合成（用于题目音频等会反复播放的长文本）
+///
+/// macOS 上用 `say -o` 合成到缓存目录再用 `afplay` 播放；移动端交给 WebView 朗读。
+pub(crate) async fn speak_cached(app: AppHandle, text: String, rate: Option<i32>) -> Result<(), String> {
+    #[cfg(any(mobile, not(target_os = "macos")))]
+    {
+        speak_text(app, text, rate, tts_language(None)?, InterruptPolicy::Queue).map(|_| ())
+    }
+
+    #[cfg(all(not(mobile), target_os = "macos"))]
+    {
+        use std::process::Command;
+
+        let rate = rate.unwrap_or(DEFAULT_RATE);
+        let path = audio_cache_dir(&app)?.join(audio_cache_name(&text, Some(rate)));
+        let service = tts_service(&app);
+        tokio::task::spawn_blocking(move || {
+            if !path.exists() {
+                let output = Command::new("say")
+                    .arg("-r")
+                    .arg(rate.to_string())
+                    .arg("-o")
+                    .arg(&path)
+                    .arg(&text)
+                    .output()
+                    .map_err(|e| e.to_string())?;
+                if !output.status.success() {
+                    return Err(String::from_utf8_lossy(&output.stderr).to_string());
+                }
+            }
+            // 合成在排队之外进行，播放时才排队
+            service
+                .run(&text, InterruptPolicy::Queue, |playback| playback.run_command(Command::new("afplay").arg(&path)))
+                .map(|_| ())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+/// 朗读音频缓存目录（不存在时自动创建）
+pub(crate) fn audio_cache_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    use tauri::Manager;
+
+    let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?.join("tts");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// 缓存音频的文件名，`rate` 为空时为默认语速
+pub(crate) fn audio_cache_name(text: &str, rate: Option<i32>) -> String {
+    format!("{:016x}.aiff", cache_key(text, rate.unwrap_or(DEFAULT_RATE)))
+}
+
+/// 缓存文件名：文本和语速的 FNV-1a 哈希，不同版本间保持稳定
+fn cache_key(text: &str, rate: i32) -> u64 {
+    format!("{}:{}", rate, text)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// 应用管理的朗读服务
+#[cfg_attr(all(not(mobile), not(target_os = "macos")), allow(dead_code))]
+fn tts_service(app: &AppHandle) -> std::sync::Arc<TtsService> {
+    use tauri::Manager;
+
+    app.state::<std::sync::Arc<TtsService>>().inner().clone()
+}
+
+/// 停止朗读并清空排队的朗读（移动端发出 `tts-stop`，由 WebView 取消播放）
+#[tauri::command]
+pub fn stop_speaking(app: AppHandle) -> Result<(), String> {
+    #[cfg(mobile)]
+    {
+        use tauri::Emitter;
+
+        app.emit("tts-stop", ()).map_err(|e| e.to_string())
+    }
+
+    #[cfg(all(not(mobile), target_os = "macos"))]
+    {
+        tts_service(&app).stop()
     }
     
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(all(not(mobile), not(target_os = "macos")))]
     {
+        let _ = app;
         Ok(())
     }
 }
+
+/// 朗读状态：是否正在朗读、当前文本和排队数量（移动端由 WebView 播放，这里只反映桌面端）
+#[tauri::command]
+pub fn get_tts_status(service: State<'_, std::sync::Arc<TtsService>>) -> Result<crate::models::TtsStatus, String> {
+    service.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// 朗读服务：依次排队，ignore 在朗读时被忽略，replace 打断当前朗读并清空队列
+    #[test]
+    fn test_tts_service_interrupt_policy() {
+        let service = Arc::new(TtsService::default());
+        let played = Arc::new(Mutex::new(Vec::new()));
+        let wait_until = |check: &dyn Fn() -> bool| {
+            for _ in 0..500 {
+                if check() {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            panic!("朗读服务状态未按预期变化");
+        };
+
+        assert!(InterruptPolicy::parse(Some("shout")).is_err());
+        assert_eq!(InterruptPolicy::parse(None).unwrap(), InterruptPolicy::Queue);
+
+        // 第一段一直播放到被打断
+        let first = {
+            let (service, played) = (service.clone(), played.clone());
+            std::thread::spawn(move || {
+                service.run("first", InterruptPolicy::Queue, |playback| {
+                    played.lock().unwrap().push("first");
+                    while !playback.cancelled() {
+                        std::thread::sleep(std::time::Duration::from_millis(5));
+                    }
+                    Ok(())
+                })
+            })
+        };
+        wait_until(&|| !played.lock().unwrap().is_empty());
+        assert_eq!(service.status().unwrap().current_text.as_deref(), Some("first"));
+
+        let second = {
+            let (service, played) = (service.clone(), played.clone());
+            std::thread::spawn(move || {
+                service.run("second", InterruptPolicy::Queue, |_| {
+                    played.lock().unwrap().push("second");
+                    Ok(())
+                })
+            })
+        };
+        wait_until(&|| service.status().unwrap().queued == 1);
+        let status = service.status().unwrap();
+        assert!(status.speaking);
+
+        // 正在朗读时 ignore 不播放
+        assert!(!service.run("ignored", InterruptPolicy::Ignore, |_| panic!("不应播放")).unwrap());
+
+        // replace 清空队列并打断第一段
+        let urgent = service
+            .run("urgent", InterruptPolicy::Replace, |_| {
+                played.lock().unwrap().push("urgent");
+                Ok(())
+            })
+            .unwrap();
+        assert!(urgent);
+        assert!(!first.join().unwrap().unwrap());
+        assert!(!second.join().unwrap().unwrap());
+        assert_eq!(*played.lock().unwrap(), vec!["first", "urgent"]);
+
+        let status = service.status().unwrap();
+        assert!(!status.speaking);
+        assert_eq!(status.queued, 0);
+
+        // 空闲时 ignore 正常播放，播放失败的错误原样返回
+        assert!(service.run("idle", InterruptPolicy::Ignore, |_| Ok(())).unwrap());
+        assert_eq!(service.run("broken", InterruptPolicy::Queue, |_| Err("say 失败".to_string())), Err("say 失败".to_string()));
+    }
+
+    /// 移动端 `tts-speak` 事件的格式是与 WebView 的约定
+    #[test]
+    fn test_mobile_speak_payload() {
+        let payload = MobileSpeakPayload::new("Hola".to_string(), 350, tts_language(Some("es-MX")).unwrap(), InterruptPolicy::Replace, 7);
+        assert_eq!(
+            serde_json::to_value(payload).unwrap(),
+            serde_json::json!({ "text": "Hola", "rate": 2.0, "lang": "es-ES", "interrupt": "replace", "id": 7 })
+        );
+        assert!(tts_language(Some("de")).is_err());
+    }
+
+    /// 进程启动前朗读已被替换：只结束这个进程，新排队的朗读不受影响
+    #[cfg(unix)]
+    #[test]
+    fn test_replaced_playback_keeps_new_queue() {
+        let service = TtsService::default();
+        let started = std::time::Instant::now();
+        let finished = service
+            .run("old", InterruptPolicy::Queue, |playback| {
+                service.submit("new", InterruptPolicy::Replace)?;
+                playback.run_command(std::process::Command::new("sleep").arg("5"))
+            })
+            .unwrap();
+        assert!(!finished);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let status = service.status().unwrap();
+        assert!(!status.speaking);
+        assert_eq!(status.queued, 1);
+        assert!(service.lock().unwrap().process.is_none());
+    }
+
+    /// `lang` 切换到中文声音
+    #[test]
+    fn test_tts_language() {
+        let english = tts_language(None).unwrap();
+        assert_eq!((english.tag, english.voice), ("en-US", None));
+        assert_eq!(tts_language(Some("en_GB")).unwrap(), english);
+        for lang in ["zh", "zh-CN", "ZH_cn"] {
+            let chinese = tts_language(Some(lang)).unwrap();
+            assert_eq!((chinese.tag, chinese.voice), ("zh-CN", Some("Tingting")));
+        }
+        assert!(tts_language(Some("de")).unwrap_err().contains("en / zh"));
+    }
+}
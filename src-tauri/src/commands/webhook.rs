@@ -0,0 +1,326 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::database::DatabaseManager;
+use crate::models::{Actor, Assignment};
+
+/// 可订阅的里程碑事件
+pub const WEBHOOK_EVENTS: [&str; 3] = ["test_completed", "streak_milestone", "assignment_completed"];
+/// 连续练习天数达到这些值时发出 `streak_milestone`
+pub const STREAK_MILESTONES: [i32; 8] = [3, 7, 14, 30, 50, 100, 200, 365];
+/// 单次投递的超时时间（秒）
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+/// 加载设置时代替已保存的签名密钥返回给前端，保存时原样传回表示不修改
+pub const SECRET_PLACEHOLDER: &str = "********";
+
+/// Webhook 设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+/// 一个接收地址，例如老师的 LMS 或 IFTTT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,     // 设置后请求带 HMAC-SHA256 签名
+    #[serde(default)]
+    pub events: Vec<String>,        // 为空时订阅全部事件
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Webhook {
+    pub fn accepts(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+
+    /// 密钥换成占位符，不把已保存的密钥返回给前端
+    fn redacted(mut self) -> Self {
+        if self.secret.as_deref().is_some_and(|s| !s.is_empty()) {
+            self.secret = Some(SECRET_PLACEHOLDER.to_string());
+        }
+        self
+    }
+
+    /// 密钥为占位符时沿用已保存的同一地址的密钥，地址改了则需要重新填写
+    fn keep_secret(mut self, saved: &WebhookSettings) -> Result<Self, String> {
+        if self.secret.as_deref() == Some(SECRET_PLACEHOLDER) {
+            self.secret = saved
+                .webhooks
+                .iter()
+                .find(|w| w.url.trim() == self.url.trim())
+                .and_then(|w| w.secret.clone())
+                .ok_or_else(|| format!("Webhook 地址已修改，请重新填写签名密钥: {}", self.url.trim()))
+                .map(Some)?;
+        }
+        Ok(self)
+    }
+}
+
+/// 发给 webhook 的事件（请求体）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event: String,
+    pub user_name: String,
+    pub occurred_at: String,
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event: &str, user_name: &str, data: serde_json::Value) -> Self {
+        Self {
+            event: event.to_string(),
+            user_name: user_name.to_string(),
+            occurred_at: chrono::Utc::now().to_rfc3339(),
+            data,
+        }
+    }
+}
+
+/// 连续天数从 `before` 增加到 `after` 并恰好达到里程碑时返回该天数
+pub fn streak_milestone(before: i32, after: i32) -> Option<i32> {
+    (after > before && STREAK_MILESTONES.contains(&after)).then_some(after)
+}
+
+/// 保存练习历史前记下的状态，保存后据此生成里程碑事件（完成作业、连续练习天数）
+///
+/// 手动保存练习历史和练习会话结束都会写入历史，两条路径发出相同的事件。
+pub struct PracticeMilestones {
+    user_name: String,
+    streak_before: i32,
+    assignments: Vec<Assignment>,
+}
+
+impl PracticeMilestones {
+    /// 在写入练习历史之前调用：记下连续天数和这次练习会完成的作业
+    pub fn before(db: &DatabaseManager, user_name: &str, article_id: i64, segment_type: &str) -> Result<Self, String> {
+        let streak_before = db.get_practice_streak(user_name).map_err(|e| e.to_string())?;
+        let assignments = db
+            .get_assignments(Some(user_name), Some("pending"))
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|a| a.article_id == article_id && a.segment_type == segment_type)
+            .collect();
+        Ok(Self { user_name: user_name.to_string(), streak_before, assignments })
+    }
+
+    /// 在写入练习历史之后调用，返回要发出的事件
+    pub fn events(self, db: &DatabaseManager) -> Result<Vec<WebhookEvent>, String> {
+        let mut events: Vec<WebhookEvent> = self
+            .assignments
+            .iter()
+            .map(|a| WebhookEvent::new("assignment_completed", &a.user_name, serde_json::json!(a)))
+            .collect();
+        let streak = db.get_practice_streak(&self.user_name).map_err(|e| e.to_string())?;
+        if let Some(days) = streak_milestone(self.streak_before, streak) {
+            events.push(WebhookEvent::new("streak_milestone", &self.user_name, serde_json::json!({ "streak_days": days })));
+        }
+        Ok(events)
+    }
+}
+
+/// 请求体的 HMAC-SHA256 签名（十六进制），放在 `X-Spelling-Game-Signature: sha256=...` 中
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    ring::hmac::sign(&key, body).as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 保存 webhook 设置
+#[tauri::command]
+pub fn save_webhook_settings(
+    app: AppHandle,
+    settings: WebhookSettings,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<(), String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    for webhook in &settings.webhooks {
+        let url = webhook.url.trim();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("Webhook 地址必须以 http:// 或 https:// 开头: {}", url));
+        }
+        if let Some(event) = webhook.events.iter().find(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+            return Err(format!("未知事件: {}，可选 {}", event, WEBHOOK_EVENTS.join(" / ")));
+        }
+    }
+    let saved = read_webhook_settings(&app)?;
+    let settings = WebhookSettings {
+        webhooks: settings.webhooks.into_iter().map(|w| w.keep_secret(&saved)).collect::<Result<_, _>>()?,
+    };
+    let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let config_path = crate::paths::app_data_file(&app, "webhook_settings.json")?;
+    std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())
+}
+
+/// 加载 webhook 设置（密钥以占位符代替）
+#[tauri::command]
+pub fn load_webhook_settings(app: AppHandle) -> Result<WebhookSettings, String> {
+    let settings = read_webhook_settings(&app)?;
+    Ok(WebhookSettings { webhooks: settings.webhooks.into_iter().map(Webhook::redacted).collect() })
+}
+
+/// 向指定地址发送一条测试事件，返回服务器的状态码；需要老师或管理员身份
+#[tauri::command]
+pub async fn send_test_webhook(
+    app: AppHandle,
+    webhook: Webhook,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<u16, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    let webhook = webhook.keep_secret(&read_webhook_settings(&app)?)?;
+    let event = WebhookEvent::new("test", "", serde_json::json!({ "message": "Spelling Game webhook test" }));
+    deliver(&webhook, &event).await
+}
+
+fn read_webhook_settings(app: &AppHandle) -> Result<WebhookSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "webhook_settings.json")?;
+    if !config_path.exists() {
+        return Ok(WebhookSettings::default());
+    }
+    let settings_json = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&settings_json).map_err(|e| e.to_string())
+}
+
+/// 在后台把事件发给订阅了它的 webhook，失败只记录日志，不影响练习
+pub fn notify(app: &AppHandle, events: Vec<WebhookEvent>) {
+    if events.is_empty() {
+        return;
+    }
+    let settings = match read_webhook_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => return log::warn!("Failed to read webhook settings: {}", e),
+    };
+    for event in events {
+        for webhook in settings.webhooks.iter().filter(|w| w.accepts(&event.event)).cloned() {
+            let event = event.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = deliver(&webhook, &event).await {
+                    log::warn!("Webhook {} for {} failed: {}", webhook.url, event.event, e);
+                }
+            });
+        }
+    }
+}
+
+async fn deliver(webhook: &Webhook, event: &WebhookEvent) -> Result<u16, String> {
+    let body = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .user_agent(concat!("SpellingGame/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client
+        .post(webhook.url.trim())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Spelling-Game-Event", &event.event);
+    if let Some(secret) = webhook.secret.as_deref().filter(|s| !s.is_empty()) {
+        request = request.header("X-Spelling-Game-Signature", format!("sha256={}", sign_payload(secret, &body)));
+    }
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Server error: {}", e))?;
+    Ok(response.status().as_u16())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 事件订阅、连续天数里程碑和请求签名
+    #[test]
+    fn test_webhook_milestones() {
+        let settings: WebhookSettings = serde_json::from_value(serde_json::json!({
+            "webhooks": [
+                { "url": "https://lms.example/hook", "secret": "s3cret", "events": ["test_completed"] },
+                { "url": "https://maker.ifttt.com/trigger/x" },
+                { "url": "https://off.example/hook", "enabled": false }
+            ]
+        }))
+        .unwrap();
+        let subscribed = |event: &str| settings.webhooks.iter().filter(|w| w.accepts(event)).map(|w| w.url.as_str()).collect::<Vec<_>>();
+        assert_eq!(subscribed("test_completed"), vec!["https://lms.example/hook", "https://maker.ifttt.com/trigger/x"]);
+        assert_eq!(subscribed("streak_milestone"), vec!["https://maker.ifttt.com/trigger/x"]);
+        let all_events = Webhook { url: String::new(), secret: None, events: vec![], enabled: true };
+        assert!(all_events.accepts("assignment_completed"));
+
+        // 只在刚好达到里程碑的那一天触发
+        assert_eq!(streak_milestone(6, 7), Some(7));
+        assert_eq!(streak_milestone(7, 7), None);
+        assert_eq!(streak_milestone(7, 8), None);
+        assert_eq!(streak_milestone(0, 1), None);
+
+        // RFC 4231 测试向量 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    fn webhook(url: &str, secret: Option<&str>) -> Webhook {
+        Webhook { url: url.to_string(), secret: secret.map(str::to_string), events: vec![], enabled: true }
+    }
+
+    /// 加载时隐藏密钥，保存时占位符沿用同一地址已保存的密钥
+    #[test]
+    fn test_webhook_secret_redaction() {
+        let saved = WebhookSettings { webhooks: vec![webhook("https://lms.example/hook", Some("s3cret"))] };
+        assert_eq!(saved.webhooks[0].clone().redacted().secret.as_deref(), Some(SECRET_PLACEHOLDER));
+        assert_eq!(webhook("https://lms.example/hook", None).redacted().secret, None);
+
+        let kept = webhook(" https://lms.example/hook ", Some(SECRET_PLACEHOLDER)).keep_secret(&saved).unwrap();
+        assert_eq!(kept.secret.as_deref(), Some("s3cret"));
+        let replaced = webhook("https://lms.example/hook", Some("new")).keep_secret(&saved).unwrap();
+        assert_eq!(replaced.secret.as_deref(), Some("new"));
+        assert_eq!(webhook("https://lms.example/hook", None).keep_secret(&saved).unwrap().secret, None);
+        // 改了地址不能沿用旧地址的密钥
+        assert!(webhook("https://other.example/hook", Some(SECRET_PLACEHOLDER)).keep_secret(&saved).is_err());
+    }
+
+    /// 练习历史写入后：完成的作业和刚达到的连续天数里程碑都生成事件
+    #[test]
+    fn test_practice_milestones() {
+        let db = DatabaseManager::new(":memory:").unwrap();
+        let article_id = db.create_article("Fruit", "apple").unwrap();
+        db.create_assignment(&crate::models::CreateAssignmentRequest {
+            user_name: "alice".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            title: "Fruit words".to_string(),
+            due_date: None,
+            check_options: None,
+        })
+        .unwrap();
+
+        let milestones = PracticeMilestones::before(&db, "alice", article_id, "word").unwrap();
+        db.save_practice_history("alice", article_id, "word", 5, 0, 60).unwrap();
+        let events = milestones.events(&db).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "assignment_completed");
+        assert_eq!(events[0].data["title"], "Fruit words");
+
+        // 作业已完成，同一天再练不再发事件
+        let milestones = PracticeMilestones::before(&db, "alice", article_id, "word").unwrap();
+        db.save_practice_history("alice", article_id, "word", 5, 0, 60).unwrap();
+        assert!(milestones.events(&db).unwrap().is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use crate::database::DatabaseManager;
 use crate::models::*;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use crate::prompts::offline;
 
 // ========== 题库管理 ==========
 
@@ -72,6 +73,69 @@ pub fn start_wida_test(
         .map_err(|e| e.to_string())
 }
 
+/// 创建测试蓝图
+#[tauri::command]
+pub fn create_wida_blueprint(
+    db: State<'_, Mutex<DatabaseManager>>,
+    name: String,
+    test_type: String,
+    sections: Vec<BlueprintSection>,
+    actor: Option<Actor>,
+) -> Result<i64, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.create_wida_blueprint(&name, &test_type, &sections)
+        .map_err(|e| e.to_string())
+}
+
+/// 修改测试蓝图
+#[tauri::command]
+pub fn update_wida_blueprint(
+    db: State<'_, Mutex<DatabaseManager>>,
+    id: i64,
+    name: String,
+    sections: Vec<BlueprintSection>,
+    actor: Option<Actor>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    if db.update_wida_blueprint(id, &name, &sections).map_err(|e| e.to_string())? {
+        Ok(())
+    } else {
+        Err("蓝图不存在".to_string())
+    }
+}
+
+/// 删除测试蓝图
+#[tauri::command]
+pub fn delete_wida_blueprint(
+    db: State<'_, Mutex<DatabaseManager>>,
+    id: i64,
+    actor: Option<Actor>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let name = db.get_wida_blueprint(id).map_err(|e| e.to_string())?.map(|blueprint| blueprint.name);
+    db.delete_wida_blueprint(id)
+        .map_err(|e| e.to_string())?;
+    if name.is_some() {
+        db.record_audit(actor.as_ref().map(|a| a.user_name.as_str()), "delete", "blueprint", id, name.as_deref())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 获取测试蓝图列表
+#[tauri::command]
+pub fn get_wida_blueprints(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: Option<String>,
+) -> Result<Vec<WidaTestBlueprint>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_wida_blueprints(test_type.as_deref())
+        .map_err(|e| e.to_string())
+}
+
 /// 获取测试会话
 #[tauri::command]
 pub fn get_wida_test_session(
@@ -105,15 +169,105 @@ pub fn submit_wida_answer(
         .map_err(|e| e.to_string())
 }
 
+/// 识别口语题录音并保存为待确认的转写
+#[tauri::command]
+pub async fn transcribe_speaking_answer(
+    app: tauri::AppHandle,
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+    question_id: i64,
+    audio: Vec<u8>,
+) -> Result<SpeakingTranscript, String> {
+    let (text, confidence) = crate::commands::pronunciation::recognize_speech(&app, audio).await?;
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.save_speaking_stt(session_id, question_id, &text, confidence)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取测试中口语题的转写，供复核
+#[tauri::command]
+pub fn get_speaking_transcripts(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+) -> Result<Vec<SpeakingTranscript>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_speaking_transcripts(session_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 修改口语题的转写（改后需要重新确认）
+#[tauri::command]
+pub fn update_speaking_transcript(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+    question_id: i64,
+    transcript: String,
+) -> Result<SpeakingTranscript, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.update_speaking_transcript(session_id, question_id, &transcript)
+        .map_err(|e| e.to_string())
+}
+
+/// 确认口语题的转写，之后按转写评分
+#[tauri::command]
+pub fn confirm_speaking_transcript(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+    question_id: i64,
+) -> Result<SpeakingTranscript, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.confirm_speaking_transcript(session_id, question_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 前端上报测试中的一次离开窗口（`focus_loss`）或暂停（`pause`）及其时长
+#[tauri::command]
+pub fn record_wida_focus_event(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+    event_type: String,
+    question_id: Option<i64>,
+    duration_ms: i64,
+) -> Result<WidaIntegritySummary, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.record_wida_focus_event(session_id, &event_type, question_id, duration_ms)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取测试的完整性摘要
+#[tauri::command]
+pub fn get_wida_integrity_summary(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+) -> Result<WidaIntegritySummary, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_wida_integrity_summary(session_id)
+        .map_err(|e| e.to_string())
+}
+
 /// 完成测试
 #[tauri::command]
 pub fn complete_wida_test(
+    app: tauri::AppHandle,
     db: State<'_, Mutex<DatabaseManager>>,
     request: CompleteWidaTestRequest,
 ) -> Result<WidaTestReport, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.complete_wida_test(&request)
-        .map_err(|e| e.to_string())
+    let report = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.complete_wida_test(&request).map_err(|e| e.to_string())?
+    };
+    let data = serde_json::json!({
+        "session_id": report.session.id,
+        "test_type": report.session.test_type,
+        "grade_level": report.session.grade_level,
+        "overall_score": report.overall_score,
+        "accuracy": report.accuracy,
+        "proficiency_level": report.proficiency_level,
+        "proficiency_level_name": report.proficiency_level_name,
+    });
+    let event = crate::commands::webhook::WebhookEvent::new("test_completed", &report.session.user_name, data);
+    crate::commands::webhook::notify(&app, vec![event]);
+    Ok(report)
 }
 
 /// 获取用户的测试历史
@@ -129,6 +283,63 @@ pub fn get_wida_history(
         .map_err(|e| e.to_string())
 }
 
+/// 根据年龄和最近的测试结果推荐下次测试的年级段和起始难度
+#[tauri::command]
+pub fn recommend_grade_level(
+    db: State<'_, Mutex<DatabaseManager>>,
+    user_name: String,
+) -> Result<GradeRecommendation, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.recommend_grade_level(&user_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 开始入门分级测试（新用户用），未指定年级段时自动推荐
+#[tauri::command]
+pub fn start_placement_test(
+    db: State<'_, Mutex<DatabaseManager>>,
+    user_name: String,
+    grade_level: Option<String>,
+) -> Result<PlacementTest, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.start_placement_test(&user_name, grade_level.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 取分级测试的下一道题，测试题数已满时返回空
+#[tauri::command]
+pub fn next_placement_question(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_id: i64,
+) -> Result<Option<PlacementQuestion>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.next_placement_question(test_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 提交分级测试当前题的答案
+#[tauri::command]
+pub fn submit_placement_answer(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_id: i64,
+    answer: String,
+) -> Result<PlacementAnswerResult, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.submit_placement_answer(test_id, &answer)
+        .map_err(|e| e.to_string())
+}
+
+/// 结束分级测试，写入初始能力档案和推荐的每日设置
+#[tauri::command]
+pub fn complete_placement_test(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_id: i64,
+) -> Result<PlacementProfile, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.complete_placement_test(test_id)
+        .map_err(|e| e.to_string())
+}
+
 /// 获取用户综合报告
 #[tauri::command]
 pub fn get_wida_comprehensive_report(
@@ -156,12 +367,315 @@ pub fn get_active_wida_sessions(
 pub fn delete_wida_session(
     db: State<'_, Mutex<DatabaseManager>>,
     session_id: i64,
+    actor: Option<Actor>,
 ) -> Result<(), String> {
     let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let Some(session) = db.get_wida_test_session(session_id).map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
     db.delete_wida_session(session_id)
+        .map_err(|e| e.to_string())?;
+    // 未指明操作人时记为会话所属用户
+    let user_name = actor.map_or(session.user_name.clone(), |a| a.user_name);
+    let details = format!("{} {} ({})", session.user_name, session.test_type, session.status);
+    db.record_audit(Some(&user_name), "delete", "wida_session", session_id, Some(&details))
+        .map_err(|e| e.to_string())
+}
+
+/// 从题库删除一道题
+#[tauri::command]
+pub fn delete_wida_question(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: String,
+    question_id: i64,
+    actor: Option<Actor>,
+) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let (question_text, _) = db.get_wida_question_info(&test_type, question_id).map_err(|e| e.to_string())?;
+    let deleted = db.delete_wida_question(&test_type, question_id).map_err(|e| e.to_string())?;
+    if deleted {
+        let details = format!("{}: {}", test_type, question_text);
+        db.record_audit(actor.as_ref().map(|a| a.user_name.as_str()), "delete", "wida_question", question_id, Some(&details))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(deleted)
+}
+
+/// 学生在测试中反馈题目有问题（看不懂、答案有误、音频无法播放等）
+#[tauri::command]
+pub fn flag_question(
+    db: State<'_, Mutex<DatabaseManager>>,
+    session_id: i64,
+    question_id: i64,
+    reason: String,
+) -> Result<WidaQuestionFlag, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.flag_question(session_id, question_id, &reason)
+        .map_err(|e| e.to_string())
+}
+
+/// 题目反馈复核队列，`status` 为空时列出全部
+#[tauri::command]
+pub fn list_flagged_questions(
+    db: State<'_, Mutex<DatabaseManager>>,
+    status: Option<String>,
+    actor: Option<Actor>,
+) -> Result<Vec<WidaQuestionFlag>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.list_flagged_questions(status.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 处理题目反馈：`resolved` 或 `dismissed`
+#[tauri::command]
+pub fn resolve_question_flag(
+    db: State<'_, Mutex<DatabaseManager>>,
+    flag_id: i64,
+    status: String,
+    actor: Option<Actor>,
+) -> Result<bool, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let found = db.resolve_question_flag(flag_id, &status).map_err(|e| e.to_string())?;
+    if found {
+        db.record_audit(actor.as_ref().map(|a| a.user_name.as_str()), &status, "wida_question_flag", flag_id, None)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(found)
+}
+
+/// 补充缺少的内置题目，`grade_levels` 为空时补充全部年级段；`force` 时把内置题目恢复为内置内容
+#[tauri::command]
+pub fn reseed_defaults(
+    db: State<'_, Mutex<DatabaseManager>>,
+    force: bool,
+    grade_levels: Option<Vec<String>>,
+    actor: Option<Actor>,
+) -> Result<ReseedReport, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.reseed_defaults(grade_levels.as_deref(), force)
+        .map_err(|e| e.to_string())
+}
+
+// ========== 错题词汇 ==========
+
+/// 错题单词表默认最多包含的单词数
+const DEFAULT_MISTAKE_WORD_COUNT: usize = 20;
+
+/// 从已完成测试的错题中提取关键词汇，生成单词表供拼写练习
+///
+/// `use_ai` 为 true 且已配置 API Key 时由 AI 挑选词汇，否则（或 AI 调用失败时）按词频提取。
+#[tauri::command]
+pub async fn create_wordlist_from_wida_mistakes(
+    app: tauri::AppHandle,
+    session_id: i64,
+    use_ai: Option<bool>,
+    max_words: Option<usize>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<ImportedArticle, String> {
+    let (texts, test_type) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+        let session = db.get_wida_test_session(session_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("测试会话不存在")?;
+        let texts = db.get_wida_missed_texts(session_id).map_err(|e| e.to_string())?;
+        (texts, session.test_type)
+    };
+    if texts.is_empty() {
+        return Err("本次测试没有答错的题目".to_string());
+    }
+
+    let limit = max_words.unwrap_or(DEFAULT_MISTAKE_WORD_COUNT);
+    let mut words = Vec::new();
+    if use_ai.unwrap_or(false) {
+        let settings = read_api_settings(&app)?;
+        if settings.api_key.is_empty() {
+            log::warn!("API Key 未配置，改用词频提取错题词汇");
+        } else {
+            match extract_vocabulary_with_ai(&db, &settings, &texts, limit).await {
+                Ok(ai_words) => words = ai_words,
+                Err(e) => log::warn!("AI 提取词汇失败，改用词频提取: {}", e),
+            }
+        }
+    }
+    if words.is_empty() {
+        words = crate::text::extract_key_vocabulary(&texts, limit);
+    }
+    if words.is_empty() {
+        return Err("错题中没有找到可练习的单词".to_string());
+    }
+
+    let doc = crate::importers::ParsedDocument {
+        title: format!("WIDA {} 错题词汇 #{}", test_type, session_id),
+        content: words.join("\n"),
+        word_list: Some(words),
+        cues: None,
+    };
+    crate::commands::import::import_document(&db, doc)
+}
+
+/// 让 AI 从错题文本中挑选关键词汇
+async fn extract_vocabulary_with_ai(
+    db: &Mutex<DatabaseManager>,
+    settings: &ApiSettings,
+    texts: &[String],
+    limit: usize,
+) -> Result<Vec<String>, String> {
+    let prompt = format!(
+        r#"以下是英语学习者在测试中答错的题目相关文本。请从中挑选最多 {} 个学生最需要掌握拼写的关键英语单词（避免 the、and 等常见虚词）。
+只返回 JSON 字符串数组，例如 ["habitat", "energy"]，不要其他内容。
+
+{}"#,
+        limit,
+        texts.join("\n")
+    );
+    let content = call_ai_api(db, "wida_mistake_vocabulary", &settings.api_url, &settings.api_key, &settings.model, &prompt).await?;
+    let candidates: Vec<String> = serde_json::from_str(extract_json_array(&content))
+        .map_err(|e| format!("解析AI返回的单词失败: {}", e))?;
+
+    let mut words: Vec<String> = Vec::new();
+    for word in candidates.iter().map(|w| w.trim().to_lowercase()) {
+        if !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic() || c == '-' || c == '\'') && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words.truncate(limit);
+    Ok(words)
+}
+
+/// 把题目设为示例题（测试开始时先做，不计分）或取消
+#[tauri::command]
+pub fn set_wida_question_sample(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: String,
+    question_id: i64,
+    is_sample: bool,
+    actor: Option<Actor>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.set_wida_question_sample(&test_type, question_id, is_sample)
+        .map_err(|e| e.to_string())
+}
+
+/// 按实际作答结果校准题目难度，`dry_run` 时只列出与标注难度相差较大的题
+#[tauri::command]
+pub fn recalibrate_difficulties(
+    db: State<'_, Mutex<DatabaseManager>>,
+    dry_run: bool,
+    actor: Option<Actor>,
+) -> Result<DifficultyRecalibration, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    let report = db.recalibrate_difficulties(dry_run).map_err(|e| e.to_string())?;
+    if !dry_run {
+        let user_name = actor.as_ref().map(|a| a.user_name.as_str());
+        for item in &report.flagged {
+            let details = format!(
+                "{}: difficulty {} -> {} ({} attempts, {:.0}% credit)",
+                item.test_type, item.stored_difficulty, item.empirical_difficulty, item.attempts, item.p_value * 100.0
+            );
+            db.record_audit(user_name, "recalibrate", "wida_question", item.question_id, Some(&details))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(report)
+}
+
+/// 设置题目标签（整体替换）
+#[tauri::command]
+pub fn set_wida_question_tags(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: String,
+    question_id: i64,
+    tags: Vec<String>,
+    actor: Option<Actor>,
+) -> Result<(), String> {
+    let mut db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.set_wida_question_tags(&test_type, question_id, &tags)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取题库中用到的全部标签
+#[tauri::command]
+pub fn get_all_question_tags(db: State<'_, Mutex<DatabaseManager>>) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_all_question_tags()
+        .map_err(|e| e.to_string())
+}
+
+/// 按条件和关键词搜索题库
+#[tauri::command]
+pub fn search_questions(
+    db: State<'_, Mutex<DatabaseManager>>,
+    filters: QuestionSearchFilters,
+) -> Result<Vec<QuestionSearchResult>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.search_questions(&filters)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取某部分（听/说/读/写）的说明文字
+#[tauri::command]
+pub fn get_wida_section_instructions(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: String,
+) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_wida_section_instructions(&test_type)
+        .map_err(|e| e.to_string())
+}
+
+/// 自定义某部分的说明文字，传 null 恢复默认
+#[tauri::command]
+pub fn set_wida_section_instructions(
+    db: State<'_, Mutex<DatabaseManager>>,
+    test_type: String,
+    instructions: Option<String>,
+    actor: Option<Actor>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    db.set_wida_section_instructions(&test_type, instructions.as_deref())
         .map_err(|e| e.to_string())
 }
 
+// ========== 题目音频 ==========
+
+/// 播放测试中某道题的音频（听力原文或口语题的音频文本），返回剩余可播放次数
+#[tauri::command]
+pub async fn play_question_audio(
+    app: tauri::AppHandle,
+    session_id: i64,
+    question_id: i64,
+    rate: Option<i32>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<i32, String> {
+    let (text, remaining, rate) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_wida_test_session(session_id).map_err(|e| e.to_string())?.ok_or("测试不存在")?;
+        let rate = crate::commands::tts::accommodated_rate(&db, Some(&session.user_name), rate)?;
+        let text = db
+            .get_wida_question_audio(session_id, question_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("该题没有音频")?;
+        let remaining = db
+            .record_wida_audio_play(session_id, question_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("已达到本次测试的最多播放次数")?;
+        (text, remaining, rate)
+    };
+    crate::commands::tts::speak_cached(app, text, rate).await?;
+    Ok(remaining)
+}
+
 // ========== 题目生成模块 ==========
 
 /// 生成题目请求
@@ -175,6 +689,8 @@ pub struct GenerateQuestionsRequest {
     pub api_url: String,            // API URL
     pub api_key: String,            // API Key
     pub model: String,              // 模型名称
+    #[serde(default)]
+    pub locale: Option<String>,     // 提示语言（en / zh）
 }
 
 /// 生成题目响应
@@ -183,6 +699,8 @@ pub struct GenerateQuestionsResponse {
     pub success: bool,
     pub message: String,
     pub generated_count: i32,
+    #[serde(default)]
+    pub warnings: Vec<String>,      // 超出年级段的听力/阅读文本：未保存或需留意的题
 }
 
 /// AI API 请求
@@ -203,6 +721,17 @@ struct AiMessage {
 #[derive(Debug, Deserialize)]
 struct AiApiResponse {
     choices: Vec<AiChoice>,
+    #[serde(default)]
+    usage: Option<AiUsage>,
+}
+
+/// token 用量（OpenAI 兼容格式）
+#[derive(Debug, Deserialize)]
+struct AiUsage {
+    #[serde(default)]
+    prompt_tokens: i64,
+    #[serde(default)]
+    completion_tokens: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -220,18 +749,31 @@ struct AiMessageContent {
 pub async fn generate_listening_questions(
     db: State<'_, Mutex<DatabaseManager>>,
     request: GenerateQuestionsRequest,
+    actor: Option<Actor>,
 ) -> Result<GenerateQuestionsResponse, String> {
-    let prompt = build_listening_prompt(&request);
-    let content = call_ai_api(&request.api_url, &request.api_key, &request.model, &prompt).await?;
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if request.api_key.trim().is_empty() {
+        return generate_offline(&db, "listening", &request);
+    }
+    let prompt = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        render_prompt(&db, "listening", &question_vars(&request))?
+    };
+    let content = call_ai_api(&db, "generate_listening", &request.api_url, &request.api_key, &request.model, &prompt).await?;
     let questions = parse_listening_questions(&content, &request)?;
+    let (questions, warnings) = screen_grade_level(questions, |q| (&q.grade_level, &q.audio_text));
     
     let db = db.lock().map_err(|e| e.to_string())?;
     let count = db.save_listening_questions(&questions).map_err(|e| e.to_string())?;
     
     Ok(GenerateQuestionsResponse {
         success: true,
-        message: format!("成功生成 {} 道听力题", count),
+        message: generated_message(request.locale.as_deref(), "wida.generated", "listening", count),
         generated_count: count,
+        warnings,
     })
 }
 
@@ -240,18 +782,31 @@ pub async fn generate_listening_questions(
 pub async fn generate_reading_questions(
     db: State<'_, Mutex<DatabaseManager>>,
     request: GenerateQuestionsRequest,
+    actor: Option<Actor>,
 ) -> Result<GenerateQuestionsResponse, String> {
-    let prompt = build_reading_prompt(&request);
-    let content = call_ai_api(&request.api_url, &request.api_key, &request.model, &prompt).await?;
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if request.api_key.trim().is_empty() {
+        return generate_offline(&db, "reading", &request);
+    }
+    let prompt = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        render_prompt(&db, "reading", &question_vars(&request))?
+    };
+    let content = call_ai_api(&db, "generate_reading", &request.api_url, &request.api_key, &request.model, &prompt).await?;
     let questions = parse_reading_questions(&content, &request)?;
+    let (questions, warnings) = screen_grade_level(questions, |q| (&q.grade_level, &q.passage));
     
     let db = db.lock().map_err(|e| e.to_string())?;
     let count = db.save_reading_questions(&questions).map_err(|e| e.to_string())?;
     
     Ok(GenerateQuestionsResponse {
         success: true,
-        message: format!("成功生成 {} 道阅读题", count),
+        message: generated_message(request.locale.as_deref(), "wida.generated", "reading", count),
         generated_count: count,
+        warnings,
     })
 }
 
@@ -260,9 +815,20 @@ pub async fn generate_reading_questions(
 pub async fn generate_speaking_questions(
     db: State<'_, Mutex<DatabaseManager>>,
     request: GenerateQuestionsRequest,
+    actor: Option<Actor>,
 ) -> Result<GenerateQuestionsResponse, String> {
-    let prompt = build_speaking_prompt(&request);
-    let content = call_ai_api(&request.api_url, &request.api_key, &request.model, &prompt).await?;
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if request.api_key.trim().is_empty() {
+        return generate_offline(&db, "speaking", &request);
+    }
+    let prompt = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        render_prompt(&db, "speaking", &question_vars(&request))?
+    };
+    let content = call_ai_api(&db, "generate_speaking", &request.api_url, &request.api_key, &request.model, &prompt).await?;
     let questions = parse_speaking_questions(&content, &request)?;
     
     let db = db.lock().map_err(|e| e.to_string())?;
@@ -270,8 +836,9 @@ pub async fn generate_speaking_questions(
     
     Ok(GenerateQuestionsResponse {
         success: true,
-        message: format!("成功生成 {} 道口语题", count),
+        message: generated_message(request.locale.as_deref(), "wida.generated", "speaking", count),
         generated_count: count,
+        warnings: Vec::new(),
     })
 }
 
@@ -280,9 +847,20 @@ pub async fn generate_speaking_questions(
 pub async fn generate_writing_questions(
     db: State<'_, Mutex<DatabaseManager>>,
     request: GenerateQuestionsRequest,
+    actor: Option<Actor>,
 ) -> Result<GenerateQuestionsResponse, String> {
-    let prompt = build_writing_prompt(&request);
-    let content = call_ai_api(&request.api_url, &request.api_key, &request.model, &prompt).await?;
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if request.api_key.trim().is_empty() {
+        return generate_offline(&db, "writing", &request);
+    }
+    let prompt = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        render_prompt(&db, "writing", &question_vars(&request))?
+    };
+    let content = call_ai_api(&db, "generate_writing", &request.api_url, &request.api_key, &request.model, &prompt).await?;
     let questions = parse_writing_questions(&content, &request)?;
     
     let db = db.lock().map_err(|e| e.to_string())?;
@@ -290,13 +868,271 @@ pub async fn generate_writing_questions(
     
     Ok(GenerateQuestionsResponse {
         success: true,
-        message: format!("成功生成 {} 道写作题", count),
+        message: generated_message(request.locale.as_deref(), "wida.generated", "writing", count),
+        generated_count: count,
+        warnings: Vec::new(),
+    })
+}
+
+/// 未配置 API Key 时按规则离线出题
+fn generate_offline(
+    db: &Mutex<DatabaseManager>,
+    test_type: &str,
+    request: &GenerateQuestionsRequest,
+) -> Result<GenerateQuestionsResponse, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let passages = offline_passages(&db, &request.grade_level)?;
+    let seed = crate::ordering::random_seed();
+    let mut warnings = Vec::new();
+    let count = match test_type {
+        "listening" => {
+            let questions = offline::listening_questions(&passages, request, seed);
+            let (questions, screened) = screen_grade_level(questions, |q| (&q.grade_level, &q.audio_text));
+            warnings = screened;
+            db.save_listening_questions(&questions)
+        }
+        "reading" => {
+            let questions = offline::reading_questions(&passages, request, seed);
+            let (questions, screened) = screen_grade_level(questions, |q| (&q.grade_level, &q.passage));
+            warnings = screened;
+            db.save_reading_questions(&questions)
+        }
+        "speaking" => db.save_speaking_questions(&offline::speaking_questions(&passages, request, seed)),
+        _ => db.save_writing_questions(&offline::writing_questions(&passages, request, seed)),
+    };
+    let count = count.map_err(|e| e.to_string())?;
+
+    Ok(GenerateQuestionsResponse {
+        success: true,
+        message: generated_message(request.locale.as_deref(), "wida.generated_offline", test_type, count),
+        generated_count: count,
+        warnings,
+    })
+}
+
+/// 离线出题素材：年级段匹配的文章，以及题库中该年级段的阅读材料
+fn offline_passages(db: &DatabaseManager, grade_level: &str) -> Result<Vec<offline::Passage>, String> {
+    let mut passages = Vec::new();
+    for article in db.get_articles().map_err(|e| e.to_string())? {
+        if grade_band(article.reading_grade) == grade_level {
+            passages.extend(offline::split_passages(&article.content, Some(article.id)));
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    for question in db.get_wida_reading_questions(grade_level, None, None).map_err(|e| e.to_string())? {
+        // 挖过空的材料不再作为素材
+        if !question.passage.contains(offline::BLANK) && seen.insert(question.passage.clone()) {
+            passages.extend(offline::split_passages(&question.passage, None));
+        }
+    }
+    if passages.is_empty() {
+        return Err("没有可用于离线出题的文章，请先导入文章或配置 API Key".to_string());
+    }
+    Ok(passages)
+}
+
+/// 生成完成的提示语，`kind` 为本地化后的题型名称
+fn generated_message(locale: Option<&str>, key: &str, test_type: &str, count: i32) -> String {
+    let kind = crate::i18n::text(locale, &format!("test_type.{}", test_type), &[]);
+    crate::i18n::text(locale, key, &[("count", count.to_string()), ("kind", kind)])
+}
+
+/// 按年级段检查听力原文 / 阅读文章，超出太多的题不保存，略超的保留并给出提示
+pub fn screen_grade_level<T>(questions: Vec<T>, text: impl Fn(&T) -> (&String, &String)) -> (Vec<T>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut warnings = Vec::new();
+    for (i, question) in questions.into_iter().enumerate() {
+        let (grade_level, content) = text(&question);
+        match crate::text::grade_check::check(content, grade_level) {
+            Some(check) if check.status == "rejected" => {
+                warnings.push(format!("第 {} 题超出 {} 难度，未保存：{}", i + 1, grade_level, check.issues.join("；")));
+                continue;
+            }
+            Some(check) if check.status == "warning" => {
+                warnings.push(format!("第 {} 题略超 {} 难度：{}", i + 1, grade_level, check.issues.join("；")));
+            }
+            _ => {}
+        }
+        kept.push(question);
+    }
+    (kept, warnings)
+}
+
+/// 检查一段听力/阅读文本是否符合年级段（句长、生词比例）
+#[tauri::command]
+pub fn check_grade_level_text(text: String, grade_level: String) -> Result<GradeLevelCheck, String> {
+    crate::text::grade_check::check(&text, &grade_level).ok_or_else(|| format!("未知的年级段: {}", grade_level))
+}
+
+/// 文章正文最多发送给 AI 的字符数
+const MAX_ARTICLE_PROMPT_CHARS: usize = 6000;
+
+/// 根据已有文章生成 WIDA 听力/阅读题，题目记录来源文章
+///
+/// 未指定 `grade_level` 时按文章的 Flesch-Kincaid 年级选择年级段。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_questions_from_article(
+    app: tauri::AppHandle,
+    article_id: i64,
+    test_type: String,
+    count: i32,
+    grade_level: Option<String>,
+    locale: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<GenerateQuestionsResponse, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if test_type != "listening" && test_type != "reading" {
+        return Err(format!("只能根据文章生成听力或阅读题: {}", test_type));
+    }
+    let article = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_article(article_id).map_err(|e| e.to_string())?.ok_or("文章不存在")?
+    };
+    let settings = read_api_settings(&app)?;
+    if settings.api_key.is_empty() {
+        return Err("请先配置 API Key".to_string());
+    }
+
+    let request = GenerateQuestionsRequest {
+        test_type: test_type.clone(),
+        grade_level: grade_level.unwrap_or_else(|| grade_band(article.reading_grade).to_string()),
+        domain: "article".to_string(),
+        difficulty: 3,
+        count,
+        api_url: settings.api_url,
+        api_key: settings.api_key,
+        model: settings.model,
+        locale,
+    };
+    let (kind, text_field, text_hint) = if test_type == "listening" {
+        ("听力", "audio_text", "从文章中摘取或改写的一段听力文本（适合用TTS朗读）")
+    } else {
+        ("阅读", "passage", "从文章中摘取的一段阅读材料")
+    };
+    let mut vars = question_vars(&request);
+    vars.extend([
+        ("kind", kind.to_string()),
+        ("title", article.title.clone()),
+        ("excerpt", article.content.chars().take(MAX_ARTICLE_PROMPT_CHARS).collect()),
+        ("text_field", text_field.to_string()),
+        ("text_hint", text_hint.to_string()),
+    ]);
+    let prompt = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        render_prompt(&db, "article", &vars)?
+    };
+    let content = call_ai_api(&db, "generate_from_article", &request.api_url, &request.api_key, &request.model, &prompt).await?;
+
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let (count, warnings) = if test_type == "listening" {
+        let mut questions = parse_listening_questions(&content, &request)?;
+        questions.iter_mut().for_each(|q| q.source_article_id = Some(article_id));
+        let (questions, warnings) = screen_grade_level(questions, |q| (&q.grade_level, &q.audio_text));
+        (db.save_listening_questions(&questions).map_err(|e| e.to_string())?, warnings)
+    } else {
+        let mut questions = parse_reading_questions(&content, &request)?;
+        questions.iter_mut().for_each(|q| q.source_article_id = Some(article_id));
+        let (questions, warnings) = screen_grade_level(questions, |q| (&q.grade_level, &q.passage));
+        (db.save_reading_questions(&questions).map_err(|e| e.to_string())?, warnings)
+    };
+
+    Ok(GenerateQuestionsResponse {
+        success: true,
+        message: crate::i18n::text(
+            request.locale.as_deref(),
+            "wida.generated_from_article",
+            &[("title", article.title.clone()), ("count", count.to_string())],
+        ),
         generated_count: count,
+        warnings,
+    })
+}
+
+/// Flesch-Kincaid 年级对应的 WIDA 年级段
+fn grade_band(reading_grade: Option<f64>) -> &'static str {
+    match reading_grade {
+        Some(g) if g < 3.0 => "grade_1_2",
+        Some(g) if g < 6.0 => "grade_3_5",
+        Some(g) if g < 9.0 => "grade_6_8",
+        Some(_) => "grade_9_12",
+        None => "grade_3_5",
+    }
+}
+
+/// 出题模板通用的占位符取值
+fn question_vars(request: &GenerateQuestionsRequest) -> Vec<(&'static str, String)> {
+    vec![
+        ("count", request.count.to_string()),
+        ("grade_level", request.grade_level.clone()),
+        ("domain", request.domain.clone()),
+        ("difficulty", request.difficulty.to_string()),
+    ]
+}
+
+/// 用用户自定义模板（没有则用默认模板）生成提示词
+fn render_prompt(db: &DatabaseManager, test_type: &str, vars: &[(&str, String)]) -> Result<String, String> {
+    let template = match db.get_prompt_template(test_type).map_err(|e| e.to_string())? {
+        Some(custom) => custom,
+        None => crate::prompts::default_template(test_type)
+            .ok_or_else(|| format!("未知的提示词模板: {}", test_type))?
+            .to_string(),
+    };
+    Ok(crate::prompts::render(&template, vars))
+}
+
+/// 获取提示词模板（自定义的优先）
+#[tauri::command]
+pub fn get_prompt_template(test_type: String, db: State<'_, Mutex<DatabaseManager>>) -> Result<PromptTemplate, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    prompt_template(&db, &test_type)
+}
+
+/// 自定义提示词模板，`template` 为空时恢复默认模板
+#[tauri::command]
+pub fn customize_prompt_template(
+    test_type: String,
+    template: Option<String>,
+    actor: Option<Actor>,
+    db: State<'_, Mutex<DatabaseManager>>,
+) -> Result<PromptTemplate, String> {
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
+    if crate::prompts::default_template(&test_type).is_none() {
+        return Err(format!("未知的提示词模板: {}", test_type));
+    }
+    let template = template.filter(|t| !t.trim().is_empty());
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.set_prompt_template(&test_type, template.as_deref()).map_err(|e| e.to_string())?;
+    prompt_template(&db, &test_type)
+}
+
+fn prompt_template(db: &DatabaseManager, test_type: &str) -> Result<PromptTemplate, String> {
+    let default = crate::prompts::default_template(test_type).ok_or_else(|| format!("未知的提示词模板: {}", test_type))?;
+    let custom = db.get_prompt_template(test_type).map_err(|e| e.to_string())?;
+    Ok(PromptTemplate {
+        test_type: test_type.to_string(),
+        is_custom: custom.is_some(),
+        template: custom.unwrap_or_else(|| default.to_string()),
+        placeholders: crate::prompts::placeholders(test_type).unwrap_or_default().iter().map(|p| p.to_string()).collect(),
     })
 }
 
-/// 调用 AI API
-async fn call_ai_api(api_url: &str, api_key: &str, model: &str, prompt: &str) -> Result<String, String> {
+/// 调用 AI API，并记录本次调用的 token 用量（`purpose` 标明用途）
+async fn call_ai_api(
+    db: &Mutex<DatabaseManager>,
+    purpose: &str,
+    api_url: &str,
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+) -> Result<String, String> {
     let client = reqwest::Client::new();
     
     let request_body = AiApiRequest {
@@ -327,6 +1163,10 @@ async fn call_ai_api(api_url: &str, api_key: &str, model: &str, prompt: &str) ->
         .json()
         .await
         .map_err(|e| format!("解析响应失败: {}", e))?;
+
+    if let Some(usage) = &api_response.usage {
+        record_ai_usage(db, purpose, api_url, model, usage);
+    }
     
     Ok(api_response.choices
         .first()
@@ -334,153 +1174,51 @@ async fn call_ai_api(api_url: &str, api_key: &str, model: &str, prompt: &str) ->
         .unwrap_or_default())
 }
 
-/// 构建听力题生成提示词
-fn build_listening_prompt(request: &GenerateQuestionsRequest) -> String {
-    format!(
-        r#"请生成 {} 道WIDA英语听力测试题目。
-
-要求：
-- 年级水平: {} (对应难度等级: {})
-- 学科领域: {}
-- 难度等级: {}/6
-
-每道题目需要包含：
-1. audio_text: 听力文本（学生会听到的内容，适合用TTS朗读）
-2. question_text: 问题文本
-3. options: 4个选项 (A, B, C, D)
-4. correct_answer: 正确答案索引 (0-3)
-5. explanation: 答案解析
-
-请严格按照以下JSON格式返回，不要包含任何其他文字：
-[
-  {{
-    "audio_text": "听力文本内容...",
-    "question_text": "问题...",
-    "options": ["选项A", "选项B", "选项C", "选项D"],
-    "correct_answer": 0,
-    "explanation": "解析..."
-  }}
-]"#,
-        request.count,
-        request.grade_level,
-        request.difficulty,
-        request.domain,
-        request.difficulty
-    )
-}
-
-/// 构建阅读题生成提示词
-fn build_reading_prompt(request: &GenerateQuestionsRequest) -> String {
-    format!(
-        r#"请生成 {} 道WIDA英语阅读测试题目。
-
-要求：
-- 年级水平: {} (对应难度等级: {})
-- 学科领域: {}
-- 难度等级: {}/6
-
-每道题目需要包含：
-1. passage: 阅读文章（根据年级调整长度和难度）
-2. question_text: 问题文本
-3. options: 4个选项 (A, B, C, D)
-4. correct_answer: 正确答案索引 (0-3)
-5. explanation: 答案解析
-
-请严格按照以下JSON格式返回，不要包含任何其他文字：
-[
-  {{
-    "passage": "阅读文章内容...",
-    "question_text": "问题...",
-    "options": ["选项A", "选项B", "选项C", "选项D"],
-    "correct_answer": 0,
-    "explanation": "解析..."
-  }}
-]"#,
-        request.count,
-        request.grade_level,
-        request.difficulty,
-        request.domain,
-        request.difficulty
-    )
-}
-
-/// 构建口语题生成提示词
-fn build_speaking_prompt(request: &GenerateQuestionsRequest) -> String {
-    format!(
-        r#"请生成 {} 道WIDA英语口语测试题目。
-
-要求：
-- 年级水平: {} (对应难度等级: {})
-- 学科领域: {}
-- 难度等级: {}/6
-- 所有题目必须包含图片描述
-
-每道题目需要包含：
-1. prompt_type: 必须是 "picture"
-2. prompt_text: 提示文本（让学生根据图片回答的问题或任务）
-3. image_description: 图片的详细描述（用于生成或选择合适的图片）
-4. sample_answer: 示范回答
-5. rubric: 评分标准（4个评价点）
-
-图片描述应该：
-- 清晰、具体，适合该年级水平
-- 包含学生需要描述或讨论的主要元素
-- 与学科领域相关
-- 激发学生的口语表达能力
-
-请严格按照以下JSON格式返回，不要包含任何其他文字：
-[
-  {{
-    "prompt_type": "picture",
-    "prompt_text": "Look at the picture and describe what you see. / What is happening in this picture? / Tell a story about this picture.",
-    "image_description": "A detailed description of the image content (e.g., 'A family having a picnic in a park on a sunny day. There are trees, a blanket on the grass, a basket with food, and children playing with a ball.')",
-    "sample_answer": "示范回答...",
-    "rubric": ["评分标准1", "评分标准2", "评分标准3", "评分标准4"]
-  }}
-]"#,
-        request.count,
-        request.grade_level,
-        request.difficulty,
-        request.domain,
-        request.difficulty
-    )
-}
-
-/// 构建写作题生成提示词
-fn build_writing_prompt(request: &GenerateQuestionsRequest) -> String {
-    format!(
-        r#"请生成 {} 道WIDA英语写作测试题目。
-
-要求：
-- 年级水平: {} (对应难度等级: {})
-- 学科领域: {}
-- 难度等级: {}/6
-
-每道题目需要包含：
-1. task_type: 任务类型 (argumentative | expository | personal_recount | email | letter | report)
-2. prompt: 写作提示
-3. word_limit_min: 最少字数
-4. word_limit_max: 最多字数
-5. rubric: 评分标准（4个评价点）
-6. sample_answer: 示范回答
-
-请严格按照以下JSON格式返回，不要包含任何其他文字：
-[
-  {{
-    "task_type": "expository",
-    "prompt": "写作提示...",
-    "word_limit_min": 50,
-    "word_limit_max": 100,
-    "rubric": ["评分标准1", "评分标准2", "评分标准3", "评分标准4"],
-    "sample_answer": "示范回答..."
-  }}
-]"#,
-        request.count,
-        request.grade_level,
-        request.difficulty,
-        request.domain,
-        request.difficulty
-    )
+/// 常见模型每百万 token 的美元价格（输入, 输出），按前缀匹配，较长的前缀放在前面
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("deepseek-chat", 0.27, 1.10),
+    ("deepseek-reasoner", 0.55, 2.19),
+];
+
+/// 按模型价格估算费用（美元）；未知模型返回 None
+fn estimate_cost(model: &str, prompt_tokens: i64, completion_tokens: i64) -> Option<f64> {
+    let model = model.to_lowercase();
+    MODEL_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, input, output)| (prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+}
+
+/// 记录 AI 用量；写入失败只记日志，不影响本次生成
+fn record_ai_usage(db: &Mutex<DatabaseManager>, purpose: &str, api_url: &str, model: &str, usage: &AiUsage) {
+    let provider = reqwest::Url::parse(api_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| api_url.to_string());
+    let cost = estimate_cost(model, usage.prompt_tokens, usage.completion_tokens);
+    let result = db.lock().map_err(|e| e.to_string()).and_then(|db| {
+        db.record_ai_usage(&provider, model, purpose, usage.prompt_tokens, usage.completion_tokens, cost)
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to record AI usage: {}", e);
+    }
+}
+
+/// 获取某月（`YYYY-MM`，默认本月）的 AI 用量汇总
+#[tauri::command]
+pub fn get_ai_usage_summary(month: Option<String>, db: State<'_, Mutex<DatabaseManager>>) -> Result<AiUsageSummary, String> {
+    let month = month.unwrap_or_else(|| chrono::Local::now().format("%Y-%m").to_string());
+    if chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_err() {
+        return Err(format!("月份格式应为 YYYY-MM: {}", month));
+    }
+    let db = db.lock().map_err(|e| e.to_string())?;
+    db.get_ai_usage_summary(&month).map_err(|e| e.to_string())
 }
 
 /// 解析听力题目
@@ -492,8 +1230,16 @@ fn parse_listening_questions(content: &str, request: &GenerateQuestionsRequest)
     struct RawQuestion {
         audio_text: String,
         question_text: String,
+        #[serde(default = "default_question_type")]
+        question_type: String,
+        #[serde(default)]
         options: Vec<String>,
+        #[serde(default)]
         correct_answer: i32,
+        #[serde(default)]
+        correct_answers: Vec<i32>,
+        #[serde(default)]
+        answer_pattern: Option<String>,
         explanation: Option<String>,
     }
     
@@ -507,9 +1253,13 @@ fn parse_listening_questions(content: &str, request: &GenerateQuestionsRequest)
         audio_text: q.audio_text,
         image_url: None,
         question_text: q.question_text,
+        question_type: q.question_type,
         options: q.options,
         correct_answer: q.correct_answer,
+        correct_answers: q.correct_answers,
+        answer_pattern: q.answer_pattern,
         explanation: q.explanation,
+        source_article_id: None,
     }).collect())
 }
 
@@ -521,8 +1271,16 @@ fn parse_reading_questions(content: &str, request: &GenerateQuestionsRequest) ->
     struct RawQuestion {
         passage: String,
         question_text: String,
+        #[serde(default = "default_question_type")]
+        question_type: String,
+        #[serde(default)]
         options: Vec<String>,
+        #[serde(default)]
         correct_answer: i32,
+        #[serde(default)]
+        correct_answers: Vec<i32>,
+        #[serde(default)]
+        answer_pattern: Option<String>,
         explanation: Option<String>,
     }
     
@@ -535,10 +1293,13 @@ fn parse_reading_questions(content: &str, request: &GenerateQuestionsRequest) ->
         difficulty: request.difficulty,
         passage: q.passage,
         question_text: q.question_text,
-        question_type: "multiple_choice".to_string(),
+        question_type: q.question_type,
         options: q.options,
         correct_answer: q.correct_answer,
+        correct_answers: q.correct_answers,
+        answer_pattern: q.answer_pattern,
         explanation: q.explanation,
+        source_article_id: None,
     }).collect())
 }
 
@@ -632,9 +1393,22 @@ pub struct GeneratedListeningQuestion {
     pub audio_text: String,
     pub image_url: Option<String>,
     pub question_text: String,
+    #[serde(default = "default_question_type")]
+    pub question_type: String,
     pub options: Vec<String>,
+    #[serde(default)]
     pub correct_answer: i32,
+    #[serde(default)]
+    pub correct_answers: Vec<i32>,          // 多选题
+    #[serde(default)]
+    pub answer_pattern: Option<String>,     // 简答题
     pub explanation: Option<String>,
+    #[serde(default)]
+    pub source_article_id: Option<i64>,     // 根据文章生成时的来源文章
+}
+
+fn default_question_type() -> String {
+    "multiple_choice".to_string()
 }
 
 /// 生成的阅读题目
@@ -645,10 +1419,18 @@ pub struct GeneratedReadingQuestion {
     pub difficulty: i32,
     pub passage: String,
     pub question_text: String,
+    #[serde(default = "default_question_type")]
     pub question_type: String,
     pub options: Vec<String>,
+    #[serde(default)]
     pub correct_answer: i32,
+    #[serde(default)]
+    pub correct_answers: Vec<i32>,
+    #[serde(default)]
+    pub answer_pattern: Option<String>,
     pub explanation: Option<String>,
+    #[serde(default)]
+    pub source_article_id: Option<i64>,
 }
 
 /// 生成的口语题目
@@ -692,21 +1474,18 @@ pub struct ApiSettings {
 #[tauri::command]
 pub async fn save_api_settings(
     settings: ApiSettings,
+    actor: Option<Actor>,
     app: tauri::AppHandle,
+    db: State<'_, Mutex<DatabaseManager>>,
 ) -> Result<(), String> {
-    use tauri::Manager;
-    
+    {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.authorize(actor.as_ref()).map_err(|e| e.to_string())?;
+    }
     // 保存到配置文件
     let settings_json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
     
-    let config_path = app.path().app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("wida_api_settings.json");
-    
-    // 确保目录存在
-    if let Some(parent) = config_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
+    let config_path = crate::paths::app_data_file(&app, "wida_api_settings.json")?;
     
     std::fs::write(&config_path, settings_json).map_err(|e| e.to_string())?;
     
@@ -718,11 +1497,12 @@ pub async fn save_api_settings(
 pub async fn load_api_settings(
     app: tauri::AppHandle,
 ) -> Result<ApiSettings, String> {
-    use tauri::Manager;
-    
-    let config_path = app.path().app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("wida_api_settings.json");
+    read_api_settings(&app)
+}
+
+/// 读取API设置文件，不存在时返回默认设置
+fn read_api_settings(app: &tauri::AppHandle) -> Result<ApiSettings, String> {
+    let config_path = crate::paths::app_data_file(app, "wida_api_settings.json")?;
     
     if !config_path.exists() {
         // 返回默认设置
@@ -738,3 +1518,24 @@ pub async fn load_api_settings(
     
     Ok(settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 超出太多的题不保存，略超的保留并给出提示
+    #[test]
+    fn test_screen_grade_level() {
+        let easy = "The cat is on the mat. My dog likes to play in the garden.";
+        let academic = "Photosynthesis converts electromagnetic radiation into chemical energy, \
+                        sustaining heterotrophic organisms throughout terrestrial ecosystems.";
+        let long_sentence = "We walk to the park and we play with the ball and then we eat cake. It is fun.";
+        let questions: Vec<(String, String)> =
+            [easy, academic, long_sentence].iter().map(|text| ("grade_1_2".to_string(), text.to_string())).collect();
+        let (kept, warnings) = screen_grade_level(questions, |q| (&q.0, &q.1));
+        assert_eq!(kept.len(), 2);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("第 2 题超出"));
+        assert!(warnings[1].starts_with("第 3 题略超"));
+    }
+}
@@ -1,6 +1,438 @@
-use rusqlite::{Connection, Result as SqliteResult};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
 use std::path::Path;
 
+/// 学习状态导出文件的格式版本
+const LEARNING_STATE_FORMAT_VERSION: i32 = 1;
+
+/// WIDA 测试未指定时，开头加入的示例题数量
+const DEFAULT_SAMPLE_ITEMS: i32 = 2;
+
+/// WIDA 测试未指定时，每道题音频最多播放的次数（含第一次）
+const DEFAULT_MAX_AUDIO_PLAYS: i32 = 2;
+
+/// 错词连续答对该次数后自动归档
+const MISTAKE_GRADUATION_STREAK: i32 = 3;
+/// 熟练度达到该等级后错词自动归档
+const MISTAKE_GRADUATION_LEVEL: i32 = 4;
+/// 熟练度达到该等级算作“已掌握”的词汇
+const KNOWN_WORD_LEVEL: i32 = 3;
+/// 掌握后又跌到该等级及以下算一次遗忘
+const RELAPSE_LEVEL: i32 = 1;
+/// 遗忘达到该次数的单词视为“顽固词”（leech）
+const LEECH_RELAPSES: i32 = 2;
+/// 顽固词答对后的最长复习间隔（天），保证短间隔的加强复习
+const LEECH_MAX_INTERVAL_DAYS: i32 = 2;
+/// 每次提示默认扣除的题目分数
+const DEFAULT_HINT_PENALTY: f64 = 0.2;
+/// 默认提示次数达到该值时，答对也按答错更新熟练度
+const DEFAULT_HINT_MASTERY_THRESHOLD: i32 = 3;
+
+/// 保留策略删除会话后一并清理的子表及其所属会话表
+const RETENTION_CHILD_TABLES: [(&str, &str); 8] = [
+    ("practice_events", "practice_sessions"),
+    ("session_hints", "practice_sessions"),
+    ("keystroke_errors", "practice_sessions"),
+    ("wida_audio_plays", "wida_test_sessions"),
+    ("wida_focus_events", "wida_test_sessions"),
+    ("wida_question_flags", "wida_test_sessions"),
+    ("wida_speaking_transcripts", "wida_test_sessions"),
+    ("mixed_session_results", "mixed_sessions"),
+];
+
+/// 某用户（?1）在某篇文章（?2）上的练习会话
+const ARTICLE_SESSION_IDS: &str = "SELECT id FROM practice_sessions WHERE user_name = ?1 AND article_id = ?2";
+
+/// 题目至少被作答这么多次才参与难度校准
+const CALIBRATION_MIN_ATTEMPTS: i32 = 5;
+/// 实测难度与标注难度相差达到该值时标记
+const CALIBRATION_FLAG_GAP: i32 = 2;
+
+/// 按平均得分率换算实测难度（1-6）：得分率越低越难
+fn empirical_difficulty(p_value: f64) -> i32 {
+    match p_value {
+        p if p >= 0.9 => 1,
+        p if p >= 0.75 => 2,
+        p if p >= 0.6 => 3,
+        p if p >= 0.45 => 4,
+        p if p >= 0.3 => 5,
+        _ => 6,
+    }
+}
+
+/// 入门分级测试的题数，听力和阅读交替出题
+const PLACEMENT_ITEMS: usize = 10;
+/// 各能力等级（1-6）的最低 Scale Score，与完成测试时的等级划分一致
+const PROFICIENCY_MIN_SCORES: [f64; 6] = [100.0, 250.0, 325.0, 400.0, 475.0, 550.0];
+/// 分级测试后按能力等级（1-6）推荐的每日新词数
+const PLACEMENT_DAILY_NEW_WORDS: [i32; 6] = [5, 8, 10, 12, 15, 20];
+
+/// 阶梯法估计能力等级：每题难度答对加 0.5、答错减 0.5 后取平均
+fn placement_level(items: &[crate::models::PlacementItem]) -> Option<i32> {
+    let answered: Vec<f64> = items
+        .iter()
+        .filter_map(|item| item.correct.map(|c| item.difficulty as f64 + if c { 0.5 } else { -0.5 }))
+        .collect();
+    (!answered.is_empty()).then(|| ((answered.iter().sum::<f64>() / answered.len() as f64).round() as i32).clamp(1, 6))
+}
+
+/// 按时间顺序的熟练度等级中，统计从掌握跌回 `RELAPSE_LEVEL` 及以下的次数和最近一次的时间
+fn count_relapses<'a>(levels: impl Iterator<Item = (i32, &'a str)>) -> (i32, Option<String>) {
+    let mut mastered = false;
+    let mut count = 0;
+    let mut last_at = None;
+    for (level, at) in levels {
+        if level >= KNOWN_WORD_LEVEL {
+            mastered = true;
+        } else if mastered && level <= RELAPSE_LEVEL {
+            mastered = false;
+            count += 1;
+            last_at = Some(at.to_string());
+        }
+    }
+    (count, last_at)
+}
+
+/// 按内容匹配熟练度时使用的内容：接受美英拼写差异时统一为美式拼写
+fn mastery_content_key(content: &str, accept_variants: bool) -> String {
+    let content = content.to_lowercase();
+    if accept_variants {
+        return crate::text::spelling_variants::to_american(&content);
+    }
+    content
+}
+
+/// 题库中保存的 JSON 下标数组，为空或格式错误时返回空数组
+fn json_indices(json: Option<String>) -> Vec<i32> {
+    json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+}
+
+/// 听力/阅读题的判分依据
+struct AnswerKey {
+    question_type: String,
+    options: Vec<String>,
+    correct_answer: i32,
+    correct_answers: Vec<i32>,
+    answer_pattern: Option<String>,
+}
+
+impl AnswerKey {
+    /// 得分比例：单选题对错各得 1/0；多选题每选错一项抵消一项选对；简答题见 `short_answer_credit`
+    fn credit(&self, user_answer: &str, option_order: Option<&Vec<usize>>) -> f64 {
+        let original = |displayed: usize| match option_order {
+            Some(order) => order.get(displayed).map(|&i| i as i32),
+            None => Some(displayed as i32),
+        };
+        match self.question_type.as_str() {
+            "multi_select" => {
+                if self.correct_answers.is_empty() {
+                    return 0.0;
+                }
+                let chosen: std::collections::HashSet<i32> =
+                    user_answer.split(',').filter_map(|i| i.trim().parse().ok()).filter_map(original).collect();
+                let hits = chosen.iter().filter(|i| self.correct_answers.contains(i)).count();
+                let wrong = chosen.len() - hits;
+                hits.saturating_sub(wrong) as f64 / self.correct_answers.len() as f64
+            }
+            "short_answer" => short_answer_credit(self.answer_pattern.as_deref().unwrap_or_default(), user_answer),
+            _ => {
+                let chosen = user_answer.trim().parse::<usize>().ok().and_then(original);
+                if chosen == Some(self.correct_answer) { 1.0 } else { 0.0 }
+            }
+        }
+    }
+
+    /// 报告中显示的正确答案
+    fn text(&self) -> String {
+        let option = |i: i32| self.options.get(i as usize).cloned().unwrap_or_default();
+        match self.question_type.as_str() {
+            "multi_select" => self.correct_answers.iter().map(|&i| option(i)).collect::<Vec<_>>().join(", "),
+            "short_answer" => self.answer_pattern.clone().unwrap_or_default(),
+            _ => option(self.correct_answer),
+        }
+    }
+}
+
+/// 简答题得分：`/…/` 为不区分大小写的正则，匹配得满分；否则为逗号分隔的关键词，按命中比例得分
+fn short_answer_credit(pattern: &str, answer: &str) -> f64 {
+    let pattern = pattern.trim();
+    if let Some(regex) = pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')).filter(|p| !p.is_empty()) {
+        return match regex::RegexBuilder::new(regex).case_insensitive(true).build() {
+            Ok(regex) if regex.is_match(answer.trim()) => 1.0,
+            _ => 0.0,
+        };
+    }
+
+    let answer = format!(" {} ", crate::text::tokenize(answer).join(" "));
+    let keywords: Vec<String> = pattern
+        .split(',')
+        .map(|keyword| crate::text::tokenize(keyword).join(" "))
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    if keywords.is_empty() {
+        return 0.0;
+    }
+    let matched = keywords.iter().filter(|keyword| answer.contains(&format!(" {} ", keyword))).count();
+    matched as f64 / keywords.len() as f64
+}
+
+/// 各部分的默认说明文字（学生可见，使用英文）
+fn default_section_instructions(test_type: &str) -> Option<&'static str> {
+    match test_type {
+        "listening" => Some("Listen carefully to each recording, then choose the best answer. The first questions are practice items and do not count toward your score."),
+        "reading" => Some("Read each passage, then answer the questions about it. The first questions are practice items and do not count toward your score."),
+        "speaking" => Some("Listen to or read each prompt, then speak your answer clearly in complete sentences. The first prompts are for practice and are not scored."),
+        "writing" => Some("Read each task and write your response. Stay within the word limit. The first tasks are for practice and are not scored."),
+        _ => None,
+    }
+}
+
+/// 各测试类型对应的题库表
+fn wida_question_table(test_type: &str) -> Option<&'static str> {
+    match test_type {
+        "listening" => Some("wida_listening_questions"),
+        "reading" => Some("wida_reading_questions"),
+        "speaking" => Some("wida_speaking_questions"),
+        "writing" => Some("wida_writing_questions"),
+        _ => None,
+    }
+}
+
+/// 题库搜索用的文本列：题干和听力原文 / 阅读文章
+fn wida_question_text_columns(test_type: &str) -> (&'static str, &'static str) {
+    match test_type {
+        "listening" => ("question_text", "audio_text"),
+        "reading" => ("question_text", "passage"),
+        "speaking" => ("prompt_text", "COALESCE(audio_text, '')"),
+        _ => ("prompt", "task_type"),
+    }
+}
+
+fn validate_blueprint(test_type: &str, sections: &[crate::models::BlueprintSection]) -> SqliteResult<()> {
+    if wida_question_table(test_type).is_none() {
+        return Err(rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)));
+    }
+    if sections.is_empty() {
+        return Err(rusqlite::Error::InvalidParameterName("蓝图至少需要一组题目".into()));
+    }
+    for section in sections {
+        if section.count <= 0
+            || section.min_difficulty < 1
+            || section.max_difficulty > 6
+            || section.min_difficulty > section.max_difficulty
+        {
+            return Err(rusqlite::Error::InvalidParameterName("题目数量需大于 0，难度范围需在 1-6 之间".into()));
+        }
+    }
+    Ok(())
+}
+
+/// 用户角色；teacher 和 admin 可以调用删除、出题和 API 设置等受限命令
+const USER_ROLES: [&str; 3] = ["student", "teacher", "admin"];
+
+/// WIDA 年级段，按年级从低到高
+const WIDA_GRADE_BANDS: [(&str, i32, i32); 4] =
+    [("grade_1_2", 1, 2), ("grade_3_5", 3, 5), ("grade_6_8", 6, 8), ("grade_9_12", 9, 12)];
+
+/// 推荐年级段时参考最近几次测试
+const GRADE_RECOMMENDATION_HISTORY: i32 = 3;
+
+/// 年龄对应的年级（6 岁上一年级），限制在 1-12
+fn grade_for_age(age: i32) -> i32 {
+    (age - 5).clamp(1, 12)
+}
+
+/// 年级所在的 WIDA 年级段下标
+fn grade_band_index(grade: i32) -> usize {
+    WIDA_GRADE_BANDS.iter().position(|(_, _, max)| grade <= *max).unwrap_or(WIDA_GRADE_BANDS.len() - 1)
+}
+
+/// 同音词对比练习连续答对该次数后视为已分清
+const HOMOPHONE_RESOLVE_STREAK: i32 = 3;
+
+/// 单元中已掌握的单词达到该比例时单元算完成
+const CURRICULUM_UNIT_COMPLETE_RATIO: f64 = 0.8;
+/// 实际进度与计划相差不超过该比例时算按计划进行
+const CURRICULUM_PACE_TOLERANCE: f64 = 0.05;
+
+/// 混合练习默认每隔几个拼写题穿插一次听力题
+const MIXED_QUIZ_EVERY: i32 = 5;
+/// 每次穿插的听力题最多数量
+const MIXED_MAX_QUIZ_QUESTIONS: i32 = 2;
+
+/// 速度与正确率分析至少需要的作答数
+const SPEED_ACCURACY_MIN_ITEMS: usize = 15;
+/// 超过该用时（毫秒）的作答视为中途离开，不参与分析
+const SPEED_ACCURACY_MAX_RESPONSE_MS: i64 = 120_000;
+/// 慢速作答的正确率比快速作答高出该比例时，建议放慢速度
+const SLOW_DOWN_ACCURACY_GAIN: f64 = 0.1;
+
+/// 音频复习默认朗读的错词数
+const AUDIO_REVIEW_DEFAULT_LIMIT: i32 = 10;
+/// 音频复习默认每个单词之后留给学生拼写的秒数
+const AUDIO_REVIEW_DEFAULT_GAP_SECONDS: i32 = 8;
+/// 音频复习单词间隔的上限（秒）
+const AUDIO_REVIEW_MAX_GAP_SECONDS: i32 = 60;
+
+/// WIDA 测试中离开窗口达到该次数时在报告中提醒老师
+const INTEGRITY_FLAG_FOCUS_LOSSES: i32 = 3;
+/// WIDA 测试中离开窗口累计达到该秒数时在报告中提醒老师
+const INTEGRITY_FLAG_AWAY_SECONDS: i64 = 60;
+/// 学生反馈题目问题时说明的最大长度
+const QUESTION_FLAG_REASON_MAX_CHARS: usize = 500;
+
+/// PIN 哈希的迭代次数
+const PIN_HASH_ROUNDS: u32 = 10_000;
+
+/// 加盐迭代 SHA-256，返回十六进制摘要
+fn hash_pin(pin: &str, salt: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut digest = Sha256::digest(format!("{}:{}", salt, pin));
+    for _ in 1..PIN_HASH_ROUNDS {
+        digest = Sha256::new().chain_update(digest).chain_update(salt).finalize();
+    }
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn map_blueprint(row: &rusqlite::Row) -> SqliteResult<crate::models::WidaTestBlueprint> {
+    let sections_json: String = row.get(3)?;
+    Ok(crate::models::WidaTestBlueprint {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        test_type: row.get(2)?,
+        sections: serde_json::from_str(&sections_json).unwrap_or_default(),
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// 删除文章时连带删除（外键级联）的数据：表名和筛选条件
+const ARTICLE_SNAPSHOT_TABLES: [(&str, &str); 10] = [
+    ("articles", "id = ?1"),
+    ("article_tags", "article_id = ?1"),
+    ("article_translations", "article_id = ?1"),
+    ("segments", "article_id = ?1"),
+    ("segment_timings", "segment_id IN (SELECT id FROM segments WHERE article_id = ?1)"),
+    ("word_mastery", "segment_id IN (SELECT id FROM segments WHERE article_id = ?1)"),
+    ("mistakes", "segment_id IN (SELECT id FROM segments WHERE article_id = ?1)"),
+    ("practice_progress", "article_id = ?1"),
+    ("practice_history", "article_id = ?1"),
+    ("leaderboard", "article_id = ?1"),
+];
+
+/// 删除前保存的文章及其分词、熟练度等数据，用于撤销删除
+#[derive(Debug, Clone)]
+pub struct ArticleSnapshot {
+    pub article_id: i64,
+    pub title: String,
+    tables: Vec<TableRows>,
+}
+
+/// 快照中某张表的行
+#[derive(Debug, Clone)]
+struct TableRows {
+    table: &'static str,
+    columns: Vec<String>,
+    rows: Vec<Vec<rusqlite::types::Value>>,
+}
+
+fn map_word_image(row: &rusqlite::Row) -> SqliteResult<crate::models::WordImage> {
+    Ok(crate::models::WordImage {
+        word: row.get(0)?,
+        file_path: row.get(1)?,
+        source: row.get(2)?,
+        source_url: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+fn map_reading_recording(row: &rusqlite::Row) -> SqliteResult<crate::models::ReadingRecording> {
+    Ok(crate::models::ReadingRecording {
+        id: row.get(0)?,
+        user_name: row.get(1)?,
+        article_id: row.get(2)?,
+        status: row.get(3)?,
+        file_path: row.get(4)?,
+        word_count: row.get(5)?,
+        duration_ms: row.get(6)?,
+        wpm: row.get(7)?,
+        started_at: row.get(8)?,
+        completed_at: row.get(9)?,
+    })
+}
+
+/// SM-2 中视为记住的最低质量等级
+const SM2_PASSING_QUALITY: i32 = 3;
+
+/// 完整 SM-2 公式：按质量等级（0-5）调整难度因子，答对时间隔按难度因子倍增
+///
+/// 第一次答对间隔 1 天，第二次 6 天，之后为上次间隔 × 难度因子；答错间隔归零，当天重新复习。
+fn sm2_schedule(ease_factor: f64, interval_days: i32, quality: i32) -> (f64, i32) {
+    let q = (5 - quality) as f64;
+    let ease_factor = (ease_factor + 0.1 - q * (0.08 + q * 0.02)).max(1.3);
+    let interval_days = if quality < SM2_PASSING_QUALITY {
+        0
+    } else {
+        match interval_days {
+            ..=0 => 1,
+            1 => 6,
+            n => (n as f64 * ease_factor).round() as i32,
+        }
+    };
+    (ease_factor, interval_days)
+}
+
+/// 逾期复习答对时额外增加的间隔：逾期天数的一半，最多与新间隔相同（即最多翻倍）
+fn overdue_credit(interval_days: i32, overdue_days: i64) -> i32 {
+    if interval_days <= 0 || overdue_days <= 0 {
+        return 0;
+    }
+    ((overdue_days / 2) as i32).min(interval_days)
+}
+
+/// 保持率统计的间隔分组（天，含两端；最后一组无上限）
+const RETENTION_BUCKETS: [(i64, Option<i64>); 7] =
+    [(0, Some(0)), (1, Some(1)), (2, Some(3)), (4, Some(7)), (8, Some(14)), (15, Some(30)), (31, None)];
+
+/// 只读查询最多返回的行数
+const READONLY_QUERY_MAX_ROWS: usize = 1000;
+/// 只读查询的执行时间上限，超时中断，避免无限递归的 CTE 一直占用数据库锁
+const READONLY_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// 每执行这么多条虚拟机指令检查一次是否超时
+const READONLY_QUERY_PROGRESS_STEPS: i32 = 10_000;
+
+/// 存放 PIN 的 users 表，只读查询中不允许出现
+static USERS_TABLE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+    regex::RegexBuilder::new(r"\busers\b").case_insensitive(true).build().expect("valid regex")
+});
+
+/// 检查只读查询：只允许单条 SELECT / WITH 语句，且不能读取存放 PIN 的 users 表
+fn validate_readonly_sql(sql: &str) -> SqliteResult<&str> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    let invalid = |reason: &str| rusqlite::Error::InvalidParameterName(reason.to_string());
+    let keyword = sql.split_whitespace().next().unwrap_or_default().to_lowercase();
+    if keyword != "select" && keyword != "with" {
+        return Err(invalid("只允许 SELECT 查询"));
+    }
+    if sql.contains(';') {
+        return Err(invalid("一次只能执行一条查询"));
+    }
+    if USERS_TABLE.is_match(sql) {
+        return Err(invalid("不允许查询 users 表"));
+    }
+    Ok(sql)
+}
+
+fn sql_value_to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()).into(),
+    }
+}
+
 pub struct DatabaseManager {
     conn: Connection,
 }
@@ -14,6 +446,8 @@ impl DatabaseManager {
     }
 
     fn initialize_schema(&self) -> SqliteResult<()> {
+        // 升级前的数据库没有熟练度事件表，建表后为已有熟练度补录一次事件
+        let backfill_mastery_events = !self.table_exists("mastery_events")?;
         self.conn.execute_batch(
             r#"
             -- 文章表
@@ -105,6 +539,191 @@ impl DatabaseManager {
             CREATE INDEX IF NOT EXISTS idx_word_mastery_user ON word_mastery(user_name);
             CREATE INDEX IF NOT EXISTS idx_word_mastery_review ON word_mastery(next_review_at);
 
+            -- 熟练度变更事件（只追加，每次更新熟练度记一条；不随分词删除，保留历史曲线）
+            CREATE TABLE IF NOT EXISTS mastery_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                segment_id INTEGER NOT NULL,
+                segment_content TEXT NOT NULL,
+                old_level INTEGER,                    -- NULL 表示迁移前已有的等级
+                new_level INTEGER NOT NULL,
+                correct INTEGER,                      -- NULL 表示迁移补录
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_mastery_events_user ON mastery_events(user_name, created_at);
+            CREATE INDEX IF NOT EXISTS idx_mastery_events_segment ON mastery_events(user_name, segment_id);
+
+            CREATE TRIGGER IF NOT EXISTS mastery_events_no_update BEFORE UPDATE ON mastery_events
+            BEGIN
+                SELECT RAISE(ABORT, 'mastery_events is append-only');
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS mastery_events_no_delete BEFORE DELETE ON mastery_events
+            BEGIN
+                SELECT RAISE(ABORT, 'mastery_events is append-only');
+            END;
+
+            -- 每日新词上限（未设置的用户不限制）
+            CREATE TABLE IF NOT EXISTS new_word_pacing (
+                user_name TEXT PRIMARY KEY,
+                daily_limit INTEGER NOT NULL,
+                carryover INTEGER NOT NULL DEFAULT 1   -- 前一天没用完的名额是否顺延
+            );
+
+            -- 按内容合并熟练度：开启后同一用户相同内容和类型的分词共用一条熟练度记录
+            CREATE TABLE IF NOT EXISTS mastery_settings (
+                user_name TEXT PRIMARY KEY,
+                content_keyed INTEGER NOT NULL DEFAULT 0,
+                spelling_variants INTEGER NOT NULL DEFAULT 0  -- 美式和英式拼写视为同一个词
+            );
+
+            -- 假期暂停复习：假期结束后把期间及之后到期的复习整体顺延
+            CREATE TABLE IF NOT EXISTS review_pauses (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL,
+                from_date TEXT NOT NULL,           -- YYYY-MM-DD，含当天
+                to_date TEXT NOT NULL,             -- YYYY-MM-DD，含当天
+                applied_at TEXT,                   -- 顺延完成的时间，未完成时为空
+                shifted_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_review_pauses_user ON review_pauses(user_name, applied_at);
+
+            -- 无障碍设置（延长时间、语速上限、放大显示、小批量练习）
+            CREATE TABLE IF NOT EXISTS user_accommodations (
+                user_name TEXT PRIMARY KEY,
+                time_multiplier REAL NOT NULL DEFAULT 1.0,
+                max_tts_rate INTEGER,
+                text_scale REAL NOT NULL DEFAULT 1.0,
+                batch_size INTEGER
+            );
+
+            -- 打字设置（键盘布局，用于区分按错相邻键和拼写错误）
+            CREATE TABLE IF NOT EXISTS typing_settings (
+                user_name TEXT PRIMARY KEY,
+                keyboard_layout TEXT NOT NULL DEFAULT 'qwerty'
+            );
+
+            -- 练习会话中按错的字符
+            CREATE TABLE IF NOT EXISTS keystroke_errors (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                user_name TEXT NOT NULL,
+                expected TEXT NOT NULL,            -- 应输入的字母（小写）
+                typed TEXT NOT NULL,
+                kind TEXT NOT NULL,                -- 'slip' 按到相邻键 | 'spelling' 拼写错误
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_keystroke_errors_user ON keystroke_errors(user_name, expected);
+
+            -- 写成同音词的记录（their → there），两个词一起安排对比练习
+            CREATE TABLE IF NOT EXISTS homophone_confusions (
+                user_name TEXT NOT NULL,
+                word TEXT NOT NULL,                -- 应写的词
+                confused_with TEXT NOT NULL,       -- 实际写成的同音词
+                confusion_count INTEGER NOT NULL DEFAULT 1,
+                correct_streak INTEGER NOT NULL DEFAULT 0,
+                last_confused_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_name, word, confused_with)
+            );
+
+            -- 用户档案（可选 PIN，只保存加盐哈希）
+            CREATE TABLE IF NOT EXISTS users (
+                user_name TEXT PRIMARY KEY,
+                pin_hash TEXT,
+                pin_salt TEXT,
+                created_at TEXT DEFAULT (datetime('now')),
+                updated_at TEXT DEFAULT (datetime('now')),
+                role TEXT NOT NULL DEFAULT 'student', -- student | teacher | admin
+                birth_year INTEGER
+            );
+
+            -- 单词配图（本地附加或从图片 API 下载，文件保存在应用数据目录）
+            CREATE TABLE IF NOT EXISTS word_images (
+                word TEXT PRIMARY KEY,                -- 小写单词
+                file_path TEXT NOT NULL,
+                source TEXT NOT NULL,                 -- local | api
+                source_url TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- 朗读文章的录音（流利度跟踪）
+            CREATE TABLE IF NOT EXISTS reading_recordings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                article_id INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'recording', -- recording | completed
+                file_path TEXT,
+                word_count INTEGER NOT NULL,          -- 朗读的单词数
+                duration_ms INTEGER,
+                wpm REAL,
+                started_at TEXT DEFAULT (datetime('now')),
+                completed_at TEXT,
+                audio TEXT                            -- JSON：解码出的时长和波形
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_reading_recordings_user ON reading_recordings(user_name, article_id);
+
+            -- 听力提速训练中每道题的朗读语速和对错
+            CREATE TABLE IF NOT EXISTS speed_rate_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                user_name TEXT NOT NULL,
+                rate INTEGER NOT NULL,
+                correct INTEGER NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_speed_rate_history_user ON speed_rate_history(user_name, session_id);
+
+            -- 删除等破坏性操作的审计记录
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT,                       -- 操作人，未知时为空
+                action TEXT NOT NULL,                 -- delete | restore | prune | recalibrate | relocate
+                target_type TEXT NOT NULL,            -- article | wida_session | wida_question | blueprint | assignment | retention | data_dir
+                target_id INTEGER NOT NULL,
+                details TEXT,                         -- 被删除对象的摘要（标题、题干等）
+                created_at TEXT DEFAULT (datetime('now', 'localtime'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_audit_log_date ON audit_log(created_at DESC);
+
+            -- 每天已发放的新词（用于计算当天剩余名额）
+            CREATE TABLE IF NOT EXISTS new_word_log (
+                user_name TEXT NOT NULL,
+                segment_id INTEGER NOT NULL,
+                served_on TEXT NOT NULL,              -- 本地日期 YYYY-MM-DD
+                PRIMARY KEY (user_name, served_on, segment_id)
+            );
+
+            -- 已作答单词的记录：当天答过的单词（无论对错）不再由调度重复出题，只保留当天的记录
+            CREATE TABLE IF NOT EXISTS served_log (
+                user_name TEXT NOT NULL,
+                segment_id INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                served_at TEXT NOT NULL               -- 本地时间 YYYY-MM-DD HH:MM:SS
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_served_log_user ON served_log(user_name, served_at);
+
+            -- 练习会话中每题的作答事件：对错和作答用时，用于分析速度与正确率的关系
+            CREATE TABLE IF NOT EXISTS practice_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL,
+                session_id INTEGER NOT NULL,
+                item_index INTEGER NOT NULL,
+                segment_id INTEGER NOT NULL,
+                correct INTEGER NOT NULL,
+                response_ms INTEGER NOT NULL,          -- 从出题（上一题作答或会话开始）到提交的用时
+                created_at TEXT NOT NULL               -- UTC，精确到毫秒
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_practice_events_user ON practice_events(user_name, created_at);
+
             -- 练习历史记录表
             CREATE TABLE IF NOT EXISTS practice_history (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -124,6 +743,149 @@ impl DatabaseManager {
             CREATE INDEX IF NOT EXISTS idx_practice_history_user ON practice_history(user_name);
             CREATE INDEX IF NOT EXISTS idx_practice_history_date ON practice_history(completed_at DESC);
 
+            -- 按月汇总的练习记录（清理过期 practice_history 前先汇总到这里）
+            CREATE TABLE IF NOT EXISTS practice_history_rollups (
+                user_name TEXT NOT NULL,
+                month TEXT NOT NULL,               -- YYYY-MM
+                segment_type TEXT NOT NULL,
+                practice_count INTEGER NOT NULL DEFAULT 0,
+                correct_count INTEGER NOT NULL DEFAULT 0,
+                incorrect_count INTEGER NOT NULL DEFAULT 0,
+                total_count INTEGER NOT NULL DEFAULT 0,
+                accuracy_sum REAL NOT NULL DEFAULT 0,
+                wpm_sum REAL NOT NULL DEFAULT 0,
+                duration_seconds INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (user_name, month, segment_type)
+            );
+
+            -- 作业表（教师面板布置）
+            CREATE TABLE IF NOT EXISTS assignments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                article_id INTEGER NOT NULL,
+                segment_type TEXT NOT NULL,
+                title TEXT NOT NULL,
+                due_date TEXT,                     -- 截止日期（可选）
+                status TEXT NOT NULL DEFAULT 'pending', -- 'pending' | 'completed'
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                completed_at TEXT,
+                check_options TEXT,                -- JSON：覆盖练习会话的答案检查设置
+                FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_assignments_user ON assignments(user_name, status);
+
+            -- 课程计划：按顺序排列的单元，每个单元包含若干文章/单词表和目标完成日期
+            CREATE TABLE IF NOT EXISTS curriculum_units (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                title TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                target_date TEXT NOT NULL,          -- YYYY-MM-DD
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_curriculum_units_user ON curriculum_units(user_name, position);
+
+            CREATE TABLE IF NOT EXISTS curriculum_unit_articles (
+                unit_id INTEGER NOT NULL,
+                article_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (unit_id, article_id),
+                FOREIGN KEY (unit_id) REFERENCES curriculum_units(id) ON DELETE CASCADE,
+                FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+            );
+
+            -- 练习会话（后端校验输入和按键计分）
+            CREATE TABLE IF NOT EXISTS practice_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                article_id INTEGER NOT NULL,
+                segment_type TEXT NOT NULL,
+                items TEXT NOT NULL DEFAULT '[]',  -- JSON array of {segment_id, content}
+                current_index INTEGER DEFAULT 0,
+                typed TEXT NOT NULL DEFAULT '',
+                keystrokes INTEGER DEFAULT 0,
+                correct_keystrokes INTEGER DEFAULT 0,
+                correct_count INTEGER DEFAULT 0,
+                incorrect_count INTEGER DEFAULT 0,
+                order_mode TEXT,
+                order_seed INTEGER,
+                status TEXT NOT NULL DEFAULT 'active', -- 'active' | 'completed'
+                started_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                checkpointed_at TEXT,
+                goal TEXT,                             -- until_20_correct | 10_minutes | clear_due_queue
+                speed_training INTEGER NOT NULL DEFAULT 0,
+                tts_rate INTEGER,                      -- 提速训练当前的朗读语速
+                speed_streak INTEGER NOT NULL DEFAULT 0, -- 当前语速下连续答对的题数
+                hint_penalty REAL NOT NULL DEFAULT 0.2, -- 每次提示扣除的题目分数
+                hint_mastery_threshold INTEGER NOT NULL DEFAULT 3,
+                score_points REAL NOT NULL DEFAULT 0,  -- 扣除提示后的累计得分
+                chunked INTEGER NOT NULL DEFAULT 0,    -- 按音节分块练习
+                check_options TEXT                     -- JSON：答案检查的严格程度
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_practice_sessions_user ON practice_sessions(user_name, status);
+
+            -- 练习会话中每道题使用的提示次数
+            CREATE TABLE IF NOT EXISTS session_hints (
+                session_id INTEGER NOT NULL,
+                item_index INTEGER NOT NULL,
+                hint_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (session_id, item_index),
+                FOREIGN KEY (session_id) REFERENCES practice_sessions(id) ON DELETE CASCADE
+            );
+
+            -- 文章标签
+            CREATE TABLE IF NOT EXISTS article_tags (
+                article_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (article_id, tag),
+                FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_article_tags_tag ON article_tags(tag);
+
+            -- 文章译文（按段落与原文对齐）
+            CREATE TABLE IF NOT EXISTS article_translations (
+                article_id INTEGER NOT NULL,
+                language TEXT NOT NULL,
+                paragraph_index INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                PRIMARY KEY (article_id, language, paragraph_index),
+                FOREIGN KEY (article_id) REFERENCES articles(id) ON DELETE CASCADE
+            );
+
+            -- 分词时间轴（字幕导入，毫秒）
+            CREATE TABLE IF NOT EXISTS segment_timings (
+                segment_id INTEGER PRIMARY KEY,
+                start_ms INTEGER NOT NULL,
+                end_ms INTEGER NOT NULL,
+                FOREIGN KEY (segment_id) REFERENCES segments(id) ON DELETE CASCADE
+            );
+
+            -- 用户自定义的 AI 出题提示词模板
+            CREATE TABLE IF NOT EXISTS prompt_templates (
+                test_type TEXT PRIMARY KEY,
+                template TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- AI 接口调用用量
+            CREATE TABLE IF NOT EXISTS ai_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider TEXT NOT NULL,               -- API 域名
+                model TEXT NOT NULL,
+                purpose TEXT NOT NULL,                -- 调用用途，如 generate_reading
+                prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                estimated_cost REAL,                  -- 美元，未知模型为空
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_ai_usage_date ON ai_usage(created_at);
+
             -- ========== WIDA 测试模块表 ==========
 
             -- WIDA 听力题库
@@ -206,6 +968,117 @@ impl DatabaseManager {
                 duration_seconds INTEGER DEFAULT 0
             );
 
+            -- WIDA 题目标签（自由填写，如 "animals unit"，用于组成专题题库）
+            CREATE TABLE IF NOT EXISTS wida_question_tags (
+                test_type TEXT NOT NULL,
+                question_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (test_type, question_id, tag)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_wida_question_tags_tag ON wida_question_tags(tag);
+
+            -- WIDA 测试蓝图（各学科领域、难度的题目数量）
+            CREATE TABLE IF NOT EXISTS wida_test_blueprints (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                test_type TEXT NOT NULL,
+                sections TEXT NOT NULL,            -- JSON array of BlueprintSection
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- WIDA 各部分的说明文字（覆盖默认说明）
+            CREATE TABLE IF NOT EXISTS wida_section_instructions (
+                test_type TEXT PRIMARY KEY,
+                instructions TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- WIDA 测试中每道题的音频播放次数
+            CREATE TABLE IF NOT EXISTS wida_audio_plays (
+                session_id INTEGER NOT NULL,
+                question_id INTEGER NOT NULL,
+                play_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (session_id, question_id)
+            );
+
+            -- 学生在测试中反馈的问题题目（看不懂、答案有误等），老师/出题人在复核队列中处理
+            CREATE TABLE IF NOT EXISTS wida_question_flags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                user_name TEXT NOT NULL,
+                test_type TEXT NOT NULL,
+                question_id INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open', -- 'open' | 'resolved' | 'dismissed'
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                resolved_at TEXT,
+                UNIQUE (session_id, question_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_wida_question_flags_question ON wida_question_flags(test_type, question_id);
+
+            -- WIDA 测试中离开窗口（失去焦点）和暂停的记录，供老师查看测试完整性
+            CREATE TABLE IF NOT EXISTS wida_focus_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,          -- 'focus_loss' | 'pause'
+                question_id INTEGER,               -- 发生时正在作答的题目
+                duration_ms INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_wida_focus_events_session ON wida_focus_events(session_id);
+
+            -- WIDA 口语题的录音转写：语音识别结果经老师/学生修改确认后用于评分
+            CREATE TABLE IF NOT EXISTS wida_speaking_transcripts (
+                session_id INTEGER NOT NULL,
+                question_id INTEGER NOT NULL,
+                stt_text TEXT,                     -- 语音识别的原始结果
+                transcript TEXT NOT NULL,          -- 当前（可能已修改的）转写
+                confidence REAL,                   -- 语音识别的可信度（0-1）
+                status TEXT NOT NULL DEFAULT 'pending', -- 'pending' | 'confirmed'
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                confirmed_at TEXT,
+                PRIMARY KEY (session_id, question_id)
+            );
+
+            -- 混合练习：拼写题中穿插 1-2 道听力小测验
+            CREATE TABLE IF NOT EXISTS mixed_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                article_id INTEGER NOT NULL,
+                segment_type TEXT NOT NULL,
+                items TEXT NOT NULL,               -- JSON array of [kind, segment_id | question_id]
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- 混合练习每题的结果（拼写题同时更新熟练度）
+            CREATE TABLE IF NOT EXISTS mixed_session_results (
+                session_id INTEGER NOT NULL,
+                item_index INTEGER NOT NULL,
+                kind TEXT NOT NULL,                -- 'spelling' | 'listening'
+                item_id INTEGER NOT NULL,
+                answer TEXT NOT NULL,
+                credit REAL NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (session_id, item_index),
+                FOREIGN KEY (session_id) REFERENCES mixed_sessions(id) ON DELETE CASCADE
+            );
+
+            -- 音频复习：只朗读错词，学生口头或在纸上拼写，结束后自评
+            CREATE TABLE IF NOT EXISTS audio_review_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL DEFAULT 'default',
+                items TEXT NOT NULL,               -- JSON array of segment_id
+                gap_seconds INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'active', -- 'active' | 'stopped' | 'completed'
+                started_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                completed_at TEXT
+            );
+
             CREATE INDEX IF NOT EXISTS idx_wida_sessions_user ON wida_test_sessions(user_name);
             CREATE INDEX IF NOT EXISTS idx_wida_sessions_status ON wida_test_sessions(status);
 
@@ -224,18 +1097,127 @@ impl DatabaseManager {
                 completed_at TEXT DEFAULT CURRENT_TIMESTAMP
             );
 
-            CREATE INDEX IF NOT EXISTS idx_wida_history_user ON wida_test_history(user_name);
-            CREATE INDEX IF NOT EXISTS idx_wida_history_date ON wida_test_history(completed_at DESC);
+            -- 入门分级测试（听力、阅读交替的自适应短测试）
+            CREATE TABLE IF NOT EXISTS placement_tests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_name TEXT NOT NULL,
+                grade_level TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'in_progress', -- in_progress | completed
+                difficulty INTEGER NOT NULL,                -- 下一题的难度
+                items TEXT NOT NULL DEFAULT '[]',           -- JSON：已出的题及是否答对
+                proficiency_level INTEGER,
+                started_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                completed_at TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_wida_history_user ON wida_test_history(user_name);
+            CREATE INDEX IF NOT EXISTS idx_wida_history_date ON wida_test_history(completed_at DESC);
+            "#,
+        )?;
+        self.migrate_schema(backfill_mastery_events)?;
+        Ok(())
+    }
+
+    fn table_exists(&self, table: &str) -> SqliteResult<bool> {
+        self.conn.query_row("SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?", [table], |row| row.get(0))
+    }
+
+    /// 为旧数据库补充后续版本新增的列，并创建依赖这些列的视图
+    fn migrate_schema(&self, backfill_mastery_events: bool) -> SqliteResult<()> {
+        self.ensure_column("mistakes", "correct_streak", "INTEGER DEFAULT 0")?;
+        self.ensure_column("mistakes", "archived_at", "TEXT")?;
+        self.ensure_column("mistakes", "archive_reason", "TEXT")?;
+        self.ensure_column("practice_progress", "order_mode", "TEXT")?;
+        self.ensure_column("practice_progress", "order_seed", "INTEGER")?;
+        self.ensure_column("practice_history", "goal", "TEXT")?;
+        self.ensure_column("practice_history", "goal_met", "INTEGER")?;
+        self.ensure_column("wida_listening_questions", "source_article_id", "INTEGER")?;
+        self.ensure_column("wida_reading_questions", "source_article_id", "INTEGER")?;
+        self.ensure_column("wida_test_sessions", "option_orders", "TEXT")?;
+        self.ensure_column("wida_test_sessions", "max_audio_plays", "INTEGER")?;
+        self.ensure_column("wida_test_sessions", "sample_count", "INTEGER NOT NULL DEFAULT 0")?;
+        for table in ["wida_listening_questions", "wida_reading_questions", "wida_speaking_questions", "wida_writing_questions"] {
+            self.ensure_column(table, "is_sample", "INTEGER NOT NULL DEFAULT 0")?;
+            self.ensure_column(table, "seed_id", "INTEGER")?; // 内置题库中的编号，用户生成的题目为空
+            self.conn.execute(&format!("CREATE UNIQUE INDEX IF NOT EXISTS idx_{0}_seed ON {0}(seed_id)", table), [])?;
+        }
+        self.ensure_column("wida_listening_questions", "question_type", "TEXT NOT NULL DEFAULT 'multiple_choice'")?;
+        self.ensure_column("wida_listening_questions", "correct_answers", "TEXT")?;
+        self.ensure_column("wida_listening_questions", "answer_pattern", "TEXT")?;
+        self.ensure_column("wida_reading_questions", "correct_answers", "TEXT")?;
+        self.ensure_column("wida_reading_questions", "answer_pattern", "TEXT")?;
+        self.ensure_column("word_mastery", "pronunciation_score", "REAL")?;
+        self.ensure_column("word_mastery", "pronunciation_phonemes", "TEXT")?;
+        self.ensure_column("word_mastery", "pronunciation_at", "TEXT")?;
+        let readability_added = self.ensure_column("articles", "reading_grade", "REAL")?;
+        self.ensure_column("articles", "avg_sentence_length", "REAL")?;
+        self.ensure_column("articles", "type_token_ratio", "REAL")?;
+        let language_added = self.ensure_column("articles", "language", "TEXT")?;
+        let segment_language_added = self.ensure_column("segments", "language", "TEXT")?;
+        let mastery_language_added = self.ensure_column("word_mastery", "language", "TEXT")?;
+        self.ensure_column("practice_history", "mode", "TEXT NOT NULL DEFAULT 'typing'")?; // 'typing' | 'audio_review'
+        // 新分词沿用文章的语言，新熟练度记录沿用分词的语言
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_word_mastery_language ON word_mastery(user_name, language);
+
+             CREATE TRIGGER IF NOT EXISTS segments_language AFTER INSERT ON segments WHEN NEW.language IS NULL
+             BEGIN
+                 UPDATE segments SET language = (SELECT language FROM articles WHERE id = NEW.article_id) WHERE id = NEW.id;
+             END;
+
+             CREATE TRIGGER IF NOT EXISTS word_mastery_language AFTER INSERT ON word_mastery WHEN NEW.language IS NULL
+             BEGIN
+                 UPDATE word_mastery SET language = (SELECT language FROM segments WHERE id = NEW.segment_id) WHERE id = NEW.id;
+             END;"
+        )?;
+        if readability_added || language_added {
+            self.refresh_all_readability()?;
+        } else if segment_language_added || mastery_language_added {
+            self.conn.execute_batch(
+                "UPDATE segments SET language = (SELECT language FROM articles WHERE id = segments.article_id);
+                 UPDATE word_mastery SET language = (SELECT language FROM segments WHERE id = word_mastery.segment_id);"
+            )?;
+        }
+
+        self.conn.execute_batch(
+            r#"
+            -- 已归档（毕业）的错词
+            CREATE VIEW IF NOT EXISTS archived_mistakes AS
+                SELECT id, user_name, segment_id, segment_content, segment_type, error_count,
+                       last_error_at, archived_at, archive_reason
+                FROM mistakes WHERE archived_at IS NOT NULL;
             "#,
         )?;
+        if backfill_mastery_events {
+            // 为已有熟练度补一条事件，以上次复习时间作为达到该等级的时间
+            self.conn.execute(
+                "INSERT INTO mastery_events (user_name, segment_id, segment_content, old_level, new_level, correct, created_at)
+                 SELECT user_name, segment_id, segment_content, NULL, mastery_level, NULL, COALESCE(last_review_at, CURRENT_TIMESTAMP)
+                 FROM word_mastery WHERE mastery_level > 0",
+                [],
+            )?;
+        }
         Ok(())
     }
 
+    /// 缺少该列时添加，返回是否新加了列
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> SqliteResult<bool> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        if !exists {
+            self.conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        }
+        Ok(!exists)
+    }
+
     // ========== 文章管理 ==========
 
     pub fn get_articles(&self) -> SqliteResult<Vec<crate::models::Article>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, created_at, updated_at FROM articles ORDER BY updated_at DESC"
+            "SELECT id, title, content, created_at, updated_at, reading_grade, avg_sentence_length, type_token_ratio, language FROM articles ORDER BY updated_at DESC"
         )?;
         let articles = stmt.query_map([], |row| {
             Ok(crate::models::Article {
@@ -244,6 +1226,10 @@ impl DatabaseManager {
                 content: row.get(2)?,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
+                reading_grade: row.get(5)?,
+                avg_sentence_length: row.get(6)?,
+                type_token_ratio: row.get(7)?,
+                language: row.get(8)?,
             })
         })?.collect::<SqliteResult<Vec<_>>>();
         articles
@@ -251,7 +1237,7 @@ impl DatabaseManager {
 
     pub fn get_article(&self, id: i64) -> SqliteResult<Option<crate::models::Article>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, created_at, updated_at FROM articles WHERE id = ?"
+            "SELECT id, title, content, created_at, updated_at, reading_grade, avg_sentence_length, type_token_ratio, language FROM articles WHERE id = ?"
         )?;
         let mut articles = stmt.query_map([id], |row| {
             Ok(crate::models::Article {
@@ -260,6 +1246,10 @@ impl DatabaseManager {
                 content: row.get(2)?,
                 created_at: row.get(3)?,
                 updated_at: row.get(4)?,
+                reading_grade: row.get(5)?,
+                avg_sentence_length: row.get(6)?,
+                type_token_ratio: row.get(7)?,
+                language: row.get(8)?,
             })
         })?;
         Ok(articles.next().transpose()?)
@@ -270,7 +1260,9 @@ impl DatabaseManager {
             "INSERT INTO articles (title, content) VALUES (?, ?)",
             [title, content],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        let id = self.conn.last_insert_rowid();
+        self.refresh_readability(id, content)?;
+        Ok(id)
     }
 
     pub fn update_article(&self, id: i64, title: Option<&str>, content: Option<&str>) -> SqliteResult<bool> {
@@ -292,14 +1284,180 @@ impl DatabaseManager {
         } else {
             return Ok(false);
         };
+        if let Some(c) = content {
+            self.refresh_readability(id, c)?;
+        }
         Ok(rows_affected > 0)
     }
 
+    /// 重新检测文章语言并计算可读性指标（只对英文文章计算）
+    fn refresh_readability(&self, id: i64, content: &str) -> SqliteResult<()> {
+        self.apply_article_language(id, crate::text::language::detect_language(content), content)
+    }
+
+    /// 手动指定文章语言（单词表太短时检测不准），同步到分词和熟练度记录
+    pub fn set_article_language(&self, id: i64, language: &str) -> SqliteResult<bool> {
+        let language = language.trim().to_lowercase();
+        if language != "zh" && !crate::text::language::PRACTICE_LANGUAGES.contains(&language.as_str()) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("不支持的语言: {}", language)));
+        }
+        let Some(article) = self.get_article(id)? else {
+            return Ok(false);
+        };
+        self.apply_article_language(id, Some(&language), &article.content)?;
+        Ok(true)
+    }
+
+    fn apply_article_language(&self, id: i64, language: Option<&str>, content: &str) -> SqliteResult<()> {
+        let metrics = if matches!(language, None | Some("en")) { crate::text::readability::analyze(content) } else { None };
+        self.conn.execute(
+            "UPDATE articles SET reading_grade = ?, avg_sentence_length = ?, type_token_ratio = ?, language = ? WHERE id = ?",
+            rusqlite::params![
+                metrics.map(|m| m.grade),
+                metrics.map(|m| m.avg_sentence_length),
+                metrics.map(|m| m.type_token_ratio),
+                language,
+                id
+            ],
+        )?;
+        self.conn.execute("UPDATE segments SET language = ?2 WHERE article_id = ?1", rusqlite::params![id, language])?;
+        self.conn.execute(
+            "UPDATE word_mastery SET language = ?2 WHERE segment_id IN (SELECT id FROM segments WHERE article_id = ?1)",
+            rusqlite::params![id, language],
+        )?;
+        Ok(())
+    }
+
+    fn refresh_all_readability(&self) -> SqliteResult<()> {
+        let mut stmt = self.conn.prepare("SELECT id, content FROM articles")?;
+        let articles = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        for (id, content) in articles {
+            self.refresh_readability(id, &content)?;
+        }
+        Ok(())
+    }
+
     pub fn delete_article(&self, id: i64) -> SqliteResult<bool> {
         let rows = self.conn.execute("DELETE FROM articles WHERE id = ?", [id])?;
         Ok(rows > 0)
     }
 
+    /// 保存文章及其级联数据的快照，文章不存在时返回 None
+    pub fn snapshot_article(&self, id: i64) -> SqliteResult<Option<ArticleSnapshot>> {
+        let Some(article) = self.get_article(id)? else {
+            return Ok(None);
+        };
+        let mut tables = Vec::new();
+        for (table, condition) in ARTICLE_SNAPSHOT_TABLES {
+            let mut stmt = self.conn.prepare(&format!("SELECT * FROM {} WHERE {}", table, condition))?;
+            let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+            let rows = stmt.query_map([id], |row| {
+                (0..columns.len()).map(|i| row.get::<_, rusqlite::types::Value>(i)).collect()
+            })?.collect::<SqliteResult<Vec<Vec<_>>>>()?;
+            tables.push(TableRows { table, columns, rows });
+        }
+        Ok(Some(ArticleSnapshot { article_id: id, title: article.title, tables }))
+    }
+
+    /// 按快照恢复已删除的文章（保留原来的 id），已存在的行不覆盖
+    pub fn restore_article(&mut self, snapshot: &ArticleSnapshot) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        for TableRows { table, columns, rows } in &snapshot.tables {
+            let placeholders = vec!["?"; columns.len()].join(", ");
+            let sql = format!("INSERT OR IGNORE INTO {} ({}) VALUES ({})", table, columns.join(", "), placeholders);
+            let mut stmt = tx.prepare(&sql)?;
+            for row in rows {
+                stmt.execute(rusqlite::params_from_iter(row))?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// 所有文章的正文（用于计算文档频率）
+    pub fn get_all_article_contents(&self) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT content FROM articles")?;
+        let contents = stmt.query_map([], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        contents
+    }
+
+    /// 设置文章标签（覆盖原有标签）
+    pub fn set_article_tags(&mut self, article_id: i64, tags: &[String]) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM article_tags WHERE article_id = ?", [article_id])?;
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            tx.execute(
+                "INSERT OR IGNORE INTO article_tags (article_id, tag) VALUES (?, ?)",
+                rusqlite::params![article_id, tag],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_article_tags(&self, article_id: i64) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT tag FROM article_tags WHERE article_id = ? ORDER BY tag")?;
+        let tags = stmt.query_map([article_id], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        tags
+    }
+
+    /// 获取所有已使用的标签
+    pub fn get_all_tags(&self) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT DISTINCT tag FROM article_tags ORDER BY tag")?;
+        let tags = stmt.query_map([], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        tags
+    }
+
+    /// 保存文章译文，按段落与原文对齐；`content` 为空时删除该语言的译文
+    pub fn save_article_translation(&mut self, article_id: i64, language: &str, content: &str) -> SqliteResult<Vec<crate::models::AlignedParagraph>> {
+        let language = language.trim().to_lowercase();
+        if language.is_empty() || language.len() > 16 {
+            return Err(rusqlite::Error::InvalidParameterName("请填写译文语言，例如 zh".to_string()));
+        }
+        if self.get_article(article_id)?.is_none() {
+            return Err(rusqlite::Error::InvalidParameterName("文章不存在".to_string()));
+        }
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM article_translations WHERE article_id = ?1 AND language = ?2", rusqlite::params![article_id, language])?;
+        for (i, paragraph) in crate::text::split_paragraphs(content).iter().enumerate() {
+            tx.execute(
+                "INSERT INTO article_translations (article_id, language, paragraph_index, content) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![article_id, language, i as i64, paragraph],
+            )?;
+        }
+        tx.commit()?;
+        self.get_aligned_paragraphs(article_id, &language)
+    }
+
+    /// 原文和译文逐段对照；段数不一致时缺少的一侧为空
+    pub fn get_aligned_paragraphs(&self, article_id: i64, language: &str) -> SqliteResult<Vec<crate::models::AlignedParagraph>> {
+        let article = self
+            .get_article(article_id)?
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("文章不存在".to_string()))?;
+        let original = crate::text::split_paragraphs(&article.content);
+        let mut stmt = self.conn.prepare(
+            "SELECT content FROM article_translations WHERE article_id = ?1 AND language = ?2 ORDER BY paragraph_index",
+        )?;
+        let translation = stmt
+            .query_map(rusqlite::params![article_id, language.trim().to_lowercase()], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok((0..original.len().max(translation.len()))
+            .map(|i| crate::models::AlignedParagraph {
+                index: i as i32,
+                original: original.get(i).cloned(),
+                translation: translation.get(i).cloned(),
+            })
+            .collect())
+    }
+
+    /// 文章已有译文的语言
+    pub fn get_translation_languages(&self, article_id: i64) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT DISTINCT language FROM article_translations WHERE article_id = ? ORDER BY language")?;
+        let languages = stmt.query_map([article_id], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        languages
+    }
+
     // ========== 分词管理 ==========
 
     pub fn save_segments(&mut self, article_id: i64, segment_type: &str, segments: &[String]) -> SqliteResult<()> {
@@ -328,7 +1486,12 @@ impl DatabaseManager {
             .collect();
         drop(mastery_stmt);
         
-        // 2. 删除旧的分词（word_mastery 会级联删除）
+        // 2. 删除旧的分词（word_mastery 会级联删除）；时间轴与旧分词一一对应，一并删除
+        tx.execute(
+            "DELETE FROM segment_timings
+             WHERE segment_id IN (SELECT id FROM segments WHERE article_id = ? AND segment_type = ?)",
+            [article_id.to_string(), segment_type.to_string()],
+        )?;
         tx.execute(
             "DELETE FROM segments WHERE article_id = ? AND segment_type = ?",
             [article_id.to_string(), segment_type.to_string()],
@@ -378,9 +1541,26 @@ impl DatabaseManager {
         Ok(())
     }
 
+    pub fn get_segment(&self, segment_id: i64) -> SqliteResult<Option<crate::models::Segment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, article_id, segment_type, content, order_index, language FROM segments WHERE id = ?"
+        )?;
+        let mut segments = stmt.query_map([segment_id], |row| {
+            Ok(crate::models::Segment {
+                id: row.get(0)?,
+                article_id: row.get(1)?,
+                segment_type: row.get(2)?,
+                content: row.get(3)?,
+                order_index: row.get(4)?,
+                language: row.get(5)?,
+            })
+        })?;
+        segments.next().transpose()
+    }
+
     pub fn get_segments(&self, article_id: i64, segment_type: &str) -> SqliteResult<Vec<crate::models::Segment>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, article_id, segment_type, content, order_index FROM segments 
+            "SELECT id, article_id, segment_type, content, order_index, language FROM segments 
              WHERE article_id = ? AND segment_type = ? ORDER BY order_index"
         )?;
         let segments = stmt.query_map([article_id.to_string(), segment_type.to_string()], |row| {
@@ -390,11 +1570,43 @@ impl DatabaseManager {
                 segment_type: row.get(2)?,
                 content: row.get(3)?,
                 order_index: row.get(4)?,
+                language: row.get(5)?,
             })
         })?.collect::<SqliteResult<Vec<_>>>();
         segments
     }
 
+    /// 保存分词时间轴，`timings` 与 order_index 一一对应
+    pub fn save_segment_timings(&mut self, article_id: i64, segment_type: &str, timings: &[(i64, i64)]) -> SqliteResult<()> {
+        let tx = self.conn.transaction()?;
+        for (index, (start_ms, end_ms)) in timings.iter().enumerate() {
+            tx.execute(
+                "INSERT OR REPLACE INTO segment_timings (segment_id, start_ms, end_ms)
+                 SELECT id, ?3, ?4 FROM segments WHERE article_id = ?1 AND segment_type = ?2 AND order_index = ?5",
+                rusqlite::params![article_id, segment_type, start_ms, end_ms, index as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_segment_timings(&self, article_id: i64, segment_type: &str) -> SqliteResult<Vec<crate::models::SegmentTiming>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.order_index, t.start_ms, t.end_ms
+             FROM segments s JOIN segment_timings t ON t.segment_id = s.id
+             WHERE s.article_id = ?1 AND s.segment_type = ?2 ORDER BY s.order_index"
+        )?;
+        let timings = stmt.query_map(rusqlite::params![article_id, segment_type], |row| {
+            Ok(crate::models::SegmentTiming {
+                segment_id: row.get(0)?,
+                order_index: row.get(1)?,
+                start_ms: row.get(2)?,
+                end_ms: row.get(3)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        timings
+    }
+
     #[allow(dead_code)]
     pub fn has_segments(&self, article_id: i64, segment_type: &str) -> SqliteResult<bool> {
         let count: i64 = self.conn.query_row(
@@ -416,19 +1628,23 @@ impl DatabaseManager {
         words_list: &str,
         correct_count: i32,
         incorrect_count: i32,
+        order_mode: Option<&str>,
+        order_seed: Option<u64>,
     ) -> SqliteResult<()> {
         self.conn.execute(
             r#"INSERT OR REPLACE INTO practice_progress 
-               (user_name, article_id, segment_type, current_index, words_list, correct_count, incorrect_count, updated_at)
-               VALUES (?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)"#,
-            [
+               (user_name, article_id, segment_type, current_index, words_list, correct_count, incorrect_count, order_mode, order_seed, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)"#,
+            rusqlite::params![
                 user_name,
-                &article_id.to_string(),
+                article_id,
                 segment_type,
-                &current_index.to_string(),
+                current_index,
                 words_list,
-                &correct_count.to_string(),
-                &incorrect_count.to_string(),
+                correct_count,
+                incorrect_count,
+                order_mode,
+                order_seed.map(|seed| seed as i64),
             ],
         )?;
         Ok(())
@@ -441,7 +1657,7 @@ impl DatabaseManager {
         segment_type: &str,
     ) -> SqliteResult<Option<crate::models::PracticeProgress>> {
         let mut stmt = self.conn.prepare(
-            "SELECT user_name, article_id, segment_type, current_index, words_list, correct_count, incorrect_count 
+            "SELECT user_name, article_id, segment_type, current_index, words_list, correct_count, incorrect_count, order_mode, order_seed 
              FROM practice_progress WHERE user_name = ? AND article_id = ? AND segment_type = ?"
         )?;
         let mut progress = stmt.query_map([user_name, &article_id.to_string(), segment_type], |row| {
@@ -453,6 +1669,8 @@ impl DatabaseManager {
                 words_list: row.get(4)?,
                 correct_count: row.get(5)?,
                 incorrect_count: row.get(6)?,
+                order_mode: row.get(7)?,
+                order_seed: row.get::<_, Option<i64>>(8)?.map(|seed| seed as u64),
             })
         })?;
         Ok(progress.next().transpose()?)
@@ -466,6 +1684,357 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// 预览重置某篇文章的练习数据，返回各类记录数和确认令牌
+    ///
+    /// 令牌由用户、文章和当前记录数推导，数据变化后旧令牌失效。
+    pub fn preview_article_reset(&self, user_name: &str, article_id: i64) -> SqliteResult<crate::models::ArticleResetPreview> {
+        let count = |sql: &str| -> SqliteResult<i32> {
+            self.conn.query_row(sql, rusqlite::params![user_name, article_id], |row| row.get(0))
+        };
+        let progress_count = count("SELECT COUNT(*) FROM practice_progress WHERE user_name = ?1 AND article_id = ?2")?;
+        let session_count = count("SELECT COUNT(*) FROM practice_sessions WHERE user_name = ?1 AND article_id = ?2")?;
+        let mut session_detail_count = 0;
+        for table in Self::practice_session_child_tables() {
+            session_detail_count += count(&format!("SELECT COUNT(*) FROM {} WHERE session_id IN ({})", table, ARTICLE_SESSION_IDS))?;
+        }
+        let mistake_count = count(
+            "SELECT COUNT(*) FROM mistakes WHERE user_name = ?1 AND segment_id IN (SELECT id FROM segments WHERE article_id = ?2)",
+        )?;
+        let mastery_count = count(
+            "SELECT COUNT(*) FROM word_mastery WHERE user_name = ?1 AND segment_id IN (SELECT id FROM segments WHERE article_id = ?2)",
+        )?;
+
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(format!(
+            "reset:{}:{}:{}:{}:{}:{}:{}",
+            user_name, article_id, progress_count, session_count, session_detail_count, mistake_count, mastery_count
+        ));
+        let confirm_token = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+
+        Ok(crate::models::ArticleResetPreview {
+            user_name: user_name.to_string(),
+            article_id,
+            progress_count,
+            session_count,
+            session_detail_count,
+            mistake_count,
+            mastery_count,
+            confirm_token,
+        })
+    }
+
+    /// 属于练习会话的子表（事件、提示、按键错误）
+    fn practice_session_child_tables() -> impl Iterator<Item = &'static str> {
+        RETENTION_CHILD_TABLES.into_iter().filter(|(_, parent)| *parent == "practice_sessions").map(|(table, _)| table)
+    }
+
+    /// 重置某篇文章的练习进度、练习会话（连同会话的子记录）、错词和熟练度（一个事务），需要先通过预览取得确认令牌
+    pub fn reset_article_progress(
+        &mut self,
+        user_name: &str,
+        article_id: i64,
+        confirm_token: &str,
+    ) -> SqliteResult<crate::models::ArticleResetPreview> {
+        let preview = self.preview_article_reset(user_name, article_id)?;
+        if preview.confirm_token != confirm_token {
+            return Err(rusqlite::Error::InvalidParameterName("确认令牌无效或已过期，请重新预览".to_string()));
+        }
+        let tx = self.conn.transaction()?;
+        let params = rusqlite::params![user_name, article_id];
+        tx.execute("DELETE FROM practice_progress WHERE user_name = ?1 AND article_id = ?2", params)?;
+        for table in Self::practice_session_child_tables() {
+            tx.execute(&format!("DELETE FROM {} WHERE session_id IN ({})", table, ARTICLE_SESSION_IDS), params)?;
+        }
+        tx.execute("DELETE FROM practice_sessions WHERE user_name = ?1 AND article_id = ?2", params)?;
+        tx.execute(
+            "DELETE FROM mistakes WHERE user_name = ?1 AND segment_id IN (SELECT id FROM segments WHERE article_id = ?2)",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM word_mastery WHERE user_name = ?1 AND segment_id IN (SELECT id FROM segments WHERE article_id = ?2)",
+            params,
+        )?;
+        tx.commit()?;
+        Ok(preview)
+    }
+
+    // ========== 练习会话 ==========
+
+    pub fn create_practice_session(&self, request: &crate::models::StartPracticeSessionRequest) -> SqliteResult<i64> {
+        let items = serde_json::to_string(&request.items).unwrap_or_else(|_| "[]".to_string());
+        let check_options = request.check_options.as_ref().and_then(|o| serde_json::to_string(o).ok());
+        self.conn.execute(
+            "INSERT INTO practice_sessions (user_name, article_id, segment_type, items, order_mode, order_seed, goal, speed_training, tts_rate,
+                                            hint_penalty, hint_mastery_threshold, chunked, check_options)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                request.user_name,
+                request.article_id,
+                request.segment_type,
+                items,
+                request.order_mode,
+                request.order_seed.map(|seed| seed as i64),
+                request.goal,
+                request.speed_training,
+                request.tts_rate,
+                request.hint_penalty.unwrap_or(DEFAULT_HINT_PENALTY).clamp(0.0, 1.0),
+                request.hint_mastery_threshold.unwrap_or(DEFAULT_HINT_MASTERY_THRESHOLD).max(1),
+                request.chunked,
+                check_options,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_practice_session(&self, session_id: i64) -> SqliteResult<Option<crate::models::PracticeSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, article_id, segment_type, items, current_index, typed, keystrokes, correct_keystrokes,
+                    correct_count, incorrect_count, order_mode, order_seed, status, started_at, updated_at, goal,
+                    speed_training, tts_rate, hint_penalty, hint_mastery_threshold, score_points, chunked, check_options
+             FROM practice_sessions WHERE id = ?"
+        )?;
+        let mut sessions = stmt.query_map([session_id], |row| {
+            let items_json: String = row.get(4)?;
+            Ok(crate::models::PracticeSession {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                article_id: row.get(2)?,
+                segment_type: row.get(3)?,
+                items: serde_json::from_str(&items_json).unwrap_or_default(),
+                current_index: row.get(5)?,
+                typed: row.get(6)?,
+                keystrokes: row.get(7)?,
+                correct_keystrokes: row.get(8)?,
+                correct_count: row.get(9)?,
+                incorrect_count: row.get(10)?,
+                order_mode: row.get(11)?,
+                order_seed: row.get::<_, Option<i64>>(12)?.map(|seed| seed as u64),
+                goal: row.get(16)?,
+                speed_training: row.get(17)?,
+                tts_rate: row.get(18)?,
+                hint_penalty: row.get(19)?,
+                hint_mastery_threshold: row.get(20)?,
+                score_points: row.get(21)?,
+                chunked: row.get(22)?,
+                check_options: row
+                    .get::<_, Option<String>>(23)?
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                status: row.get(13)?,
+                started_at: row.get(14)?,
+                updated_at: row.get(15)?,
+            })
+        })?;
+        sessions.next().transpose()
+    }
+
+    /// 记录当前输入和新增的按键数
+    pub fn record_session_typing(&self, session_id: i64, typed: &str, keystrokes: i32, correct_keystrokes: i32) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE practice_sessions SET typed = ?2, keystrokes = keystrokes + ?3, correct_keystrokes = correct_keystrokes + ?4,
+                    updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            rusqlite::params![session_id, typed, keystrokes, correct_keystrokes],
+        )?;
+        Ok(())
+    }
+
+    /// 记录答题结果：答对进入下一题，全部答完时会话结束
+    pub fn record_session_answer(&self, session_id: i64, correct: bool, total_items: i32) -> SqliteResult<i32> {
+        self.conn.execute(
+            "UPDATE practice_sessions SET
+                correct_count = correct_count + ?2,
+                incorrect_count = incorrect_count + ?3,
+                current_index = current_index + ?2,
+                typed = CASE WHEN ?2 = 1 THEN '' ELSE typed END,
+                status = CASE WHEN current_index + ?2 >= ?4 THEN 'completed' ELSE status END,
+                updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            rusqlite::params![session_id, correct as i32, (!correct) as i32, total_items],
+        )?;
+        self.conn.query_row("SELECT current_index FROM practice_sessions WHERE id = ?", [session_id], |row| row.get(0))
+    }
+
+    /// 记录一次提示，返回该题累计的提示次数
+    pub fn record_session_hint(&self, session_id: i64, item_index: i32) -> SqliteResult<i32> {
+        self.conn.execute(
+            "INSERT INTO session_hints (session_id, item_index, hint_count) VALUES (?1, ?2, 1)
+             ON CONFLICT(session_id, item_index) DO UPDATE SET hint_count = hint_count + 1",
+            rusqlite::params![session_id, item_index],
+        )?;
+        self.session_item_hints(session_id, item_index)
+    }
+
+    /// 某道题已使用的提示次数
+    pub fn session_item_hints(&self, session_id: i64, item_index: i32) -> SqliteResult<i32> {
+        self.conn.query_row(
+            "SELECT COALESCE(SUM(hint_count), 0) FROM session_hints WHERE session_id = ?1 AND item_index = ?2",
+            rusqlite::params![session_id, item_index],
+            |row| row.get(0),
+        )
+    }
+
+    /// 整个会话使用的提示次数
+    pub fn session_hints_used(&self, session_id: i64) -> SqliteResult<i32> {
+        self.conn.query_row(
+            "SELECT COALESCE(SUM(hint_count), 0) FROM session_hints WHERE session_id = ?",
+            [session_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// 累加扣除提示后的题目得分
+    pub fn add_session_score(&self, session_id: i64, points: f64) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE practice_sessions SET score_points = score_points + ?2 WHERE id = ?1",
+            rusqlite::params![session_id, points],
+        )?;
+        Ok(())
+    }
+
+    /// 提速训练中连续答对的题数
+    pub fn session_speed_streak(&self, session_id: i64) -> SqliteResult<i32> {
+        self.conn.query_row("SELECT speed_streak FROM practice_sessions WHERE id = ?", [session_id], |row| row.get(0))
+    }
+
+    /// 记录提速训练中一道题的语速和对错，并保存下一题的语速
+    pub fn record_speed_answer(
+        &self,
+        session_id: i64,
+        user_name: &str,
+        rate: i32,
+        correct: bool,
+        next_rate: i32,
+        streak: i32,
+    ) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO speed_rate_history (session_id, user_name, rate, correct) VALUES (?, ?, ?, ?)",
+            rusqlite::params![session_id, user_name, rate, correct],
+        )?;
+        self.conn.execute(
+            "UPDATE practice_sessions SET tts_rate = ?, speed_streak = ? WHERE id = ?",
+            rusqlite::params![next_rate, streak, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// 每次提速训练的起止语速和答对过的最高语速（最近的在前）
+    pub fn get_listening_speed_history(&self, user_name: &str, limit: i32) -> SqliteResult<Vec<crate::models::ListeningSpeedRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT h.session_id, s.started_at,
+                    (SELECT rate FROM speed_rate_history WHERE session_id = h.session_id ORDER BY id LIMIT 1),
+                    s.tts_rate,
+                    MAX(CASE WHEN h.correct = 1 THEN h.rate END),
+                    COUNT(*)
+             FROM speed_rate_history h JOIN practice_sessions s ON s.id = h.session_id
+             WHERE h.user_name = ?
+             GROUP BY h.session_id
+             ORDER BY h.session_id DESC LIMIT ?"
+        )?;
+        let records = stmt.query_map(rusqlite::params![user_name, limit], |row| {
+            Ok(crate::models::ListeningSpeedRecord {
+                session_id: row.get(0)?,
+                started_at: row.get(1)?,
+                start_rate: row.get(2)?,
+                final_rate: row.get(3)?,
+                comfortable_rate: row.get(4)?,
+                answers: row.get(5)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        records
+    }
+
+    /// 会话已进行的秒数
+    pub fn session_elapsed_seconds(&self, session_id: i64) -> SqliteResult<i64> {
+        self.conn.query_row(
+            "SELECT CAST((julianday('now') - julianday(started_at)) * 86400 AS INTEGER) FROM practice_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// 结束会话并写入练习历史（连同目标及是否达成）
+    pub fn finish_practice_session(&self, session_id: i64, goal_met: Option<bool>) -> SqliteResult<()> {
+        let Some(session) = self.get_practice_session(session_id)? else { return Ok(()) };
+        let duration_seconds = self.session_elapsed_seconds(session_id)?;
+        self.conn.execute(
+            "UPDATE practice_sessions SET status = 'completed', updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            [session_id],
+        )?;
+        let history_id = self.save_practice_history(
+            &session.user_name,
+            session.article_id,
+            &session.segment_type,
+            session.correct_count,
+            session.incorrect_count,
+            duration_seconds as i32,
+        )?;
+        if session.goal.is_some() {
+            self.conn.execute(
+                "UPDATE practice_history SET goal = ?1, goal_met = ?2 WHERE id = ?3",
+                rusqlite::params![session.goal, goal_met, history_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 把会话进度写入 practice_progress（与前端手动保存的进度共用，便于恢复）
+    pub fn checkpoint_practice_session(&self, session_id: i64) -> SqliteResult<()> {
+        let Some(session) = self.get_practice_session(session_id)? else { return Ok(()) };
+        if session.status != "active" {
+            return Ok(());
+        }
+
+        let words: Vec<&str> = session.items.iter().map(|item| item.content.as_str()).collect();
+        let words_list = serde_json::to_string(&words).unwrap_or_else(|_| "[]".to_string());
+        self.save_progress(
+            &session.user_name,
+            session.article_id,
+            &session.segment_type,
+            session.current_index,
+            &words_list,
+            session.correct_count,
+            session.incorrect_count,
+            session.order_mode.as_deref(),
+            session.order_seed,
+        )?;
+        self.conn.execute(
+            "UPDATE practice_sessions SET checkpointed_at = CURRENT_TIMESTAMP WHERE id = ?",
+            [session_id],
+        )?;
+        Ok(())
+    }
+
+    /// 为每个用户最近一次进行中的会话保存检查点（窗口关闭时调用）
+    ///
+    /// 更早的未完成会话已被放弃，不再覆盖新的练习进度。
+    pub fn checkpoint_active_sessions(&self) -> SqliteResult<usize> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM practice_sessions s WHERE status = 'active' AND id = (
+                 SELECT id FROM practice_sessions WHERE user_name = s.user_name AND status = 'active'
+                 ORDER BY updated_at DESC, id DESC LIMIT 1
+             )",
+        )?;
+        let ids = stmt.query_map([], |row| row.get::<_, i64>(0))?.collect::<SqliteResult<Vec<_>>>()?;
+        for id in &ids {
+            self.checkpoint_practice_session(*id)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// 获取用户最近一次未完成的会话（崩溃或异常退出后恢复）
+    pub fn get_last_active_session(&self, user_name: &str) -> SqliteResult<Option<crate::models::PracticeSession>> {
+        let id: Option<i64> = self.conn.query_row(
+            "SELECT id FROM practice_sessions WHERE user_name = ? AND status = 'active' ORDER BY updated_at DESC, id DESC LIMIT 1",
+            [user_name],
+            |row| row.get(0),
+        ).optional()?;
+        match id {
+            Some(id) => self.get_practice_session(id),
+            None => Ok(None),
+        }
+    }
+
     // ========== 错词/错句管理 ==========
 
     pub fn add_mistake(
@@ -479,12 +2048,22 @@ impl DatabaseManager {
             r#"INSERT INTO mistakes (user_name, segment_id, segment_content, segment_type, error_count, last_error_at)
                VALUES (?, ?, ?, ?, 1, CURRENT_TIMESTAMP)
                ON CONFLICT(user_name, segment_id) 
-               DO UPDATE SET error_count = error_count + 1, last_error_at = CURRENT_TIMESTAMP"#,
+               DO UPDATE SET error_count = error_count + 1, last_error_at = CURRENT_TIMESTAMP,
+                             correct_streak = 0, archived_at = NULL, archive_reason = NULL"#,
             [user_name, &segment_id.to_string(), segment_content, segment_type],
         )?;
         Ok(())
     }
 
+    /// 批量记录错词：全部在一个事务中完成，中途退出不会只写入一部分
+    pub fn add_mistakes_batch(&self, mistakes: &[crate::models::AddMistakeRequest]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for m in mistakes {
+            self.add_mistake(&m.user_name, m.segment_id, &m.segment_content, &m.segment_type)?;
+        }
+        tx.commit()
+    }
+
     pub fn remove_mistake(&self, user_name: &str, segment_id: i64) -> SqliteResult<()> {
         self.conn.execute(
             "DELETE FROM mistakes WHERE user_name = ? AND segment_id = ?",
@@ -497,12 +2076,12 @@ impl DatabaseManager {
         let mut stmt = if segment_type.is_some() {
             self.conn.prepare(
                 "SELECT id, user_name, segment_id, segment_content, segment_type, error_count, last_error_at 
-                 FROM mistakes WHERE user_name = ? AND segment_type = ? ORDER BY last_error_at DESC"
+                 FROM mistakes WHERE user_name = ? AND segment_type = ? AND archived_at IS NULL ORDER BY last_error_at DESC"
             )?
         } else {
             self.conn.prepare(
                 "SELECT id, user_name, segment_id, segment_content, segment_type, error_count, last_error_at 
-                 FROM mistakes WHERE user_name = ? ORDER BY last_error_at DESC"
+                 FROM mistakes WHERE user_name = ? AND archived_at IS NULL ORDER BY last_error_at DESC"
             )?
         };
 
@@ -534,11 +2113,58 @@ impl DatabaseManager {
         mistakes
     }
 
-    // ========== 排行榜 ==========
+    /// 记录错词的一次作答：答对累计连对次数，连对或熟练度达标后自动归档；答错清零
+    fn record_mistake_outcome(&self, user_name: &str, segment_id: i64, correct: bool, mastery_level: i32) -> SqliteResult<()> {
+        if !correct {
+            self.conn.execute(
+                "UPDATE mistakes SET correct_streak = 0 WHERE user_name = ?1 AND segment_id = ?2",
+                rusqlite::params![user_name, segment_id],
+            )?;
+            return Ok(());
+        }
 
-    pub fn save_record(
-        &self,
-        user_name: &str,
+        self.conn.execute(
+            "UPDATE mistakes SET correct_streak = correct_streak + 1
+             WHERE user_name = ?1 AND segment_id = ?2 AND archived_at IS NULL",
+            rusqlite::params![user_name, segment_id],
+        )?;
+        self.conn.execute(
+            "UPDATE mistakes SET archived_at = CURRENT_TIMESTAMP,
+                    archive_reason = CASE WHEN ?3 >= ?4 THEN 'mastered' ELSE 'streak' END
+             WHERE user_name = ?1 AND segment_id = ?2 AND archived_at IS NULL
+               AND (correct_streak >= ?5 OR ?3 >= ?4)",
+            rusqlite::params![user_name, segment_id, mastery_level, MISTAKE_GRADUATION_LEVEL, MISTAKE_GRADUATION_STREAK],
+        )?;
+        Ok(())
+    }
+
+    /// 获取已归档的错词（历史记录）
+    pub fn get_archived_mistakes(&self, user_name: &str) -> SqliteResult<Vec<crate::models::ArchivedMistake>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, segment_id, segment_content, segment_type, error_count, last_error_at, archived_at, archive_reason
+             FROM archived_mistakes WHERE user_name = ? ORDER BY archived_at DESC"
+        )?;
+        let mistakes = stmt.query_map([user_name], |row| {
+            Ok(crate::models::ArchivedMistake {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                segment_id: row.get(2)?,
+                segment_content: row.get(3)?,
+                segment_type: row.get(4)?,
+                error_count: row.get(5)?,
+                last_error_at: row.get(6)?,
+                archived_at: row.get(7)?,
+                archive_reason: row.get(8)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        mistakes
+    }
+
+    // ========== 排行榜 ==========
+
+    pub fn save_record(
+        &self,
+        user_name: &str,
         article_id: i64,
         segment_type: &str,
         score: f64,
@@ -558,38 +2184,13 @@ impl DatabaseManager {
         segment_type: Option<&str>,
         limit: i32,
     ) -> SqliteResult<Vec<crate::models::LeaderboardRecord>> {
-        let sql = match (article_id, segment_type) {
-            (Some(aid), Some(st)) => format!(
-                "SELECT l.id, l.user_name, l.article_id, a.title, l.segment_type, l.score, l.accuracy, l.wpm, l.completed_at 
-                 FROM leaderboard l JOIN articles a ON l.article_id = a.id 
-                 WHERE l.article_id = {} AND l.segment_type = '{}' 
-                 ORDER BY l.score DESC LIMIT {}", 
-                aid, st, limit
-            ),
-            (Some(aid), None) => format!(
-                "SELECT l.id, l.user_name, l.article_id, a.title, l.segment_type, l.score, l.accuracy, l.wpm, l.completed_at 
-                 FROM leaderboard l JOIN articles a ON l.article_id = a.id 
-                 WHERE l.article_id = {} 
-                 ORDER BY l.score DESC LIMIT {}", 
-                aid, limit
-            ),
-            (None, Some(st)) => format!(
-                "SELECT l.id, l.user_name, l.article_id, a.title, l.segment_type, l.score, l.accuracy, l.wpm, l.completed_at 
-                 FROM leaderboard l JOIN articles a ON l.article_id = a.id 
-                 WHERE l.segment_type = '{}' 
-                 ORDER BY l.score DESC LIMIT {}", 
-                st, limit
-            ),
-            (None, None) => format!(
-                "SELECT l.id, l.user_name, l.article_id, a.title, l.segment_type, l.score, l.accuracy, l.wpm, l.completed_at 
-                 FROM leaderboard l JOIN articles a ON l.article_id = a.id 
-                 ORDER BY l.score DESC LIMIT {}", 
-                limit
-            ),
-        };
-
-        let mut stmt = self.conn.prepare(&sql)?;
-        let records = stmt.query_map([], |row| {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.id, l.user_name, l.article_id, a.title, l.segment_type, l.score, l.accuracy, l.wpm, l.completed_at
+             FROM leaderboard l JOIN articles a ON l.article_id = a.id
+             WHERE (?1 IS NULL OR l.article_id = ?1) AND (?2 IS NULL OR l.segment_type = ?2)
+             ORDER BY l.score DESC LIMIT ?3",
+        )?;
+        let records = stmt.query_map(rusqlite::params![article_id, segment_type, limit], |row| {
             Ok(crate::models::LeaderboardRecord {
                 id: row.get(0)?,
                 user_name: row.get(1)?,
@@ -605,6 +2206,79 @@ impl DatabaseManager {
         records
     }
 
+    // ========== 自定义复习 ==========
+
+    /// 按任意条件组合复习列表，内容相同的分词只保留一个（课程计划当前单元的、错误次数多、熟练度低的优先）
+    pub fn build_custom_session(&self, filters: &crate::models::CustomSessionFilters) -> SqliteResult<crate::models::ScheduledWordsResponse> {
+        let article_ids = filters.article_ids.as_ref().map(|ids| serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string()));
+        let tags = filters.tags.as_ref().map(|tags| serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string()));
+        let unit_articles = serde_json::to_string(&self.current_unit_article_ids(&filters.user_name)?).unwrap_or_else(|_| "[]".to_string());
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.content, s.segment_type, COALESCE(m.mastery_level, 0), m.next_review_at, s.article_id
+             FROM segments s
+             LEFT JOIN word_mastery m ON m.segment_id = s.id AND m.user_name = ?1
+             LEFT JOIN mistakes mi ON mi.segment_id = s.id AND mi.user_name = ?1 AND mi.archived_at IS NULL
+             WHERE (?2 IS NULL OR s.article_id IN (SELECT value FROM json_each(?2)))
+               AND (?3 IS NULL OR s.article_id IN (SELECT article_id FROM article_tags WHERE tag IN (SELECT value FROM json_each(?3))))
+               AND (?4 IS NULL OR s.segment_type = ?4)
+               AND (?5 IS NULL OR COALESCE(m.mastery_level, 0) >= ?5)
+               AND (?6 IS NULL OR COALESCE(m.mastery_level, 0) <= ?6)
+               AND (?7 IS NULL OR COALESCE(mi.error_count, 0) >= ?7)
+               AND (?8 IS NULL OR m.last_review_at >= ?8)
+               AND (?9 IS NULL OR m.last_review_at IS NULL OR m.last_review_at < ?9)
+               AND (?10 IS NULL OR s.language = ?10)
+             ORDER BY s.article_id IN (SELECT value FROM json_each(?11)) DESC, COALESCE(mi.error_count, 0) DESC, COALESCE(m.mastery_level, 0), s.article_id, s.order_index"
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![
+                filters.user_name,
+                article_ids,
+                tags,
+                filters.segment_type,
+                filters.min_mastery,
+                filters.max_mastery,
+                filters.min_error_count,
+                filters.last_seen_after,
+                filters.last_seen_before,
+                filters.language,
+                unit_articles,
+            ],
+            |row| {
+                let next_review_at: Option<String> = row.get(4)?;
+                Ok(crate::models::ScheduledWord {
+                    segment_id: row.get(0)?,
+                    article_id: row.get(5)?,
+                    content: row.get(1)?,
+                    segment_type: row.get(2)?,
+                    mastery_level: row.get(3)?,
+                    is_new: next_review_at.is_none(),
+                    next_review_at: next_review_at.unwrap_or_else(|| "2999-12-31 23:59:59".to_string()),
+                    is_leech: false,
+                })
+            },
+        )?.collect::<SqliteResult<Vec<_>>>()?;
+
+        let leeches = self.leech_segment_ids(&filters.user_name)?;
+        let mut seen = std::collections::HashSet::new();
+        let mut words: Vec<crate::models::ScheduledWord> = rows
+            .into_iter()
+            .filter(|w| seen.insert(w.content.trim().to_lowercase()))
+            .map(|w| crate::models::ScheduledWord { is_leech: leeches.contains(&w.segment_id), ..w })
+            .collect();
+        if let Some(limit) = filters.limit {
+            words.truncate(limit.max(0) as usize);
+        }
+
+        let new_words_count = words.iter().filter(|w| w.is_new).count() as i32;
+        Ok(crate::models::ScheduledWordsResponse {
+            review_words_count: words.len() as i32 - new_words_count,
+            new_words_count,
+            words,
+            seed: None,
+        })
+    }
+
     // ========== SM-2 间隔重复算法 ==========
 
     /// 获取需要复习的单词（到期 + 新词）
@@ -614,22 +2288,29 @@ impl DatabaseManager {
         article_id: i64,
         segment_type: &str,
         limit: i32,
+        include_answered: bool,
     ) -> SqliteResult<crate::models::ScheduledWordsResponse> {
+        self.apply_review_pauses(user_name)?;
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         
-        // 1. 获取该文章的所有分词
+        // 1. 获取该文章的所有分词；今天已作答的（无论对错）除非明确要求，不再出题
         let mut stmt = self.conn.prepare(
             "SELECT id, content, segment_type FROM segments WHERE article_id = ?1 AND segment_type = ?2"
         )?;
-        let all_segments: Vec<(i64, String, String)> = stmt.query_map(rusqlite::params![article_id, segment_type], |row| {
+        let mut all_segments: Vec<(i64, String, String)> = stmt.query_map(rusqlite::params![article_id, segment_type], |row| {
             Ok((row.get(0)?, row.get(1)?, row.get(2)?))
         })?.collect::<SqliteResult<Vec<_>>>()?;
+        if !include_answered {
+            let (served_ids, served_contents) = self.served_today(user_name)?;
+            all_segments.retain(|(id, content, _)| !served_ids.contains(id) && !served_contents.contains(&content.to_lowercase()));
+        }
         
         if all_segments.is_empty() {
             return Ok(crate::models::ScheduledWordsResponse {
                 words: vec![],
                 new_words_count: 0,
                 review_words_count: 0,
+                seed: None,
             });
         }
         
@@ -638,14 +2319,39 @@ impl DatabaseManager {
             "SELECT segment_id, mastery_level, next_review_at FROM word_mastery 
              WHERE user_name = ?1 AND segment_id IN (SELECT id FROM segments WHERE article_id = ?2 AND segment_type = ?3)"
         )?;
-        let mastery_map: std::collections::HashMap<i64, (i32, String)> = mastery_stmt
+        let mut mastery_map: std::collections::HashMap<i64, (i32, String)> = mastery_stmt
             .query_map(rusqlite::params![user_name, article_id, segment_type], |row| {
                 Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
             })?
             .filter_map(|r| r.ok())
             .map(|(id, (level, next))| (id, (level, next)))
             .collect();
+
+        // 按内容合并时，其他文章里同内容的熟练度也算数
+        if self.content_keyed_mastery(user_name)? {
+            let mut content_stmt = self.conn.prepare(
+                "SELECT lower(segment_content), mastery_level, next_review_at FROM word_mastery
+                 WHERE user_name = ?1 AND segment_type = ?2 ORDER BY last_review_at"
+            )?;
+            let rows = content_stmt
+                .query_map(rusqlite::params![user_name, segment_type], |row| {
+                    Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+                })?
+                .collect::<SqliteResult<Vec<(String, (i32, String))>>>()?;
+            let accept_variants = self.accept_spelling_variants(user_name)?;
+            let mut by_content = std::collections::HashMap::new();
+            for (content, mastery) in rows {
+                by_content.insert(mastery_content_key(&content, accept_variants), mastery);
+            }
+            for (segment_id, content, _) in &all_segments {
+                if let Some(mastery) = by_content.get(&mastery_content_key(content, accept_variants)) {
+                    mastery_map.entry(*segment_id).or_insert_with(|| mastery.clone());
+                }
+            }
+        }
         
+        let leeches = self.leech_segment_ids(user_name)?;
+
         // 3. 分类：到期复习的单词 + 未学习的新单词
         let mut review_words: Vec<crate::models::ScheduledWord> = vec![];
         let mut new_words: Vec<crate::models::ScheduledWord> = vec![];
@@ -659,22 +2365,26 @@ impl DatabaseManager {
                     // 到期，纳入复习
                     review_words.push(crate::models::ScheduledWord {
                         segment_id: *segment_id,
+                        article_id,
                         content: content.clone(),
                         segment_type: seg_type.clone(),
                         mastery_level: *mastery_level,
                         is_new: false,
                         next_review_at: next_review_at.clone(),
+                        is_leech: leeches.contains(segment_id),
                     });
                 }
             } else {
                 // 新单词
                 new_words.push(crate::models::ScheduledWord {
                     segment_id: *segment_id,
+                    article_id,
                     content: content.clone(),
                     segment_type: seg_type.clone(),
                     mastery_level: 0,
                     is_new: true,
                     next_review_at: future_time.to_string(),
+                    is_leech: false,
                 });
             }
         }
@@ -694,6 +2404,23 @@ impl DatabaseManager {
             a.mastery_level.cmp(&b.mastery_level)
         });
         
+        // 每日新词上限：今天已发放过的新词不占名额，其余按剩余名额放行
+        let today = chrono::Local::now().date_naive();
+        if let Some(remaining) = self.new_words_remaining(user_name, today)? {
+            let served = self.new_words_served_on(user_name, today)?;
+            let mut allowance = remaining;
+            new_words.retain(|w| {
+                if served.contains(&w.segment_id) {
+                    true
+                } else if allowance > 0 {
+                    allowance -= 1;
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+
         // 合并逻辑：复习单词优先（已排序），新单词填充剩余位置
         // limit = 0 表示不限制，返回所有单词
         let effective_limit = if limit == 0 { usize::MAX } else { limit as usize };
@@ -708,6 +2435,15 @@ impl DatabaseManager {
             result.extend(new_to_add);
         }
         
+        // 记录今天发放的新词
+        let served_on = today.format("%Y-%m-%d").to_string();
+        for word in result.iter().filter(|w| w.is_new) {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO new_word_log (user_name, segment_id, served_on) VALUES (?, ?, ?)",
+                rusqlite::params![user_name, word.segment_id, served_on],
+            )?;
+        }
+        
         // 统计新词和复习词数量
         let new_count = result.iter().filter(|w| w.is_new).count() as i32;
         let review_count_val = result.iter().filter(|w| !w.is_new).count() as i32;
@@ -716,2103 +2452,8808 @@ impl DatabaseManager {
             words: result,
             new_words_count: new_count,
             review_words_count: review_count_val,
+            seed: None,
         })
     }
 
-    /// 更新单词熟练度（SM-2 算法）
-    pub fn update_word_mastery(
-        &self,
-        user_name: &str,
-        segment_id: i64,
-        segment_content: &str,
-        segment_type: &str,
-        correct: bool,
-    ) -> SqliteResult<crate::models::WordMastery> {
-        let now = chrono::Utc::now();
-        let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
-        
-        // 查询现有记录
+    /// 是否按内容合并熟练度（同一用户相同内容和类型共用一条记录）
+    pub fn content_keyed_mastery(&self, user_name: &str) -> SqliteResult<bool> {
+        let mut stmt = self.conn.prepare("SELECT content_keyed FROM mastery_settings WHERE user_name = ?")?;
+        let enabled = stmt.query_map([user_name], |row| row.get(0))?.next().transpose()?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    pub fn set_content_keyed_mastery(&self, user_name: &str, enabled: bool) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO mastery_settings (user_name, content_keyed) VALUES (?1, ?2)
+             ON CONFLICT(user_name) DO UPDATE SET content_keyed = ?2",
+            rusqlite::params![user_name, enabled],
+        )?;
+        Ok(())
+    }
+
+    /// 是否把美式和英式拼写（color / colour）视为同一个词
+    pub fn accept_spelling_variants(&self, user_name: &str) -> SqliteResult<bool> {
+        let mut stmt = self.conn.prepare("SELECT spelling_variants FROM mastery_settings WHERE user_name = ?")?;
+        let enabled = stmt.query_map([user_name], |row| row.get(0))?.next().transpose()?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    pub fn set_accept_spelling_variants(&self, user_name: &str, enabled: bool) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO mastery_settings (user_name, spelling_variants) VALUES (?1, ?2)
+             ON CONFLICT(user_name) DO UPDATE SET spelling_variants = ?2",
+            rusqlite::params![user_name, enabled],
+        )?;
+        Ok(())
+    }
+
+    /// 熟练度记录使用的分词 ID：按内容合并时沿用已有同内容记录的分词 ID
+    fn mastery_segment_id(&self, user_name: &str, segment_id: i64, content: &str, segment_type: &str) -> SqliteResult<i64> {
+        if !self.content_keyed_mastery(user_name)? {
+            return Ok(segment_id);
+        }
+        let variants = if self.accept_spelling_variants(user_name)? {
+            let content = content.to_lowercase();
+            [crate::text::spelling_variants::to_american(&content), crate::text::spelling_variants::to_british(&content)]
+        } else {
+            [content.to_string(), content.to_string()]
+        };
         let mut stmt = self.conn.prepare(
-            "SELECT mastery_level, ease_factor, interval_days, review_count FROM word_mastery 
-             WHERE user_name = ?1 AND segment_id = ?2"
+            "SELECT segment_id FROM word_mastery
+             WHERE user_name = ?1 AND lower(segment_content) IN (lower(?2), lower(?5), lower(?6)) AND segment_type = ?3
+             ORDER BY segment_id = ?4 DESC, last_review_at DESC LIMIT 1"
         )?;
-        
-        let existing: Option<(i32, f64, i32, i32)> = stmt
-            .query_row(rusqlite::params![user_name, segment_id], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-            })
-            .ok();
-        
-        // SM-2 算法计算
-        let (mastery_level, ease_factor, interval_days, review_count) = if let Some((ml, ef, iv, rc)) = existing {
-            if correct {
-                // 答对：增加熟练度，延长间隔
-                let new_ml = (ml + 1).min(5);
-                let new_ef = (ef + 0.1).min(3.0).max(1.3);
-                let new_iv = match new_ml {
-                    0 => 1,
-                    1 => 1,
-                    2 => 3,
-                    3 => 7,
-                    4 => 14,
-                    5 => 30,
-                    _ => iv,
-                };
-                (new_ml, new_ef, new_iv, rc + 1)
-            } else {
-                // 答错：降低熟练度，重置间隔
-                let new_ml = (ml - 1).max(0);
-                let new_ef = (ef - 0.2).max(1.3);
-                let new_iv = 0; // 立即需要再次复习
-                (new_ml, new_ef, new_iv, rc)
+        let existing = stmt
+            .query_map(rusqlite::params![user_name, content, segment_type, segment_id, variants[0], variants[1]], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        Ok(existing.unwrap_or(segment_id))
+    }
+
+    /// 合并同一用户相同内容和类型的重复熟练度记录，返回删除的重复行数
+    ///
+    /// 内容按 `mastery_content_key` 比较（接受美英拼写差异时 color / colour 算同一个词）。
+    /// 保留最近复习的一条（沿用它的等级和复习计划），复习次数累加到这一条上。
+    pub fn merge_duplicate_masteries(&mut self, user_name: &str) -> SqliteResult<i32> {
+        let tx = self.conn.transaction()?;
+        let removed = Self::merge_masteries(&tx, user_name)?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    fn merge_masteries(conn: &Connection, user_name: &str) -> SqliteResult<i32> {
+        let accept_variants: Option<bool> = conn
+            .prepare("SELECT spelling_variants FROM mastery_settings WHERE user_name = ?")?
+            .query_map([user_name], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        let accept_variants = accept_variants.unwrap_or(false);
+        // (内容键, 类型) → [(id, 最近复习时间, 复习次数)]
+        let mut groups = std::collections::HashMap::<_, Vec<(i64, Option<String>, i32)>>::new();
+        {
+            let mut stmt = conn.prepare(
+                "SELECT id, segment_content, segment_type, last_review_at, review_count FROM word_mastery WHERE user_name = ?1 ORDER BY id"
+            )?;
+            let rows = stmt.query_map([user_name], |row| {
+                Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?, (row.get(0)?, row.get(3)?, row.get(4)?)))
+            })?;
+            for row in rows {
+                let (content, segment_type, mastery) = row?;
+                groups.entry((mastery_content_key(&content, accept_variants), segment_type)).or_default().push(mastery);
             }
-        } else {
-            // 新单词
-            if correct {
-                (1, 2.5, 1, 1) // 答对后熟练度1，间隔1天
-            } else {
-                (0, 2.5, 0, 0) // 答错保持新词状态
+        }
+        let mut removed = 0;
+        for masteries in groups.into_values().filter(|m| m.len() > 1) {
+            // 复习时间和次数都相同时保留较早的记录
+            let keep_id = masteries
+                .iter()
+                .max_by_key(|(id, last_review_at, review_count)| (last_review_at.clone(), *review_count, std::cmp::Reverse(*id)))
+                .map(|(id, _, _)| *id)
+                .unwrap_or_default();
+            let total_reviews: i32 = masteries.iter().map(|(_, _, reviews)| reviews).sum();
+            conn.execute("UPDATE word_mastery SET review_count = ?1 WHERE id = ?2", rusqlite::params![total_reviews, keep_id])?;
+            for (id, _, _) in masteries.iter().filter(|(id, _, _)| *id != keep_id) {
+                removed += conn.execute("DELETE FROM word_mastery WHERE id = ?", [id])? as i32;
             }
-        };
-        
-        // 计算下次复习时间
-        let next_review = if interval_days == 0 {
-            // 答错或新词，当天或明天继续
-            now_str.clone()
-        } else {
-            let next = now + chrono::Duration::days(interval_days as i64);
-            next.format("%Y-%m-%d %H:%M:%S").to_string()
-        };
-        
-        // 保存到数据库
+        }
+        Ok(removed)
+    }
+
+    /// 设置每日新词上限，`daily_limit` 为 None 时取消限制
+    pub fn set_new_word_pacing(&self, user_name: &str, daily_limit: Option<i32>, carryover: bool) -> SqliteResult<()> {
+        match daily_limit {
+            Some(limit) if limit < 0 => Err(rusqlite::Error::InvalidParameterName("每日新词上限不能为负数".to_string())),
+            Some(limit) => {
+                self.conn.execute(
+                    "INSERT INTO new_word_pacing (user_name, daily_limit, carryover) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(user_name) DO UPDATE SET daily_limit = ?2, carryover = ?3",
+                    rusqlite::params![user_name, limit, carryover],
+                )?;
+                Ok(())
+            }
+            None => {
+                self.conn.execute("DELETE FROM new_word_pacing WHERE user_name = ?", [user_name])?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 保存用户的无障碍设置
+    pub fn set_accommodations(&self, accommodations: &crate::models::Accommodations) -> SqliteResult<()> {
+        let invalid = |message: &str| Err(rusqlite::Error::InvalidParameterName(message.to_string()));
+        if !(1.0..=4.0).contains(&accommodations.time_multiplier) {
+            return invalid("时间倍数需在 1 到 4 之间");
+        }
+        if !(1.0..=3.0).contains(&accommodations.text_scale) {
+            return invalid("显示放大倍数需在 1 到 3 之间");
+        }
+        if accommodations.max_tts_rate.is_some_and(|rate| rate < 50) {
+            return invalid("语速上限不能低于每分钟 50 词");
+        }
+        if accommodations.batch_size.is_some_and(|size| size < 1) {
+            return invalid("每批单词数至少为 1");
+        }
         self.conn.execute(
-            "INSERT INTO word_mastery (user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-             ON CONFLICT(user_name, segment_id) DO UPDATE SET
-                mastery_level = excluded.mastery_level,
-                ease_factor = excluded.ease_factor,
-                interval_days = excluded.interval_days,
-                next_review_at = excluded.next_review_at,
-                last_review_at = excluded.last_review_at,
-                review_count = excluded.review_count,
-                segment_content = excluded.segment_content,
-                segment_type = excluded.segment_type",
+            "INSERT INTO user_accommodations (user_name, time_multiplier, max_tts_rate, text_scale, batch_size)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(user_name) DO UPDATE SET time_multiplier = ?2, max_tts_rate = ?3, text_scale = ?4, batch_size = ?5",
             rusqlite::params![
-                user_name,
-                segment_id,
-                segment_content,
-                segment_type,
-                mastery_level,
-                ease_factor,
-                interval_days,
-                next_review,
-                now_str,
-                review_count
+                accommodations.user_name,
+                accommodations.time_multiplier,
+                accommodations.max_tts_rate,
+                accommodations.text_scale,
+                accommodations.batch_size,
             ],
         )?;
-        
-        Ok(crate::models::WordMastery {
-            user_name: user_name.to_string(),
-            segment_id,
-            segment_content: segment_content.to_string(),
-            segment_type: segment_type.to_string(),
-            mastery_level,
-            ease_factor,
-            interval_days,
-            next_review_at: next_review,
-            last_review_at: now_str,
-            review_count,
-        })
+        Ok(())
     }
 
-    /// 获取用户所有单词的熟练度
-    pub fn get_word_masteries(
-        &self,
-        user_name: &str,
-        segment_type: Option<&str>,
-    ) -> SqliteResult<Vec<crate::models::WordMastery>> {
-        let sql = match segment_type {
-            Some(st) => format!(
-                "SELECT user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count 
-                 FROM word_mastery WHERE user_name = '{}' AND segment_type = '{}' ORDER BY mastery_level ASC",
-                user_name, st
-            ),
-            None => format!(
-                "SELECT user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count 
-                 FROM word_mastery WHERE user_name = '{}' ORDER BY mastery_level ASC",
-                user_name
-            ),
-        };
-        
-        let mut stmt = self.conn.prepare(&sql)?;
-        let masteries: SqliteResult<Vec<_>> = stmt.query_map([], |row| {
-            Ok(crate::models::WordMastery {
-                user_name: row.get(0)?,
-                segment_id: row.get(1)?,
-                segment_content: row.get(2)?,
-                segment_type: row.get(3)?,
-                mastery_level: row.get(4)?,
-                ease_factor: row.get(5)?,
-                interval_days: row.get(6)?,
-                next_review_at: row.get(7)?,
-                last_review_at: row.get(8)?,
-                review_count: row.get(9)?,
-            })
-        })?.collect();
-        
-        Ok(masteries?)
+    /// 设置键盘布局（qwerty / azerty）
+    pub fn set_keyboard_layout(&self, user_name: &str, layout: &str) -> SqliteResult<()> {
+        let layout = layout.to_lowercase();
+        if !crate::text::keyboard::KEYBOARD_LAYOUTS.contains(&layout.as_str()) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("不支持的键盘布局: {}", layout)));
+        }
+        self.conn.execute(
+            "INSERT INTO typing_settings (user_name, keyboard_layout) VALUES (?1, ?2)
+             ON CONFLICT(user_name) DO UPDATE SET keyboard_layout = ?2",
+            rusqlite::params![user_name, layout],
+        )?;
+        Ok(())
     }
 
-    // ========== 练习历史记录 ==========
+    pub fn get_keyboard_layout(&self, user_name: &str) -> SqliteResult<String> {
+        let mut stmt = self.conn.prepare("SELECT keyboard_layout FROM typing_settings WHERE user_name = ?")?;
+        let layout = stmt.query_map([user_name], |row| row.get(0))?.next().transpose()?;
+        Ok(layout.unwrap_or_else(|| crate::text::keyboard::DEFAULT_KEYBOARD_LAYOUT.to_string()))
+    }
 
-    /// 保存练习历史
-    pub fn save_practice_history(
-        &self,
-        user_name: &str,
-        article_id: i64,
-        segment_type: &str,
-        correct_count: i32,
-        incorrect_count: i32,
-        duration_seconds: i32,
-    ) -> SqliteResult<()> {
-        let total_count = correct_count + incorrect_count;
-        let accuracy = if total_count > 0 {
-            (correct_count as f64 / total_count as f64) * 100.0
-        } else {
-            0.0
-        };
-        
-        // 计算WPM（每分钟单词数）
-        let wpm = if duration_seconds > 0 {
-            (total_count as f64 / duration_seconds as f64) * 60.0
-        } else {
-            0.0
-        };
-        
+    /// 记录一次按错的字符
+    pub fn record_keystroke_error(&self, session_id: i64, user_name: &str, expected: char, typed: char, kind: &str) -> SqliteResult<()> {
         self.conn.execute(
-            "INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, incorrect_count, total_count, accuracy, wpm, duration_seconds) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            rusqlite::params![
-                user_name,
-                article_id,
-                segment_type,
-                correct_count,
-                incorrect_count,
-                total_count,
-                accuracy,
-                wpm,
-                duration_seconds
-            ],
+            "INSERT INTO keystroke_errors (session_id, user_name, expected, typed, kind) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![session_id, user_name, expected.to_lowercase().to_string(), typed.to_string(), kind],
         )?;
         Ok(())
     }
 
-    /// 获取用户练习历史
-    pub fn get_practice_history(
-        &self,
-        user_name: &str,
-        limit: i32,
-    ) -> SqliteResult<Vec<crate::models::PracticeHistory>> {
-        let sql = format!(
-            "SELECT h.id, h.user_name, h.article_id, a.title, h.segment_type, h.correct_count, h.incorrect_count, h.total_count, h.accuracy, h.wpm, h.duration_seconds, h.completed_at 
-             FROM practice_history h 
-             LEFT JOIN articles a ON h.article_id = a.id 
-             WHERE h.user_name = '{}' 
-             ORDER BY h.completed_at DESC 
-             LIMIT {}",
-            user_name, limit
-        );
-        
-        let mut stmt = self.conn.prepare(&sql)?;
-        let histories = stmt.query_map([], |row| {
-            Ok(crate::models::PracticeHistory {
-                id: row.get(0)?,
-                user_name: row.get(1)?,
-                article_id: row.get(2)?,
-                article_title: row.get(3).unwrap_or_else(|_| "未知文章".to_string()),
-                segment_type: row.get(4)?,
-                correct_count: row.get(5)?,
-                incorrect_count: row.get(6)?,
-                total_count: row.get(7)?,
-                accuracy: row.get(8)?,
-                wpm: row.get(9)?,
-                duration_seconds: row.get(10)?,
-                completed_at: row.get(11)?,
+    /// 记录一次同音词混淆，并重新开始这组词的对比练习
+    pub fn record_homophone_confusion(&self, user_name: &str, word: &str, confused_with: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO homophone_confusions (user_name, word, confused_with) VALUES (?1, ?2, ?3)
+             ON CONFLICT(user_name, word, confused_with)
+             DO UPDATE SET confusion_count = confusion_count + 1, correct_streak = 0, last_confused_at = CURRENT_TIMESTAMP",
+            [user_name, word, confused_with],
+        )?;
+        Ok(())
+    }
+
+    /// 正确拼写了混淆过的某个词：包含该词的对比练习连续答对数加一
+    pub fn record_homophone_success(&self, user_name: &str, word: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE homophone_confusions SET correct_streak = correct_streak + 1
+             WHERE user_name = ?1 AND (word = lower(?2) OR confused_with = lower(?2))",
+            [user_name, word.trim()],
+        )?;
+        Ok(())
+    }
+
+    /// 待对比练习的同音词组（两个词都连续答对够次数后不再出现），最近混淆的在前
+    pub fn get_homophone_practice(&self, user_name: &str) -> SqliteResult<Vec<crate::models::HomophonePair>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT word, confused_with, confusion_count, correct_streak, last_confused_at FROM homophone_confusions
+             WHERE user_name = ? AND correct_streak < ? ORDER BY last_confused_at DESC, confusion_count DESC"
+        )?;
+        let pairs = stmt.query_map(rusqlite::params![user_name, HOMOPHONE_RESOLVE_STREAK], |row| {
+            Ok(crate::models::HomophonePair {
+                word: row.get(0)?,
+                confused_with: row.get(1)?,
+                confusion_count: row.get(2)?,
+                correct_streak: row.get(3)?,
+                last_confused_at: row.get(4)?,
             })
         })?.collect::<SqliteResult<Vec<_>>>();
-        
-        histories
+        pairs
     }
 
-    /// 获取用户统计信息
-    pub fn get_user_statistics(&self, user_name: &str) -> SqliteResult<crate::models::UserStatistics> {
-        // 总体统计
-        let stats_sql = format!(
-            "SELECT 
-                COUNT(*) as total_practices,
-                COALESCE(SUM(correct_count), 0) as total_correct,
-                COALESCE(SUM(incorrect_count), 0) as total_incorrect,
-                COALESCE(SUM(total_count), 0) as total_words,
-                COALESCE(AVG(accuracy), 0) as avg_accuracy,
-                COALESCE(AVG(wpm), 0) as avg_wpm,
-                COALESCE(MAX(accuracy), 0) as best_accuracy,
-                COALESCE(MAX(wpm), 0) as best_wpm,
-                COALESCE(SUM(duration_seconds), 0) as total_duration_seconds
-             FROM practice_history 
-             WHERE user_name = '{}'",
-            user_name
-        );
-        
-        let (total_practices, total_correct, total_incorrect, total_words, avg_accuracy, avg_wpm, best_accuracy, best_wpm, total_duration_seconds): (
-            i32, i32, i32, i32, f64, f64, f64, f64, i32
-        ) = self.conn.query_row(&stats_sql, [], |row| {
-            Ok((
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                row.get(3)?,
-                row.get(4)?,
-                row.get(5)?,
-                row.get(6)?,
-                row.get(7)?,
-                row.get(8)?,
-            ))
+    /// 按字母统计错误，拼写错误多的排在前面（“易错字母”只看拼写错误，不含按错相邻键）
+    pub fn get_letter_errors(&self, user_name: &str) -> SqliteResult<Vec<crate::models::LetterErrorStat>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT expected, COALESCE(SUM(kind = 'spelling'), 0), COALESCE(SUM(kind = 'slip'), 0)
+             FROM keystroke_errors WHERE user_name = ?
+             GROUP BY expected ORDER BY 2 DESC, 3 DESC, expected"
+        )?;
+        let stats = stmt.query_map([user_name], |row| {
+            Ok(crate::models::LetterErrorStat {
+                letter: row.get(0)?,
+                spelling_errors: row.get(1)?,
+                slips: row.get(2)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        stats
+    }
+
+    /// 获取用户的无障碍设置，未设置时返回默认值
+    pub fn get_accommodations(&self, user_name: &str) -> SqliteResult<crate::models::Accommodations> {
+        let mut stmt = self.conn.prepare(
+            "SELECT time_multiplier, max_tts_rate, text_scale, batch_size FROM user_accommodations WHERE user_name = ?",
+        )?;
+        let mut rows = stmt.query_map([user_name], |row| {
+            Ok(crate::models::Accommodations {
+                user_name: user_name.to_string(),
+                time_multiplier: row.get(0)?,
+                max_tts_rate: row.get(1)?,
+                text_scale: row.get(2)?,
+                batch_size: row.get(3)?,
+            })
         })?;
-        
-        // 获取最近的练习记录
-        let recent_histories = self.get_practice_history(user_name, 10)?;
-        
-        Ok(crate::models::UserStatistics {
+        Ok(rows.next().transpose()?.unwrap_or_else(|| crate::models::Accommodations {
             user_name: user_name.to_string(),
-            total_practices,
-            total_correct,
-            total_incorrect,
-            total_words,
-            avg_accuracy,
-            avg_wpm,
-            best_accuracy,
-            best_wpm,
-            total_duration_minutes: total_duration_seconds as f64 / 60.0,
-            recent_histories,
+            time_multiplier: 1.0,
+            max_tts_rate: None,
+            text_scale: 1.0,
+            batch_size: None,
+        }))
+    }
+
+    /// 设置、修改或清除（`new_pin` 为 None）用户 PIN；已有 PIN 时必须提供正确的当前 PIN
+    pub fn set_user_pin(&self, user_name: &str, current_pin: Option<&str>, new_pin: Option<&str>) -> SqliteResult<()> {
+        if !self.verify_user_pin(user_name, current_pin.unwrap_or_default())? {
+            return Err(rusqlite::Error::InvalidParameterName("当前 PIN 不正确".to_string()));
+        }
+        if new_pin.is_some_and(|pin| !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit())) {
+            return Err(rusqlite::Error::InvalidParameterName("PIN 需为 4 到 8 位数字".to_string()));
+        }
+        let salt = new_pin.map(|_| uuid::Uuid::new_v4().simple().to_string());
+        let hash = new_pin.zip(salt.as_deref()).map(|(pin, salt)| hash_pin(pin, salt));
+        self.conn.execute(
+            "INSERT INTO users (user_name, pin_hash, pin_salt) VALUES (?1, ?2, ?3)
+             ON CONFLICT(user_name) DO UPDATE SET pin_hash = ?2, pin_salt = ?3, updated_at = datetime('now')",
+            rusqlite::params![user_name, hash, salt],
+        )?;
+        Ok(())
+    }
+
+    /// 用户是否设置了 PIN
+    pub fn user_has_pin(&self, user_name: &str) -> SqliteResult<bool> {
+        Ok(self.user_pin(user_name)?.is_some())
+    }
+
+    /// 校验用户 PIN；未设置 PIN 的用户总是通过
+    pub fn verify_user_pin(&self, user_name: &str, pin: &str) -> SqliteResult<bool> {
+        Ok(match self.user_pin(user_name)? {
+            Some((hash, salt)) => hash_pin(pin, &salt) == hash,
+            None => true,
         })
     }
 
-    // ========== WIDA 测试模块 ==========
+    /// 查看受 PIN 保护的档案前的校验，失败时返回错误
+    pub fn require_user_pin(&self, user_name: &str, pin: Option<&str>) -> SqliteResult<()> {
+        if self.verify_user_pin(user_name, pin.unwrap_or_default())? {
+            Ok(())
+        } else {
+            Err(rusqlite::Error::InvalidParameterName(format!("用户 {} 的 PIN 不正确", user_name)))
+        }
+    }
 
-    /// 获取听力题库
-    pub fn get_wida_listening_questions(
-        &self,
-        grade_level: &str,
-        domain: Option<&str>,
-        limit: Option<i32>,
-    ) -> SqliteResult<Vec<crate::models::WidaListeningQuestion>> {
-        let sql = match (domain, limit) {
-            (Some(d), Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation 
-                 FROM wida_listening_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, d, l
-            ),
-            (None, Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation 
-                 FROM wida_listening_questions WHERE grade_level = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, l
-            ),
-            (Some(d), None) => format!(
-                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation 
-                 FROM wida_listening_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY id",
-                grade_level, d
-            ),
-            (None, None) => format!(
-                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation 
-                 FROM wida_listening_questions WHERE grade_level = '{}' 
-                 ORDER BY id",
-                grade_level
-            ),
-        };
+    /// 用户角色，未登记的用户为 student
+    pub fn get_user_role(&self, user_name: &str) -> SqliteResult<String> {
+        let mut stmt = self.conn.prepare("SELECT role FROM users WHERE user_name = ?")?;
+        let role = stmt.query_map([user_name], |row| row.get(0))?.next().transpose()?;
+        Ok(role.unwrap_or_else(|| USER_ROLES[0].to_string()))
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let questions = stmt.query_map([], |row| {
-            let options_json: String = row.get(7)?;
-            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
-            Ok(crate::models::WidaListeningQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                audio_text: row.get(4)?,
-                image_url: row.get(5)?,
-                question_text: row.get(6)?,
-                options,
-                correct_answer: row.get(8)?,
-                explanation: row.get(9)?,
-            })
-        })?.collect::<SqliteResult<Vec<_>>>();
-        questions
+    /// 设置用户角色，只有 admin 可以操作（还没有 admin 时任何人都可以指定第一个）
+    pub fn set_user_role(&self, actor: Option<&crate::models::Actor>, user_name: &str, role: &str) -> SqliteResult<()> {
+        if !USER_ROLES.contains(&role) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("未知的角色: {}", role)));
+        }
+        let has_admin: bool = self.conn.query_row("SELECT EXISTS(SELECT 1 FROM users WHERE role = 'admin')", [], |row| row.get(0))?;
+        if has_admin {
+            self.authorize_roles(actor, &["admin"])?;
+        }
+        self.conn.execute(
+            "INSERT INTO users (user_name, role) VALUES (?1, ?2)
+             ON CONFLICT(user_name) DO UPDATE SET role = ?2, updated_at = datetime('now')",
+            rusqlite::params![user_name, role],
+        )?;
+        Ok(())
     }
 
-    /// 获取阅读题库
-    pub fn get_wida_reading_questions(
-        &self,
-        grade_level: &str,
-        domain: Option<&str>,
-        limit: Option<i32>,
-    ) -> SqliteResult<Vec<crate::models::WidaReadingQuestion>> {
-        let sql = match (domain, limit) {
-            (Some(d), Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation 
-                 FROM wida_reading_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, d, l
-            ),
-            (None, Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation 
-                 FROM wida_reading_questions WHERE grade_level = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, l
-            ),
-            (Some(d), None) => format!(
-                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation 
-                 FROM wida_reading_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY id",
-                grade_level, d
-            ),
-            (None, None) => format!(
-                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation 
-                 FROM wida_reading_questions WHERE grade_level = '{}' 
-                 ORDER BY id",
-                grade_level
-            ),
-        };
+    /// 设置或清除（None）用户出生年份，用于推荐 WIDA 年级段
+    pub fn set_user_birth_year(&self, user_name: &str, birth_year: Option<i32>) -> SqliteResult<()> {
+        let this_year = chrono::Datelike::year(&chrono::Local::now().date_naive());
+        if birth_year.is_some_and(|year| !(this_year - 100..=this_year).contains(&year)) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("出生年份需在 {} 到 {} 之间", this_year - 100, this_year)));
+        }
+        self.conn.execute(
+            "INSERT INTO users (user_name, birth_year) VALUES (?1, ?2)
+             ON CONFLICT(user_name) DO UPDATE SET birth_year = ?2, updated_at = datetime('now')",
+            rusqlite::params![user_name, birth_year],
+        )?;
+        Ok(())
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let questions = stmt.query_map([], |row| {
-            let options_json: String = row.get(7)?;
-            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
-            Ok(crate::models::WidaReadingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                passage: row.get(4)?,
-                question_text: row.get(5)?,
-                question_type: row.get(6)?,
-                options,
-                correct_answer: row.get(8)?,
-                explanation: row.get(9)?,
-            })
-        })?.collect::<SqliteResult<Vec<_>>>();
-        questions
+    /// 用户出生年份，未设置时为 None
+    pub fn get_user_birth_year(&self, user_name: &str) -> SqliteResult<Option<i32>> {
+        let mut stmt = self.conn.prepare("SELECT birth_year FROM users WHERE user_name = ?")?;
+        let year = stmt.query_map([user_name], |row| row.get::<_, Option<i32>>(0))?.next().transpose()?;
+        Ok(year.flatten())
     }
 
-    /// 获取口语题库
-    pub fn get_wida_speaking_questions(
-        &self,
-        grade_level: &str,
-        domain: Option<&str>,
-        limit: Option<i32>,
-    ) -> SqliteResult<Vec<crate::models::WidaSpeakingQuestion>> {
-        let sql = match (domain, limit) {
-            (Some(d), Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
-                 FROM wida_speaking_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, d, l
-            ),
-            (None, Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
-                 FROM wida_speaking_questions WHERE grade_level = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, l
-            ),
-            (Some(d), None) => format!(
-                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
-                 FROM wida_speaking_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY id",
-                grade_level, d
-            ),
-            (None, None) => format!(
-                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
-                 FROM wida_speaking_questions WHERE grade_level = '{}' 
-                 ORDER BY id",
-                grade_level
-            ),
+    /// 根据年龄和最近的测试结果推荐下次测试的年级段和起始难度
+    ///
+    /// 有出生年份时年级段按年龄确定；否则沿用最近一次测试的年级段，能力等级达到 5 以上升一段、低于 2 降一段。
+    /// 起始难度取最近几次能力等级的平均值，最近一次正确率高于 80% 加 1、低于 50% 减 1。
+    pub fn recommend_grade_level(&self, user_name: &str) -> SqliteResult<crate::models::GradeRecommendation> {
+        let today = chrono::Local::now().date_naive();
+        let age = self.get_user_birth_year(user_name)?.map(|year| chrono::Datelike::year(&today) - year);
+        let history = self.get_wida_history(user_name, None, Some(GRADE_RECOMMENDATION_HISTORY))?;
+        let recent_level = (!history.is_empty())
+            .then(|| history.iter().map(|r| r.proficiency_level as f64).sum::<f64>() / history.len() as f64);
+
+        let mut reasons = Vec::new();
+        let band = match (age, history.first()) {
+            (Some(age), _) => {
+                reasons.push(format!("{} 岁约为 {} 年级", age, grade_for_age(age)));
+                grade_band_index(grade_for_age(age))
+            }
+            (None, Some(latest)) => {
+                let current = WIDA_GRADE_BANDS.iter().position(|(band, _, _)| *band == latest.grade_level).unwrap_or(1);
+                let level = recent_level.unwrap_or_default();
+                if level >= 5.0 && current + 1 < WIDA_GRADE_BANDS.len() {
+                    reasons.push(format!("最近能力等级平均 {:.1}，升一个年级段", level));
+                    current + 1
+                } else if level < 2.0 && current > 0 {
+                    reasons.push(format!("最近能力等级平均 {:.1}，降一个年级段", level));
+                    current - 1
+                } else {
+                    reasons.push("沿用最近一次测试的年级段".to_string());
+                    current
+                }
+            }
+            (None, None) => {
+                reasons.push("没有出生年份和测试记录，使用默认年级段".to_string());
+                1
+            }
         };
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let questions = stmt.query_map([], |row| {
-            let rubric_json: String = row.get(9)?;
-            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
-            Ok(crate::models::WidaSpeakingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                prompt_type: row.get(4)?,
-                prompt_text: row.get(5)?,
-                image_url: row.get(6)?,
-                audio_text: row.get(7)?,
-                sample_answer: row.get(8)?,
-                rubric,
-            })
-        })?.collect::<SqliteResult<Vec<_>>>();
-        questions
+        let difficulty = match (recent_level, history.first()) {
+            (Some(level), Some(latest)) => {
+                reasons.push(format!("最近 {} 次测试能力等级平均 {:.1}", history.len(), level));
+                let adjust = if latest.accuracy >= 80.0 {
+                    1
+                } else if latest.accuracy < 50.0 {
+                    -1
+                } else {
+                    0
+                };
+                (level.round() as i32 + adjust).clamp(1, 6)
+            }
+            _ => 1,
+        };
+
+        Ok(crate::models::GradeRecommendation {
+            user_name: user_name.to_string(),
+            grade_level: WIDA_GRADE_BANDS[band].0.to_string(),
+            difficulty,
+            age,
+            recent_proficiency_level: recent_level,
+            reason: reasons.join("；"),
+        })
     }
 
-    /// 获取写作题库
-    pub fn get_wida_writing_questions(
-        &self,
-        grade_level: &str,
-        domain: Option<&str>,
-        limit: Option<i32>,
-    ) -> SqliteResult<Vec<crate::models::WidaWritingQuestion>> {
-        let sql = match (domain, limit) {
-            (Some(d), Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
-                 FROM wida_writing_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, d, l
-            ),
-            (None, Some(l)) => format!(
-                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
-                 FROM wida_writing_questions WHERE grade_level = '{}' 
-                 ORDER BY RANDOM() LIMIT {}",
-                grade_level, l
-            ),
-            (Some(d), None) => format!(
-                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
-                 FROM wida_writing_questions WHERE grade_level = '{}' AND domain = '{}' 
-                 ORDER BY id",
-                grade_level, d
-            ),
-            (None, None) => format!(
-                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
-                 FROM wida_writing_questions WHERE grade_level = '{}' 
-                 ORDER BY id",
-                grade_level
-            ),
-        };
+    /// 受限命令的权限检查：调用者须为 teacher 或 admin 并通过 PIN 校验
+    ///
+    /// 还没有任何 teacher/admin 时不做限制，避免首次使用时被锁在外面。
+    pub fn authorize(&self, actor: Option<&crate::models::Actor>) -> SqliteResult<()> {
+        let has_privileged: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE role IN ('teacher', 'admin'))",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_privileged {
+            self.authorize_roles(actor, &["teacher", "admin"])?;
+        }
+        Ok(())
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let questions = stmt.query_map([], |row| {
-            let rubric_json: String = row.get(9)?;
-            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
-            Ok(crate::models::WidaWritingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                task_type: row.get(4)?,
-                prompt: row.get(5)?,
-                image_url: row.get(6)?,
-                word_limit_min: row.get(7)?,
-                word_limit_max: row.get(8)?,
-                rubric,
-                sample_answer: row.get(10)?,
-            })
-        })?.collect::<SqliteResult<Vec<_>>>();
-        questions
+    fn authorize_roles(&self, actor: Option<&crate::models::Actor>, roles: &[&str]) -> SqliteResult<()> {
+        let actor = actor.ok_or_else(|| rusqlite::Error::InvalidParameterName("该操作需要老师或管理员身份".to_string()))?;
+        if !roles.contains(&self.get_user_role(&actor.user_name)?.as_str()) {
+            return Err(rusqlite::Error::InvalidParameterName(format!("用户 {} 没有权限执行该操作", actor.user_name)));
+        }
+        self.require_user_pin(&actor.user_name, actor.pin.as_deref())
     }
 
-    /// 开始新的 WIDA 测试
-    pub fn start_wida_test(&self, request: &crate::models::StartWidaTestRequest) -> SqliteResult<crate::models::WidaTestSession> {
-        let question_ids: Vec<i64>;
-        
-        // 根据测试类型获取题目ID
-        match request.test_type.as_str() {
-            "listening" => {
-                let questions = self.get_wida_listening_questions(
-                    &request.grade_level,
-                    request.domain.as_deref(),
-                    Some(request.question_count),
-                )?;
-                question_ids = questions.iter().map(|q| q.id).collect();
-            }
-            "reading" => {
-                let questions = self.get_wida_reading_questions(
-                    &request.grade_level,
-                    request.domain.as_deref(),
-                    Some(request.question_count),
-                )?;
-                question_ids = questions.iter().map(|q| q.id).collect();
-            }
-            "speaking" => {
-                let questions = self.get_wida_speaking_questions(
-                    &request.grade_level,
-                    request.domain.as_deref(),
-                    Some(request.question_count),
-                )?;
-                question_ids = questions.iter().map(|q| q.id).collect();
+    fn user_pin(&self, user_name: &str) -> SqliteResult<Option<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pin_hash, pin_salt FROM users WHERE user_name = ? AND pin_hash IS NOT NULL",
+        )?;
+        let pin = stmt.query_map([user_name], |row| Ok((row.get(0)?, row.get(1)?)))?.next().transpose();
+        pin
+    }
+
+    /// 获取每日新词上限及今天的使用情况
+    pub fn get_new_word_pacing(&self, user_name: &str) -> SqliteResult<crate::models::NewWordPacing> {
+        let today = chrono::Local::now().date_naive();
+        let (daily_limit, carryover) = self.pacing_settings(user_name)?.map_or((None, false), |(l, c)| (Some(l), c));
+        Ok(crate::models::NewWordPacing {
+            user_name: user_name.to_string(),
+            daily_limit,
+            carryover,
+            served_today: self.new_words_served_on(user_name, today)?.len() as i32,
+            remaining_today: self.new_words_remaining(user_name, today)?,
+        })
+    }
+
+    fn pacing_settings(&self, user_name: &str) -> SqliteResult<Option<(i32, bool)>> {
+        let mut stmt = self.conn.prepare("SELECT daily_limit, carryover FROM new_word_pacing WHERE user_name = ?")?;
+        let mut rows = stmt.query_map([user_name], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.next().transpose()
+    }
+
+    fn new_words_served_on(&self, user_name: &str, day: chrono::NaiveDate) -> SqliteResult<std::collections::HashSet<i64>> {
+        let mut stmt = self.conn.prepare("SELECT segment_id FROM new_word_log WHERE user_name = ?1 AND served_on = ?2")?;
+        let served = stmt
+            .query_map(rusqlite::params![user_name, day.format("%Y-%m-%d").to_string()], |row| row.get(0))?
+            .collect::<SqliteResult<_>>();
+        served
+    }
+
+    /// 记录已作答的单词，同时清理前一天及更早的记录
+    fn log_served(&self, user_name: &str, segment_id: i64, content: &str) -> SqliteResult<()> {
+        let now = chrono::Local::now();
+        self.conn.execute(
+            "DELETE FROM served_log WHERE user_name = ? AND served_at < ?",
+            rusqlite::params![user_name, now.format("%Y-%m-%d").to_string()],
+        )?;
+        self.conn.execute(
+            "INSERT INTO served_log (user_name, segment_id, content, served_at) VALUES (?, ?, ?, ?)",
+            rusqlite::params![user_name, segment_id, content, now.format("%Y-%m-%d %H:%M:%S").to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// 今天已作答的分词 ID 和内容（小写）
+    fn served_today(&self, user_name: &str) -> SqliteResult<(std::collections::HashSet<i64>, std::collections::HashSet<String>)> {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare("SELECT segment_id, lower(content) FROM served_log WHERE user_name = ?1 AND served_at >= ?2")?;
+        let rows = stmt
+            .query_map(rusqlite::params![user_name, today], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(rows.into_iter().unzip())
+    }
+
+    /// 当天剩余的新词名额；未设置上限时返回 None
+    ///
+    /// 开启顺延时，前一天没用完的名额加到今天，但最多顺延一天的量（即当天最多为上限的两倍）。
+    fn new_words_remaining(&self, user_name: &str, today: chrono::NaiveDate) -> SqliteResult<Option<i32>> {
+        let Some((daily_limit, carryover)) = self.pacing_settings(user_name)? else {
+            return Ok(None);
+        };
+        let mut quota = daily_limit;
+        if carryover {
+            if let Some(yesterday) = today.pred_opt() {
+                let unused = daily_limit - self.new_words_served_on(user_name, yesterday)?.len() as i32;
+                quota += unused.clamp(0, daily_limit);
             }
-            "writing" => {
-                let questions = self.get_wida_writing_questions(
-                    &request.grade_level,
-                    request.domain.as_deref(),
-                    Some(request.question_count),
-                )?;
-                question_ids = questions.iter().map(|q| q.id).collect();
+        }
+        let served_today = self.new_words_served_on(user_name, today)?.len() as i32;
+        Ok(Some((quota - served_today).max(0)))
+    }
+
+    /// 把复习量异常多的日子里的部分单词挪到相邻日期
+    ///
+    /// 超过 `max_per_day`（默认为未来每日平均量的 1.5 倍）的日子，从间隔最长的单词开始，
+    /// 在其间隔 ±10%（至少 ±1 天）范围内挪到最空的一天；不会挪到今天或更早。
+    pub fn rebalance_reviews(&mut self, user_name: &str, max_per_day: Option<i32>) -> SqliteResult<crate::models::ReviewRebalance> {
+        let now = chrono::Utc::now();
+        let today = now.date_naive();
+        let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, interval_days, next_review_at FROM word_mastery
+             WHERE user_name = ?1 AND next_review_at > ?2 ORDER BY interval_days DESC, id"
+        )?;
+        let rows: Vec<(i64, i32, chrono::NaiveDateTime)> = stmt
+            .query_map(rusqlite::params![user_name, now_str], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i32>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?
+            .into_iter()
+            .filter_map(|(id, interval, due)| {
+                chrono::NaiveDateTime::parse_from_str(&due, "%Y-%m-%d %H:%M:%S").ok().map(|due| (id, interval, due))
+            })
+            .collect();
+        drop(stmt);
+
+        let mut loads: std::collections::BTreeMap<chrono::NaiveDate, i32> = std::collections::BTreeMap::new();
+        for (_, _, due) in &rows {
+            *loads.entry(due.date()).or_default() += 1;
+        }
+        let limit = max_per_day.unwrap_or_else(|| {
+            let average = rows.len() as f64 / loads.len().max(1) as f64;
+            (average * 1.5).ceil() as i32
+        }).max(1);
+
+        let mut moves: Vec<(i64, chrono::NaiveDateTime)> = Vec::new();
+        let overloaded: Vec<chrono::NaiveDate> = loads.iter().filter(|(_, &n)| n > limit).map(|(&d, _)| d).collect();
+        for day in overloaded {
+            for (id, interval, due) in rows.iter().filter(|(_, _, due)| due.date() == day) {
+                if loads[&day] <= limit {
+                    break;
+                }
+                let window = crate::ordering::fuzz_range(*interval).max(1) as i64;
+                let target = (-window..=window)
+                    .filter(|&offset| offset != 0)
+                    .map(|offset| day + chrono::Duration::days(offset))
+                    .filter(|d| *d > today && loads.get(d).copied().unwrap_or(0) < limit)
+                    .min_by_key(|d| (loads.get(d).copied().unwrap_or(0), (*d - day).num_days().abs()));
+                if let Some(target) = target {
+                    *loads.get_mut(&day).unwrap() -= 1;
+                    *loads.entry(target).or_default() += 1;
+                    moves.push((*id, target.and_time(due.time())));
+                }
             }
-            _ => return Err(rusqlite::Error::InvalidParameterName("Invalid test type".into())),
         }
 
-        if question_ids.is_empty() {
-            return Err(rusqlite::Error::QueryReturnedNoRows);
+        let tx = self.conn.transaction()?;
+        for (id, due) in &moves {
+            tx.execute(
+                "UPDATE word_mastery SET next_review_at = ? WHERE id = ?",
+                rusqlite::params![due.format("%Y-%m-%d %H:%M:%S").to_string(), id],
+            )?;
         }
+        tx.commit()?;
 
-        let question_ids_json = serde_json::to_string(&question_ids).unwrap_or_else(|_| "[]".to_string());
-        let total_questions = question_ids.len() as i32;
-        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        Ok(crate::models::ReviewRebalance {
+            max_per_day: limit,
+            moved: moves.len() as i32,
+            due_days: loads
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|(date, count)| crate::models::DueDayLoad { date: date.format("%Y-%m-%d").to_string(), count })
+                .collect(),
+        })
+    }
 
+    /// 登记假期（含首尾两天）；假期结束后首次排课时自动把到期日顺延假期天数
+    pub fn pause_reviews(&self, user_name: &str, from: &str, to: &str) -> SqliteResult<crate::models::ReviewPause> {
+        let parse = |date: &str| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| rusqlite::Error::InvalidParameterName(format!("日期格式应为 YYYY-MM-DD: {}", date)))
+        };
+        let (from_date, to_date) = (parse(from)?, parse(to)?);
+        if to_date < from_date {
+            return Err(rusqlite::Error::InvalidParameterName("结束日期不能早于开始日期".to_string()));
+        }
         self.conn.execute(
-            "INSERT INTO wida_test_sessions (user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, started_at)
-             VALUES (?, ?, ?, ?, 'in_progress', 0, ?, ?, '[]', ?)",
-            rusqlite::params![
-                request.user_name,
-                request.test_type,
-                request.grade_level,
-                request.domain,
-                total_questions,
-                question_ids_json,
-                now
-            ],
+            "INSERT INTO review_pauses (user_name, from_date, to_date) VALUES (?, ?, ?)",
+            rusqlite::params![user_name, from_date.format("%Y-%m-%d").to_string(), to_date.format("%Y-%m-%d").to_string()],
         )?;
-
-        let session_id = self.conn.last_insert_rowid();
-        
-        Ok(crate::models::WidaTestSession {
-            id: session_id,
-            user_name: request.user_name.clone(),
-            test_type: request.test_type.clone(),
-            grade_level: request.grade_level.clone(),
-            domain: request.domain.clone(),
-            status: "in_progress".to_string(),
-            current_question: 0,
-            total_questions,
-            answers: "[]".to_string(),
-            score: None,
-            proficiency_level: None,
-            started_at: now,
-            completed_at: None,
-            duration_seconds: 0,
-        })
+        let id = self.conn.last_insert_rowid();
+        self.apply_review_pauses(user_name)?;
+        self.get_review_pauses(user_name)?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
     }
 
-    /// 获取测试会话
-    pub fn get_wida_test_session(&self, session_id: i64) -> SqliteResult<Option<crate::models::WidaTestSession>> {
+    pub fn get_review_pauses(&self, user_name: &str) -> SqliteResult<Vec<crate::models::ReviewPause>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, score, proficiency_level, started_at, completed_at, duration_seconds
-             FROM wida_test_sessions WHERE id = ?"
+            "SELECT id, user_name, from_date, to_date, applied_at, shifted_count FROM review_pauses
+             WHERE user_name = ? ORDER BY from_date DESC"
         )?;
-        
-        let mut sessions = stmt.query_map([session_id], |row| {
-            Ok(crate::models::WidaTestSession {
+        let pauses = stmt.query_map([user_name], |row| {
+            Ok(crate::models::ReviewPause {
                 id: row.get(0)?,
                 user_name: row.get(1)?,
-                test_type: row.get(2)?,
-                grade_level: row.get(3)?,
-                domain: row.get(4)?,
-                status: row.get(5)?,
-                current_question: row.get(6)?,
-                total_questions: row.get(7)?,
-                // question_ids is stored but not returned in session
-                answers: row.get(9)?,
-                score: row.get(10)?,
-                proficiency_level: row.get(11)?,
-                started_at: row.get(12)?,
-                completed_at: row.get(13)?,
-                duration_seconds: row.get(14)?,
+                from_date: row.get(2)?,
+                to_date: row.get(3)?,
+                applied_at: row.get(4)?,
+                shifted_count: row.get(5)?,
             })
-        })?;
-        
-        Ok(sessions.next().transpose()?)
+        })?.collect::<SqliteResult<Vec<_>>>();
+        pauses
     }
 
-    /// 获取测试题目
-    pub fn get_wida_test_questions(&self, session_id: i64) -> SqliteResult<serde_json::Value> {
-        let question_ids_json: String = self.conn.query_row(
-            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
-            [session_id],
+    /// 取消还没生效的假期，返回是否删除
+    pub fn cancel_review_pause(&self, id: i64) -> SqliteResult<bool> {
+        let deleted = self.conn.execute("DELETE FROM review_pauses WHERE id = ? AND applied_at IS NULL", [id])?;
+        Ok(deleted > 0)
+    }
+
+    /// 对已结束的假期顺延复习：假期开始及之后到期、且假期中没有复习过的单词，到期日推后假期天数
+    fn apply_review_pauses(&self, user_name: &str) -> SqliteResult<i32> {
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, from_date, julianday(to_date) - julianday(from_date) + 1 FROM review_pauses
+             WHERE user_name = ?1 AND applied_at IS NULL AND to_date < ?2 ORDER BY from_date"
+        )?;
+        let pending = stmt
+            .query_map(rusqlite::params![user_name, today], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)? as i64))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut shifted_total = 0;
+        for (id, from_date, days) in pending {
+            let shifted = self.conn.execute(
+                "UPDATE word_mastery SET next_review_at = datetime(next_review_at, ?3)
+                 WHERE user_name = ?1 AND next_review_at >= ?2 AND (last_review_at IS NULL OR last_review_at < ?2)",
+                rusqlite::params![user_name, from_date, format!("+{} days", days)],
+            )? as i32;
+            self.conn.execute(
+                "UPDATE review_pauses SET applied_at = CURRENT_TIMESTAMP, shifted_count = ? WHERE id = ?",
+                rusqlite::params![shifted, id],
+            )?;
+            shifted_total += shifted;
+        }
+        Ok(shifted_total)
+    }
+
+    /// 最近 7 天的练习小结
+    pub fn get_weekly_summary(&self, user_name: &str) -> SqliteResult<crate::models::WeeklySummary> {
+        self.get_practice_summary(user_name, 7)
+    }
+
+    /// 最近 `days` 天的练习小结
+    pub fn get_practice_summary(&self, user_name: &str, days: i64) -> SqliteResult<crate::models::WeeklySummary> {
+        let since = (chrono::Utc::now() - chrono::Duration::days(days)).format("%Y-%m-%d %H:%M:%S").to_string();
+        let (practices, words_practiced, avg_accuracy, duration_seconds) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(total_count), 0), COALESCE(AVG(accuracy), 0), COALESCE(SUM(duration_seconds), 0)
+             FROM practice_history WHERE user_name = ?1 AND completed_at >= ?2",
+            rusqlite::params![user_name, since],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+        let words_mastered = self.conn.query_row(
+            "SELECT COUNT(DISTINCT segment_id) FROM mastery_events
+             WHERE user_name = ?1 AND created_at >= ?2 AND new_level >= ?3 AND COALESCE(old_level, 0) < ?3",
+            rusqlite::params![user_name, since, MISTAKE_GRADUATION_LEVEL],
             |row| row.get(0),
         )?;
+        Ok(crate::models::WeeklySummary {
+            user_name: user_name.to_string(),
+            practices,
+            words_practiced,
+            words_mastered,
+            avg_accuracy,
+            duration_seconds,
+            streak_days: self.get_practice_streak(user_name)?,
+            speed_accuracy: Some(self.get_speed_accuracy_profile(user_name, Some(days))?),
+        })
+    }
 
-        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
-        let test_type: String = self.conn.query_row(
-            "SELECT test_type FROM wida_test_sessions WHERE id = ?",
+    /// 记录练习会话中一题的作答，用时从上一题作答（第一题从会话开始）算起，返回用时（毫秒）
+    pub fn record_practice_event(&self, session_id: i64, user_name: &str, item_index: i32, segment_id: i64, correct: bool) -> SqliteResult<i64> {
+        let now = chrono::Utc::now().naive_utc();
+        let previous: String = self.conn.query_row(
+            "SELECT COALESCE((SELECT MAX(created_at) FROM practice_events WHERE session_id = ?1), started_at)
+             FROM practice_sessions WHERE id = ?1",
             [session_id],
             |row| row.get(0),
         )?;
+        let previous = chrono::NaiveDateTime::parse_from_str(&previous, "%Y-%m-%d %H:%M:%S%.3f")
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(&previous, "%Y-%m-%d %H:%M:%S"))
+            .unwrap_or(now);
+        let response_ms = (now - previous).num_milliseconds().max(0);
+        self.conn.execute(
+            "INSERT INTO practice_events (user_name, session_id, item_index, segment_id, correct, response_ms, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                user_name,
+                session_id,
+                item_index,
+                segment_id,
+                correct,
+                response_ms,
+                now.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+            ],
+        )?;
+        Ok(response_ms)
+    }
 
-        let questions = match test_type.as_str() {
-            "listening" => {
-                let q: Vec<crate::models::WidaListeningQuestion> = question_ids.iter()
-                    .filter_map(|&id| self.get_wida_listening_question_by_id(id).ok().flatten())
-                    .collect();
-                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
-            }
-            "reading" => {
-                let q: Vec<crate::models::WidaReadingQuestion> = question_ids.iter()
-                    .filter_map(|&id| self.get_wida_reading_question_by_id(id).ok().flatten())
-                    .collect();
-                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
-            }
-            "speaking" => {
-                let q: Vec<crate::models::WidaSpeakingQuestion> = question_ids.iter()
-                    .filter_map(|&id| self.get_wida_speaking_question_by_id(id).ok().flatten())
-                    .collect();
-                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
-            }
-            "writing" => {
-                let q: Vec<crate::models::WidaWritingQuestion> = question_ids.iter()
-                    .filter_map(|&id| self.get_wida_writing_question_by_id(id).ok().flatten())
-                    .collect();
-                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
-            }
-            _ => serde_json::json!([]),
+    /// 速度与正确率的关系：按作答用时分成快、中、慢三组比较正确率，判断放慢速度是否有帮助
+    ///
+    /// `days` 为空时分析全部记录；作答数不足时不分组，`recommendation` 为 `insufficient_data`。
+    pub fn get_speed_accuracy_profile(&self, user_name: &str, days: Option<i64>) -> SqliteResult<crate::models::SpeedAccuracyProfile> {
+        let since = days.map(|d| (chrono::Utc::now() - chrono::Duration::days(d)).format("%Y-%m-%d %H:%M:%S").to_string());
+        let mut stmt = self.conn.prepare(
+            "SELECT response_ms, correct FROM practice_events
+             WHERE user_name = ?1 AND (?2 IS NULL OR created_at >= ?2) AND response_ms <= ?3
+             ORDER BY response_ms"
+        )?;
+        let events = stmt
+            .query_map(rusqlite::params![user_name, since, SPEED_ACCURACY_MAX_RESPONSE_MS], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let n = events.len();
+        let accuracy = |group: &[(i64, bool)]| group.iter().filter(|(_, c)| *c).count() as f64 / group.len().max(1) as f64;
+        let mut profile = crate::models::SpeedAccuracyProfile {
+            user_name: user_name.to_string(),
+            items: n as i32,
+            accuracy: accuracy(&events),
+            median_response_ms: events.get(n / 2).map(|e| e.0),
+            correlation: None,
+            buckets: Vec::new(),
+            recommendation: "insufficient_data".to_string(),
         };
+        if n < SPEED_ACCURACY_MIN_ITEMS {
+            return Ok(profile);
+        }
 
-        Ok(questions)
+        // 用时与对错（0/1）的相关系数，正值表示越慢越准
+        let mean_ms = events.iter().map(|e| e.0 as f64).sum::<f64>() / n as f64;
+        let mean_correct = profile.accuracy;
+        let (mut cov, mut var_ms, mut var_correct) = (0.0, 0.0, 0.0);
+        for (ms, correct) in &events {
+            let (dx, dy) = (*ms as f64 - mean_ms, *correct as i32 as f64 - mean_correct);
+            cov += dx * dy;
+            var_ms += dx * dx;
+            var_correct += dy * dy;
+        }
+        profile.correlation = (var_ms > 0.0 && var_correct > 0.0).then(|| cov / (var_ms * var_correct).sqrt());
+
+        for (label, group) in ["fast", "medium", "slow"].iter().zip([&events[..n / 3], &events[n / 3..2 * n / 3], &events[2 * n / 3..]]) {
+            profile.buckets.push(crate::models::SpeedBucket {
+                label: label.to_string(),
+                min_ms: group.first().map_or(0, |e| e.0),
+                max_ms: group.last().map_or(0, |e| e.0),
+                items: group.len() as i32,
+                accuracy: accuracy(group),
+            });
+        }
+        let gain = profile.buckets[2].accuracy - profile.buckets[0].accuracy;
+        profile.recommendation = if gain >= SLOW_DOWN_ACCURACY_GAIN { "slow_down" } else { "balanced" }.to_string();
+        Ok(profile)
     }
 
-    fn get_wida_listening_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaListeningQuestion>> {
+    /// 连续练习天数（截至今天或昨天）
+    pub fn get_practice_streak(&self, user_name: &str) -> SqliteResult<i32> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation 
-             FROM wida_listening_questions WHERE id = ?"
+            "SELECT DISTINCT date(completed_at) FROM practice_history WHERE user_name = ? ORDER BY 1 DESC"
         )?;
-        let mut questions = stmt.query_map([id], |row| {
-            let options_json: String = row.get(7)?;
-            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
-            Ok(crate::models::WidaListeningQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                audio_text: row.get(4)?,
-                image_url: row.get(5)?,
-                question_text: row.get(6)?,
-                options,
-                correct_answer: row.get(8)?,
-                explanation: row.get(9)?,
-            })
-        })?;
-        Ok(questions.next().transpose()?)
+        let days = stmt
+            .query_map([user_name], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let today = chrono::Utc::now().date_naive();
+        let mut expected = today;
+        let mut streak = 0;
+        for day in days.iter().filter_map(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+            if streak == 0 && day == today - chrono::Duration::days(1) {
+                expected = day;
+            }
+            if day != expected {
+                break;
+            }
+            streak += 1;
+            expected = day - chrono::Duration::days(1);
+        }
+        Ok(streak)
     }
 
-    fn get_wida_reading_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaReadingQuestion>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation 
-             FROM wida_reading_questions WHERE id = ?"
-        )?;
-        let mut questions = stmt.query_map([id], |row| {
-            let options_json: String = row.get(7)?;
-            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
-            Ok(crate::models::WidaReadingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                passage: row.get(4)?,
-                question_text: row.get(5)?,
-                question_type: row.get(6)?,
-                options,
-                correct_answer: row.get(8)?,
-                explanation: row.get(9)?,
-            })
-        })?;
-        Ok(questions.next().transpose()?)
+    /// 统计当前到期待复习的单词数（user_name 为空时统计所有用户）
+    pub fn count_due_words(&self, user_name: Option<&str>) -> SqliteResult<i32> {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM word_mastery WHERE (?1 IS NULL OR user_name = ?1) AND next_review_at <= ?2",
+            rusqlite::params![user_name, now],
+            |row| row.get(0),
+        )
     }
 
-    fn get_wida_speaking_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaSpeakingQuestion>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
-             FROM wida_speaking_questions WHERE id = ?"
-        )?;
-        let mut questions = stmt.query_map([id], |row| {
-            let rubric_json: String = row.get(9)?;
-            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
-            Ok(crate::models::WidaSpeakingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                prompt_type: row.get(4)?,
-                prompt_text: row.get(5)?,
-                image_url: row.get(6)?,
-                audio_text: row.get(7)?,
-                sample_answer: row.get(8)?,
-                rubric,
-            })
-        })?;
-        Ok(questions.next().transpose()?)
+    /// 这些分词中当前到期待复习的数量
+    pub fn count_due_segments(&self, user_name: &str, segment_ids: &[i64]) -> SqliteResult<i32> {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let segment_ids = serde_json::to_string(segment_ids).unwrap_or_else(|_| "[]".to_string());
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM word_mastery
+             WHERE user_name = ?1 AND next_review_at <= ?2 AND segment_id IN (SELECT value FROM json_each(?3))",
+            rusqlite::params![user_name, now, segment_ids],
+            |row| row.get(0),
+        )
     }
 
-    fn get_wida_writing_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaWritingQuestion>> {
+    /// 当前到期待复习的单词，课程计划当前单元的单词在前，其余最早到期的在前
+    ///
+    /// `language` 不为空时只返回该语言的单词。
+    pub fn get_due_words(&self, user_name: &str, limit: i32, language: Option<&str>) -> SqliteResult<Vec<crate::models::WordMastery>> {
+        self.apply_review_pauses(user_name)?;
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let unit_articles = serde_json::to_string(&self.current_unit_article_ids(user_name)?).unwrap_or_else(|_| "[]".to_string());
         let mut stmt = self.conn.prepare(
-            "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
-             FROM wida_writing_questions WHERE id = ?"
+            "SELECT user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count, language
+             FROM word_mastery WHERE user_name = ?1 AND next_review_at <= ?2 AND (?4 IS NULL OR language = ?4)
+             ORDER BY lower(segment_content) IN (
+                          SELECT lower(content) FROM segments WHERE article_id IN (SELECT value FROM json_each(?5))
+                      ) DESC,
+                      next_review_at ASC, segment_id ASC LIMIT ?3",
         )?;
-        let mut questions = stmt.query_map([id], |row| {
-            let rubric_json: String = row.get(9)?;
-            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
-            Ok(crate::models::WidaWritingQuestion {
-                id: row.get(0)?,
-                grade_level: row.get(1)?,
-                domain: row.get(2)?,
-                difficulty: row.get(3)?,
-                task_type: row.get(4)?,
-                prompt: row.get(5)?,
-                image_url: row.get(6)?,
-                word_limit_min: row.get(7)?,
-                word_limit_max: row.get(8)?,
-                rubric,
-                sample_answer: row.get(10)?,
+        let words = stmt.query_map(rusqlite::params![user_name, now, limit, language, unit_articles], |row| {
+            Ok(crate::models::WordMastery {
+                user_name: row.get(0)?,
+                segment_id: row.get(1)?,
+                segment_content: row.get(2)?,
+                segment_type: row.get(3)?,
+                mastery_level: row.get(4)?,
+                ease_factor: row.get(5)?,
+                interval_days: row.get(6)?,
+                next_review_at: row.get(7)?,
+                last_review_at: row.get(8)?,
+                review_count: row.get(9)?,
+                language: row.get(10)?,
             })
-        })?;
-        Ok(questions.next().transpose()?)
+        })?.collect::<SqliteResult<Vec<_>>>();
+        words
     }
 
-    /// 提交答案
-    pub fn submit_wida_answer(&self, request: &crate::models::SubmitWidaAnswerRequest) -> SqliteResult<()> {
-        // 获取当前答案列表
-        let answers_json: String = self.conn.query_row(
-            "SELECT answers FROM wida_test_sessions WHERE id = ?",
-            [request.session_id],
-            |row| row.get(0),
-        )?;
+    /// 导航栏角标：待复习单词、未完成作业和未完成的 WIDA 测试数，一次查询返回
+    pub fn get_due_counts(&self, user_name: &str) -> SqliteResult<crate::models::DueCounts> {
+        self.apply_review_pauses(user_name)?;
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM word_mastery WHERE user_name = ?1 AND next_review_at <= ?2),
+                (SELECT COUNT(*) FROM assignments WHERE user_name = ?1 AND status = 'pending'),
+                (SELECT COUNT(*) FROM wida_test_sessions WHERE user_name = ?1 AND status = 'in_progress')",
+            rusqlite::params![user_name, now],
+            |row| {
+                Ok(crate::models::DueCounts {
+                    due_words: row.get(0)?,
+                    pending_assignments: row.get(1)?,
+                    unfinished_wida_sessions: row.get(2)?,
+                })
+            },
+        )
+    }
 
-        let mut answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers_json).unwrap_or_default();
+    /// 更新单词熟练度（SM-2 算法）
+    pub fn update_word_mastery(
+        &self,
+        user_name: &str,
+        segment_id: i64,
+        segment_content: &str,
+        segment_type: &str,
+        correct: bool,
+    ) -> SqliteResult<crate::models::WordMastery> {
+        self.update_word_mastery_graded(user_name, segment_id, segment_content, segment_type, correct, None)
+    }
+
+    /// 更新单词熟练度，可附带自评质量等级（0-5）
+    ///
+    /// 有等级时按完整 SM-2 公式计算难度因子和间隔，等级 ≥ 3 视为答对（`correct` 被忽略）；
+    /// 没有等级时沿用按熟练度查表的固定间隔。
+    pub fn update_word_mastery_graded(
+        &self,
+        user_name: &str,
+        segment_id: i64,
+        segment_content: &str,
+        segment_type: &str,
+        correct: bool,
+        quality: Option<i32>,
+    ) -> SqliteResult<crate::models::WordMastery> {
+        if quality.is_some_and(|q| !(0..=5).contains(&q)) {
+            return Err(rusqlite::Error::InvalidParameterName("质量等级必须在 0-5 之间".to_string()));
+        }
+        let correct = quality.map_or(correct, |q| q >= SM2_PASSING_QUALITY);
+        let now = chrono::Utc::now();
+        let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
+        let mastery_id = self.mastery_segment_id(user_name, segment_id, segment_content, segment_type)?;
         
-        // 添加新答案
-        answers.push(crate::models::WidaTestAnswer {
-            question_id: request.question_id,
-            user_answer: request.answer.clone(),
-            is_correct: None,
-            time_spent_seconds: request.time_spent_seconds,
-        });
+        // 查询现有记录
+        let mut stmt = self.conn.prepare(
+            "SELECT mastery_level, ease_factor, interval_days, review_count FROM word_mastery 
+             WHERE user_name = ?1 AND segment_id = ?2"
+        )?;
+        
+        let existing: Option<(i32, f64, i32, i32)> = stmt
+            .query_row(rusqlite::params![user_name, mastery_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .ok();
+        
+        // SM-2 算法计算
+        let (mastery_level, ease_factor, interval_days, review_count) = if let Some(quality) = quality {
+            let (ml, ef, iv, rc) = existing.unwrap_or((0, 2.5, 0, 0));
+            let (new_ef, new_iv) = sm2_schedule(ef, iv, quality);
+            if correct {
+                ((ml + 1).min(5), new_ef, new_iv, rc + 1)
+            } else {
+                ((ml - 1).max(0), new_ef, new_iv, rc)
+            }
+        } else if let Some((ml, ef, iv, rc)) = existing {
+            if correct {
+                // 答对：增加熟练度，延长间隔
+                let new_ml = (ml + 1).min(5);
+                let new_ef = (ef + 0.1).min(3.0).max(1.3);
+                let new_iv = match new_ml {
+                    0 => 1,
+                    1 => 1,
+                    2 => 3,
+                    3 => 7,
+                    4 => 14,
+                    5 => 30,
+                    _ => iv,
+                };
+                (new_ml, new_ef, new_iv, rc + 1)
+            } else {
+                // 答错：降低熟练度，重置间隔
+                let new_ml = (ml - 1).max(0);
+                let new_ef = (ef - 0.2).max(1.3);
+                let new_iv = 0; // 立即需要再次复习
+                (new_ml, new_ef, new_iv, rc)
+            }
+        } else {
+            // 新单词
+            if correct {
+                (1, 2.5, 1, 1) // 答对后熟练度1，间隔1天
+            } else {
+                (0, 2.5, 0, 0) // 答错保持新词状态
+            }
+        };
 
-        let new_answers_json = serde_json::to_string(&answers).unwrap_or_else(|_| "[]".to_string());
-        let new_current_question = answers.len() as i32;
+        // 逾期复习仍然答对，说明记忆比预期牢固，按逾期天数加长间隔
+        let interval_days = if correct {
+            interval_days + overdue_credit(interval_days, self.overdue_days(user_name, mastery_id, now)?)
+        } else {
+            interval_days
+        };
 
+        // 顽固词答对后也只安排短间隔的加强复习
+        let interval_days = if correct && self.relapse_count(user_name, mastery_id)? >= LEECH_RELAPSES {
+            interval_days.min(LEECH_MAX_INTERVAL_DAYS)
+        } else {
+            interval_days
+        };
+        
+        // 计算下次复习时间
+        let next_review = if interval_days == 0 {
+            // 答错或新词，当天或明天继续
+            now_str.clone()
+        } else {
+            // 到期日随机浮动 ±10%，分散复习量
+            let fuzz = crate::ordering::fuzz_days(interval_days, crate::ordering::random_seed() ^ mastery_id as u64);
+            let next = now + chrono::Duration::days((interval_days + fuzz) as i64);
+            next.format("%Y-%m-%d %H:%M:%S").to_string()
+        };
+        
+        // 保存到数据库
         self.conn.execute(
-            "UPDATE wida_test_sessions SET answers = ?, current_question = ? WHERE id = ?",
-            rusqlite::params![new_answers_json, new_current_question, request.session_id],
+            "INSERT INTO word_mastery (user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(user_name, segment_id) DO UPDATE SET
+                mastery_level = excluded.mastery_level,
+                ease_factor = excluded.ease_factor,
+                interval_days = excluded.interval_days,
+                next_review_at = excluded.next_review_at,
+                last_review_at = excluded.last_review_at,
+                review_count = excluded.review_count,
+                segment_content = excluded.segment_content,
+                segment_type = excluded.segment_type",
+            rusqlite::params![
+                user_name,
+                mastery_id,
+                segment_content,
+                segment_type,
+                mastery_level,
+                ease_factor,
+                interval_days,
+                next_review,
+                now_str,
+                review_count
+            ],
         )?;
 
-        Ok(())
-    }
-
-    /// 完成测试并计算成绩
-    pub fn complete_wida_test(&self, request: &crate::models::CompleteWidaTestRequest) -> SqliteResult<crate::models::WidaTestReport> {
-        let session = self.get_wida_test_session(request.session_id)?.ok_or_else(|| {
-            rusqlite::Error::QueryReturnedNoRows
-        })?;
-
-        // 获取答案和题目
-        let answers_json: String = self.conn.query_row(
-            "SELECT answers FROM wida_test_sessions WHERE id = ?",
-            [request.session_id],
-            |row| row.get(0),
+        let old_level = existing.map(|(ml, _, _, _)| ml);
+        self.conn.execute(
+            "INSERT INTO mastery_events (user_name, segment_id, segment_content, old_level, new_level, correct, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![user_name, mastery_id, segment_content, old_level, mastery_level, correct, now_str],
         )?;
 
-        let question_ids_json: String = self.conn.query_row(
-            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
-            [request.session_id],
+        self.record_mistake_outcome(user_name, segment_id, correct, mastery_level)?;
+        self.log_served(user_name, segment_id, segment_content)?;
+        let language: Option<String> = self.conn.query_row(
+            "SELECT language FROM word_mastery WHERE user_name = ? AND segment_id = ?",
+            rusqlite::params![user_name, mastery_id],
             |row| row.get(0),
         )?;
-
-        let answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers_json).unwrap_or_default();
-        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
-
-        // 计算成绩
-        let mut correct_count = 0;
-        let mut details: Vec<crate::models::WidaAnswerDetail> = Vec::new();
-        let total_count = question_ids.len() as i32;
-
-        // 根据测试类型验证答案
-        for (idx, &question_id) in question_ids.iter().enumerate() {
-            if idx < answers.len() {
-                let answer = &answers[idx];
-                let is_correct = self.check_wida_answer(&session.test_type, question_id, &answer.user_answer)?;
-                
-                if is_correct {
-                    correct_count += 1;
-                }
-
-                // 获取题目文本和正确答案
-                let (question_text, correct_answer_text) = self.get_wida_question_info(&session.test_type, question_id)?;
-
-                details.push(crate::models::WidaAnswerDetail {
-                    question_id,
-                    question_text,
-                    user_answer: answer.user_answer.clone(),
-                    correct_answer: correct_answer_text,
-                    is_correct,
-                    time_spent_seconds: answer.time_spent_seconds,
-                    explanation: None,
-                });
-            }
-        }
-
-        let accuracy = if total_count > 0 {
-            (correct_count as f64 / total_count as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        // 计算 Scale Score (100-600)
-        let score = 100.0 + (accuracy / 100.0) * 500.0;
         
-        // 计算 Proficiency Level (1-6)
-        let proficiency_level = if score >= 550.0 { 6 }
-            else if score >= 475.0 { 5 }
-            else if score >= 400.0 { 4 }
-            else if score >= 325.0 { 3 }
-            else if score >= 250.0 { 2 }
-            else { 1 };
-
-        let proficiency_level_name = match proficiency_level {
-            1 => "Entering",
-            2 => "Emerging",
-            3 => "Developing",
-            4 => "Expanding",
-            5 => "Bridging",
-            6 => "Reaching",
-            _ => "Unknown",
-        }.to_string();
+        Ok(crate::models::WordMastery {
+            user_name: user_name.to_string(),
+            segment_id: mastery_id,
+            segment_content: segment_content.to_string(),
+            segment_type: segment_type.to_string(),
+            mastery_level,
+            ease_factor,
+            interval_days,
+            next_review_at: next_review,
+            last_review_at: now_str,
+            review_count,
+            language,
+        })
+    }
 
-        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    /// 批量更新单词熟练度：全部在一个事务中完成，任一失败则整体回滚
+    pub fn update_word_masteries_batch(
+        &self,
+        updates: &[crate::models::UpdateMasteryRequest],
+    ) -> SqliteResult<Vec<crate::models::WordMastery>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let masteries = updates
+            .iter()
+            .map(|u| {
+                self.update_word_mastery_graded(&u.user_name, u.segment_id, &u.segment_content, &u.segment_type, u.correct, u.quality)
+            })
+            .collect::<SqliteResult<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(masteries)
+    }
 
-        // 更新会话状态
-        self.conn.execute(
-            "UPDATE wida_test_sessions SET status = 'completed', score = ?, proficiency_level = ?, completed_at = ? WHERE id = ?",
-            rusqlite::params![score, proficiency_level, now, request.session_id],
+    /// 词汇量增长曲线：按周统计首次达到掌握等级的不同单词数及累计值
+    pub fn get_vocabulary_growth(&self, user_name: &str) -> SqliteResult<Vec<crate::models::VocabularyGrowthPoint>> {
+        // 以周一作为每周的开始
+        let mut stmt = self.conn.prepare(
+            "SELECT date(first_at, 'weekday 0', '-6 days') AS week_start, COUNT(*)
+             FROM (
+                SELECT lower(segment_content) AS word, MIN(created_at) AS first_at
+                FROM mastery_events
+                WHERE user_name = ?1 AND new_level >= ?2
+                GROUP BY word
+             )
+             GROUP BY week_start ORDER BY week_start"
         )?;
+        let weeks = stmt
+            .query_map(rusqlite::params![user_name, KNOWN_WORD_LEVEL], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut total_words = 0;
+        Ok(weeks
+            .into_iter()
+            .map(|(week_start, new_words)| {
+                total_words += new_words;
+                crate::models::VocabularyGrowthPoint { week_start, new_words, total_words }
+            })
+            .collect())
+    }
 
-        // 保存到历史记录
-        self.conn.execute(
-            "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0)",
-            rusqlite::params![
-                session.user_name,
-                session.test_type,
-                session.grade_level,
-                score,
-                proficiency_level,
-                accuracy,
-                total_count,
-                correct_count
-            ],
+    /// 熟练度变更事件（新到旧），可按分词筛选
+    pub fn get_mastery_events(
+        &self,
+        user_name: &str,
+        segment_id: Option<i64>,
+        limit: i32,
+    ) -> SqliteResult<Vec<crate::models::MasteryEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, segment_id, segment_content, old_level, new_level, correct, created_at
+             FROM mastery_events
+             WHERE user_name = ?1 AND (?2 IS NULL OR segment_id = ?2)
+             ORDER BY created_at DESC, id DESC LIMIT ?3"
         )?;
+        let events = stmt
+            .query_map(rusqlite::params![user_name, segment_id, limit], Self::map_mastery_event)?
+            .collect::<SqliteResult<Vec<_>>>();
+        events
+    }
 
-        Ok(crate::models::WidaTestReport {
-            session: crate::models::WidaTestSession {
-                id: session.id,
-                user_name: session.user_name,
-                test_type: session.test_type.clone(),
-                grade_level: session.grade_level,
-                domain: session.domain,
-                status: "completed".to_string(),
-                current_question: session.current_question,
-                total_questions: session.total_questions,
-                answers: session.answers,
-                score: Some(score),
-                proficiency_level: Some(proficiency_level),
-                started_at: session.started_at,
-                completed_at: Some(now),
-                duration_seconds: session.duration_seconds,
-            },
-            correct_count,
-            total_count,
-            accuracy,
-            listening_score: if session.test_type == "listening" { Some(score) } else { None },
-            reading_score: if session.test_type == "reading" { Some(score) } else { None },
-            speaking_score: if session.test_type == "speaking" { Some(score) } else { None },
-            writing_score: if session.test_type == "writing" { Some(score) } else { None },
-            overall_score: score,
-            proficiency_level,
-            proficiency_level_name,
-            details,
-        })
+    /// 遗忘检测：曾达到掌握等级、目前又跌回以下的单词，返回每个单词最近一次跌落的事件
+    pub fn get_relapsed_words(&self, user_name: &str) -> SqliteResult<Vec<crate::models::MasteryEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.id, e.user_name, e.segment_id, e.segment_content, e.old_level, e.new_level, e.correct, e.created_at
+             FROM mastery_events e
+             JOIN word_mastery m ON m.user_name = e.user_name AND m.segment_id = e.segment_id
+             WHERE e.user_name = ?1 AND e.old_level >= ?2 AND e.new_level < ?2 AND m.mastery_level < ?2
+               AND e.id = (
+                   SELECT MAX(id) FROM mastery_events
+                   WHERE user_name = e.user_name AND segment_id = e.segment_id AND old_level >= ?2 AND new_level < ?2
+               )
+             ORDER BY e.created_at DESC"
+        )?;
+        let events = stmt
+            .query_map(rusqlite::params![user_name, KNOWN_WORD_LEVEL], Self::map_mastery_event)?
+            .collect::<SqliteResult<Vec<_>>>();
+        events
     }
 
-    fn check_wida_answer(&self, test_type: &str, question_id: i64, user_answer: &str) -> SqliteResult<bool> {
-        match test_type {
-            "listening" => {
-                if let Some(q) = self.get_wida_listening_question_by_id(question_id)? {
-                    return Ok(user_answer.parse::<i32>().unwrap_or(-1) == q.correct_answer);
-                }
-            }
-            "reading" => {
-                if let Some(q) = self.get_wida_reading_question_by_id(question_id)? {
-                    return Ok(user_answer.parse::<i32>().unwrap_or(-1) == q.correct_answer);
-                }
+    /// 顽固词：多次从掌握（≥3 级）跌回 1 级及以下的单词，遗忘次数多的在前
+    pub fn get_leech_words(&self, user_name: &str) -> SqliteResult<Vec<crate::models::LeechWord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.segment_id, e.new_level, e.created_at, m.segment_content, m.segment_type, m.mastery_level
+             FROM mastery_events e
+             JOIN word_mastery m ON m.user_name = e.user_name AND m.segment_id = e.segment_id
+             WHERE e.user_name = ?1
+             ORDER BY e.segment_id, e.id"
+        )?;
+        let rows = stmt
+            .query_map([user_name], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i32>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, i32>(5)?,
+                ))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut leeches: Vec<crate::models::LeechWord> = Vec::new();
+        for group in rows.chunk_by(|a, b| a.0 == b.0) {
+            let (relapse_count, last_relapse_at) = count_relapses(group.iter().map(|r| (r.1, r.2.as_str())));
+            if relapse_count >= LEECH_RELAPSES {
+                let (segment_id, _, _, segment_content, segment_type, mastery_level) = group[0].clone();
+                leeches.push(crate::models::LeechWord {
+                    segment_id,
+                    segment_content,
+                    segment_type,
+                    mastery_level,
+                    relapse_count,
+                    last_relapse_at: last_relapse_at.unwrap_or_default(),
+                });
             }
-            // 口语和写作需要人工评分，暂时返回true
-            "speaking" | "writing" => return Ok(true),
-            _ => {}
         }
-        Ok(false)
+        leeches.sort_by(|a, b| b.relapse_count.cmp(&a.relapse_count).then(b.last_relapse_at.cmp(&a.last_relapse_at)));
+        Ok(leeches)
     }
 
-    fn get_wida_question_info(&self, test_type: &str, question_id: i64) -> SqliteResult<(String, String)> {
-        match test_type {
-            "listening" => {
-                if let Some(q) = self.get_wida_listening_question_by_id(question_id)? {
-                    return Ok((q.question_text, q.options.get(q.correct_answer as usize).cloned().unwrap_or_default()));
-                }
-            }
-            "reading" => {
-                if let Some(q) = self.get_wida_reading_question_by_id(question_id)? {
-                    return Ok((q.question_text, q.options.get(q.correct_answer as usize).cloned().unwrap_or_default()));
-                }
-            }
-            "speaking" => {
-                if let Some(q) = self.get_wida_speaking_question_by_id(question_id)? {
-                    return Ok((q.prompt_text, q.sample_answer));
+    /// 找出包含最薄弱单词的句子，用于语境听写复习
+    ///
+    /// 薄弱词取熟练度最低的 `word_limit` 个未掌握单词；句子按包含的薄弱词数量从多到少排列。
+    pub fn get_context_sentences(&self, user_name: &str, word_limit: i32) -> SqliteResult<Vec<crate::models::ContextSentence>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT lower(segment_content) FROM word_mastery
+             WHERE user_name = ?1 AND segment_type = 'word' AND mastery_level < ?2
+             ORDER BY mastery_level, ease_factor, review_count DESC LIMIT ?3"
+        )?;
+        let weak_words: Vec<String> = stmt
+            .query_map(rusqlite::params![user_name, MISTAKE_GRADUATION_LEVEL, word_limit], |row| row.get(0))?
+            .collect::<SqliteResult<_>>()?;
+        if weak_words.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, article_id, content FROM segments WHERE segment_type = 'sentence' ORDER BY article_id, order_index"
+        )?;
+        let sentences = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut result: Vec<crate::models::ContextSentence> = sentences
+            .into_iter()
+            .filter_map(|(segment_id, article_id, content)| {
+                let tokens = crate::text::tokenize(&content);
+                let matched: Vec<String> = weak_words.iter().filter(|w| tokens.contains(w)).cloned().collect();
+                (!matched.is_empty()).then_some(crate::models::ContextSentence {
+                    segment_id,
+                    article_id,
+                    content,
+                    weak_words: matched,
+                })
+            })
+            .collect();
+        result.sort_by_key(|s| std::cmp::Reverse(s.weak_words.len()));
+        Ok(result)
+    }
+
+    /// 按距上次复习的天数分组统计回忆率，用于检验复习间隔是否达到约 90% 的保持率
+    ///
+    /// 每个单词相邻两次熟练度事件之间的间隔算一次复习，后一次答对即为记住；迁移补录的事件不计。
+    pub fn get_retention_stats(&self, user_name: &str) -> SqliteResult<Vec<crate::models::RetentionBucket>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT segment_id, created_at, correct FROM mastery_events WHERE user_name = ?1 ORDER BY segment_id, id"
+        )?;
+        let rows = stmt
+            .query_map([user_name], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<bool>>(2)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut buckets: Vec<crate::models::RetentionBucket> = RETENTION_BUCKETS
+            .iter()
+            .map(|&(min_days, max_days)| crate::models::RetentionBucket {
+                min_days,
+                max_days,
+                reviews: 0,
+                recalled: 0,
+                retention_rate: 0.0,
+            })
+            .collect();
+        let parse = |at: &str| chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M:%S").ok();
+        for group in rows.chunk_by(|a, b| a.0 == b.0) {
+            for pair in group.windows(2) {
+                let (Some(previous), Some(current), Some(correct)) = (parse(&pair[0].1), parse(&pair[1].1), pair[1].2) else {
+                    continue;
+                };
+                let elapsed = (current - previous).num_days();
+                if let Some(bucket) = buckets.iter_mut().find(|b| elapsed >= b.min_days && b.max_days.is_none_or(|max| elapsed <= max)) {
+                    bucket.reviews += 1;
+                    bucket.recalled += correct as i32;
                 }
             }
-            "writing" => {
-                if let Some(q) = self.get_wida_writing_question_by_id(question_id)? {
-                    return Ok((q.prompt, q.sample_answer.unwrap_or_default()));
-                }
+        }
+        for bucket in &mut buckets {
+            if bucket.reviews > 0 {
+                bucket.retention_rate = bucket.recalled as f64 / bucket.reviews as f64 * 100.0;
             }
-            _ => {}
         }
-        Ok(("".to_string(), "".to_string()))
+        Ok(buckets)
     }
 
-    /// 获取用户测试历史
-    pub fn get_wida_history(&self, user_name: &str, test_type: Option<&str>, limit: Option<i32>) -> SqliteResult<Vec<crate::models::WidaHistoryRecord>> {
-        let sql = match (test_type, limit) {
-            (Some(t), Some(l)) => format!(
-                "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
-                 FROM wida_test_history WHERE user_name = '{}' AND test_type = '{}' ORDER BY completed_at DESC LIMIT {}",
-                user_name, t, l
-            ),
-            (None, Some(l)) => format!(
-                "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
-                 FROM wida_test_history WHERE user_name = '{}' ORDER BY completed_at DESC LIMIT {}",
-                user_name, l
-            ),
-            (Some(t), None) => format!(
-                "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
-                 FROM wida_test_history WHERE user_name = '{}' AND test_type = '{}' ORDER BY completed_at DESC",
-                user_name, t
+    /// 顽固词的分词 ID，与 `get_leech_words` 的判定一致，但只用一条聚合查询
+    ///
+    /// 只看掌握（>= `KNOWN_WORD_LEVEL`）和跌回（<= `RELAPSE_LEVEL`）两类事件，跌回事件的前一条是掌握时记一次遗忘。
+    fn leech_segment_ids(&self, user_name: &str) -> SqliteResult<std::collections::HashSet<i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT segment_id FROM (
+                 SELECT segment_id, new_level, LAG(new_level) OVER (PARTITION BY segment_id ORDER BY id) AS previous_level
+                 FROM mastery_events
+                 WHERE user_name = ?1 AND (new_level >= ?2 OR new_level <= ?3)
+                   AND segment_id IN (SELECT segment_id FROM word_mastery WHERE user_name = ?1)
+             )
+             WHERE new_level <= ?3 AND previous_level >= ?2
+             GROUP BY segment_id HAVING COUNT(*) >= ?4",
+        )?;
+        let ids = stmt
+            .query_map(rusqlite::params![user_name, KNOWN_WORD_LEVEL, RELAPSE_LEVEL, LEECH_RELAPSES], |row| row.get(0))?
+            .collect();
+        ids
+    }
+
+    /// 距离应复习时间已逾期的整天数（未到期或没有记录时为 0）
+    fn overdue_days(&self, user_name: &str, segment_id: i64, now: chrono::DateTime<chrono::Utc>) -> SqliteResult<i64> {
+        let mut stmt = self.conn.prepare(
+            "SELECT next_review_at FROM word_mastery WHERE user_name = ?1 AND segment_id = ?2"
+        )?;
+        let due: Option<Option<String>> = stmt
+            .query_map(rusqlite::params![user_name, segment_id], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        Ok(due
+            .flatten()
+            .and_then(|due| chrono::NaiveDateTime::parse_from_str(&due, "%Y-%m-%d %H:%M:%S").ok())
+            .map_or(0, |due| (now.naive_utc() - due).num_days().max(0)))
+    }
+
+    fn relapse_count(&self, user_name: &str, segment_id: i64) -> SqliteResult<i32> {
+        let mut stmt = self.conn.prepare(
+            "SELECT new_level, created_at FROM mastery_events WHERE user_name = ?1 AND segment_id = ?2 ORDER BY id"
+        )?;
+        let levels = stmt
+            .query_map(rusqlite::params![user_name, segment_id], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(count_relapses(levels.iter().map(|(level, at)| (*level, at.as_str()))).0)
+    }
+
+    fn map_mastery_event(row: &rusqlite::Row) -> SqliteResult<crate::models::MasteryEvent> {
+        Ok(crate::models::MasteryEvent {
+            id: row.get(0)?,
+            user_name: row.get(1)?,
+            segment_id: row.get(2)?,
+            segment_content: row.get(3)?,
+            old_level: row.get(4)?,
+            new_level: row.get(5)?,
+            correct: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+
+    /// 获取用户所有单词的熟练度
+    pub fn get_word_masteries(
+        &self,
+        user_name: &str,
+        segment_type: Option<&str>,
+    ) -> SqliteResult<Vec<crate::models::WordMastery>> {
+        let sql = match segment_type {
+            Some(st) => format!(
+                "SELECT user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count, language 
+                 FROM word_mastery WHERE user_name = '{}' AND segment_type = '{}' ORDER BY mastery_level ASC",
+                user_name, st
             ),
-            (None, None) => format!(
-                "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
-                 FROM wida_test_history WHERE user_name = '{}' ORDER BY completed_at DESC",
+            None => format!(
+                "SELECT user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count, language 
+                 FROM word_mastery WHERE user_name = '{}' ORDER BY mastery_level ASC",
                 user_name
             ),
         };
-
+        
         let mut stmt = self.conn.prepare(&sql)?;
-        let records = stmt.query_map([], |row| {
-            Ok(crate::models::WidaHistoryRecord {
-                id: row.get(0)?,
-                user_name: row.get(1)?,
-                test_type: row.get(2)?,
-                grade_level: row.get(3)?,
-                score: row.get(4)?,
-                proficiency_level: row.get(5)?,
-                accuracy: row.get(6)?,
-                total_questions: row.get(7)?,
-                correct_count: row.get(8)?,
-                duration_seconds: row.get(9)?,
-                completed_at: row.get(10)?,
+        let masteries: SqliteResult<Vec<_>> = stmt.query_map([], |row| {
+            Ok(crate::models::WordMastery {
+                user_name: row.get(0)?,
+                segment_id: row.get(1)?,
+                segment_content: row.get(2)?,
+                segment_type: row.get(3)?,
+                mastery_level: row.get(4)?,
+                ease_factor: row.get(5)?,
+                interval_days: row.get(6)?,
+                next_review_at: row.get(7)?,
+                last_review_at: row.get(8)?,
+                review_count: row.get(9)?,
+                language: row.get(10)?,
             })
-        })?.collect::<SqliteResult<Vec<_>>>();
-        records
+        })?.collect();
+        
+        Ok(masteries?)
     }
 
-    /// 获取用户综合报告
-    pub fn get_wida_comprehensive_report(&self, user_name: &str) -> SqliteResult<crate::models::WidaComprehensiveReport> {
-        let history = self.get_wida_history(user_name, None, Some(100))?;
-
-        let mut listening_scores: Vec<f64> = Vec::new();
-        let mut reading_scores: Vec<f64> = Vec::new();
-        let mut speaking_scores: Vec<f64> = Vec::new();
-        let mut writing_scores: Vec<f64> = Vec::new();
-
-        for record in &history {
-            match record.test_type.as_str() {
-                "listening" => listening_scores.push(record.score),
-                "reading" => reading_scores.push(record.score),
-                "speaking" => speaking_scores.push(record.score),
-                "writing" => writing_scores.push(record.score),
-                _ => {}
-            }
-        }
-
-        let listening_score = if !listening_scores.is_empty() { Some(listening_scores.iter().sum::<f64>() / listening_scores.len() as f64) } else { None };
-        let reading_score = if !reading_scores.is_empty() { Some(reading_scores.iter().sum::<f64>() / reading_scores.len() as f64) } else { None };
-        let speaking_score = if !speaking_scores.is_empty() { Some(speaking_scores.iter().sum::<f64>() / speaking_scores.len() as f64) } else { None };
-        let writing_score = if !writing_scores.is_empty() { Some(writing_scores.iter().sum::<f64>() / writing_scores.len() as f64) } else { None };
-
-        let listening_level = listening_score.map(|s| score_to_level(s));
-        let reading_level = reading_score.map(|s| score_to_level(s));
-        let speaking_level = speaking_score.map(|s| score_to_level(s));
-        let writing_level = writing_score.map(|s| score_to_level(s));
+    // ========== 练习历史记录 ==========
 
-        // 计算综合分数
-        let oral_score = match (&listening_score, &speaking_score) {
-            (Some(l), Some(s)) => Some((l + s) / 2.0),
-            (Some(l), None) => Some(*l),
-            (None, Some(s)) => Some(*s),
-            _ => None,
+    /// 保存练习历史
+    pub fn save_practice_history(
+        &self,
+        user_name: &str,
+        article_id: i64,
+        segment_type: &str,
+        correct_count: i32,
+        incorrect_count: i32,
+        duration_seconds: i32,
+    ) -> SqliteResult<i64> {
+        let total_count = correct_count + incorrect_count;
+        let accuracy = if total_count > 0 {
+            (correct_count as f64 / total_count as f64) * 100.0
+        } else {
+            0.0
         };
-
-        let literacy_score = match (&reading_score, &writing_score) {
-            (Some(r), Some(w)) => Some((r + w) / 2.0),
-            (Some(r), None) => Some(*r),
-            (None, Some(w)) => Some(*w),
-            _ => None,
+        
+        // 计算WPM（每分钟单词数）
+        let wpm = if duration_seconds > 0 {
+            (total_count as f64 / duration_seconds as f64) * 60.0
+        } else {
+            0.0
         };
+        
+        self.conn.execute(
+            "INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, incorrect_count, total_count, accuracy, wpm, duration_seconds) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                user_name,
+                article_id,
+                segment_type,
+                correct_count,
+                incorrect_count,
+                total_count,
+                accuracy,
+                wpm,
+                duration_seconds
+            ],
+        )?;
+        let history_id = self.conn.last_insert_rowid();
 
-        // 总分 = 30%听口 + 70%读写
-        let overall_score = match (&oral_score, &literacy_score) {
-            (Some(o), Some(l)) => o * 0.3 + l * 0.7,
-            (Some(o), None) => *o,
-            (None, Some(l)) => *l,
-            _ => 0.0,
-        };
+        // 完成练习即视为完成对应的作业
+        self.conn.execute(
+            "UPDATE assignments SET status = 'completed', completed_at = CURRENT_TIMESTAMP
+             WHERE user_name = ?1 AND article_id = ?2 AND segment_type = ?3 AND status = 'pending'",
+            rusqlite::params![user_name, article_id, segment_type],
+        )?;
+        Ok(history_id)
+    }
 
-        let overall_level = score_to_level(overall_score);
-        let last_test_date = history.first().map(|r| r.completed_at.clone()).unwrap_or_default();
+    /// 获取用户练习历史
+    pub fn get_practice_history(
+        &self,
+        user_name: &str,
+        limit: i32,
+    ) -> SqliteResult<Vec<crate::models::PracticeHistory>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT h.id, h.user_name, h.article_id, a.title, h.segment_type, h.correct_count, h.incorrect_count, h.total_count, h.accuracy, h.wpm, h.duration_seconds, h.completed_at, h.goal, h.goal_met, h.mode
+             FROM practice_history h
+             LEFT JOIN articles a ON h.article_id = a.id
+             WHERE h.user_name = ?1
+             ORDER BY h.completed_at DESC
+             LIMIT ?2",
+        )?;
+        let histories = stmt.query_map(rusqlite::params![user_name, limit], |row| {
+            Ok(crate::models::PracticeHistory {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                article_id: row.get(2)?,
+                article_title: row.get(3).unwrap_or_else(|_| "未知文章".to_string()),
+                segment_type: row.get(4)?,
+                correct_count: row.get(5)?,
+                incorrect_count: row.get(6)?,
+                total_count: row.get(7)?,
+                accuracy: row.get(8)?,
+                wpm: row.get(9)?,
+                duration_seconds: row.get(10)?,
+                completed_at: row.get(11)?,
+                goal: row.get(12)?,
+                goal_met: row.get(13)?,
+                mode: row.get(14)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        
+        histories
+    }
 
-        Ok(crate::models::WidaComprehensiveReport {
+    /// 获取用户统计信息
+    ///
+    /// `compare` 为 true 时附带与其他本地用户的匿名百分位对比
+    pub fn get_user_statistics(&self, user_name: &str, compare: bool) -> SqliteResult<crate::models::UserStatistics> {
+        // 总体统计
+        let stats_sql = format!(
+            "SELECT 
+                COUNT(*) as total_practices,
+                COALESCE(SUM(correct_count), 0) as total_correct,
+                COALESCE(SUM(incorrect_count), 0) as total_incorrect,
+                COALESCE(SUM(total_count), 0) as total_words,
+                COALESCE(AVG(accuracy), 0) as avg_accuracy,
+                COALESCE(AVG(wpm), 0) as avg_wpm,
+                COALESCE(MAX(accuracy), 0) as best_accuracy,
+                COALESCE(MAX(wpm), 0) as best_wpm,
+                COALESCE(SUM(duration_seconds), 0) as total_duration_seconds
+             FROM practice_history 
+             WHERE user_name = '{}'",
+            user_name
+        );
+        
+        let (total_practices, total_correct, total_incorrect, total_words, avg_accuracy, avg_wpm, best_accuracy, best_wpm, total_duration_seconds): (
+            i32, i32, i32, i32, f64, f64, f64, f64, i32
+        ) = self.conn.query_row(&stats_sql, [], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                row.get(8)?,
+            ))
+        })?;
+        
+        // 获取最近的练习记录
+        let recent_histories = self.get_practice_history(user_name, 10)?;
+
+        // 按练习模式分别统计
+        let mut stmt = self.conn.prepare(
+            "SELECT mode, COUNT(*), COALESCE(SUM(total_count), 0), COALESCE(AVG(accuracy), 0), COALESCE(SUM(duration_seconds), 0)
+             FROM practice_history WHERE user_name = ? GROUP BY mode ORDER BY mode",
+        )?;
+        let by_mode = stmt
+            .query_map([user_name], |row| {
+                Ok(crate::models::PracticeModeStats {
+                    mode: row.get(0)?,
+                    practices: row.get(1)?,
+                    total_words: row.get(2)?,
+                    avg_accuracy: row.get(3)?,
+                    total_duration_minutes: row.get::<_, i64>(4)? as f64 / 60.0,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let comparison = if compare && total_practices > 0 {
+            self.get_cohort_comparison(user_name, avg_accuracy, avg_wpm)?
+        } else {
+            None
+        };
+        
+        Ok(crate::models::UserStatistics {
             user_name: user_name.to_string(),
-            listening_score,
-            listening_level,
-            reading_score,
-            reading_level,
-            speaking_score,
-            speaking_level,
-            writing_score,
-            writing_level,
-            oral_score,
-            literacy_score,
-            overall_score,
-            overall_level,
-            test_count: history.len() as i32,
-            last_test_date,
+            total_practices,
+            total_correct,
+            total_incorrect,
+            total_words,
+            avg_accuracy,
+            avg_wpm,
+            best_accuracy,
+            best_wpm,
+            total_duration_minutes: total_duration_seconds as f64 / 60.0,
+            recent_histories,
+            by_mode,
+            comparison,
         })
     }
 
-    /// 获取进行中的测试会话
-    pub fn get_active_wida_sessions(&self, user_name: &str) -> SqliteResult<Vec<crate::models::WidaTestSession>> {
+    /// 计算用户在其他本地用户中的正确率/WPM 百分位（不返回其他用户的名字和成绩）
+    ///
+    /// 百分位 = 低于该用户的人数占比，持平的人算一半；没有其他用户时返回 None。
+    fn get_cohort_comparison(
+        &self,
+        user_name: &str,
+        avg_accuracy: f64,
+        avg_wpm: f64,
+    ) -> SqliteResult<Option<crate::models::CohortComparison>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, score, proficiency_level, started_at, completed_at, duration_seconds
-             FROM wida_test_sessions WHERE user_name = ? AND status = 'in_progress' ORDER BY started_at DESC"
+            "SELECT AVG(accuracy), AVG(wpm) FROM practice_history
+             WHERE user_name != ?1 GROUP BY user_name"
         )?;
-        
-        let sessions = stmt.query_map([user_name], |row| {
-            Ok(crate::models::WidaTestSession {
+        let others = stmt
+            .query_map([user_name], |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        if others.is_empty() {
+            return Ok(None);
+        }
+
+        let percentile = |values: Vec<f64>, own: f64| {
+            let below = values.iter().filter(|&&v| v < own).count() as f64;
+            let equal = values.iter().filter(|&&v| v == own).count() as f64;
+            (below + equal / 2.0) / values.len() as f64 * 100.0
+        };
+        Ok(Some(crate::models::CohortComparison {
+            cohort_size: others.len() as i32,
+            accuracy_percentile: percentile(others.iter().map(|o| o.0).collect(), avg_accuracy),
+            wpm_percentile: percentile(others.iter().map(|o| o.1).collect(), avg_wpm),
+        }))
+    }
+
+    // ========== 作业管理 ==========
+
+    /// 布置作业
+    pub fn create_assignment(&self, request: &crate::models::CreateAssignmentRequest) -> SqliteResult<i64> {
+        self.conn.execute(
+            "INSERT INTO assignments (user_name, article_id, segment_type, title, due_date, check_options) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                request.user_name,
+                request.article_id,
+                request.segment_type,
+                request.title,
+                request.due_date,
+                request.check_options.as_ref().and_then(|o| serde_json::to_string(o).ok()),
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 获取作业列表（可按学生、状态筛选）
+    pub fn get_assignments(
+        &self,
+        user_name: Option<&str>,
+        status: Option<&str>,
+    ) -> SqliteResult<Vec<crate::models::Assignment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.user_name, s.article_id, COALESCE(a.title, ''), s.segment_type, s.title,
+                    s.due_date, s.status, s.created_at, s.completed_at, s.check_options
+             FROM assignments s LEFT JOIN articles a ON s.article_id = a.id
+             WHERE (?1 IS NULL OR s.user_name = ?1) AND (?2 IS NULL OR s.status = ?2)
+             ORDER BY s.status DESC, s.due_date IS NULL, s.due_date, s.created_at DESC"
+        )?;
+        let assignments = stmt.query_map(rusqlite::params![user_name, status], |row| {
+            Ok(crate::models::Assignment {
                 id: row.get(0)?,
                 user_name: row.get(1)?,
-                test_type: row.get(2)?,
-                grade_level: row.get(3)?,
-                domain: row.get(4)?,
-                status: row.get(5)?,
-                current_question: row.get(6)?,
-                total_questions: row.get(7)?,
-                answers: row.get(9)?,
-                score: row.get(10)?,
-                proficiency_level: row.get(11)?,
-                started_at: row.get(12)?,
-                completed_at: row.get(13)?,
-                duration_seconds: row.get(14)?,
+                article_id: row.get(2)?,
+                article_title: row.get(3)?,
+                segment_type: row.get(4)?,
+                title: row.get(5)?,
+                due_date: row.get(6)?,
+                check_options: row.get::<_, Option<String>>(10)?.and_then(|json| serde_json::from_str(&json).ok()),
+                status: row.get(7)?,
+                created_at: row.get(8)?,
+                completed_at: row.get(9)?,
             })
         })?.collect::<SqliteResult<Vec<_>>>();
-        sessions
+        assignments
     }
 
-    /// 删除测试会话
-    pub fn delete_wida_session(&self, session_id: i64) -> SqliteResult<()> {
-        self.conn.execute("DELETE FROM wida_test_sessions WHERE id = ?", [session_id])?;
-        Ok(())
+    /// 删除作业
+    pub fn delete_assignment(&self, id: i64) -> SqliteResult<bool> {
+        let rows = self.conn.execute("DELETE FROM assignments WHERE id = ?", [id])?;
+        Ok(rows > 0)
     }
-    
-    // ========== 保存生成的题目 ==========
-    
-    /// 保存生成的听力题目
-    pub fn save_listening_questions(&self, questions: &[crate::commands::wida::GeneratedListeningQuestion]) -> SqliteResult<i32> {
-        let mut count = 0;
-        for q in questions {
-            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_listening_questions (grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.audio_text,
-                    q.image_url,
-                    q.question_text,
-                    options_json,
-                    q.correct_answer,
-                    q.explanation,
-                ],
-            )?;
-            count += 1;
+
+    // ========== 课程计划 ==========
+
+    /// 在用户课程计划末尾添加一个单元
+    pub fn create_curriculum_unit(&self, request: &crate::models::CreateCurriculumUnitRequest) -> SqliteResult<i64> {
+        let title = request.title.trim();
+        if title.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("单元名称不能为空".to_string()));
         }
-        Ok(count)
-    }
-    
-    /// 保存生成的阅读题目
-    pub fn save_reading_questions(&self, questions: &[crate::commands::wida::GeneratedReadingQuestion]) -> SqliteResult<i32> {
-        let mut count = 0;
-        for q in questions {
-            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_reading_questions (grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.passage,
-                    q.question_text,
-                    q.question_type,
-                    options_json,
-                    q.correct_answer,
-                    q.explanation,
-                ],
+        let target_date = chrono::NaiveDate::parse_from_str(&request.target_date, "%Y-%m-%d")
+            .map_err(|_| rusqlite::Error::InvalidParameterName(format!("日期格式应为 YYYY-MM-DD: {}", request.target_date)))?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO curriculum_units (user_name, title, position, target_date)
+             SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0), ?3 FROM curriculum_units WHERE user_name = ?1",
+            rusqlite::params![request.user_name, title, target_date.format("%Y-%m-%d").to_string()],
+        )?;
+        let id = tx.last_insert_rowid();
+        for (index, article_id) in request.article_ids.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO curriculum_unit_articles (unit_id, article_id, position) VALUES (?, ?, ?)",
+                rusqlite::params![id, article_id, index as i64],
             )?;
-            count += 1;
         }
-        Ok(count)
+        tx.commit()?;
+        Ok(id)
     }
-    
-    /// 保存生成的口语题目
-    pub fn save_speaking_questions(&self, questions: &[crate::commands::wida::GeneratedSpeakingQuestion]) -> SqliteResult<i32> {
-        let mut count = 0;
-        for q in questions {
-            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_speaking_questions (grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.prompt_type,
-                    q.prompt_text,
-                    q.image_url,
-                    q.audio_text,
-                    q.sample_answer,
-                    rubric_json,
-                ],
+
+    /// 用户的课程计划，按单元顺序
+    pub fn get_curriculum(&self, user_name: &str) -> SqliteResult<Vec<crate::models::CurriculumUnit>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, title, position, target_date, created_at FROM curriculum_units
+             WHERE user_name = ? ORDER BY position, id"
+        )?;
+        let mut units = stmt.query_map([user_name], |row| {
+            Ok(crate::models::CurriculumUnit {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                title: row.get(2)?,
+                position: row.get(3)?,
+                target_date: row.get(4)?,
+                created_at: row.get(5)?,
+                article_ids: Vec::new(),
+            })
+        })?.collect::<SqliteResult<Vec<_>>>()?;
+        let mut article_stmt = self.conn.prepare(
+            "SELECT article_id FROM curriculum_unit_articles WHERE unit_id = ? ORDER BY position"
+        )?;
+        for unit in &mut units {
+            unit.article_ids = article_stmt.query_map([unit.id], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>()?;
+        }
+        Ok(units)
+    }
+
+    /// 按给定顺序重新排列用户的单元，未列出的单元排在最后
+    pub fn reorder_curriculum_units(&self, user_name: &str, unit_ids: &[i64]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE curriculum_units SET position = position + ?2 WHERE user_name = ?1",
+            rusqlite::params![user_name, unit_ids.len() as i64],
+        )?;
+        for (index, id) in unit_ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE curriculum_units SET position = ?3 WHERE id = ?2 AND user_name = ?1",
+                rusqlite::params![user_name, id, index as i64],
             )?;
-            count += 1;
         }
-        Ok(count)
+        tx.commit()
     }
-    
-    /// 保存生成的写作题目
-    pub fn save_writing_questions(&self, questions: &[crate::commands::wida::GeneratedWritingQuestion]) -> SqliteResult<i32> {
-        let mut count = 0;
-        for q in questions {
-            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_writing_questions (grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.task_type,
-                    q.prompt,
-                    q.image_url,
-                    q.word_limit_min,
-                    q.word_limit_max,
-                    rubric_json,
-                    q.sample_answer,
-                ],
+
+    /// 删除单元
+    pub fn delete_curriculum_unit(&self, id: i64) -> SqliteResult<bool> {
+        let rows = self.conn.execute("DELETE FROM curriculum_units WHERE id = ?", [id])?;
+        Ok(rows > 0)
+    }
+
+    /// 课程进度：每个单元掌握了多少单词，与按目标日期均匀推进的计划相比是超前还是落后
+    ///
+    /// 单元的计划时间段从上一单元的目标日期（第一个单元从创建日期）到本单元的目标日期。
+    pub fn get_curriculum_progress(&self, user_name: &str) -> SqliteResult<crate::models::CurriculumProgress> {
+        let today = chrono::Local::now().date_naive();
+        let parse = |date: &str| chrono::NaiveDate::parse_from_str(date.get(..10).unwrap_or(date), "%Y-%m-%d").ok();
+        let mut units = Vec::new();
+        let (mut total_words, mut mastered_words, mut planned_words) = (0, 0, 0.0);
+        let mut plan_start = None;
+        for unit in self.get_curriculum(user_name)? {
+            let (words, mastered): (i32, i32) = self.conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(
+                     EXISTS(SELECT 1 FROM word_mastery m WHERE m.user_name = ?2 AND lower(m.segment_content) = w.content
+                            AND m.segment_type = 'word' AND m.mastery_level >= ?3)
+                 ), 0)
+                 FROM (SELECT DISTINCT lower(s.content) AS content FROM segments s
+                       JOIN curriculum_unit_articles ua ON ua.article_id = s.article_id
+                       WHERE ua.unit_id = ?1 AND s.segment_type = 'word') w",
+                rusqlite::params![unit.id, user_name, KNOWN_WORD_LEVEL],
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )?;
-            count += 1;
+
+            let target = parse(&unit.target_date);
+            let start = plan_start.or_else(|| parse(&unit.created_at)).unwrap_or(today);
+            let planned = match target {
+                Some(target) if today >= target => 1.0,
+                Some(target) if today > start => (today - start).num_days() as f64 / (target - start).num_days().max(1) as f64,
+                _ => 0.0,
+            };
+            plan_start = target.or(plan_start);
+
+            let progress = if words > 0 { mastered as f64 / words as f64 } else { 0.0 };
+            total_words += words;
+            mastered_words += mastered;
+            planned_words += planned * words as f64;
+            units.push(crate::models::CurriculumUnitProgress {
+                unit_id: unit.id,
+                title: unit.title,
+                target_date: unit.target_date,
+                total_words: words,
+                mastered_words: mastered,
+                progress,
+                planned_progress: planned,
+                completed: words > 0 && progress >= CURRICULUM_UNIT_COMPLETE_RATIO,
+                overdue: planned >= 1.0 && progress < CURRICULUM_UNIT_COMPLETE_RATIO,
+            });
         }
-        Ok(count)
+
+        let ratio = |count: f64| if total_words > 0 { count / total_words as f64 } else { 0.0 };
+        let (actual_progress, planned_progress) = (ratio(mastered_words as f64), ratio(planned_words));
+        let pace = if actual_progress + CURRICULUM_PACE_TOLERANCE < planned_progress {
+            "behind"
+        } else if actual_progress > planned_progress + CURRICULUM_PACE_TOLERANCE {
+            "ahead"
+        } else {
+            "on_track"
+        };
+        Ok(crate::models::CurriculumProgress {
+            current_unit_id: units.iter().find(|u| !u.completed).map(|u| u.unit_id),
+            actual_progress,
+            planned_progress,
+            pace: pace.to_string(),
+            units,
+        })
     }
-}
 
-fn score_to_level(score: f64) -> i32 {
-    if score >= 550.0 { 6 }
-    else if score >= 475.0 { 5 }
-    else if score >= 400.0 { 4 }
-    else if score >= 325.0 { 3 }
-    else if score >= 250.0 { 2 }
-    else { 1 }
-}
+    /// 当前单元（第一个未完成的单元）的文章，复习时优先安排这些文章里的单词
+    fn current_unit_article_ids(&self, user_name: &str) -> SqliteResult<Vec<i64>> {
+        let progress = self.get_curriculum_progress(user_name)?;
+        let Some(unit_id) = progress.current_unit_id else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self.conn.prepare("SELECT article_id FROM curriculum_unit_articles WHERE unit_id = ?")?;
+        let ids = stmt.query_map([unit_id], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        ids
+    }
 
-impl DatabaseManager {
-    /// 初始化WIDA题库（如果为空）
-    pub fn seed_wida_questions(&self) -> SqliteResult<()> {
-        // 检查是否已有数据
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM wida_listening_questions",
-            [],
-            |row| row.get(0),
+    // ========== 教师面板 ==========
+
+    /// 获取本机所有出现过的用户名
+    pub fn get_known_users(&self) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT user_name FROM practice_history
+             UNION SELECT user_name FROM word_mastery
+             UNION SELECT user_name FROM wida_test_history
+             UNION SELECT user_name FROM assignments
+             UNION SELECT user_name FROM users
+             UNION SELECT user_name FROM practice_history_rollups
+             ORDER BY user_name"
         )?;
+        let users = stmt.query_map([], |row| row.get(0))?.collect::<SqliteResult<Vec<_>>>();
+        users
+    }
 
-        if count > 0 {
-            return Ok(()); // 已有数据，跳过
-        }
+    /// 教师面板总览（只读统计）
+    pub fn get_dashboard_overview(&self) -> SqliteResult<crate::models::DashboardOverview> {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let article_count: i32 = self.conn.query_row("SELECT COUNT(*) FROM articles", [], |row| row.get(0))?;
+
+        let mut users = Vec::new();
+        for user_name in self.get_known_users()? {
+            let (total_practices, avg_accuracy, total_duration_seconds, last_practice_at): (i32, f64, i32, Option<String>) =
+                self.conn.query_row(
+                    "SELECT COUNT(*), COALESCE(AVG(accuracy), 0), COALESCE(SUM(duration_seconds), 0), MAX(completed_at)
+                     FROM practice_history WHERE user_name = ?",
+                    [&user_name],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )?;
 
-        // 插入听力题
-        let listening_questions = get_default_listening_questions();
-        for q in listening_questions {
-            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_listening_questions (id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.id,
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.audio_text,
-                    q.image_url,
-                    q.question_text,
-                    options_json,
-                    q.correct_answer,
-                    q.explanation,
-                ],
+            let (mastered_words, due_words): (i32, i32) = self.conn.query_row(
+                "SELECT COALESCE(SUM(mastery_level >= 4), 0), COALESCE(SUM(next_review_at <= ?2), 0)
+                 FROM word_mastery WHERE user_name = ?1",
+                rusqlite::params![user_name, now],
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )?;
-        }
 
-        // 插入阅读题
-        let reading_questions = get_default_reading_questions();
-        for q in reading_questions {
-            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_reading_questions (id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.id,
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.passage,
-                    q.question_text,
-                    q.question_type,
-                    options_json,
-                    q.correct_answer,
-                    q.explanation,
-                ],
+            let pending_assignments: i32 = self.conn.query_row(
+                "SELECT COUNT(*) FROM assignments WHERE user_name = ? AND status = 'pending'",
+                [&user_name],
+                |row| row.get(0),
             )?;
-        }
 
-        // 插入口语题
-        let speaking_questions = get_default_speaking_questions();
-        for q in speaking_questions {
-            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_speaking_questions (id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.id,
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.prompt_type,
-                    q.prompt_text,
-                    q.image_url,
-                    q.audio_text,
-                    q.sample_answer,
-                    rubric_json,
-                ],
-            )?;
+            let latest_wida_score: Option<f64> = self.conn.query_row(
+                "SELECT score FROM wida_test_history WHERE user_name = ? ORDER BY completed_at DESC LIMIT 1",
+                [&user_name],
+                |row| row.get(0),
+            ).optional()?;
+
+            users.push(crate::models::DashboardUserSummary {
+                user_name,
+                total_practices,
+                avg_accuracy,
+                total_duration_minutes: total_duration_seconds as f64 / 60.0,
+                mastered_words,
+                due_words,
+                pending_assignments,
+                latest_wida_score,
+                last_active_at: last_practice_at,
+            });
         }
 
-        // 插入写作题
-        let writing_questions = get_default_writing_questions();
-        for q in writing_questions {
-            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
-            self.conn.execute(
-                "INSERT INTO wida_writing_questions (id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![
-                    q.id,
-                    q.grade_level,
-                    q.domain,
-                    q.difficulty,
-                    q.task_type,
-                    q.prompt,
-                    q.image_url,
-                    q.word_limit_min,
-                    q.word_limit_max,
-                    rubric_json,
-                    q.sample_answer,
-                ],
-            )?;
+        let (pending_assignments, completed_assignments): (i32, i32) = self.conn.query_row(
+            "SELECT COALESCE(SUM(status = 'pending'), 0), COALESCE(SUM(status = 'completed'), 0) FROM assignments",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(crate::models::DashboardOverview {
+            article_count,
+            users,
+            pending_assignments,
+            completed_assignments,
+        })
+    }
+
+    // ========== WIDA 测试模块 ==========
+
+    /// 获取听力题库
+    pub fn get_wida_listening_questions(
+        &self,
+        grade_level: &str,
+        domain: Option<&str>,
+        limit: Option<i32>,
+    ) -> SqliteResult<Vec<crate::models::WidaListeningQuestion>> {
+        let sql = match (domain, limit) {
+            (Some(d), Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, question_type, correct_answers, answer_pattern 
+                 FROM wida_listening_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, d, l
+            ),
+            (None, Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, question_type, correct_answers, answer_pattern 
+                 FROM wida_listening_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, l
+            ),
+            (Some(d), None) => format!(
+                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, question_type, correct_answers, answer_pattern 
+                 FROM wida_listening_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY id",
+                grade_level, d
+            ),
+            (None, None) => format!(
+                "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, question_type, correct_answers, answer_pattern 
+                 FROM wida_listening_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY id",
+                grade_level
+            ),
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let questions = stmt.query_map([], |row| {
+            let options_json: String = row.get(7)?;
+            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
+            Ok(crate::models::WidaListeningQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                audio_text: row.get(4)?,
+                image_url: row.get(5)?,
+                question_text: row.get(6)?,
+                question_type: row.get(10)?,
+                options,
+                correct_answer: row.get(8)?,
+                correct_answers: json_indices(row.get(11)?),
+                answer_pattern: row.get(12)?,
+                explanation: row.get(9)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        questions
+    }
+
+    /// 获取阅读题库
+    pub fn get_wida_reading_questions(
+        &self,
+        grade_level: &str,
+        domain: Option<&str>,
+        limit: Option<i32>,
+    ) -> SqliteResult<Vec<crate::models::WidaReadingQuestion>> {
+        let sql = match (domain, limit) {
+            (Some(d), Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, correct_answers, answer_pattern 
+                 FROM wida_reading_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, d, l
+            ),
+            (None, Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, correct_answers, answer_pattern 
+                 FROM wida_reading_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, l
+            ),
+            (Some(d), None) => format!(
+                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, correct_answers, answer_pattern 
+                 FROM wida_reading_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY id",
+                grade_level, d
+            ),
+            (None, None) => format!(
+                "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, correct_answers, answer_pattern 
+                 FROM wida_reading_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY id",
+                grade_level
+            ),
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let questions = stmt.query_map([], |row| {
+            let options_json: String = row.get(7)?;
+            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
+            Ok(crate::models::WidaReadingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                passage: row.get(4)?,
+                question_text: row.get(5)?,
+                question_type: row.get(6)?,
+                options,
+                correct_answer: row.get(8)?,
+                correct_answers: json_indices(row.get(10)?),
+                answer_pattern: row.get(11)?,
+                explanation: row.get(9)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        questions
+    }
+
+    /// 获取口语题库
+    pub fn get_wida_speaking_questions(
+        &self,
+        grade_level: &str,
+        domain: Option<&str>,
+        limit: Option<i32>,
+    ) -> SqliteResult<Vec<crate::models::WidaSpeakingQuestion>> {
+        let sql = match (domain, limit) {
+            (Some(d), Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
+                 FROM wida_speaking_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, d, l
+            ),
+            (None, Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
+                 FROM wida_speaking_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, l
+            ),
+            (Some(d), None) => format!(
+                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
+                 FROM wida_speaking_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY id",
+                grade_level, d
+            ),
+            (None, None) => format!(
+                "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
+                 FROM wida_speaking_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY id",
+                grade_level
+            ),
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let questions = stmt.query_map([], |row| {
+            let rubric_json: String = row.get(9)?;
+            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
+            Ok(crate::models::WidaSpeakingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                prompt_type: row.get(4)?,
+                prompt_text: row.get(5)?,
+                image_url: row.get(6)?,
+                audio_text: row.get(7)?,
+                sample_answer: row.get(8)?,
+                rubric,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        questions
+    }
+
+    /// 获取写作题库
+    pub fn get_wida_writing_questions(
+        &self,
+        grade_level: &str,
+        domain: Option<&str>,
+        limit: Option<i32>,
+    ) -> SqliteResult<Vec<crate::models::WidaWritingQuestion>> {
+        let sql = match (domain, limit) {
+            (Some(d), Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
+                 FROM wida_writing_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, d, l
+            ),
+            (None, Some(l)) => format!(
+                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
+                 FROM wida_writing_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY RANDOM() LIMIT {}",
+                grade_level, l
+            ),
+            (Some(d), None) => format!(
+                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
+                 FROM wida_writing_questions WHERE is_sample = 0 AND grade_level = '{}' AND domain = '{}' 
+                 ORDER BY id",
+                grade_level, d
+            ),
+            (None, None) => format!(
+                "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
+                 FROM wida_writing_questions WHERE is_sample = 0 AND grade_level = '{}' 
+                 ORDER BY id",
+                grade_level
+            ),
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let questions = stmt.query_map([], |row| {
+            let rubric_json: String = row.get(9)?;
+            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
+            Ok(crate::models::WidaWritingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                task_type: row.get(4)?,
+                prompt: row.get(5)?,
+                image_url: row.get(6)?,
+                word_limit_min: row.get(7)?,
+                word_limit_max: row.get(8)?,
+                rubric,
+                sample_answer: row.get(10)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        questions
+    }
+
+    /// 开始新的 WIDA 测试
+    pub fn start_wida_test(&self, request: &crate::models::StartWidaTestRequest) -> SqliteResult<crate::models::WidaTestSession> {
+        let mut question_ids: Vec<i64>;
+        
+        if let Some(blueprint_id) = request.blueprint_id {
+            // 按蓝图逐组抽题
+            let blueprint = self.get_wida_blueprint(blueprint_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            if blueprint.test_type != request.test_type {
+                return Err(rusqlite::Error::InvalidParameterName("蓝图的测试类型不匹配".into()));
+            }
+            question_ids = self.blueprint_question_ids(&blueprint, &request.grade_level, request.tags.as_deref())?;
+        } else if let Some(tags) = request.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            question_ids = self.tagged_question_ids(&request.test_type, &request.grade_level, request.domain.as_deref(), tags, request.question_count)?;
+        } else {
+            // 根据测试类型获取题目ID
+            match request.test_type.as_str() {
+                "listening" => {
+                    let questions = self.get_wida_listening_questions(
+                        &request.grade_level,
+                        request.domain.as_deref(),
+                        Some(request.question_count),
+                    )?;
+                    question_ids = questions.iter().map(|q| q.id).collect();
+                }
+                "reading" => {
+                    let questions = self.get_wida_reading_questions(
+                        &request.grade_level,
+                        request.domain.as_deref(),
+                        Some(request.question_count),
+                    )?;
+                    question_ids = questions.iter().map(|q| q.id).collect();
+                }
+                "speaking" => {
+                    let questions = self.get_wida_speaking_questions(
+                        &request.grade_level,
+                        request.domain.as_deref(),
+                        Some(request.question_count),
+                    )?;
+                    question_ids = questions.iter().map(|q| q.id).collect();
+                }
+                "writing" => {
+                    let questions = self.get_wida_writing_questions(
+                        &request.grade_level,
+                        request.domain.as_deref(),
+                        Some(request.question_count),
+                    )?;
+                    question_ids = questions.iter().map(|q| q.id).collect();
+                }
+                _ => return Err(rusqlite::Error::InvalidParameterName("Invalid test type".into())),
+            }
+        }
+
+        if question_ids.is_empty() {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        if request.max_audio_plays.is_some_and(|max| max < 1) {
+            return Err(rusqlite::Error::InvalidParameterName("音频播放次数至少为 1".into()));
+        }
+
+        // 不计分的示例题排在最前面
+        let samples = self.wida_sample_question_ids(
+            &request.test_type,
+            &request.grade_level,
+            request.sample_count.unwrap_or(DEFAULT_SAMPLE_ITEMS),
+        )?;
+        let sample_count = samples.len() as i32;
+        question_ids.splice(0..0, samples);
+
+        let question_ids_json = serde_json::to_string(&question_ids).unwrap_or_else(|_| "[]".to_string());
+        let total_questions = question_ids.len() as i32;
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        self.conn.execute(
+            "INSERT INTO wida_test_sessions (user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, started_at, max_audio_plays, sample_count)
+             VALUES (?, ?, ?, ?, 'in_progress', 0, ?, ?, '[]', ?, ?, ?)",
+            rusqlite::params![
+                request.user_name,
+                request.test_type,
+                request.grade_level,
+                request.domain,
+                total_questions,
+                question_ids_json,
+                now,
+                request.max_audio_plays,
+                sample_count
+            ],
+        )?;
+
+        let session_id = self.conn.last_insert_rowid();
+        
+        Ok(crate::models::WidaTestSession {
+            id: session_id,
+            user_name: request.user_name.clone(),
+            test_type: request.test_type.clone(),
+            grade_level: request.grade_level.clone(),
+            domain: request.domain.clone(),
+            status: "in_progress".to_string(),
+            current_question: 0,
+            total_questions,
+            answers: "[]".to_string(),
+            score: None,
+            proficiency_level: None,
+            started_at: now,
+            completed_at: None,
+            duration_seconds: 0,
+        })
+    }
+
+    // ========== 测试蓝图 ==========
+
+    /// 创建测试蓝图
+    pub fn create_wida_blueprint(
+        &self,
+        name: &str,
+        test_type: &str,
+        sections: &[crate::models::BlueprintSection],
+    ) -> SqliteResult<i64> {
+        validate_blueprint(test_type, sections)?;
+        let sections_json = serde_json::to_string(sections).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "INSERT INTO wida_test_blueprints (name, test_type, sections) VALUES (?1, ?2, ?3)",
+            rusqlite::params![name, test_type, sections_json],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 修改蓝图名称和题目组成，返回是否找到该蓝图
+    pub fn update_wida_blueprint(
+        &self,
+        id: i64,
+        name: &str,
+        sections: &[crate::models::BlueprintSection],
+    ) -> SqliteResult<bool> {
+        let Some(blueprint) = self.get_wida_blueprint(id)? else {
+            return Ok(false);
+        };
+        validate_blueprint(&blueprint.test_type, sections)?;
+        let sections_json = serde_json::to_string(sections).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "UPDATE wida_test_blueprints SET name = ?1, sections = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+            rusqlite::params![name, sections_json, id],
+        )?;
+        Ok(true)
+    }
+
+    /// 删除测试蓝图
+    pub fn delete_wida_blueprint(&self, id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM wida_test_blueprints WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// 获取测试蓝图列表，可按测试类型筛选
+    pub fn get_wida_blueprints(&self, test_type: Option<&str>) -> SqliteResult<Vec<crate::models::WidaTestBlueprint>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, test_type, sections, created_at, updated_at FROM wida_test_blueprints
+             WHERE ?1 IS NULL OR test_type = ?1 ORDER BY name, id",
+        )?;
+        let blueprints = stmt.query_map([test_type], map_blueprint)?.collect::<SqliteResult<Vec<_>>>();
+        blueprints
+    }
+
+    pub fn get_wida_blueprint(&self, id: i64) -> SqliteResult<Option<crate::models::WidaTestBlueprint>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, test_type, sections, created_at, updated_at FROM wida_test_blueprints WHERE id = ?1",
+        )?;
+        let mut rows = stmt.query_map([id], map_blueprint)?;
+        rows.next().transpose()
+    }
+
+    /// 按蓝图逐组随机抽题，题库不足时该组有多少取多少，同一题不会重复出现
+    ///
+    /// 指定 `tags` 时只从带有其中任一标签的题目中抽。
+    fn blueprint_question_ids(
+        &self,
+        blueprint: &crate::models::WidaTestBlueprint,
+        grade_level: &str,
+        tags: Option<&[String]>,
+    ) -> SqliteResult<Vec<i64>> {
+        let table = wida_question_table(&blueprint.test_type)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("Invalid test type".into()))?;
+        let tags_json = tags.filter(|tags| !tags.is_empty()).map(|tags| serde_json::to_string(tags).unwrap_or_default());
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM {} WHERE is_sample = 0 AND grade_level = ?1 AND (?2 IS NULL OR domain = ?2)
+             AND difficulty BETWEEN ?3 AND ?4
+             AND (?5 IS NULL OR id IN (SELECT question_id FROM wida_question_tags
+                                       WHERE test_type = ?6 AND tag IN (SELECT value FROM json_each(?5))))
+             ORDER BY RANDOM()",
+            table
+        ))?;
+
+        let mut question_ids: Vec<i64> = Vec::new();
+        for section in &blueprint.sections {
+            let candidates = stmt
+                .query_map(
+                    rusqlite::params![
+                        grade_level,
+                        section.domain,
+                        section.min_difficulty,
+                        section.max_difficulty,
+                        tags_json,
+                        blueprint.test_type
+                    ],
+                    |row| row.get::<_, i64>(0),
+                )?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            let fresh: Vec<i64> = candidates
+                .into_iter()
+                .filter(|id| !question_ids.contains(id))
+                .take(section.count as usize)
+                .collect();
+            question_ids.extend(fresh);
+        }
+        Ok(question_ids)
+    }
+
+    // ========== 题目标签和搜索 ==========
+
+    /// 随机抽取带有任一标签的题目
+    fn tagged_question_ids(
+        &self,
+        test_type: &str,
+        grade_level: &str,
+        domain: Option<&str>,
+        tags: &[String],
+        limit: i32,
+    ) -> SqliteResult<Vec<i64>> {
+        let table = wida_question_table(test_type)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("Invalid test type".into()))?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM {} WHERE is_sample = 0 AND grade_level = ?1 AND (?2 IS NULL OR domain = ?2)
+             AND id IN (SELECT question_id FROM wida_question_tags WHERE test_type = ?3 AND tag IN (SELECT value FROM json_each(?4)))
+             ORDER BY RANDOM() LIMIT ?5",
+            table
+        ))?;
+        let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+        let ids = stmt
+            .query_map(rusqlite::params![grade_level, domain, test_type, tags_json, limit], |row| row.get(0))?
+            .collect();
+        ids
+    }
+
+    /// 设置题目的标签（整体替换），空白标签忽略
+    pub fn set_wida_question_tags(&mut self, test_type: &str, question_id: i64, tags: &[String]) -> SqliteResult<()> {
+        let table = wida_question_table(test_type)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+        let exists: bool =
+            self.conn.query_row(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table), [question_id], |row| row.get(0))?;
+        if !exists {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM wida_question_tags WHERE test_type = ?1 AND question_id = ?2",
+            rusqlite::params![test_type, question_id],
+        )?;
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            tx.execute(
+                "INSERT OR IGNORE INTO wida_question_tags (test_type, question_id, tag) VALUES (?1, ?2, ?3)",
+                rusqlite::params![test_type, question_id, tag],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 获取题目的标签
+    pub fn get_wida_question_tags(&self, test_type: &str, question_id: i64) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tag FROM wida_question_tags WHERE test_type = ?1 AND question_id = ?2 ORDER BY tag",
+        )?;
+        let tags = stmt.query_map(rusqlite::params![test_type, question_id], |row| row.get(0))?.collect();
+        tags
+    }
+
+    /// 题库中用到的全部标签
+    pub fn get_all_question_tags(&self) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT DISTINCT tag FROM wida_question_tags ORDER BY tag")?;
+        let tags = stmt.query_map([], |row| row.get(0))?.collect();
+        tags
+    }
+
+    /// 搜索题库：按题型、年级段、学科领域、难度和标签（任一）筛选，`text` 在题干和听力原文 / 阅读文章 / 写作提示中查找
+    pub fn search_questions(&self, filters: &crate::models::QuestionSearchFilters) -> SqliteResult<Vec<crate::models::QuestionSearchResult>> {
+        let test_types: Vec<&str> = match filters.test_type.as_deref() {
+            Some(test_type) if wida_question_table(test_type).is_none() => {
+                return Err(rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)));
+            }
+            Some(test_type) => vec![test_type],
+            None => vec!["listening", "reading", "speaking", "writing"],
+        };
+        let tags_json = filters.tags.as_ref().filter(|tags| !tags.is_empty()).map(|tags| serde_json::to_string(tags).unwrap_or_default());
+        let pattern = filters.text.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(|t| format!("%{}%", t));
+
+        let mut results = Vec::new();
+        for test_type in test_types {
+            let table = wida_question_table(test_type)
+                .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+            let (prompt, extra) = wida_question_text_columns(test_type);
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT id, grade_level, domain, difficulty, {prompt}, is_sample FROM {table}
+                 WHERE (?1 IS NULL OR grade_level = ?1) AND (?2 IS NULL OR domain = ?2)
+                   AND difficulty BETWEEN ?3 AND ?4
+                   AND (?5 IS NULL OR {prompt} LIKE ?5 OR {extra} LIKE ?5)
+                   AND (?6 IS NULL OR id IN (SELECT question_id FROM wida_question_tags
+                                             WHERE test_type = ?7 AND tag IN (SELECT value FROM json_each(?6))))
+                 ORDER BY id",
+            ))?;
+            let rows = stmt
+                .query_map(
+                    rusqlite::params![
+                        filters.grade_level,
+                        filters.domain,
+                        filters.min_difficulty.unwrap_or(1),
+                        filters.max_difficulty.unwrap_or(6),
+                        pattern,
+                        tags_json,
+                        test_type
+                    ],
+                    |row| {
+                        Ok(crate::models::QuestionSearchResult {
+                            test_type: test_type.to_string(),
+                            question_id: row.get(0)?,
+                            grade_level: row.get(1)?,
+                            domain: row.get(2)?,
+                            difficulty: row.get(3)?,
+                            prompt: row.get(4)?,
+                            is_sample: row.get(5)?,
+                            tags: Vec::new(),
+                        })
+                    },
+                )?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            results.extend(rows);
+        }
+        results.truncate(filters.limit.unwrap_or(200).max(0) as usize);
+        for result in &mut results {
+            result.tags = self.get_wida_question_tags(&result.test_type, result.question_id)?;
+        }
+        Ok(results)
+    }
+
+    // ========== 难度校准 ==========
+
+    /// 从已完成的听力、阅读测试统计每道题的作答次数和平均得分率（示例题不计）
+    fn wida_question_stats(&self) -> SqliteResult<std::collections::HashMap<(String, i64), (i32, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, test_type, question_ids, answers FROM wida_test_sessions
+             WHERE status = 'completed' AND test_type IN ('listening', 'reading')",
+        )?;
+        let sessions = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stats: std::collections::HashMap<(String, i64), (i32, f64)> = std::collections::HashMap::new();
+        for (session_id, test_type, question_ids, answers) in sessions {
+            let question_ids: Vec<i64> = serde_json::from_str(&question_ids).unwrap_or_default();
+            let answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers).unwrap_or_default();
+            let option_orders = self.wida_option_orders(session_id)?;
+            let sample_count = self.wida_session_sample_count(session_id)?;
+            for (question_id, answer) in question_ids.iter().zip(answers.iter()).skip(sample_count) {
+                let credit = self.wida_answer_credit(&test_type, *question_id, &answer.user_answer, option_orders.get(question_id), Some(session_id))?;
+                let entry = stats.entry((test_type.clone(), *question_id)).or_default();
+                entry.0 += 1;
+                entry.1 += credit;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 按实际作答结果重新估计题目难度，标出与标注难度相差较大的题
+    ///
+    /// `dry_run` 为 false 时把被标记题目的难度改为实测难度。
+    pub fn recalibrate_difficulties(&self, dry_run: bool) -> SqliteResult<crate::models::DifficultyRecalibration> {
+        let mut examined = 0;
+        let mut flagged = Vec::new();
+        let mut stats: Vec<_> = self.wida_question_stats()?.into_iter().collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        for ((test_type, question_id), (attempts, total_credit)) in stats {
+            if attempts < CALIBRATION_MIN_ATTEMPTS {
+                continue;
+            }
+            let table = wida_question_table(&test_type)
+                .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+            let mut stmt = self.conn.prepare(&format!("SELECT difficulty FROM {} WHERE id = ?", table))?;
+            let Some(stored_difficulty) = stmt.query_map([question_id], |row| row.get::<_, i32>(0))?.next().transpose()? else {
+                continue; // 题目已删除
+            };
+            examined += 1;
+            let p_value = total_credit / attempts as f64;
+            let empirical = empirical_difficulty(p_value);
+            if (empirical - stored_difficulty).abs() >= CALIBRATION_FLAG_GAP {
+                flagged.push(crate::models::DifficultyCalibration {
+                    test_type,
+                    question_id,
+                    stored_difficulty,
+                    empirical_difficulty: empirical,
+                    attempts,
+                    p_value,
+                });
+            }
+        }
+
+        if !dry_run {
+            let tx = self.conn.unchecked_transaction()?;
+            for item in &flagged {
+                let table = wida_question_table(&item.test_type)
+                    .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", item.test_type)))?;
+                tx.execute(
+                    &format!("UPDATE {} SET difficulty = ?1 WHERE id = ?2", table),
+                    rusqlite::params![item.empirical_difficulty, item.question_id],
+                )?;
+            }
+            tx.commit()?;
+        }
+
+        Ok(crate::models::DifficultyRecalibration {
+            dry_run,
+            min_attempts: CALIBRATION_MIN_ATTEMPTS,
+            examined,
+            updated: if dry_run { 0 } else { flagged.len() as i32 },
+            flagged,
+        })
+    }
+
+    // ========== 入门分级测试 ==========
+
+    /// 开始入门分级测试，未指定年级段时按 `recommend_grade_level` 推荐
+    pub fn start_placement_test(&self, user_name: &str, grade_level: Option<&str>) -> SqliteResult<crate::models::PlacementTest> {
+        let recommendation = self.recommend_grade_level(user_name)?;
+        let grade_level = grade_level.map_or(recommendation.grade_level, str::to_string);
+        self.conn.execute(
+            "INSERT INTO placement_tests (user_name, grade_level, difficulty) VALUES (?1, ?2, ?3)",
+            rusqlite::params![user_name, grade_level, recommendation.difficulty],
+        )?;
+        self.get_placement_test(self.conn.last_insert_rowid())?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    pub fn get_placement_test(&self, test_id: i64) -> SqliteResult<Option<crate::models::PlacementTest>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, grade_level, status, difficulty, items, proficiency_level, started_at, completed_at
+             FROM placement_tests WHERE id = ?",
+        )?;
+        let test = stmt
+            .query_map([test_id], |row| {
+                Ok(crate::models::PlacementTest {
+                    id: row.get(0)?,
+                    user_name: row.get(1)?,
+                    grade_level: row.get(2)?,
+                    status: row.get(3)?,
+                    difficulty: row.get(4)?,
+                    items: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                    total_items: PLACEMENT_ITEMS as i32,
+                    proficiency_level: row.get(6)?,
+                    started_at: row.get(7)?,
+                    completed_at: row.get(8)?,
+                })
+            })?
+            .next()
+            .transpose();
+        test
+    }
+
+    fn in_progress_placement_test(&self, test_id: i64) -> SqliteResult<crate::models::PlacementTest> {
+        let test = self.get_placement_test(test_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        if test.status != "in_progress" {
+            return Err(rusqlite::Error::InvalidParameterName("分级测试已结束".to_string()));
+        }
+        Ok(test)
+    }
+
+    fn save_placement_items(&self, test_id: i64, items: &[crate::models::PlacementItem], difficulty: i32) -> SqliteResult<()> {
+        let items_json = serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "UPDATE placement_tests SET items = ?1, difficulty = ?2 WHERE id = ?3",
+            rusqlite::params![items_json, difficulty, test_id],
+        )?;
+        Ok(())
+    }
+
+    /// 取下一道题：听力、阅读交替，选难度最接近当前难度且本次没出过的题；题数已满或题库用完时返回 None
+    ///
+    /// 上一题还没作答时重复返回上一题。
+    pub fn next_placement_question(&self, test_id: i64) -> SqliteResult<Option<crate::models::PlacementQuestion>> {
+        let mut test = self.in_progress_placement_test(test_id)?;
+        if let Some(item) = test.items.last().filter(|item| item.correct.is_none()) {
+            return self.placement_question(&test, test.items.len() - 1, &item.test_type, item.question_id).map(Some);
+        }
+        if test.items.len() >= PLACEMENT_ITEMS {
+            return Ok(None);
+        }
+
+        let preferred = if test.items.len() % 2 == 0 { ["listening", "reading"] } else { ["reading", "listening"] };
+        for test_type in preferred {
+            let used: Vec<i64> =
+                test.items.iter().filter(|item| item.test_type == test_type).map(|item| item.question_id).collect();
+            let table = wida_question_table(test_type)
+                .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT id, difficulty FROM {} WHERE is_sample = 0 AND grade_level = ?1
+                 ORDER BY abs(difficulty - ?2), RANDOM()",
+                table
+            ))?;
+            let candidate = stmt
+                .query_map(rusqlite::params![test.grade_level, test.difficulty], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i32>(1)?)))?
+                .collect::<SqliteResult<Vec<_>>>()?
+                .into_iter()
+                .find(|(id, _)| !used.contains(id));
+            if let Some((question_id, difficulty)) = candidate {
+                test.items.push(crate::models::PlacementItem {
+                    test_type: test_type.to_string(),
+                    question_id,
+                    difficulty,
+                    correct: None,
+                });
+                self.save_placement_items(test_id, &test.items, test.difficulty)?;
+                return self.placement_question(&test, test.items.len() - 1, test_type, question_id).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn placement_question(
+        &self,
+        test: &crate::models::PlacementTest,
+        index: usize,
+        test_type: &str,
+        question_id: i64,
+    ) -> SqliteResult<crate::models::PlacementQuestion> {
+        let question = match test_type {
+            "listening" => serde_json::to_value(self.get_wida_listening_question_by_id(question_id)?),
+            _ => serde_json::to_value(self.get_wida_reading_question_by_id(question_id)?),
+        }
+        .unwrap_or_default();
+        Ok(crate::models::PlacementQuestion {
+            test_id: test.id,
+            index: index as i32,
+            total_items: PLACEMENT_ITEMS as i32,
+            test_type: test_type.to_string(),
+            difficulty: test.items[index].difficulty,
+            question,
+        })
+    }
+
+    /// 提交当前题的答案：答对下一题难度加 1，答错减 1（限制在 1-6）
+    pub fn submit_placement_answer(&self, test_id: i64, answer: &str) -> SqliteResult<crate::models::PlacementAnswerResult> {
+        let mut test = self.in_progress_placement_test(test_id)?;
+        let Some(item) = test.items.last_mut().filter(|item| item.correct.is_none()) else {
+            return Err(rusqlite::Error::InvalidParameterName("没有待作答的题目".to_string()));
+        };
+        let credit = self.wida_answer_credit(&item.test_type, item.question_id, answer, None, None)?;
+        let correct = credit >= 1.0;
+        item.correct = Some(correct);
+        let difficulty = (item.difficulty + if correct { 1 } else { -1 }).clamp(1, 6);
+        self.save_placement_items(test_id, &test.items, difficulty)?;
+        Ok(crate::models::PlacementAnswerResult {
+            correct,
+            credit,
+            next_difficulty: difficulty,
+            remaining: (PLACEMENT_ITEMS - test.items.len()) as i32,
+        })
+    }
+
+    /// 结束分级测试，写入初始能力档案
+    ///
+    /// 听力、阅读分别估计能力等级并记入测试历史；用户还没有设置每日新词上限时，按总体等级设置推荐值。
+    pub fn complete_placement_test(&self, test_id: i64) -> SqliteResult<crate::models::PlacementProfile> {
+        let test = self.in_progress_placement_test(test_id)?;
+        let answered: Vec<crate::models::PlacementItem> = test.items.iter().filter(|item| item.correct.is_some()).cloned().collect();
+        let level = placement_level(&answered)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("分级测试还没有作答的题目".to_string()))?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut section_levels = Vec::new();
+        for test_type in ["listening", "reading"] {
+            let items: Vec<crate::models::PlacementItem> =
+                answered.iter().filter(|item| item.test_type == test_type).cloned().collect();
+            let Some(section_level) = placement_level(&items) else {
+                section_levels.push(None);
+                continue;
+            };
+            let correct = items.iter().filter(|item| item.correct == Some(true)).count() as i32;
+            tx.execute(
+                "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0)",
+                rusqlite::params![
+                    test.user_name,
+                    test_type,
+                    test.grade_level,
+                    PROFICIENCY_MIN_SCORES[(section_level - 1) as usize],
+                    section_level,
+                    correct as f64 / items.len() as f64 * 100.0,
+                    items.len() as i32,
+                    correct
+                ],
+            )?;
+            section_levels.push(Some(section_level));
+        }
+
+        let daily_new_words = PLACEMENT_DAILY_NEW_WORDS[(level - 1) as usize];
+        let daily_settings_applied = self.pacing_settings(&test.user_name)?.is_none();
+        if daily_settings_applied {
+            tx.execute(
+                "INSERT INTO new_word_pacing (user_name, daily_limit, carryover) VALUES (?1, ?2, 0)",
+                rusqlite::params![test.user_name, daily_new_words],
+            )?;
+        }
+        tx.execute(
+            "UPDATE placement_tests SET status = 'completed', proficiency_level = ?1, completed_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            rusqlite::params![level, test_id],
+        )?;
+        tx.commit()?;
+
+        let correct = answered.iter().filter(|item| item.correct == Some(true)).count();
+        Ok(crate::models::PlacementProfile {
+            test_id,
+            user_name: test.user_name,
+            grade_level: test.grade_level,
+            proficiency_level: level,
+            proficiency_level_name: crate::i18n::proficiency_level_name(None, level),
+            listening_level: section_levels[0],
+            reading_level: section_levels[1],
+            accuracy: correct as f64 / answered.len() as f64 * 100.0,
+            daily_new_words,
+            daily_settings_applied,
+        })
+    }
+
+    /// 获取测试会话
+    pub fn get_wida_test_session(&self, session_id: i64) -> SqliteResult<Option<crate::models::WidaTestSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, score, proficiency_level, started_at, completed_at, duration_seconds
+             FROM wida_test_sessions WHERE id = ?"
+        )?;
+        
+        let mut sessions = stmt.query_map([session_id], |row| {
+            Ok(crate::models::WidaTestSession {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                test_type: row.get(2)?,
+                grade_level: row.get(3)?,
+                domain: row.get(4)?,
+                status: row.get(5)?,
+                current_question: row.get(6)?,
+                total_questions: row.get(7)?,
+                // question_ids is stored but not returned in session
+                answers: row.get(9)?,
+                score: row.get(10)?,
+                proficiency_level: row.get(11)?,
+                started_at: row.get(12)?,
+                completed_at: row.get(13)?,
+                duration_seconds: row.get(14)?,
+            })
+        })?;
+        
+        Ok(sessions.next().transpose()?)
+    }
+
+    /// 获取测试题目
+    pub fn get_wida_test_questions(&self, session_id: i64) -> SqliteResult<serde_json::Value> {
+        let question_ids_json: String = self.conn.query_row(
+            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
+        let test_type: String = self.conn.query_row(
+            "SELECT test_type FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+
+        // 选择题按本次会话的顺序打乱选项，首次取题时生成并保存，之后保持不变
+        let mut option_orders = self.wida_option_orders(session_id)?;
+        let orders_before = option_orders.len();
+        let seed = crate::ordering::random_seed();
+        let mut shuffled = |id: i64, options: &mut Vec<String>, correct_answer: &mut i32, correct_answers: &mut Vec<i32>| {
+            let order = option_orders.entry(id).or_insert_with(|| {
+                let mut order: Vec<usize> = (0..options.len()).collect();
+                crate::ordering::shuffle(&mut order, seed.wrapping_add(id as u64));
+                order
+            });
+            if order.len() == options.len() {
+                let displayed = |original: i32| order.iter().position(|&i| i as i32 == original).map_or(-1, |i| i as i32);
+                *options = order.iter().map(|&i| options[i].clone()).collect();
+                *correct_answer = displayed(*correct_answer);
+                *correct_answers = correct_answers.iter().map(|&i| displayed(i)).collect();
+            }
+        };
+
+        let questions = match test_type.as_str() {
+            "listening" => {
+                let q: Vec<crate::models::WidaListeningQuestion> = question_ids.iter()
+                    .filter_map(|&id| self.get_wida_listening_question_by_id(id).ok().flatten())
+                    .map(|mut q| {
+                        shuffled(q.id, &mut q.options, &mut q.correct_answer, &mut q.correct_answers);
+                        q
+                    })
+                    .collect();
+                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
+            }
+            "reading" => {
+                let q: Vec<crate::models::WidaReadingQuestion> = question_ids.iter()
+                    .filter_map(|&id| self.get_wida_reading_question_by_id(id).ok().flatten())
+                    .map(|mut q| {
+                        shuffled(q.id, &mut q.options, &mut q.correct_answer, &mut q.correct_answers);
+                        q
+                    })
+                    .collect();
+                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
+            }
+            "speaking" => {
+                let q: Vec<crate::models::WidaSpeakingQuestion> = question_ids.iter()
+                    .filter_map(|&id| self.get_wida_speaking_question_by_id(id).ok().flatten())
+                    .collect();
+                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
+            }
+            "writing" => {
+                let q: Vec<crate::models::WidaWritingQuestion> = question_ids.iter()
+                    .filter_map(|&id| self.get_wida_writing_question_by_id(id).ok().flatten())
+                    .collect();
+                serde_json::to_value(q).unwrap_or(serde_json::json!([]))
+            }
+            _ => serde_json::json!([]),
+        };
+
+        let sample_count = self.wida_session_sample_count(session_id)?;
+        let mut questions = questions;
+        if let Some(items) = questions.as_array_mut() {
+            for (idx, item) in items.iter_mut().enumerate() {
+                item["is_sample"] = serde_json::json!(idx < sample_count);
+            }
+        }
+
+        if option_orders.len() != orders_before {
+            let orders_json = serde_json::to_string(&option_orders).unwrap_or_else(|_| "{}".to_string());
+            self.conn.execute(
+                "UPDATE wida_test_sessions SET option_orders = ?1 WHERE id = ?2",
+                rusqlite::params![orders_json, session_id],
+            )?;
+        }
+
+        Ok(questions)
+    }
+
+    /// 会话开头的示例题数量
+    fn wida_session_sample_count(&self, session_id: i64) -> SqliteResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT sample_count FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        Ok(count.max(0) as usize)
+    }
+
+    /// 按题号顺序取示例题
+    fn wida_sample_question_ids(&self, test_type: &str, grade_level: &str, limit: i32) -> SqliteResult<Vec<i64>> {
+        let Some(table) = wida_question_table(test_type) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM {} WHERE is_sample = 1 AND grade_level = ?1 ORDER BY id LIMIT ?2",
+            table
+        ))?;
+        let ids = stmt.query_map(rusqlite::params![grade_level, limit.max(0)], |row| row.get(0))?.collect();
+        ids
+    }
+
+    /// 把题目设为示例题（不计分，测试开始时先做）或取消
+    pub fn set_wida_question_sample(&self, test_type: &str, question_id: i64, is_sample: bool) -> SqliteResult<()> {
+        let table = wida_question_table(test_type)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+        let updated = self.conn.execute(
+            &format!("UPDATE {} SET is_sample = ?1 WHERE id = ?2", table),
+            rusqlite::params![is_sample, question_id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    /// 某部分的说明文字，未自定义时使用默认说明
+    pub fn get_wida_section_instructions(&self, test_type: &str) -> SqliteResult<String> {
+        let mut stmt = self.conn.prepare("SELECT instructions FROM wida_section_instructions WHERE test_type = ?")?;
+        let custom: Option<String> = stmt.query_map([test_type], |row| row.get(0))?.next().transpose()?;
+        match custom {
+            Some(instructions) => Ok(instructions),
+            None => default_section_instructions(test_type)
+                .map(str::to_string)
+                .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type))),
+        }
+    }
+
+    /// 自定义某部分的说明文字，`instructions` 为 None 时恢复默认
+    pub fn set_wida_section_instructions(&self, test_type: &str, instructions: Option<&str>) -> SqliteResult<()> {
+        if default_section_instructions(test_type).is_none() {
+            return Err(rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)));
+        }
+        match instructions {
+            Some(instructions) => self.conn.execute(
+                "INSERT INTO wida_section_instructions (test_type, instructions) VALUES (?1, ?2)
+                 ON CONFLICT(test_type) DO UPDATE SET instructions = ?2, updated_at = CURRENT_TIMESTAMP",
+                rusqlite::params![test_type, instructions],
+            )?,
+            None => self.conn.execute("DELETE FROM wida_section_instructions WHERE test_type = ?", [test_type])?,
+        };
+        Ok(())
+    }
+
+    /// 会话中各题的选项顺序：question_id → 显示位置对应的原选项下标
+    fn wida_option_orders(&self, session_id: i64) -> SqliteResult<std::collections::HashMap<i64, Vec<usize>>> {
+        let orders_json: Option<String> = self.conn.query_row(
+            "SELECT option_orders FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        Ok(orders_json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+    }
+
+    fn get_wida_listening_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaListeningQuestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, question_type, correct_answers, answer_pattern 
+             FROM wida_listening_questions WHERE id = ?"
+        )?;
+        let mut questions = stmt.query_map([id], |row| {
+            let options_json: String = row.get(7)?;
+            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
+            Ok(crate::models::WidaListeningQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                audio_text: row.get(4)?,
+                image_url: row.get(5)?,
+                question_text: row.get(6)?,
+                question_type: row.get(10)?,
+                options,
+                correct_answer: row.get(8)?,
+                correct_answers: json_indices(row.get(11)?),
+                answer_pattern: row.get(12)?,
+                explanation: row.get(9)?,
+            })
+        })?;
+        Ok(questions.next().transpose()?)
+    }
+
+    fn get_wida_reading_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaReadingQuestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, correct_answers, answer_pattern 
+             FROM wida_reading_questions WHERE id = ?"
+        )?;
+        let mut questions = stmt.query_map([id], |row| {
+            let options_json: String = row.get(7)?;
+            let options: Vec<String> = serde_json::from_str(&options_json).unwrap_or_default();
+            Ok(crate::models::WidaReadingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                passage: row.get(4)?,
+                question_text: row.get(5)?,
+                question_type: row.get(6)?,
+                options,
+                correct_answer: row.get(8)?,
+                correct_answers: json_indices(row.get(10)?),
+                answer_pattern: row.get(11)?,
+                explanation: row.get(9)?,
+            })
+        })?;
+        Ok(questions.next().transpose()?)
+    }
+
+    fn get_wida_speaking_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaSpeakingQuestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric 
+             FROM wida_speaking_questions WHERE id = ?"
+        )?;
+        let mut questions = stmt.query_map([id], |row| {
+            let rubric_json: String = row.get(9)?;
+            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
+            Ok(crate::models::WidaSpeakingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                prompt_type: row.get(4)?,
+                prompt_text: row.get(5)?,
+                image_url: row.get(6)?,
+                audio_text: row.get(7)?,
+                sample_answer: row.get(8)?,
+                rubric,
+            })
+        })?;
+        Ok(questions.next().transpose()?)
+    }
+
+    fn get_wida_writing_question_by_id(&self, id: i64) -> SqliteResult<Option<crate::models::WidaWritingQuestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer 
+             FROM wida_writing_questions WHERE id = ?"
+        )?;
+        let mut questions = stmt.query_map([id], |row| {
+            let rubric_json: String = row.get(9)?;
+            let rubric: Vec<String> = serde_json::from_str(&rubric_json).unwrap_or_default();
+            Ok(crate::models::WidaWritingQuestion {
+                id: row.get(0)?,
+                grade_level: row.get(1)?,
+                domain: row.get(2)?,
+                difficulty: row.get(3)?,
+                task_type: row.get(4)?,
+                prompt: row.get(5)?,
+                image_url: row.get(6)?,
+                word_limit_min: row.get(7)?,
+                word_limit_max: row.get(8)?,
+                rubric,
+                sample_answer: row.get(10)?,
+            })
+        })?;
+        Ok(questions.next().transpose()?)
+    }
+
+    /// 提交答案
+    pub fn submit_wida_answer(&self, request: &crate::models::SubmitWidaAnswerRequest) -> SqliteResult<()> {
+        // 获取当前答案列表
+        let answers_json: String = self.conn.query_row(
+            "SELECT answers FROM wida_test_sessions WHERE id = ?",
+            [request.session_id],
+            |row| row.get(0),
+        )?;
+
+        let mut answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers_json).unwrap_or_default();
+        
+        // 添加新答案
+        answers.push(crate::models::WidaTestAnswer {
+            question_id: request.question_id,
+            user_answer: request.answer.clone(),
+            is_correct: None,
+            time_spent_seconds: request.time_spent_seconds,
+            replay_count: self.get_wida_audio_plays(request.session_id, request.question_id)?,
+        });
+
+        let new_answers_json = serde_json::to_string(&answers).unwrap_or_else(|_| "[]".to_string());
+        let new_current_question = answers.len() as i32;
+
+        self.conn.execute(
+            "UPDATE wida_test_sessions SET answers = ?, current_question = ? WHERE id = ?",
+            rusqlite::params![new_answers_json, new_current_question, request.session_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// 完成测试并计算成绩
+    pub fn complete_wida_test(&self, request: &crate::models::CompleteWidaTestRequest) -> SqliteResult<crate::models::WidaTestReport> {
+        let session = self.get_wida_test_session(request.session_id)?.ok_or_else(|| {
+            rusqlite::Error::QueryReturnedNoRows
+        })?;
+
+        // 获取答案和题目
+        let answers_json: String = self.conn.query_row(
+            "SELECT answers FROM wida_test_sessions WHERE id = ?",
+            [request.session_id],
+            |row| row.get(0),
+        )?;
+
+        let question_ids_json: String = self.conn.query_row(
+            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
+            [request.session_id],
+            |row| row.get(0),
+        )?;
+
+        let answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers_json).unwrap_or_default();
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
+
+        // 计算成绩，多选和简答题按得分比例累计
+        let mut correct_count = 0;
+        let mut total_credit = 0.0;
+        let mut details: Vec<crate::models::WidaAnswerDetail> = Vec::new();
+        let sample_count = self.wida_session_sample_count(request.session_id)?;
+        let total_count = question_ids.len().saturating_sub(sample_count) as i32;
+
+        let option_orders = self.wida_option_orders(request.session_id)?;
+
+        // 根据测试类型验证答案，示例题不计分
+        for (idx, &question_id) in question_ids.iter().enumerate().skip(sample_count) {
+            if idx < answers.len() {
+                let answer = &answers[idx];
+                let credit = self.wida_answer_credit(
+                    &session.test_type,
+                    question_id,
+                    &answer.user_answer,
+                    option_orders.get(&question_id),
+                    Some(request.session_id),
+                )?;
+                let is_correct = credit >= 1.0;
+                total_credit += credit;
+                if is_correct {
+                    correct_count += 1;
+                }
+
+                // 获取题目文本和正确答案
+                let (question_text, correct_answer_text) = self.get_wida_question_info(&session.test_type, question_id)?;
+
+                details.push(crate::models::WidaAnswerDetail {
+                    question_id,
+                    question_text,
+                    user_answer: answer.user_answer.clone(),
+                    correct_answer: correct_answer_text,
+                    is_correct,
+                    credit,
+                    time_spent_seconds: answer.time_spent_seconds,
+                    replay_count: answer.replay_count,
+                    explanation: None,
+                });
+            }
+        }
+
+        let accuracy = if total_count > 0 {
+            (total_credit / total_count as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        // 计算 Scale Score (100-600)
+        let score = 100.0 + (accuracy / 100.0) * 500.0;
+        
+        // 计算 Proficiency Level (1-6)
+        let proficiency_level = if score >= 550.0 { 6 }
+            else if score >= 475.0 { 5 }
+            else if score >= 400.0 { 4 }
+            else if score >= 325.0 { 3 }
+            else if score >= 250.0 { 2 }
+            else { 1 };
+
+        let proficiency_level_name = crate::i18n::proficiency_level_name(request.locale.as_deref(), proficiency_level);
+
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        // 更新会话状态
+        self.conn.execute(
+            "UPDATE wida_test_sessions SET status = 'completed', score = ?, proficiency_level = ?, completed_at = ? WHERE id = ?",
+            rusqlite::params![score, proficiency_level, now, request.session_id],
+        )?;
+
+        // 保存到历史记录
+        self.conn.execute(
+            "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0)",
+            rusqlite::params![
+                session.user_name,
+                session.test_type,
+                session.grade_level,
+                score,
+                proficiency_level,
+                accuracy,
+                total_count,
+                correct_count
+            ],
+        )?;
+
+        Ok(crate::models::WidaTestReport {
+            session: crate::models::WidaTestSession {
+                id: session.id,
+                user_name: session.user_name,
+                test_type: session.test_type.clone(),
+                grade_level: session.grade_level,
+                domain: session.domain,
+                status: "completed".to_string(),
+                current_question: session.current_question,
+                total_questions: session.total_questions,
+                answers: session.answers,
+                score: Some(score),
+                proficiency_level: Some(proficiency_level),
+                started_at: session.started_at,
+                completed_at: Some(now),
+                duration_seconds: session.duration_seconds,
+            },
+            correct_count,
+            total_count,
+            accuracy,
+            listening_score: if session.test_type == "listening" { Some(score) } else { None },
+            reading_score: if session.test_type == "reading" { Some(score) } else { None },
+            speaking_score: if session.test_type == "speaking" { Some(score) } else { None },
+            writing_score: if session.test_type == "writing" { Some(score) } else { None },
+            overall_score: score,
+            proficiency_level,
+            proficiency_level_name,
+            details,
+            integrity: self.get_wida_integrity_summary(request.session_id)?,
+        })
+    }
+
+    /// 答案得分比例（0-1）；`option_order` 为该题在会话中的选项顺序，用于把显示位置换回原选项下标，
+    /// `session_id` 用于查找口语题确认过的转写
+    fn wida_answer_credit(
+        &self,
+        test_type: &str,
+        question_id: i64,
+        user_answer: &str,
+        option_order: Option<&Vec<usize>>,
+        session_id: Option<i64>,
+    ) -> SqliteResult<f64> {
+        match test_type {
+            // 口语题有确认过的转写时按转写评分
+            "speaking" => match session_id.map(|id| self.confirmed_speaking_transcript(id, question_id)).transpose()?.flatten() {
+                Some(transcript) => Ok(self
+                    .get_wida_speaking_question_by_id(question_id)?
+                    .map_or(1.0, |q| crate::text::speaking_rubric::speaking_credit(&transcript, &q.sample_answer))),
+                None => Ok(1.0),
+            },
+            // 写作需要人工评分，暂时算满分
+            "writing" => Ok(1.0),
+            _ => Ok(self
+                .wida_answer_key(test_type, question_id)?
+                .map_or(0.0, |key| key.credit(user_answer, option_order))),
+        }
+    }
+
+    /// 听力/阅读题的判分依据
+    fn wida_answer_key(&self, test_type: &str, question_id: i64) -> SqliteResult<Option<AnswerKey>> {
+        Ok(match test_type {
+            "listening" => self.get_wida_listening_question_by_id(question_id)?.map(|q| AnswerKey {
+                question_type: q.question_type,
+                options: q.options,
+                correct_answer: q.correct_answer,
+                correct_answers: q.correct_answers,
+                answer_pattern: q.answer_pattern,
+            }),
+            "reading" => self.get_wida_reading_question_by_id(question_id)?.map(|q| AnswerKey {
+                question_type: q.question_type,
+                options: q.options,
+                correct_answer: q.correct_answer,
+                correct_answers: q.correct_answers,
+                answer_pattern: q.answer_pattern,
+            }),
+            _ => None,
+        })
+    }
+
+    /// 获取已完成测试中答错题目的相关文本（题干、听力原文、正确选项、解析）
+    pub fn get_wida_missed_texts(&self, session_id: i64) -> SqliteResult<Vec<String>> {
+        let session = self.get_wida_test_session(session_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        if session.status != "completed" {
+            return Err(rusqlite::Error::InvalidParameterName("测试尚未完成".to_string()));
+        }
+
+        let question_ids_json: String = self.conn.query_row(
+            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
+        let answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&session.answers).unwrap_or_default();
+
+        let option_orders = self.wida_option_orders(session_id)?;
+
+        let sample_count = self.wida_session_sample_count(session_id)?;
+
+        let mut texts = Vec::new();
+        for (question_id, answer) in question_ids.iter().zip(answers.iter()).skip(sample_count) {
+            let order = option_orders.get(question_id);
+            if self.wida_answer_credit(&session.test_type, *question_id, &answer.user_answer, order, Some(session_id))? >= 1.0 {
+                continue;
+            }
+            let correct = self.wida_answer_key(&session.test_type, *question_id)?.map(|key| key.text()).unwrap_or_default();
+            match session.test_type.as_str() {
+                "listening" => {
+                    if let Some(q) = self.get_wida_listening_question_by_id(*question_id)? {
+                        texts.extend([q.audio_text, q.question_text, correct]);
+                        texts.extend(q.explanation);
+                    }
+                }
+                "reading" => {
+                    // 阅读原文过长会稀释关键词，只取题干、正确选项和解析
+                    if let Some(q) = self.get_wida_reading_question_by_id(*question_id)? {
+                        texts.extend([q.question_text, correct]);
+                        texts.extend(q.explanation);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(texts)
+    }
+
+    /// 题目文本和正确答案文本，题目不存在时为空字符串
+    pub fn get_wida_question_info(&self, test_type: &str, question_id: i64) -> SqliteResult<(String, String)> {
+        match test_type {
+            "listening" => {
+                if let Some(q) = self.get_wida_listening_question_by_id(question_id)? {
+                    let correct = self.wida_answer_key(test_type, question_id)?.map(|key| key.text()).unwrap_or_default();
+                    return Ok((q.question_text, correct));
+                }
+            }
+            "reading" => {
+                if let Some(q) = self.get_wida_reading_question_by_id(question_id)? {
+                    let correct = self.wida_answer_key(test_type, question_id)?.map(|key| key.text()).unwrap_or_default();
+                    return Ok((q.question_text, correct));
+                }
+            }
+            "speaking" => {
+                if let Some(q) = self.get_wida_speaking_question_by_id(question_id)? {
+                    return Ok((q.prompt_text, q.sample_answer));
+                }
+            }
+            "writing" => {
+                if let Some(q) = self.get_wida_writing_question_by_id(question_id)? {
+                    return Ok((q.prompt, q.sample_answer.unwrap_or_default()));
+                }
+            }
+            _ => {}
+        }
+        Ok(("".to_string(), "".to_string()))
+    }
+
+    /// 获取用户测试历史
+    pub fn get_wida_history(&self, user_name: &str, test_type: Option<&str>, limit: Option<i32>) -> SqliteResult<Vec<crate::models::WidaHistoryRecord>> {
+        // LIMIT -1 表示不限条数
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
+             FROM wida_test_history WHERE user_name = ?1 AND (?2 IS NULL OR test_type = ?2)
+             ORDER BY completed_at DESC LIMIT ?3",
+        )?;
+        let records = stmt.query_map(rusqlite::params![user_name, test_type, limit.unwrap_or(-1)], |row| {
+            Ok(crate::models::WidaHistoryRecord {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                test_type: row.get(2)?,
+                grade_level: row.get(3)?,
+                score: row.get(4)?,
+                proficiency_level: row.get(5)?,
+                accuracy: row.get(6)?,
+                total_questions: row.get(7)?,
+                correct_count: row.get(8)?,
+                duration_seconds: row.get(9)?,
+                completed_at: row.get(10)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        records
+    }
+
+    /// 获取一条测试历史
+    pub fn get_wida_history_record(&self, id: i64) -> SqliteResult<Option<crate::models::WidaHistoryRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at
+             FROM wida_test_history WHERE id = ?"
+        )?;
+        let record = stmt.query_map([id], |row| {
+            Ok(crate::models::WidaHistoryRecord {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                test_type: row.get(2)?,
+                grade_level: row.get(3)?,
+                score: row.get(4)?,
+                proficiency_level: row.get(5)?,
+                accuracy: row.get(6)?,
+                total_questions: row.get(7)?,
+                correct_count: row.get(8)?,
+                duration_seconds: row.get(9)?,
+                completed_at: row.get(10)?,
+            })
+        })?.next().transpose();
+        record
+    }
+
+    /// 获取用户综合报告
+    pub fn get_wida_comprehensive_report(&self, user_name: &str) -> SqliteResult<crate::models::WidaComprehensiveReport> {
+        let history = self.get_wida_history(user_name, None, Some(100))?;
+
+        let mut listening_scores: Vec<f64> = Vec::new();
+        let mut reading_scores: Vec<f64> = Vec::new();
+        let mut speaking_scores: Vec<f64> = Vec::new();
+        let mut writing_scores: Vec<f64> = Vec::new();
+
+        for record in &history {
+            match record.test_type.as_str() {
+                "listening" => listening_scores.push(record.score),
+                "reading" => reading_scores.push(record.score),
+                "speaking" => speaking_scores.push(record.score),
+                "writing" => writing_scores.push(record.score),
+                _ => {}
+            }
+        }
+
+        let listening_score = if !listening_scores.is_empty() { Some(listening_scores.iter().sum::<f64>() / listening_scores.len() as f64) } else { None };
+        let reading_score = if !reading_scores.is_empty() { Some(reading_scores.iter().sum::<f64>() / reading_scores.len() as f64) } else { None };
+        let speaking_score = if !speaking_scores.is_empty() { Some(speaking_scores.iter().sum::<f64>() / speaking_scores.len() as f64) } else { None };
+        let writing_score = if !writing_scores.is_empty() { Some(writing_scores.iter().sum::<f64>() / writing_scores.len() as f64) } else { None };
+
+        let listening_level = listening_score.map(|s| score_to_level(s));
+        let reading_level = reading_score.map(|s| score_to_level(s));
+        let speaking_level = speaking_score.map(|s| score_to_level(s));
+        let writing_level = writing_score.map(|s| score_to_level(s));
+
+        // 计算综合分数
+        let oral_score = match (&listening_score, &speaking_score) {
+            (Some(l), Some(s)) => Some((l + s) / 2.0),
+            (Some(l), None) => Some(*l),
+            (None, Some(s)) => Some(*s),
+            _ => None,
+        };
+
+        let literacy_score = match (&reading_score, &writing_score) {
+            (Some(r), Some(w)) => Some((r + w) / 2.0),
+            (Some(r), None) => Some(*r),
+            (None, Some(w)) => Some(*w),
+            _ => None,
+        };
+
+        // 总分 = 30%听口 + 70%读写
+        let overall_score = match (&oral_score, &literacy_score) {
+            (Some(o), Some(l)) => o * 0.3 + l * 0.7,
+            (Some(o), None) => *o,
+            (None, Some(l)) => *l,
+            _ => 0.0,
+        };
+
+        let overall_level = score_to_level(overall_score);
+        let last_test_date = history.first().map(|r| r.completed_at.clone()).unwrap_or_default();
+
+        Ok(crate::models::WidaComprehensiveReport {
+            user_name: user_name.to_string(),
+            listening_score,
+            listening_level,
+            reading_score,
+            reading_level,
+            speaking_score,
+            speaking_level,
+            writing_score,
+            writing_level,
+            oral_score,
+            literacy_score,
+            overall_score,
+            overall_level,
+            test_count: history.len() as i32,
+            last_test_date,
+        })
+    }
+
+    /// 获取进行中的测试会话
+    pub fn get_active_wida_sessions(&self, user_name: &str) -> SqliteResult<Vec<crate::models::WidaTestSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, test_type, grade_level, domain, status, current_question, total_questions, question_ids, answers, score, proficiency_level, started_at, completed_at, duration_seconds
+             FROM wida_test_sessions WHERE user_name = ? AND status = 'in_progress' ORDER BY started_at DESC"
+        )?;
+        
+        let sessions = stmt.query_map([user_name], |row| {
+            Ok(crate::models::WidaTestSession {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                test_type: row.get(2)?,
+                grade_level: row.get(3)?,
+                domain: row.get(4)?,
+                status: row.get(5)?,
+                current_question: row.get(6)?,
+                total_questions: row.get(7)?,
+                answers: row.get(9)?,
+                score: row.get(10)?,
+                proficiency_level: row.get(11)?,
+                started_at: row.get(12)?,
+                completed_at: row.get(13)?,
+                duration_seconds: row.get(14)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        sessions
+    }
+
+    /// 删除题库中的一道题，返回是否存在
+    pub fn delete_wida_question(&self, test_type: &str, question_id: i64) -> SqliteResult<bool> {
+        let table = wida_question_table(test_type)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("未知的测试类型: {}", test_type)))?;
+        let rows = self.conn.execute(&format!("DELETE FROM {} WHERE id = ?", table), [question_id])?;
+        self.conn.execute(
+            "DELETE FROM wida_question_tags WHERE test_type = ?1 AND question_id = ?2",
+            rusqlite::params![test_type, question_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM wida_question_flags WHERE test_type = ?1 AND question_id = ?2",
+            rusqlite::params![test_type, question_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// 删除测试会话
+    pub fn delete_wida_session(&self, session_id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM wida_audio_plays WHERE session_id = ?", [session_id])?;
+        self.conn.execute("DELETE FROM wida_speaking_transcripts WHERE session_id = ?", [session_id])?;
+        self.conn.execute("DELETE FROM wida_focus_events WHERE session_id = ?", [session_id])?;
+        self.conn.execute("DELETE FROM wida_test_sessions WHERE id = ?", [session_id])?;
+        Ok(())
+    }
+
+    // ========== 题目音频 ==========
+
+    /// 测试中某道题要朗读的文本（听力原文或口语题的音频文本）；题目不属于该测试时返回 None
+    pub fn get_wida_question_audio(&self, session_id: i64, question_id: i64) -> SqliteResult<Option<String>> {
+        let (test_type, question_ids_json): (String, String) = self.conn.query_row(
+            "SELECT test_type, question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids_json).unwrap_or_default();
+        if !question_ids.contains(&question_id) {
+            return Ok(None);
+        }
+        Ok(match test_type.as_str() {
+            "listening" => self.get_wida_listening_question_by_id(question_id)?.map(|q| q.audio_text),
+            "speaking" => self.get_wida_speaking_question_by_id(question_id)?.and_then(|q| q.audio_text),
+            _ => None,
+        })
+    }
+
+    /// 记录一次音频播放，返回之后还能播放的次数；已达到本次测试的播放上限时不记录并返回 None
+    pub fn record_wida_audio_play(&self, session_id: i64, question_id: i64) -> SqliteResult<Option<i32>> {
+        let max_plays: Option<i32> = self.conn.query_row(
+            "SELECT max_audio_plays FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        let max_plays = max_plays.unwrap_or(DEFAULT_MAX_AUDIO_PLAYS);
+        let plays = self.get_wida_audio_plays(session_id, question_id)?;
+        if plays >= max_plays {
+            return Ok(None);
+        }
+        self.conn.execute(
+            "INSERT INTO wida_audio_plays (session_id, question_id, play_count) VALUES (?1, ?2, 1)
+             ON CONFLICT(session_id, question_id) DO UPDATE SET play_count = play_count + 1",
+            rusqlite::params![session_id, question_id],
+        )?;
+        Ok(Some(max_plays - plays - 1))
+    }
+
+    /// 测试中某道题音频已播放的次数
+    pub fn get_wida_audio_plays(&self, session_id: i64, question_id: i64) -> SqliteResult<i32> {
+        let mut stmt = self.conn.prepare(
+            "SELECT play_count FROM wida_audio_plays WHERE session_id = ?1 AND question_id = ?2",
+        )?;
+        let mut rows = stmt.query_map([session_id, question_id], |row| row.get(0))?;
+        Ok(rows.next().transpose()?.unwrap_or(0))
+    }
+
+    // ========== 监考记录 ==========
+
+    /// 记录测试中的一次离开窗口或暂停，`duration_ms` 为离开/暂停的时长；只能在测试进行中记录
+    pub fn record_wida_focus_event(
+        &self,
+        session_id: i64,
+        event_type: &str,
+        question_id: Option<i64>,
+        duration_ms: i64,
+    ) -> SqliteResult<crate::models::WidaIntegritySummary> {
+        if !matches!(event_type, "focus_loss" | "pause") {
+            return Err(rusqlite::Error::InvalidParameterName(format!("未知的事件类型: {}", event_type)));
+        }
+        if duration_ms < 0 {
+            return Err(rusqlite::Error::InvalidParameterName("时长不能为负数".to_string()));
+        }
+        let status: String = self.conn.query_row(
+            "SELECT status FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        if status != "in_progress" {
+            return Err(rusqlite::Error::InvalidParameterName("测试不在进行中".to_string()));
+        }
+        self.conn.execute(
+            "INSERT INTO wida_focus_events (session_id, event_type, question_id, duration_ms) VALUES (?, ?, ?, ?)",
+            rusqlite::params![session_id, event_type, question_id, duration_ms],
+        )?;
+        self.get_wida_integrity_summary(session_id)
+    }
+
+    /// 测试的完整性摘要：离开窗口和暂停的次数、时长，离开过多时标记提醒老师
+    pub fn get_wida_integrity_summary(&self, session_id: i64) -> SqliteResult<crate::models::WidaIntegritySummary> {
+        let mut stmt = self.conn.prepare(
+            "SELECT event_type, COUNT(*), COALESCE(SUM(duration_ms), 0), COALESCE(MAX(duration_ms), 0)
+             FROM wida_focus_events WHERE session_id = ? GROUP BY event_type",
+        )?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut summary = crate::models::WidaIntegritySummary { session_id, ..Default::default() };
+        for (event_type, count, total_ms, longest_ms) in rows {
+            match event_type.as_str() {
+                "focus_loss" => {
+                    summary.focus_loss_count = count;
+                    summary.focus_loss_seconds = total_ms / 1000;
+                    summary.longest_focus_loss_seconds = longest_ms / 1000;
+                }
+                "pause" => {
+                    summary.pause_count = count;
+                    summary.pause_seconds = total_ms / 1000;
+                }
+                _ => {}
+            }
+        }
+        summary.flagged = summary.focus_loss_count >= INTEGRITY_FLAG_FOCUS_LOSSES
+            || summary.focus_loss_seconds >= INTEGRITY_FLAG_AWAY_SECONDS;
+        Ok(summary)
+    }
+
+    // ========== 题目反馈 ==========
+
+    /// 学生在测试中反馈题目有问题；同一次测试中重复反馈同一道题时更新说明并重新打开
+    pub fn flag_question(&self, session_id: i64, question_id: i64, reason: &str) -> SqliteResult<crate::models::WidaQuestionFlag> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("请说明题目的问题".to_string()));
+        }
+        if reason.chars().count() > QUESTION_FLAG_REASON_MAX_CHARS {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "问题说明不能超过 {} 个字",
+                QUESTION_FLAG_REASON_MAX_CHARS
+            )));
+        }
+        let (user_name, test_type, question_ids): (String, String, String) = self.conn.query_row(
+            "SELECT user_name, test_type, question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids).unwrap_or_default();
+        if !question_ids.contains(&question_id) {
+            return Err(rusqlite::Error::InvalidParameterName("该题不属于本次测试".to_string()));
+        }
+        self.conn.execute(
+            "INSERT INTO wida_question_flags (session_id, user_name, test_type, question_id, reason) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id, question_id) DO UPDATE SET
+                reason = excluded.reason, status = 'open', resolved_at = NULL, created_at = CURRENT_TIMESTAMP",
+            rusqlite::params![session_id, user_name, test_type, question_id, reason],
+        )?;
+        let flag_id: i64 = self.conn.query_row(
+            "SELECT id FROM wida_question_flags WHERE session_id = ?1 AND question_id = ?2",
+            [session_id, question_id],
+            |row| row.get(0),
+        )?;
+        self.list_flagged_questions(None)?
+            .into_iter()
+            .find(|flag| flag.id == flag_id)
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// 题目反馈复核队列，`status` 为空时列出全部；待处理的排在前面，同一道题被反馈的次数越多越靠前
+    pub fn list_flagged_questions(&self, status: Option<&str>) -> SqliteResult<Vec<crate::models::WidaQuestionFlag>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT f.id, f.session_id, f.user_name, f.test_type, f.question_id, f.reason, f.status, f.created_at, f.resolved_at,
+                    (SELECT COUNT(*) FROM wida_question_flags o
+                     WHERE o.test_type = f.test_type AND o.question_id = f.question_id AND o.status = 'open') AS open_flags
+             FROM wida_question_flags f
+             WHERE ?1 IS NULL OR f.status = ?1
+             ORDER BY f.status != 'open', open_flags DESC, f.created_at DESC, f.id DESC",
+        )?;
+        let rows = stmt
+            .query_map([status], |row| {
+                Ok(crate::models::WidaQuestionFlag {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    user_name: row.get(2)?,
+                    test_type: row.get(3)?,
+                    question_id: row.get(4)?,
+                    question_text: String::new(),
+                    reason: row.get(5)?,
+                    status: row.get(6)?,
+                    created_at: row.get(7)?,
+                    resolved_at: row.get(8)?,
+                    open_flags: row.get(9)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        rows.into_iter()
+            .map(|mut flag| {
+                flag.question_text = self.get_wida_question_info(&flag.test_type, flag.question_id)?.0;
+                Ok(flag)
+            })
+            .collect()
+    }
+
+    /// 处理题目反馈：`resolved`（已修改题目）或 `dismissed`（题目没有问题），返回是否找到该反馈
+    pub fn resolve_question_flag(&self, flag_id: i64, status: &str) -> SqliteResult<bool> {
+        if !matches!(status, "resolved" | "dismissed") {
+            return Err(rusqlite::Error::InvalidParameterName(format!("未知的处理结果: {}", status)));
+        }
+        let rows = self.conn.execute(
+            "UPDATE wida_question_flags SET status = ?1, resolved_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            rusqlite::params![status, flag_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // ========== 口语转写 ==========
+
+    /// 检查会话是口语测试且包含该题，返回题目
+    fn speaking_session_question(&self, session_id: i64, question_id: i64) -> SqliteResult<crate::models::WidaSpeakingQuestion> {
+        let (test_type, question_ids): (String, String) = self.conn.query_row(
+            "SELECT test_type, question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if test_type != "speaking" {
+            return Err(rusqlite::Error::InvalidParameterName("只有口语测试有录音转写".to_string()));
+        }
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids).unwrap_or_default();
+        if !question_ids.contains(&question_id) {
+            return Err(rusqlite::Error::InvalidParameterName("该题不属于本次测试".to_string()));
+        }
+        self.get_wida_speaking_question_by_id(question_id)?
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("口语题不存在".to_string()))
+    }
+
+    /// 保存语音识别结果；转写已确认时不覆盖
+    pub fn save_speaking_stt(
+        &self,
+        session_id: i64,
+        question_id: i64,
+        stt_text: &str,
+        confidence: Option<f64>,
+    ) -> SqliteResult<crate::models::SpeakingTranscript> {
+        self.speaking_session_question(session_id, question_id)?;
+        if self.confirmed_speaking_transcript(session_id, question_id)?.is_some() {
+            return Err(rusqlite::Error::InvalidParameterName("转写已确认，不能重新识别".to_string()));
+        }
+        let stt_text = stt_text.trim();
+        self.conn.execute(
+            "INSERT INTO wida_speaking_transcripts (session_id, question_id, stt_text, transcript, confidence)
+             VALUES (?1, ?2, ?3, ?3, ?4)
+             ON CONFLICT(session_id, question_id) DO UPDATE SET
+                stt_text = excluded.stt_text, transcript = excluded.transcript, confidence = excluded.confidence,
+                updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![session_id, question_id, stt_text, confidence],
+        )?;
+        self.get_speaking_transcript(session_id, question_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// 修改转写；已确认的转写改回待确认，需要重新确认才参与评分
+    pub fn update_speaking_transcript(
+        &self,
+        session_id: i64,
+        question_id: i64,
+        transcript: &str,
+    ) -> SqliteResult<crate::models::SpeakingTranscript> {
+        self.speaking_session_question(session_id, question_id)?;
+        self.conn.execute(
+            "INSERT INTO wida_speaking_transcripts (session_id, question_id, transcript) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id, question_id) DO UPDATE SET
+                transcript = excluded.transcript, status = 'pending', confirmed_at = NULL, updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![session_id, question_id, transcript.trim()],
+        )?;
+        self.get_speaking_transcript(session_id, question_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// 确认转写：之后按转写评分，并把转写作为该题的答案保存到会话中
+    pub fn confirm_speaking_transcript(&self, session_id: i64, question_id: i64) -> SqliteResult<crate::models::SpeakingTranscript> {
+        self.speaking_session_question(session_id, question_id)?;
+        let transcript: String = self
+            .conn
+            .query_row(
+                "SELECT transcript FROM wida_speaking_transcripts WHERE session_id = ?1 AND question_id = ?2",
+                [session_id, question_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => rusqlite::Error::InvalidParameterName("该题还没有转写".to_string()),
+                e => e,
+            })?;
+        self.conn.execute(
+            "UPDATE wida_speaking_transcripts SET status = 'confirmed', confirmed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE session_id = ?1 AND question_id = ?2",
+            [session_id, question_id],
+        )?;
+
+        let answers_json: String = self.conn.query_row(
+            "SELECT answers FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        let mut answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&answers_json).unwrap_or_default();
+        if let Some(answer) = answers.iter_mut().find(|a| a.question_id == question_id) {
+            answer.user_answer = transcript;
+            let answers_json = serde_json::to_string(&answers).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "UPDATE wida_test_sessions SET answers = ? WHERE id = ?",
+                rusqlite::params![answers_json, session_id],
+            )?;
+        }
+        self.get_speaking_transcript(session_id, question_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// 测试中所有口语题的转写，按题目顺序
+    pub fn get_speaking_transcripts(&self, session_id: i64) -> SqliteResult<Vec<crate::models::SpeakingTranscript>> {
+        let question_ids: String = self.conn.query_row(
+            "SELECT question_ids FROM wida_test_sessions WHERE id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        let question_ids: Vec<i64> = serde_json::from_str(&question_ids).unwrap_or_default();
+        let mut transcripts = Vec::new();
+        for question_id in question_ids {
+            transcripts.extend(self.get_speaking_transcript(session_id, question_id)?);
+        }
+        Ok(transcripts)
+    }
+
+    /// 一道题的转写，附带题目和按当前转写估算的得分
+    fn get_speaking_transcript(&self, session_id: i64, question_id: i64) -> SqliteResult<Option<crate::models::SpeakingTranscript>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT stt_text, transcript, confidence, status, updated_at, confirmed_at
+             FROM wida_speaking_transcripts WHERE session_id = ?1 AND question_id = ?2",
+        )?;
+        let row = stmt
+            .query_map([session_id, question_id], |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<f64>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })?
+            .next()
+            .transpose()?;
+        let Some((stt_text, transcript, confidence, status, updated_at, confirmed_at)) = row else {
+            return Ok(None);
+        };
+        let question = self.get_wida_speaking_question_by_id(question_id)?;
+        Ok(Some(crate::models::SpeakingTranscript {
+            session_id,
+            question_id,
+            prompt_text: question.as_ref().map(|q| q.prompt_text.clone()).unwrap_or_default(),
+            rubric: question.as_ref().map(|q| q.rubric.clone()).unwrap_or_default(),
+            estimated_credit: question
+                .as_ref()
+                .map_or(0.0, |q| crate::text::speaking_rubric::speaking_credit(&transcript, &q.sample_answer)),
+            stt_text,
+            transcript,
+            confidence,
+            status,
+            updated_at,
+            confirmed_at,
+        }))
+    }
+
+    /// 确认过的转写，没有或未确认时为空
+    fn confirmed_speaking_transcript(&self, session_id: i64, question_id: i64) -> SqliteResult<Option<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT transcript FROM wida_speaking_transcripts
+             WHERE session_id = ?1 AND question_id = ?2 AND status = 'confirmed'",
+        )?;
+        let mut rows = stmt.query_map([session_id, question_id], |row| row.get(0))?;
+        rows.next().transpose()
+    }
+
+
+    // ========== 混合练习 ==========
+
+    /// 组织混合练习：每 `quiz_every` 个拼写题后穿插 1-2 道听力题
+    ///
+    /// 听力题的年级段按文章的可读性年级（没有时按推荐年级段），难度取推荐的起始难度，优先选难度最接近的题目。
+    pub fn build_mixed_session(&self, request: &crate::models::BuildMixedSessionRequest) -> SqliteResult<crate::models::MixedSession> {
+        let words = self
+            .get_scheduled_words(&request.user_name, request.article_id, &request.segment_type, request.limit, false)?
+            .words;
+        let recommendation = self.recommend_grade_level(&request.user_name)?;
+        let grade_level = match self.get_article(request.article_id)?.and_then(|a| a.reading_grade) {
+            Some(grade) => WIDA_GRADE_BANDS[grade_band_index((grade.round() as i32).clamp(1, 12))].0.to_string(),
+            None => recommendation.grade_level,
+        };
+        let quiz_every = request.quiz_every.unwrap_or(MIXED_QUIZ_EVERY).max(1) as usize;
+        let per_quiz = request.questions_per_quiz.unwrap_or(1).clamp(1, MIXED_MAX_QUIZ_QUESTIONS) as usize;
+        let quizzes = words.len() / quiz_every;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM wida_listening_questions WHERE is_sample = 0 AND grade_level = ?1
+             ORDER BY ABS(difficulty - ?2), RANDOM() LIMIT ?3"
+        )?;
+        let question_ids = stmt
+            .query_map(rusqlite::params![grade_level, recommendation.difficulty, (quizzes * per_quiz) as i64], |row| row.get::<_, i64>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut questions = question_ids.into_iter().filter_map(|id| self.get_wida_listening_question_by_id(id).ok().flatten());
+
+        let (mut items, mut keys) = (Vec::new(), Vec::new());
+        for (i, word) in words.into_iter().enumerate() {
+            keys.push(("spelling", word.segment_id));
+            items.push(crate::models::MixedSessionItem { index: items.len() as i32, kind: "spelling".to_string(), word: Some(word), question: None });
+            if (i + 1) % quiz_every == 0 {
+                for question in questions.by_ref().take(per_quiz) {
+                    keys.push(("listening", question.id));
+                    items.push(crate::models::MixedSessionItem { index: items.len() as i32, kind: "listening".to_string(), word: None, question: Some(question) });
+                }
+            }
+        }
+        self.conn.execute(
+            "INSERT INTO mixed_sessions (user_name, article_id, segment_type, items) VALUES (?, ?, ?, ?)",
+            rusqlite::params![
+                request.user_name,
+                request.article_id,
+                request.segment_type,
+                serde_json::to_string(&keys).unwrap_or_else(|_| "[]".to_string())
+            ],
+        )?;
+        Ok(crate::models::MixedSession {
+            id: self.conn.last_insert_rowid(),
+            user_name: request.user_name.clone(),
+            grade_level,
+            difficulty: recommendation.difficulty,
+            items,
+        })
+    }
+
+    /// 提交混合练习中一题的答案；拼写题更新熟练度和错词本，听力题按题库判分，两种结果都记录
+    pub fn submit_mixed_answer(&self, session_id: i64, item_index: i32, answer: &str) -> SqliteResult<crate::models::MixedItemResult> {
+        let (user_name, segment_type, items_json): (String, String, String) = self.conn.query_row(
+            "SELECT user_name, segment_type, items FROM mixed_sessions WHERE id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let items: Vec<(String, i64)> = serde_json::from_str(&items_json).unwrap_or_default();
+        let (kind, item_id) = items
+            .get(item_index.max(0) as usize)
+            .cloned()
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("题目不存在: {}", item_index)))?;
+        let answered: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM mixed_session_results WHERE session_id = ? AND item_index = ?)",
+            rusqlite::params![session_id, item_index],
+            |row| row.get(0),
+        )?;
+        if answered {
+            return Err(rusqlite::Error::InvalidParameterName("该题已作答".to_string()));
+        }
+
+        let (credit, expected, mastery) = if kind == "spelling" {
+            let segment = self.get_segment(item_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            let options = crate::models::AnswerCheckOptions {
+                accept_spelling_variants: Some(self.accept_spelling_variants(&user_name)?),
+                ..Default::default()
+            };
+            let check = crate::text::answer_check::check_answer(answer, &segment.content, &options);
+            let mastery = self.update_word_mastery(&user_name, item_id, &segment.content, &segment_type, check.correct)?;
+            if !check.correct {
+                self.add_mistake(&user_name, item_id, &segment.content, &segment_type)?;
+            }
+            (if check.correct { 1.0 } else { check.credit }, segment.content, Some(mastery))
+        } else {
+            let key = self.wida_answer_key("listening", item_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            (key.credit(answer, None), key.text(), None)
+        };
+
+        self.conn.execute(
+            "INSERT INTO mixed_session_results (session_id, item_index, kind, item_id, answer, credit) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![session_id, item_index, kind, item_id, answer, credit],
+        )?;
+        Ok(crate::models::MixedItemResult { index: item_index, kind, correct: credit >= 1.0, credit, expected, mastery })
+    }
+
+    /// 混合练习小结：拼写题和听力题分别统计
+    pub fn get_mixed_session_summary(&self, session_id: i64) -> SqliteResult<crate::models::MixedSessionSummary> {
+        let items_json: String = self.conn.query_row("SELECT items FROM mixed_sessions WHERE id = ?", [session_id], |row| row.get(0))?;
+        let items: Vec<(String, i64)> = serde_json::from_str(&items_json).unwrap_or_default();
+        let count = |kind: &str| items.iter().filter(|(k, _)| k == kind).count() as i32;
+        let (spelling_correct, listening_correct, listening_credit, answered): (i32, i32, f64, i32) = self.conn.query_row(
+            "SELECT COALESCE(SUM(kind = 'spelling' AND credit >= 1.0), 0), COALESCE(SUM(kind = 'listening' AND credit >= 1.0), 0),
+                    COALESCE(SUM(CASE WHEN kind = 'listening' THEN credit ELSE 0 END), 0), COUNT(*)
+             FROM mixed_session_results WHERE session_id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+        Ok(crate::models::MixedSessionSummary {
+            session_id,
+            total_items: items.len() as i32,
+            answered,
+            spelling_total: count("spelling"),
+            spelling_correct,
+            listening_total: count("listening"),
+            listening_correct,
+            listening_credit,
+        })
+    }
+    
+    // ========== 音频复习 ==========
+
+    /// 开始音频复习：取出到期（或还没有复习计划）的错词，错误次数多的在前
+    pub fn start_audio_review(&self, request: &crate::models::StartAudioReviewRequest) -> SqliteResult<crate::models::AudioReviewSession> {
+        self.apply_review_pauses(&request.user_name)?;
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let limit = request.limit.unwrap_or(AUDIO_REVIEW_DEFAULT_LIMIT).max(1);
+        let gap_seconds = request.gap_seconds.unwrap_or(AUDIO_REVIEW_DEFAULT_GAP_SECONDS).clamp(1, AUDIO_REVIEW_MAX_GAP_SECONDS);
+        let mut stmt = self.conn.prepare(
+            "SELECT m.segment_id, m.segment_content, m.segment_type, s.language
+             FROM mistakes m
+             LEFT JOIN segments s ON s.id = m.segment_id
+             LEFT JOIN word_mastery w ON w.user_name = m.user_name AND w.segment_id = m.segment_id
+             WHERE m.user_name = ?1 AND m.archived_at IS NULL AND (w.next_review_at IS NULL OR w.next_review_at <= ?2)
+             ORDER BY m.error_count DESC, m.last_error_at DESC, m.segment_id ASC LIMIT ?3",
+        )?;
+        let items = stmt
+            .query_map(rusqlite::params![request.user_name, now, limit], |row| {
+                Ok(crate::models::AudioReviewItem {
+                    index: 0,
+                    segment_id: row.get(0)?,
+                    content: row.get(1)?,
+                    segment_type: row.get(2)?,
+                    language: row.get(3)?,
+                })
+            })?
+            .enumerate()
+            .map(|(i, item)| item.map(|item| crate::models::AudioReviewItem { index: i as i32, ..item }))
+            .collect::<SqliteResult<Vec<_>>>()?;
+        if items.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("没有需要复习的错词".to_string()));
+        }
+
+        let segment_ids: Vec<i64> = items.iter().map(|item| item.segment_id).collect();
+        self.conn.execute(
+            "INSERT INTO audio_review_sessions (user_name, items, gap_seconds) VALUES (?, ?, ?)",
+            rusqlite::params![
+                request.user_name,
+                serde_json::to_string(&segment_ids).unwrap_or_else(|_| "[]".to_string()),
+                gap_seconds
+            ],
+        )?;
+        Ok(crate::models::AudioReviewSession {
+            id: self.conn.last_insert_rowid(),
+            user_name: request.user_name.clone(),
+            gap_seconds,
+            items,
+        })
+    }
+
+    /// 音频复习是否仍在朗读（没有停止或结束）
+    pub fn is_audio_review_active(&self, session_id: i64) -> SqliteResult<bool> {
+        let mut stmt = self.conn.prepare("SELECT status = 'active' FROM audio_review_sessions WHERE id = ?")?;
+        let mut rows = stmt.query_map([session_id], |row| row.get(0))?;
+        Ok(rows.next().transpose()?.unwrap_or(false))
+    }
+
+    /// 停止朗读，之后仍可提交自评；返回是否停止了正在进行的复习
+    pub fn stop_audio_review(&self, session_id: i64) -> SqliteResult<bool> {
+        Ok(self.conn.execute(
+            "UPDATE audio_review_sessions SET status = 'stopped' WHERE id = ? AND status = 'active'",
+            [session_id],
+        )? > 0)
+    }
+
+    /// 结束音频复习：按自评更新熟练度和错词本，并以 `audio_review` 模式记入练习历史
+    ///
+    /// 没有自评的单词只计入总数，不影响熟练度和正确率。
+    pub fn finish_audio_review(
+        &self,
+        session_id: i64,
+        marks: &[crate::models::AudioReviewMark],
+        duration_seconds: i32,
+    ) -> SqliteResult<crate::models::AudioReviewSummary> {
+        let (user_name, items_json, status): (String, String, String) = self.conn.query_row(
+            "SELECT user_name, items, status FROM audio_review_sessions WHERE id = ?",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        if status == "completed" {
+            return Err(rusqlite::Error::InvalidParameterName("该音频复习已结束".to_string()));
+        }
+        let items: Vec<i64> = serde_json::from_str(&items_json).unwrap_or_default();
+        let mut marked = std::collections::HashSet::new();
+        for mark in marks {
+            if !items.contains(&mark.segment_id) {
+                return Err(rusqlite::Error::InvalidParameterName(format!("分词不在本次复习中: {}", mark.segment_id)));
+            }
+            if !marked.insert(mark.segment_id) {
+                return Err(rusqlite::Error::InvalidParameterName(format!("重复自评: {}", mark.segment_id)));
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut article_id = None;
+        for mark in marks {
+            let segment = self.get_segment(mark.segment_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            article_id.get_or_insert(segment.article_id);
+            self.update_word_mastery(&user_name, segment.id, &segment.content, &segment.segment_type, mark.correct)?;
+            if !mark.correct {
+                self.add_mistake(&user_name, segment.id, &segment.content, &segment.segment_type)?;
+            }
+        }
+        let article_id = match article_id {
+            Some(id) => id,
+            None => match items.first() {
+                Some(&segment_id) => self.get_segment(segment_id)?.map_or(0, |segment| segment.article_id),
+                None => 0,
+            },
+        };
+
+        let total = items.len() as i32;
+        let correct = marks.iter().filter(|mark| mark.correct).count() as i32;
+        let incorrect = marks.len() as i32 - correct;
+        let accuracy = if marks.is_empty() { 0.0 } else { correct as f64 / marks.len() as f64 * 100.0 };
+        self.conn.execute(
+            "INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, incorrect_count, total_count,
+                                           accuracy, wpm, duration_seconds, mode)
+             VALUES (?, ?, 'word', ?, ?, ?, ?, 0, ?, 'audio_review')",
+            rusqlite::params![user_name, article_id, correct, incorrect, total, accuracy, duration_seconds.max(0)],
+        )?;
+        self.conn.execute(
+            "UPDATE audio_review_sessions SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?",
+            [session_id],
+        )?;
+        tx.commit()?;
+        Ok(crate::models::AudioReviewSummary { session_id, total, marked: marks.len() as i32, correct, accuracy })
+    }
+
+    // ========== 提示词模板 ==========
+
+    /// 用户自定义的提示词模板
+    pub fn get_prompt_template(&self, test_type: &str) -> SqliteResult<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT template FROM prompt_templates WHERE test_type = ?")?;
+        let mut rows = stmt.query_map([test_type], |row| row.get(0))?;
+        rows.next().transpose()
+    }
+
+    /// 保存自定义模板，`template` 为 None 时删除（恢复默认）
+    pub fn set_prompt_template(&self, test_type: &str, template: Option<&str>) -> SqliteResult<()> {
+        match template {
+            Some(template) => self.conn.execute(
+                "INSERT INTO prompt_templates (test_type, template) VALUES (?1, ?2)
+                 ON CONFLICT(test_type) DO UPDATE SET template = ?2, updated_at = CURRENT_TIMESTAMP",
+                rusqlite::params![test_type, template],
+            )?,
+            None => self.conn.execute("DELETE FROM prompt_templates WHERE test_type = ?", [test_type])?,
+        };
+        Ok(())
+    }
+
+    // ========== AI 用量 ==========
+
+    /// 记录一次 AI 调用的用量
+    pub fn record_ai_usage(
+        &self,
+        provider: &str,
+        model: &str,
+        purpose: &str,
+        prompt_tokens: i64,
+        completion_tokens: i64,
+        estimated_cost: Option<f64>,
+    ) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO ai_usage (provider, model, purpose, prompt_tokens, completion_tokens, estimated_cost, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, datetime('now', 'localtime'))",
+            rusqlite::params![provider, model, purpose, prompt_tokens, completion_tokens, estimated_cost],
+        )?;
+        Ok(())
+    }
+
+    /// 某月（`YYYY-MM`，本地时间）的 AI 用量汇总
+    pub fn get_ai_usage_summary(&self, month: &str) -> SqliteResult<crate::models::AiUsageSummary> {
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, model, purpose, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(estimated_cost)
+             FROM ai_usage WHERE strftime('%Y-%m', created_at) = ?
+             GROUP BY provider, model, purpose
+             ORDER BY SUM(estimated_cost) DESC, COUNT(*) DESC"
+        )?;
+        let breakdown = stmt.query_map([month], |row| {
+            Ok(crate::models::AiUsageBreakdown {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                purpose: row.get(2)?,
+                calls: row.get(3)?,
+                prompt_tokens: row.get(4)?,
+                completion_tokens: row.get(5)?,
+                estimated_cost: row.get(6)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(crate::models::AiUsageSummary {
+            month: month.to_string(),
+            total_calls: breakdown.iter().map(|b| b.calls).sum(),
+            prompt_tokens: breakdown.iter().map(|b| b.prompt_tokens).sum(),
+            completion_tokens: breakdown.iter().map(|b| b.completion_tokens).sum(),
+            estimated_cost: breakdown.iter().filter_map(|b| b.estimated_cost).sum(),
+            breakdown,
+        })
+    }
+
+    // ========== 单词配图 ==========
+
+    /// 保存单词配图，返回被替换的旧图片路径（由调用方删除文件）
+    pub fn set_word_image(
+        &self,
+        word: &str,
+        file_path: &str,
+        source: &str,
+        source_url: Option<&str>,
+    ) -> SqliteResult<Option<String>> {
+        let word = word.trim().to_lowercase();
+        if word.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("单词不能为空".to_string()));
+        }
+        let old = self.get_word_image(&word)?.map(|image| image.file_path).filter(|path| path != file_path);
+        self.conn.execute(
+            "INSERT INTO word_images (word, file_path, source, source_url) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(word) DO UPDATE SET file_path = ?2, source = ?3, source_url = ?4, created_at = CURRENT_TIMESTAMP",
+            rusqlite::params![word, file_path, source, source_url],
+        )?;
+        Ok(old)
+    }
+
+    /// 单词的配图（不区分大小写）
+    pub fn get_word_image(&self, word: &str) -> SqliteResult<Option<crate::models::WordImage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT word, file_path, source, source_url, created_at FROM word_images WHERE word = ?",
+        )?;
+        let image = stmt.query_map([word.trim().to_lowercase()], map_word_image)?.next().transpose();
+        image
+    }
+
+    /// 一组单词中有配图的部分（练习界面批量获取）
+    pub fn get_word_images(&self, words: &[String]) -> SqliteResult<Vec<crate::models::WordImage>> {
+        let mut images = Vec::new();
+        for word in words {
+            if let Some(image) = self.get_word_image(word)? {
+                if !images.iter().any(|i: &crate::models::WordImage| i.word == image.word) {
+                    images.push(image);
+                }
+            }
+        }
+        Ok(images)
+    }
+
+    /// 删除单词配图，返回图片路径（由调用方删除文件）
+    pub fn delete_word_image(&self, word: &str) -> SqliteResult<Option<String>> {
+        let path = self.get_word_image(word)?.map(|image| image.file_path);
+        self.conn.execute("DELETE FROM word_images WHERE word = ?", [word.trim().to_lowercase()])?;
+        Ok(path)
+    }
+
+    // ========== 发音评分 ==========
+
+    /// 把发音评分保存到熟练度记录上，单词需已练习过
+    pub fn save_pronunciation_score(
+        &self,
+        user_name: &str,
+        assessment: &crate::models::PronunciationAssessment,
+    ) -> SqliteResult<()> {
+        let phonemes = serde_json::to_string(&assessment.phonemes)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let rows = self.conn.execute(
+            "UPDATE word_mastery SET pronunciation_score = ?3, pronunciation_phonemes = ?4, pronunciation_at = datetime('now')
+             WHERE user_name = ?1 AND segment_id = ?2",
+            rusqlite::params![user_name, assessment.segment_id, assessment.overall_score, phonemes],
+        )?;
+        if rows == 0 {
+            return Err(rusqlite::Error::InvalidParameterName(format!("还没有练习过该单词: {}", assessment.word)));
+        }
+        Ok(())
+    }
+
+    /// 最近一次发音评分
+    pub fn get_pronunciation_score(
+        &self,
+        user_name: &str,
+        segment_id: i64,
+    ) -> SqliteResult<Option<crate::models::PronunciationAssessment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT segment_content, pronunciation_score, pronunciation_phonemes, pronunciation_at FROM word_mastery
+             WHERE user_name = ? AND segment_id = ? AND pronunciation_score IS NOT NULL",
+        )?;
+        let assessment = stmt.query_map(rusqlite::params![user_name, segment_id], |row| {
+            let phonemes: Option<String> = row.get(2)?;
+            Ok(crate::models::PronunciationAssessment {
+                segment_id,
+                word: row.get(0)?,
+                overall_score: row.get(1)?,
+                phonemes: phonemes.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                assessed_at: row.get(3)?,
+            })
+        })?.next().transpose();
+        assessment
+    }
+
+    // ========== 朗读录音 ==========
+
+    /// 开始一次朗读录音，单词数按文章正文计算
+    pub fn start_reading_recording(&self, user_name: &str, article_id: i64) -> SqliteResult<i64> {
+        let article = self.get_article(article_id)?
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("文章不存在: {}", article_id)))?;
+        let word_count = crate::text::tokenize(&article.content).len() as i32;
+        self.conn.execute(
+            "INSERT INTO reading_recordings (user_name, article_id, word_count) VALUES (?, ?, ?)",
+            rusqlite::params![user_name, article_id, word_count],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 保存录音并计算每分钟单词数
+    ///
+    /// 未提供 `duration_ms` 时按开始到现在的时间计算；只读了一部分时用 `words_read` 覆盖单词数。
+    pub fn complete_reading_recording(
+        &self,
+        id: i64,
+        file_path: &str,
+        duration_ms: Option<i64>,
+        words_read: Option<i32>,
+    ) -> SqliteResult<crate::models::ReadingRecording> {
+        let recording = self.get_reading_recording(id)?
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("录音不存在: {}", id)))?;
+        if recording.status != "recording" {
+            return Err(rusqlite::Error::InvalidParameterName("录音已完成".to_string()));
+        }
+        let duration_ms = match duration_ms {
+            Some(ms) => ms,
+            None => {
+                let started = chrono::NaiveDateTime::parse_from_str(&recording.started_at, "%Y-%m-%d %H:%M:%S")
+                    .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+                (chrono::Utc::now().naive_utc() - started).num_milliseconds()
+            }
+        };
+        if duration_ms <= 0 {
+            return Err(rusqlite::Error::InvalidParameterName("录音时长必须大于 0".to_string()));
+        }
+        let word_count = words_read.unwrap_or(recording.word_count).clamp(0, recording.word_count);
+        let wpm = word_count as f64 / (duration_ms as f64 / 60_000.0);
+        self.conn.execute(
+            "UPDATE reading_recordings SET status = 'completed', file_path = ?, word_count = ?, duration_ms = ?, wpm = ?,
+                 completed_at = datetime('now') WHERE id = ?",
+            rusqlite::params![file_path, word_count, duration_ms, wpm, id],
+        )?;
+        self.get_reading_recording(id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// 保存从录音文件解码出的时长和波形
+    pub fn save_recording_audio(&self, id: i64, audio: &crate::models::RecordingAudio) -> SqliteResult<()> {
+        let audio = serde_json::to_string(audio).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute("UPDATE reading_recordings SET audio = ? WHERE id = ?", rusqlite::params![audio, id])?;
+        Ok(())
+    }
+
+    /// 录音的时长和波形，录音不存在时为空
+    pub fn get_recording_metadata(&self, id: i64) -> SqliteResult<Option<crate::models::RecordingMetadata>> {
+        let mut stmt = self.conn.prepare("SELECT duration_ms, audio FROM reading_recordings WHERE id = ?")?;
+        let metadata = stmt
+            .query_map([id], |row| {
+                let audio: Option<crate::models::RecordingAudio> =
+                    row.get::<_, Option<String>>(1)?.and_then(|json| serde_json::from_str(&json).ok());
+                Ok(crate::models::RecordingMetadata {
+                    recording_id: id,
+                    duration_ms: audio.as_ref().map(|audio| audio.duration_ms).or(row.get(0)?),
+                    audio,
+                })
+            })?
+            .next()
+            .transpose();
+        metadata
+    }
+
+    /// 获取一次朗读录音
+    pub fn get_reading_recording(&self, id: i64) -> SqliteResult<Option<crate::models::ReadingRecording>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, article_id, status, file_path, word_count, duration_ms, wpm, started_at, completed_at
+             FROM reading_recordings WHERE id = ?",
+        )?;
+        let recording = stmt.query_map([id], map_reading_recording)?.next().transpose();
+        recording
+    }
+
+    /// 某用户朗读某篇文章的已完成录音（按时间先后）
+    pub fn get_reading_recordings(&self, user_name: &str, article_id: i64) -> SqliteResult<Vec<crate::models::ReadingRecording>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, article_id, status, file_path, word_count, duration_ms, wpm, started_at, completed_at
+             FROM reading_recordings WHERE user_name = ? AND article_id = ? AND status = 'completed'
+             ORDER BY id",
+        )?;
+        let recordings = stmt.query_map(rusqlite::params![user_name, article_id], map_reading_recording)?
+            .collect::<SqliteResult<Vec<_>>>();
+        recordings
+    }
+
+    /// 同一篇文章多次朗读的流利度变化
+    pub fn get_fluency_trend(&self, user_name: &str, article_id: i64) -> SqliteResult<crate::models::FluencyTrend> {
+        let readings = self.get_reading_recordings(user_name, article_id)?;
+        let wpms: Vec<f64> = readings.iter().filter_map(|r| r.wpm).collect();
+        let first_wpm = wpms.first().copied();
+        let latest_wpm = wpms.last().copied();
+        let change_percent = match (first_wpm, latest_wpm) {
+            (Some(first), Some(latest)) if first > 0.0 && wpms.len() > 1 => Some((latest - first) / first * 100.0),
+            _ => None,
+        };
+        Ok(crate::models::FluencyTrend {
+            user_name: user_name.to_string(),
+            article_id,
+            first_wpm,
+            latest_wpm,
+            best_wpm: wpms.iter().copied().reduce(f64::max),
+            change_percent,
+            readings,
+        })
+    }
+
+    /// 删除录音，返回录音文件路径（由调用方删除文件）
+    pub fn delete_reading_recording(&self, id: i64) -> SqliteResult<Option<String>> {
+        let path = self.get_reading_recording(id)?.and_then(|r| r.file_path);
+        self.conn.execute("DELETE FROM reading_recordings WHERE id = ?", [id])?;
+        Ok(path)
+    }
+
+    // ========== 单元包 ==========
+
+    /// 文章及其分词、标签和根据文章生成的听力/阅读题，用于打包分发；文章不存在时返回 None
+    pub fn export_pack_article(&self, article_id: i64) -> SqliteResult<Option<crate::packs::PackArticle>> {
+        use crate::commands::wida::{GeneratedListeningQuestion, GeneratedReadingQuestion};
+        let Some(article) = self.get_article(article_id)? else {
+            return Ok(None);
+        };
+        fn json_list<T: serde::de::DeserializeOwned>(json: Option<String>) -> Vec<T> {
+            json.and_then(|j| serde_json::from_str(&j).ok()).unwrap_or_default()
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.segment_type, s.content, t.start_ms, t.end_ms
+             FROM segments s LEFT JOIN segment_timings t ON t.segment_id = s.id
+             WHERE s.article_id = ? ORDER BY s.segment_type, s.order_index"
+        )?;
+        let segments = stmt
+            .query_map([article_id], |row| {
+                Ok(crate::packs::PackSegment {
+                    segment_type: row.get(0)?,
+                    content: row.get(1)?,
+                    start_ms: row.get(2)?,
+                    end_ms: row.get(3)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT grade_level, domain, difficulty, audio_text, image_url, question_text, question_type, options,
+                    correct_answer, correct_answers, answer_pattern, explanation
+             FROM wida_listening_questions WHERE source_article_id = ? ORDER BY id"
+        )?;
+        let listening_questions = stmt
+            .query_map([article_id], |row| {
+                Ok(GeneratedListeningQuestion {
+                    grade_level: row.get(0)?,
+                    domain: row.get(1)?,
+                    difficulty: row.get(2)?,
+                    audio_text: row.get(3)?,
+                    image_url: row.get(4)?,
+                    question_text: row.get(5)?,
+                    question_type: row.get(6)?,
+                    options: json_list(row.get(7)?),
+                    correct_answer: row.get(8)?,
+                    correct_answers: json_list(row.get(9)?),
+                    answer_pattern: row.get(10)?,
+                    explanation: row.get(11)?,
+                    source_article_id: None,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT grade_level, domain, difficulty, passage, question_text, question_type, options,
+                    correct_answer, correct_answers, answer_pattern, explanation
+             FROM wida_reading_questions WHERE source_article_id = ? ORDER BY id"
+        )?;
+        let reading_questions = stmt
+            .query_map([article_id], |row| {
+                Ok(GeneratedReadingQuestion {
+                    grade_level: row.get(0)?,
+                    domain: row.get(1)?,
+                    difficulty: row.get(2)?,
+                    passage: row.get(3)?,
+                    question_text: row.get(4)?,
+                    question_type: row.get(5)?,
+                    options: json_list(row.get(6)?),
+                    correct_answer: row.get(7)?,
+                    correct_answers: json_list(row.get(8)?),
+                    answer_pattern: row.get(9)?,
+                    explanation: row.get(10)?,
+                    source_article_id: None,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(Some(crate::packs::PackArticle {
+            title: article.title,
+            content: article.content,
+            tags: self.get_article_tags(article_id)?,
+            segments,
+            listening_questions,
+            reading_questions,
+        }))
+    }
+
+    /// 导入单元包中的文章（一个事务，全部成功或全部不导入），返回每篇文章的新 ID 和导入的题目数
+    pub fn import_pack_articles(&self, articles: &[crate::packs::PackArticle]) -> SqliteResult<Vec<(i64, i32)>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut imported = Vec::with_capacity(articles.len());
+        for article in articles {
+            let article_id = self.create_article(&article.title, &article.content)?;
+            let mut order: std::collections::HashMap<&str, i64> = std::collections::HashMap::new();
+            for segment in &article.segments {
+                let index = order.entry(segment.segment_type.as_str()).or_insert(0);
+                self.conn.execute(
+                    "INSERT INTO segments (article_id, segment_type, content, order_index) VALUES (?, ?, ?, ?)",
+                    rusqlite::params![article_id, segment.segment_type, segment.content, *index],
+                )?;
+                *index += 1;
+                if let (Some(start_ms), Some(end_ms)) = (segment.start_ms, segment.end_ms) {
+                    self.conn.execute(
+                        "INSERT INTO segment_timings (segment_id, start_ms, end_ms) VALUES (?, ?, ?)",
+                        rusqlite::params![self.conn.last_insert_rowid(), start_ms, end_ms],
+                    )?;
+                }
+            }
+            for tag in article.tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO article_tags (article_id, tag) VALUES (?, ?)",
+                    rusqlite::params![article_id, tag],
+                )?;
+            }
+            let listening: Vec<_> = article
+                .listening_questions
+                .iter()
+                .map(|q| crate::commands::wida::GeneratedListeningQuestion { source_article_id: Some(article_id), ..q.clone() })
+                .collect();
+            let reading: Vec<_> = article
+                .reading_questions
+                .iter()
+                .map(|q| crate::commands::wida::GeneratedReadingQuestion { source_article_id: Some(article_id), ..q.clone() })
+                .collect();
+            let questions = self.save_listening_questions(&listening)? + self.save_reading_questions(&reading)?;
+            imported.push((article_id, questions));
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+
+    // ========== 学习状态导出/导入 ==========
+
+    /// 导出用户的学习状态（熟练度、错词、练习记录、设置），按单词内容和文章标题关联
+    pub fn export_learning_state(&self, user_name: &str) -> SqliteResult<crate::models::LearningStateExport> {
+        use crate::models::{ExportedMastery, ExportedMistake, ExportedPractice, ExportedSettings, ExportedWidaResult};
+
+        // 按内容合并后同一单词可能有多条记录，只导出最近复习的一条
+        let mut seen = std::collections::HashSet::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT segment_content, segment_type, mastery_level, ease_factor, interval_days, next_review_at, last_review_at, review_count
+             FROM word_mastery WHERE user_name = ? ORDER BY last_review_at DESC, review_count DESC"
+        )?;
+        let mastery = stmt
+            .query_map([user_name], |row| {
+                Ok(ExportedMastery {
+                    content: row.get(0)?,
+                    segment_type: row.get(1)?,
+                    mastery_level: row.get(2)?,
+                    ease_factor: row.get(3)?,
+                    interval_days: row.get(4)?,
+                    next_review_at: row.get(5)?,
+                    last_review_at: row.get(6)?,
+                    review_count: row.get(7)?,
+                })
+            })?
+            .filter(|m| m.as_ref().map_or(true, |m| seen.insert((m.content.to_lowercase(), m.segment_type.clone()))))
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT segment_content, segment_type, error_count, last_error_at, correct_streak, archived_at, archive_reason
+             FROM mistakes WHERE user_name = ? ORDER BY last_error_at DESC"
+        )?;
+        let mistakes = stmt
+            .query_map([user_name], |row| {
+                Ok(ExportedMistake {
+                    content: row.get(0)?,
+                    segment_type: row.get(1)?,
+                    error_count: row.get(2)?,
+                    last_error_at: row.get(3)?,
+                    correct_streak: row.get::<_, Option<i32>>(4)?.unwrap_or(0),
+                    archived_at: row.get(5)?,
+                    archive_reason: row.get(6)?,
+                })
+            })?
+            .filter(|m| m.as_ref().map_or(true, |m| seen.insert((m.content.to_lowercase(), m.segment_type.clone()))))
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT a.title, h.segment_type, h.correct_count, h.incorrect_count, h.total_count, h.accuracy, h.wpm,
+                    h.duration_seconds, h.completed_at, h.goal, h.goal_met, h.mode
+             FROM practice_history h JOIN articles a ON a.id = h.article_id
+             WHERE h.user_name = ? ORDER BY h.completed_at"
+        )?;
+        let practice_history = stmt
+            .query_map([user_name], |row| {
+                Ok(ExportedPractice {
+                    article_title: row.get(0)?,
+                    segment_type: row.get(1)?,
+                    correct_count: row.get(2)?,
+                    incorrect_count: row.get(3)?,
+                    total_count: row.get(4)?,
+                    accuracy: row.get(5)?,
+                    wpm: row.get(6)?,
+                    duration_seconds: row.get(7)?,
+                    completed_at: row.get(8)?,
+                    goal: row.get(9)?,
+                    goal_met: row.get(10)?,
+                    mode: row.get(11)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count,
+                    duration_seconds, completed_at
+             FROM wida_test_history WHERE user_name = ? ORDER BY completed_at"
+        )?;
+        let wida_history = stmt
+            .query_map([user_name], |row| {
+                Ok(ExportedWidaResult {
+                    test_type: row.get(0)?,
+                    grade_level: row.get(1)?,
+                    score: row.get(2)?,
+                    proficiency_level: row.get(3)?,
+                    accuracy: row.get(4)?,
+                    total_questions: row.get(5)?,
+                    correct_count: row.get(6)?,
+                    duration_seconds: row.get(7)?,
+                    completed_at: row.get(8)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let (daily_new_words, carryover) = self.pacing_settings(user_name)?.map_or((None, false), |(l, c)| (Some(l), c));
+        let has_accommodations: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM user_accommodations WHERE user_name = ?)",
+            [user_name],
+            |row| row.get(0),
+        )?;
+        let has_layout: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM typing_settings WHERE user_name = ?)",
+            [user_name],
+            |row| row.get(0),
+        )?;
+        let settings = ExportedSettings {
+            birth_year: self.get_user_birth_year(user_name)?,
+            daily_new_words,
+            carryover,
+            content_keyed_mastery: self.content_keyed_mastery(user_name)?,
+            accept_spelling_variants: self.accept_spelling_variants(user_name)?,
+            keyboard_layout: if has_layout { Some(self.get_keyboard_layout(user_name)?) } else { None },
+            accommodations: if has_accommodations { Some(self.get_accommodations(user_name)?) } else { None },
+        };
+
+        Ok(crate::models::LearningStateExport {
+            format_version: LEARNING_STATE_FORMAT_VERSION,
+            user_name: user_name.to_string(),
+            exported_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            mastery,
+            mistakes,
+            practice_history,
+            wida_history,
+            settings,
+        })
+    }
+
+    /// 把导出的学习状态导入到 `user_name`：单词按内容和类型关联本机的分词，练习记录按文章标题关联
+    ///
+    /// 本机已有的熟练度复习次数不少于导入记录时保留本机记录，错词保留最近出错的一条；
+    /// 已存在的练习记录和测试历史不重复导入，可以多次导入同一文件。
+    pub fn import_learning_state(
+        &self,
+        user_name: &str,
+        export: &crate::models::LearningStateExport,
+    ) -> SqliteResult<crate::models::LearningStateImport> {
+        if export.format_version > LEARNING_STATE_FORMAT_VERSION {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "导出文件版本 {} 过新，请先升级程序",
+                export.format_version
+            )));
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let mut result = crate::models::LearningStateImport {
+            user_name: user_name.to_string(),
+            mastery_imported: 0,
+            mistakes_imported: 0,
+            practice_imported: 0,
+            wida_imported: 0,
+            unmatched_words: vec![],
+            unmatched_articles: vec![],
+        };
+
+        let settings = &export.settings;
+        self.set_content_keyed_mastery(user_name, settings.content_keyed_mastery)?;
+        self.set_accept_spelling_variants(user_name, settings.accept_spelling_variants)?;
+        let content_keyed = settings.content_keyed_mastery;
+        if content_keyed {
+            Self::merge_masteries(&tx, user_name)?;
+        }
+        let matching_segments = |content: &str, segment_type: &str| -> SqliteResult<Vec<i64>> {
+            let mut stmt = self.conn.prepare(
+                "SELECT id FROM segments WHERE lower(content) = lower(?1) AND segment_type = ?2 ORDER BY id"
+            )?;
+            let mut ids = stmt.query_map([content, segment_type], |row| row.get(0))?.collect::<SqliteResult<Vec<i64>>>()?;
+            // 按内容合并熟练度时只写一条记录
+            if content_keyed {
+                ids.truncate(1);
+            }
+            Ok(ids)
+        };
+
+        for mastery in &export.mastery {
+            let segment_ids = matching_segments(&mastery.content, &mastery.segment_type)?;
+            if segment_ids.is_empty() {
+                result.unmatched_words.push(mastery.content.clone());
+                continue;
+            }
+            let mut imported = false;
+            for segment_id in segment_ids {
+                // 按内容合并时写到已有的同内容记录上，不新增重复行
+                let segment_id = self.mastery_segment_id(user_name, segment_id, &mastery.content, &mastery.segment_type)?;
+                let mut stmt = self.conn.prepare(
+                    "SELECT mastery_level, review_count FROM word_mastery WHERE user_name = ?1 AND segment_id = ?2"
+                )?;
+                let existing: Option<(i32, i32)> = stmt
+                    .query_map(rusqlite::params![user_name, segment_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .next()
+                    .transpose()?;
+                if existing.is_some_and(|(_, reviews)| reviews >= mastery.review_count) {
+                    continue;
+                }
+                self.conn.execute(
+                    "INSERT INTO word_mastery (user_name, segment_id, segment_content, segment_type, mastery_level, ease_factor,
+                                               interval_days, next_review_at, last_review_at, review_count)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                     ON CONFLICT(user_name, segment_id) DO UPDATE SET
+                        mastery_level = ?5, ease_factor = ?6, interval_days = ?7,
+                        next_review_at = ?8, last_review_at = ?9, review_count = ?10",
+                    rusqlite::params![
+                        user_name,
+                        segment_id,
+                        mastery.content,
+                        mastery.segment_type,
+                        mastery.mastery_level,
+                        mastery.ease_factor,
+                        mastery.interval_days,
+                        mastery.next_review_at,
+                        mastery.last_review_at,
+                        mastery.review_count,
+                    ],
+                )?;
+                self.conn.execute(
+                    "INSERT INTO mastery_events (user_name, segment_id, segment_content, old_level, new_level, correct)
+                     VALUES (?, ?, ?, ?, ?, NULL)",
+                    rusqlite::params![user_name, segment_id, mastery.content, existing.map(|(level, _)| level), mastery.mastery_level],
+                )?;
+                imported = true;
+            }
+            if imported {
+                result.mastery_imported += 1;
+            }
+        }
+
+        for mistake in &export.mistakes {
+            let segment_ids = matching_segments(&mistake.content, &mistake.segment_type)?;
+            if segment_ids.is_empty() {
+                if !result.unmatched_words.contains(&mistake.content) {
+                    result.unmatched_words.push(mistake.content.clone());
+                }
+                continue;
+            }
+            let mut imported = false;
+            for segment_id in segment_ids {
+                imported |= self.conn.execute(
+                    "INSERT INTO mistakes (user_name, segment_id, segment_content, segment_type, error_count, last_error_at,
+                                           correct_streak, archived_at, archive_reason)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                     ON CONFLICT(user_name, segment_id) DO UPDATE SET
+                        error_count = ?5, last_error_at = ?6, correct_streak = ?7, archived_at = ?8, archive_reason = ?9
+                     WHERE COALESCE(?6, '') > COALESCE(mistakes.last_error_at, '')",
+                    rusqlite::params![
+                        user_name,
+                        segment_id,
+                        mistake.content,
+                        mistake.segment_type,
+                        mistake.error_count,
+                        mistake.last_error_at,
+                        mistake.correct_streak,
+                        mistake.archived_at,
+                        mistake.archive_reason,
+                    ],
+                )? > 0;
+            }
+            if imported {
+                result.mistakes_imported += 1;
+            }
+        }
+
+        for practice in &export.practice_history {
+            let mut stmt = self.conn.prepare("SELECT id FROM articles WHERE title = ? ORDER BY id LIMIT 1")?;
+            let article_id: Option<i64> = stmt.query_map([&practice.article_title], |row| row.get(0))?.next().transpose()?;
+            let Some(article_id) = article_id else {
+                if !result.unmatched_articles.contains(&practice.article_title) {
+                    result.unmatched_articles.push(practice.article_title.clone());
+                }
+                continue;
+            };
+            result.practice_imported += self.conn.execute(
+                "INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, incorrect_count, total_count,
+                                               accuracy, wpm, duration_seconds, completed_at, goal, goal_met, mode)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
+                 WHERE NOT EXISTS (SELECT 1 FROM practice_history
+                                   WHERE user_name = ?1 AND article_id = ?2 AND segment_type = ?3 AND completed_at = ?10)",
+                rusqlite::params![
+                    user_name,
+                    article_id,
+                    practice.segment_type,
+                    practice.correct_count,
+                    practice.incorrect_count,
+                    practice.total_count,
+                    practice.accuracy,
+                    practice.wpm,
+                    practice.duration_seconds,
+                    practice.completed_at,
+                    practice.goal,
+                    practice.goal_met,
+                    practice.mode,
+                ],
+            )? as i32;
+        }
+
+        for wida in &export.wida_history {
+            result.wida_imported += self.conn.execute(
+                "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy,
+                                                total_questions, correct_count, duration_seconds, completed_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
+                 WHERE NOT EXISTS (SELECT 1 FROM wida_test_history WHERE user_name = ?1 AND test_type = ?2 AND completed_at = ?10)",
+                rusqlite::params![
+                    user_name,
+                    wida.test_type,
+                    wida.grade_level,
+                    wida.score,
+                    wida.proficiency_level,
+                    wida.accuracy,
+                    wida.total_questions,
+                    wida.correct_count,
+                    wida.duration_seconds,
+                    wida.completed_at,
+                ],
+            )? as i32;
+        }
+
+        if settings.birth_year.is_some() {
+            self.set_user_birth_year(user_name, settings.birth_year)?;
+        }
+        if settings.daily_new_words.is_some() {
+            self.set_new_word_pacing(user_name, settings.daily_new_words, settings.carryover)?;
+        }
+        if let Some(layout) = &settings.keyboard_layout {
+            self.set_keyboard_layout(user_name, layout)?;
+        }
+        if let Some(accommodations) = &settings.accommodations {
+            self.set_accommodations(&crate::models::Accommodations { user_name: user_name.to_string(), ..accommodations.clone() })?;
+        }
+
+        tx.commit()?;
+        Ok(result)
+    }
+
+    // ========== 数据目录迁移 ==========
+
+    /// 把数据库完整复制到 `path`（数据库使用中也能得到一致的副本），并检查副本完整性
+    pub fn backup_to(&self, path: &Path) -> SqliteResult<()> {
+        self.conn.execute("VACUUM INTO ?", [path.to_string_lossy()])?;
+        let status: String = Connection::open(path)?.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if status != "ok" {
+            return Err(rusqlite::Error::InvalidParameterName(format!("数据库副本校验失败: {}", status)));
+        }
+        Ok(())
+    }
+
+    /// 数据目录迁移后，把图片和录音记录中以 `from` 开头的文件路径改为以 `to` 开头，返回修改的行数
+    pub fn relocate_file_paths(&self, from: &str, to: &str) -> SqliteResult<usize> {
+        let mut updated = 0;
+        for table in ["word_images", "reading_recordings"] {
+            updated += self.conn.execute(
+                &format!(
+                    "UPDATE {} SET file_path = ?2 || substr(file_path, length(?1) + 1)
+                     WHERE substr(file_path, 1, length(?1)) = ?1",
+                    table
+                ),
+                rusqlite::params![from, to],
+            )?;
+        }
+        Ok(updated)
+    }
+
+    // ========== 审计日志 ==========
+
+    /// 记录一次破坏性操作
+    pub fn record_audit(
+        &self,
+        user_name: Option<&str>,
+        action: &str,
+        target_type: &str,
+        target_id: i64,
+        details: Option<&str>,
+    ) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO audit_log (user_name, action, target_type, target_id, details) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![user_name, action, target_type, target_id, details],
+        )?;
+        Ok(())
+    }
+
+    /// 成绩册：完成的作业（取完成时那次练习的正确率）和 WIDA 测试，按日期排列
+    pub fn get_gradebook(&self, user_name: Option<&str>, since: Option<&str>) -> SqliteResult<Vec<crate::models::GradebookEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.user_name, COALESCE(NULLIF(s.title, ''), a.title, ''), 'assignment',
+                    (SELECT h.accuracy FROM practice_history h
+                     WHERE h.user_name = s.user_name AND h.article_id = s.article_id AND h.segment_type = s.segment_type
+                       AND h.completed_at >= s.created_at
+                     ORDER BY h.completed_at ASC, h.id ASC LIMIT 1),
+                    s.completed_at
+             FROM assignments s LEFT JOIN articles a ON s.article_id = a.id
+             WHERE s.status = 'completed' AND (?1 IS NULL OR s.user_name = ?1) AND (?2 IS NULL OR s.completed_at >= ?2)
+             UNION ALL
+             SELECT user_name, 'WIDA ' || test_type || ' (' || grade_level || ')', 'wida', accuracy, completed_at
+             FROM wida_test_history
+             WHERE (?1 IS NULL OR user_name = ?1) AND (?2 IS NULL OR completed_at >= ?2)
+             ORDER BY 5, 1"
+        )?;
+        let entries = stmt.query_map(rusqlite::params![user_name, since], |row| {
+            Ok(crate::models::GradebookEntry {
+                student: row.get(0)?,
+                assignment: row.get(1)?,
+                kind: row.get(2)?,
+                score: row.get(3)?,
+                date: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        entries
+    }
+
+    /// 最近的审计记录，可按对象类型筛选
+    pub fn get_audit_log(&self, target_type: Option<&str>, limit: i32) -> SqliteResult<Vec<crate::models::AuditLogEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_name, action, target_type, target_id, details, created_at FROM audit_log
+             WHERE ?1 IS NULL OR target_type = ?1
+             ORDER BY id DESC LIMIT ?2"
+        )?;
+        let entries = stmt.query_map(rusqlite::params![target_type, limit], |row| {
+            Ok(crate::models::AuditLogEntry {
+                id: row.get(0)?,
+                user_name: row.get(1)?,
+                action: row.get(2)?,
+                target_type: row.get(3)?,
+                target_id: row.get(4)?,
+                details: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        entries
+    }
+
+    /// 执行只读查询，每行以列名为键返回，最多 1000 行
+    pub fn run_readonly_query(&self, sql: &str) -> SqliteResult<Vec<serde_json::Map<String, serde_json::Value>>> {
+        self.run_readonly_query_within(sql, READONLY_QUERY_TIMEOUT)
+    }
+
+    fn run_readonly_query_within(
+        &self,
+        sql: &str,
+        timeout: std::time::Duration,
+    ) -> SqliteResult<Vec<serde_json::Map<String, serde_json::Value>>> {
+        let deadline = std::time::Instant::now() + timeout;
+        self.conn.progress_handler(READONLY_QUERY_PROGRESS_STEPS, Some(move || std::time::Instant::now() > deadline));
+        let result = self.collect_readonly_rows(sql);
+        self.conn.progress_handler(0, None::<fn() -> bool>);
+        result.map_err(|e| match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::OperationInterrupted) => {
+                rusqlite::Error::InvalidParameterName(format!("查询超过 {} 秒，已中断", timeout.as_secs_f32()))
+            }
+            _ => e,
+        })
+    }
+
+    fn collect_readonly_rows(&self, sql: &str) -> SqliteResult<Vec<serde_json::Map<String, serde_json::Value>>> {
+        let sql = validate_readonly_sql(sql)?;
+        let mut stmt = self.conn.prepare(sql)?;
+        if !stmt.readonly() {
+            return Err(rusqlite::Error::InvalidParameterName("只允许只读查询".to_string()));
+        }
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let mut rows = stmt.query([])?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            if result.len() >= READONLY_QUERY_MAX_ROWS {
+                break;
+            }
+            let mut record = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                record.insert(column.clone(), sql_value_to_json(row.get_ref(i)?));
+            }
+            result.push(record);
+        }
+        Ok(result)
+    }
+
+    /// 按保留期限清理旧数据：练习记录先按月汇总，已结束的练习会话和 WIDA 测试会话连同作答事件等子表记录直接删除
+    /// （WIDA 成绩保留在 wida_test_history）。`dry_run` 时只统计将被删除的行数。
+    pub fn apply_retention_policy(&mut self, months: u32, dry_run: bool) -> SqliteResult<crate::models::RetentionReport> {
+        if months == 0 {
+            return Err(rusqlite::Error::InvalidParameterName("保留期限至少为 1 个月".to_string()));
+        }
+        let cutoff = (chrono::Utc::now() - chrono::Months::new(months)).format("%Y-%m-%d %H:%M:%S").to_string();
+
+        const HISTORY_FILTER: &str = "completed_at < ?1";
+        const PRACTICE_SESSION_FILTER: &str = "status != 'active' AND updated_at < ?1";
+        const WIDA_SESSION_FILTER: &str = "status != 'in_progress' AND COALESCE(completed_at, started_at) < ?1";
+        // 作答事件随所属会话一起删除，包括会话已不存在的旧事件
+        const PRACTICE_EVENT_FILTER: &str =
+            "session_id NOT IN (SELECT id FROM practice_sessions WHERE NOT (status != 'active' AND updated_at < ?1))";
+        let count = |table: &str, filter: &str| -> SqliteResult<i32> {
+            self.conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, filter), [&cutoff], |row| row.get(0))
+        };
+        let report = crate::models::RetentionReport {
+            months,
+            cutoff: cutoff.clone(),
+            dry_run,
+            practice_history: count("practice_history", HISTORY_FILTER)?,
+            practice_sessions: count("practice_sessions", PRACTICE_SESSION_FILTER)?,
+            practice_events: count("practice_events", PRACTICE_EVENT_FILTER)?,
+            wida_test_sessions: count("wida_test_sessions", WIDA_SESSION_FILTER)?,
+        };
+        if dry_run {
+            return Ok(report);
+        }
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            &format!(
+                "INSERT INTO practice_history_rollups
+                    (user_name, month, segment_type, practice_count, correct_count, incorrect_count, total_count, accuracy_sum, wpm_sum, duration_seconds)
+                 SELECT user_name, substr(completed_at, 1, 7), segment_type, COUNT(*), SUM(correct_count), SUM(incorrect_count),
+                        SUM(total_count), SUM(accuracy), SUM(wpm), SUM(duration_seconds)
+                 FROM practice_history WHERE {} GROUP BY user_name, substr(completed_at, 1, 7), segment_type
+                 ON CONFLICT(user_name, month, segment_type) DO UPDATE SET
+                    practice_count = practice_count + excluded.practice_count,
+                    correct_count = correct_count + excluded.correct_count,
+                    incorrect_count = incorrect_count + excluded.incorrect_count,
+                    total_count = total_count + excluded.total_count,
+                    accuracy_sum = accuracy_sum + excluded.accuracy_sum,
+                    wpm_sum = wpm_sum + excluded.wpm_sum,
+                    duration_seconds = duration_seconds + excluded.duration_seconds",
+                HISTORY_FILTER
+            ),
+            [&cutoff],
+        )?;
+        tx.execute(&format!("DELETE FROM practice_history WHERE {}", HISTORY_FILTER), [&cutoff])?;
+        tx.execute(&format!("DELETE FROM practice_sessions WHERE {}", PRACTICE_SESSION_FILTER), [&cutoff])?;
+        tx.execute(&format!("DELETE FROM wida_test_sessions WHERE {}", WIDA_SESSION_FILTER), [&cutoff])?;
+        for (table, parent) in RETENTION_CHILD_TABLES {
+            tx.execute(&format!("DELETE FROM {} WHERE session_id NOT IN (SELECT id FROM {})", table, parent), [])?;
+        }
+        tx.commit()?;
+        Ok(report)
+    }
+
+    /// 获取按月汇总的练习记录
+    pub fn get_practice_rollups(&self, user_name: &str) -> SqliteResult<Vec<crate::models::PracticeRollup>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT user_name, month, segment_type, practice_count, correct_count, incorrect_count, total_count,
+                    accuracy_sum / practice_count, wpm_sum / practice_count, duration_seconds
+             FROM practice_history_rollups WHERE user_name = ? ORDER BY month DESC, segment_type"
+        )?;
+        let rollups = stmt.query_map([user_name], |row| {
+            Ok(crate::models::PracticeRollup {
+                user_name: row.get(0)?,
+                month: row.get(1)?,
+                segment_type: row.get(2)?,
+                practice_count: row.get(3)?,
+                correct_count: row.get(4)?,
+                incorrect_count: row.get(5)?,
+                total_count: row.get(6)?,
+                avg_accuracy: row.get(7)?,
+                avg_wpm: row.get(8)?,
+                duration_seconds: row.get(9)?,
+            })
+        })?.collect::<SqliteResult<Vec<_>>>();
+        rollups
+    }
+
+    // ========== 保存生成的题目 ==========
+    
+    /// 保存生成的听力题目
+    pub fn save_listening_questions(&self, questions: &[crate::commands::wida::GeneratedListeningQuestion]) -> SqliteResult<i32> {
+        let mut count = 0;
+        for q in questions {
+            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_listening_questions (grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation, source_article_id, question_type, correct_answers, answer_pattern)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.audio_text,
+                    q.image_url,
+                    q.question_text,
+                    options_json,
+                    q.correct_answer,
+                    q.explanation,
+                    q.source_article_id,
+                    q.question_type,
+                    serde_json::to_string(&q.correct_answers).ok(),
+                    q.answer_pattern,
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    
+    /// 保存生成的阅读题目
+    pub fn save_reading_questions(&self, questions: &[crate::commands::wida::GeneratedReadingQuestion]) -> SqliteResult<i32> {
+        let mut count = 0;
+        for q in questions {
+            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_reading_questions (grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation, source_article_id, correct_answers, answer_pattern)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.passage,
+                    q.question_text,
+                    q.question_type,
+                    options_json,
+                    q.correct_answer,
+                    q.explanation,
+                    q.source_article_id,
+                    serde_json::to_string(&q.correct_answers).ok(),
+                    q.answer_pattern,
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    
+    /// 保存生成的口语题目
+    pub fn save_speaking_questions(&self, questions: &[crate::commands::wida::GeneratedSpeakingQuestion]) -> SqliteResult<i32> {
+        let mut count = 0;
+        for q in questions {
+            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_speaking_questions (grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.prompt_type,
+                    q.prompt_text,
+                    q.image_url,
+                    q.audio_text,
+                    q.sample_answer,
+                    rubric_json,
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    
+    /// 保存生成的写作题目
+    pub fn save_writing_questions(&self, questions: &[crate::commands::wida::GeneratedWritingQuestion]) -> SqliteResult<i32> {
+        let mut count = 0;
+        for q in questions {
+            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_writing_questions (grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.task_type,
+                    q.prompt,
+                    q.image_url,
+                    q.word_limit_min,
+                    q.word_limit_max,
+                    rubric_json,
+                    q.sample_answer,
+                ],
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn score_to_level(score: f64) -> i32 {
+    if score >= 550.0 { 6 }
+    else if score >= 475.0 { 5 }
+    else if score >= 400.0 { 4 }
+    else if score >= 325.0 { 3 }
+    else if score >= 250.0 { 2 }
+    else { 1 }
+}
+
+/// 内置题目在题库中的状态
+enum SeedRow {
+    /// 已写入（有 seed_id，或旧版按编号写入的同一道题）
+    Seeded,
+    /// 用户生成或导入了内容相同的题目，不再写入
+    Duplicate,
+    Missing,
+}
+
+impl DatabaseManager {
+    /// 初始化WIDA题库（如果为空）
+    pub fn seed_wida_questions(&self) -> SqliteResult<()> {
+        // 检查是否已有数据
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM wida_listening_questions",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if count > 0 {
+            return Ok(()); // 已有数据，跳过
+        }
+
+        self.reseed_defaults(None, false)?;
+        log::info!("WIDA questions seeded successfully");
+        Ok(())
+    }
+
+    /// 补充缺少的内置题目，`grade_levels` 为空时补充全部年级段
+    ///
+    /// 按 seed_id 判断内置题目是否已存在，与用户生成的题目内容相同时不再写入；
+    /// `force` 时把已有的内置题目恢复为内置内容（如校准后改过的难度）。全部题目在一个事务中写入。
+    pub fn reseed_defaults(&self, grade_levels: Option<&[String]>, force: bool) -> SqliteResult<crate::models::ReseedReport> {
+        if let Some(grades) = grade_levels {
+            if let Some(unknown) = grades.iter().find(|g| !WIDA_GRADE_BANDS.iter().any(|(band, _, _)| band == g)) {
+                return Err(rusqlite::Error::InvalidParameterName(format!("未知的年级段: {}", unknown)));
+            }
+        }
+        let selected = |grade: &str| grade_levels.is_none_or(|grades| grades.iter().any(|g| g == grade));
+        let to_sql_error = |e: serde_json::Error| rusqlite::Error::ToSqlConversionFailure(Box::new(e));
+        let mut report = crate::models::ReseedReport::default();
+        let record = |row: SeedRow, report: &mut crate::models::ReseedReport| -> bool {
+            match row {
+                SeedRow::Missing => report.added += 1,
+                SeedRow::Seeded if force => report.reset += 1,
+                SeedRow::Seeded => return false,
+                SeedRow::Duplicate => {
+                    report.skipped += 1;
+                    return false;
+                }
+            }
+            true
+        };
+        let tx = self.conn.unchecked_transaction()?;
+
+        // 听力题
+        for q in crate::seeds::listening_questions().map_err(to_sql_error)? {
+            if !selected(&q.grade_level) {
+                continue;
+            }
+            let row = self.seed_row("wida_listening_questions", "question_text", q.id, &q.grade_level, &q.question_text)?;
+            if !record(row, &mut report) {
+                continue;
+            }
+            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_listening_questions (seed_id, grade_level, domain, difficulty, audio_text, image_url, question_text, options, correct_answer, explanation)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(seed_id) DO UPDATE SET
+                    grade_level = excluded.grade_level, domain = excluded.domain, difficulty = excluded.difficulty,
+                    audio_text = excluded.audio_text, image_url = excluded.image_url, question_text = excluded.question_text,
+                    options = excluded.options, correct_answer = excluded.correct_answer, explanation = excluded.explanation",
+                rusqlite::params![
+                    q.id,
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.audio_text,
+                    q.image_url,
+                    q.question_text,
+                    options_json,
+                    q.correct_answer,
+                    q.explanation,
+                ],
+            )?;
+        }
+
+        // 阅读题
+        for q in crate::seeds::reading_questions().map_err(to_sql_error)? {
+            if !selected(&q.grade_level) {
+                continue;
+            }
+            let row = self.seed_row("wida_reading_questions", "question_text", q.id, &q.grade_level, &q.question_text)?;
+            if !record(row, &mut report) {
+                continue;
+            }
+            let options_json = serde_json::to_string(&q.options).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_reading_questions (seed_id, grade_level, domain, difficulty, passage, question_text, question_type, options, correct_answer, explanation)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(seed_id) DO UPDATE SET
+                    grade_level = excluded.grade_level, domain = excluded.domain, difficulty = excluded.difficulty,
+                    passage = excluded.passage, question_text = excluded.question_text, question_type = excluded.question_type,
+                    options = excluded.options, correct_answer = excluded.correct_answer, explanation = excluded.explanation",
+                rusqlite::params![
+                    q.id,
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.passage,
+                    q.question_text,
+                    q.question_type,
+                    options_json,
+                    q.correct_answer,
+                    q.explanation,
+                ],
+            )?;
+        }
+
+        // 口语题
+        for q in crate::seeds::speaking_questions().map_err(to_sql_error)? {
+            if !selected(&q.grade_level) {
+                continue;
+            }
+            let row = self.seed_row("wida_speaking_questions", "prompt_text", q.id, &q.grade_level, &q.prompt_text)?;
+            if !record(row, &mut report) {
+                continue;
+            }
+            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_speaking_questions (seed_id, grade_level, domain, difficulty, prompt_type, prompt_text, image_url, audio_text, sample_answer, rubric)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(seed_id) DO UPDATE SET
+                    grade_level = excluded.grade_level, domain = excluded.domain, difficulty = excluded.difficulty,
+                    prompt_type = excluded.prompt_type, prompt_text = excluded.prompt_text, image_url = excluded.image_url,
+                    audio_text = excluded.audio_text, sample_answer = excluded.sample_answer, rubric = excluded.rubric",
+                rusqlite::params![
+                    q.id,
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.prompt_type,
+                    q.prompt_text,
+                    q.image_url,
+                    q.audio_text,
+                    q.sample_answer,
+                    rubric_json,
+                ],
+            )?;
+        }
+
+        // 写作题
+        for q in crate::seeds::writing_questions().map_err(to_sql_error)? {
+            if !selected(&q.grade_level) {
+                continue;
+            }
+            let row = self.seed_row("wida_writing_questions", "prompt", q.id, &q.grade_level, &q.prompt)?;
+            if !record(row, &mut report) {
+                continue;
+            }
+            let rubric_json = serde_json::to_string(&q.rubric).unwrap_or_else(|_| "[]".to_string());
+            self.conn.execute(
+                "INSERT INTO wida_writing_questions (seed_id, grade_level, domain, difficulty, task_type, prompt, image_url, word_limit_min, word_limit_max, rubric, sample_answer)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(seed_id) DO UPDATE SET
+                    grade_level = excluded.grade_level, domain = excluded.domain, difficulty = excluded.difficulty,
+                    task_type = excluded.task_type, prompt = excluded.prompt, image_url = excluded.image_url,
+                    word_limit_min = excluded.word_limit_min, word_limit_max = excluded.word_limit_max,
+                    rubric = excluded.rubric, sample_answer = excluded.sample_answer",
+                rusqlite::params![
+                    q.id,
+                    q.grade_level,
+                    q.domain,
+                    q.difficulty,
+                    q.task_type,
+                    q.prompt,
+                    q.image_url,
+                    q.word_limit_min,
+                    q.word_limit_max,
+                    rubric_json,
+                    q.sample_answer,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(report)
+    }
+
+    /// 查找内置题目：旧版按编号写入、还没有 seed_id 的同一道题在这里补上 seed_id
+    fn seed_row(&self, table: &str, text_column: &str, seed_id: i64, grade_level: &str, text: &str) -> SqliteResult<SeedRow> {
+        let seeded: bool = self.conn.query_row(
+            &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE seed_id = ?)", table),
+            [seed_id],
+            |row| row.get(0),
+        )?;
+        if seeded {
+            return Ok(SeedRow::Seeded);
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM {} WHERE seed_id IS NULL AND grade_level = ?1 AND {} = ?2 ORDER BY id != ?3, id",
+            table, text_column
+        ))?;
+        let same: Option<i64> = stmt
+            .query_map(rusqlite::params![grade_level, text, seed_id], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        match same {
+            Some(id) if id == seed_id => {
+                self.conn.execute(&format!("UPDATE {} SET seed_id = ?1 WHERE id = ?1", table), [seed_id])?;
+                Ok(SeedRow::Seeded)
+            }
+            Some(_) => Ok(SeedRow::Duplicate),
+            None => Ok(SeedRow::Missing),
+        }
+    }
+}
+
+// ========== 记忆曲线测试模块 ==========
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// 创建测试数据库
+    fn create_test_db() -> DatabaseManager {
+        let conn = Connection::open_in_memory().unwrap();
+        let db = DatabaseManager { conn };
+        db.initialize_schema().unwrap();
+        db
+    }
+    
+    /// 创建测试文章和分词
+    fn setup_test_data(db: &mut DatabaseManager) -> (i64, i64, i64) {
+        // 创建文章
+        db.create_article("测试文章", "这是一篇测试文章").unwrap();
+        
+        // 添加分词
+        let article_id = 1;
+        let segments_vec: Vec<String> = vec![
+            "apple".to_string(), "banana".to_string(), "cherry".to_string(),
+            "date".to_string(), "elder".to_string()
+        ];
+        db.save_segments(article_id, "word", &segments_vec).unwrap();
+        
+        // 获取分词 ID（按 order_index 排序）
+        let segments = db.get_segments(article_id, "word").unwrap();
+        assert_eq!(segments.len(), 5);
+        
+        (article_id, segments[0].id, segments[1].id)
+    }
+    
+    /// 测试 1: 新单词答对 → 熟练度变为 1，间隔 1 天
+    #[test]
+    fn test_new_word_correct() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 答对新单词
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        
+        assert_eq!(result.mastery_level, 1);
+        assert_eq!(result.interval_days, 1);
+        assert_eq!(result.review_count, 1);
+    }
+    
+    /// 测试 2: 新单词答错 → 熟练度保持 0，间隔 0 天
+    #[test]
+    fn test_new_word_incorrect() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 答错新单词
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        assert_eq!(result.mastery_level, 0);
+        assert_eq!(result.interval_days, 0);
+        assert_eq!(result.review_count, 0);
+    }
+    
+    /// 测试 3: 已学习单词答对 → 熟练度 +1
+    #[test]
+    fn test_existing_word_correct() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 先答对，熟练度变为 1
+        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        
+        // 再次答对，熟练度变为 2
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        
+        assert_eq!(result.mastery_level, 2);
+        assert_eq!(result.interval_days, 3); // 熟练度 2 → 间隔 3 天
+    }
+    
+    /// 测试 4: 已学习单词答错 → 熟练度 -1，间隔重置
+    #[test]
+    fn test_existing_word_incorrect() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 先答对，熟练度变为 1
+        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        
+        // 答错，熟练度变为 0
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        assert_eq!(result.mastery_level, 0);
+        assert_eq!(result.interval_days, 0); // 立即需要复习
+    }
+    
+    /// 测试 5: 熟练度达到 5 后答对 → 保持 5，间隔 30 天
+    #[test]
+    fn test_max_mastery_level() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 连续答对 5 次，达到熟练度 5
+        for _ in 0..5 {
+            db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        }
+        
+        // 第 6 次答对，应该保持熟练度 5
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        
+        assert_eq!(result.mastery_level, 5);
+        assert_eq!(result.interval_days, 30); // 熟练度 5 → 间隔 30 天
+    }
+    
+    /// 测试 6: 熟练度为 0 后答错 → 保持 0
+    #[test]
+    fn test_min_mastery_level() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 答错，熟练度为 0
+        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        // 再次答错，应该保持 0
+        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        assert_eq!(result.mastery_level, 0);
+    }
+    
+    /// 测试 7: 文章没有分词 → 返回空
+    #[test]
+    fn test_no_segments() {
+        let db = create_test_db();
+        let _ = db.create_article("空文章", "内容").unwrap();
+        
+        let result = db.get_scheduled_words("default", 1, "word", 10, false).unwrap();
+        
+        assert!(result.words.is_empty());
+        assert_eq!(result.new_words_count, 0);
+        assert_eq!(result.review_words_count, 0);
+    }
+    
+    /// 测试 8: 只有新词 → 返回新词
+    #[test]
+    fn test_only_new_words() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+        
+        let result = db.get_scheduled_words("default", article_id, "word", 10, false).unwrap();
+        
+        assert_eq!(result.words.len(), 5);
+        assert_eq!(result.new_words_count, 5);
+        assert_eq!(result.review_words_count, 0);
+        
+        // 所有都是新词
+        for word in &result.words {
+            assert!(word.is_new);
+            assert_eq!(word.mastery_level, 0);
+        }
+    }
+    
+    /// 测试 9: 到期复习词优先于新词
+    #[test]
+    fn test_review_words_first() {
+        let mut db = create_test_db();
+        let (article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 让第一个单词到期（答错，interval=0）
+        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        let result = db.get_scheduled_words("default", article_id, "word", 5, true).unwrap();
+        
+        // 到期的复习词应该排在前面
+        assert_eq!(result.words.len(), 5);
+        assert_eq!(result.review_words_count, 1);
+        assert_eq!(result.new_words_count, 4);
+        
+        // 第一个应该是已复习的 apple
+        assert_eq!(result.words[0].content, "apple");
+        assert!(!result.words[0].is_new);
+    }
+    
+    /// 测试 10: 复习词数量超过 limit → 只返回 limit 个
+    #[test]
+    fn test_review_words_exceed_limit() {
+        let mut db = create_test_db();
+        let (article_id, segment_id1, segment_id2) = setup_test_data(&mut db);
+        
+        // 让两个单词都到期
+        db.update_word_mastery("default", segment_id1, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", segment_id2, "banana", "word", false).unwrap();
+        
+        // limit = 1
+        let result = db.get_scheduled_words("default", article_id, "word", 1, true).unwrap();
+        
+        assert_eq!(result.words.len(), 1);
+        assert_eq!(result.review_words_count, 1);
+    }
+    
+    /// 测试 11: 复习词不足 limit → 补充新词
+    #[test]
+    fn test_review_words_insufficient() {
+        let mut db = create_test_db();
+        let (article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 让一个单词到期
+        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        // limit = 5，复习词只有 1 个，需要补充 4 个新词
+        let result = db.get_scheduled_words("default", article_id, "word", 5, true).unwrap();
+        
+        assert_eq!(result.words.len(), 5);
+        assert_eq!(result.review_words_count, 1);
+        assert_eq!(result.new_words_count, 4);
+    }
+    
+    /// 测试 12: 按熟练度排序 → 低的优先
+    #[test]
+    fn test_sort_by_mastery_level() {
+        let mut db = create_test_db();
+        let (article_id, segment_id1, segment_id2) = setup_test_data(&mut db);
+        
+        // apple 熟练度 2
+        db.update_word_mastery("default", segment_id1, "apple", "word", true).unwrap(); // 1
+        db.update_word_mastery("default", segment_id1, "apple", "word", true).unwrap(); // 2
+        
+        // banana 熟练度 1
+        db.update_word_mastery("default", segment_id2, "banana", "word", true).unwrap(); // 1
+        
+        // 让两个都到期
+        db.update_word_mastery("default", segment_id1, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", segment_id2, "banana", "word", false).unwrap();
+        
+        let result = db.get_scheduled_words("default", article_id, "word", 5, true).unwrap();
+        
+        // 熟练度低的应该排在前面
+        assert_eq!(result.words[0].content, "banana"); // 熟练度 1
+        assert_eq!(result.words[1].content, "apple");  // 熟练度 2
+    }
+    
+    /// 测试 13: 重新分词后保留记忆曲线数据
+    #[test]
+    fn test_resegment_preserves_mastery() {
+        let mut db = create_test_db();
+        let (article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 答错 apple（interval=0，当天到期）
+        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        
+        // 验证 apple 已学习（因为答错，当天到期）
+        let result1 = db.get_scheduled_words("default", article_id, "word", 10, true).unwrap();
+        
+        // 找到 apple
+        let apple_before = result1.words.iter().find(|w| w.content == "apple");
+        assert!(apple_before.is_some(), "apple should exist before resegment");
+        assert!(!apple_before.unwrap().is_new, "apple should not be new");
+        
+        // 重新分词：添加一个新词，保持原有词
+        let new_segments: Vec<String> = vec![
+            "apple".to_string(), "banana".to_string(), "new_word".to_string()
+        ];
+        db.save_segments(article_id, "word", &new_segments).unwrap();
+        
+        // 检查分词是否正确插入
+        let segments = db.get_segments(article_id, "word").unwrap();
+        assert_eq!(segments.len(), 3, "Expected 3 segments, got {}", segments.len());
+        
+        // 检查 apple 的熟练度是否保留
+        let result = db.get_scheduled_words("default", article_id, "word", 10, true).unwrap();
+        
+        // apple 应该是已学习的
+        let apple = result.words.iter().find(|w| w.content == "apple");
+        assert!(apple.is_some(), "apple should exist in scheduled words");
+        let apple = apple.unwrap();
+        assert!(!apple.is_new, "apple should not be new after resegment");
+        
+        // new_word 应该是新的
+        let new_word = result.words.iter().find(|w| w.content == "new_word").unwrap();
+        assert!(new_word.is_new);
+    }
+    
+    /// 测试 14: 难度因子调整
+    #[test]
+    fn test_ease_factor() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 第一次答对 → ease_factor 保持 2.5（初始值）
+        let result1 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert_eq!(result1.ease_factor, 2.5);
+        
+        // 第二次答对 → ease_factor 增加
+        let result2 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert!(result2.ease_factor > 2.5);
+        
+        // 答错 → ease_factor 减少
+        let result3 = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
+        assert!(result3.ease_factor < result2.ease_factor);
+        
+        // ease_factor 应该在 1.3 ~ 3.0 范围内
+        assert!(result3.ease_factor >= 1.3);
+    }
+    
+    /// 测试 15: 间隔天数正确计算
+    #[test]
+    fn test_interval_days() {
+        let mut db = create_test_db();
+        let (_article_id, segment_id, _) = setup_test_data(&mut db);
+        
+        // 熟练度 0 → 间隔 1 天
+        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        let r1 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert_eq!(r1.interval_days, 3); // 熟练度 2
+        
+        let r2 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert_eq!(r2.interval_days, 7); // 熟练度 3
+        
+        let r3 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert_eq!(r3.interval_days, 14); // 熟练度 4
+        
+        let r4 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
+        assert_eq!(r4.interval_days, 30); // 熟练度 5
+    }
+
+    /// 测试 16: 完成练习后对应作业自动标记为完成
+    #[test]
+    fn test_assignment_completed_by_practice() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+
+        db.create_assignment(&crate::models::CreateAssignmentRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            title: "第一课单词".to_string(),
+            due_date: None,
+            check_options: None,
+        }).unwrap();
+        assert_eq!(db.get_assignments(Some("default"), Some("pending")).unwrap().len(), 1);
+
+        db.save_practice_history("default", article_id, "word", 4, 1, 60).unwrap();
+
+        let completed = db.get_assignments(Some("default"), Some("completed")).unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].article_title, "测试文章");
+        assert_eq!(db.get_dashboard_overview().unwrap().pending_assignments, 0);
+    }
+
+    /// 测试 17: 字幕时间轴随分词保存，重新分词时清除
+    #[test]
+    fn test_segment_timings() {
+        let mut db = create_test_db();
+        let article_id = db.create_article("字幕", "Hello there.\nHow are you?").unwrap();
+        let sentences = vec!["Hello there.".to_string(), "How are you?".to_string()];
+        db.save_segments(article_id, "sentence", &sentences).unwrap();
+        db.save_segment_timings(article_id, "sentence", &[(1000, 2500), (3000, 4200)]).unwrap();
+
+        let timings = db.get_segment_timings(article_id, "sentence").unwrap();
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[1].order_index, 1);
+        assert_eq!((timings[1].start_ms, timings[1].end_ms), (3000, 4200));
+
+        db.save_segments(article_id, "sentence", &sentences).unwrap();
+        assert!(db.get_segment_timings(article_id, "sentence").unwrap().is_empty());
+    }
+
+    /// 测试 18: 提取 WIDA 错题文本
+    #[test]
+    fn test_wida_missed_texts() {
+        let db = create_test_db();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "science".to_string(),
+            difficulty: 2,
+            audio_text: "Plants need sunlight to grow.".to_string(),
+            image_url: None,
+            question_text: "What do plants need?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Sunlight".to_string(), "Sand".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "listening".to_string(),
+            grade_level: "grade_3_5".to_string(),
+            domain: None,
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+        assert!(db.get_wida_missed_texts(session.id).is_err());
+
+        let question_ids: Vec<i64> = serde_json::from_str(
+            &db.conn.query_row("SELECT question_ids FROM wida_test_sessions WHERE id = ?", [session.id], |row| row.get::<_, String>(0)).unwrap()
+        ).unwrap();
+        db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+            session_id: session.id,
+            question_id: question_ids[0],
+            answer: "1".to_string(),
+            time_spent_seconds: 5,
+        }).unwrap();
+        db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+
+        let texts = db.get_wida_missed_texts(session.id).unwrap();
+        assert!(texts.contains(&"Sunlight".to_string()));
+        assert!(texts.contains(&"Plants need sunlight to grow.".to_string()));
+    }
+
+    /// 测试 19: 错词连续答对后自动归档，再次答错时恢复
+    #[test]
+    fn test_mistake_graduation() {
+        let mut db = create_test_db();
+        let (_, seg1, seg2) = setup_test_data(&mut db);
+
+        db.add_mistake("default", seg1, "apple", "word").unwrap();
+        db.add_mistake("default", seg2, "banana", "word").unwrap();
+
+        for _ in 0..MISTAKE_GRADUATION_STREAK {
+            db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        }
+        db.update_word_mastery("default", seg2, "banana", "word", true).unwrap();
+        db.update_word_mastery("default", seg2, "banana", "word", false).unwrap();
+        db.update_word_mastery("default", seg2, "banana", "word", true).unwrap();
+
+        let active = db.get_mistakes("default", None).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].segment_id, seg2);
+
+        let archived = db.get_archived_mistakes("default").unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].archive_reason, "streak");
+
+        // 归档后再次答错，重新回到错词本
+        db.add_mistake("default", seg1, "apple", "word").unwrap();
+        assert_eq!(db.get_mistakes("default", None).unwrap().len(), 2);
+        assert!(db.get_archived_mistakes("default").unwrap().is_empty());
+    }
+
+    /// 测试 20: 自定义复习按标签、错误次数筛选并去重
+    #[test]
+    fn test_build_custom_session() {
+        let mut db = create_test_db();
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        let other_id = db.create_article("另一篇", "apple pie").unwrap();
+        db.save_segments(other_id, "word", &["Apple".to_string(), "pie".to_string()]).unwrap();
+        db.set_article_tags(article_id, &["fruit".to_string()]).unwrap();
+        db.set_article_tags(other_id, &["fruit".to_string(), "food".to_string()]).unwrap();
+        assert_eq!(db.get_all_tags().unwrap(), vec!["food".to_string(), "fruit".to_string()]);
+
+        let mut filters = crate::models::CustomSessionFilters {
+            user_name: "default".to_string(),
+            article_ids: None,
+            tags: Some(vec!["fruit".to_string()]),
+            segment_type: Some("word".to_string()),
+            min_mastery: None,
+            max_mastery: None,
+            min_error_count: None,
+            last_seen_after: None,
+            last_seen_before: None,
+            limit: None,
+            order_mode: None,
+            seed: None,
+            language: None,
+        };
+        let session = db.build_custom_session(&filters).unwrap();
+        // 5 个单词 + pie，"Apple" 与 "apple" 去重
+        assert_eq!(session.words.len(), 6);
+        assert_eq!(session.new_words_count, 6);
+
+        db.add_mistake("default", seg1, "apple", "word").unwrap();
+        filters.min_error_count = Some(1);
+        let session = db.build_custom_session(&filters).unwrap();
+        assert_eq!(session.words.len(), 1);
+        assert_eq!(session.words[0].segment_id, seg1);
+    }
+
+    /// 测试 21: 打乱顺序的种子随进度保存，相同种子复现相同顺序
+    #[test]
+    fn test_seeded_order_replay() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+
+        let mut first = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap().words;
+        let seed = crate::ordering::apply_order(&mut first, Some("shuffled"), None).unwrap().unwrap();
+        db.save_progress("default", article_id, "word", 2, "[]", 2, 0, Some("shuffled"), Some(seed)).unwrap();
+
+        let progress = db.get_progress("default", article_id, "word").unwrap().unwrap();
+        assert_eq!(progress.order_mode.as_deref(), Some("shuffled"));
+        assert_eq!(progress.order_seed, Some(seed));
+
+        let mut replay = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap().words;
+        crate::ordering::apply_order(&mut replay, progress.order_mode.as_deref(), progress.order_seed).unwrap();
+        let ids = |words: &[crate::models::ScheduledWord]| words.iter().map(|w| w.segment_id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&replay));
+    }
+
+    /// 测试 22: 练习会话按键计数与答题推进
+    #[test]
+    fn test_practice_session_flow() {
+        let mut db = create_test_db();
+        let (article_id, seg1, seg2) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![
+                crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() },
+                crate::models::SessionItem { segment_id: seg2, content: "banana".to_string() },
+            ],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        }).unwrap();
+
+        db.record_session_typing(session_id, "apx", 3, 2).unwrap();
+        assert_eq!(db.record_session_answer(session_id, false, 2).unwrap(), 0);
+        assert_eq!(db.record_session_answer(session_id, true, 2).unwrap(), 1);
+        assert_eq!(db.record_session_answer(session_id, true, 2).unwrap(), 2);
+
+        let session = db.get_practice_session(session_id).unwrap().unwrap();
+        assert_eq!(session.status, "completed");
+        assert_eq!((session.keystrokes, session.correct_keystrokes), (3, 2));
+        assert_eq!((session.correct_count, session.incorrect_count), (2, 1));
+        assert_eq!(session.typed, "");
+    }
+
+    /// 测试 23: 会话检查点写入练习进度，并可恢复最近的会话
+    #[test]
+    fn test_session_checkpoint_and_recover() {
+        let mut db = create_test_db();
+        let (article_id, seg1, seg2) = setup_test_data(&mut db);
+        let request = |seed: u64| crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![
+                crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() },
+                crate::models::SessionItem { segment_id: seg2, content: "banana".to_string() },
+            ],
+            order_mode: Some("shuffled".to_string()),
+            order_seed: Some(seed),
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        };
+        // 更早放弃的会话不覆盖最近会话的进度
+        let abandoned = db.create_practice_session(&request(7)).unwrap();
+        db.conn.execute("UPDATE practice_sessions SET updated_at = '2000-01-01 00:00:00' WHERE id = ?", [abandoned]).unwrap();
+        let session_id = db.create_practice_session(&request(42)).unwrap();
+        db.record_session_answer(session_id, true, 2).unwrap();
+
+        assert_eq!(db.checkpoint_active_sessions().unwrap(), 1);
+        let progress = db.get_progress("default", article_id, "word").unwrap().unwrap();
+        assert_eq!(progress.current_index, 1);
+        assert_eq!(progress.words_list, r#"["apple","banana"]"#);
+        assert_eq!(progress.order_seed, Some(42));
+
+        let recovered = db.get_last_active_session("default").unwrap().unwrap();
+        assert_eq!(recovered.id, session_id);
+        assert!(db.get_last_active_session("other").unwrap().is_none());
+    }
+
+    /// 测试 24: 与其他本地用户的匿名百分位对比
+    #[test]
+    fn test_user_statistics_cohort_comparison() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+        db.save_practice_history("alice", article_id, "word", 9, 1, 60).unwrap();
+        db.save_practice_history("bob", article_id, "word", 5, 5, 60).unwrap();
+        db.save_practice_history("carol", article_id, "word", 9, 1, 30).unwrap();
+
+        let stats = db.get_user_statistics("alice", true).unwrap();
+        let comparison = stats.comparison.unwrap();
+        assert_eq!(comparison.cohort_size, 2);
+        // 正确率：高于 bob，与 carol 持平
+        assert!((comparison.accuracy_percentile - 75.0).abs() < 1e-9);
+        // WPM：10 vs bob 10、carol 20
+        assert!((comparison.wpm_percentile - 25.0).abs() < 1e-9);
+
+        assert!(db.get_user_statistics("alice", false).unwrap().comparison.is_none());
+        assert!(db.get_user_statistics("nobody", true).unwrap().comparison.is_none());
+    }
+
+    /// 测试 25: 词汇量增长曲线按首次达到掌握等级统计
+    #[test]
+    fn test_vocabulary_growth() {
+        let mut db = create_test_db();
+        let (_, seg1, seg2) = setup_test_data(&mut db);
+        for _ in 0..3 {
+            db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        }
+        // 掉级后再升回去不重复计数
+        db.update_word_mastery("default", seg1, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        db.update_word_mastery("default", seg2, "banana", "word", true).unwrap();
+
+        let growth = db.get_vocabulary_growth("default").unwrap();
+        assert_eq!(growth.len(), 1);
+        assert_eq!(growth[0].new_words, 1);
+        assert_eq!(growth[0].total_words, 1);
+        let week_start = chrono::NaiveDate::parse_from_str(&growth[0].week_start, "%Y-%m-%d").unwrap();
+        assert_eq!(chrono::Datelike::weekday(&week_start), chrono::Weekday::Mon);
+        assert!(db.get_vocabulary_growth("other").unwrap().is_empty());
+    }
+
+    /// 测试 26: 熟练度事件只追加，并可检测遗忘
+    #[test]
+    fn test_mastery_events_and_relapse() {
+        let mut db = create_test_db();
+        let (_, seg1, seg2) = setup_test_data(&mut db);
+        for _ in 0..3 {
+            db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        }
+        db.update_word_mastery("default", seg1, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", seg2, "banana", "word", false).unwrap();
+
+        let events = db.get_mastery_events("default", Some(seg1), 10).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!((events[0].old_level, events[0].new_level, events[0].correct), (Some(3), 2, Some(false)));
+        assert_eq!(events[3].old_level, None);
+        // 答错的新词也记录一条（等级不变）
+        assert_eq!(db.get_mastery_events("default", None, 10).unwrap().len(), 5);
+
+        let relapsed = db.get_relapsed_words("default").unwrap();
+        assert_eq!(relapsed.len(), 1);
+        assert_eq!(relapsed[0].segment_id, seg1);
+
+        // 重新掌握后不再算遗忘
+        db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        assert!(db.get_relapsed_words("default").unwrap().is_empty());
+
+        assert!(db.conn.execute("DELETE FROM mastery_events", []).is_err());
+        assert!(db.conn.execute("UPDATE mastery_events SET new_level = 5", []).is_err());
+
+        // 只在建表时为已有熟练度补录一次
+        let event_count = |db: &DatabaseManager| -> i32 { db.conn.query_row("SELECT COUNT(*) FROM mastery_events", [], |row| row.get(0)).unwrap() };
+        let before = event_count(&db);
+        db.initialize_schema().unwrap();
+        assert_eq!(event_count(&db), before);
+        db.conn.execute("DROP TABLE mastery_events", []).unwrap();
+        db.initialize_schema().unwrap();
+        assert_eq!(event_count(&db), 1);
+        db.initialize_schema().unwrap();
+        assert_eq!(event_count(&db), 1);
+    }
+
+    /// 测试 27: 顽固词检测与短间隔加强复习
+    #[test]
+    fn test_leech_words() {
+        let mut db = create_test_db();
+        let (article_id, seg1, seg2) = setup_test_data(&mut db);
+        // 两次从 3 级跌回 1 级
+        for round in 0..2 {
+            for _ in 0..(3 - round) {
+                db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+            }
+            for _ in 0..2 {
+                db.update_word_mastery("default", seg1, "apple", "word", false).unwrap();
+            }
         }
 
-        log::info!("WIDA questions seeded successfully");
-        Ok(())
+        // 只遗忘一次、在 2 级和 1 级之间反复的词不是顽固词
+        for correct in [true, true, true, false, false, true, false, true, false] {
+            db.update_word_mastery("default", seg2, "banana", "word", correct).unwrap();
+        }
+
+        let leeches = db.get_leech_words("default").unwrap();
+        assert_eq!(leeches.len(), 1);
+        assert_eq!(leeches[0].segment_id, seg1);
+        assert_eq!(leeches[0].relapse_count, 2);
+        assert_eq!(db.leech_segment_ids("default").unwrap(), std::collections::HashSet::from([seg1]));
+        assert!(db.leech_segment_ids("nobody").unwrap().is_empty());
+        assert_eq!(leeches[0].mastery_level, 1);
+
+        // 答对后的间隔被压缩
+        for _ in 0..3 {
+            db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        }
+        let mastery = db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        assert_eq!(mastery.interval_days, LEECH_MAX_INTERVAL_DAYS);
+
+        let scheduled = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap();
+        assert!(scheduled.words.iter().all(|w| w.is_leech == (w.segment_id == seg1)));
     }
-}
 
-#[derive(Debug)]
-struct ListeningQuestionData {
-    id: i64,
-    grade_level: String,
-    domain: String,
-    difficulty: i32,
-    audio_text: String,
-    image_url: Option<String>,
-    question_text: String,
-    options: Vec<String>,
-    correct_answer: i32,
-    explanation: Option<String>,
-}
+    /// 测试 29: 每日新词上限与顺延
+    #[test]
+    fn test_new_word_daily_cap() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+        db.set_new_word_pacing("default", Some(1), false).unwrap();
+
+        let first = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap();
+        assert_eq!(first.new_words_count, 1);
+        // 同一天再次获取，已发放的新词仍可练习，但不再发放新的
+        let again = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap();
+        assert_eq!(again.words[0].segment_id, first.words[0].segment_id);
+        assert_eq!(again.new_words_count, 1);
+
+        let pacing = db.get_new_word_pacing("default").unwrap();
+        assert_eq!((pacing.served_today, pacing.remaining_today), (1, Some(0)));
+
+        // 顺延：昨天一个都没用，今天名额翻倍（已用 1 个，剩 1 个）
+        db.set_new_word_pacing("default", Some(1), true).unwrap();
+        assert_eq!(db.get_new_word_pacing("default").unwrap().remaining_today, Some(1));
+        assert_eq!(db.get_scheduled_words("default", article_id, "word", 0, false).unwrap().new_words_count, 2);
+
+        db.set_new_word_pacing("default", None, false).unwrap();
+        assert_eq!(db.get_scheduled_words("default", article_id, "word", 0, false).unwrap().new_words_count, 5);
+        assert!(db.set_new_word_pacing("default", Some(-1), false).is_err());
+    }
 
-#[derive(Debug)]
-struct ReadingQuestionData {
-    id: i64,
-    grade_level: String,
-    domain: String,
-    difficulty: i32,
-    passage: String,
-    question_text: String,
-    question_type: String,
-    options: Vec<String>,
-    correct_answer: i32,
-    explanation: Option<String>,
-}
+    /// 测试 30: 导航栏角标计数
+    #[test]
+    fn test_due_counts() {
+        let mut db = create_test_db();
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        // 答错后立即到期
+        db.update_word_mastery("default", seg1, "apple", "word", false).unwrap();
+        db.create_assignment(&crate::models::CreateAssignmentRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            title: "作业".to_string(),
+            due_date: None,
+            check_options: None,
+        }).unwrap();
+
+        let counts = db.get_due_counts("default").unwrap();
+        assert_eq!(counts.due_words, 1);
+        assert_eq!(counts.pending_assignments, 1);
+        assert_eq!(counts.unfinished_wida_sessions, 0);
+        assert_eq!(db.get_due_counts("other").unwrap().due_words, 0);
+    }
 
-#[derive(Debug)]
-struct SpeakingQuestionData {
-    id: i64,
-    grade_level: String,
-    domain: String,
-    difficulty: i32,
-    prompt_type: String,
-    prompt_text: String,
-    image_url: Option<String>,
-    audio_text: Option<String>,
-    sample_answer: String,
-    rubric: Vec<String>,
-}
+    /// 测试 31: 目标会话提前结束并把目标写入练习历史
+    #[test]
+    fn test_goal_session_history() {
+        let mut db = create_test_db();
+        let (article_id, seg1, seg2) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![
+                crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() },
+                crate::models::SessionItem { segment_id: seg2, content: "banana".to_string() },
+            ],
+            order_mode: None,
+            order_seed: None,
+            goal: Some("until_1_correct".to_string()),
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        }).unwrap();
+        db.record_session_answer(session_id, true, 2).unwrap();
+        db.finish_practice_session(session_id, Some(true)).unwrap();
+
+        let session = db.get_practice_session(session_id).unwrap().unwrap();
+        assert_eq!(session.status, "completed");
+        assert_eq!(session.goal.as_deref(), Some("until_1_correct"));
+
+        let history = db.get_practice_history("default", 1).unwrap();
+        assert_eq!(history[0].goal.as_deref(), Some("until_1_correct"));
+        assert_eq!(history[0].goal_met, Some(true));
+        assert_eq!(history[0].correct_count, 1);
+
+        // 清空到期复习的目标只看会话中的题目
+        let cherry = db.get_segments(article_id, "word").unwrap().into_iter().find(|s| s.content == "cherry").unwrap().id;
+        for segment_id in [seg1, cherry] {
+            db.conn.execute(
+                "INSERT INTO word_mastery (user_name, segment_id, segment_content, segment_type, next_review_at)
+                 VALUES ('default', ?, 'word', 'word', '2000-01-01 00:00:00')",
+                [segment_id],
+            ).unwrap();
+        }
+        assert_eq!(db.count_due_segments("default", &[seg1, seg2]).unwrap(), 1);
+        assert_eq!(db.count_due_segments("default", &[seg2]).unwrap(), 0);
+    }
 
-#[derive(Debug)]
-struct WritingQuestionData {
-    id: i64,
-    grade_level: String,
-    domain: String,
-    difficulty: i32,
-    task_type: String,
-    prompt: String,
-    image_url: Option<String>,
-    word_limit_min: i32,
-    word_limit_max: i32,
-    rubric: Vec<String>,
-    sample_answer: Option<String>,
-}
+    /// 测试 32: 创建和修改文章时计算可读性指标
+    #[test]
+    fn test_article_readability() {
+        let db = create_test_db();
+        let id = db.create_article("Cat", "The cat sat. The cat ran!").unwrap();
+        let article = db.get_article(id).unwrap().unwrap();
+        assert_eq!(article.avg_sentence_length, Some(3.0));
+        assert_eq!(article.type_token_ratio, Some(4.0 / 6.0));
+        assert_eq!(article.reading_grade, Some(0.0));
+
+        let long = "Understanding photosynthesis requires considerable scientific vocabulary and patience";
+        db.update_article(id, None, Some(long)).unwrap();
+        let article = db.get_article(id).unwrap().unwrap();
+        assert!(article.reading_grade.unwrap() > 12.0);
+        assert_eq!(article.avg_sentence_length, Some(8.0));
+
+        let chinese = db.create_article("中文", "这是一篇中文文章。").unwrap();
+        assert!(db.get_article(chinese).unwrap().unwrap().reading_grade.is_none());
+    }
 
-fn get_default_listening_questions() -> Vec<ListeningQuestionData> {
-    vec![
-        ListeningQuestionData {
-            id: 1,
+    /// 测试 33: TF-IDF 优先选出本文特有的单词
+    #[test]
+    fn test_focus_word_suggestions() {
+        let db = create_test_db();
+        db.create_article("A", "The planet orbits the star. Every planet has gravity and gravity pulls.").unwrap();
+        db.create_article("B", "Gravity keeps us on the ground. The ground is solid.").unwrap();
+        db.create_article("C", "Gravity is everywhere in the universe.").unwrap();
+
+        let corpus = db.get_all_article_contents().unwrap();
+        let words = crate::text::tf_idf_keywords(&corpus[0], &corpus, 10);
+        // gravity 与 planet 出现次数相同，但每篇都有，planet 更有区分度
+        assert_eq!(words[0].0, "planet");
+        assert_eq!(words[0].2, 2);
+        let gravity = words.iter().find(|w| w.0 == "gravity").unwrap();
+        assert_eq!(gravity.2, 2);
+        assert!(gravity.1 < words[0].1);
+        assert_eq!(crate::text::tf_idf_keywords(&corpus[0], &corpus, 1).len(), 1);
+    }
+
+    /// 测试 34: 根据文章生成的题目记录来源文章
+    #[test]
+    fn test_generated_question_source_article() {
+        let db = create_test_db();
+        let article_id = db.create_article("Frogs", "Frogs live near water.").unwrap();
+        let question = crate::commands::wida::GeneratedReadingQuestion {
             grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            audio_text: "Hello, my name is Sarah. I am six years old. What is your name?".to_string(),
-            image_url: None,
-            question_text: "What is the speaker's name?".to_string(),
-            options: vec!["Sarah".to_string(), "Emma".to_string(), "Lisa".to_string(), "Anna".to_string()],
+            domain: "article".to_string(),
+            difficulty: 3,
+            passage: "Frogs live near water.".to_string(),
+            question_text: "Where do frogs live?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Near water".to_string(), "In trees".to_string()],
             correct_answer: 0,
-            explanation: Some("The speaker introduces herself as Sarah at the beginning.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 2,
-            grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            audio_text: "Look at the picture. There are three apples on the table. Two apples are red and one apple is green.".to_string(),
-            image_url: None,
-            question_text: "How many apples are there in total?".to_string(),
-            options: vec!["Two".to_string(), "Three".to_string(), "Four".to_string(), "Five".to_string()],
-            correct_answer: 1,
-            explanation: Some("The speaker says 'There are three apples on the table.'".to_string()),
-        },
-        ListeningQuestionData {
-            id: 3,
-            grade_level: "grade_1_2".to_string(),
-            domain: "mathematics".to_string(),
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: Some(article_id),
+        };
+        assert_eq!(db.save_reading_questions(&[question]).unwrap(), 1);
+
+        let source: Option<i64> = db.conn.query_row(
+            "SELECT source_article_id FROM wida_reading_questions WHERE question_text = 'Where do frogs live?'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(source, Some(article_id));
+    }
+
+    /// 测试 35: 自定义提示词模板覆盖默认模板
+    #[test]
+    fn test_prompt_template_override() {
+        let db = create_test_db();
+        assert!(db.get_prompt_template("reading").unwrap().is_none());
+        let default = crate::prompts::default_template("reading").unwrap();
+        let vars = [("count", "3".to_string()), ("grade_level", "grade_1_2".to_string())];
+        assert!(crate::prompts::render(default, &vars).contains("请生成 3 道WIDA英语阅读测试题目"));
+
+        db.set_prompt_template("reading", Some("Write {count} questions for {grade_level} in {language}.")).unwrap();
+        let custom = db.get_prompt_template("reading").unwrap().unwrap();
+        // 未提供的占位符原样保留
+        assert_eq!(crate::prompts::render(&custom, &vars), "Write 3 questions for grade_1_2 in {language}.");
+
+        db.set_prompt_template("reading", None).unwrap();
+        assert!(db.get_prompt_template("reading").unwrap().is_none());
+    }
+
+    /// 测试 36: AI 用量按月汇总
+    #[test]
+    fn test_ai_usage_summary() {
+        let db = create_test_db();
+        db.record_ai_usage("api.openai.com", "gpt-4o-mini", "generate_reading", 1000, 500, Some(0.00045)).unwrap();
+        db.record_ai_usage("api.openai.com", "gpt-4o-mini", "generate_reading", 2000, 500, Some(0.0006)).unwrap();
+        db.record_ai_usage("localhost", "llama3", "generate_writing", 300, 100, None).unwrap();
+
+        let month = chrono::Local::now().format("%Y-%m").to_string();
+        let summary = db.get_ai_usage_summary(&month).unwrap();
+        assert_eq!(summary.total_calls, 3);
+        assert_eq!((summary.prompt_tokens, summary.completion_tokens), (3300, 1100));
+        assert!((summary.estimated_cost - 0.00105).abs() < 1e-12);
+        assert_eq!(summary.breakdown.len(), 2);
+        assert_eq!(summary.breakdown[0].calls, 2);
+        assert_eq!(summary.breakdown[1].estimated_cost, None);
+
+        assert_eq!(db.get_ai_usage_summary("2000-01").unwrap().total_calls, 0);
+    }
+
+    /// 测试 37: 离线出题生成完形填空和理解题
+    #[test]
+    fn test_offline_question_generation() {
+        use crate::prompts::offline;
+        let db = create_test_db();
+        let content = "Plants need sunlight to grow. Their leaves capture energy and turn it into food. \
+            Farmers water their crops every morning so the roots stay healthy and strong.\n\n\
+            Volcanoes form where melted rock rises through cracks in the crust. When pressure builds, \
+            the mountain erupts and sends lava flowing down its slopes toward nearby villages.\n\n\
+            Penguins live in cold places near the South Pole. They cannot fly, but they swim quickly \
+            through icy water to catch fish and escape from hungry seals.";
+        let article_id = db.create_article("Nature", content).unwrap();
+        let passages = offline::split_passages(content, Some(article_id));
+        assert_eq!(passages.len(), 3);
+
+        let request = crate::commands::wida::GenerateQuestionsRequest {
+            test_type: "reading".to_string(),
+            grade_level: "grade_3_5".to_string(),
+            domain: "science".to_string(),
             difficulty: 2,
-            audio_text: "Tom has five pencils. He gives two pencils to his friend. How many pencils does Tom have now?".to_string(),
-            image_url: None,
-            question_text: "How many pencils does Tom have after giving away two?".to_string(),
-            options: vec!["Two".to_string(), "Three".to_string(), "Four".to_string(), "Five".to_string()],
-            correct_answer: 1,
-            explanation: Some("Five minus two equals three.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 4,
+            count: 10,
+            api_url: String::new(),
+            api_key: String::new(),
+            model: String::new(),
+            locale: None,
+        };
+        let questions = offline::reading_questions(&passages, &request, 7);
+        // 每段最多两道（完形填空 + 理解题）
+        assert_eq!(questions.len(), 6);
+        for q in &questions {
+            assert_eq!(q.options.len(), 4);
+            assert_eq!(q.source_article_id, Some(article_id));
+            let answer = &q.options[q.correct_answer as usize];
+            if q.passage.contains(offline::BLANK) {
+                assert!(content.to_lowercase().contains(answer.as_str()));
+            } else {
+                assert!(q.passage.contains(answer.as_str()));
+            }
+        }
+        assert_eq!(db.save_reading_questions(&questions).unwrap(), 6);
+
+        let listening = offline::listening_questions(&passages, &request, 7);
+        assert!(listening.iter().all(|q| q.options.len() == 4));
+        assert_eq!(offline::writing_questions(&passages, &request, 7).len(), 3);
+    }
+
+    /// 测试 38: 按测试蓝图组卷
+    #[test]
+    fn test_wida_blueprint() {
+        use crate::models::BlueprintSection;
+        let db = create_test_db();
+        let mut questions = Vec::new();
+        for (domain, difficulty) in [("social", 1), ("social", 2), ("science", 3), ("science", 4), ("science", 3), ("math", 6)] {
+            questions.push(crate::commands::wida::GeneratedReadingQuestion {
+                grade_level: "grade_6_8".to_string(),
+                domain: domain.to_string(),
+                difficulty,
+                passage: "A short passage.".to_string(),
+                question_text: format!("{} {}", domain, difficulty),
+                question_type: "multiple_choice".to_string(),
+                options: vec!["a".to_string(), "b".to_string()],
+                correct_answer: 0,
+                correct_answers: Vec::new(),
+                answer_pattern: None,
+                explanation: None,
+                source_article_id: None,
+            });
+        }
+        db.save_reading_questions(&questions).unwrap();
+
+        let section = |domain: Option<&str>, min_difficulty, max_difficulty, count| BlueprintSection {
+            domain: domain.map(str::to_string),
+            min_difficulty,
+            max_difficulty,
+            count,
+        };
+        assert!(db.create_wida_blueprint("bad", "reading", &[section(None, 4, 2, 1)]).is_err());
+        let id = db
+            .create_wida_blueprint("mix", "reading", &[section(Some("social"), 1, 2, 2), section(Some("science"), 3, 4, 2)])
+            .unwrap();
+        // 难度 5-6 只有一道题，且不会与前面的组重复
+        assert!(db
+            .update_wida_blueprint(id, "mix", &[section(Some("social"), 1, 2, 2), section(Some("science"), 3, 4, 2), section(None, 5, 6, 3)])
+            .unwrap());
+        assert_eq!(db.get_wida_blueprints(Some("reading")).unwrap()[0].sections.len(), 3);
+
+        let mut request = crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "reading".to_string(),
+            grade_level: "grade_6_8".to_string(),
+            domain: None,
+            question_count: 10,
+            blueprint_id: Some(id),
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        };
+        let session = db.start_wida_test(&request).unwrap();
+        assert_eq!(session.total_questions, 5);
+        let difficulties: Vec<i64> = db.conn.prepare(
+            "SELECT q.difficulty FROM wida_test_sessions s, json_each(s.question_ids) j
+             JOIN wida_reading_questions q ON q.id = j.value WHERE s.id = ?1 ORDER BY j.key",
+        ).unwrap().query_map([session.id], |row| row.get(0)).unwrap().collect::<SqliteResult<Vec<_>>>().unwrap();
+        assert_eq!(difficulties.len(), 5);
+        assert!(difficulties[..2].iter().all(|d| *d <= 2));
+        assert!(difficulties[2..4].iter().all(|d| (3..=4).contains(d)));
+        assert_eq!(difficulties[4], 6);
+
+        request.test_type = "listening".to_string();
+        assert!(db.start_wida_test(&request).is_err());
+        db.delete_wida_blueprint(id).unwrap();
+        assert!(db.get_wida_blueprints(None).unwrap().is_empty());
+    }
+
+    /// 测试 39: 选项按会话打乱，判分按原选项下标
+    #[test]
+    fn test_wida_option_shuffle() {
+        let db = create_test_db();
+        let options: Vec<String> = ["apple", "banana", "cherry", "grape", "lemon", "mango"].iter().map(|o| o.to_string()).collect();
+        db.save_reading_questions(&[crate::commands::wida::GeneratedReadingQuestion {
             grade_level: "grade_1_2".to_string(),
             domain: "science".to_string(),
-            difficulty: 2,
-            audio_text: "Plants need water and sunlight to grow. Without water, plants will dry up and die.".to_string(),
-            image_url: None,
-            question_text: "What do plants need to grow according to the audio?".to_string(),
-            options: vec!["Only water".to_string(), "Only sunlight".to_string(), "Water and sunlight".to_string(), "Nothing".to_string()],
+            difficulty: 1,
+            passage: "Cherries are red.".to_string(),
+            question_text: "Which fruit is red?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: options.clone(),
             correct_answer: 2,
-            explanation: Some("The speaker mentions that plants need both water and sunlight.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 5,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "reading".to_string(),
+            grade_level: "grade_1_2".to_string(),
+            domain: None,
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+
+        let first = db.get_wida_test_questions(session.id).unwrap();
+        // 再次取题时顺序不变
+        assert_eq!(db.get_wida_test_questions(session.id).unwrap(), first);
+        let shown: Vec<String> = serde_json::from_value(first[0]["options"].clone()).unwrap();
+        let mut sorted = shown.clone();
+        sorted.sort();
+        assert_eq!(sorted, options);
+        let displayed_correct = shown.iter().position(|o| o == "cherry").unwrap();
+        assert_eq!(first[0]["correct_answer"], displayed_correct as i64);
+
+        db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+            session_id: session.id,
+            question_id: first[0]["id"].as_i64().unwrap(),
+            answer: displayed_correct.to_string(),
+            time_spent_seconds: 5,
+        }).unwrap();
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!(report.correct_count, 1);
+    }
+
+    /// 测试 40: 多选题和简答题按比例得分
+    #[test]
+    fn test_wida_partial_credit() {
+        let db = create_test_db();
+        let question = |text: &str, question_type: &str, options: &[&str], correct_answers: Vec<i32>, pattern: Option<&str>| {
+            crate::commands::wida::GeneratedReadingQuestion {
+                grade_level: "grade_9_12".to_string(),
+                domain: "science".to_string(),
+                difficulty: 4,
+                passage: "Plants make food from sunlight. The Moon orbits the Earth.".to_string(),
+                question_text: text.to_string(),
+                question_type: question_type.to_string(),
+                options: options.iter().map(|o| o.to_string()).collect(),
+                correct_answer: 0,
+                correct_answers,
+                answer_pattern: pattern.map(str::to_string),
+                explanation: None,
+                source_article_id: None,
+            }
+        };
+        db.save_reading_questions(&[
+            question("Which are true?", "multi_select", &["Plants make food", "Rocks grow", "The Moon orbits", "Fish fly"], vec![0, 2], None),
+            question("What do plants need?", "short_answer", &[], Vec::new(), Some("sunlight, water")),
+            question("What orbits the Earth?", "short_answer", &[], Vec::new(), Some("/^(the )?moon$/")),
+        ]).unwrap();
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "reading".to_string(),
+            grade_level: "grade_9_12".to_string(),
+            domain: None,
+            question_count: 3,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+
+        let questions = db.get_wida_test_questions(session.id).unwrap();
+        for q in questions.as_array().unwrap() {
+            let answer = match q["question_text"].as_str().unwrap() {
+                // 只选了一个正确项
+                "Which are true?" => {
+                    let options: Vec<String> = serde_json::from_value(q["options"].clone()).unwrap();
+                    options.iter().position(|o| o == "Plants make food").unwrap().to_string()
+                }
+                "What do plants need?" => "They need sunlight.".to_string(),
+                _ => "The Moon".to_string(),
+            };
+            db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+                session_id: session.id,
+                question_id: q["id"].as_i64().unwrap(),
+                answer,
+                time_spent_seconds: 10,
+            }).unwrap();
+        }
+
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!(report.correct_count, 1);
+        assert!((report.accuracy - 200.0 / 3.0).abs() < 1e-9);
+        for detail in &report.details {
+            match detail.question_text.as_str() {
+                "Which are true?" => {
+                    assert_eq!(detail.credit, 0.5);
+                    assert_eq!(detail.correct_answer, "Plants make food, The Moon orbits");
+                }
+                "What do plants need?" => assert_eq!(detail.credit, 0.5),
+                _ => assert!(detail.is_correct),
+            }
+        }
+    }
+
+    /// 测试 41: 题目音频播放次数限制并记入答案
+    #[test]
+    fn test_wida_audio_plays() {
+        let db = create_test_db();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
             grade_level: "grade_3_5".to_string(),
-            domain: "social_instructional".to_string(),
+            domain: "science".to_string(),
             difficulty: 2,
-            audio_text: "Good morning, students! Today we are going to learn about animals. First, let's talk about mammals. Mammals are animals that have hair or fur and feed their babies with milk.".to_string(),
+            audio_text: "Bees make honey.".to_string(),
             image_url: None,
-            question_text: "What characteristic is mentioned about mammals?".to_string(),
-            options: vec!["They have feathers".to_string(), "They have scales".to_string(), "They have hair or fur".to_string(), "They lay eggs".to_string()],
-            correct_answer: 2,
-            explanation: Some("The speaker says mammals have hair or fur.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 6,
+            question_text: "What do bees make?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Honey".to_string(), "Milk".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "listening".to_string(),
             grade_level: "grade_3_5".to_string(),
-            domain: "mathematics".to_string(),
-            difficulty: 3,
-            audio_text: "A rectangle has four sides. The two longer sides are each 8 centimeters. The two shorter sides are each 5 centimeters. What is the perimeter of the rectangle?".to_string(),
-            image_url: None,
-            question_text: "What is the perimeter of the rectangle?".to_string(),
-            options: vec!["13 cm".to_string(), "21 cm".to_string(), "26 cm".to_string(), "40 cm".to_string()],
-            correct_answer: 2,
-            explanation: Some("Perimeter = 8 + 8 + 5 + 5 = 26 centimeters.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 7,
+            domain: None,
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+        let question_id: i64 = db.conn.query_row(
+            "SELECT id FROM wida_listening_questions WHERE question_text = 'What do bees make?'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+
+        assert_eq!(db.get_wida_question_audio(session.id, question_id).unwrap().as_deref(), Some("Bees make honey."));
+        assert_eq!(db.get_wida_question_audio(session.id, question_id + 1000).unwrap(), None);
+        // 未指定上限时默认两次
+        assert_eq!(db.record_wida_audio_play(session.id, question_id).unwrap(), Some(1));
+        assert_eq!(db.record_wida_audio_play(session.id, question_id).unwrap(), Some(0));
+        assert_eq!(db.record_wida_audio_play(session.id, question_id).unwrap(), None);
+
+        db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+            session_id: session.id,
+            question_id,
+            answer: "0".to_string(),
+            time_spent_seconds: 3,
+        }).unwrap();
+        let session = db.get_wida_test_session(session.id).unwrap().unwrap();
+        let answers: Vec<crate::models::WidaTestAnswer> = serde_json::from_str(&session.answers).unwrap();
+        assert_eq!(answers[0].replay_count, 2);
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!(report.details[0].replay_count, 2);
+
+        let limited = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "listening".to_string(),
             grade_level: "grade_3_5".to_string(),
+            domain: None,
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: Some(1),
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+        assert_eq!(db.record_wida_audio_play(limited.id, question_id).unwrap(), Some(0));
+        assert_eq!(db.record_wida_audio_play(limited.id, question_id).unwrap(), None);
+    }
+
+    /// 测试 42: 示例题排在最前且不计分，不会被正式抽题选中
+    #[test]
+    fn test_wida_sample_items() {
+        let db = create_test_db();
+        let question = |text: &str| crate::commands::wida::GeneratedReadingQuestion {
+            grade_level: "grade_1_2".to_string(),
             domain: "science".to_string(),
-            difficulty: 3,
-            audio_text: "The water cycle is the process of water moving from the Earth to the sky and back again. First, the sun heats up water in lakes and oceans. Then the water becomes vapor and rises into the air. This is called evaporation.".to_string(),
-            image_url: None,
-            question_text: "What happens during evaporation?".to_string(),
-            options: vec!["Water falls as rain".to_string(), "Water becomes vapor and rises".to_string(), "Water freezes".to_string(), "Water flows in rivers".to_string()],
-            correct_answer: 1,
-            explanation: Some("During evaporation, water becomes vapor and rises into the air.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 8,
+            difficulty: 1,
+            passage: "Cats say meow.".to_string(),
+            question_text: text.to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Meow".to_string(), "Woof".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        };
+        db.save_reading_questions(&[question("Sample"), question("Real 1"), question("Real 2")]).unwrap();
+        let id_of = |text: &str| -> i64 {
+            db.conn.query_row("SELECT id FROM wida_reading_questions WHERE question_text = ?", [text], |row| row.get(0)).unwrap()
+        };
+        db.set_wida_question_sample("reading", id_of("Sample"), true).unwrap();
+        assert_eq!(db.get_wida_reading_questions("grade_1_2", None, None).unwrap().len(), 2);
+
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "reading".to_string(),
+            grade_level: "grade_1_2".to_string(),
+            domain: None,
+            question_count: 5,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+        assert_eq!(session.total_questions, 3);
+        let questions = db.get_wida_test_questions(session.id).unwrap();
+        assert_eq!(questions[0]["question_text"], "Sample");
+        assert_eq!(questions[0]["is_sample"], true);
+        assert_eq!(questions[1]["is_sample"], false);
+
+        // 示例题答错、正式题全对
+        for (idx, q) in questions.as_array().unwrap().iter().enumerate() {
+            let correct = q["correct_answer"].as_i64().unwrap();
+            db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+                session_id: session.id,
+                question_id: q["id"].as_i64().unwrap(),
+                answer: if idx == 0 { (1 - correct).to_string() } else { correct.to_string() },
+                time_spent_seconds: 2,
+            }).unwrap();
+        }
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!((report.correct_count, report.total_count), (2, 2));
+        assert_eq!(report.accuracy, 100.0);
+
+        assert!(db.get_wida_section_instructions("reading").unwrap().contains("practice"));
+        db.set_wida_section_instructions("reading", Some("Read carefully.")).unwrap();
+        assert_eq!(db.get_wida_section_instructions("reading").unwrap(), "Read carefully.");
+        db.set_wida_section_instructions("reading", None).unwrap();
+        assert!(db.set_wida_section_instructions("math", Some("x")).is_err());
+    }
+
+    /// 测试 43: 无障碍设置的默认值与校验
+    #[test]
+    fn test_accommodations() {
+        let db = create_test_db();
+        let defaults = db.get_accommodations("alice").unwrap();
+        assert_eq!((defaults.time_multiplier, defaults.max_tts_rate, defaults.batch_size), (1.0, None, None));
+
+        let mut accommodations = crate::models::Accommodations {
+            user_name: "alice".to_string(),
+            time_multiplier: 1.5,
+            max_tts_rate: Some(120),
+            text_scale: 1.25,
+            batch_size: Some(5),
+        };
+        db.set_accommodations(&accommodations).unwrap();
+        let saved = db.get_accommodations("alice").unwrap();
+        assert_eq!((saved.time_multiplier, saved.max_tts_rate, saved.text_scale, saved.batch_size), (1.5, Some(120), 1.25, Some(5)));
+        assert_eq!(crate::commands::tts::accommodated_rate(&db, Some("alice"), Some(200)).unwrap(), Some(120));
+        assert_eq!(crate::commands::tts::accommodated_rate(&db, Some("alice"), Some(90)).unwrap(), Some(90));
+        assert_eq!(crate::commands::tts::accommodated_rate(&db, None, Some(200)).unwrap(), Some(200));
+
+        accommodations.time_multiplier = 0.5;
+        assert!(db.set_accommodations(&accommodations).is_err());
+    }
+
+    /// 测试 44: 报告和提示语按语言本地化
+    #[test]
+    fn test_localized_report() {
+        let db = create_test_db();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
             grade_level: "grade_3_5".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 3,
-            audio_text: "Once upon a time, there was a little rabbit named Ruby. Ruby loved to hop through the green meadow. One sunny day, she found a beautiful blue flower.".to_string(),
-            image_url: None,
-            question_text: "What color was the flower Ruby found?".to_string(),
-            options: vec!["Red".to_string(), "Yellow".to_string(), "Blue".to_string(), "White".to_string()],
-            correct_answer: 2,
-            explanation: Some("The story says Ruby found a beautiful blue flower.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 9,
-            grade_level: "grade_6_8".to_string(),
-            domain: "social_studies".to_string(),
-            difficulty: 4,
-            audio_text: "The American Revolution began in 1775. The thirteen colonies wanted independence from Great Britain. They were unhappy about paying taxes without having representatives in the British government.".to_string(),
-            image_url: None,
-            question_text: "Why were the colonies unhappy with Great Britain?".to_string(),
-            options: vec!["They wanted more land".to_string(), "They had to pay taxes without representation".to_string(), "They wanted to join France".to_string(), "They disliked the British king".to_string()],
-            correct_answer: 1,
-            explanation: Some("The colonies were unhappy about paying taxes without representatives.".to_string()),
-        },
-        ListeningQuestionData {
-            id: 10,
-            grade_level: "grade_6_8".to_string(),
             domain: "science".to_string(),
-            difficulty: 4,
-            audio_text: "Photosynthesis is the process by which plants make their own food. During this process, plants use sunlight, carbon dioxide, and water to produce glucose and oxygen. This process mainly occurs in the leaves of plants.".to_string(),
+            difficulty: 2,
+            audio_text: "Plants need sunlight to grow.".to_string(),
             image_url: None,
-            question_text: "What is produced during photosynthesis?".to_string(),
-            options: vec!["Carbon dioxide and water".to_string(), "Sunlight and oxygen".to_string(), "Glucose and oxygen".to_string(), "Water and glucose only".to_string()],
-            correct_answer: 2,
-            explanation: Some("Photosynthesis produces glucose and oxygen.".to_string()),
-        },
-    ]
-}
+            question_text: "What do plants need?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Sunlight".to_string(), "Sand".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+
+        let mut names = Vec::new();
+        for locale in [None, Some("en-US".to_string()), Some("fr".to_string())] {
+            let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+                user_name: "default".to_string(),
+                test_type: "listening".to_string(),
+                grade_level: "grade_3_5".to_string(),
+                domain: None,
+                question_count: 1,
+                blueprint_id: None,
+                max_audio_plays: None,
+                sample_count: None,
+                tags: None,
+            }).unwrap();
+            let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale }).unwrap();
+            names.push(report.proficiency_level_name);
+        }
+        // 不支持的语言回退到中文
+        assert_eq!(names, vec!["起步", "Entering", "起步"]);
+
+        let vars = [("count", "3".to_string()), ("kind", "reading".to_string())];
+        assert_eq!(crate::i18n::text(Some("en"), "wida.generated", &vars), "Generated 3 reading questions");
+        assert_eq!(crate::i18n::text(Some("en"), "missing.key", &[]), "missing.key");
+        let en = crate::i18n::strings(Some("en"));
+        assert_eq!(en.len(), crate::i18n::strings(None).len());
+        assert_eq!(en["test_type.writing"], "writing");
+    }
+
+    /// 测试 45: 用户 PIN 的设置、校验与清除
+    #[test]
+    fn test_user_pin() {
+        let db = create_test_db();
+        assert!(!db.user_has_pin("alice").unwrap());
+        assert!(db.verify_user_pin("alice", "").unwrap());
+        assert!(db.set_user_pin("alice", None, Some("12a4")).is_err());
+
+        db.set_user_pin("alice", None, Some("1234")).unwrap();
+        assert!(db.user_has_pin("alice").unwrap());
+        assert!(db.get_known_users().unwrap().contains(&"alice".to_string()));
+        let stored: String = db.conn.query_row("SELECT pin_hash FROM users WHERE user_name = 'alice'", [], |row| row.get(0)).unwrap();
+        assert!(!stored.contains("1234"));
+        assert!(db.verify_user_pin("alice", "1234").unwrap());
+        assert!(!db.verify_user_pin("alice", "4321").unwrap());
+        assert!(db.require_user_pin("alice", None).is_err());
+
+        // 修改和清除都需要当前 PIN
+        assert!(db.set_user_pin("alice", Some("0000"), Some("5678")).is_err());
+        db.set_user_pin("alice", Some("1234"), Some("5678")).unwrap();
+        assert!(db.verify_user_pin("alice", "5678").unwrap());
+        db.set_user_pin("alice", Some("5678"), None).unwrap();
+        assert!(!db.user_has_pin("alice").unwrap());
+    }
+
+    /// 测试 46: 老师/管理员角色与受限命令的权限检查
+    #[test]
+    fn test_user_roles() {
+        let db = create_test_db();
+        let actor = |name: &str, pin: Option<&str>| crate::models::Actor {
+            user_name: name.to_string(),
+            pin: pin.map(str::to_string),
+        };
+        // 还没有老师或管理员时不限制
+        assert!(db.authorize(None).is_ok());
+        assert_eq!(db.get_user_role("kid").unwrap(), "student");
+        assert!(db.set_user_role(None, "kid", "owner").is_err());
+
+        db.set_user_role(None, "mom", "admin").unwrap();
+        db.set_user_pin("mom", None, Some("2468")).unwrap();
+        assert!(db.authorize(None).is_err());
+        assert!(db.authorize(Some(&actor("kid", None))).is_err());
+        assert!(db.authorize(Some(&actor("mom", Some("1111")))).is_err());
+        assert!(db.authorize(Some(&actor("mom", Some("2468")))).is_ok());
+
+        // 只有管理员能分配角色
+        assert!(db.set_user_role(Some(&actor("kid", None)), "kid", "teacher").is_err());
+        db.set_user_role(Some(&actor("mom", Some("2468"))), "teacher_li", "teacher").unwrap();
+        assert!(db.authorize(Some(&actor("teacher_li", None))).is_ok());
+        assert!(db.set_user_role(Some(&actor("teacher_li", None)), "kid", "admin").is_err());
+    }
+
+    /// 测试 47: 审计日志记录删除操作并可按类型筛选
+    #[test]
+    fn test_audit_log() {
+        let db = create_test_db();
+        db.save_reading_questions(&[crate::commands::wida::GeneratedReadingQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "science".to_string(),
+            difficulty: 2,
+            passage: "Bees make honey.".to_string(),
+            question_text: "What do bees make?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Honey".to_string(), "Milk".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let question_id = db.conn.last_insert_rowid();
+        assert!(db.delete_wida_question("reading", question_id).unwrap());
+        assert!(!db.delete_wida_question("reading", question_id).unwrap());
+        assert!(db.delete_wida_question("math", question_id).is_err());
+
+        db.record_audit(Some("mom"), "delete", "wida_question", question_id, Some("reading: Q")).unwrap();
+        db.record_audit(None, "delete", "article", 7, Some("Nature")).unwrap();
+
+        let entries = db.get_audit_log(None, 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].target_type, "article");
+        assert_eq!(entries[0].user_name, None);
+        let questions = db.get_audit_log(Some("wida_question"), 10).unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].user_name.as_deref(), Some("mom"));
+        assert_eq!(questions[0].target_id, question_id);
+    }
+
+    /// 测试 48: 按快照恢复删除的文章及其分词和熟练度
+    #[test]
+    fn test_restore_deleted_article() {
+        let mut db = create_test_db();
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        db.add_mistake("default", seg1, "apple", "word").unwrap();
+        db.set_article_tags(article_id, &["fruit".to_string()]).unwrap();
+        let segment_count = db.get_segments(article_id, "word").unwrap().len();
+
+        let snapshot = db.snapshot_article(article_id).unwrap().unwrap();
+        assert!(db.snapshot_article(article_id + 100).unwrap().is_none());
+        assert!(db.delete_article(article_id).unwrap());
+        assert!(db.get_article(article_id).unwrap().is_none());
+
+        db.restore_article(&snapshot).unwrap();
+        assert_eq!(db.get_article(article_id).unwrap().unwrap().title, snapshot.title);
+        assert_eq!(db.get_segments(article_id, "word").unwrap().len(), segment_count);
+        assert_eq!(db.get_article_tags(article_id).unwrap(), vec!["fruit".to_string()]);
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 1);
+        assert_eq!(db.get_mistakes("default", None).unwrap().len(), 1);
+    }
+
+    /// 测试 49: 单词配图按小写单词保存，替换时返回旧文件
+    #[test]
+    fn test_word_images() {
+        let db = create_test_db();
+        assert!(db.set_word_image("  ", "/tmp/a.png", "local", None).is_err());
+        assert_eq!(db.set_word_image("Apple", "/tmp/apple-1.png", "local", None).unwrap(), None);
+        assert_eq!(db.get_word_image("APPLE").unwrap().unwrap().file_path, "/tmp/apple-1.png");
+
+        let old = db.set_word_image("apple", "/tmp/apple-2.jpg", "api", Some("https://example.com/a.jpg")).unwrap();
+        assert_eq!(old.as_deref(), Some("/tmp/apple-1.png"));
+        let image = db.get_word_image("apple").unwrap().unwrap();
+        assert_eq!((image.source.as_str(), image.source_url.as_deref()), ("api", Some("https://example.com/a.jpg")));
+
+        let words = vec!["apple".to_string(), "Apple".to_string(), "pear".to_string()];
+        assert_eq!(db.get_word_images(&words).unwrap().len(), 1);
+        assert_eq!(db.delete_word_image("apple").unwrap().as_deref(), Some("/tmp/apple-2.jpg"));
+        assert!(db.get_word_image("apple").unwrap().is_none());
+    }
+
+    /// 测试 50: 朗读录音的每分钟单词数和流利度趋势
+    #[test]
+    fn test_reading_fluency() {
+        let db = create_test_db();
+        let article_id = db.create_article("Bees", "Bees make honey in the hive every summer day.").unwrap();
+        assert!(db.start_reading_recording("default", article_id + 1).is_err());
+
+        let first = db.start_reading_recording("default", article_id).unwrap();
+        let recording = db.complete_reading_recording(first, "/tmp/r1.webm", Some(6_000), None).unwrap();
+        assert_eq!(recording.word_count, 9);
+        assert!((recording.wpm.unwrap() - 90.0).abs() < 1e-9);
+        assert!(db.complete_reading_recording(first, "/tmp/r1.webm", Some(6_000), None).is_err());
+
+        let second = db.start_reading_recording("default", article_id).unwrap();
+        db.complete_reading_recording(second, "/tmp/r2.webm", Some(4_500), None).unwrap();
+        // 未完成的录音不计入趋势
+        db.start_reading_recording("default", article_id).unwrap();
+
+        let trend = db.get_fluency_trend("default", article_id).unwrap();
+        assert_eq!(trend.readings.len(), 2);
+        assert!((trend.latest_wpm.unwrap() - 120.0).abs() < 1e-9);
+        assert!((trend.best_wpm.unwrap() - 120.0).abs() < 1e-9);
+        assert!((trend.change_percent.unwrap() - 100.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(db.delete_reading_recording(first).unwrap().as_deref(), Some("/tmp/r1.webm"));
+        assert_eq!(db.get_reading_recordings("default", article_id).unwrap().len(), 1);
+    }
+
+    /// 测试 51: 解析发音评分并保存到熟练度记录
+    #[test]
+    fn test_pronunciation_score() {
+        let mut db = create_test_db();
+        let (_, seg1, _) = setup_test_data(&mut db);
+        let response = serde_json::json!({
+            "RecognitionStatus": "Success",
+            "NBest": [{
+                "PronunciationAssessment": { "AccuracyScore": 82.0 },
+                "Words": [{
+                    "Word": "apple",
+                    "Phonemes": [
+                        { "Phoneme": "ae", "PronunciationAssessment": { "AccuracyScore": 95.0 } },
+                        { "Phoneme": "p", "AccuracyScore": 60.0 },
+                        { "Phoneme": "l" }
+                    ]
+                }]
+            }]
+        });
+        let (overall_score, phonemes) = crate::commands::pronunciation::parse_assessment(&response).unwrap();
+        assert_eq!(overall_score, 82.0);
+        assert_eq!(phonemes.len(), 2);
+        assert_eq!(phonemes[1], crate::models::PhonemeScore { phoneme: "p".to_string(), score: 60.0 });
+        assert!(crate::commands::pronunciation::parse_assessment(&serde_json::json!({ "RecognitionStatus": "NoMatch" })).is_err());
+
+        let assessment = crate::models::PronunciationAssessment {
+            segment_id: seg1,
+            word: "apple".to_string(),
+            overall_score,
+            phonemes,
+            assessed_at: None,
+        };
+        // 评分保存在已有的熟练度记录上
+        assert!(db.save_pronunciation_score("default", &assessment).is_err());
+        db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        db.save_pronunciation_score("default", &assessment).unwrap();
+        let saved = db.get_pronunciation_score("default", seg1).unwrap().unwrap();
+        assert_eq!(saved.word, "apple");
+        assert_eq!(saved.phonemes.len(), 2);
+        assert!(db.get_pronunciation_score("other", seg1).unwrap().is_none());
+        assert_eq!(db.get_word_masteries("default", None).unwrap()[0].review_count, 1);
+    }
+
+    /// 测试 52: 听力提速训练按对错调整语速并记录历史
+    #[test]
+    fn test_listening_speed_training() {
+        use crate::commands::session::next_speed_rate;
+        assert_eq!(next_speed_rate(150, 0, true, None), (150, 1));
+        assert_eq!(next_speed_rate(150, 2, true, None), (160, 0));
+        assert_eq!(next_speed_rate(150, 2, false, None), (140, 0));
+        assert_eq!(next_speed_rate(100, 0, false, None), (100, 0));
+        // 不超过无障碍设置的语速上限
+        assert_eq!(next_speed_rate(160, 2, true, Some(160)), (160, 0));
+
+        let mut db = create_test_db();
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() }],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: true,
+            tts_rate: Some(150),
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        }).unwrap();
+        let session = db.get_practice_session(session_id).unwrap().unwrap();
+        assert!(session.speed_training);
+
+        let mut rate = session.tts_rate.unwrap();
+        let mut streak = db.session_speed_streak(session_id).unwrap();
+        for correct in [true, true, true, true, false] {
+            let (next_rate, next_streak) = next_speed_rate(rate, streak, correct, None);
+            db.record_speed_answer(session_id, "default", rate, correct, next_rate, next_streak).unwrap();
+            (rate, streak) = (next_rate, next_streak);
+        }
+        assert_eq!(db.get_practice_session(session_id).unwrap().unwrap().tts_rate, Some(150));
+
+        let history = db.get_listening_speed_history("default", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].start_rate, history[0].final_rate), (150, 150));
+        assert_eq!(history[0].comfortable_rate, Some(160));
+        assert_eq!(history[0].answers, 5);
+    }
+
+    /// 测试 54: 批量更新熟练度在一个事务中完成，同一个词可多次出现
+    #[test]
+    fn test_update_word_masteries_batch() {
+        use crate::models::UpdateMasteryRequest;
+        let mut db = create_test_db();
+        let (_article_id, apple, banana) = setup_test_data(&mut db);
+        let request = |segment_id: i64, content: &str, correct: bool| UpdateMasteryRequest {
+            user_name: "default".to_string(),
+            segment_id,
+            segment_content: content.to_string(),
+            segment_type: "word".to_string(),
+            correct,
+            quality: None,
+        };
+
+        let masteries = db
+            .update_word_masteries_batch(&[
+                request(apple, "apple", true),
+                request(banana, "banana", false),
+                request(apple, "apple", true),
+            ])
+            .unwrap();
+        assert_eq!(masteries.len(), 3);
+        assert_eq!(masteries[2].mastery_level, 2);
+        assert_eq!(masteries[1].mastery_level, 0);
+
+        let stored = db.get_word_masteries("default", None).unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(db.update_word_masteries_batch(&[]).unwrap().is_empty());
+    }
+
+    /// 测试 55: 批量记录错词，同一个词重复出现时累加错误次数
+    #[test]
+    fn test_add_mistakes_batch() {
+        use crate::models::AddMistakeRequest;
+        let mut db = create_test_db();
+        let (_article_id, apple, banana) = setup_test_data(&mut db);
+        let request = |segment_id: i64, content: &str| AddMistakeRequest {
+            user_name: "default".to_string(),
+            segment_id,
+            segment_content: content.to_string(),
+            segment_type: "word".to_string(),
+        };
+
+        db.add_mistakes_batch(&[request(apple, "apple"), request(banana, "banana"), request(apple, "apple")]).unwrap();
+        let mistakes = db.get_mistakes("default", None).unwrap();
+        assert_eq!(mistakes.len(), 2);
+        let apple_mistake = mistakes.iter().find(|m| m.segment_id == apple).unwrap();
+        assert_eq!(apple_mistake.error_count, 2);
+
+        // 不存在的分词违反外键，整批回滚
+        assert!(db.add_mistakes_batch(&[request(banana, "banana"), request(9999, "ghost")]).is_err());
+        let banana_mistake = db.get_mistakes("default", None).unwrap().into_iter().find(|m| m.segment_id == banana).unwrap();
+        assert_eq!(banana_mistake.error_count, 1);
+    }
+
+    /// 测试 56: 只读查询只接受单条 SELECT，拒绝写入和 users 表，超时中断
+    #[test]
+    fn test_run_readonly_query() {
+        let mut db = create_test_db();
+        setup_test_data(&mut db);
+
+        let rows = db.run_readonly_query("SELECT content, order_index FROM segments ORDER BY order_index LIMIT 2;").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["content"], "apple");
+        assert!(rows[1]["order_index"].is_i64());
+
+        assert!(db.run_readonly_query("DELETE FROM segments").is_err());
+        assert!(db.run_readonly_query("SELECT 1; DELETE FROM segments").is_err());
+        assert!(db.run_readonly_query("select * from Users").is_err());
+        assert_eq!(db.get_segments(1, "word").unwrap().len(), 5);
+
+        // 无限递归的 CTE 超时中断，之后连接照常可用
+        let endless = "WITH RECURSIVE r(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r) SELECT COUNT(*) FROM r";
+        let started = std::time::Instant::now();
+        let err = db.run_readonly_query_within(endless, std::time::Duration::from_millis(200)).unwrap_err();
+        assert!(err.to_string().contains("已中断"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(db.run_readonly_query("SELECT COUNT(*) AS n FROM segments").unwrap()[0]["n"], 5);
+    }
+
+    /// 测试 57: 保留策略先汇总旧练习记录再删除，dry-run 不改动数据
+    #[test]
+    fn test_apply_retention_policy() {
+        let mut db = create_test_db();
+        setup_test_data(&mut db);
+        for (completed_at, accuracy) in [("2020-01-05 10:00:00", 80.0), ("2020-01-20 10:00:00", 100.0)] {
+            db.conn.execute(
+                "INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, total_count, accuracy, wpm, duration_seconds, completed_at)
+                 VALUES ('default', 1, 'word', 4, 5, ?, 20, 60, ?)",
+                rusqlite::params![accuracy, completed_at],
+            ).unwrap();
+        }
+        db.conn.execute(
+            "INSERT INTO practice_history (user_name, article_id, segment_type, accuracy) VALUES ('default', 1, 'word', 50)",
+            [],
+        ).unwrap();
+
+        // 旧会话（id 1）与新会话（id 2）及其子表记录；作答事件的会话 5 已不存在
+        db.conn.execute_batch(
+            "INSERT INTO practice_sessions (id, article_id, segment_type, status, updated_at) VALUES
+                (1, 1, 'word', 'completed', '2020-01-05 10:00:00'), (2, 1, 'word', 'completed', CURRENT_TIMESTAMP);
+             INSERT INTO practice_events (user_name, session_id, item_index, segment_id, correct, response_ms, created_at) VALUES
+                ('default', 1, 0, 1, 1, 900, '2020-01-05 10:00:00.000'), ('default', 2, 0, 1, 1, 900, '2024-01-05 10:00:00.000'),
+                ('default', 5, 0, 1, 0, 900, '2020-01-05 10:00:00.000');
+             INSERT INTO session_hints (session_id, item_index, hint_count) VALUES (1, 0, 1), (2, 0, 1);
+             INSERT INTO keystroke_errors (session_id, user_name, expected, typed, kind) VALUES (1, 'default', 'a', 's', 'slip');
+             INSERT INTO wida_test_sessions (id, test_type, grade_level, status, total_questions, question_ids, started_at, completed_at)
+                VALUES (1, 'reading', '3-5', 'completed', 1, '[1]', '2020-01-05 10:00:00', '2020-01-05 10:10:00');
+             INSERT INTO wida_question_flags (session_id, user_name, test_type, question_id, reason) VALUES (1, 'default', 'reading', 1, 'typo');
+             INSERT INTO wida_focus_events (session_id, event_type) VALUES (1, 'focus_loss');",
+        ).unwrap();
+
+        let preview = db.apply_retention_policy(12, true).unwrap();
+        assert_eq!(preview.practice_history, 2);
+        assert_eq!((preview.practice_sessions, preview.practice_events, preview.wida_test_sessions), (1, 2, 1));
+        assert_eq!(db.get_practice_history("default", 10).unwrap().len(), 3);
+
+        let report = db.apply_retention_policy(12, false).unwrap();
+        assert_eq!(report.practice_history, 2);
+        assert_eq!(db.get_practice_history("default", 10).unwrap().len(), 1);
+        let rollups = db.get_practice_rollups("default").unwrap();
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(rollups[0].month, "2020-01");
+        assert_eq!(rollups[0].practice_count, 2);
+        assert!((rollups[0].avg_accuracy - 90.0).abs() < 1e-9);
+        let rows = |table: &str| -> i32 { db.conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap() };
+        assert_eq!((rows("practice_sessions"), rows("practice_events"), rows("session_hints")), (1, 1, 1));
+        assert_eq!((rows("keystroke_errors"), rows("wida_test_sessions"), rows("wida_question_flags")), (0, 0, 0));
+        assert_eq!(rows("wida_focus_events"), 0);
+        assert!(db.apply_retention_policy(0, true).is_err());
+    }
+
+    /// 测试 58: 重置文章练习数据需要有效的确认令牌，只影响该用户
+    #[test]
+    fn test_reset_article_progress() {
+        let mut db = create_test_db();
+        let (article_id, apple, banana) = setup_test_data(&mut db);
+        db.update_word_mastery("default", apple, "apple", "word", true).unwrap();
+        db.update_word_mastery("other", apple, "apple", "word", true).unwrap();
+        db.add_mistake("default", banana, "banana", "word").unwrap();
+        let start_test_session = |db: &DatabaseManager, user_name: &str, article_id: i64| {
+            db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+                user_name: user_name.to_string(),
+                article_id,
+                segment_type: "word".to_string(),
+                items: vec![crate::models::SessionItem { segment_id: apple, content: "apple".to_string() }],
+                order_mode: None,
+                order_seed: None,
+                goal: None,
+                speed_training: false,
+                tts_rate: None,
+                hint_penalty: None,
+                hint_mastery_threshold: None,
+                chunked: false,
+                check_options: None,
+            })
+            .unwrap()
+        };
+        let session_id = start_test_session(&db, "default", article_id);
+        let other_session = start_test_session(&db, "other", article_id);
+        for id in [session_id, other_session] {
+            db.record_practice_event(id, "default", 0, apple, true).unwrap();
+            db.record_session_hint(id, 0).unwrap();
+        }
+
+        let preview = db.preview_article_reset("default", article_id).unwrap();
+        assert_eq!(preview.mastery_count, 1);
+        assert_eq!(preview.mistake_count, 1);
+        assert_eq!((preview.session_count, preview.session_detail_count), (1, 2));
+        assert!(db.reset_article_progress("default", article_id, "bogus").is_err());
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 1);
+
+        db.reset_article_progress("default", article_id, &preview.confirm_token).unwrap();
+        assert!(db.get_word_masteries("default", None).unwrap().is_empty());
+        assert!(db.get_mistakes("default", None).unwrap().is_empty());
+        assert_eq!(db.get_word_masteries("other", None).unwrap().len(), 1);
+        // 会话的子记录一起删除，不依赖外键级联
+        let details = |id: i64| -> i32 {
+            db.conn
+                .query_row(
+                    "SELECT (SELECT COUNT(*) FROM practice_events WHERE session_id = ?1) + (SELECT COUNT(*) FROM session_hints WHERE session_id = ?1)",
+                    [id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!((details(session_id), details(other_session)), (0, 2));
+
+        // 数据变化后旧令牌失效，包括只有会话子记录变化
+        db.add_mistake("default", banana, "banana", "word").unwrap();
+        assert!(db.reset_article_progress("default", article_id, &preview.confirm_token).is_err());
+        let session_id = start_test_session(&db, "default", article_id);
+        let preview = db.preview_article_reset("default", article_id).unwrap();
+        db.record_session_hint(session_id, 0).unwrap();
+        assert!(db.reset_article_progress("default", article_id, &preview.confirm_token).is_err());
+    }
+
+    /// 测试 59: 按内容合并熟练度后，另一篇文章里的同一个词沿用已有记录
+    #[test]
+    fn test_content_keyed_mastery() {
+        let mut db = create_test_db();
+        let (_article_id, apple, _) = setup_test_data(&mut db);
+        db.create_article("第二篇", "Apple pie").unwrap();
+        db.save_segments(2, "word", &["Apple".to_string(), "pie".to_string()]).unwrap();
+        let other_apple = db.get_segments(2, "word").unwrap()[0].id;
+
+        db.update_word_mastery("default", apple, "apple", "word", true).unwrap();
+        db.update_word_mastery("default", other_apple, "Apple", "word", true).unwrap();
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 2);
+
+        db.set_content_keyed_mastery("default", true).unwrap();
+        assert_eq!(db.merge_duplicate_masteries("default").unwrap(), 1);
+        let masteries = db.get_word_masteries("default", None).unwrap();
+        assert_eq!(masteries.len(), 1);
+        assert_eq!(masteries[0].review_count, 2);
+
+        // 再次答对任一篇里的 apple 都更新同一条记录
+        let updated = db.update_word_mastery("default", other_apple, "Apple", "word", true).unwrap();
+        assert_eq!(updated.segment_id, masteries[0].segment_id);
+        assert_eq!(updated.mastery_level, 2);
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 1);
+
+        // 调度时另一篇文章的 apple 不再算新词
+        let scheduled = db.get_scheduled_words("default", 2, "word", 0, false).unwrap();
+        assert!(scheduled.words.iter().all(|w| w.segment_id != other_apple || !w.is_new));
+    }
+
+    /// 测试 60: 语境复习优先返回包含更多薄弱词的句子
+    #[test]
+    fn test_get_context_sentences() {
+        let mut db = create_test_db();
+        let (article_id, apple, banana) = setup_test_data(&mut db);
+        db.save_segments(article_id, "sentence", &[
+            "I like tea.".to_string(),
+            "An apple a day.".to_string(),
+            "Apple and banana bread.".to_string(),
+        ]).unwrap();
+        assert!(db.get_context_sentences("default", 5).unwrap().is_empty());
+
+        db.update_word_mastery("default", apple, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", banana, "banana", "word", false).unwrap();
+
+        let sentences = db.get_context_sentences("default", 5).unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[0].content, "Apple and banana bread.");
+        assert_eq!(sentences[0].weak_words.len(), 2);
+        assert_eq!(sentences[1].weak_words, vec!["apple".to_string()]);
+    }
+
+    /// 测试 61: 渐进提示按提示次数扣分
+    #[test]
+    fn test_progressive_hints() {
+        use crate::commands::session::hinted_item_score;
+        let mut db = create_test_db();
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() }],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: Some(0.25),
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        }).unwrap();
+        assert_eq!(db.record_session_hint(session_id, 0).unwrap(), 1);
+        assert_eq!(db.record_session_hint(session_id, 0).unwrap(), 2);
+        assert_eq!(db.session_hints_used(session_id).unwrap(), 2);
+
+        let session = db.get_practice_session(session_id).unwrap().unwrap();
+        assert_eq!(session.hint_mastery_threshold, 3);
+        let score = hinted_item_score(true, 2, session.hint_penalty);
+        assert!((score - 0.5).abs() < 1e-9);
+        assert_eq!(hinted_item_score(true, 10, session.hint_penalty), 0.0);
+        assert_eq!(hinted_item_score(false, 0, session.hint_penalty), 0.0);
+        db.add_session_score(session_id, score).unwrap();
+        assert!((db.get_practice_session(session_id).unwrap().unwrap().score_points - 0.5).abs() < 1e-9);
+    }
+
+    /// 测试 62: 带质量等级时按 SM-2 公式计算难度因子和倍增间隔
+    #[test]
+    fn test_sm2_quality_grades() {
+        let mut db = create_test_db();
+        let (_article_id, seg1, seg2) = setup_test_data(&mut db);
+
+        let first = db.update_word_mastery_graded("default", seg1, "apple", "word", false, Some(5)).unwrap();
+        assert_eq!(first.interval_days, 1);
+        assert!((first.ease_factor - 2.6).abs() < 1e-9);
+        let second = db.update_word_mastery_graded("default", seg1, "apple", "word", false, Some(4)).unwrap();
+        assert_eq!(second.interval_days, 6);
+        assert!((second.ease_factor - 2.6).abs() < 1e-9);
+        let third = db.update_word_mastery_graded("default", seg1, "apple", "word", false, Some(3)).unwrap();
+        assert!((third.ease_factor - 2.46).abs() < 1e-9);
+        assert_eq!(third.interval_days, 15);
+        assert_eq!(third.mastery_level, 3);
+
+        let forgot = db.update_word_mastery_graded("default", seg1, "apple", "word", true, Some(1)).unwrap();
+        assert_eq!(forgot.interval_days, 0);
+        assert_eq!(forgot.mastery_level, 2);
+
+        assert!(db.update_word_mastery_graded("default", seg2, "banana", "word", true, Some(6)).is_err());
+    }
+
+    /// 测试 63: 逾期复习答对时按逾期天数加长间隔，最多翻倍
+    #[test]
+    fn test_overdue_review_credit() {
+        let mut db = create_test_db();
+        let (_article_id, seg1, seg2) = setup_test_data(&mut db);
+        for (segment_id, content) in [(seg1, "apple"), (seg2, "banana")] {
+            db.update_word_mastery("default", segment_id, content, "word", true).unwrap();
+        }
+        // apple 逾期 4 天，banana 逾期 60 天
+        for (segment_id, days) in [(seg1, 4), (seg2, 60)] {
+            let due = (chrono::Utc::now() - chrono::Duration::days(days)).format("%Y-%m-%d %H:%M:%S").to_string();
+            db.conn.execute(
+                "UPDATE word_mastery SET next_review_at = ? WHERE segment_id = ?",
+                rusqlite::params![due, segment_id],
+            ).unwrap();
+        }
 
-fn get_default_reading_questions() -> Vec<ReadingQuestionData> {
-    vec![
-        ReadingQuestionData {
-            id: 1,
-            grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            passage: "My name is Tom. I have a dog. My dog's name is Spot. Spot is brown and white. He likes to play with his ball.".to_string(),
-            question_text: "What color is Spot?".to_string(),
-            question_type: "multiple_choice".to_string(),
-            options: vec!["Black".to_string(), "Brown and white".to_string(), "Gray".to_string(), "Yellow".to_string()],
-            correct_answer: 1,
-            explanation: Some("The passage says 'Spot is brown and white.'".to_string()),
-        },
-        ReadingQuestionData {
-            id: 2,
-            grade_level: "grade_1_2".to_string(),
-            domain: "mathematics".to_string(),
-            difficulty: 1,
-            passage: "Sara has 3 red apples. She buys 2 more red apples at the store. Now Sara has apples to share with her friends.".to_string(),
-            question_text: "How many apples does Sara have in total?".to_string(),
-            question_type: "multiple_choice".to_string(),
-            options: vec!["3 apples".to_string(), "2 apples".to_string(), "5 apples".to_string(), "6 apples".to_string()],
-            correct_answer: 2,
-            explanation: Some("3 + 2 = 5 apples.".to_string()),
-        },
-        ReadingQuestionData {
-            id: 3,
-            grade_level: "grade_1_2".to_string(),
-            domain: "science".to_string(),
-            difficulty: 2,
-            passage: "Fish live in water. They have fins to help them swim. Fish use gills to breathe underwater. Some fish are small, and some fish are very big.".to_string(),
-            question_text: "What do fish use to breathe underwater?".to_string(),
-            question_type: "multiple_choice".to_string(),
-            options: vec!["Lungs".to_string(), "Fins".to_string(), "Gills".to_string(), "Nose".to_string()],
-            correct_answer: 2,
-            explanation: Some("The passage states that fish use gills to breathe underwater.".to_string()),
-        },
-        ReadingQuestionData {
-            id: 4,
-            grade_level: "grade_3_5".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 2,
-            passage: "The Little Red Hen lived on a farm with a pig, a duck, and a cat. One day, she found some wheat seeds. She asked her friends, 'Who will help me plant these seeds?' 'Not I,' said the pig. 'Not I,' said the duck. 'Not I,' said the cat. So the Little Red Hen planted the seeds herself.".to_string(),
-            question_text: "Who helped the Little Red Hen plant the seeds?".to_string(),
-            question_type: "multiple_choice".to_string(),
-            options: vec!["The pig".to_string(), "The duck".to_string(), "The cat".to_string(), "No one".to_string()],
-            correct_answer: 3,
-            explanation: Some("All the animals said 'Not I,' so the hen planted the seeds herself.".to_string()),
-        },
-        ReadingQuestionData {
-            id: 5,
+        let apple = db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        assert_eq!(apple.interval_days, 3 + 2);
+        let banana = db.update_word_mastery("default", seg2, "banana", "word", true).unwrap();
+        assert_eq!(banana.interval_days, 3 * 2);
+
+        // 答错不加间隔
+        let wrong = db.update_word_mastery("default", seg1, "apple", "word", false).unwrap();
+        assert_eq!(wrong.interval_days, 0);
+    }
+
+    /// 测试 64: 复习量过大的日子分散到相邻日期
+    #[test]
+    fn test_review_fuzz_and_rebalance() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+        let segments = db.get_segments(article_id, "word").unwrap();
+        let pile_up = (chrono::Utc::now() + chrono::Duration::days(20)).format("%Y-%m-%d 09:00:00").to_string();
+        for segment in &segments {
+            db.update_word_mastery("default", segment.id, &segment.content, "word", true).unwrap();
+            db.conn.execute(
+                "UPDATE word_mastery SET next_review_at = ?, interval_days = 20 WHERE segment_id = ?",
+                rusqlite::params![pile_up, segment.id],
+            ).unwrap();
+        }
+
+        let result = db.rebalance_reviews("default", Some(2)).unwrap();
+        assert_eq!(result.moved, 3);
+        assert!(result.due_days.iter().all(|d| d.count <= 2));
+        assert_eq!(result.due_days.iter().map(|d| d.count).sum::<i32>(), 5);
+    }
+
+    /// 测试 65: 假期结束后把期间到期的复习顺延假期天数
+    #[test]
+    fn test_pause_reviews() {
+        let mut db = create_test_db();
+        let (_article_id, seg1, seg2) = setup_test_data(&mut db);
+        db.update_word_mastery("default", seg1, "apple", "word", true).unwrap();
+        db.update_word_mastery("default", seg2, "banana", "word", true).unwrap();
+
+        // 两周的假期已经结束：apple 在假期中到期，banana 在假期前就到期了
+        let day = |offset: i64| (chrono::Local::now().date_naive() + chrono::Duration::days(offset)).format("%Y-%m-%d").to_string();
+        let set_due = |segment_id: i64, due: String| {
+            db.conn.execute(
+                "UPDATE word_mastery SET next_review_at = ?, last_review_at = '2000-01-01 00:00:00' WHERE segment_id = ?",
+                rusqlite::params![due, segment_id],
+            ).unwrap();
+        };
+        set_due(seg1, format!("{} 08:00:00", day(-10)));
+        set_due(seg2, format!("{} 08:00:00", day(-20)));
+
+        assert!(db.pause_reviews("default", &day(-3), &day(-5)).is_err());
+        let pause = db.pause_reviews("default", &day(-15), &day(-2)).unwrap();
+        assert_eq!(pause.shifted_count, 1);
+        assert!(pause.applied_at.is_some());
+
+        let masteries = db.get_word_masteries("default", None).unwrap();
+        let apple = masteries.iter().find(|m| m.segment_id == seg1).unwrap();
+        assert_eq!(apple.next_review_at, format!("{} 08:00:00", day(4)));
+        let banana = masteries.iter().find(|m| m.segment_id == seg2).unwrap();
+        assert_eq!(banana.next_review_at, format!("{} 08:00:00", day(-20)));
+
+        // 已生效的假期不能取消，未来的可以
+        assert!(!db.cancel_review_pause(pause.id).unwrap());
+        let upcoming = db.pause_reviews("default", &day(5), &day(9)).unwrap();
+        assert!(upcoming.applied_at.is_none());
+        assert!(db.cancel_review_pause(upcoming.id).unwrap());
+    }
+
+    /// 测试 66: 按复习间隔分组统计回忆率
+    #[test]
+    fn test_retention_stats() {
+        let mut db = create_test_db();
+        let (_article_id, seg1, seg2) = setup_test_data(&mut db);
+        let events = [
+            (seg1, "2024-01-01 10:00:00", Some(true)),
+            (seg1, "2024-01-02 10:00:00", Some(true)),
+            (seg1, "2024-01-05 10:00:00", Some(false)),
+            (seg2, "2024-01-01 10:00:00", None),
+            (seg2, "2024-01-02 11:00:00", Some(true)),
+            (seg2, "2024-01-20 11:00:00", Some(true)),
+        ];
+        for (segment_id, at, correct) in events {
+            db.conn.execute(
+                "INSERT INTO mastery_events (user_name, segment_id, segment_content, new_level, correct, created_at)
+                 VALUES ('default', ?, 'word', 1, ?, ?)",
+                rusqlite::params![segment_id, correct, at],
+            ).unwrap();
+        }
+
+        let stats = db.get_retention_stats("default").unwrap();
+        let bucket = |min: i64| stats.iter().find(|b| b.min_days == min).unwrap();
+        assert_eq!((bucket(1).reviews, bucket(1).recalled), (2, 2));
+        assert_eq!((bucket(2).reviews, bucket(2).recalled), (1, 0));
+        assert_eq!(bucket(15).reviews, 1);
+        assert!((bucket(1).retention_rate - 100.0).abs() < 1e-9);
+        assert_eq!(bucket(31).reviews, 0);
+    }
+
+    /// 测试 67: 键盘布局设置与按字母统计按错相邻键和拼写错误
+    #[test]
+    fn test_keyboard_layout_errors() {
+        let db = create_test_db();
+        assert_eq!(db.get_keyboard_layout("default").unwrap(), "qwerty");
+        assert!(db.set_keyboard_layout("default", "dvorak").is_err());
+        db.set_keyboard_layout("default", "AZERTY").unwrap();
+        assert_eq!(db.get_keyboard_layout("default").unwrap(), "azerty");
+
+        db.record_keystroke_error(1, "default", 'E', 'r', "slip").unwrap();
+        db.record_keystroke_error(1, "default", 'e', 'a', "spelling").unwrap();
+        db.record_keystroke_error(1, "default", 'i', 'e', "spelling").unwrap();
+        db.record_keystroke_error(1, "default", 'i', 'y', "spelling").unwrap();
+        let stats = db.get_letter_errors("default").unwrap();
+        assert_eq!(stats[0].letter, "i");
+        assert_eq!(stats[0].spelling_errors, 2);
+        assert_eq!((stats[1].spelling_errors, stats[1].slips), (1, 1));
+    }
+
+    /// 测试 68: 每周小结、连续练习天数和分享卡片 PNG
+    #[test]
+    fn test_share_card_data() {
+        let mut db = create_test_db();
+        setup_test_data(&mut db);
+        for days_ago in [1, 2, 3, 5] {
+            let at = (chrono::Utc::now() - chrono::Duration::days(days_ago)).format("%Y-%m-%d %H:%M:%S").to_string();
+            db.conn.execute(
+                "INSERT INTO practice_history (user_name, article_id, segment_type, total_count, accuracy, duration_seconds, completed_at)
+                 VALUES ('default', 1, 'word', 10, 80, 120, ?)",
+                [at],
+            ).unwrap();
+        }
+        assert_eq!(db.get_practice_streak("default").unwrap(), 3);
+        let summary = db.get_weekly_summary("default").unwrap();
+        assert_eq!(summary.practices, 4);
+        assert_eq!(summary.words_practiced, 40);
+        assert_eq!(summary.streak_days, 3);
+
+        let png = crate::cards::render_png(&crate::cards::ShareCard {
+            title: "Weekly summary".to_string(),
+            headline: "40 words".to_string(),
+            lines: vec![("Accuracy".to_string(), "80%".to_string())],
+            footer: "default".to_string(),
+            accent: [60, 120, 200],
+        }).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let decoder = png::Decoder::new(png.as_slice());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (600, 340));
+    }
+
+    /// 测试 70: 按出生年份和最近测试结果推荐 WIDA 年级段和起始难度
+    #[test]
+    fn test_recommend_grade_level() {
+        let db = create_test_db();
+        let this_year = chrono::Datelike::year(&chrono::Local::now().date_naive());
+
+        let fresh = db.recommend_grade_level("alice").unwrap();
+        assert_eq!((fresh.grade_level.as_str(), fresh.difficulty, fresh.age), ("grade_3_5", 1, None));
+
+        assert!(db.set_user_birth_year("alice", Some(this_year + 1)).is_err());
+        db.set_user_birth_year("alice", Some(this_year - 12)).unwrap();
+        assert_eq!(db.get_user_birth_year("alice").unwrap(), Some(this_year - 12));
+        let by_age = db.recommend_grade_level("alice").unwrap();
+        assert_eq!((by_age.grade_level.as_str(), by_age.age), ("grade_6_8", Some(12)));
+
+        let record = |user: &str, grade: &str, level: i32, accuracy: f64, at: &str| {
+            db.conn
+                .execute(
+                    "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy,
+                     total_questions, correct_count, duration_seconds, completed_at)
+                     VALUES (?1, 'reading', ?2, 400, ?3, ?4, 10, 8, 600, ?5)",
+                    rusqlite::params![user, grade, level, accuracy, at],
+                )
+                .unwrap();
+        };
+        record("alice", "grade_6_8", 3, 60.0, "2026-01-01 10:00:00");
+        record("alice", "grade_6_8", 4, 85.0, "2026-02-01 10:00:00");
+        let with_history = db.recommend_grade_level("alice").unwrap();
+        assert_eq!(with_history.grade_level, "grade_6_8");
+        assert_eq!(with_history.recent_proficiency_level, Some(3.5));
+        // 平均 3.5 取整为 4，最近一次正确率 85% 再加 1
+        assert_eq!(with_history.difficulty, 5);
+
+        // 没有出生年份时按历史升降年级段
+        record("bob", "grade_3_5", 5, 90.0, "2026-01-01 10:00:00");
+        record("bob", "grade_3_5", 6, 95.0, "2026-02-01 10:00:00");
+        let bob = db.recommend_grade_level("bob").unwrap();
+        assert_eq!((bob.grade_level.as_str(), bob.difficulty), ("grade_6_8", 6));
+        record("carol", "grade_3_5", 1, 30.0, "2026-01-01 10:00:00");
+        let carol = db.recommend_grade_level("carol").unwrap();
+        assert_eq!((carol.grade_level.as_str(), carol.difficulty), ("grade_1_2", 1));
+
+        db.set_user_birth_year("alice", None).unwrap();
+        assert_eq!(db.get_user_birth_year("alice").unwrap(), None);
+    }
+
+    /// 测试 71: 入门分级测试按答题结果调整难度，结束后写入能力档案和每日新词数
+    #[test]
+    fn test_placement_test() {
+        use crate::commands::wida::{GeneratedListeningQuestion, GeneratedReadingQuestion};
+        let db = create_test_db();
+        let options = vec!["a".to_string(), "b".to_string()];
+        let listening: Vec<GeneratedListeningQuestion> = (1..=6)
+            .map(|difficulty| GeneratedListeningQuestion {
+                grade_level: "grade_3_5".to_string(),
+                domain: "social".to_string(),
+                difficulty,
+                audio_text: format!("listen {}", difficulty),
+                image_url: None,
+                question_text: format!("listening {}", difficulty),
+                question_type: "multiple_choice".to_string(),
+                options: options.clone(),
+                correct_answer: 0,
+                correct_answers: Vec::new(),
+                answer_pattern: None,
+                explanation: None,
+                source_article_id: None,
+            })
+            .collect();
+        let reading: Vec<GeneratedReadingQuestion> = (1..=6)
+            .map(|difficulty| GeneratedReadingQuestion {
+                grade_level: "grade_3_5".to_string(),
+                domain: "social".to_string(),
+                difficulty,
+                passage: "A short passage.".to_string(),
+                question_text: format!("reading {}", difficulty),
+                question_type: "multiple_choice".to_string(),
+                options: options.clone(),
+                correct_answer: 0,
+                correct_answers: Vec::new(),
+                answer_pattern: None,
+                explanation: None,
+                source_article_id: None,
+            })
+            .collect();
+        db.save_listening_questions(&listening).unwrap();
+        db.save_reading_questions(&reading).unwrap();
+
+        // 新用户：默认年级段，从难度 1 开始
+        let test = db.start_placement_test("alice", None).unwrap();
+        assert_eq!((test.grade_level.as_str(), test.difficulty, test.total_items), ("grade_3_5", 1, 10));
+        assert!(db.submit_placement_answer(test.id, "0").is_err());
+
+        let mut seen = Vec::new();
+        for expected_type in ["listening", "reading", "listening", "reading"] {
+            let question = db.next_placement_question(test.id).unwrap().unwrap();
+            // 未作答时重复返回同一题
+            assert_eq!(db.next_placement_question(test.id).unwrap().unwrap().index, question.index);
+            assert_eq!(question.test_type, expected_type);
+            seen.push(question.difficulty);
+            let result = db.submit_placement_answer(test.id, "0").unwrap();
+            assert!(result.correct);
+            assert_eq!(result.next_difficulty, question.difficulty + 1);
+        }
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+
+        let profile = db.complete_placement_test(test.id).unwrap();
+        // (1.5 + 2.5 + 3.5 + 4.5) / 4 = 3；听力 (1.5 + 3.5) / 2 ≈ 3，阅读 (2.5 + 4.5) / 2 ≈ 4
+        assert_eq!(profile.proficiency_level, 3);
+        assert_eq!((profile.listening_level, profile.reading_level), (Some(3), Some(4)));
+        assert_eq!((profile.daily_new_words, profile.daily_settings_applied), (10, true));
+        assert_eq!(db.get_new_word_pacing("alice").unwrap().daily_limit, Some(10));
+        let history = db.get_wida_history("alice", None, None).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|r| r.accuracy == 100.0 && r.total_questions == 2));
+        assert!(db.next_placement_question(test.id).is_err());
+        assert!(db.complete_placement_test(test.id).is_err());
+
+        // 已有每日设置的用户不覆盖；答错时难度下降但不低于 1
+        db.set_new_word_pacing("bob", Some(30), false).unwrap();
+        let test = db.start_placement_test("bob", Some("grade_3_5")).unwrap();
+        assert!(db.complete_placement_test(test.id).is_err());
+        db.next_placement_question(test.id).unwrap().unwrap();
+        assert_eq!(db.submit_placement_answer(test.id, "1").unwrap().next_difficulty, 1);
+        let profile = db.complete_placement_test(test.id).unwrap();
+        assert_eq!((profile.proficiency_level, profile.reading_level), (1, None));
+        assert!(!profile.daily_settings_applied);
+        assert_eq!(db.get_new_word_pacing("bob").unwrap().daily_limit, Some(30));
+    }
+
+    /// 测试 72: 题目标签、题库搜索和按标签抽题
+    #[test]
+    fn test_question_tags_and_search() {
+        use crate::models::QuestionSearchFilters;
+        let mut db = create_test_db();
+        let questions: Vec<crate::commands::wida::GeneratedReadingQuestion> = [
+            ("The cat sleeps on the mat.", "What does the cat do?", 2),
+            ("Elephants are large animals.", "Which animal is large?", 3),
+            ("Plants need water and light.", "What do plants need?", 4),
+        ]
+        .into_iter()
+        .map(|(passage, question_text, difficulty)| crate::commands::wida::GeneratedReadingQuestion {
             grade_level: "grade_3_5".to_string(),
             domain: "science".to_string(),
-            difficulty: 3,
-            passage: "Plants are very important for life on Earth. They take in carbon dioxide from the air and release oxygen. This process is called photosynthesis. Humans and animals need oxygen to breathe. Plants also provide food for many animals.".to_string(),
-            question_text: "What gas do plants release during photosynthesis?".to_string(),
+            difficulty,
+            passage: passage.to_string(),
+            question_text: question_text.to_string(),
             question_type: "multiple_choice".to_string(),
-            options: vec!["Carbon dioxide".to_string(), "Nitrogen".to_string(), "Oxygen".to_string(), "Hydrogen".to_string()],
-            correct_answer: 2,
-            explanation: Some("The passage says plants release oxygen during photosynthesis.".to_string()),
-        },
-    ]
-}
+            options: vec!["a".to_string(), "b".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        })
+        .collect();
+        db.save_reading_questions(&questions).unwrap();
+        let ids: Vec<i64> = db.get_wida_reading_questions("grade_3_5", None, None).unwrap().iter().map(|q| q.id).collect();
+
+        db.set_wida_question_tags("reading", ids[0], &["animals unit".to_string(), " ".to_string()]).unwrap();
+        db.set_wida_question_tags("reading", ids[1], &["animals unit".to_string(), "week 2".to_string()]).unwrap();
+        assert!(db.set_wida_question_tags("reading", 9999, &["x".to_string()]).is_err());
+        assert_eq!(db.get_all_question_tags().unwrap(), vec!["animals unit", "week 2"]);
+
+        // 关键词同时搜索题干和阅读文章，不区分大小写
+        let search = |filters: QuestionSearchFilters| -> Vec<i64> {
+            db.search_questions(&filters).unwrap().iter().map(|r| r.question_id).collect()
+        };
+        assert_eq!(search(QuestionSearchFilters { text: Some("ELEPHANTS".into()), ..Default::default() }), vec![ids[1]]);
+        assert_eq!(search(QuestionSearchFilters { text: Some("plants need".into()), ..Default::default() }), vec![ids[2]]);
+        assert_eq!(
+            search(QuestionSearchFilters { tags: Some(vec!["animals unit".into()]), ..Default::default() }),
+            vec![ids[0], ids[1]]
+        );
+        assert_eq!(
+            search(QuestionSearchFilters { tags: Some(vec!["animals unit".into()]), max_difficulty: Some(2), ..Default::default() }),
+            vec![ids[0]]
+        );
+        assert!(search(QuestionSearchFilters { test_type: Some("listening".into()), ..Default::default() }).is_empty());
+        assert!(db.search_questions(&QuestionSearchFilters { test_type: Some("maths".into()), ..Default::default() }).is_err());
+        let tagged = db.search_questions(&QuestionSearchFilters { text: Some("cat".into()), ..Default::default() }).unwrap();
+        assert_eq!(tagged[0].tags, vec!["animals unit"]);
+
+        // 按标签抽题只会抽到专题题库里的题
+        let session = db
+            .start_wida_test(&crate::models::StartWidaTestRequest {
+                user_name: "default".to_string(),
+                test_type: "reading".to_string(),
+                grade_level: "grade_3_5".to_string(),
+                domain: None,
+                question_count: 10,
+                blueprint_id: None,
+                max_audio_plays: None,
+                sample_count: None,
+                tags: Some(vec!["animals unit".to_string()]),
+            })
+            .unwrap();
+        assert_eq!(session.total_questions, 2);
+        let question_ids: String =
+            db.conn.query_row("SELECT question_ids FROM wida_test_sessions WHERE id = ?", [session.id], |row| row.get(0)).unwrap();
+        let mut question_ids: Vec<i64> = serde_json::from_str(&question_ids).unwrap();
+        question_ids.sort();
+        assert_eq!(question_ids, vec![ids[0], ids[1]]);
+
+        db.delete_wida_question("reading", ids[1]).unwrap();
+        assert_eq!(db.get_all_question_tags().unwrap(), vec!["animals unit"]);
+    }
 
-fn get_default_speaking_questions() -> Vec<SpeakingQuestionData> {
-    vec![
-        SpeakingQuestionData {
-            id: 1,
-            grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this picture and tell me about your family. How many people are in your family? Who are they?".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1511895426328-dc8714191300?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "There are four people in my family. I have a mother, a father, and a brother. My mother is a teacher. My father works in an office. My brother is younger than me.".to_string(),
-            rubric: vec!["Uses complete sentences".to_string(), "Includes number of family members".to_string(), "Names family members".to_string(), "Speaks clearly".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 2,
-            grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this picture of a park. Tell me what you see in the picture.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1565109160632-244323ef1b0e?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "I see a big park. There are many trees and flowers. Children are playing on the swings. Some people are sitting on benches. There is a dog running in the grass.".to_string(),
-            rubric: vec!["Describes main objects in picture".to_string(), "Uses appropriate vocabulary".to_string(), "Forms complete sentences".to_string(), "Speaks at appropriate pace".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 3,
-            grade_level: "grade_1_2".to_string(),
-            domain: "mathematics".to_string(),
-            difficulty: 2,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at the numbers in this picture. Count from one to twenty. Then tell me which number comes after fifteen.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1503676260728-1c00da094a0b?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "One, two, three, four, five, six, seven, eight, nine, ten, eleven, twelve, thirteen, fourteen, fifteen, sixteen, seventeen, eighteen, nineteen, twenty. The number after fifteen is sixteen.".to_string(),
-            rubric: vec!["Counts accurately".to_string(), "Pronounces numbers clearly".to_string(), "Identifies correct number".to_string(), "Completes the task".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 4,
-            grade_level: "grade_3_5".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 2,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at these books. Tell me about your favorite book. What is it about? Why do you like it?".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1495446815901-a7297e633e8d?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "My favorite book is 'Charlotte's Web.' It is about a pig named Wilbur and his friend Charlotte, who is a spider. Charlotte helps Wilbur by writing words in her web. I like this book because it shows the importance of friendship and kindness.".to_string(),
-            rubric: vec!["Names the book".to_string(), "Summarizes the story".to_string(), "Explains personal opinion".to_string(), "Uses descriptive language".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 5,
-            grade_level: "grade_3_5".to_string(),
-            domain: "science".to_string(),
-            difficulty: 3,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this picture showing the water cycle. Explain what happens during the water cycle. Include the words evaporation, condensation, and precipitation in your answer.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1439066615861-d1af74d74000?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "The water cycle is how water moves around the Earth. First, evaporation happens when the sun heats water and it turns into vapor. Then, condensation occurs when the vapor cools and forms clouds. Finally, precipitation happens when water falls from clouds as rain or snow.".to_string(),
-            rubric: vec!["Uses all three vocabulary words correctly".to_string(), "Explains the process in order".to_string(), "Shows understanding of the concept".to_string(), "Uses complete sentences".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 6,
+    /// 测试 73: 按作答结果校准题目难度
+    #[test]
+    fn test_recalibrate_difficulties() {
+        let db = create_test_db();
+        let question = |text: &str, difficulty: i32, pattern: &str| crate::commands::wida::GeneratedReadingQuestion {
             grade_level: "grade_3_5".to_string(),
-            domain: "social_studies".to_string(),
-            difficulty: 3,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this picture of a community. Describe your community. What are some important places in your community? What do people do there?".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1480714378408-67cf0d13bc1b?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "My community has many important places. We have a school where children learn. There is a library where people borrow books. The fire station has firefighters who help keep us safe. We also have parks where families play and stores where people buy food and clothes.".to_string(),
-            rubric: vec!["Names multiple community places".to_string(), "Describes functions of places".to_string(), "Shows community understanding".to_string(), "Uses varied vocabulary".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 7,
-            grade_level: "grade_6_8".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 3,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at these travel destinations. If you could travel to any country in the world, where would you go and why? What would you want to see or do there?".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1476514525535-07fb3b4ae5f1?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "I would travel to Japan because I am interested in its culture and technology. I would want to visit Tokyo to see the modern buildings and try Japanese food like sushi. I would also like to see ancient temples and learn about Japanese history.".to_string(),
-            rubric: vec!["States clear destination".to_string(), "Provides multiple reasons".to_string(), "Includes specific activities".to_string(), "Uses varied sentence structures".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 8,
-            grade_level: "grade_6_8".to_string(),
-            domain: "science".to_string(),
-            difficulty: 4,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at these energy sources. Explain the difference between renewable and non-renewable energy sources. Give examples of each.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1466611653911-95081537e5b7?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "Renewable energy sources can be naturally replenished. Examples include solar power from the sun, wind energy from wind turbines, and hydroelectric power from moving water. Non-renewable energy sources cannot be easily replaced once used. Examples include coal, oil, and natural gas, which take millions of years to form.".to_string(),
-            rubric: vec!["Defines both types clearly".to_string(), "Provides accurate examples for each".to_string(), "Shows understanding of sustainability".to_string(), "Uses appropriate scientific vocabulary".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 9,
-            grade_level: "grade_6_8".to_string(),
-            domain: "social_studies".to_string(),
-            difficulty: 4,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this picture showing people voting. What is democracy? Explain how it works and why it is important.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1541872703-74c5e44368f9?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "Democracy is a form of government where citizens have the power to make decisions. In a democracy, people vote to elect leaders who represent them. It is important because it gives everyone a voice in how their country is run. Democratic governments protect individual rights and freedoms, and leaders can be held accountable for their actions.".to_string(),
-            rubric: vec!["Defines democracy accurately".to_string(), "Explains the voting process".to_string(), "Discusses importance".to_string(), "Shows understanding of civic concepts".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 10,
-            grade_level: "grade_9_12".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 5,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this image showing social media. Do you think social media has a positive or negative effect on society? Support your opinion with specific examples.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1611162616305-c69b3fa7fbe0?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "Social media has both positive and negative effects on society. On the positive side, it allows people to connect with friends and family around the world and gives everyone a platform to share ideas. For example, social media has helped organize important social movements. However, there are negative effects too, such as the spread of misinformation and the impact on mental health. Overall, I believe social media can be positive if used responsibly.".to_string(),
-            rubric: vec!["Presents clear opinion".to_string(), "Provides balanced analysis".to_string(), "Uses specific examples".to_string(), "Demonstrates critical thinking".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 11,
-            grade_level: "grade_9_12".to_string(),
-            domain: "science".to_string(),
-            difficulty: 5,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this medical image. Explain how vaccines work to protect the body from diseases. Include information about the immune system in your explanation.".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1576091160399-112ba8d25d1d?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "Vaccines work by training the immune system to recognize and fight specific diseases. When you receive a vaccine, it contains weakened or inactive parts of a pathogen that cause the disease. The immune system responds by producing antibodies and memory cells. If you later encounter the actual disease, your immune system recognizes it quickly and can fight it off more effectively. This is why vaccines can prevent serious illnesses.".to_string(),
-            rubric: vec!["Explains vaccine mechanism".to_string(), "Describes immune system response".to_string(), "Uses accurate scientific terms".to_string(), "Shows comprehensive understanding".to_string()],
-        },
-        SpeakingQuestionData {
-            id: 12,
-            grade_level: "grade_9_12".to_string(),
-            domain: "social_studies".to_string(),
-            difficulty: 5,
-            prompt_type: "picture".to_string(),
-            prompt_text: "Look at this global connection image. Discuss the causes and effects of globalization. How has it changed the world we live in?".to_string(),
-            image_url: Some("https://images.unsplash.com/photo-1451187580459-43490279c0fa?w=800&h=600&fit=crop".to_string()),
-            audio_text: None,
-            sample_answer: "Globalization is the increasing interconnectedness of countries through trade, technology, and cultural exchange. Causes include advances in transportation and communication technology. The effects are significant: economies are more connected, with products made in one country sold worldwide. Cultures influence each other, leading to both cultural exchange and concerns about losing traditions. While globalization has created economic opportunities, it has also raised concerns about inequality and environmental impact.".to_string(),
-            rubric: vec!["Defines globalization".to_string(), "Identifies multiple causes".to_string(), "Discusses various effects".to_string(), "Shows nuanced understanding".to_string()],
-        },
-    ]
-}
+            domain: "social".to_string(),
+            difficulty,
+            passage: "The cat and the dog play.".to_string(),
+            question_text: text.to_string(),
+            question_type: "short_answer".to_string(),
+            options: Vec::new(),
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: Some(pattern.to_string()),
+            explanation: None,
+            source_article_id: None,
+        };
+        // 标成最难的题其实人人都答对，标成最易的题大多答错
+        db.save_reading_questions(&[question("Who plays?", 6, "cat"), question("Which pet?", 1, "dog"), question("How many?", 3, "two")])
+            .unwrap();
+
+        let run_session = |correct_second: bool, correct_third: bool| {
+            let session = db
+                .start_wida_test(&crate::models::StartWidaTestRequest {
+                    user_name: "default".to_string(),
+                    test_type: "reading".to_string(),
+                    grade_level: "grade_3_5".to_string(),
+                    domain: None,
+                    question_count: 3,
+                    blueprint_id: None,
+                    max_audio_plays: None,
+                    sample_count: None,
+                    tags: None,
+                })
+                .unwrap();
+            for q in db.get_wida_test_questions(session.id).unwrap().as_array().unwrap() {
+                let answer = match q["question_text"].as_str().unwrap() {
+                    "Who plays?" => "the cat",
+                    "Which pet?" if correct_second => "dog",
+                    "How many?" if correct_third => "two",
+                    _ => "no idea",
+                };
+                db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+                    session_id: session.id,
+                    question_id: q["id"].as_i64().unwrap(),
+                    answer: answer.to_string(),
+                    time_spent_seconds: 5,
+                })
+                .unwrap();
+            }
+            db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        };
 
-fn get_default_writing_questions() -> Vec<WritingQuestionData> {
-    vec![
-        WritingQuestionData {
-            id: 1,
-            grade_level: "grade_1_2".to_string(),
-            domain: "social_instructional".to_string(),
-            difficulty: 1,
-            task_type: "personal_recount".to_string(),
-            prompt: "Write about your favorite day of the week. What do you do on that day? Why do you like it?".to_string(),
-            image_url: None,
-            word_limit_min: 20,
-            word_limit_max: 50,
-            rubric: vec!["Writes complete sentences".to_string(), "Includes activities".to_string(), "Explains why it is favorite".to_string(), "Uses correct spelling".to_string()],
-            sample_answer: Some("My favorite day is Saturday. On Saturday, I don't go to school. I play with my friends in the park. We play soccer and ride bikes. I like Saturday because I can sleep late and have fun all day.".to_string()),
-        },
-        WritingQuestionData {
-            id: 2,
-            grade_level: "grade_3_5".to_string(),
-            domain: "language_arts".to_string(),
-            difficulty: 2,
-            task_type: "personal_recount".to_string(),
-            prompt: "Write a story about a time when you helped someone. Describe what happened and how you felt.".to_string(),
-            image_url: None,
-            word_limit_min: 50,
-            word_limit_max: 100,
-            rubric: vec!["Tells a complete story".to_string(), "Includes feelings and reactions".to_string(), "Uses descriptive language".to_string(), "Has beginning, middle, and end".to_string()],
-            sample_answer: Some("Last week, I helped my grandmother carry groceries from the car. She had many heavy bags, and she walks slowly. I ran to help her and carried the heaviest bags into the house. She smiled and thanked me. I felt happy and proud that I could help.".to_string()),
-        },
-        WritingQuestionData {
-            id: 3,
-            grade_level: "grade_6_8".to_string(),
-            domain: "social_studies".to_string(),
-            difficulty: 4,
-            task_type: "argumentative".to_string(),
-            prompt: "Should students have homework every day? Write an essay stating your opinion and supporting it with reasons and examples.".to_string(),
-            image_url: None,
-            word_limit_min: 100,
-            word_limit_max: 200,
-            rubric: vec!["States clear opinion".to_string(), "Provides multiple supporting reasons".to_string(), "Uses examples to support arguments".to_string(), "Organizes essay logically".to_string()],
-            sample_answer: Some("I believe students should not have homework every day. First, children need time to rest and play. Second, many students have activities after school. Third, spending time with family is important. A better approach would be to have homework only a few times a week.".to_string()),
-        },
-    ]
-}
+        for i in 0..4 {
+            run_session(i == 0, i < 3);
+        }
+        // 作答不足 5 次时不校准
+        assert_eq!(db.recalibrate_difficulties(true).unwrap().examined, 0);
+        run_session(false, false);
+
+        let preview = db.recalibrate_difficulties(true).unwrap();
+        // 第三题得分率 60%，与标注难度 3 一致
+        assert_eq!((preview.examined, preview.updated), (3, 0));
+        let mut flagged: Vec<(String, i32, i32, i32)> = preview
+            .flagged
+            .iter()
+            .map(|f| (db.get_wida_question_info("reading", f.question_id).unwrap().0, f.stored_difficulty, f.empirical_difficulty, f.attempts))
+            .collect();
+        flagged.sort();
+        // 得分率 100% → 1；20% → 6
+        assert_eq!(flagged, vec![("Which pet?".to_string(), 1, 6, 5), ("Who plays?".to_string(), 6, 1, 5)]);
+        let difficulties = || -> Vec<i32> {
+            db.get_wida_reading_questions("grade_3_5", None, None).unwrap().iter().map(|q| q.difficulty).collect()
+        };
+        assert_eq!(difficulties(), vec![6, 1, 3]);
 
-// ========== 记忆曲线测试模块 ==========
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    /// 创建测试数据库
-    fn create_test_db() -> DatabaseManager {
-        let conn = Connection::open_in_memory().unwrap();
-        let db = DatabaseManager { conn };
-        db.initialize_schema().unwrap();
-        db
+        let applied = db.recalibrate_difficulties(false).unwrap();
+        assert_eq!(applied.updated, 2);
+        assert_eq!(difficulties(), vec![1, 6, 3]);
+        assert!(db.recalibrate_difficulties(true).unwrap().flagged.is_empty());
     }
-    
-    /// 创建测试文章和分词
-    fn setup_test_data(db: &mut DatabaseManager) -> (i64, i64, i64) {
-        // 创建文章
-        db.create_article("测试文章", "这是一篇测试文章").unwrap();
-        
-        // 添加分词
-        let article_id = 1;
-        let segments_vec: Vec<String> = vec![
-            "apple".to_string(), "banana".to_string(), "cherry".to_string(),
-            "date".to_string(), "elder".to_string()
-        ];
-        db.save_segments(article_id, "word", &segments_vec).unwrap();
-        
-        // 获取分词 ID（按 order_index 排序）
-        let segments = db.get_segments(article_id, "word").unwrap();
-        assert_eq!(segments.len(), 5);
-        
-        (article_id, segments[0].id, segments[1].id)
+
+    /// 测试 75: 后端会话计时（暂停、继续、限时到期）
+    #[test]
+    fn test_session_timers() {
+        use crate::commands::timer::SessionTimers;
+        use std::time::{Duration, Instant};
+
+        let mut timers = SessionTimers::default();
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+
+        let start = timers.start("practice", 1, None, t0);
+        assert_eq!((start.elapsed_seconds, start.remaining_seconds, start.running), (0, None, true));
+        timers.start("wida", 7, Some(Duration::from_secs(10)), t0);
+
+        let ticks = timers.tick(at(3));
+        assert_eq!(ticks.len(), 2);
+        assert_eq!((ticks[0].kind.as_str(), ticks[0].elapsed_seconds), ("practice", 3));
+        assert_eq!(ticks[1].remaining_seconds, Some(7));
+
+        // 暂停期间不计时，也不发出 tick
+        assert_eq!(timers.pause("practice", 1, at(4)).unwrap().elapsed_seconds, 4);
+        assert_eq!(timers.tick(at(6)).len(), 1);
+        assert_eq!(timers.get("practice", 1, at(9)).unwrap().elapsed_seconds, 4);
+        timers.resume("practice", 1, at(9)).unwrap();
+        assert_eq!(timers.get("practice", 1, at(11)).unwrap().elapsed_seconds, 6);
+
+        // 限时到期：最后一次标记 expired，之后计时器移除
+        let ticks = timers.tick(at(12));
+        let wida = ticks.iter().find(|t| t.kind == "wida").unwrap();
+        assert!(wida.expired);
+        assert_eq!(wida.remaining_seconds, Some(0));
+        assert!(timers.get("wida", 7, at(13)).is_none());
+
+        assert_eq!(timers.stop("practice", 1, at(15)).unwrap().elapsed_seconds, 10);
+        assert!(timers.tick(at(16)).is_empty());
+        assert!(timers.pause("practice", 1, at(16)).is_none());
     }
-    
-    /// 测试 1: 新单词答对 → 熟练度变为 1，间隔 1 天
+
+    /// 测试 76: 数据目录迁移（目录解析、文件复制、数据库副本和路径改写）
     #[test]
-    fn test_new_word_correct() {
+    fn test_data_directory_migration() {
+        use crate::paths;
+        let root = std::env::temp_dir().join(format!("spelling-data-dir-{}", uuid::Uuid::new_v4().simple()));
+        let (default_dir, exe_dir, custom) = (root.join("default"), root.join("exe"), root.join("custom"));
+        std::fs::create_dir_all(&exe_dir).unwrap();
+
+        // 系统默认 → 自定义目录 → 便携模式优先
+        assert_eq!(paths::resolve_data_dir(&default_dir, Some(&exe_dir)), default_dir);
+        paths::set_custom_data_dir(&default_dir, Some(&custom)).unwrap();
+        assert_eq!(paths::resolve_data_dir(&default_dir, Some(&exe_dir)), custom);
+        std::fs::write(exe_dir.join(paths::PORTABLE_MARKER), b"").unwrap();
+        assert_eq!(paths::resolve_data_dir(&default_dir, Some(&exe_dir)), exe_dir.join("data"));
+        paths::set_custom_data_dir(&default_dir, None).unwrap();
+        assert_eq!(paths::resolve_data_dir(&default_dir, None), default_dir);
+
+        // 复制设置和图片，不复制数据库文件
+        std::fs::create_dir_all(default_dir.join("word_images")).unwrap();
+        std::fs::write(default_dir.join("image_api_settings.json"), "{}").unwrap();
+        std::fs::write(default_dir.join("word_images").join("apple.png"), b"png").unwrap();
+        std::fs::write(default_dir.join(paths::DB_FILE), b"old").unwrap();
+        assert_eq!(paths::copy_data_files(&default_dir, &custom).unwrap(), 2);
+        assert!(custom.join("word_images").join("apple.png").exists());
+        assert!(!custom.join(paths::DB_FILE).exists());
+
+        // 数据库副本包含原有数据，文件路径改到新目录
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 答对新单词
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        
-        assert_eq!(result.mastery_level, 1);
-        assert_eq!(result.interval_days, 1);
-        assert_eq!(result.review_count, 1);
+        let (article_id, _, _) = setup_test_data(&mut db);
+        let old_image = format!("{}/word_images/apple.png", default_dir.display());
+        db.set_word_image("apple", &old_image, "upload", None).unwrap();
+        db.set_word_image("banana", "/elsewhere/banana.png", "upload", None).unwrap();
+        let copy_path = custom.join(paths::DB_FILE);
+        db.backup_to(&copy_path).unwrap();
+        let copy = DatabaseManager::new(&copy_path).unwrap();
+        assert_eq!(copy.get_segments(article_id, "word").unwrap().len(), 5);
+        let updated = copy
+            .relocate_file_paths(&format!("{}/", default_dir.display()), &format!("{}/", custom.display()))
+            .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(
+            copy.get_word_image("apple").unwrap().unwrap().file_path,
+            format!("{}/word_images/apple.png", custom.display())
+        );
+        assert_eq!(copy.get_word_image("banana").unwrap().unwrap().file_path, "/elsewhere/banana.png");
+        assert_eq!(db.get_word_image("apple").unwrap().unwrap().file_path, old_image);
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
-    
-    /// 测试 2: 新单词答错 → 熟练度保持 0，间隔 0 天
+
+    /// 测试 77: 学习状态按内容导出，导入到文章 ID 不同的数据库
     #[test]
-    fn test_new_word_incorrect() {
-        let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 答错新单词
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        assert_eq!(result.mastery_level, 0);
-        assert_eq!(result.interval_days, 0);
-        assert_eq!(result.review_count, 0);
+    fn test_learning_state_export_import() {
+        let mut source = create_test_db();
+        let (article_id, seg_apple, seg_banana) = setup_test_data(&mut source);
+        let seg_elder = source.get_segments(article_id, "word").unwrap()[4].id;
+        source.update_word_mastery("alice", seg_apple, "apple", "word", true).unwrap();
+        source.update_word_mastery("alice", seg_apple, "apple", "word", true).unwrap();
+        source.update_word_mastery("alice", seg_elder, "elder", "word", true).unwrap();
+        source.add_mistake("alice", seg_banana, "banana", "word").unwrap();
+        source.save_practice_history("alice", article_id, "word", 8, 2, 60).unwrap();
+        source.conn.execute(
+            "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy,
+                                            total_questions, correct_count, duration_seconds, completed_at)
+             VALUES ('alice', 'listening', '3-5', 320, 3, 60, 10, 6, 300, '2026-09-01 10:00:00')",
+            [],
+        ).unwrap();
+        source.set_keyboard_layout("alice", "azerty").unwrap();
+        source.set_new_word_pacing("alice", Some(5), true).unwrap();
+
+        let export = source.export_learning_state("alice").unwrap();
+        assert_eq!(export.mastery.len(), 2);
+        assert_eq!(export.practice_history[0].article_title, "测试文章");
+        // 经过 JSON 往返
+        let export: crate::models::LearningStateExport =
+            serde_json::from_str(&serde_json::to_string(&export).unwrap()).unwrap();
+
+        // 目标库中文章 ID 不同，且没有 elder
+        let mut target = create_test_db();
+        let other = target.create_article("其他文章", "zebra").unwrap();
+        target.save_segments(other, "word", &["zebra".to_string()]).unwrap();
+        let article = target.create_article("测试文章", "这是一篇测试文章").unwrap();
+        let words: Vec<String> = ["Apple", "banana", "cherry"].iter().map(|w| w.to_string()).collect();
+        target.save_segments(article, "word", &words).unwrap();
+        let segments = target.get_segments(article, "word").unwrap();
+        assert_ne!(segments[0].id, seg_apple);
+
+        let result = target.import_learning_state("alice", &export).unwrap();
+        assert_eq!(result.mastery_imported, 1);
+        assert_eq!(result.mistakes_imported, 1);
+        assert_eq!(result.practice_imported, 1);
+        assert_eq!(result.wida_imported, 1);
+        assert_eq!(result.unmatched_words, vec!["elder".to_string()]);
+
+        let mastery = |db: &DatabaseManager, segment_id: i64| -> (i32, i32) {
+            db.conn
+                .query_row(
+                    "SELECT mastery_level, review_count FROM word_mastery WHERE user_name = 'alice' AND segment_id = ?",
+                    [segment_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap()
+        };
+        assert_eq!(mastery(&target, segments[0].id), mastery(&source, seg_apple));
+        assert_eq!(mastery(&target, segments[0].id).1, 2);
+        let history_article: i64 = target
+            .conn
+            .query_row("SELECT article_id FROM practice_history WHERE user_name = 'alice'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(history_article, article);
+        assert_eq!(target.get_keyboard_layout("alice").unwrap(), "azerty");
+        assert_eq!(target.get_new_word_pacing("alice").unwrap().daily_limit, Some(5));
+
+        // 重复导入不产生重复记录
+        let again = target.import_learning_state("alice", &export).unwrap();
+        assert_eq!((again.mastery_imported, again.mistakes_imported, again.practice_imported, again.wida_imported), (0, 0, 0, 0));
     }
-    
-    /// 测试 3: 已学习单词答对 → 熟练度 +1
+
+    /// 测试 78: 单元包导出和导入
     #[test]
-    fn test_existing_word_correct() {
+    fn test_spelling_pack_roundtrip() {
+        let mut source = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut source);
+        source.set_article_tags(article_id, &["unit-3".to_string()]).unwrap();
+        source.save_segments(article_id, "sentence", &["Apples are red.".to_string()]).unwrap();
+        source.save_segment_timings(article_id, "sentence", &[(0, 1500)]).unwrap();
+        source.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "science".to_string(),
+            difficulty: 2,
+            audio_text: "Apples are red.".to_string(),
+            image_url: None,
+            question_text: "What color are apples?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Red".to_string(), "Blue".to_string()],
+            correct_answer: 0,
+            correct_answers: vec![],
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: Some(article_id),
+        }]).unwrap();
+
+        let article = source.export_pack_article(article_id).unwrap().unwrap();
+        assert_eq!(article.segments.len(), 6);
+        assert_eq!(article.listening_questions.len(), 1);
+        assert!(source.export_pack_article(999).unwrap().is_none());
+        let bytes = crate::packs::write_pack("Unit 3", None, &[article], &[("0123456789abcdef.aiff".to_string(), b"audio".to_vec())]).unwrap();
+
+        let pack = crate::packs::read_pack(&bytes).unwrap();
+        assert_eq!(pack.manifest.version, crate::packs::PACK_VERSION);
+        assert_eq!(pack.audio.len(), 1);
+
+        // 目标库中先有另一篇文章，导入后 ID 不同
+        let target = create_test_db();
+        target.create_article("其他文章", "zebra").unwrap();
+        let imported = target.import_pack_articles(&pack.articles).unwrap();
+        let (new_id, questions) = imported[0];
+        assert_eq!(questions, 1);
+        assert_ne!(new_id, article_id);
+        assert_eq!(target.get_segments(new_id, "word").unwrap().len(), 5);
+        assert_eq!(target.get_segment_timings(new_id, "sentence").unwrap()[0].end_ms, 1500);
+        assert_eq!(target.get_article_tags(new_id).unwrap(), vec!["unit-3".to_string()]);
+        let copied = target.export_pack_article(new_id).unwrap().unwrap();
+        assert_eq!(copied.listening_questions[0].question_text, "What color are apples?");
+    }
+
+    /// 测试 82: 邮件报告：按周期统计并渲染正文
+    #[test]
+    fn test_report_email() {
+        use crate::commands::email::render_report_email;
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 先答对，熟练度变为 1
-        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        
-        // 再次答对，熟练度变为 2
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        
-        assert_eq!(result.mastery_level, 2);
-        assert_eq!(result.interval_days, 3); // 熟练度 2 → 间隔 3 天
+        let (article_id, _, _) = setup_test_data(&mut db);
+        db.save_practice_history("Tom <b>", article_id, "word", 8, 2, 150).unwrap();
+        db.save_practice_history("Tom <b>", article_id, "word", 10, 0, 90).unwrap();
+        db.conn
+            .execute("INSERT INTO practice_history (user_name, article_id, segment_type, correct_count, incorrect_count, total_count, accuracy, wpm, duration_seconds, completed_at)
+                      VALUES ('Tom <b>', ?1, 'word', 5, 5, 10, 50, 10, 60, datetime('now', '-20 days'))", [article_id])
+            .unwrap();
+
+        let week = db.get_practice_summary("Tom <b>", 7).unwrap();
+        assert_eq!((week.practices, week.words_practiced), (2, 20));
+        assert_eq!(db.get_weekly_summary("Tom <b>").unwrap().practices, 2);
+        let month = db.get_practice_summary("Tom <b>", 30).unwrap();
+        assert_eq!((month.practices, month.words_practiced), (3, 30));
+
+        let email = render_report_email(&week, "week", Some("en"));
+        assert_eq!(email.subject, "Weekly learning report for Tom <b>");
+        assert!(email.text.contains("Words practiced: 20"));
+        assert!(email.text.contains("Time practiced: 4 min"));
+        // HTML 正文中转义用户名
+        assert!(email.html.contains("Tom &lt;b&gt;"));
+        assert!(!email.html.contains("Tom <b>"));
+
+        let email = render_report_email(&month, "month", Some("zh"));
+        assert_eq!(email.subject, "Tom <b> 的每月学习报告");
+        assert!(email.text.contains("练习次数: 3"));
     }
-    
-    /// 测试 4: 已学习单词答错 → 熟练度 -1，间隔重置
+
+    /// 测试 83: 成绩册 CSV：完成的作业和 WIDA 测试
     #[test]
-    fn test_existing_word_incorrect() {
+    fn test_gradebook_csv() {
+        use crate::commands::dashboard::gradebook_csv;
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 先答对，熟练度变为 1
-        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        
-        // 答错，熟练度变为 0
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        assert_eq!(result.mastery_level, 0);
-        assert_eq!(result.interval_days, 0); // 立即需要复习
+        let (article_id, _, _) = setup_test_data(&mut db);
+        let assignment = |user_name: &str, title: &str| crate::models::CreateAssignmentRequest {
+            user_name: user_name.to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            title: title.to_string(),
+            due_date: None,
+            check_options: None,
+        };
+        db.create_assignment(&assignment("Tom", "Unit 1, spelling")).unwrap();
+        db.create_assignment(&assignment("=cmd", "")).unwrap();
+        db.create_assignment(&assignment("Amy", "Not done")).unwrap();
+        db.conn.execute("UPDATE assignments SET created_at = '2024-01-01 00:00:00'", []).unwrap();
+        db.save_practice_history("Tom", article_id, "word", 9, 1, 60).unwrap();
+        db.save_practice_history("Tom", article_id, "word", 5, 5, 60).unwrap();
+        db.save_practice_history("=cmd", article_id, "word", 3, 1, 60).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO wida_test_history (user_name, test_type, grade_level, score, proficiency_level, accuracy, total_questions, correct_count, duration_seconds, completed_at)
+                 VALUES ('Tom', 'listening', 'grade_3_5', 350, 3, 66.7, 12, 8, 600, '2024-02-01 10:00:00')",
+                [],
+            )
+            .unwrap();
+
+        let entries = db.get_gradebook(None, None).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, "wida");
+        let tom = entries.iter().find(|e| e.student == "Tom" && e.kind == "assignment").unwrap();
+        assert_eq!(tom.score, Some(90.0));     // 完成作业的那次练习，而不是之后的
+        let untitled = entries.iter().find(|e| e.student == "=cmd").unwrap();
+        assert_eq!(untitled.assignment, "测试文章");
+
+        assert_eq!(db.get_gradebook(Some("Tom"), None).unwrap().len(), 2);
+        assert_eq!(db.get_gradebook(None, Some("2024-03-01")).unwrap().len(), 2);
+
+        let csv = gradebook_csv(&entries);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "Student,Assignment,Score,Date");
+        assert_eq!(lines[1], "Tom,WIDA listening (grade_3_5),67,2024-02-01");
+        assert!(csv.contains("Tom,\"Unit 1, spelling\",90,"));
+        assert!(csv.contains("'=cmd,测试文章,75,"));
     }
-    
-    /// 测试 5: 熟练度达到 5 后答对 → 保持 5，间隔 30 天
+
+    /// 测试 85: 文章译文按段落对齐
     #[test]
-    fn test_max_mastery_level() {
+    fn test_article_translation_alignment() {
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 连续答对 5 次，达到熟练度 5
-        for _ in 0..5 {
-            db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        }
-        
-        // 第 6 次答对，应该保持熟练度 5
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        
-        assert_eq!(result.mastery_level, 5);
-        assert_eq!(result.interval_days, 30); // 熟练度 5 → 间隔 30 天
+        let article_id = db
+            .create_article("Seasons", "Spring is warm.\nFlowers bloom.\n\nSummer is hot.\n\nWinter is cold.")
+            .unwrap();
+        assert_eq!(crate::text::split_paragraphs("line one\r\nline two\n"), vec!["line one", "line two"]);
+
+        let aligned = db.save_article_translation(article_id, "ZH", "春天很暖和。\n花开了。\n\n夏天很热。").unwrap();
+        assert_eq!(aligned.len(), 3);
+        assert_eq!(aligned[0].original.as_deref(), Some("Spring is warm. Flowers bloom."));
+        assert_eq!(aligned[0].translation.as_deref(), Some("春天很暖和。 花开了。"));
+        assert_eq!(aligned[1].translation.as_deref(), Some("夏天很热。"));
+        // 译文段数较少时，多出的原文没有对应译文
+        assert_eq!(aligned[2].original.as_deref(), Some("Winter is cold."));
+        assert!(aligned[2].translation.is_none());
+        assert_eq!(db.get_translation_languages(article_id).unwrap(), vec!["zh"]);
+
+        // 重新保存替换旧译文，空内容删除
+        let aligned = db.save_article_translation(article_id, "zh", "春\n\n夏\n\n冬\n\n多余的一段").unwrap();
+        assert_eq!(aligned.len(), 4);
+        assert!(aligned[3].original.is_none());
+        assert_eq!(db.get_aligned_paragraphs(article_id, "zh").unwrap()[2].translation.as_deref(), Some("冬"));
+        db.save_article_translation(article_id, "zh", "  ").unwrap();
+        assert!(db.get_translation_languages(article_id).unwrap().is_empty());
+
+        assert!(db.save_article_translation(article_id, " ", "x").is_err());
+        assert!(db.get_aligned_paragraphs(999, "zh").is_err());
     }
-    
-    /// 测试 6: 熟练度为 0 后答错 → 保持 0
+
+    /// 测试 87: 分块练习设置随练习会话保存
     #[test]
-    fn test_min_mastery_level() {
+    fn test_chunked_syllable_practice() {
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 答错，熟练度为 0
-        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        // 再次答错，应该保持 0
-        let result = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        assert_eq!(result.mastery_level, 0);
+        let (article_id, seg1, _) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![crate::models::SessionItem { segment_id: seg1, content: "apple".to_string() }],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: true,
+            check_options: None,
+        }).unwrap();
+        assert!(db.get_practice_session(session_id).unwrap().unwrap().chunked);
     }
-    
-    /// 测试 7: 文章没有分词 → 返回空
+
+    /// 测试 88: 同音词混淆的记录与对比练习
     #[test]
-    fn test_no_segments() {
+    fn test_homophone_confusions() {
         let db = create_test_db();
-        let _ = db.create_article("空文章", "内容").unwrap();
-        
-        let result = db.get_scheduled_words("default", 1, "word", 10).unwrap();
-        
-        assert!(result.words.is_empty());
-        assert_eq!(result.new_words_count, 0);
-        assert_eq!(result.review_words_count, 0);
+        db.record_homophone_confusion("Tom", "their", "there").unwrap();
+        db.record_homophone_confusion("Tom", "their", "there").unwrap();
+        db.record_homophone_confusion("Amy", "to", "too").unwrap();
+        let pairs = db.get_homophone_practice("Tom").unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].word.as_str(), pairs[0].confused_with.as_str()), ("their", "there"));
+        assert_eq!(pairs[0].confusion_count, 2);
+
+        // 任一个词答对都计入，连续答对 3 次后不再安排
+        db.record_homophone_success("Tom", "There").unwrap();
+        db.record_homophone_success("Tom", "their").unwrap();
+        assert_eq!(db.get_homophone_practice("Tom").unwrap()[0].correct_streak, 2);
+        db.record_homophone_confusion("Tom", "their", "there").unwrap();
+        assert_eq!(db.get_homophone_practice("Tom").unwrap()[0].correct_streak, 0);
+        for _ in 0..3 {
+            db.record_homophone_success("Tom", "their").unwrap();
+        }
+        assert!(db.get_homophone_practice("Tom").unwrap().is_empty());
+        assert_eq!(db.get_homophone_practice("Amy").unwrap().len(), 1);
     }
-    
-    /// 测试 8: 只有新词 → 返回新词
+
+    /// 测试 89: 作业和练习会话分别保存默写句子的检查选项
     #[test]
-    fn test_only_new_words() {
+    fn test_answer_check_options() {
+        use crate::models::AnswerCheckOptions;
+        let expected = "The cat sat on the mat.";
+        let loose = AnswerCheckOptions { ignore_punctuation: true, ..Default::default() };
+        let exact = AnswerCheckOptions { require_exact: true, ..Default::default() };
+
+        // 开始会话时作业的设置优先
         let mut db = create_test_db();
         let (article_id, _, _) = setup_test_data(&mut db);
-        
-        let result = db.get_scheduled_words("default", article_id, "word", 10).unwrap();
-        
-        assert_eq!(result.words.len(), 5);
-        assert_eq!(result.new_words_count, 5);
-        assert_eq!(result.review_words_count, 0);
-        
-        // 所有都是新词
-        for word in &result.words {
-            assert!(word.is_new);
-            assert_eq!(word.mastery_level, 0);
-        }
+        db.create_assignment(&crate::models::CreateAssignmentRequest {
+            user_name: "Tom".to_string(),
+            article_id,
+            segment_type: "sentence".to_string(),
+            title: "Dictation".to_string(),
+            due_date: None,
+            check_options: Some(loose.clone()),
+        }).unwrap();
+        let assignments = db.get_assignments(Some("Tom"), None).unwrap();
+        assert_eq!(assignments[0].check_options, Some(loose));
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "Tom".to_string(),
+            article_id,
+            segment_type: "sentence".to_string(),
+            items: vec![crate::models::SessionItem { segment_id: 1, content: expected.to_string() }],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: Some(exact.clone()),
+        }).unwrap();
+        assert_eq!(db.get_practice_session(session_id).unwrap().unwrap().check_options, exact);
     }
-    
-    /// 测试 9: 到期复习词优先于新词
+
+    /// 测试 90: 美式/英式拼写按内容合并熟练度
     #[test]
-    fn test_review_words_first() {
+    fn test_spelling_variants() {
         let mut db = create_test_db();
-        let (article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 让第一个单词到期（答错，interval=0）
-        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        let result = db.get_scheduled_words("default", article_id, "word", 5).unwrap();
-        
-        // 到期的复习词应该排在前面
-        assert_eq!(result.words.len(), 5);
-        assert_eq!(result.review_words_count, 1);
-        assert_eq!(result.new_words_count, 4);
-        
-        // 第一个应该是已复习的 apple
-        assert_eq!(result.words[0].content, "apple");
-        assert!(!result.words[0].is_new);
+        setup_test_data(&mut db);
+        db.create_article("US", "color").unwrap();
+        db.create_article("UK", "colour").unwrap();
+        db.save_segments(2, "word", &["color".to_string()]).unwrap();
+        db.save_segments(3, "word", &["colour".to_string()]).unwrap();
+        let us = db.get_segments(2, "word").unwrap()[0].id;
+        let uk = db.get_segments(3, "word").unwrap()[0].id;
+        db.set_content_keyed_mastery("default", true).unwrap();
+        assert!(!db.accept_spelling_variants("default").unwrap());
+        db.update_word_mastery("default", us, "color", "word", true).unwrap();
+        db.update_word_mastery("default", uk, "colour", "word", true).unwrap();
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 2);
+
+        // 开启后两种拼写共用一条熟练度记录，已有的两条合并为一条
+        db.set_accept_spelling_variants("default", true).unwrap();
+        assert!(db.accept_spelling_variants("default").unwrap());
+        assert!(db.content_keyed_mastery("default").unwrap());
+        assert_eq!(db.merge_duplicate_masteries("default").unwrap(), 1);
+        let masteries = db.get_word_masteries("default", None).unwrap();
+        assert_eq!((masteries.len(), masteries[0].segment_id, masteries[0].review_count), (1, us, 2));
+        let updated = db.update_word_mastery("default", uk, "colour", "word", true).unwrap();
+        assert_eq!(updated.segment_id, us);
+        assert_eq!(db.get_word_masteries("default", None).unwrap().len(), 1);
+        let scheduled = db.get_scheduled_words("default", 3, "word", 0, false).unwrap();
+        assert!(scheduled.words.iter().all(|w| !w.is_new));
     }
-    
-    /// 测试 10: 复习词数量超过 limit → 只返回 limit 个
+
+    /// 测试 91: 创建和修改文章时记录检测到的语言，非英文不计算可读性
     #[test]
-    fn test_review_words_exceed_limit() {
+    fn test_article_language_detection() {
+        let db = create_test_db();
+        let english = db.create_article("English", "The cat sat on the mat. The dog ran in the park.").unwrap();
+        let chinese = db.create_article("中文", "今天天气很好，我们一起去公园散步吧。").unwrap();
+        assert_eq!(db.get_article(english).unwrap().unwrap().language.as_deref(), Some("en"));
+        let article = db.get_article(chinese).unwrap().unwrap();
+        assert_eq!(article.language.as_deref(), Some("zh"));
+        assert!(article.reading_grade.is_none());
+        db.update_article(chinese, None, Some("My family likes to play football in the park.")).unwrap();
+        assert_eq!(db.get_article(chinese).unwrap().unwrap().language.as_deref(), Some("en"));
+    }
+
+    /// 测试 92: 西班牙语/法语文章：分词和熟练度记录语言，复习按语言筛选，朗读切换声音
+    #[test]
+    fn test_multi_language_practice() {
+        use crate::text::language::{check_article_language, segments_words};
+        let spanish = check_article_language("El perro corre por el parque todos los días con su dueño y sus amigos.");
+        assert_eq!(spanish.language.as_deref(), Some("es"));
+        assert!(spanish.supported && spanish.warning.is_none());
+        assert!(segments_words(Some("es")) && segments_words(Some("fr")));
+        let french = crate::commands::tts::tts_language(Some("fr-FR")).unwrap();
+        assert_eq!((french.tag, french.voice), ("fr-FR", Some("Thomas")));
+
         let mut db = create_test_db();
-        let (article_id, segment_id1, segment_id2) = setup_test_data(&mut db);
-        
-        // 让两个单词都到期
-        db.update_word_mastery("default", segment_id1, "apple", "word", false).unwrap();
-        db.update_word_mastery("default", segment_id2, "banana", "word", false).unwrap();
-        
-        // limit = 1
-        let result = db.get_scheduled_words("default", article_id, "word", 1).unwrap();
-        
-        assert_eq!(result.words.len(), 1);
-        assert_eq!(result.review_words_count, 1);
+        let (english_id, apple, _) = setup_test_data(&mut db);
+        let spanish_id = db.create_article("Español", "El perro corre por el parque todos los días con su dueño y sus amigos.").unwrap();
+        db.save_segments(spanish_id, "word", &["perro".to_string(), "parque".to_string()]).unwrap();
+        let perro = db.get_segments(spanish_id, "word").unwrap()[0].clone();
+        assert_eq!(perro.language.as_deref(), Some("es"));
+        assert_eq!(db.get_segment(apple).unwrap().unwrap().language, db.get_article(english_id).unwrap().unwrap().language);
+
+        let mastery = db.update_word_mastery("default", perro.id, "perro", "word", false).unwrap();
+        assert_eq!(mastery.language.as_deref(), Some("es"));
+        db.update_word_mastery("default", apple, "apple", "word", false).unwrap();
+        let due = db.get_due_words("default", 10, Some("es")).unwrap();
+        assert_eq!(due.iter().map(|w| w.segment_content.as_str()).collect::<Vec<_>>(), vec!["perro"]);
+        assert_eq!(db.get_due_words("default", 10, None).unwrap().len(), 2);
+
+        let filters = crate::models::CustomSessionFilters {
+            user_name: "default".to_string(),
+            article_ids: None,
+            tags: None,
+            segment_type: Some("word".to_string()),
+            min_mastery: None,
+            max_mastery: None,
+            min_error_count: None,
+            last_seen_after: None,
+            last_seen_before: None,
+            limit: None,
+            order_mode: None,
+            seed: None,
+            language: Some("es".to_string()),
+        };
+        let session = db.build_custom_session(&filters).unwrap();
+        assert_eq!(session.words.iter().map(|w| w.content.as_str()).collect::<Vec<_>>(), vec!["perro", "parque"]);
+
+        // 太短被识别成英文的单词表可以手动改成法语，已有熟练度一起更新
+        let list_id = db.create_article("Mots", "chat chien").unwrap();
+        db.save_segments(list_id, "word", &["chat".to_string(), "chien".to_string()]).unwrap();
+        let chat = db.get_segments(list_id, "word").unwrap()[0].id;
+        db.update_word_mastery("default", chat, "chat", "word", false).unwrap();
+        assert!(db.set_article_language(list_id, "FR").unwrap());
+        assert!(db.get_segments(list_id, "word").unwrap().iter().all(|s| s.language.as_deref() == Some("fr")));
+        assert_eq!(db.get_due_words("default", 10, Some("fr")).unwrap().len(), 1);
+        assert!(db.set_article_language(list_id, "de").is_err());
     }
-    
-    /// 测试 11: 复习词不足 limit → 补充新词
+
+    /// 测试 93: 课程计划：单元进度与计划对比，复习优先安排当前单元的单词
     #[test]
-    fn test_review_words_insufficient() {
+    fn test_curriculum_progress() {
+        use crate::models::CreateCurriculumUnitRequest;
         let mut db = create_test_db();
-        let (article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 让一个单词到期
-        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        // limit = 5，复习词只有 1 个，需要补充 4 个新词
-        let result = db.get_scheduled_words("default", article_id, "word", 5).unwrap();
-        
-        assert_eq!(result.words.len(), 5);
-        assert_eq!(result.review_words_count, 1);
-        assert_eq!(result.new_words_count, 4);
+        let (fruit_id, apple, _) = setup_test_data(&mut db);
+        let berries_id = db.create_article("Berries", "fig grape").unwrap();
+        db.save_segments(berries_id, "word", &["fig".to_string(), "grape".to_string()]).unwrap();
+        let fig = db.get_segments(berries_id, "word").unwrap()[0].id;
+
+        let today = chrono::Local::now().date_naive();
+        let unit = |title: &str, article_id: i64, days: i64| CreateCurriculumUnitRequest {
+            user_name: "default".to_string(),
+            title: title.to_string(),
+            article_ids: vec![article_id],
+            target_date: (today + chrono::Duration::days(days)).format("%Y-%m-%d").to_string(),
+        };
+        let unit1 = db.create_curriculum_unit(&unit("Unit 1", fruit_id, -1)).unwrap();
+        let unit2 = db.create_curriculum_unit(&unit("Unit 2", berries_id, 30)).unwrap();
+        assert!(db.create_curriculum_unit(&CreateCurriculumUnitRequest { target_date: "next week".to_string(), ..unit("x", fruit_id, 1) }).is_err());
+
+        let progress = db.get_curriculum_progress("default").unwrap();
+        assert_eq!(progress.current_unit_id, Some(unit1));
+        assert_eq!(progress.units[0].total_words, 5);
+        assert!(progress.units[0].overdue && !progress.units[1].overdue);
+        assert_eq!(progress.pace, "behind");
+
+        // 当前单元的单词排在更早到期的其他单词前面
+        db.update_word_mastery("default", fig, "fig", "word", false).unwrap();
+        db.conn.execute("UPDATE word_mastery SET next_review_at = '2000-01-01 00:00:00' WHERE segment_id = ?", [fig]).unwrap();
+        db.update_word_mastery("default", apple, "apple", "word", false).unwrap();
+        let due = db.get_due_words("default", 10, None).unwrap();
+        assert_eq!(due[0].segment_content, "apple");
+
+        // 第一单元掌握后，当前单元变为第二单元，进度与计划一致
+        db.conn.execute("INSERT OR IGNORE INTO word_mastery (user_name, segment_id, segment_content, segment_type) SELECT 'default', id, content, segment_type FROM segments WHERE article_id = ?", [fruit_id]).unwrap();
+        db.conn.execute("UPDATE word_mastery SET mastery_level = 5 WHERE segment_content <> 'fig'", []).unwrap();
+        let progress = db.get_curriculum_progress("default").unwrap();
+        assert_eq!(progress.current_unit_id, Some(unit2));
+        assert!(progress.units[0].completed);
+        assert!((progress.actual_progress - 5.0 / 7.0).abs() < 1e-9);
+        assert_eq!(progress.pace, "on_track");
+        assert_eq!(db.get_due_words("default", 10, None).unwrap()[0].segment_content, "fig");
+
+        db.reorder_curriculum_units("default", &[unit2, unit1]).unwrap();
+        let units = db.get_curriculum("default").unwrap();
+        assert_eq!(units.iter().map(|u| u.id).collect::<Vec<_>>(), vec![unit2, unit1]);
+        assert_eq!(units[0].article_ids, vec![berries_id]);
+        assert!(db.delete_curriculum_unit(unit2).unwrap());
+        assert_eq!(db.get_curriculum("default").unwrap().len(), 1);
     }
-    
-    /// 测试 12: 按熟练度排序 → 低的优先
+
+    /// 测试 94: 混合练习：拼写题中按难度穿插听力题，两种结果都记录
     #[test]
-    fn test_sort_by_mastery_level() {
+    fn test_mixed_session() {
         let mut db = create_test_db();
-        let (article_id, segment_id1, segment_id2) = setup_test_data(&mut db);
-        
-        // apple 熟练度 2
-        db.update_word_mastery("default", segment_id1, "apple", "word", true).unwrap(); // 1
-        db.update_word_mastery("default", segment_id1, "apple", "word", true).unwrap(); // 2
-        
-        // banana 熟练度 1
-        db.update_word_mastery("default", segment_id2, "banana", "word", true).unwrap(); // 1
-        
-        // 让两个都到期
-        db.update_word_mastery("default", segment_id1, "apple", "word", false).unwrap();
-        db.update_word_mastery("default", segment_id2, "banana", "word", false).unwrap();
-        
-        let result = db.get_scheduled_words("default", article_id, "word", 5).unwrap();
-        
-        // 熟练度低的应该排在前面
-        assert_eq!(result.words[0].content, "banana"); // 熟练度 1
-        assert_eq!(result.words[1].content, "apple");  // 熟练度 2
+        let (article_id, _, _) = setup_test_data(&mut db);
+        for (grade_level, difficulty) in [("grade_3_5", 6), ("grade_3_5", 1), ("grade_3_5", 3), ("grade_6_8", 1)] {
+            db.conn.execute(
+                "INSERT INTO wida_listening_questions (grade_level, domain, difficulty, audio_text, question_text, options, correct_answer)
+                 VALUES (?, 'science', ?, 'Plants need water.', 'What do plants need?', '[\"water\",\"sand\"]', 0)",
+                rusqlite::params![grade_level, difficulty],
+            ).unwrap();
+        }
+
+        let request = crate::models::BuildMixedSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            limit: 5,
+            quiz_every: Some(2),
+            questions_per_quiz: Some(2),
+        };
+        let session = db.build_mixed_session(&request).unwrap();
+        let kinds: Vec<&str> = session.items.iter().map(|i| i.kind.as_str()).collect();
+        // 题库里只有 3 道该年级段的题，第二次只穿插 1 道
+        assert_eq!(kinds, vec!["spelling", "spelling", "listening", "listening", "spelling", "spelling", "listening", "spelling"]);
+        assert_eq!((session.grade_level.as_str(), session.difficulty), ("grade_3_5", 1));
+        assert_eq!(session.items[2].question.as_ref().unwrap().difficulty, 1);
+        assert_eq!(session.items[3].question.as_ref().unwrap().difficulty, 3);
+        assert!(session.items.iter().enumerate().all(|(i, item)| item.index == i as i32));
+
+        let word = session.items[0].word.clone().unwrap();
+        let result = db.submit_mixed_answer(session.id, 0, &word.content).unwrap();
+        assert!(result.correct);
+        assert_eq!(result.mastery.unwrap().segment_id, word.segment_id);
+        assert!(!db.submit_mixed_answer(session.id, 1, "wrong").unwrap().correct);
+        assert_eq!(db.get_mistakes("default", None).unwrap().len(), 1);
+        let listening = db.submit_mixed_answer(session.id, 2, "0").unwrap();
+        assert!(listening.correct && listening.mastery.is_none());
+        assert_eq!(listening.expected, "water");
+        assert!(!db.submit_mixed_answer(session.id, 3, "1").unwrap().correct);
+        assert!(db.submit_mixed_answer(session.id, 2, "0").is_err());
+        assert!(db.submit_mixed_answer(session.id, 99, "0").is_err());
+
+        let summary = db.get_mixed_session_summary(session.id).unwrap();
+        assert_eq!((summary.total_items, summary.answered), (8, 4));
+        assert_eq!((summary.spelling_total, summary.spelling_correct), (5, 1));
+        assert_eq!((summary.listening_total, summary.listening_correct), (3, 1));
     }
-    
-    /// 测试 13: 重新分词后保留记忆曲线数据
+
+    /// 测试 95: 当天已作答的单词（无论对错）不再由调度出题，明确要求时照常安排
     #[test]
-    fn test_resegment_preserves_mastery() {
+    fn test_answered_words_excluded_same_day() {
         let mut db = create_test_db();
-        let (article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 答错 apple（interval=0，当天到期）
-        db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        
-        // 验证 apple 已学习（因为答错，当天到期）
-        let result1 = db.get_scheduled_words("default", article_id, "word", 10).unwrap();
-        
-        // 找到 apple
-        let apple_before = result1.words.iter().find(|w| w.content == "apple");
-        assert!(apple_before.is_some(), "apple should exist before resegment");
-        assert!(!apple_before.unwrap().is_new, "apple should not be new");
-        
-        // 重新分词：添加一个新词，保持原有词
-        let new_segments: Vec<String> = vec![
-            "apple".to_string(), "banana".to_string(), "new_word".to_string()
-        ];
-        db.save_segments(article_id, "word", &new_segments).unwrap();
-        
-        // 检查分词是否正确插入
-        let segments = db.get_segments(article_id, "word").unwrap();
-        assert_eq!(segments.len(), 3, "Expected 3 segments, got {}", segments.len());
-        
-        // 检查 apple 的熟练度是否保留
-        let result = db.get_scheduled_words("default", article_id, "word", 10).unwrap();
-        
-        // apple 应该是已学习的
-        let apple = result.words.iter().find(|w| w.content == "apple");
-        assert!(apple.is_some(), "apple should exist in scheduled words");
-        let apple = apple.unwrap();
-        assert!(!apple.is_new, "apple should not be new after resegment");
-        
-        // new_word 应该是新的
-        let new_word = result.words.iter().find(|w| w.content == "new_word").unwrap();
-        assert!(new_word.is_new);
+        let (article_id, apple, banana) = setup_test_data(&mut db);
+        db.update_word_mastery("default", apple, "apple", "word", false).unwrap();
+        db.update_word_mastery("default", banana, "banana", "word", true).unwrap();
+
+        let scheduled = db.get_scheduled_words("default", article_id, "word", 0, false).unwrap();
+        let contents: Vec<&str> = scheduled.words.iter().map(|w| w.content.as_str()).collect();
+        assert_eq!(scheduled.words.len(), 3);
+        assert!(!contents.contains(&"apple") && !contents.contains(&"banana"));
+
+        // 答错的 apple 当天到期，明确要求时仍然安排复习
+        let again = db.get_scheduled_words("default", article_id, "word", 0, true).unwrap();
+        assert_eq!(again.words[0].content, "apple");
+
+        // 其他文章里同内容的分词也算已作答；前一天的记录不再生效
+        let other = db.create_article("Fruit", "Apple pie").unwrap();
+        db.save_segments(other, "word", &["Apple".to_string(), "pie".to_string()]).unwrap();
+        let scheduled = db.get_scheduled_words("default", other, "word", 0, false).unwrap();
+        assert_eq!(scheduled.words.iter().map(|w| w.content.as_str()).collect::<Vec<_>>(), vec!["pie"]);
+        db.conn.execute("UPDATE served_log SET served_at = '2000-01-01 08:00:00'", []).unwrap();
+        assert_eq!(db.get_scheduled_words("default", other, "word", 0, false).unwrap().words.len(), 2);
     }
-    
-    /// 测试 14: 难度因子调整
+
+    /// 测试 96: 速度与正确率分析：慢答明显更准时建议放慢速度，并写入每周报告
     #[test]
-    fn test_ease_factor() {
+    fn test_speed_accuracy_profile() {
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 第一次答对 → ease_factor 保持 2.5（初始值）
-        let result1 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert_eq!(result1.ease_factor, 2.5);
-        
-        // 第二次答对 → ease_factor 增加
-        let result2 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert!(result2.ease_factor > 2.5);
-        
-        // 答错 → ease_factor 减少
-        let result3 = db.update_word_mastery("default", segment_id, "apple", "word", false).unwrap();
-        assert!(result3.ease_factor < result2.ease_factor);
-        
-        // ease_factor 应该在 1.3 ~ 3.0 范围内
-        assert!(result3.ease_factor >= 1.3);
+        let (article_id, apple, _) = setup_test_data(&mut db);
+        let session_id = db.create_practice_session(&crate::models::StartPracticeSessionRequest {
+            user_name: "default".to_string(),
+            article_id,
+            segment_type: "word".to_string(),
+            items: vec![crate::models::SessionItem { segment_id: apple, content: "apple".to_string() }],
+            order_mode: None,
+            order_seed: None,
+            goal: None,
+            speed_training: false,
+            tts_rate: None,
+            hint_penalty: None,
+            hint_mastery_threshold: None,
+            chunked: false,
+            check_options: None,
+        }).unwrap();
+        db.conn.execute("UPDATE practice_sessions SET started_at = datetime('now', '-5 seconds') WHERE id = ?", [session_id]).unwrap();
+        let first = db.record_practice_event(session_id, "default", 0, apple, true).unwrap();
+        assert!((4_000..60_000).contains(&first));
+        assert!(db.record_practice_event(session_id, "default", 1, apple, true).unwrap() < 4_000);
+        assert_eq!(db.get_speed_accuracy_profile("default", None).unwrap().recommendation, "insufficient_data");
+
+        // 快答（1-1.9 秒）一半答错，慢答（5-5.9 秒）全对；超过 2 分钟的不计入
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        db.conn.execute("DELETE FROM practice_events", []).unwrap();
+        for i in 0..10 {
+            for (ms, correct) in [(1_000 + i * 100, i % 2 == 0), (3_000 + i * 100, i % 3 != 0), (5_000 + i * 100, true), (300_000, false)] {
+                db.conn.execute(
+                    "INSERT INTO practice_events (user_name, session_id, item_index, segment_id, correct, response_ms, created_at)
+                     VALUES ('default', ?, ?, ?, ?, ?, ?)",
+                    rusqlite::params![session_id, i, apple, correct, ms, now],
+                ).unwrap();
+            }
+        }
+        let profile = db.get_speed_accuracy_profile("default", Some(7)).unwrap();
+        assert_eq!(profile.items, 30);
+        assert_eq!(profile.buckets.iter().map(|b| b.items).collect::<Vec<_>>(), vec![10, 10, 10]);
+        assert_eq!((profile.buckets[0].accuracy, profile.buckets[2].accuracy), (0.5, 1.0));
+        assert!(profile.correlation.unwrap() > 0.0);
+        assert_eq!(profile.recommendation, "slow_down");
+
+        let summary = db.get_weekly_summary("default").unwrap();
+        assert_eq!(summary.speed_accuracy.as_ref().unwrap().recommendation, "slow_down");
+        let email = crate::commands::email::render_report_email(&summary, "week", Some("en"));
+        assert!(email.text.contains("fast 50%, slow 100%"));
     }
-    
-    /// 测试 15: 间隔天数正确计算
+
+    /// 测试 98: 音频复习：只朗读到期的错词，自评更新熟练度，并以 audio_review 模式记入练习历史
     #[test]
-    fn test_interval_days() {
+    fn test_audio_review_session() {
+        use crate::models::{AudioReviewMark, StartAudioReviewRequest};
+
         let mut db = create_test_db();
-        let (_article_id, segment_id, _) = setup_test_data(&mut db);
-        
-        // 熟练度 0 → 间隔 1 天
-        db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        let r1 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert_eq!(r1.interval_days, 3); // 熟练度 2
-        
-        let r2 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert_eq!(r2.interval_days, 7); // 熟练度 3
-        
-        let r3 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert_eq!(r3.interval_days, 14); // 熟练度 4
-        
-        let r4 = db.update_word_mastery("default", segment_id, "apple", "word", true).unwrap();
-        assert_eq!(r4.interval_days, 30); // 熟练度 5
+        let (_article_id, apple, banana) = setup_test_data(&mut db);
+        let cherry = db.get_segments(1, "word").unwrap()[2].id;
+        let request = StartAudioReviewRequest { user_name: "default".to_string(), limit: None, gap_seconds: Some(999), rate: None };
+        assert!(db.start_audio_review(&request).is_err());
+
+        db.add_mistake("default", apple, "apple", "word").unwrap();
+        db.add_mistake("default", apple, "apple", "word").unwrap();
+        db.add_mistake("default", banana, "banana", "word").unwrap();
+        db.add_mistake("default", cherry, "cherry", "word").unwrap();
+        // cherry 刚复习过，还没到期
+        db.update_word_mastery("default", cherry, "cherry", "word", true).unwrap();
+
+        let session = db.start_audio_review(&request).unwrap();
+        assert_eq!(session.gap_seconds, 60);
+        let words: Vec<&str> = session.items.iter().map(|item| item.content.as_str()).collect();
+        assert_eq!(words, vec!["apple", "banana"]);
+        assert_eq!(session.items[1].index, 1);
+
+        assert!(db.is_audio_review_active(session.id).unwrap());
+        assert!(db.stop_audio_review(session.id).unwrap());
+        assert!(!db.stop_audio_review(session.id).unwrap());
+        assert!(!db.is_audio_review_active(session.id).unwrap());
+
+        let mark = |segment_id, correct| AudioReviewMark { segment_id, correct };
+        assert!(db.finish_audio_review(session.id, &[mark(cherry, true)], 60).is_err());
+        assert!(db.finish_audio_review(session.id, &[mark(apple, true), mark(apple, false)], 60).is_err());
+
+        let summary = db.finish_audio_review(session.id, &[mark(apple, true), mark(banana, false)], 90).unwrap();
+        assert_eq!((summary.total, summary.marked, summary.correct), (2, 2, 1));
+        assert!((summary.accuracy - 50.0).abs() < 1e-9);
+        assert!(db.finish_audio_review(session.id, &[], 90).is_err());
+
+        let banana_mistake = db.get_mistakes("default", None).unwrap().into_iter().find(|m| m.segment_id == banana).unwrap();
+        assert_eq!(banana_mistake.error_count, 2);
+        let mastery_level: i32 = db
+            .conn
+            .query_row("SELECT mastery_level FROM word_mastery WHERE user_name = 'default' AND segment_id = ?", [apple], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mastery_level, 1);
+
+        let history = db.get_practice_history("default", 10).unwrap();
+        assert_eq!(history[0].mode, "audio_review");
+        assert_eq!(history[0].duration_seconds, 90);
+        let stats = db.get_user_statistics("default", false).unwrap();
+        let audio = stats.by_mode.iter().find(|m| m.mode == "audio_review").unwrap();
+        assert_eq!((audio.practices, audio.total_words), (1, 2));
+    }
+
+    /// 测试 100: 录音波形：解码 wav 得到时长和峰值包络，保存后随录音元数据返回
+    #[test]
+    fn test_recording_waveform_metadata() {
+        let mut db = create_test_db();
+        let (article_id, _, _) = setup_test_data(&mut db);
+
+        // 8 kHz 单声道 16 位 wav：前 0.5 秒静音，后 0.5 秒为振幅 0.5 的正弦波
+        let rate = 8000u32;
+        let samples: Vec<i16> = (0..rate)
+            .map(|i| if i < rate / 2 { 0 } else { (0.5 * (i as f32 * 0.3).sin() * i16::MAX as f32) as i16 })
+            .collect();
+        let data_len = samples.len() as u32 * 2;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&rate.to_le_bytes());
+        wav.extend_from_slice(&(rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        let path = std::env::temp_dir().join(format!("spelling-waveform-{}.wav", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, &wav).unwrap();
+
+        let audio = crate::waveform::analyze_file(&path).unwrap();
+        assert_eq!(audio.duration_ms, 1000);
+        assert_eq!((audio.sample_rate, audio.channels), (8000, 1));
+        // 10 毫秒一块，共 100 块，不超过 200 个点时不再合并
+        assert_eq!(audio.waveform.len(), 100);
+        assert!(audio.waveform[..50].iter().all(|peak| *peak == 0.0));
+        assert!(audio.waveform[50..].iter().all(|peak| (*peak - 0.5).abs() < 0.02));
+
+        let broken = path.with_extension("m4a");
+        std::fs::write(&broken, b"not audio").unwrap();
+        assert!(crate::waveform::analyze_file(&broken).is_err());
+
+        let recording_id = db.start_reading_recording("default", article_id).unwrap();
+        db.complete_reading_recording(recording_id, &path.to_string_lossy(), Some(1_200), None).unwrap();
+        let metadata = db.get_recording_metadata(recording_id).unwrap().unwrap();
+        assert_eq!(metadata.duration_ms, Some(1_200));
+        assert!(metadata.audio.is_none());
+
+        db.save_recording_audio(recording_id, &audio).unwrap();
+        let metadata = db.get_recording_metadata(recording_id).unwrap().unwrap();
+        assert_eq!(metadata.duration_ms, Some(1000));
+        assert_eq!(metadata.audio.unwrap().waveform, audio.waveform);
+        assert!(db.get_recording_metadata(recording_id + 1).unwrap().is_none());
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&broken);
+    }
+
+    /// 测试 101: 口语转写：识别结果可修改，确认后按转写评分并保存为答案
+    #[test]
+    fn test_speaking_transcript_review() {
+        let db = create_test_db();
+        db.save_speaking_questions(&[crate::commands::wida::GeneratedSpeakingQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "transcript_test".to_string(),
+            difficulty: 2,
+            prompt_type: "text".to_string(),
+            prompt_text: "Describe your favorite animal.".to_string(),
+            image_url: None,
+            audio_text: None,
+            sample_answer: "My favorite animal is the elephant because elephants are smart and gentle.".to_string(),
+            rubric: vec!["Names an animal".to_string(), "Gives a reason".to_string()],
+        }]).unwrap();
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "speaking".to_string(),
+            grade_level: "grade_3_5".to_string(),
+            domain: Some("transcript_test".to_string()),
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+        let question_id: i64 = db.conn.query_row(
+            "SELECT id FROM wida_speaking_questions WHERE domain = 'transcript_test'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        db.submit_wida_answer(&crate::models::SubmitWidaAnswerRequest {
+            session_id: session.id,
+            question_id,
+            answer: String::new(),
+            time_spent_seconds: 30,
+        }).unwrap();
+
+        assert_eq!(crate::text::speaking_rubric::speaking_credit("", "Elephants are smart."), 0.0);
+        assert!(db.save_speaking_stt(session.id, question_id + 1, "hello", None).is_err());
+        assert!(db.confirm_speaking_transcript(session.id, question_id).is_err());
+
+        let stt = db.save_speaking_stt(session.id, question_id, " I like the elefant. ", Some(0.62)).unwrap();
+        assert_eq!(stt.stt_text.as_deref(), Some("I like the elefant."));
+        assert_eq!(stt.status, "pending");
+        assert_eq!(stt.rubric.len(), 2);
+
+        // 未确认时口语题仍按满分计
+        let edited = db
+            .update_speaking_transcript(session.id, question_id, "I like the elephant because elephants are smart.")
+            .unwrap();
+        assert_eq!(edited.stt_text.as_deref(), Some("I like the elefant."));
+        assert!(edited.estimated_credit > stt.estimated_credit);
+        let confirmed = db.confirm_speaking_transcript(session.id, question_id).unwrap();
+        assert_eq!(confirmed.status, "confirmed");
+        assert!(confirmed.confirmed_at.is_some());
+        assert!(db.save_speaking_stt(session.id, question_id, "again", None).is_err());
+
+        let transcripts = db.get_speaking_transcripts(session.id).unwrap();
+        assert_eq!(transcripts.len(), 1);
+        assert_eq!(transcripts[0].transcript, "I like the elephant because elephants are smart.");
+
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!(report.details[0].user_answer, "I like the elephant because elephants are smart.");
+        assert_eq!(report.details[0].credit, confirmed.estimated_credit);
+        assert!(report.details[0].credit < 1.0);
+
+        // 修改后回到待确认
+        let reopened = db.update_speaking_transcript(session.id, question_id, "Elephants.").unwrap();
+        assert_eq!(reopened.status, "pending");
+        assert!(reopened.confirmed_at.is_none());
+
+        db.delete_wida_session(session.id).unwrap();
+        let remaining: i64 = db.conn.query_row("SELECT COUNT(*) FROM wida_speaking_transcripts", [], |row| row.get(0)).unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    /// 测试 102: 监考记录：累计离开窗口和暂停的次数与时长，超过阈值时在报告中标记
+    #[test]
+    fn test_wida_integrity_summary() {
+        let db = create_test_db();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "integrity_test".to_string(),
+            difficulty: 2,
+            audio_text: "Cats sleep a lot.".to_string(),
+            image_url: None,
+            question_text: "What do cats do a lot?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Sleep".to_string(), "Swim".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let session = db.start_wida_test(&crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "listening".to_string(),
+            grade_level: "grade_3_5".to_string(),
+            domain: Some("integrity_test".to_string()),
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        }).unwrap();
+
+        assert!(db.record_wida_focus_event(session.id, "resize", None, 1000).is_err());
+        assert!(db.record_wida_focus_event(session.id, "pause", None, -1).is_err());
+
+        db.record_wida_focus_event(session.id, "focus_loss", None, 4_500).unwrap();
+        db.record_wida_focus_event(session.id, "pause", None, 30_000).unwrap();
+        let summary = db.record_wida_focus_event(session.id, "focus_loss", None, 12_000).unwrap();
+        assert_eq!((summary.focus_loss_count, summary.focus_loss_seconds), (2, 16));
+        assert_eq!(summary.longest_focus_loss_seconds, 12);
+        assert_eq!((summary.pause_count, summary.pause_seconds), (1, 30));
+        assert!(!summary.flagged);
+
+        db.record_wida_focus_event(session.id, "focus_loss", None, 1_000).unwrap();
+        let report = db.complete_wida_test(&crate::models::CompleteWidaTestRequest { session_id: session.id, locale: None }).unwrap();
+        assert_eq!(report.integrity.focus_loss_count, 3);
+        assert!(report.integrity.flagged);
+
+        // 测试结束后不再记录
+        assert!(db.record_wida_focus_event(session.id, "focus_loss", None, 1_000).is_err());
+        db.delete_wida_session(session.id).unwrap();
+        assert_eq!(db.get_wida_integrity_summary(session.id).unwrap().focus_loss_count, 0);
+    }
+
+    /// 测试 103: 题目反馈：学生在测试中反馈问题题目，老师在复核队列中处理
+    #[test]
+    fn test_question_flags() {
+        let db = create_test_db();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
+            grade_level: "grade_3_5".to_string(),
+            domain: "flag_test".to_string(),
+            difficulty: 2,
+            audio_text: "Fish live in water.".to_string(),
+            image_url: None,
+            question_text: "Where do fish live?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Water".to_string(), "Trees".to_string()],
+            correct_answer: 0,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let request = crate::models::StartWidaTestRequest {
+            user_name: "default".to_string(),
+            test_type: "listening".to_string(),
+            grade_level: "grade_3_5".to_string(),
+            domain: Some("flag_test".to_string()),
+            question_count: 1,
+            blueprint_id: None,
+            max_audio_plays: None,
+            sample_count: None,
+            tags: None,
+        };
+        let first = db.start_wida_test(&request).unwrap();
+        let second = db.start_wida_test(&request).unwrap();
+        let question_id: i64 = db.conn.query_row(
+            "SELECT id FROM wida_listening_questions WHERE domain = 'flag_test'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+
+        assert!(db.flag_question(first.id, question_id, "  ").is_err());
+        assert!(db.flag_question(first.id, question_id + 1, "Broken").is_err());
+        assert!(db.flag_question(first.id, question_id, &"x".repeat(501)).is_err());
+
+        let flag = db.flag_question(first.id, question_id, "Audio does not play").unwrap();
+        assert_eq!(flag.question_text, "Where do fish live?");
+        assert_eq!((flag.status.as_str(), flag.open_flags), ("open", 1));
+        // 同一次测试重复反馈只更新说明
+        let flag = db.flag_question(first.id, question_id, " Audio is silent ").unwrap();
+        assert_eq!(flag.reason, "Audio is silent");
+        let other = db.flag_question(second.id, question_id, "Two answers look right").unwrap();
+        assert_eq!(other.open_flags, 2);
+        assert_eq!(db.list_flagged_questions(Some("open")).unwrap().len(), 2);
+
+        assert!(db.resolve_question_flag(flag.id, "deleted").is_err());
+        assert!(db.resolve_question_flag(flag.id, "resolved").unwrap());
+        assert!(!db.resolve_question_flag(flag.id + 100, "dismissed").unwrap());
+        let queue = db.list_flagged_questions(None).unwrap();
+        assert_eq!(queue[0].id, other.id);
+        assert_eq!(queue[0].open_flags, 1);
+        assert_eq!(queue[1].status, "resolved");
+        assert!(queue[1].resolved_at.is_some());
+
+        db.delete_wida_question("listening", question_id).unwrap();
+        assert!(db.list_flagged_questions(None).unwrap().is_empty());
+    }
+
+    /// 测试 104: 内置题库：按年级段补充缺少的内置题目，不与用户的题目重复，force 时恢复内置内容
+    #[test]
+    fn test_reseed_defaults() {
+        let db = create_test_db();
+        let count = |table: &str| -> i64 {
+            db.conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+        db.seed_wida_questions().unwrap();
+        let seeded = [
+            ("wida_listening_questions", crate::seeds::listening_questions().unwrap().len() as i64),
+            ("wida_reading_questions", crate::seeds::reading_questions().unwrap().len() as i64),
+            ("wida_speaking_questions", crate::seeds::speaking_questions().unwrap().len() as i64),
+            ("wida_writing_questions", crate::seeds::writing_questions().unwrap().len() as i64),
+        ];
+        for (table, expected) in seeded {
+            assert!(expected > 0);
+            assert_eq!(count(table), expected);
+        }
+        let total: i64 = seeded.iter().map(|(_, n)| n).sum();
+        let first: String = db.conn.query_row("SELECT question_text FROM wida_listening_questions WHERE id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(first, "What is the speaker's name?");
+
+        // 旧版写入的内置题目没有 seed_id，按编号和内容认领，不重复写入
+        db.conn.execute("UPDATE wida_listening_questions SET seed_id = NULL", []).unwrap();
+        let report = db.reseed_defaults(None, false).unwrap();
+        assert_eq!((report.added, report.reset, report.skipped), (0, 0, 0));
+        assert_eq!(count("wida_listening_questions"), seeded[0].1);
+
+        // 删除的内置题目会补回；用户题目与内置题目相同时跳过
+        db.delete_wida_question("listening", 2).unwrap();
+        db.delete_wida_question("listening", 3).unwrap();
+        db.save_listening_questions(&[crate::commands::wida::GeneratedListeningQuestion {
+            grade_level: "grade_1_2".to_string(),
+            domain: "social_instructional".to_string(),
+            difficulty: 1,
+            audio_text: "There are three apples on the table.".to_string(),
+            image_url: None,
+            question_text: "How many apples are there in total?".to_string(),
+            question_type: "multiple_choice".to_string(),
+            options: vec!["Two".to_string(), "Three".to_string()],
+            correct_answer: 1,
+            correct_answers: Vec::new(),
+            answer_pattern: None,
+            explanation: None,
+            source_article_id: None,
+        }]).unwrap();
+        let report = db.reseed_defaults(None, false).unwrap();
+        assert_eq!((report.added, report.skipped), (1, 1));
+        assert_eq!(count("wida_listening_questions"), seeded[0].1);
+
+        // 只补充指定年级段
+        db.conn.execute("DELETE FROM wida_speaking_questions WHERE grade_level IN ('grade_1_2', 'grade_9_12')", []).unwrap();
+        assert!(db.reseed_defaults(Some(&["grade_13".to_string()]), false).is_err());
+        let report = db.reseed_defaults(Some(&["grade_9_12".to_string()]), false).unwrap();
+        assert!(report.added > 0);
+        let grade_1_2: i64 = db.conn.query_row(
+            "SELECT COUNT(*) FROM wida_speaking_questions WHERE grade_level = 'grade_1_2'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(grade_1_2, 0);
+        db.reseed_defaults(None, false).unwrap();
+        assert_eq!(count("wida_speaking_questions"), seeded[2].1);
+
+        // force 恢复内置内容（如校准改过的难度），不影响用户题目
+        db.conn.execute("UPDATE wida_listening_questions SET difficulty = 6 WHERE seed_id = 1", []).unwrap();
+        let report = db.reseed_defaults(None, true).unwrap();
+        assert_eq!((report.added, report.reset, report.skipped), (0, total as i32 - 1, 1));
+        let difficulty: i32 = db.conn.query_row("SELECT difficulty FROM wida_listening_questions WHERE seed_id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(difficulty, 1);
+        assert_eq!(count("wida_listening_questions"), seeded[0].1);
     }
 }
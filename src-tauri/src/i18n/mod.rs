@@ -0,0 +1,113 @@
+//! 界面文字的多语言资源（en / zh）
+//!
+//! 报告和后端生成的提示语都从这里取，`{name}` 形式的占位符与提示词模板相同。
+//! 找不到的键依次回退到默认语言和键名本身。
+
+use std::collections::HashMap;
+
+/// 未指定或不支持的语言时使用
+pub const DEFAULT_LOCALE: &str = "zh";
+
+/// 支持的语言
+pub const LOCALES: &[&str] = &["zh", "en"];
+
+const ZH: &[(&str, &str)] = &[
+    ("proficiency.1", "起步"),
+    ("proficiency.2", "萌芽"),
+    ("proficiency.3", "发展"),
+    ("proficiency.4", "扩展"),
+    ("proficiency.5", "衔接"),
+    ("proficiency.6", "达标"),
+    ("test_type.listening", "听力"),
+    ("test_type.reading", "阅读"),
+    ("test_type.speaking", "口语"),
+    ("test_type.writing", "写作"),
+    ("report.score", "成绩"),
+    ("report.accuracy", "正确率"),
+    ("report.correct_count", "答对题数"),
+    ("report.proficiency_level", "能力等级"),
+    ("report.replay_count", "播放次数"),
+    ("report.partial_credit", "部分得分"),
+    ("wida.generated", "成功生成 {count} 道{kind}题"),
+    ("wida.generated_offline", "未配置 API Key，已离线生成 {count} 道{kind}题"),
+    ("wida.generated_from_article", "根据《{title}》生成 {count} 道题"),
+    ("email.subject.week", "{user} 的每周学习报告"),
+    ("email.subject.month", "{user} 的每月学习报告"),
+    ("email.practices", "练习次数"),
+    ("email.words_practiced", "练习单词数"),
+    ("email.words_mastered", "新掌握单词"),
+    ("email.time_practiced", "练习时长"),
+    ("email.minutes", "{count} 分钟"),
+    ("email.streak", "连续练习"),
+    ("email.days", "{count} 天"),
+    ("email.pace", "答题节奏"),
+    ("email.slow_down", "放慢速度时更准确（快答 {fast}%，慢答 {slow}%）"),
+    ("email.footer", "本邮件由拼写练习软件自动发送"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("proficiency.1", "Entering"),
+    ("proficiency.2", "Emerging"),
+    ("proficiency.3", "Developing"),
+    ("proficiency.4", "Expanding"),
+    ("proficiency.5", "Bridging"),
+    ("proficiency.6", "Reaching"),
+    ("test_type.listening", "listening"),
+    ("test_type.reading", "reading"),
+    ("test_type.speaking", "speaking"),
+    ("test_type.writing", "writing"),
+    ("report.score", "Score"),
+    ("report.accuracy", "Accuracy"),
+    ("report.correct_count", "Correct answers"),
+    ("report.proficiency_level", "Proficiency level"),
+    ("report.replay_count", "Replays"),
+    ("report.partial_credit", "Partial credit"),
+    ("wida.generated", "Generated {count} {kind} questions"),
+    ("wida.generated_offline", "No API key configured; generated {count} {kind} questions offline"),
+    ("wida.generated_from_article", "Generated {count} questions from \"{title}\""),
+    ("email.subject.week", "Weekly learning report for {user}"),
+    ("email.subject.month", "Monthly learning report for {user}"),
+    ("email.practices", "Practice sessions"),
+    ("email.words_practiced", "Words practiced"),
+    ("email.words_mastered", "Words mastered"),
+    ("email.time_practiced", "Time practiced"),
+    ("email.minutes", "{count} min"),
+    ("email.streak", "Streak"),
+    ("email.days", "{count} days"),
+    ("email.pace", "Pace"),
+    ("email.slow_down", "More accurate when slowing down (fast {fast}%, slow {slow}%)"),
+    ("email.footer", "Sent automatically by Spelling Game"),
+];
+
+fn bundle(locale: &str) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        "en" => EN,
+        _ => ZH,
+    }
+}
+
+/// 规范化语言代码：`en-US` → `en`，不支持的语言返回默认语言
+pub fn normalize(locale: Option<&str>) -> &'static str {
+    let language = locale.unwrap_or(DEFAULT_LOCALE).split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    LOCALES.iter().copied().find(|l| *l == language).unwrap_or(DEFAULT_LOCALE)
+}
+
+/// 取一条文字并替换占位符
+pub fn text(locale: Option<&str>, key: &str, vars: &[(&str, String)]) -> String {
+    let lookup = |bundle: &[(&str, &'static str)]| bundle.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    let template = lookup(bundle(normalize(locale))).or_else(|| lookup(bundle(DEFAULT_LOCALE))).unwrap_or(key);
+    crate::prompts::render(template, vars)
+}
+
+/// WIDA 能力等级（1-6）的名称
+pub fn proficiency_level_name(locale: Option<&str>, level: i32) -> String {
+    text(locale, &format!("proficiency.{}", level), &[])
+}
+
+/// 某种语言的全部文字，缺少的键用默认语言补齐
+pub fn strings(locale: Option<&str>) -> HashMap<String, String> {
+    let mut strings: HashMap<String, String> =
+        bundle(DEFAULT_LOCALE).iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    strings.extend(bundle(normalize(locale)).iter().map(|(k, v)| (k.to_string(), v.to_string())));
+    strings
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+use super::{html, ParsedDocument};
+
+/// 解析后的 EPUB 书籍
+pub struct EpubBook {
+    pub title: String,
+    /// 按书脊（spine）顺序排列的非空章节
+    pub chapters: Vec<ParsedDocument>,
+}
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+/// 打开 EPUB 并按阅读顺序提取所有章节
+pub fn read_epub(path: &Path) -> Result<EpubBook, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("无效的 EPUB 文件: {}", e))?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let opf_path = find_rootfile(&container).ok_or("EPUB 缺少 OPF 文件")?;
+    let opf = read_entry(&mut archive, &opf_path)?;
+    let opf_dir = parent_dir(&opf_path);
+
+    let (book_title, manifest, spine, ncx_id) = parse_opf(&opf)?;
+    let toc_labels = read_toc_labels(&mut archive, &manifest, ncx_id.as_deref(), &opf_dir);
+
+    let mut chapters = Vec::new();
+    for idref in spine {
+        let Some(item) = manifest.get(&idref) else { continue };
+        if !item.media_type.contains("html") {
+            continue;
+        }
+        let chapter_path = resolve_path(&opf_dir, &item.href);
+        let Ok(xhtml) = read_entry(&mut archive, &chapter_path) else { continue };
+        let (heading, content) = html::body_text(&xhtml);
+        // 封面、插图页等没有正文的章节直接跳过
+        if content.is_empty() {
+            continue;
+        }
+        let title = toc_labels
+            .get(&chapter_path)
+            .cloned()
+            .or(heading)
+            .unwrap_or_else(|| format!("第 {} 章", chapters.len() + 1));
+        chapters.push(ParsedDocument { title, content, word_list: None, cues: None });
+    }
+
+    if chapters.is_empty() {
+        return Err("EPUB 中没有找到章节正文".to_string());
+    }
+
+    let title = book_title.unwrap_or_else(|| {
+        path.file_stem().and_then(|s| s.to_str()).unwrap_or("未命名").to_string()
+    });
+    Ok(EpubBook { title, chapters })
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<String, String> {
+    let mut entry = archive.by_name(name).map_err(|e| format!("{}: {}", name, e))?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content).map_err(|e| format!("{}: {}", name, e))?;
+    Ok(content)
+}
+
+fn find_rootfile(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"rootfile" => {
+                return attr(&e, b"full-path");
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+type OpfContent = (Option<String>, HashMap<String, ManifestItem>, Vec<String>, Option<String>);
+
+/// 解析 OPF：书名、manifest、spine 顺序和 NCX 目录 id
+fn parse_opf(opf: &str) -> Result<OpfContent, String> {
+    let mut reader = Reader::from_str(opf);
+    let mut title = None;
+    let mut in_title = false;
+    let mut manifest = HashMap::new();
+    let mut spine = Vec::new();
+    let mut ncx_id = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"title" if title.is_none() => in_title = true,
+                b"item" => {
+                    if let (Some(id), Some(href)) = (attr(&e, b"id"), attr(&e, b"href")) {
+                        manifest.insert(
+                            id,
+                            ManifestItem {
+                                href,
+                                media_type: attr(&e, b"media-type").unwrap_or_default(),
+                                properties: attr(&e, b"properties").unwrap_or_default(),
+                            },
+                        );
+                    }
+                }
+                b"spine" => ncx_id = attr(&e, b"toc"),
+                b"itemref" => {
+                    if let Some(idref) = attr(&e, b"idref") {
+                        spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(t)) if in_title => {
+                let text = t.unescape().map_err(|e| e.to_string())?.trim().to_string();
+                if !text.is_empty() {
+                    title = Some(text);
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"title" => in_title = false,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("OPF 解析失败: {}", e)),
+            _ => {}
+        }
+    }
+
+    Ok((title, manifest, spine, ncx_id))
+}
+
+/// 读取目录中的章节标题，返回 章节路径 -> 标题（EPUB 3 nav 优先，其次 EPUB 2 NCX）
+fn read_toc_labels(
+    archive: &mut ZipArchive<File>,
+    manifest: &HashMap<String, ManifestItem>,
+    ncx_id: Option<&str>,
+    opf_dir: &str,
+) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+
+    if let Some(nav) = manifest.values().find(|item| item.properties.split_whitespace().any(|p| p == "nav")) {
+        let nav_path = resolve_path(opf_dir, &nav.href);
+        if let Ok(xhtml) = read_entry(archive, &nav_path) {
+            let nav_dir = parent_dir(&nav_path);
+            let document = scraper::Html::parse_document(&xhtml);
+            if let Ok(selector) = scraper::Selector::parse("nav a[href]") {
+                for link in document.select(&selector) {
+                    let label = link.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+                    if let Some(href) = link.value().attr("href") {
+                        insert_label(&mut labels, resolve_path(&nav_dir, href), label);
+                    }
+                }
+            }
+        }
+    }
+
+    let ncx = ncx_id
+        .and_then(|id| manifest.get(id))
+        .or_else(|| manifest.values().find(|item| item.media_type == "application/x-dtbncx+xml"));
+    if let Some(ncx) = ncx {
+        let ncx_path = resolve_path(opf_dir, &ncx.href);
+        if let Ok(xml) = read_entry(archive, &ncx_path) {
+            let ncx_dir = parent_dir(&ncx_path);
+            let mut reader = Reader::from_str(&xml);
+            let mut in_text = false;
+            let mut last_label = String::new();
+            loop {
+                match reader.read_event() {
+                    Ok(Event::Start(e)) if e.local_name().as_ref() == b"text" => in_text = true,
+                    Ok(Event::End(e)) if e.local_name().as_ref() == b"text" => in_text = false,
+                    Ok(Event::Text(t)) if in_text => {
+                        last_label = t.unescape().map(|s| s.trim().to_string()).unwrap_or_default();
+                    }
+                    Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"content" => {
+                        if let Some(src) = attr(&e, b"src") {
+                            insert_label(&mut labels, resolve_path(&ncx_dir, &src), last_label.clone());
+                        }
+                    }
+                    Ok(Event::Eof) | Err(_) => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    labels
+}
+
+/// 同一文件有多个目录项时保留第一个（章节标题，而非小节标题）
+fn insert_label(labels: &mut HashMap<String, String>, path: String, label: String) {
+    if !label.is_empty() {
+        labels.entry(path).or_insert(label);
+    }
+}
+
+fn attr(element: &BytesStart<'_>, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.to_string())
+}
+
+fn parent_dir(path: &str) -> String {
+    path.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default()
+}
+
+/// 把相对链接解析为压缩包内路径（去掉锚点，处理 `..` 和 URL 编码）
+fn resolve_path(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or("");
+    let href = percent_decode(href);
+    let mut parts: Vec<&str> = if base_dir.is_empty() { Vec::new() } else { base_dir.split('/').collect() };
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(resolve_path("OEBPS", "Text/ch1.xhtml#start"), "OEBPS/Text/ch1.xhtml");
+        assert_eq!(resolve_path("OEBPS/Text", "../Text/ch%201.xhtml"), "OEBPS/Text/ch 1.xhtml");
+        assert_eq!(resolve_path("", "./nav.xhtml"), "nav.xhtml");
+        assert_eq!(resolve_path("a", "../../b.xhtml"), "b.xhtml");
+        assert_eq!(percent_decode("%E4%B8%AD%2"), "中%2");
+    }
+
+    /// 按书脊顺序提取章节：目录标题优先，其次正文标题，跳过没有正文的封面
+    #[test]
+    fn test_read_epub_chapters() {
+        let files = [
+            ("META-INF/container.xml", r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#),
+            (
+                "OEBPS/content.opf",
+                r#"<package><metadata><dc:title>Fox Tales</dc:title></metadata>
+                   <manifest>
+                     <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+                     <item id="cover" href="Text/cover.xhtml" media-type="application/xhtml+xml"/>
+                     <item id="ch1" href="Text/ch1.xhtml" media-type="application/xhtml+xml"/>
+                     <item id="ch2" href="Text/ch2.xhtml" media-type="application/xhtml+xml"/>
+                     <item id="css" href="style.css" media-type="text/css"/>
+                   </manifest>
+                   <spine><itemref idref="cover"/><itemref idref="css"/><itemref idref="ch2"/><itemref idref="ch1"/></spine>
+                 </package>"#,
+            ),
+            ("OEBPS/nav.xhtml", r#"<html><body><nav><ol><li><a href="Text/ch1.xhtml#top">Chapter  One</a></li></ol></nav></body></html>"#),
+            ("OEBPS/Text/cover.xhtml", r#"<html><body><img src="cover.png"/></body></html>"#),
+            ("OEBPS/Text/ch1.xhtml", r#"<html><body><h1>Ignored heading</h1><p>The fox ran.</p></body></html>"#),
+            ("OEBPS/Text/ch2.xhtml", r#"<html><body><h2>The Den</h2><p>The fox slept.</p></body></html>"#),
+        ];
+        let path = std::env::temp_dir().join(format!("spelling-epub-test-{}.epub", std::process::id()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        for (name, content) in files {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let book = read_epub(&path);
+        std::fs::remove_file(&path).unwrap();
+        let book = book.unwrap();
+        assert_eq!(book.title, "Fox Tales");
+        let chapters: Vec<(&str, &str)> = book.chapters.iter().map(|c| (c.title.as_str(), c.content.as_str())).collect();
+        assert_eq!(chapters, vec![("The Den", "The Den\nThe fox slept."), ("Chapter One", "Ignored heading\nThe fox ran.")]);
+    }
+}
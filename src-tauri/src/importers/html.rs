@@ -0,0 +1,180 @@
+use scraper::{ElementRef, Html, Selector};
+
+use super::ParsedDocument;
+
+/// 不属于正文的标签，提取时整体跳过
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "button", "svg", "iframe", "figure",
+];
+
+/// 块级标签，提取文本时在其前后断行
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "li", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "br", "tr", "pre",
+];
+
+/// 少于该字数的段落视为导航/版权等噪声
+const MIN_PARAGRAPH_CHARS: usize = 40;
+
+/// 从网页 HTML 中提取标题和正文（简化版 readability 算法）
+///
+/// 优先使用 `<article>`/`<main>`；否则给每个段落的父节点按正文长度打分，取得分最高的容器。
+pub fn extract_article(html: &str) -> Result<ParsedDocument, String> {
+    let document = Html::parse_document(html);
+    let title = extract_title(&document).unwrap_or_else(|| "网页文章".to_string());
+
+    let container = find_content_container(&document).ok_or("未找到正文内容")?;
+    let content = element_text(container);
+    if content.is_empty() {
+        return Err("未找到正文内容".to_string());
+    }
+
+    Ok(ParsedDocument { title, content, word_list: None, cues: None })
+}
+
+/// 提取 HTML/XHTML 片段的正文文本和第一个标题（供 EPUB 章节等使用）
+pub fn body_text(html: &str) -> (Option<String>, String) {
+    let document = Html::parse_document(html);
+    let heading = ["h1", "h2", "h3", "title"].iter().find_map(|tag| {
+        let selector = Selector::parse(tag).ok()?;
+        document
+            .select(&selector)
+            .map(|e| collapse_whitespace(&e.text().collect::<String>()))
+            .find(|t| !t.is_empty())
+    });
+    let text = Selector::parse("body")
+        .ok()
+        .and_then(|body| document.select(&body).next())
+        .map(element_text)
+        .unwrap_or_default();
+    (heading, text)
+}
+
+/// 标题优先级：og:title > <title> > 第一个 <h1>
+fn extract_title(document: &Html) -> Option<String> {
+    let og_title = Selector::parse(r#"meta[property="og:title"]"#).ok()?;
+    if let Some(title) = document
+        .select(&og_title)
+        .filter_map(|e| e.value().attr("content"))
+        .map(collapse_whitespace)
+        .find(|t| !t.is_empty())
+    {
+        return Some(title);
+    }
+
+    ["title", "h1"].iter().find_map(|tag| {
+        let selector = Selector::parse(tag).ok()?;
+        document
+            .select(&selector)
+            .map(|e| collapse_whitespace(&e.text().collect::<String>()))
+            .find(|t| !t.is_empty())
+    })
+}
+
+fn find_content_container(document: &Html) -> Option<ElementRef<'_>> {
+    for tag in ["article", "main", r#"[role="main"]"#] {
+        let selector = Selector::parse(tag).ok()?;
+        if let Some(element) = document
+            .select(&selector)
+            .max_by_key(|e| element_text(*e).chars().count())
+            .filter(|e| element_text(*e).chars().count() >= MIN_PARAGRAPH_CHARS)
+        {
+            return Some(element);
+        }
+    }
+
+    // 按段落文本长度给父节点打分
+    let paragraph = Selector::parse("p").ok()?;
+    let mut scores: Vec<(ElementRef<'_>, usize)> = Vec::new();
+    for p in document.select(&paragraph) {
+        let length = collapse_whitespace(&p.text().collect::<String>()).chars().count();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let Some(parent) = p.parent().and_then(ElementRef::wrap) else { continue };
+        match scores.iter_mut().find(|(e, _)| e.id() == parent.id()) {
+            Some((_, score)) => *score += length,
+            None => scores.push((parent, length)),
+        }
+    }
+
+    scores
+        .into_iter()
+        .max_by_key(|(_, score)| *score)
+        .map(|(e, _)| e)
+        .or_else(|| {
+            let body = Selector::parse("body").ok()?;
+            document.select(&body).next()
+        })
+}
+
+/// 提取元素文本：跳过非正文标签，块级元素之间断行
+fn element_text(element: ElementRef<'_>) -> String {
+    let mut raw = String::new();
+    collect_text(element, &mut raw);
+
+    raw.lines()
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collect_text(element: ElementRef<'_>, out: &mut String) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            out.push_str(text);
+        } else if let Some(child_element) = ElementRef::wrap(child) {
+            let name = child_element.value().name();
+            if SKIPPED_TAGS.contains(&name) {
+                continue;
+            }
+            let is_block = BLOCK_TAGS.contains(&name);
+            if is_block {
+                out.push('\n');
+            }
+            collect_text(child_element, out);
+            if is_block {
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAGRAPH: &str = "The quick brown fox jumps over the lazy dog near the river bank.";
+
+    #[test]
+    fn test_extract_article() {
+        let html = format!(
+            r#"<html><head><title>Page title</title><meta property="og:title" content=" Fox  News "></head>
+               <body><nav>Home | About</nav><div class="sidebar"><p>Short link</p></div>
+               <div class="story"><p>{0}</p><script>var x = 1;</script><p>{0}</p></div>
+               <footer>Copyright</footer></body></html>"#,
+            PARAGRAPH
+        );
+        let doc = extract_article(&html).unwrap();
+        assert_eq!(doc.title, "Fox News");
+        assert_eq!(doc.content, format!("{0}\n{0}", PARAGRAPH));
+    }
+
+    #[test]
+    fn test_extract_article_prefers_article_tag() {
+        let html = format!(
+            "<html><body><h1>Heading</h1><div><p>{0}</p><p>{0}</p></div><article><p>{0}</p><aside>Related</aside></article></body></html>",
+            PARAGRAPH
+        );
+        let doc = extract_article(&html).unwrap();
+        assert_eq!(doc.title, "Heading");
+        assert_eq!(doc.content, PARAGRAPH);
+
+        let empty = extract_article("<html><body><script>x</script></body></html>");
+        assert_eq!(empty.unwrap_err(), "未找到正文内容");
+    }
+}
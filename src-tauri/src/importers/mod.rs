@@ -0,0 +1,172 @@
+use std::path::Path;
+
+pub mod epub;
+pub mod html;
+pub mod pdf;
+pub mod subtitle;
+
+use subtitle::SubtitleCue;
+
+/// 解析后的待导入文档
+#[derive(Debug, Clone)]
+pub struct ParsedDocument {
+    pub title: String,
+    pub content: String,
+    /// 预先确定的单词列表（如 CSV 单词表），导入时直接保存为 word 分词
+    pub word_list: Option<Vec<String>>,
+    /// 字幕时间轴，导入时每条字幕保存为一个 sentence 分词
+    pub cues: Option<Vec<SubtitleCue>>,
+}
+
+/// 根据扩展名解析文件
+pub fn parse_file(path: &Path) -> Result<ParsedDocument, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let title = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("未命名")
+        .to_string();
+
+    match extension.as_str() {
+        "txt" => {
+            let content = read_text_file(path)?;
+            Ok(ParsedDocument { title, content: content.trim().to_string(), word_list: None, cues: None })
+        }
+        "md" | "markdown" => {
+            let content = read_text_file(path)?;
+            Ok(ParsedDocument { title, content: strip_markdown(&content), word_list: None, cues: None })
+        }
+        "csv" => {
+            let content = read_text_file(path)?;
+            let words = parse_word_list_csv(&content);
+            if words.is_empty() {
+                return Err(format!("{}: CSV 中没有找到单词", path.display()));
+            }
+            Ok(ParsedDocument { title, content: words.join("\n"), word_list: Some(words), cues: None })
+        }
+        "srt" | "vtt" => {
+            let content = read_text_file(path)?;
+            let cues = subtitle::parse_subtitles(&content);
+            if cues.is_empty() {
+                return Err(format!("{}: 没有找到字幕内容", path.display()));
+            }
+            let content = cues.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("\n");
+            Ok(ParsedDocument { title, content, word_list: None, cues: Some(cues) })
+        }
+        "pdf" => pdf::read_pdf(path, None),
+        _ => Err(format!("{}: 不支持的文件类型", path.display())),
+    }
+}
+
+/// 读取文本文件（兼容 UTF-8 BOM）
+fn read_text_file(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// 去除常见 Markdown 标记，保留正文文本
+pub fn strip_markdown(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let text = trimmed
+            .trim_start_matches('#')
+            .trim_start_matches('>')
+            .trim_start();
+        let text = text
+            .strip_prefix("- ")
+            .or_else(|| text.strip_prefix("* "))
+            .or_else(|| text.strip_prefix("+ "))
+            .unwrap_or(text);
+        let text = strip_markdown_links(text);
+        let text: String = text.chars().filter(|c| !matches!(c, '*' | '`' | '_')).collect();
+        lines.push(text.trim().to_string());
+    }
+
+    // 合并多余空行
+    let mut result = String::new();
+    for line in lines {
+        if line.is_empty() {
+            if !result.is_empty() && !result.ends_with("\n\n") {
+                result.push_str("\n\n");
+            }
+            continue;
+        }
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(&line);
+    }
+    result.trim().to_string()
+}
+
+/// 把 `[文本](链接)` 和 `![说明](图片)` 替换为文本本身
+fn strip_markdown_links(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let Some(mid) = rest[start..].find("](") else { break };
+        let Some(end) = rest[start + mid..].find(')') else { break };
+        let prefix = rest[..start].trim_end_matches('!');
+        result.push_str(prefix);
+        result.push_str(&rest[start + 1..start + mid]);
+        rest = &rest[start + mid + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 解析 CSV 单词表：取每行第一列，跳过表头和空行，去重保序
+pub fn parse_word_list_csv(content: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let first = line.split([',', ';', '\t']).next().unwrap_or("");
+        let word = first.trim().trim_matches('"').trim();
+        if word.is_empty() {
+            continue;
+        }
+        if index == 0 && matches!(word.to_lowercase().as_str(), "word" | "words" | "单词") {
+            continue;
+        }
+        if !words.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+            words.push(word.to_string());
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_markdown() {
+        let markdown = "# Title\n\n> A **bold** _quote_ with `code`\n\n\n\n- See [the docs](https://example.com)\n* ![logo](logo.png) here\n```\nlet x = 1;\n```\nEnd";
+        assert_eq!(strip_markdown(markdown), "Title\n\nA bold quote with code\n\nSee the docs\nlogo here\nEnd");
+        assert_eq!(strip_markdown_links("[a](b) and [unclosed"), "a and [unclosed");
+    }
+
+    #[test]
+    fn test_parse_word_list_csv() {
+        let csv = "Word,Meaning\napple,苹果\n\n\"Banana\";香蕉\ncherry\tfruit\nAPPLE,again\n";
+        assert_eq!(parse_word_list_csv(csv), vec!["apple", "Banana", "cherry"]);
+        // 表头只在第一行跳过
+        assert_eq!(parse_word_list_csv("dog\nword\n"), vec!["dog", "word"]);
+        assert!(parse_word_list_csv("单词\n\n").is_empty());
+    }
+}
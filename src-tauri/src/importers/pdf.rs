@@ -0,0 +1,149 @@
+use std::path::Path;
+
+use super::ParsedDocument;
+
+/// 提取 PDF 文本，`page_range` 为从 1 开始的闭区间
+pub fn read_pdf(path: &Path, page_range: Option<(usize, usize)>) -> Result<ParsedDocument, String> {
+    // pdf-extract 遇到不支持的文件结构时会 panic，捕获后按解析失败处理
+    let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_by_pages(path))
+        .map_err(|_| format!("{}: PDF 解析失败: 不支持的文件结构", path.display()))?
+        .map_err(|e| format!("{}: PDF 解析失败: {}", path.display(), e))?;
+    if pages.is_empty() {
+        return Err(format!("{}: PDF 没有页面", path.display()));
+    }
+
+    let (start, end) = page_range.unwrap_or((1, pages.len()));
+    if start == 0 || start > end || start > pages.len() {
+        return Err(format!("页码范围无效: {}-{}（共 {} 页）", start, end, pages.len()));
+    }
+    let end = end.min(pages.len());
+
+    let text = pages[start - 1..end].iter().map(|page| strip_page_number(page)).collect::<Vec<_>>().join("\n\n");
+    let content = clean_layout(&text);
+    if content.is_empty() {
+        // 扫描件没有文本层
+        return Err(format!("{}: 没有可提取的文本（可能是扫描件）", path.display()));
+    }
+
+    let title = path.file_stem().and_then(|s| s.to_str()).unwrap_or("未命名").to_string();
+    Ok(ParsedDocument { title, content, word_list: None, cues: None })
+}
+
+/// 去掉页面首尾单独成行的页码（`12`、`- 12 -`、`Page 3 of 10`），正文中的数字行保留
+pub fn strip_page_number(page: &str) -> String {
+    let lines: Vec<&str> = page.lines().collect();
+    let first = lines.iter().position(|line| !line.trim().is_empty());
+    let last = lines.iter().rposition(|line| !line.trim().is_empty());
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| !((Some(*i) == first || Some(*i) == last) && is_page_number(line)))
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_page_number(line: &str) -> bool {
+    let line = line.trim().to_lowercase();
+    let line = line.strip_prefix("page").unwrap_or(&line);
+    let line = line.trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '–' | '—' | '(' | ')' | '[' | ']'));
+    let number = |part: &str| (1..=4).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit());
+    match line.split_once('/').or_else(|| line.split_once(" of ")) {
+        Some((page, total)) => number(page.trim()) && number(total.trim()),
+        None => number(line),
+    }
+}
+
+/// 整理 PDF 排版：合并断行、去掉行尾连字符
+pub fn clean_layout(text: &str) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            // 句子未结束的段落（如跨页）继续与下一行合并
+            if ends_sentence(&current) {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if current.is_empty() {
+            current.push_str(line);
+        } else if let Some(stem) = hyphenated_stem(&current, line) {
+            current.truncate(stem);
+            current.push_str(line);
+        } else {
+            current.push(' ');
+            current.push_str(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+        .iter()
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn ends_sentence(paragraph: &str) -> bool {
+    paragraph
+        .trim_end()
+        .chars()
+        .last()
+        .is_some_and(|c| matches!(c, '.' | '!' | '?' | ':' | '"' | '”' | '。' | '！' | '？'))
+}
+
+/// `exam-` + `ple` 这类断词：返回去掉连字符后的长度
+fn hyphenated_stem(current: &str, next_line: &str) -> Option<usize> {
+    let stem = current.strip_suffix('-')?;
+    let before = stem.chars().last()?;
+    let after = next_line.chars().next()?;
+    (before.is_alphabetic() && after.is_lowercase()).then_some(stem.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_page_number() {
+        assert_eq!(strip_page_number("12\nThe fox ran.\n- 13 -"), "The fox ran.");
+        assert_eq!(strip_page_number("Page 3 of 10\nThe fox ran.\n\n[4]\n"), "The fox ran.\n");
+        assert_eq!(strip_page_number("The fox ran.\n3/10"), "The fox ran.");
+        // 正文中的年份和超过四位的数字不是页码
+        assert_eq!(strip_page_number("It was\n1969\nwhen we landed."), "It was\n1969\nwhen we landed.");
+        assert_eq!(strip_page_number("Call\n12345"), "Call\n12345");
+    }
+
+    #[test]
+    fn test_clean_layout() {
+        let text = "The quick brown fox jumps over the lazy dog and an exam-\nple of a long\n\nline that spans pages.\n\nNew paragraph.";
+        assert_eq!(
+            clean_layout(text),
+            "The quick brown fox jumps over the lazy dog and an example of a long line that spans pages.\n\nNew paragraph."
+        );
+        assert_eq!(clean_layout("In the year\n2020\nwe met."), "In the year 2020 we met.");
+        assert_eq!(clean_layout("well-\nKnown"), "well- Known");
+    }
+
+    /// 损坏或不是 PDF 的文件返回错误，不会让程序崩溃
+    #[test]
+    fn test_read_pdf_rejects_broken_files() {
+        let dir = std::env::temp_dir().join(format!("spelling-pdf-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, data) in [
+            ("empty.pdf", &b""[..]),
+            ("text.pdf", &b"not a pdf"[..]),
+            ("truncated.pdf", &b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF"[..]),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, data).unwrap();
+            assert!(read_pdf(&path, None).is_err(), "{}", name);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
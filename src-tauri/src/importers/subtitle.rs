@@ -0,0 +1,91 @@
+/// 一条字幕（时间单位：毫秒）
+#[derive(Debug, Clone)]
+pub struct SubtitleCue {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// 解析 SRT / WebVTT 字幕；只保留带时间轴的块，WEBVTT 头和 NOTE 块自然被跳过
+pub fn parse_subtitles(content: &str) -> Vec<SubtitleCue> {
+    let normalized = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+
+    for block in normalized.split("\n\n") {
+        let mut lines = block.lines().map(str::trim).filter(|l| !l.is_empty());
+        // 时间轴之前可能有序号或 cue 标识
+        let Some(timing) = lines.by_ref().find(|l| l.contains("-->")) else { continue };
+        let Some((start_ms, end_ms)) = parse_timing_line(timing) else { continue };
+
+        let text = lines.map(strip_cue_markup).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        cues.push(SubtitleCue { start_ms, end_ms, text });
+    }
+
+    cues
+}
+
+/// 解析 `00:00:01,000 --> 00:00:03,500 align:start` 形式的时间轴
+fn parse_timing_line(line: &str) -> Option<(i64, i64)> {
+    let (start, rest) = line.split_once("-->")?;
+    // VTT 的 cue 设置跟在结束时间之后
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+/// 支持 `hh:mm:ss,mmm`、`hh:mm:ss.mmm` 和 `mm:ss.mmm`
+fn parse_timestamp(text: &str) -> Option<i64> {
+    let text = text.replace(',', ".");
+    let (clock, millis) = text.split_once('.').unwrap_or((&text, "0"));
+    let parts: Vec<i64> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let seconds = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        _ => return None,
+    };
+    let millis: i64 = format!("{:0<3}", &millis[..millis.len().min(3)]).parse().ok()?;
+    Some(seconds * 1000 + millis)
+}
+
+/// 去掉 `<i>`、`<c.yellow>` 等标签和 `{\an8}` 等 ASS 控制码
+fn strip_cue_markup(line: &str) -> String {
+    let mut result = String::new();
+    let mut in_tag = false;
+    let mut in_brace = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            '{' => in_brace = true,
+            '}' if in_brace => in_brace = false,
+            _ if !in_tag && !in_brace => result.push(c),
+            _ => {}
+        }
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:03,500\r\n<i>Hello</i> there\r\nfriend\r\n\r\n2\r\n00:00:04,000 --> 00:00:05,000\r\n{\\an8}\r\n\r\n3\r\n01:00:00,25 --> 01:00:01,000\r\nBye\r\n";
+        let cues = parse_subtitles(srt);
+        let cues: Vec<(i64, i64, &str)> = cues.iter().map(|c| (c.start_ms, c.end_ms, c.text.as_str())).collect();
+        assert_eq!(cues, vec![(1000, 3500, "Hello there friend"), (3_600_250, 3_601_000, "Bye")]);
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let vtt = "WEBVTT\n\nNOTE a comment\n\nintro\n00:01.500 --> 00:02.000 align:start\n<c.yellow>Good</c> morning\n\n00:03.000 --> bad\nSkipped";
+        let cues = parse_subtitles(vtt);
+        assert_eq!(cues.len(), 1);
+        assert_eq!((cues[0].start_ms, cues[0].end_ms, cues[0].text.as_str()), (1500, 2000, "Good morning"));
+        assert_eq!(parse_timestamp("1:2:3"), Some(3_723_000));
+        assert_eq!(parse_timestamp("oops"), None);
+    }
+}
@@ -1,6 +1,21 @@
+mod cards;
 mod commands;
 mod database;
+mod i18n;
+mod importers;
 mod models;
+mod ordering;
+mod packs;
+mod paths;
+mod prompts;
+mod seeds;
+#[cfg(feature = "http-server")]
+pub mod server;
+mod text;
+mod waveform;
+mod worksheet;
+#[cfg(desktop)]
+mod tray;
 
 use tauri::Manager;
 
@@ -8,17 +23,11 @@ use tauri::Manager;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
             // 初始化数据库
-            let app_handle = app.handle();
-            let db_path = app_handle.path().app_data_dir()
-                .expect("Failed to get app data dir")
-                .join("spelling.db");
-            
-            // 确保目录存在
-            if let Some(parent) = db_path.parent() {
-                std::fs::create_dir_all(parent).ok();
-            }
+            let db_path = paths::app_data_file(app.handle(), paths::DB_FILE)
+                .expect("Failed to get app data dir");
             
             let db = database::DatabaseManager::new(&db_path)
                 .expect("Failed to initialize database");
@@ -28,39 +37,176 @@ pub fn run() {
             
             // 将数据库实例存储到 state
             app.manage(std::sync::Mutex::new(db));
+            app.manage(std::sync::Mutex::new(commands::article::DeletedArticles::default()));
+            app.manage(std::sync::Mutex::new(commands::timer::SessionTimers::default()));
+            app.manage(std::sync::Arc::new(commands::tts::TtsService::default()));
+            commands::timer::start_tick_loop(app.handle().clone());
+            app.manage(std::sync::Mutex::new(commands::ipc::IpcServer::default()));
+            commands::ipc::start_if_enabled(app.handle());
             
             log::info!("Database initialized at {:?}", db_path);
+
+            // 系统托盘（每日复习快捷入口，仅桌面端）
+            #[cfg(desktop)]
+            tray::setup_tray(app)?;
             Ok(())
         })
+        .on_window_event(|window, event| match event {
+            // 拖放 txt/md/csv/srt/vtt/pdf 文件直接导入
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                commands::import::handle_dropped_files(window, paths);
+            }
+            // 关闭窗口前保存进行中的练习会话
+            tauri::WindowEvent::CloseRequested { .. } => {
+                commands::session::checkpoint_active_sessions(&window.state::<std::sync::Mutex<database::DatabaseManager>>());
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             // 文章管理
             commands::article::get_articles,
             commands::article::get_article,
             commands::article::create_article,
+            commands::article::detect_article_language,
+            commands::article::set_article_language,
             commands::article::update_article,
             commands::article::delete_article,
+            commands::article::undo_last_delete,
             commands::article::save_segments,
             commands::article::get_segments,
+            commands::article::get_segment_timings,
+            commands::article::set_article_tags,
+            commands::article::get_article_tags,
+            commands::article::get_all_tags,
+            commands::article::save_article_translation,
+            commands::article::get_aligned_paragraphs,
+            commands::article::get_translation_languages,
+            commands::article::suggest_focus_words,
+            commands::article::save_focus_wordlist,
+            commands::article::generate_worksheet_pdf,
+            commands::article::export_pack,
+            commands::article::import_pack,
+            // 内容目录（下载并导入远程单元包）
+            commands::catalog::save_catalog_settings,
+            commands::catalog::load_catalog_settings,
+            commands::catalog::browse_content_catalog,
+            commands::catalog::install_catalog_pack,
+            // 单词配图
+            commands::image::attach_word_image,
+            commands::image::fetch_word_image,
+            commands::image::get_word_image,
+            commands::image::get_word_images,
+            commands::image::remove_word_image,
+            commands::image::save_image_api_settings,
+            commands::image::load_image_api_settings,
+            // 文章导入
+            commands::import::create_article_from_clipboard,
+            commands::import::import_files,
+            commands::import::import_article_from_url,
+            commands::import::import_article_from_pdf,
+            commands::import::list_epub_chapters,
+            commands::import::import_epub_chapters,
             // 练习相关
             commands::practice::save_progress,
             commands::practice::get_progress,
             commands::practice::clear_progress,
+            commands::practice::preview_article_reset,
+            commands::practice::reset_article_progress,
             commands::practice::add_mistake,
+            commands::practice::add_mistakes_batch,
             commands::practice::remove_mistake,
             commands::practice::get_mistakes,
+            commands::practice::get_archived_mistakes,
             commands::practice::save_record,
             commands::practice::get_leaderboard,
+            commands::practice::analyze_morphology,
+            commands::practice::syllabify,
             // 智能复习（SM-2）
             commands::practice::get_scheduled_words,
+            commands::practice::build_custom_session,
+            commands::practice::set_new_word_pacing,
+            commands::practice::set_content_keyed_mastery,
+            commands::practice::merge_duplicate_masteries,
+            commands::practice::set_accept_spelling_variants,
+            commands::practice::get_accept_spelling_variants,
+            commands::practice::get_new_word_pacing,
+            commands::practice::set_accommodations,
+            commands::practice::get_accommodations,
+            commands::practice::set_keyboard_layout,
+            commands::practice::get_keyboard_layout,
+            commands::practice::get_letter_errors,
+            commands::practice::set_user_pin,
+            commands::practice::user_has_pin,
+            commands::practice::verify_user_pin,
+            commands::practice::get_user_role,
+            commands::practice::set_user_role,
+            commands::practice::set_user_birth_year,
+            commands::practice::get_user_birth_year,
+            commands::practice::export_learning_state,
+            commands::practice::import_learning_state,
             commands::practice::update_word_mastery,
+            commands::practice::update_word_masteries_batch,
             commands::practice::get_word_masteries,
+            commands::practice::get_mastery_events,
+            commands::practice::get_relapsed_words,
+            commands::practice::get_leech_words,
+            commands::practice::get_context_sentences,
+            commands::practice::get_retention_stats,
+            commands::practice::rebalance_reviews,
+            commands::practice::pause_reviews,
+            commands::practice::get_review_pauses,
+            commands::practice::cancel_review_pause,
+            commands::practice::get_due_counts,
+            // 练习会话（后端校验输入）
+            commands::session::start_practice_session,
+            commands::session::get_practice_session,
+            commands::session::validate_input,
+            commands::session::request_hint,
+            commands::session::submit_session_answer,
+            commands::session::get_session_stats,
+            commands::session::recover_last_session,
+            commands::session::get_listening_speed_history,
+            commands::session::get_homophone_practice,
+            commands::session::build_mixed_session,
+            commands::session::submit_mixed_answer,
+            commands::session::get_mixed_session_summary,
+            commands::session::get_speed_accuracy_profile,
+            // 会话计时（后端每秒发出 session-tick 事件）
+            commands::timer::start_session_timer,
+            commands::timer::pause_session_timer,
+            commands::timer::resume_session_timer,
+            commands::timer::stop_session_timer,
+            commands::timer::get_session_timer,
             // 练习历史
             commands::practice::save_practice_history,
             commands::practice::get_practice_history,
             commands::practice::get_user_statistics,
+            commands::practice::get_vocabulary_growth,
+            // 朗读录音（流利度）
+            commands::reading::record_reading,
+            commands::reading::finish_reading,
+            commands::reading::get_reading_recordings,
+            commands::reading::get_recording_metadata,
+            commands::reading::get_fluency_trend,
+            commands::reading::delete_reading_recording,
+            // 发音评分
+            commands::pronunciation::assess_pronunciation,
+            commands::pronunciation::get_pronunciation_score,
+            commands::pronunciation::save_pronunciation_settings,
+            commands::pronunciation::load_pronunciation_settings,
+            // 麦克风（口语测试前选择和检测音量）
+            commands::audio::list_audio_input_devices,
+            commands::audio::select_audio_input_device,
+            commands::audio::test_microphone_level,
             // TTS
             commands::tts::speak,
+            commands::tts::speak_hint,
             commands::tts::stop_speaking,
+            commands::tts::get_tts_status,
+            // 音频复习（朗读错词，口头或纸上拼写）
+            commands::audio_review::start_audio_review,
+            commands::audio_review::stop_audio_review,
+            commands::audio_review::finish_audio_review,
             // 分词服务
             commands::segment::segment_text,
             // WIDA 测试
@@ -69,21 +215,91 @@ pub fn run() {
             commands::wida::get_wida_speaking_questions,
             commands::wida::get_wida_writing_questions,
             commands::wida::start_wida_test,
+            commands::wida::create_wida_blueprint,
+            commands::wida::update_wida_blueprint,
+            commands::wida::delete_wida_blueprint,
+            commands::wida::get_wida_blueprints,
             commands::wida::get_wida_test_session,
             commands::wida::get_wida_test_questions,
             commands::wida::submit_wida_answer,
+            commands::wida::transcribe_speaking_answer,
+            commands::wida::get_speaking_transcripts,
+            commands::wida::update_speaking_transcript,
+            commands::wida::confirm_speaking_transcript,
+            commands::wida::record_wida_focus_event,
+            commands::wida::get_wida_integrity_summary,
             commands::wida::complete_wida_test,
             commands::wida::get_wida_history,
+            commands::wida::recommend_grade_level,
+            commands::wida::start_placement_test,
+            commands::wida::next_placement_question,
+            commands::wida::submit_placement_answer,
+            commands::wida::complete_placement_test,
             commands::wida::get_wida_comprehensive_report,
             commands::wida::get_active_wida_sessions,
             commands::wida::delete_wida_session,
+            commands::wida::delete_wida_question,
+            commands::wida::flag_question,
+            commands::wida::list_flagged_questions,
+            commands::wida::resolve_question_flag,
+            commands::wida::reseed_defaults,
+            commands::wida::play_question_audio,
+            commands::wida::set_wida_question_sample,
+            commands::wida::set_wida_question_tags,
+            commands::wida::get_all_question_tags,
+            commands::wida::search_questions,
+            commands::wida::recalibrate_difficulties,
+            commands::wida::get_wida_section_instructions,
+            commands::wida::set_wida_section_instructions,
+            commands::wida::create_wordlist_from_wida_mistakes,
             // WIDA 题目生成
             commands::wida::generate_listening_questions,
             commands::wida::generate_reading_questions,
             commands::wida::generate_speaking_questions,
             commands::wida::generate_writing_questions,
+            commands::wida::generate_questions_from_article,
+            commands::wida::check_grade_level_text,
+            commands::wida::get_prompt_template,
+            commands::wida::customize_prompt_template,
+            commands::wida::get_ai_usage_summary,
             commands::wida::save_api_settings,
             commands::wida::load_api_settings,
+            // 教师面板
+            commands::dashboard::open_dashboard_window,
+            commands::dashboard::get_dashboard_overview,
+            commands::dashboard::create_assignment,
+            commands::dashboard::get_assignments,
+            commands::dashboard::delete_assignment,
+            commands::dashboard::export_gradebook_csv,
+            commands::dashboard::get_audit_log,
+            commands::dashboard::run_readonly_query,
+            commands::dashboard::apply_retention_policy,
+            commands::dashboard::get_practice_rollups,
+            // 课程计划
+            commands::curriculum::create_curriculum_unit,
+            commands::curriculum::get_curriculum,
+            commands::curriculum::reorder_curriculum_units,
+            commands::curriculum::delete_curriculum_unit,
+            commands::curriculum::get_curriculum_progress,
+            // 分享卡片
+            commands::share::render_share_card,
+            // 界面语言
+            commands::locale::get_locale_strings,
+            // 数据目录（迁移、便携模式）
+            commands::storage::get_data_directory,
+            commands::storage::set_data_directory,
+            commands::storage::set_portable_mode,
+            // 本机 IPC 接口（脚本导出、家庭看板）
+            commands::ipc::save_ipc_settings,
+            commands::ipc::get_ipc_status,
+            // 里程碑 Webhook（测试完成、连续练习、作业完成）
+            commands::webhook::save_webhook_settings,
+            commands::webhook::load_webhook_settings,
+            commands::webhook::send_test_webhook,
+            // 邮件报告（SMTP）
+            commands::email::save_smtp_settings,
+            commands::email::load_smtp_settings,
+            commands::email::send_report_email,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
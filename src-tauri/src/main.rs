@@ -1,5 +1,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // 无界面模式：只运行 HTTP 接口，不启动窗口
+    #[cfg(feature = "http-server")]
+    if std::env::args().any(|arg| arg == "--headless") {
+        let result = spelling_game::server::ServerConfig::from_args(std::env::args().skip(1))
+            .and_then(spelling_game::server::run_headless);
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     spelling_game::run()
 }
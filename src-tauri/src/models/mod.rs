@@ -8,6 +8,18 @@ pub struct Article {
     pub content: String,
     pub created_at: String,
     pub updated_at: String,
+    pub reading_grade: Option<f64>,         // Flesch-Kincaid 年级（非英文文章为空）
+    pub avg_sentence_length: Option<f64>,   // 平均句长（单词数）
+    pub type_token_ratio: Option<f64>,      // 词汇丰富度 0-1
+    pub language: Option<String>,           // 检测到的语言（en、zh…）
+}
+
+/// 文章语言检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageCheck {
+    pub language: Option<String>,           // 没有可识别的文字时为空
+    pub supported: bool,                    // 不支持的语言不能导入
+    pub warning: Option<String>,
 }
 
 /// 创建文章请求
@@ -24,6 +36,68 @@ pub struct UpdateArticleRequest {
     pub content: Option<String>,
 }
 
+/// 导入文章结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedArticle {
+    pub article_id: i64,
+    pub title: String,
+    pub kind: String,                   // "article" | "wordlist"
+    pub segmented_modes: Vec<String>,   // 已成功分词并保存的模式
+    pub segment_errors: Vec<String>,    // 分词失败信息（文章本身已创建）
+    #[serde(default)]
+    pub language: Option<String>,       // 检测到的文章语言
+    #[serde(default)]
+    pub warning: Option<String>,        // 语言提示（如中文文章不按单词分词）
+}
+
+/// 单元包导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackImportResult {
+    pub title: String,
+    pub version: u32,                   // 包格式版本
+    pub app_version: String,            // 打包时的程序版本
+    pub article_ids: Vec<i64>,
+    pub questions_imported: i32,
+    pub audio_files: i32,               // 写入朗读缓存的音频文件数
+}
+
+/// 文章的重点词汇建议（TF-IDF）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusWord {
+    pub word: String,
+    pub score: f64,
+    pub frequency: i32,                 // 在本文中出现的次数
+}
+
+/// 拖放导入完成事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesImportedEvent {
+    pub imported: Vec<ImportedArticle>,
+    pub errors: Vec<String>,
+}
+
+/// PDF 页码范围（从 1 开始，含首尾）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// EPUB 书籍目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpubToc {
+    pub title: String,
+    pub chapters: Vec<EpubChapter>,
+}
+
+/// EPUB 章节概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpubChapter {
+    pub index: usize,
+    pub title: String,
+    pub char_count: usize,
+}
+
 /// 分词片段
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Segment {
@@ -32,6 +106,8 @@ pub struct Segment {
     pub segment_type: String, // "word" | "phrase" | "sentence"
     pub content: String,
     pub order_index: i32,
+    #[serde(default)]
+    pub language: Option<String>,   // 所属文章的语言（en / es / fr / zh）
 }
 
 /// 保存分词请求
@@ -42,6 +118,15 @@ pub struct SaveSegmentsRequest {
     pub segments: Vec<String>,
 }
 
+/// 分词时间轴（毫秒），用于音视频同步听写
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentTiming {
+    pub segment_id: i64,
+    pub order_index: i32,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
 /// 练习进度
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PracticeProgress {
@@ -52,6 +137,8 @@ pub struct PracticeProgress {
     pub words_list: String, // JSON array
     pub correct_count: i32,
     pub incorrect_count: i32,
+    pub order_mode: Option<String>, // 练习顺序模式，重练时按相同顺序复现
+    pub order_seed: Option<u64>,
 }
 
 /// 保存进度请求
@@ -64,6 +151,10 @@ pub struct SaveProgressRequest {
     pub words_list: Vec<String>,
     pub correct_count: i32,
     pub incorrect_count: i32,
+    #[serde(default)]
+    pub order_mode: Option<String>,
+    #[serde(default)]
+    pub order_seed: Option<u64>,
 }
 
 /// 错误记录
@@ -78,6 +169,20 @@ pub struct Mistake {
     pub last_error_at: String,
 }
 
+/// 已归档的错词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMistake {
+    pub id: i64,
+    pub user_name: String,
+    pub segment_id: i64,
+    pub segment_content: String,
+    pub segment_type: String,
+    pub error_count: i32,
+    pub last_error_at: String,
+    pub archived_at: String,
+    pub archive_reason: String,     // "streak" | "mastered"
+}
+
 /// 排行榜记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderboardRecord {
@@ -109,6 +214,10 @@ pub struct SegmentRequest {
     pub text: String,
     pub mode: String, // "word" | "phrase" | "sentence"
     pub server_url: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,  // 单次请求超时，默认 30 秒
+    #[serde(default)]
+    pub max_retries: Option<u32>,   // 临时故障的重试次数，默认 3 次
 }
 
 /// 分词响应
@@ -117,6 +226,85 @@ pub struct SegmentResponse {
     pub segments: Vec<String>,
     pub success: bool,
     pub error: Option<String>,
+    #[serde(default)]
+    pub error_kind: Option<String>, // connection_refused | timeout | server_error | parse_error | network_error
+}
+
+/// 熟练度变更事件（每次更新熟练度记录一条）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasteryEvent {
+    pub id: i64,
+    pub user_name: String,
+    pub segment_id: i64,
+    pub segment_content: String,
+    pub old_level: Option<i32>,     // 首次学习或迁移补录时为空
+    pub new_level: i32,
+    pub correct: Option<bool>,      // 迁移补录时为空
+    pub created_at: String,
+}
+
+/// 顽固词（多次掌握后又遗忘）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeechWord {
+    pub segment_id: i64,
+    pub segment_content: String,
+    pub segment_type: String,
+    pub mastery_level: i32,         // 当前熟练度
+    pub relapse_count: i32,         // 遗忘次数
+    pub last_relapse_at: String,
+}
+
+/// 某个复习间隔范围内的回忆率
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionBucket {
+    pub min_days: i64,
+    pub max_days: Option<i64>,          // 为空表示无上限
+    pub reviews: i32,
+    pub recalled: i32,                  // 答对（记住）的次数
+    pub retention_rate: f64,            // 0-100
+}
+
+/// 假期暂停复习
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPause {
+    pub id: i64,
+    pub user_name: String,
+    pub from_date: String,              // YYYY-MM-DD
+    pub to_date: String,                // YYYY-MM-DD
+    pub applied_at: Option<String>,     // 假期结束后顺延完成的时间
+    pub shifted_count: i32,             // 顺延的单词数
+}
+
+/// 某一天到期的复习量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueDayLoad {
+    pub date: String,                   // YYYY-MM-DD
+    pub count: i32,
+}
+
+/// 复习量分散的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRebalance {
+    pub max_per_day: i32,
+    pub moved: i32,                     // 挪动了到期日的单词数
+    pub due_days: Vec<DueDayLoad>,      // 调整后未来每天的复习量
+}
+
+/// 包含薄弱词的句子（语境听写复习）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSentence {
+    pub segment_id: i64,
+    pub article_id: i64,
+    pub content: String,
+    pub weak_words: Vec<String>, // 句中出现的薄弱词（小写）
+}
+
+/// 词汇量增长曲线上的一周
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VocabularyGrowthPoint {
+    pub week_start: String,         // 该周周一（YYYY-MM-DD）
+    pub new_words: i32,             // 本周新掌握的单词数
+    pub total_words: i32,           // 截至本周累计掌握的单词数
 }
 
 /// 单词熟练度（SM-2 算法）
@@ -132,6 +320,8 @@ pub struct WordMastery {
     pub next_review_at: String,  // 下次复习时间
     pub last_review_at: String,  // 上次复习时间
     pub review_count: i32,       // 复习次数
+    #[serde(default)]
+    pub language: Option<String>, // 分词的语言
 }
 
 /// 获取智能调度单词请求
@@ -151,6 +341,30 @@ pub struct UpdateMasteryRequest {
     pub segment_content: String,
     pub segment_type: String,
     pub correct: bool,           // 是否回答正确
+    #[serde(default)]
+    pub quality: Option<i32>,    // 自评质量等级 0-5（如 难=3 / 良好=4 / 简单=5），有值时按完整 SM-2 计算
+}
+
+/// 记录错词请求（批量提交时使用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddMistakeRequest {
+    pub user_name: String,
+    pub segment_id: i64,
+    pub segment_content: String,
+    pub segment_type: String,
+}
+
+/// 重置文章练习数据的预览（各类将被删除的记录数和确认令牌）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArticleResetPreview {
+    pub user_name: String,
+    pub article_id: i64,
+    pub progress_count: i32,
+    pub session_count: i32,
+    pub session_detail_count: i32, // 会话中的作答事件、提示和按键错误
+    pub mistake_count: i32,
+    pub mastery_count: i32,
+    pub confirm_token: String,
 }
 
 /// 智能调度单词响应
@@ -159,17 +373,267 @@ pub struct ScheduledWordsResponse {
     pub words: Vec<ScheduledWord>,
     pub new_words_count: i32,   // 新单词数量
     pub review_words_count: i32, // 复习单词数量
+    pub seed: Option<u64>,      // shuffled 模式使用的种子
 }
 
 /// 调度单词
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledWord {
     pub segment_id: i64,
+    pub article_id: i64,
     pub content: String,
     pub segment_type: String,
     pub mastery_level: i32,
     pub is_new: bool,           // 是否是新单词
     pub next_review_at: String, // 下次复习时间（用于排序）
+    pub is_leech: bool,         // 顽固词，前端可建议换一种学习方式（如拆音节、造句）
+}
+
+/// 每日新词上限及今天的使用情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewWordPacing {
+    pub user_name: String,
+    pub daily_limit: Option<i32>,   // 未设置时不限制
+    pub carryover: bool,            // 前一天没用完的名额是否顺延
+    pub served_today: i32,          // 今天已发放的新词数
+    pub remaining_today: Option<i32>,
+}
+
+/// 用户的无障碍设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Accommodations {
+    pub user_name: String,
+    pub time_multiplier: f64,       // 计时目标的时间倍数，1.0 = 不延长
+    pub max_tts_rate: Option<i32>,  // 朗读语速上限（每分钟单词数）
+    pub text_scale: f64,            // 单词显示放大倍数，由前端使用
+    pub batch_size: Option<i32>,    // 每批练习的单词数上限
+}
+
+/// 调用受限命令的用户（设置了 PIN 时需一并提供）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub user_name: String,
+    #[serde(default)]
+    pub pin: Option<String>,
+}
+
+/// 自定义复习筛选条件（未设置的条件不限制）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomSessionFilters {
+    pub user_name: String,
+    pub article_ids: Option<Vec<i64>>,
+    pub tags: Option<Vec<String>>,              // 任一标签匹配即可
+    pub segment_type: Option<String>,
+    pub min_mastery: Option<i32>,               // 未学过的分词按熟练度 0 计
+    pub max_mastery: Option<i32>,
+    pub min_error_count: Option<i32>,
+    pub last_seen_after: Option<String>,        // 上次复习时间下限
+    pub last_seen_before: Option<String>,       // 上次复习时间上限（含从未复习）
+    pub limit: Option<i32>,
+    #[serde(default)]
+    pub order_mode: Option<String>,             // 见 ordering::apply_order
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub language: Option<String>,               // 只复习该语言的分词（en / es / fr）
+}
+
+/// 答案检查的严格程度，默写句子时可放宽大小写和标点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnswerCheckOptions {
+    #[serde(default = "default_ignore_case")]
+    pub ignore_case: bool,
+    #[serde(default)]
+    pub ignore_punctuation: bool,
+    #[serde(default)]
+    pub require_exact: bool,            // 大小写、标点都必须一致，优先于以上两项
+    #[serde(default)]
+    pub accept_spelling_variants: Option<bool>, // colour 与 color 视为相同，为空时按用户设置
+}
+
+fn default_ignore_case() -> bool {
+    true
+}
+
+impl Default for AnswerCheckOptions {
+    fn default() -> Self {
+        Self { ignore_case: true, ignore_punctuation: false, require_exact: false, accept_spelling_variants: None }
+    }
+}
+
+/// 答案中一个单词的对照结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMatch {
+    pub expected: String,
+    pub typed: Option<String>,          // 对应位置输入的单词，漏写时为空
+    pub correct: bool,
+}
+
+/// 练习会话中的一个题目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionItem {
+    pub segment_id: i64,
+    pub content: String,
+}
+
+/// 开始练习会话请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartPracticeSessionRequest {
+    pub user_name: String,
+    pub article_id: i64,
+    pub segment_type: String,
+    pub items: Vec<SessionItem>,
+    #[serde(default)]
+    pub order_mode: Option<String>,
+    #[serde(default)]
+    pub order_seed: Option<u64>,
+    #[serde(default)]
+    pub goal: Option<String>,           // "until_20_correct" | "10_minutes" | "clear_due_queue"
+    #[serde(default)]
+    pub speed_training: bool,           // 听力提速训练：按正确率自动调整朗读语速
+    #[serde(default)]
+    pub tts_rate: Option<i32>,          // 起始朗读语速（每分钟单词数）
+    #[serde(default)]
+    pub hint_penalty: Option<f64>,      // 每次提示扣除的题目分数（0-1），默认 0.2
+    #[serde(default)]
+    pub hint_mastery_threshold: Option<i32>, // 提示次数达到该值时答对也不提升熟练度，默认 3
+    #[serde(default)]
+    pub chunked: bool,                  // 分块练习：按音节逐块显示和校验，适合低年级
+    #[serde(default)]
+    pub check_options: Option<AnswerCheckOptions>, // 答案检查的严格程度，对应作业有设置时以作业为准
+}
+
+/// 练习会话（由后端校验输入和计分）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PracticeSession {
+    pub id: i64,
+    pub user_name: String,
+    pub article_id: i64,
+    pub segment_type: String,
+    pub items: Vec<SessionItem>,
+    pub current_index: i32,
+    pub typed: String,                  // 当前题目已输入的内容
+    pub keystrokes: i32,
+    pub correct_keystrokes: i32,
+    pub correct_count: i32,
+    pub incorrect_count: i32,
+    pub order_mode: Option<String>,
+    pub order_seed: Option<u64>,
+    pub goal: Option<String>,
+    pub speed_training: bool,
+    pub tts_rate: Option<i32>,          // 当前题目的朗读语速
+    pub hint_penalty: f64,
+    pub hint_mastery_threshold: i32,
+    pub score_points: f64,              // 扣除提示后的累计得分（每题最多 1 分）
+    pub chunked: bool,
+    pub check_options: AnswerCheckOptions,
+    pub status: String,                 // "active" | "completed"
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+/// 会话计时（`session-tick` 事件，计时中的会话每秒一次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTick {
+    pub kind: String,                   // "practice" | "wida"
+    pub session_id: i64,
+    pub elapsed_seconds: u64,
+    pub remaining_seconds: Option<u64>, // 没有限时时为空
+    pub running: bool,
+    pub expired: bool,                  // 限时已到，计时器随后停止
+}
+
+/// 单次按键反馈（`keystroke-feedback` 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystrokeFeedback {
+    pub session_id: i64,
+    pub position: usize,
+    pub key: String,
+    pub correct: bool,
+    pub error_kind: Option<String>,     // 按错时："slip" 按到相邻键 | "spelling" 拼写错误
+}
+
+/// 某个字母的按错统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LetterErrorStat {
+    pub letter: String,
+    pub spelling_errors: i32,
+    pub slips: i32,                     // 按到相邻键
+}
+
+/// 混淆过的同音词组（对比练习时两个词一起出现）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HomophonePair {
+    pub word: String,                   // 应写的词
+    pub confused_with: String,          // 写成的同音词
+    pub confusion_count: i32,
+    pub correct_streak: i32,
+    pub last_confused_at: String,
+}
+
+/// 输入校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputValidation {
+    pub is_prefix: bool,                // 目前输入是否仍是答案的前缀
+    pub is_complete: bool,              // 输入与答案完全一致
+    pub first_error_at: Option<usize>,  // 第一个错误字符的位置
+    pub keystrokes: Vec<KeystrokeFeedback>,
+    pub chunks: Vec<String>,            // 分块练习：当前题目的音节块（非分块练习为空）
+    pub completed_chunks: usize,        // 分块练习：已正确拼完的音节块数
+}
+
+/// 提交答案结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAnswerResult {
+    pub correct: bool,
+    pub expected: String,
+    pub current_index: i32,
+    pub finished: bool,
+    pub goal_met: Option<bool>,         // 会话结束时目标是否达成（无目标时为空）
+    pub mastery: WordMastery,
+    pub tts_rate: Option<i32>,          // 提速训练中下一题的朗读语速
+    pub hints_used: i32,                // 本题使用的提示次数
+    pub item_score: f64,                // 本题得分（答错为 0）
+    pub error_class: Option<String>,    // 答错类型："homophone" 写成了同音词
+    pub confused_with: Option<String>,  // 写成的同音词
+    pub tokens: Vec<TokenMatch>,        // 逐词对照，答错的句子按对上的单词给部分分
+}
+
+/// 一次提示的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHint {
+    pub session_id: i64,
+    pub item_index: i32,
+    pub hint_level: i32,
+    pub kind: String,                   // "length" | "first_letter" | "repeat_audio" | "letter"
+    pub hint: String,                   // 未显示的字母用 `_` 遮盖
+    pub speak: bool,                    // 前端需要重新朗读本题
+}
+
+/// 一次听力提速训练的语速变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListeningSpeedRecord {
+    pub session_id: i64,
+    pub started_at: String,
+    pub start_rate: i32,
+    pub final_rate: i32,
+    pub comfortable_rate: Option<i32>,  // 答对过的最高语速
+    pub answers: i32,
+}
+
+/// 会话按键统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub session_id: i64,
+    pub keystrokes: i32,
+    pub correct_keystrokes: i32,
+    pub keystroke_accuracy: f64,        // 0-100
+    pub correct_count: i32,
+    pub incorrect_count: i32,
+    pub answered: i32,
+    pub total_items: i32,
+    pub hints_used: i32,
+    pub score: f64,                     // 扣除提示后的得分（0-100）
 }
 
 /// 练习历史记录
@@ -187,6 +651,14 @@ pub struct PracticeHistory {
     pub wpm: f64,               // 每分钟单词数
     pub duration_seconds: i32,   // 练习时长(秒)
     pub completed_at: String,
+    pub goal: Option<String>,    // 目标练习的目标
+    pub goal_met: Option<bool>,
+    #[serde(default = "default_practice_mode")]
+    pub mode: String,            // "typing" | "audio_review"
+}
+
+fn default_practice_mode() -> String {
+    "typing".to_string()
 }
 
 /// 保存练习历史请求
@@ -214,6 +686,184 @@ pub struct UserStatistics {
     pub best_wpm: f64,              // 最高WPM
     pub total_duration_minutes: f64, // 总练习时长(分钟)
     pub recent_histories: Vec<PracticeHistory>, // 最近几次练习记录
+    pub by_mode: Vec<PracticeModeStats>, // 按练习模式分别统计
+    pub comparison: Option<CohortComparison>, // 与本机其他用户的匿名对比（需请求时才计算）
+}
+
+/// 某种练习模式的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PracticeModeStats {
+    pub mode: String,               // "typing" | "audio_review"
+    pub practices: i32,
+    pub total_words: i32,
+    pub avg_accuracy: f64,
+    pub total_duration_minutes: f64,
+}
+
+/// 与本机其他用户的匿名对比，百分位表示超过了多少比例的其他用户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortComparison {
+    pub cohort_size: i32,           // 参与对比的其他用户数（至少练习过一次）
+    pub accuracy_percentile: f64,   // 平均正确率百分位 0-100
+    pub wpm_percentile: f64,        // 平均WPM百分位 0-100
+}
+
+/// 最近 7 天的练习小结
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub user_name: String,
+    pub practices: i32,
+    pub words_practiced: i32,
+    pub words_mastered: i32,            // 本周新达到掌握等级的单词
+    pub avg_accuracy: f64,
+    pub duration_seconds: i32,
+    pub streak_days: i32,               // 连续练习天数
+    #[serde(default)]
+    pub speed_accuracy: Option<SpeedAccuracyProfile>, // 同一时段的速度与正确率分析
+}
+
+/// 按作答用时分组的正确率
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedBucket {
+    pub label: String,                  // "fast" | "medium" | "slow"
+    pub min_ms: i64,
+    pub max_ms: i64,
+    pub items: i32,
+    pub accuracy: f64,                  // 0-1
+}
+
+/// 速度与正确率的关系
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedAccuracyProfile {
+    pub user_name: String,
+    pub items: i32,
+    pub accuracy: f64,                  // 0-1
+    pub median_response_ms: Option<i64>,
+    pub correlation: Option<f64>,       // 用时与对错的相关系数，正值表示越慢越准
+    pub buckets: Vec<SpeedBucket>,
+    pub recommendation: String,         // "slow_down" | "balanced" | "insufficient_data"
+}
+
+/// 导航栏角标计数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueCounts {
+    pub due_words: i32,                 // 到期待复习的单词
+    pub pending_assignments: i32,       // 未完成的作业
+    pub unfinished_wida_sessions: i32,  // 未完成的 WIDA 测试
+}
+
+/// 作业（教师布置给学生的练习任务）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub id: i64,
+    pub user_name: String,
+    pub article_id: i64,
+    pub article_title: String,
+    pub segment_type: String,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub check_options: Option<AnswerCheckOptions>, // 覆盖练习会话的答案检查设置
+    pub status: String,             // "pending" | "completed"
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// 课程计划中的单元
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurriculumUnit {
+    pub id: i64,
+    pub user_name: String,
+    pub title: String,
+    pub position: i32,
+    pub target_date: String,        // 目标完成日期（YYYY-MM-DD）
+    pub created_at: String,
+    pub article_ids: Vec<i64>,      // 单元包含的文章/单词表，按顺序
+}
+
+/// 添加单元请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCurriculumUnitRequest {
+    pub user_name: String,
+    pub title: String,
+    pub article_ids: Vec<i64>,
+    pub target_date: String,
+}
+
+/// 单个单元的进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurriculumUnitProgress {
+    pub unit_id: i64,
+    pub title: String,
+    pub target_date: String,
+    pub total_words: i32,
+    pub mastered_words: i32,
+    pub progress: f64,              // 已掌握的单词比例（0-1）
+    pub planned_progress: f64,      // 按计划今天应达到的比例（0-1）
+    pub completed: bool,
+    pub overdue: bool,              // 已过目标日期仍未完成
+}
+
+/// 课程进度：实际进度与计划对比
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurriculumProgress {
+    pub units: Vec<CurriculumUnitProgress>,
+    pub current_unit_id: Option<i64>, // 第一个未完成的单元
+    pub actual_progress: f64,       // 全部单元中已掌握的单词比例
+    pub planned_progress: f64,      // 按计划今天应达到的比例
+    pub pace: String,               // "ahead" | "on_track" | "behind"
+}
+
+/// 原文和译文对照的一段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedParagraph {
+    pub index: i32,
+    pub original: Option<String>,
+    pub translation: Option<String>,    // 没有对应译文时为空
+}
+
+/// 成绩册中的一行：完成的作业或 WIDA 测试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradebookEntry {
+    pub student: String,
+    pub assignment: String,
+    pub kind: String,               // "assignment" | "wida"
+    pub score: Option<f64>,         // 正确率（0-100），练习记录已被清理时为空
+    pub date: String,
+}
+
+/// 创建作业请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAssignmentRequest {
+    pub user_name: String,
+    pub article_id: i64,
+    pub segment_type: String,
+    pub title: String,
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub check_options: Option<AnswerCheckOptions>,
+}
+
+/// 教师面板 - 单个学生概况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardUserSummary {
+    pub user_name: String,
+    pub total_practices: i32,
+    pub avg_accuracy: f64,
+    pub total_duration_minutes: f64,
+    pub mastered_words: i32,        // mastery_level >= 4 的单词数
+    pub due_words: i32,             // 当前到期待复习的单词数
+    pub pending_assignments: i32,
+    pub latest_wida_score: Option<f64>,
+    pub last_active_at: Option<String>,
+}
+
+/// 教师面板总览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardOverview {
+    pub article_count: i32,
+    pub users: Vec<DashboardUserSummary>,
+    pub pending_assignments: i32,
+    pub completed_assignments: i32,
 }
 
 // ========== WIDA 测试模块 ==========
@@ -269,11 +919,70 @@ pub struct WidaListeningQuestion {
     pub audio_text: String,         // 音频文本（用于TTS播放）
     pub image_url: Option<String>,  // 配图（可选）
     pub question_text: String,      // 问题文本
+    pub question_type: String,      // "multiple_choice" | "multi_select" | "short_answer"
     pub options: Vec<String>,       // 选项 A, B, C, D
     pub correct_answer: i32,        // 正确答案索引 (0-3)
+    pub correct_answers: Vec<i32>,  // 多选题的全部正确选项索引
+    pub answer_pattern: Option<String>, // 简答题：逗号分隔的关键词，或 /正则/
     pub explanation: Option<String>,// 答案解析
 }
 
+/// 组织混合练习请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildMixedSessionRequest {
+    pub user_name: String,
+    pub article_id: i64,
+    pub segment_type: String,
+    pub limit: i32,                 // 拼写题数量
+    #[serde(default)]
+    pub quiz_every: Option<i32>,    // 每隔几个拼写题穿插一次听力题，默认 5
+    #[serde(default)]
+    pub questions_per_quiz: Option<i32>, // 每次穿插的听力题数量（1-2），默认 1
+}
+
+/// 混合练习中的一题：拼写题带 `word`，听力题带 `question`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedSessionItem {
+    pub index: i32,
+    pub kind: String,               // "spelling" | "listening"
+    pub word: Option<ScheduledWord>,
+    pub question: Option<WidaListeningQuestion>,
+}
+
+/// 混合练习
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedSession {
+    pub id: i64,
+    pub user_name: String,
+    pub grade_level: String,        // 听力题的年级段
+    pub difficulty: i32,            // 听力题的目标难度（1-6）
+    pub items: Vec<MixedSessionItem>,
+}
+
+/// 混合练习中一题的判定结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedItemResult {
+    pub index: i32,
+    pub kind: String,
+    pub correct: bool,
+    pub credit: f64,                // 得分比例（0-1）
+    pub expected: String,           // 正确拼写或听力题的正确答案
+    pub mastery: Option<WordMastery>, // 拼写题更新后的熟练度
+}
+
+/// 混合练习小结
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedSessionSummary {
+    pub session_id: i64,
+    pub total_items: i32,
+    pub answered: i32,
+    pub spelling_total: i32,
+    pub spelling_correct: i32,
+    pub listening_total: i32,
+    pub listening_correct: i32,
+    pub listening_credit: f64,      // 听力题得分合计（多选、简答题可得部分分）
+}
+
 /// WIDA 题目 - 阅读选择题
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WidaReadingQuestion {
@@ -283,9 +992,11 @@ pub struct WidaReadingQuestion {
     pub difficulty: i32,
     pub passage: String,            // 阅读文章
     pub question_text: String,
-    pub question_type: String,      // "multiple_choice" | "true_false" | "matching"
+    pub question_type: String,      // "multiple_choice" | "true_false" | "matching" | "multi_select" | "short_answer"
     pub options: Vec<String>,
     pub correct_answer: i32,
+    pub correct_answers: Vec<i32>,
+    pub answer_pattern: Option<String>,
     pub explanation: Option<String>,
 }
 
@@ -320,6 +1031,14 @@ pub struct WidaWritingQuestion {
     pub sample_answer: Option<String>,
 }
 
+/// 补充内置题目的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReseedReport {
+    pub added: i32,                 // 新写入的内置题目
+    pub reset: i32,                 // 恢复为内置内容的题目（force 时）
+    pub skipped: i32,               // 与用户已有题目内容相同而跳过的题目
+}
+
 /// WIDA 测试会话
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WidaTestSession {
@@ -343,9 +1062,27 @@ pub struct WidaTestSession {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WidaTestAnswer {
     pub question_id: i64,
-    pub user_answer: String,        // 用户答案（选择题为选项索引，写作题为文本）
+    pub user_answer: String,        // 用户答案（选择题为选项索引，多选题为逗号分隔的索引，简答和写作题为文本）
     pub is_correct: Option<bool>,   // 是否正确（写作题需要人工评分）
     pub time_spent_seconds: i32,    // 答题用时
+    #[serde(default)]
+    pub replay_count: i32,          // 音频播放次数
+}
+
+/// 口语题录音的转写（语音识别后可修改，确认后参与评分）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakingTranscript {
+    pub session_id: i64,
+    pub question_id: i64,
+    pub prompt_text: String,
+    pub rubric: Vec<String>,
+    pub stt_text: Option<String>,   // 语音识别的原始结果，手动录入时为空
+    pub transcript: String,
+    pub confidence: Option<f64>,    // 语音识别的可信度（0-1）
+    pub status: String,             // "pending" | "confirmed"
+    pub estimated_credit: f64,      // 按当前转写估算的得分比例（0-1）
+    pub updated_at: Option<String>,
+    pub confirmed_at: Option<String>,
 }
 
 /// 开始 WIDA 测试请求
@@ -356,6 +1093,60 @@ pub struct StartWidaTestRequest {
     pub grade_level: String,
     pub domain: Option<String>,
     pub question_count: i32,        // 题目数量
+    #[serde(default)]
+    pub blueprint_id: Option<i64>,  // 按蓝图组卷，指定时忽略 domain 和 question_count
+    #[serde(default)]
+    pub max_audio_plays: Option<i32>, // 每道题音频最多播放次数，不指定时为 2
+    #[serde(default)]
+    pub sample_count: Option<i32>,  // 开头不计分的示例题数量，不指定时为 2（题库中有多少用多少）
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,  // 只从带有其中任一标签的题目中抽题（如专题题库）
+}
+
+/// 题库搜索条件（未设置的条件不限制）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestionSearchFilters {
+    pub test_type: Option<String>,
+    pub grade_level: Option<String>,
+    pub domain: Option<String>,
+    pub min_difficulty: Option<i32>,
+    pub max_difficulty: Option<i32>,
+    pub tags: Option<Vec<String>>,  // 带有任一标签即可
+    pub text: Option<String>,       // 在题干、听力原文、阅读文章和写作提示中查找
+    pub limit: Option<i32>,         // 默认 200
+}
+
+/// 题库搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionSearchResult {
+    pub test_type: String,
+    pub question_id: i64,
+    pub grade_level: String,
+    pub domain: String,
+    pub difficulty: i32,
+    pub prompt: String,             // 题干或写作提示
+    pub is_sample: bool,
+    pub tags: Vec<String>,
+}
+
+/// WIDA 测试蓝图：规定一次测试中各学科领域、难度的题目数量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidaTestBlueprint {
+    pub id: i64,
+    pub name: String,
+    pub test_type: String,
+    pub sections: Vec<BlueprintSection>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// 蓝图中的一组题目，例如“3 道难度 1-2 的 social 题”
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlueprintSection {
+    pub domain: Option<String>,     // 为空时不限学科领域
+    pub min_difficulty: i32,        // 1-6
+    pub max_difficulty: i32,
+    pub count: i32,
 }
 
 /// 提交答案请求
@@ -371,6 +1162,9 @@ pub struct SubmitWidaAnswerRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompleteWidaTestRequest {
     pub session_id: i64,
+    /// 报告文字的语言（en / zh），默认中文
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 /// WIDA 测试结果报告
@@ -388,6 +1182,35 @@ pub struct WidaTestReport {
     pub proficiency_level: i32,     // 1-6
     pub proficiency_level_name: String,
     pub details: Vec<WidaAnswerDetail>,
+    pub integrity: WidaIntegritySummary, // 离开窗口和暂停的记录
+}
+
+/// 学生在测试中反馈的问题题目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidaQuestionFlag {
+    pub id: i64,
+    pub session_id: i64,
+    pub user_name: String,
+    pub test_type: String,
+    pub question_id: i64,
+    pub question_text: String,      // 题目已删除时为空
+    pub reason: String,
+    pub status: String,             // "open" | "resolved" | "dismissed"
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub open_flags: i32,            // 该题待处理的反馈数
+}
+
+/// WIDA 测试的完整性摘要（供老师查看）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WidaIntegritySummary {
+    pub session_id: i64,
+    pub focus_loss_count: i32,      // 离开窗口（失去焦点）的次数
+    pub focus_loss_seconds: i64,    // 离开窗口的累计秒数
+    pub longest_focus_loss_seconds: i64,
+    pub pause_count: i32,
+    pub pause_seconds: i64,
+    pub flagged: bool,              // 离开次数或时长超过阈值，建议老师复核
 }
 
 /// 答案详情
@@ -398,7 +1221,9 @@ pub struct WidaAnswerDetail {
     pub user_answer: String,
     pub correct_answer: String,
     pub is_correct: bool,
+    pub credit: f64,                // 得分比例 0-1，多选和简答题可得部分分
     pub time_spent_seconds: i32,
+    pub replay_count: i32,          // 音频播放次数
     pub explanation: Option<String>,
 }
 
@@ -418,6 +1243,125 @@ pub struct WidaHistoryRecord {
     pub completed_at: String,
 }
 
+/// 下次 WIDA 测试的年级段和起始难度推荐
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeRecommendation {
+    pub user_name: String,
+    pub grade_level: String,        // grade_1_2 | grade_3_5 | grade_6_8 | grade_9_12
+    pub difficulty: i32,            // 1-6
+    pub age: Option<i32>,           // 按出生年份计算，未设置时为空
+    pub recent_proficiency_level: Option<f64>, // 最近几次测试的平均能力等级
+    pub reason: String,             // 推荐依据
+}
+
+/// 构词分析结果，各部分按顺序拼接即为原词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Morphology {
+    pub word: String,
+    pub parts: Vec<MorphemePart>,
+    pub base: Option<String>,           // 词干的原形与拼写不同时（happi → happy、runn → run）
+}
+
+/// 单词的一个组成部分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MorphemePart {
+    pub text: String,
+    pub kind: String,                   // "prefix" | "root" | "suffix"
+    pub meaning: Option<String>,        // 词缀或词根的含义
+}
+
+/// 听力/阅读文本的年级段检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeLevelCheck {
+    pub grade_level: String,
+    pub status: String,             // "ok" | "warning" | "rejected"
+    pub avg_sentence_length: f64,   // 平均每句单词数
+    pub longest_sentence: i32,
+    pub rare_word_ratio: f64,       // 不在常用词表中的单词比例 0-1
+    pub rare_words: Vec<String>,
+    pub issues: Vec<String>,
+}
+
+/// 难度校准中被标记的题目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyCalibration {
+    pub test_type: String,
+    pub question_id: i64,
+    pub stored_difficulty: i32,
+    pub empirical_difficulty: i32,  // 按平均得分率估计的难度
+    pub attempts: i32,
+    pub p_value: f64,               // 平均得分率 0-1
+}
+
+/// 难度校准结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyRecalibration {
+    pub dry_run: bool,
+    pub min_attempts: i32,          // 作答次数不足的题目不参与校准
+    pub examined: i32,
+    pub updated: i32,
+    pub flagged: Vec<DifficultyCalibration>,
+}
+
+/// 入门分级测试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementTest {
+    pub id: i64,
+    pub user_name: String,
+    pub grade_level: String,
+    pub status: String,             // "in_progress" | "completed"
+    pub difficulty: i32,            // 下一题的难度 1-6
+    pub items: Vec<PlacementItem>,
+    pub total_items: i32,
+    pub proficiency_level: Option<i32>,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// 分级测试中出过的一道题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementItem {
+    pub test_type: String,          // "listening" | "reading"
+    pub question_id: i64,
+    pub difficulty: i32,
+    pub correct: Option<bool>,      // 未作答时为空
+}
+
+/// 分级测试的下一道题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementQuestion {
+    pub test_id: i64,
+    pub index: i32,
+    pub total_items: i32,
+    pub test_type: String,
+    pub difficulty: i32,
+    pub question: serde_json::Value, // WidaListeningQuestion 或 WidaReadingQuestion
+}
+
+/// 分级测试答题结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementAnswerResult {
+    pub correct: bool,
+    pub credit: f64,
+    pub next_difficulty: i32,
+    pub remaining: i32,
+}
+
+/// 分级测试得出的初始能力档案和推荐的每日设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacementProfile {
+    pub test_id: i64,
+    pub user_name: String,
+    pub grade_level: String,
+    pub proficiency_level: i32,
+    pub proficiency_level_name: String,
+    pub listening_level: Option<i32>,
+    pub reading_level: Option<i32>,
+    pub accuracy: f64,
+    pub daily_new_words: i32,       // 推荐的每日新词数
+    pub daily_settings_applied: bool, // 用户原来没有设置，已按推荐值保存
+}
+
 /// WIDA 综合报告
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WidaComprehensiveReport {
@@ -437,3 +1381,354 @@ pub struct WidaComprehensiveReport {
     pub test_count: i32,
     pub last_test_date: String,
 }
+
+/// AI 出题提示词模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub test_type: String,              // listening | reading | speaking | writing | article
+    pub template: String,
+    pub is_custom: bool,                // 是否为用户自定义
+    pub placeholders: Vec<String>,      // 可用的 {占位符}
+}
+
+/// 单词配图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordImage {
+    pub word: String,
+    pub file_path: String,          // 本地图片文件，前端用 convertFileSrc 显示
+    pub source: String,             // local | api
+    pub source_url: Option<String>, // 从 API 下载时的原图地址
+    pub created_at: String,
+}
+
+/// 单词发音评分（0-100）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationAssessment {
+    pub segment_id: i64,
+    pub word: String,
+    pub overall_score: f64,
+    pub phonemes: Vec<PhonemeScore>,
+    pub assessed_at: Option<String>,
+}
+
+/// 单个音素的评分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhonemeScore {
+    pub phoneme: String,
+    pub score: f64,
+}
+
+/// 朗读文章的录音
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingRecording {
+    pub id: i64,
+    pub user_name: String,
+    pub article_id: i64,
+    pub status: String,             // recording | completed
+    pub file_path: Option<String>,
+    pub word_count: i32,
+    pub duration_ms: Option<i64>,
+    pub wpm: Option<f64>,           // 每分钟朗读单词数
+    pub started_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// 从录音文件解码出的时长和波形
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingAudio {
+    pub duration_ms: i64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub waveform: Vec<f32>,             // 降采样后的峰值包络（0-1），最多 200 个点
+}
+
+/// 录音的播放元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingMetadata {
+    pub recording_id: i64,
+    pub duration_ms: Option<i64>,       // 解码出的时长，无法解码时为录音时记录的时长
+    pub audio: Option<RecordingAudio>,  // 无法解码（如 WebM 中的 Opus）时为空
+}
+
+/// 同一篇文章多次朗读的流利度趋势
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FluencyTrend {
+    pub user_name: String,
+    pub article_id: i64,
+    pub first_wpm: Option<f64>,
+    pub latest_wpm: Option<f64>,
+    pub best_wpm: Option<f64>,
+    pub change_percent: Option<f64>, // 最近一次相对第一次的提升，至少两次朗读才有
+    pub readings: Vec<ReadingRecording>,
+}
+
+/// 审计日志条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub user_name: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: i64,
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+/// 用户学习状态导出：只含熟练度、错词、练习记录和设置，按单词内容和文章标题关联（不含行 ID），
+/// 可以导入到文章 ID 不同的另一台电脑
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningStateExport {
+    pub format_version: i32,
+    pub user_name: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub mastery: Vec<ExportedMastery>,
+    #[serde(default)]
+    pub mistakes: Vec<ExportedMistake>,
+    #[serde(default)]
+    pub practice_history: Vec<ExportedPractice>,
+    #[serde(default)]
+    pub wida_history: Vec<ExportedWidaResult>,
+    #[serde(default)]
+    pub settings: ExportedSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMastery {
+    pub content: String,
+    pub segment_type: String,
+    pub mastery_level: i32,
+    pub ease_factor: f64,
+    pub interval_days: i32,
+    pub next_review_at: Option<String>,
+    pub last_review_at: Option<String>,
+    pub review_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMistake {
+    pub content: String,
+    pub segment_type: String,
+    pub error_count: i32,
+    pub last_error_at: Option<String>,
+    pub correct_streak: i32,
+    pub archived_at: Option<String>,
+    pub archive_reason: Option<String>,
+}
+
+/// 练习记录，按文章标题关联
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedPractice {
+    pub article_title: String,
+    pub segment_type: String,
+    pub correct_count: i32,
+    pub incorrect_count: i32,
+    pub total_count: i32,
+    pub accuracy: f64,
+    pub wpm: f64,
+    pub duration_seconds: i32,
+    pub completed_at: String,
+    pub goal: Option<String>,
+    pub goal_met: Option<bool>,
+    #[serde(default = "default_practice_mode")]
+    pub mode: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedWidaResult {
+    pub test_type: String,
+    pub grade_level: String,
+    pub score: f64,
+    pub proficiency_level: i32,
+    pub accuracy: f64,
+    pub total_questions: i32,
+    pub correct_count: i32,
+    pub duration_seconds: i32,
+    pub completed_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportedSettings {
+    pub birth_year: Option<i32>,
+    pub daily_new_words: Option<i32>,   // 每日新词上限，未设置时不限制
+    pub carryover: bool,
+    pub content_keyed_mastery: bool,
+    #[serde(default)]
+    pub accept_spelling_variants: bool,
+    pub keyboard_layout: Option<String>,
+    pub accommodations: Option<Accommodations>,
+}
+
+/// 学习状态导入结果；本机没有的单词和文章无法关联，列在 `unmatched_*` 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningStateImport {
+    pub user_name: String,
+    pub mastery_imported: i32,
+    pub mistakes_imported: i32,
+    pub practice_imported: i32,
+    pub wida_imported: i32,
+    pub unmatched_words: Vec<String>,
+    pub unmatched_articles: Vec<String>,
+}
+
+/// 当前数据目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDirectoryInfo {
+    pub path: String,
+    pub default_path: String,
+    pub portable: bool,     // 便携模式：数据在可执行文件旁的 data 目录
+    pub custom: bool,       // 使用自定义目录
+}
+
+/// 数据目录迁移结果；旧目录中的数据保留不删
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDirectoryMigration {
+    pub from: String,
+    pub to: String,
+    pub files_copied: usize,            // 数据库以外的文件（设置、图片、录音）
+    pub paths_updated: usize,           // 改写了文件路径的图片和录音记录
+    pub replaced_database: Option<String>, // 新目录中原有的数据库改名后的备份路径
+}
+
+/// 数据保留策略执行结果（dry-run 时为将被删除的行数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub months: u32,
+    pub cutoff: String,
+    pub dry_run: bool,
+    pub practice_history: i32,
+    pub practice_sessions: i32,
+    pub practice_events: i32,
+    pub wida_test_sessions: i32,
+}
+
+/// 按月汇总的练习记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PracticeRollup {
+    pub user_name: String,
+    pub month: String,
+    pub segment_type: String,
+    pub practice_count: i32,
+    pub correct_count: i32,
+    pub incorrect_count: i32,
+    pub total_count: i32,
+    pub avg_accuracy: f64,
+    pub avg_wpm: f64,
+    pub duration_seconds: i32,
+}
+
+/// AI 用量汇总（按月）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsageSummary {
+    pub month: String,                  // YYYY-MM
+    pub total_calls: i32,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub estimated_cost: f64,            // 美元，未知价格的模型不计入
+    pub breakdown: Vec<AiUsageBreakdown>,
+}
+
+/// 按服务商、模型和用途分组的 AI 用量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsageBreakdown {
+    pub provider: String,
+    pub model: String,
+    pub purpose: String,
+    pub calls: i32,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub estimated_cost: Option<f64>,    // 全部调用都无法估价时为空
+}
+
+/// 朗读服务状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsStatus {
+    pub speaking: bool,
+    pub current_text: Option<String>,   // 正在朗读的文本
+    pub queued: i32,                    // 排队等待朗读的数量
+}
+
+/// 开始音频复习请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartAudioReviewRequest {
+    pub user_name: String,
+    #[serde(default)]
+    pub limit: Option<i32>,             // 朗读的错词数，默认 10
+    #[serde(default)]
+    pub gap_seconds: Option<i32>,       // 每个单词之后的拼写时间（秒），默认 8
+    #[serde(default)]
+    pub rate: Option<i32>,              // 朗读语速（每分钟单词数）
+}
+
+/// 音频复习中朗读的一个错词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReviewItem {
+    pub index: i32,
+    pub segment_id: i64,
+    pub content: String,                // 朗读结束前前端不应显示
+    pub segment_type: String,
+    pub language: Option<String>,
+}
+
+/// 音频复习
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReviewSession {
+    pub id: i64,
+    pub user_name: String,
+    pub gap_seconds: i32,
+    pub items: Vec<AudioReviewItem>,
+}
+
+/// `audio-review-progress` 事件：开始朗读第 `index` 个单词，`finished` 时朗读全部结束
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReviewProgress {
+    pub session_id: i64,
+    pub index: i32,
+    pub total: i32,
+    pub finished: bool,
+}
+
+/// 学生对一个单词的自评
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReviewMark {
+    pub segment_id: i64,
+    pub correct: bool,
+}
+
+/// 音频复习结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReviewSummary {
+    pub session_id: i64,
+    pub total: i32,
+    pub marked: i32,                    // 有自评的单词数
+    pub correct: i32,
+    pub accuracy: f64,                  // 自评答对比例 0-100
+}
+
+/// 本机的麦克风
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioInputDevice {
+    pub name: String,
+    pub is_default: bool,               // 系统默认麦克风
+    pub selected: bool,                 // 设置中选择的麦克风
+    pub channels: Option<u16>,
+    pub sample_rate: Option<u32>,
+}
+
+/// 音频设置（本机）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioSettings {
+    #[serde(default)]
+    pub input_device: Option<String>,   // 为空时使用系统默认麦克风
+}
+
+/// 麦克风音量检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrophoneLevel {
+    pub device: String,
+    pub peak: f64,                      // 0-1
+    pub rms: f64,                       // 0-1
+    pub peak_db: f64,                   // dBFS，静音时为 -100
+    pub rms_db: f64,
+    pub status: String,                 // "silent" | "low" | "ok" | "too_loud"
+}
@@ -0,0 +1,199 @@
+use crate::models::ScheduledWord;
+
+/// 按顺序模式重排练习列表，返回实际使用的种子（仅 shuffled 模式有种子）
+///
+/// - `sequential`（默认）：保持原顺序
+/// - `shuffled`：按种子随机打乱，相同种子得到相同顺序，便于重练时复现
+/// - `interleaved`：按文章轮流穿插
+/// - `hardest_first`：熟练度低的优先，已学过的排在新词前面
+pub fn apply_order(words: &mut Vec<ScheduledWord>, mode: Option<&str>, seed: Option<u64>) -> Result<Option<u64>, String> {
+    match mode.unwrap_or("sequential") {
+        "sequential" => Ok(None),
+        "shuffled" => {
+            let seed = seed.unwrap_or_else(random_seed);
+            shuffle(words, seed);
+            Ok(Some(seed))
+        }
+        "interleaved" => {
+            interleave_by_article(words);
+            Ok(None)
+        }
+        "hardest_first" => {
+            words.sort_by_key(|w| (w.mastery_level, w.is_new));
+            Ok(None)
+        }
+        other => Err(format!("未知的顺序模式: {}", other)),
+    }
+}
+
+/// 相似词最多向后查找的距离，超出则保持原顺序，避免打乱到期优先级
+const SPREAD_LOOKAHEAD: usize = 4;
+
+/// 穿插相似词：尽量不让首字母相同或只差一个字母的单词连续出现
+///
+/// 贪心地从剩余单词的前 `SPREAD_LOOKAHEAD` 个里取第一个与上一个词不相似的，
+/// 找不到时取排在最前的，因此到期早的单词仍然基本靠前。
+pub fn spread_similar(words: &mut Vec<ScheduledWord>) {
+    let mut remaining: std::collections::VecDeque<ScheduledWord> = words.drain(..).collect();
+    while !remaining.is_empty() {
+        let pick = match words.last() {
+            Some(previous) => remaining
+                .iter()
+                .take(SPREAD_LOOKAHEAD + 1)
+                .position(|w| !looks_similar(&previous.content, &w.content))
+                .unwrap_or(0),
+            None => 0,
+        };
+        if let Some(word) = remaining.remove(pick) {
+            words.push(word);
+        }
+    }
+}
+
+/// 首字母相同，或只差一个字母（如 cat / bat）
+fn looks_similar(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+    if a.first().is_some_and(|c| b.first() == Some(c)) {
+        return true;
+    }
+    a.len().abs_diff(b.len()) <= 1 && edit_distance(&a, &b) <= 1
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Fisher-Yates 洗牌；使用自带的 SplitMix64，保证不同版本间同一种子顺序一致
+pub(crate) fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut rng = SplitMix64(seed);
+    for i in (1..items.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// 按文章分组后轮流取词，组内保持原顺序
+fn interleave_by_article(words: &mut Vec<ScheduledWord>) {
+    let mut groups: Vec<(i64, std::collections::VecDeque<ScheduledWord>)> = Vec::new();
+    for word in words.drain(..) {
+        match groups.iter_mut().find(|(article_id, _)| *article_id == word.article_id) {
+            Some((_, group)) => group.push_back(word),
+            None => groups.push((word.article_id, std::collections::VecDeque::from([word]))),
+        }
+    }
+
+    while groups.iter().any(|(_, group)| !group.is_empty()) {
+        for (_, group) in groups.iter_mut() {
+            if let Some(word) = group.pop_front() {
+                words.push(word);
+            }
+        }
+    }
+}
+
+/// 复习日期的随机浮动比例（±10%）
+const INTERVAL_FUZZ: f64 = 0.1;
+
+/// 复习间隔允许浮动的天数：间隔的 10%，四舍五入（短间隔不浮动）
+pub(crate) fn fuzz_range(interval_days: i32) -> i32 {
+    (interval_days as f64 * INTERVAL_FUZZ).round() as i32
+}
+
+/// 在 ±`fuzz_range` 天内随机取一个偏移，避免同一天学的词在同一天集中到期
+pub(crate) fn fuzz_days(interval_days: i32, seed: u64) -> i32 {
+    let range = fuzz_range(interval_days);
+    if range <= 0 {
+        return 0;
+    }
+    (SplitMix64(seed).next() % (2 * range as u64 + 1)) as i32 - range
+}
+
+/// 种子限制在 2^53 以内，前端用 JS number 保存时不会丢失精度
+const MAX_SEED: u64 = (1 << 53) - 1;
+
+pub(crate) fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+        & MAX_SEED
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(contents: &[&str]) -> Vec<ScheduledWord> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| ScheduledWord {
+                segment_id: i as i64 + 1,
+                article_id: 1,
+                content: content.to_string(),
+                segment_type: "word".to_string(),
+                mastery_level: 0,
+                is_new: true,
+                next_review_at: String::new(),
+                is_leech: false,
+            })
+            .collect()
+    }
+
+    fn contents(words: &[ScheduledWord]) -> Vec<&str> {
+        words.iter().map(|w| w.content.as_str()).collect()
+    }
+
+    /// 相同种子复现相同顺序，未知模式报错
+    #[test]
+    fn test_apply_order_seed() {
+        let mut first = words(&["apple", "banana", "cherry", "date", "elder"]);
+        let seed = apply_order(&mut first, Some("shuffled"), None).unwrap().unwrap();
+        assert!(seed <= MAX_SEED);
+        let mut replay = words(&["apple", "banana", "cherry", "date", "elder"]);
+        assert_eq!(apply_order(&mut replay, Some("shuffled"), Some(seed)).unwrap(), Some(seed));
+        assert_eq!(contents(&first), contents(&replay));
+        assert!(apply_order(&mut replay, Some("random"), None).is_err());
+    }
+
+    /// 首字母相同或只差一个字母的单词不连续出现
+    #[test]
+    fn test_spread_similar() {
+        let mut scheduled = words(&["apple", "ant", "axe", "bee", "cat", "bat"]);
+        spread_similar(&mut scheduled);
+        assert_eq!(contents(&scheduled), vec!["apple", "bee", "ant", "cat", "axe", "bat"]);
+    }
+
+    /// 到期日随机浮动不超过 ±10%，短间隔不浮动
+    #[test]
+    fn test_fuzz_days() {
+        assert_eq!(fuzz_range(3), 0);
+        assert_eq!(fuzz_range(30), 3);
+        for seed in 0..100 {
+            assert!(fuzz_days(30, seed).abs() <= 3);
+            assert_eq!(fuzz_days(1, seed), 0);
+        }
+    }
+}
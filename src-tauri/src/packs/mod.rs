@@ -0,0 +1,278 @@
+//! `.spelling-pack` 单元包：老师把准备好的文章、分词、题目和题目音频打包分发
+//!
+//! 包是一个 zip：`manifest.json` 记录格式版本和每个文件的 SHA-256，
+//! `articles/NNN.json` 每篇文章一个文件，`audio/` 下是题目音频缓存。导入时逐个校验。
+
+use std::io::{Cursor, Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::commands::wida::{GeneratedListeningQuestion, GeneratedReadingQuestion};
+
+pub const PACK_EXTENSION: &str = "spelling-pack";
+const PACK_FORMAT: &str = "spelling-pack";
+/// 包格式版本，格式不兼容地变化时加一
+pub const PACK_VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+/// 清单文件的大小上限
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+/// 清单中所有文件解压后的总大小上限
+const MAX_UNPACKED_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 包清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,        // 打包时的程序版本
+    pub title: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub articles: Vec<String>,      // 文章标题
+    pub files: Vec<PackFile>,
+}
+
+/// 包内文件及其校验和
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// 包内的一篇文章（不含行 ID）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackArticle {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub segments: Vec<PackSegment>,
+    #[serde(default)]
+    pub listening_questions: Vec<GeneratedListeningQuestion>,
+    #[serde(default)]
+    pub reading_questions: Vec<GeneratedReadingQuestion>,
+}
+
+/// 分词，按类型内的顺序排列；字幕导入的分词带时间轴
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSegment {
+    pub segment_type: String,
+    pub content: String,
+    #[serde(default)]
+    pub start_ms: Option<i64>,
+    #[serde(default)]
+    pub end_ms: Option<i64>,
+}
+
+/// 解开并校验过的包
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub manifest: PackManifest,
+    pub articles: Vec<PackArticle>,
+    pub audio: Vec<(String, Vec<u8>)>,  // (文件名, 内容)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 只接受不含目录的普通文件名，避免解包时写到缓存目录以外
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+/// 打包，返回 zip 内容
+pub fn write_pack(
+    title: &str,
+    description: Option<&str>,
+    articles: &[PackArticle],
+    audio: &[(String, Vec<u8>)],
+) -> Result<Vec<u8>, String> {
+    if articles.is_empty() {
+        return Err("单元包至少需要一篇文章".to_string());
+    }
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    for (i, article) in articles.iter().enumerate() {
+        let json = serde_json::to_vec_pretty(article).map_err(|e| e.to_string())?;
+        entries.push((format!("articles/{:03}.json", i + 1), json));
+    }
+    for (name, data) in audio {
+        if !is_plain_file_name(name) {
+            return Err(format!("无效的音频文件名: {}", name));
+        }
+        entries.push((format!("audio/{}", name), data.clone()));
+    }
+
+    let manifest = PackManifest {
+        format: PACK_FORMAT.to_string(),
+        version: PACK_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        title: title.to_string(),
+        description: description.map(str::to_string),
+        created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        articles: articles.iter().map(|a| a.title.clone()).collect(),
+        files: entries
+            .iter()
+            .map(|(path, data)| PackFile { path: path.clone(), size: data.len() as u64, sha256: sha256_hex(data) })
+            .collect(),
+    };
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    for (path, data) in std::iter::once((MANIFEST.to_string(), manifest_json)).chain(entries) {
+        zip.start_file(path, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+/// 解包并校验：格式版本、清单中每个文件的大小和 SHA-256，清单外的文件视为损坏
+pub fn read_pack(bytes: &[u8]) -> Result<Pack, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("无效的单元包: {}", e))?;
+    // 最多读取 limit + 1 字节，解压后超过上限的文件（zip 炸弹）不会被完整读入内存
+    let mut read_entry = |name: &str, limit: u64| -> Result<Vec<u8>, String> {
+        let entry = archive.by_name(name).map_err(|e| format!("{}: {}", name, e))?;
+        let mut data = Vec::new();
+        entry.take(limit + 1).read_to_end(&mut data).map_err(|e| format!("{}: {}", name, e))?;
+        if data.len() as u64 > limit {
+            return Err(format!("单元包已损坏，文件过大: {}", name));
+        }
+        Ok(data)
+    };
+
+    let manifest: PackManifest =
+        serde_json::from_slice(&read_entry(MANIFEST, MAX_MANIFEST_BYTES)?).map_err(|e| format!("单元包清单无效: {}", e))?;
+    if manifest.format != PACK_FORMAT {
+        return Err(format!("不是单元包: {}", manifest.format));
+    }
+    if manifest.version > PACK_VERSION {
+        return Err(format!("单元包版本 {} 过新（由 {} 版程序打包），请先升级程序", manifest.version, manifest.app_version));
+    }
+
+    if manifest.files.iter().try_fold(0u64, |total, file| total.checked_add(file.size)).is_none_or(|total| total > MAX_UNPACKED_BYTES) {
+        return Err("单元包过大".to_string());
+    }
+
+    let mut articles = Vec::new();
+    let mut audio = Vec::new();
+    for file in &manifest.files {
+        let data = read_entry(&file.path, file.size)?;
+        if data.len() as u64 != file.size || sha256_hex(&data) != file.sha256 {
+            return Err(format!("单元包已损坏，校验失败: {}", file.path));
+        }
+        if let Some(name) = file.path.strip_prefix("audio/") {
+            if !is_plain_file_name(name) {
+                return Err(format!("无效的音频文件名: {}", name));
+            }
+            audio.push((name.to_string(), data));
+        } else if file.path.starts_with("articles/") {
+            articles.push(serde_json::from_slice(&data).map_err(|e| format!("{}: {}", file.path, e))?);
+        }
+    }
+    let unlisted = archive
+        .file_names()
+        .find(|name| *name != MANIFEST && !name.ends_with('/') && !manifest.files.iter().any(|f| f.path == *name))
+        .map(str::to_string);
+    if let Some(name) = unlisted {
+        return Err(format!("单元包中有清单以外的文件: {}", name));
+    }
+    if articles.is_empty() {
+        return Err("单元包中没有文章".to_string());
+    }
+    Ok(Pack { manifest, articles, audio })
+}
+
+/// 校验发布者对包文件的 Ed25519 签名，签名和公钥均为 base64
+pub fn verify_signature(data: &[u8], signature: &str, public_key: &str) -> Result<(), String> {
+    use base64::Engine;
+    let decode = |value: &str| base64::engine::general_purpose::STANDARD.decode(value.trim());
+    let signature = decode(signature).map_err(|_| "签名格式无效".to_string())?;
+    let public_key = decode(public_key).map_err(|_| "公钥格式无效".to_string())?;
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+        .verify(data, &signature)
+        .map_err(|_| "签名校验失败，单元包可能被篡改或不是该发布者签名的".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zip_of(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (path, data) in files {
+            zip.start_file(*path, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn manifest_json(files: Vec<PackFile>) -> Vec<u8> {
+        serde_json::to_vec(&PackManifest {
+            format: PACK_FORMAT.to_string(),
+            version: PACK_VERSION,
+            app_version: "1.0.0".to_string(),
+            title: "Unit".to_string(),
+            description: None,
+            created_at: "2024-01-01 00:00:00".to_string(),
+            articles: vec![],
+            files,
+        })
+        .unwrap()
+    }
+
+    /// 解压后超过清单大小的文件和过大的清单只读到上限即报错
+    #[test]
+    fn test_read_pack_limits_entry_size() {
+        let bomb = vec![0u8; 4 * 1024 * 1024];
+        let listed = PackFile { path: "audio/a.aiff".to_string(), size: 16, sha256: sha256_hex(&bomb) };
+        let bytes = zip_of(&[(MANIFEST, manifest_json(vec![listed])), ("audio/a.aiff", bomb)]);
+        assert!(bytes.len() < 64 * 1024);
+        assert!(read_pack(&bytes).unwrap_err().contains("文件过大: audio/a.aiff"));
+
+        let padded = [manifest_json(vec![]), vec![b' '; MAX_MANIFEST_BYTES as usize]].concat();
+        assert!(read_pack(&zip_of(&[(MANIFEST, padded)])).unwrap_err().contains("文件过大: manifest.json"));
+
+        let huge = |path: &str| PackFile { path: path.to_string(), size: u64::MAX / 2 + 1, sha256: String::new() };
+        let bytes = zip_of(&[(MANIFEST, manifest_json(vec![huge("audio/a.aiff"), huge("audio/b.aiff")]))]);
+        assert_eq!(read_pack(&bytes).unwrap_err(), "单元包过大");
+    }
+
+    /// 改动包内文件后校验失败
+    #[test]
+    fn test_read_pack_detects_tampering() {
+        let article = PackArticle {
+            title: "Fruit".to_string(),
+            content: "apple".to_string(),
+            tags: vec![],
+            segments: vec![],
+            listening_questions: vec![],
+            reading_questions: vec![],
+        };
+        let bytes = write_pack("Unit 3", None, &[article], &[("0123456789abcdef.aiff".to_string(), b"audio".to_vec())]).unwrap();
+        let pack = read_pack(&bytes).unwrap();
+        assert_eq!(pack.manifest.version, PACK_VERSION);
+        assert_eq!(pack.articles[0].content, "apple");
+        assert_eq!(pack.audio.len(), 1);
+
+        let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let mut files = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            if entry.name().starts_with("articles/") {
+                data = String::from_utf8(data).unwrap().replace("apple", "appel").into_bytes();
+            }
+            files.push((entry.name().to_string(), data));
+        }
+        let files: Vec<(&str, Vec<u8>)> = files.iter().map(|(name, data)| (name.as_str(), data.clone())).collect();
+        assert!(read_pack(&zip_of(&files)).unwrap_err().contains("校验失败"));
+    }
+}
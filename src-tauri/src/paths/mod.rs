@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+/// 数据库文件名
+pub const DB_FILE: &str = "spelling.db";
+/// 便携模式标记文件：放在可执行文件旁边时，数据保存在可执行文件旁的 `data` 目录
+pub const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DATA_DIR: &str = "data";
+/// 系统默认数据目录中记录自定义数据目录的文件
+const LOCATION_FILE: &str = "data_location.txt";
+
+/// 应用数据目录（不存在时自动创建）
+///
+/// 桌面端与移动端统一走 Tauri 的路径解析：iOS/Android 上对应应用沙盒内的目录，
+/// 不要自行拼接 HOME 等路径。便携模式或设置了自定义目录时使用对应目录。
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = resolve_data_dir(&default_data_dir(app)?, exe_dir().as_deref());
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// 应用数据目录下的子目录（不存在时自动创建）
+pub fn app_data_subdir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app_data_dir(app)?.join(name);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// 应用数据目录下的文件路径
+pub fn app_data_file(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    Ok(app_data_dir(app)?.join(file_name))
+}
+
+/// 系统默认的应用数据目录（不考虑便携模式和自定义目录）
+pub fn default_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+/// 可执行文件所在目录
+pub fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+/// 便携模式的数据目录
+pub fn portable_data_dir(exe_dir: &Path) -> PathBuf {
+    exe_dir.join(PORTABLE_DATA_DIR)
+}
+
+pub fn is_portable(exe_dir: Option<&Path>) -> bool {
+    exe_dir.is_some_and(|dir| dir.join(PORTABLE_MARKER).is_file())
+}
+
+/// 实际使用的数据目录：便携模式 > 自定义目录 > 系统默认目录
+pub fn resolve_data_dir(default_dir: &Path, exe_dir: Option<&Path>) -> PathBuf {
+    match exe_dir.filter(|dir| is_portable(Some(dir))) {
+        Some(exe_dir) => portable_data_dir(exe_dir),
+        None => custom_data_dir(default_dir).unwrap_or_else(|| default_dir.to_path_buf()),
+    }
+}
+
+/// 记录在系统默认目录中的自定义数据目录
+pub fn custom_data_dir(default_dir: &Path) -> Option<PathBuf> {
+    let location = std::fs::read_to_string(default_dir.join(LOCATION_FILE)).ok()?;
+    Some(location.trim()).filter(|l| !l.is_empty()).map(PathBuf::from)
+}
+
+/// 设置自定义数据目录，`None` 恢复系统默认目录
+pub fn set_custom_data_dir(default_dir: &Path, dir: Option<&Path>) -> Result<(), String> {
+    let file = default_dir.join(LOCATION_FILE);
+    match dir {
+        Some(dir) => {
+            std::fs::create_dir_all(default_dir).map_err(|e| e.to_string())?;
+            std::fs::write(file, dir.to_string_lossy().as_bytes()).map_err(|e| e.to_string())
+        }
+        None if file.exists() => std::fs::remove_file(file).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// 把数据目录中除数据库和目录记录以外的文件（设置、图片、录音等）复制到新目录，返回复制的文件数
+pub fn copy_data_files(from: &Path, to: &Path) -> Result<usize, String> {
+    let mut copied = 0;
+    std::fs::create_dir_all(to).map_err(|e| e.to_string())?;
+    for entry in std::fs::read_dir(from).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+        if name_str.starts_with(DB_FILE) || name_str == LOCATION_FILE {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            copied += copy_data_files(&path, &to.join(&name))?;
+        } else {
+            std::fs::copy(&path, to.join(&name)).map_err(|e| format!("复制 {} 失败: {}", path.display(), e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
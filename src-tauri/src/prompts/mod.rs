@@ -0,0 +1,187 @@
+//! AI 出题提示词模板，`{name}` 形式的占位符在生成时替换
+//!
+//! 用户可覆盖任一模板（`customize_prompt_template`，保存在数据库），未覆盖时使用这里的默认模板。
+
+pub mod offline;
+
+/// 可定制的模板及其可用占位符
+pub const TEMPLATE_PLACEHOLDERS: &[(&str, &[&str])] = &[
+    ("listening", &["count", "grade_level", "domain", "difficulty"]),
+    ("reading", &["count", "grade_level", "domain", "difficulty"]),
+    ("speaking", &["count", "grade_level", "domain", "difficulty"]),
+    ("writing", &["count", "grade_level", "domain", "difficulty"]),
+    ("article", &["count", "grade_level", "difficulty", "kind", "title", "excerpt", "text_field", "text_hint"]),
+];
+
+/// 模板可用的占位符；未知的模板返回 None
+pub fn placeholders(test_type: &str) -> Option<&'static [&'static str]> {
+    TEMPLATE_PLACEHOLDERS.iter().find(|(name, _)| *name == test_type).map(|(_, p)| *p)
+}
+
+/// 默认模板
+pub fn default_template(test_type: &str) -> Option<&'static str> {
+    match test_type {
+        "listening" => Some(LISTENING),
+        "reading" => Some(READING),
+        "speaking" => Some(SPEAKING),
+        "writing" => Some(WRITING),
+        "article" => Some(ARTICLE),
+        _ => None,
+    }
+}
+
+/// 替换模板中的 `{name}` 占位符，未提供的占位符原样保留
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// 听力题
+const LISTENING: &str = r#"请生成 {count} 道WIDA英语听力测试题目。
+
+要求：
+- 年级水平: {grade_level} (对应难度等级: {difficulty})
+- 学科领域: {domain}
+- 难度等级: {difficulty}/6
+
+每道题目需要包含：
+1. audio_text: 听力文本（学生会听到的内容，适合用TTS朗读）
+2. question_text: 问题文本
+3. question_type: 题型，multiple_choice（单选，默认）、multi_select（多选）或 short_answer（简答）
+4. options: 4个选项 (A, B, C, D)，简答题为空数组
+5. correct_answer: 单选题的正确答案索引 (0-3)
+6. correct_answers: 多选题的全部正确答案索引，如 [0, 2]
+7. answer_pattern: 简答题的评分关键词（逗号分隔），或用 /正则/ 表示可接受的答案
+8. explanation: 答案解析
+
+请严格按照以下JSON格式返回，不要包含任何其他文字：
+[
+  {
+    "audio_text": "听力文本内容...",
+    "question_text": "问题...",
+    "options": ["选项A", "选项B", "选项C", "选项D"],
+    "correct_answer": 0,
+    "explanation": "解析..."
+  }
+]"#;
+
+/// 阅读题
+const READING: &str = r#"请生成 {count} 道WIDA英语阅读测试题目。
+
+要求：
+- 年级水平: {grade_level} (对应难度等级: {difficulty})
+- 学科领域: {domain}
+- 难度等级: {difficulty}/6
+
+每道题目需要包含：
+1. passage: 阅读文章（根据年级调整长度和难度）
+2. question_text: 问题文本
+3. question_type: 题型，multiple_choice（单选，默认）、multi_select（多选）或 short_answer（简答）
+4. options: 4个选项 (A, B, C, D)，简答题为空数组
+5. correct_answer: 单选题的正确答案索引 (0-3)
+6. correct_answers: 多选题的全部正确答案索引，如 [0, 2]
+7. answer_pattern: 简答题的评分关键词（逗号分隔），或用 /正则/ 表示可接受的答案
+8. explanation: 答案解析
+
+请严格按照以下JSON格式返回，不要包含任何其他文字：
+[
+  {
+    "passage": "阅读文章内容...",
+    "question_text": "问题...",
+    "options": ["选项A", "选项B", "选项C", "选项D"],
+    "correct_answer": 0,
+    "explanation": "解析..."
+  }
+]"#;
+
+/// 口语题
+const SPEAKING: &str = r#"请生成 {count} 道WIDA英语口语测试题目。
+
+要求：
+- 年级水平: {grade_level} (对应难度等级: {difficulty})
+- 学科领域: {domain}
+- 难度等级: {difficulty}/6
+- 所有题目必须包含图片描述
+
+每道题目需要包含：
+1. prompt_type: 必须是 "picture"
+2. prompt_text: 提示文本（让学生根据图片回答的问题或任务）
+3. image_description: 图片的详细描述（用于生成或选择合适的图片）
+4. sample_answer: 示范回答
+5. rubric: 评分标准（4个评价点）
+
+图片描述应该：
+- 清晰、具体，适合该年级水平
+- 包含学生需要描述或讨论的主要元素
+- 与学科领域相关
+- 激发学生的口语表达能力
+
+请严格按照以下JSON格式返回，不要包含任何其他文字：
+[
+  {
+    "prompt_type": "picture",
+    "prompt_text": "Look at the picture and describe what you see. / What is happening in this picture? / Tell a story about this picture.",
+    "image_description": "A detailed description of the image content (e.g., 'A family having a picnic in a park on a sunny day. There are trees, a blanket on the grass, a basket with food, and children playing with a ball.')",
+    "sample_answer": "示范回答...",
+    "rubric": ["评分标准1", "评分标准2", "评分标准3", "评分标准4"]
+  }
+]"#;
+
+/// 写作题
+const WRITING: &str = r#"请生成 {count} 道WIDA英语写作测试题目。
+
+要求：
+- 年级水平: {grade_level} (对应难度等级: {difficulty})
+- 学科领域: {domain}
+- 难度等级: {difficulty}/6
+
+每道题目需要包含：
+1. task_type: 任务类型 (argumentative | expository | personal_recount | email | letter | report)
+2. prompt: 写作提示
+3. word_limit_min: 最少字数
+4. word_limit_max: 最多字数
+5. rubric: 评分标准（4个评价点）
+6. sample_answer: 示范回答
+
+请严格按照以下JSON格式返回，不要包含任何其他文字：
+[
+  {
+    "task_type": "expository",
+    "prompt": "写作提示...",
+    "word_limit_min": 50,
+    "word_limit_max": 100,
+    "rubric": ["评分标准1", "评分标准2", "评分标准3", "评分标准4"],
+    "sample_answer": "示范回答..."
+  }
+]"#;
+
+/// 根据文章出听力/阅读题
+const ARTICLE: &str = r#"请根据下面的文章生成 {count} 道WIDA英语{kind}测试题目，题目必须依据文章内容，不要引入文章以外的事实。
+
+文章标题: {title}
+文章内容:
+"""
+{excerpt}
+"""
+
+要求：
+- 年级水平: {grade_level}
+- 难度等级: {difficulty}/6
+
+每道题目需要包含：
+1. {text_field}: {text_hint}
+2. question_text: 问题文本
+3. options: 4个选项 (A, B, C, D)
+4. correct_answer: 正确答案索引 (0-3)
+5. explanation: 答案解析
+
+请严格按照以下JSON格式返回，不要包含任何其他文字：
+[
+  {
+    "{text_field}": "...",
+    "question_text": "问题...",
+    "options": ["选项A", "选项B", "选项C", "选项D"],
+    "correct_answer": 0,
+    "explanation": "解析..."
+  }
+]"#;
@@ -0,0 +1,310 @@
+//! 离线出题：未配置 API Key 时，按规则从已有文章和题库里的阅读材料生成题目
+//!
+//! 听力/阅读生成完形填空和理解题（干扰项取自素材中的其他关键词或其他段落），
+//! 口语生成复述题，写作生成摘要题。
+
+use crate::commands::wida::{
+    GenerateQuestionsRequest, GeneratedListeningQuestion, GeneratedReadingQuestion, GeneratedSpeakingQuestion,
+    GeneratedWritingQuestion,
+};
+use crate::ordering::shuffle;
+
+/// 出题素材：一段文本及其来源文章
+#[derive(Debug, Clone)]
+pub struct Passage {
+    pub text: String,
+    pub source_article_id: Option<i64>,
+}
+
+/// 适合出题的段落长度（单词数）
+const MIN_PASSAGE_WORDS: usize = 20;
+const MAX_PASSAGE_WORDS: usize = 120;
+/// 选择题的干扰项数量
+const DISTRACTORS: usize = 3;
+/// 关键词池大小，完形填空的答案和干扰项都从中选取
+const VOCABULARY_POOL: usize = 300;
+/// 完形填空的空格线
+pub const BLANK: &str = "_____";
+
+/// 把文章按空行分段，过长的段落按句切成多段，过短的丢弃
+pub fn split_passages(content: &str, source_article_id: Option<i64>) -> Vec<Passage> {
+    let mut passages = Vec::new();
+    for paragraph in content.replace("\r\n", "\n").split("\n\n") {
+        let mut current: Vec<String> = Vec::new();
+        let mut words = 0;
+        for sentence in sentences(paragraph) {
+            let sentence_words = sentence.split_whitespace().count();
+            if words + sentence_words > MAX_PASSAGE_WORDS && words >= MIN_PASSAGE_WORDS {
+                passages.push(Passage { text: current.join(" "), source_article_id });
+                current.clear();
+                words = 0;
+            }
+            current.push(sentence);
+            words += sentence_words;
+        }
+        if words >= MIN_PASSAGE_WORDS {
+            passages.push(Passage { text: current.join(" "), source_article_id });
+        }
+    }
+    passages
+}
+
+/// 听力题：朗读整段，轮流出完形填空和“听到了哪个单词”
+pub fn listening_questions(
+    passages: &[Passage],
+    request: &GenerateQuestionsRequest,
+    seed: u64,
+) -> Vec<GeneratedListeningQuestion> {
+    let pool = vocabulary_pool(passages);
+    rotation(passages, request.count, 2, seed)
+        .filter_map(|(i, passage, round)| {
+            let seed = seed.wrapping_add(i as u64);
+            let (question_text, options, correct_answer, explanation) = if round == 0 {
+                let (sentence, answer) = pick_cloze_sentence(&passage.text, &pool)?;
+                let (options, correct) = choices(&answer, distractors(&answer, &pool, None, seed)?, seed);
+                let question = format!("Which word completes the sentence you heard: \"{}\"", blank_out(&sentence, &answer));
+                (question, options, correct, format!("The speaker said \"{}\".", sentence))
+            } else {
+                let answer = longest_keyword(&passage.text, &pool)?;
+                let (options, correct) =
+                    choices(&answer, distractors(&answer, &pool, Some(&passage.text), seed)?, seed);
+                let question = "Which word did you hear in the passage?".to_string();
+                (question, options, correct, format!("\"{}\" appears in the passage.", answer))
+            };
+            Some(GeneratedListeningQuestion {
+                grade_level: request.grade_level.clone(),
+                domain: request.domain.clone(),
+                difficulty: request.difficulty,
+                audio_text: passage.text.clone(),
+                image_url: None,
+                question_text,
+                question_type: "multiple_choice".to_string(),
+                options,
+                correct_answer,
+                correct_answers: Vec::new(),
+                answer_pattern: None,
+                explanation: Some(explanation),
+                source_article_id: passage.source_article_id,
+            })
+        })
+        .collect()
+}
+
+/// 阅读题：第一轮在段落中挖空，第二轮判断哪句话出自本段
+pub fn reading_questions(
+    passages: &[Passage],
+    request: &GenerateQuestionsRequest,
+    seed: u64,
+) -> Vec<GeneratedReadingQuestion> {
+    let pool = vocabulary_pool(passages);
+    rotation(passages, request.count, 2, seed)
+        .filter_map(|(i, passage, round)| {
+            let seed = seed.wrapping_add(i as u64);
+            let (text, question_text, options, correct_answer, explanation) = if round == 0 {
+                let (sentence, answer) = pick_cloze_sentence(&passage.text, &pool)?;
+                let (options, correct) = choices(&answer, distractors(&answer, &pool, None, seed)?, seed);
+                let text = passage.text.replacen(&sentence, &blank_out(&sentence, &answer), 1);
+                let question = "Which word best fills the blank in the passage?".to_string();
+                (text, question, options, correct, format!("The original sentence is \"{}\"", sentence))
+            } else {
+                let (answer, others) = comprehension_options(passages, passage, seed)?;
+                let (options, correct) = choices(&answer, others, seed);
+                let question = "According to the passage, which statement is true?".to_string();
+                (passage.text.clone(), question, options, correct, "This sentence comes from the passage.".to_string())
+            };
+            Some(GeneratedReadingQuestion {
+                grade_level: request.grade_level.clone(),
+                domain: request.domain.clone(),
+                difficulty: request.difficulty,
+                passage: text,
+                question_text,
+                question_type: "multiple_choice".to_string(),
+                options,
+                correct_answer,
+                correct_answers: Vec::new(),
+                answer_pattern: None,
+                explanation: Some(explanation),
+                source_article_id: passage.source_article_id,
+            })
+        })
+        .collect()
+}
+
+/// 口语题：听一段材料后复述
+pub fn speaking_questions(
+    passages: &[Passage],
+    request: &GenerateQuestionsRequest,
+    seed: u64,
+) -> Vec<GeneratedSpeakingQuestion> {
+    rotation(passages, request.count, 1, seed)
+        .map(|(_, passage, _)| GeneratedSpeakingQuestion {
+            grade_level: request.grade_level.clone(),
+            domain: request.domain.clone(),
+            difficulty: request.difficulty,
+            prompt_type: "audio".to_string(),
+            prompt_text: "Listen to the passage. Then retell it in your own words.".to_string(),
+            image_url: None,
+            audio_text: Some(passage.text.clone()),
+            sample_answer: sentences(&passage.text).into_iter().take(2).collect::<Vec<_>>().join(" "),
+            rubric: vec![
+                "Retells the main idea".to_string(),
+                "Includes key details from the passage".to_string(),
+                "Speaks in complete sentences".to_string(),
+                "Uses vocabulary from the passage".to_string(),
+            ],
+        })
+        .collect()
+}
+
+/// 写作题：阅读一段材料后写摘要
+pub fn writing_questions(
+    passages: &[Passage],
+    request: &GenerateQuestionsRequest,
+    seed: u64,
+) -> Vec<GeneratedWritingQuestion> {
+    let (word_limit_min, word_limit_max) = match request.grade_level.as_str() {
+        "grade_1_2" => (20, 50),
+        "grade_3_5" => (40, 80),
+        "grade_6_8" => (80, 150),
+        _ => (120, 200),
+    };
+    rotation(passages, request.count, 1, seed)
+        .map(|(_, passage, _)| GeneratedWritingQuestion {
+            grade_level: request.grade_level.clone(),
+            domain: request.domain.clone(),
+            difficulty: request.difficulty,
+            task_type: "expository".to_string(),
+            prompt: format!(
+                "Read the passage below. Write a summary of its main ideas in your own words.\n\n{}",
+                passage.text
+            ),
+            image_url: None,
+            word_limit_min,
+            word_limit_max,
+            rubric: vec![
+                "States the main idea".to_string(),
+                "Includes supporting details".to_string(),
+                "Uses own words instead of copying".to_string(),
+                "Correct grammar and spelling".to_string(),
+            ],
+            sample_answer: Some(sentences(&passage.text).into_iter().take(2).collect::<Vec<_>>().join(" ")),
+        })
+        .collect()
+}
+
+/// 按随机顺序轮流取段落，返回（序号，段落，第几轮），最多 `rounds` 轮
+fn rotation(
+    passages: &[Passage],
+    count: i32,
+    rounds: usize,
+    seed: u64,
+) -> impl Iterator<Item = (usize, &Passage, usize)> {
+    let mut order: Vec<usize> = (0..passages.len()).collect();
+    shuffle(&mut order, seed);
+    let total = (count.max(0) as usize).min(passages.len() * rounds);
+    (0..total).map(move |i| (i, &passages[order[i % order.len()]], i / order.len()))
+}
+
+fn vocabulary_pool(passages: &[Passage]) -> Vec<String> {
+    let texts: Vec<String> = passages.iter().map(|p| p.text.clone()).collect();
+    crate::text::extract_key_vocabulary(&texts, VOCABULARY_POOL)
+}
+
+/// 段落中含关键词的第一句，以及句中最长的关键词
+fn pick_cloze_sentence(text: &str, pool: &[String]) -> Option<(String, String)> {
+    sentences(text).into_iter().find_map(|sentence| {
+        let answer = longest_keyword(&sentence, pool)?;
+        Some((sentence, answer))
+    })
+}
+
+fn longest_keyword(text: &str, pool: &[String]) -> Option<String> {
+    crate::text::tokenize(text)
+        .into_iter()
+        .filter(|word| pool.contains(word))
+        .fold(None, |best: Option<String>, word| match best {
+            Some(best) if best.len() >= word.len() => Some(best),
+            _ => Some(word),
+        })
+}
+
+/// 把句中第一次出现的 `answer`（忽略大小写）替换成空格线，保留标点
+fn blank_out(sentence: &str, answer: &str) -> String {
+    let mut replaced = false;
+    sentence
+        .split(' ')
+        .map(|token| {
+            let core = token.trim_matches(|c: char| !c.is_ascii_alphabetic());
+            if !replaced && !core.is_empty() && core.eq_ignore_ascii_case(answer) {
+                replaced = true;
+                token.replacen(core, BLANK, 1)
+            } else {
+                token.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 从关键词池中挑长度相近的干扰项；`exclude_text` 中出现过的词不能作为干扰项
+fn distractors(answer: &str, pool: &[String], exclude_text: Option<&str>, seed: u64) -> Option<Vec<String>> {
+    let excluded: Vec<String> = exclude_text.map(crate::text::tokenize).unwrap_or_default();
+    let mut candidates: Vec<&String> =
+        pool.iter().filter(|word| word.as_str() != answer && !excluded.contains(word)).collect();
+    candidates.sort_by_key(|word| word.len().abs_diff(answer.len()));
+    candidates.truncate(DISTRACTORS * 3);
+    shuffle(&mut candidates, seed);
+    if candidates.len() < DISTRACTORS {
+        return None;
+    }
+    Some(candidates.into_iter().take(DISTRACTORS).cloned().collect())
+}
+
+/// 理解题选项：本段的一句作为正确答案，其他段落的句子作为干扰项
+fn comprehension_options(passages: &[Passage], passage: &Passage, seed: u64) -> Option<(String, Vec<String>)> {
+    let answer = sentences(&passage.text).into_iter().max_by_key(|s| s.len())?;
+    let mut others: Vec<String> = passages
+        .iter()
+        .filter(|p| p.text != passage.text)
+        .flat_map(|p| sentences(&p.text))
+        .filter(|s| s.split_whitespace().count() >= 5 && !passage.text.contains(s.as_str()))
+        .collect();
+    shuffle(&mut others, seed);
+    others.truncate(DISTRACTORS);
+    if others.len() < DISTRACTORS {
+        return None;
+    }
+    Some((answer, others))
+}
+
+/// 打乱选项，返回选项和正确答案的下标
+fn choices(answer: &str, distractors: Vec<String>, seed: u64) -> (Vec<String>, i32) {
+    let mut options = distractors;
+    options.push(answer.to_string());
+    shuffle(&mut options, seed);
+    let correct = options.iter().position(|o| o == answer).unwrap_or(0) as i32;
+    (options, correct)
+}
+
+/// 按句末标点切句
+fn sentences(text: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let at_boundary = !matches!(chars.peek(), Some(next) if !next.is_whitespace());
+        if matches!(c, '.' | '!' | '?') && at_boundary {
+            let sentence = current.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !sentence.is_empty() {
+                result.push(sentence);
+            }
+            current.clear();
+        }
+    }
+    let rest = current.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !rest.is_empty() {
+        result.push(rest);
+    }
+    result
+}
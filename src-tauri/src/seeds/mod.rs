@@ -0,0 +1,87 @@
+//! 内置 WIDA 示例题库：题目以 JSON 编译进程序，首次启动和 `reseed_defaults` 时写入数据库
+//!
+//! 每道题的 `id` 是它在内置题库中的编号，写入数据库后保存在 `seed_id` 列，用来判断哪些内置题目已经存在。
+
+use serde::Deserialize;
+
+const LISTENING_JSON: &str = include_str!("listening.json");
+const READING_JSON: &str = include_str!("reading.json");
+const SPEAKING_JSON: &str = include_str!("speaking.json");
+const WRITING_JSON: &str = include_str!("writing.json");
+
+#[derive(Debug, Deserialize)]
+pub struct ListeningQuestionData {
+    pub id: i64,
+    pub grade_level: String,
+    pub domain: String,
+    pub difficulty: i32,
+    pub audio_text: String,
+    pub image_url: Option<String>,
+    pub question_text: String,
+    pub options: Vec<String>,
+    pub correct_answer: i32,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadingQuestionData {
+    pub id: i64,
+    pub grade_level: String,
+    pub domain: String,
+    pub difficulty: i32,
+    pub passage: String,
+    pub question_text: String,
+    pub question_type: String,
+    pub options: Vec<String>,
+    pub correct_answer: i32,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpeakingQuestionData {
+    pub id: i64,
+    pub grade_level: String,
+    pub domain: String,
+    pub difficulty: i32,
+    pub prompt_type: String,
+    pub prompt_text: String,
+    pub image_url: Option<String>,
+    pub audio_text: Option<String>,
+    pub sample_answer: String,
+    pub rubric: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WritingQuestionData {
+    pub id: i64,
+    pub grade_level: String,
+    pub domain: String,
+    pub difficulty: i32,
+    pub task_type: String,
+    pub prompt: String,
+    pub image_url: Option<String>,
+    pub word_limit_min: i32,
+    pub word_limit_max: i32,
+    pub rubric: Vec<String>,
+    pub sample_answer: Option<String>,
+}
+
+/// 内置听力题
+pub fn listening_questions() -> serde_json::Result<Vec<ListeningQuestionData>> {
+    serde_json::from_str(LISTENING_JSON)
+}
+
+/// 内置阅读题
+pub fn reading_questions() -> serde_json::Result<Vec<ReadingQuestionData>> {
+    serde_json::from_str(READING_JSON)
+}
+
+/// 内置口语题
+pub fn speaking_questions() -> serde_json::Result<Vec<SpeakingQuestionData>> {
+    serde_json::from_str(SPEAKING_JSON)
+}
+
+/// 内置写作题
+pub fn writing_questions() -> serde_json::Result<Vec<WritingQuestionData>> {
+    serde_json::from_str(WRITING_JSON)
+}
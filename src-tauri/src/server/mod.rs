@@ -0,0 +1,461 @@
+//! 无界面部署用的 HTTP REST 接口（`http-server` 功能）
+//!
+//! 学习引擎运行在教室服务器上，瘦客户端通过 HTTP 连接；各接口调用与 Tauri 命令相同的
+//! `DatabaseManager` 方法。启动方式：
+//!
+//! `spelling-game --headless [--listen 0.0.0.0:8787] [--data-dir DIR] [--token TOKEN]`
+//!
+//! 参数未提供时依次读取环境变量 `SPELLING_GAME_LISTEN`、`SPELLING_GAME_DATA_DIR`、`SPELLING_GAME_TOKEN`。
+//! 设置了令牌时，除 `/api/health` 外的请求都需要带 `Authorization: Bearer <令牌>`；
+//! 监听本机以外的地址时必须设置令牌。
+//!
+//! 写入接口与对应的 Tauri 命令一样检查身份：请求头 `X-Spelling-Game-User` / `X-Spelling-Game-Pin`
+//! 为操作人。创建文章需要老师或管理员身份，写入某个用户的学习数据时，设置了 PIN 的用户需要本人的 PIN。
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use crate::database::DatabaseManager;
+use crate::models::{
+    Actor, AddMistakeRequest, Article, CreateArticleRequest, LeaderboardRecord, Mistake, PracticeHistory, SaveRecordRequest,
+    ScheduledWordsResponse, Segment, UpdateMasteryRequest, WidaHistoryRecord, WordMastery,
+};
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:8787";
+const DEFAULT_DATA_DIR: &str = "data";
+/// 操作人请求头，对应 Tauri 命令的 `actor` 参数
+const ACTOR_HEADER: &str = "x-spelling-game-user";
+const PIN_HEADER: &str = "x-spelling-game-pin";
+
+/// 无界面服务的启动参数
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub listen: SocketAddr,
+    pub data_dir: PathBuf,
+    pub token: Option<String>,
+}
+
+impl ServerConfig {
+    /// 解析命令行参数，未提供的参数读取环境变量
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let (mut listen, mut data_dir, mut token) =
+            (env("SPELLING_GAME_LISTEN"), env("SPELLING_GAME_DATA_DIR"), env("SPELLING_GAME_TOKEN"));
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let slot = match arg.as_str() {
+                "--headless" => continue,
+                "--listen" => &mut listen,
+                "--data-dir" => &mut data_dir,
+                "--token" => &mut token,
+                other => return Err(format!("未知参数: {}", other)),
+            };
+            *slot = Some(args.next().ok_or_else(|| format!("{} 缺少参数值", arg))?);
+        }
+        let listen = listen.as_deref().unwrap_or(DEFAULT_LISTEN);
+        let listen: SocketAddr = listen.parse().map_err(|_| format!("无效的监听地址: {}", listen))?;
+        let token = token.filter(|t| !t.trim().is_empty());
+        if !listen.ip().is_loopback() && token.is_none() {
+            return Err(format!("监听 {} 时必须用 --token 或 SPELLING_GAME_TOKEN 设置访问令牌", listen));
+        }
+        Ok(Self {
+            listen,
+            data_dir: PathBuf::from(data_dir.unwrap_or_else(|| DEFAULT_DATA_DIR.to_string())),
+            token,
+        })
+    }
+}
+
+/// 打开数据目录中的数据库并启动 HTTP 服务，直到进程退出
+pub fn run_headless(config: ServerConfig) -> Result<(), String> {
+    let _ = env_logger::try_init();
+    std::fs::create_dir_all(&config.data_dir).map_err(|e| e.to_string())?;
+    let db_path = config.data_dir.join(crate::paths::DB_FILE);
+    let db = DatabaseManager::new(&db_path).map_err(|e| e.to_string())?;
+    db.seed_wida_questions().map_err(|e| e.to_string())?;
+    log::info!("Database initialized at {:?}", db_path);
+
+    let app = router(Arc::new(Mutex::new(db)), config.token);
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind(config.listen).await.map_err(|e| e.to_string())?;
+        log::info!("HTTP server listening on {}", config.listen);
+        axum::serve(listener, app).await.map_err(|e| e.to_string())
+    })
+}
+
+#[derive(Clone)]
+struct ServerState {
+    db: Arc<Mutex<DatabaseManager>>,
+    token: Option<Arc<TokenCheck>>,
+}
+
+/// 访问令牌校验：令牌的 HMAC 标签配合 `hmac::verify` 做常量时间比较
+/// （ring 0.17 起 `constant_time::verify_slices_are_equal` 已弃用）
+struct TokenCheck {
+    key: ring::hmac::Key,
+    tag: ring::hmac::Tag,
+}
+
+impl TokenCheck {
+    fn new(token: &str) -> Self {
+        let key = ring::hmac::Key::generate(ring::hmac::HMAC_SHA256, &ring::rand::SystemRandom::new())
+            .expect("生成令牌校验密钥失败");
+        let tag = ring::hmac::sign(&key, token.as_bytes());
+        Self { key, tag }
+    }
+
+    fn matches(&self, provided: &str) -> bool {
+        ring::hmac::verify(&self.key, provided.as_bytes(), self.tag.as_ref()).is_ok()
+    }
+}
+
+/// 接口错误，以 `{"error": "..."}` 返回
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            // 数据库层用 InvalidParameterName 表示参数校验失败
+            rusqlite::Error::InvalidParameterName(message) => ApiError(StatusCode::BAD_REQUEST, message),
+            e => ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+impl ServerState {
+    fn db(&self) -> Result<MutexGuard<'_, DatabaseManager>, ApiError> {
+        self.db.lock().map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    }
+}
+
+/// 从请求头读取操作人
+fn actor_from_headers(headers: &HeaderMap) -> Option<Actor> {
+    let value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+    value(ACTOR_HEADER).map(|user_name| Actor {
+        user_name: user_name.to_string(),
+        pin: value(PIN_HEADER).map(str::to_string),
+    })
+}
+
+/// 写入这些用户的学习数据前校验 PIN：操作人是本人时用操作人的 PIN，没有设置 PIN 的用户不受限制
+fn require_user_pins<'a>(
+    db: &DatabaseManager,
+    actor: Option<&Actor>,
+    user_names: impl IntoIterator<Item = &'a str>,
+) -> Result<(), ApiError> {
+    for user_name in user_names {
+        let pin = actor.filter(|a| a.user_name == user_name).and_then(|a| a.pin.as_deref());
+        db.require_user_pin(user_name, pin).map_err(forbidden)?;
+    }
+    Ok(())
+}
+
+fn forbidden(e: rusqlite::Error) -> ApiError {
+    match e {
+        rusqlite::Error::InvalidParameterName(message) => ApiError(StatusCode::FORBIDDEN, message),
+        e => e.into(),
+    }
+}
+
+/// 接口路由
+pub(crate) fn router(db: Arc<Mutex<DatabaseManager>>, token: Option<String>) -> Router {
+    let state = ServerState { db, token: token.map(|t| Arc::new(TokenCheck::new(&t))) };
+    Router::new()
+        .route("/api/articles", get(list_articles).post(create_article))
+        .route("/api/articles/:id", get(get_article))
+        .route("/api/articles/:id/segments/:segment_type", get(get_segments))
+        .route("/api/scheduled-words", get(scheduled_words))
+        .route("/api/masteries", post(update_masteries))
+        .route("/api/mistakes", get(list_mistakes).post(add_mistakes))
+        .route("/api/records", post(save_record))
+        .route("/api/leaderboard", get(leaderboard))
+        .route("/api/users/:user_name/practice-history", get(practice_history))
+        .route("/api/users/:user_name/wida-history", get(wida_history))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .route("/api/health", get(health))
+        .with_state(state)
+}
+
+async fn require_token(State(state): State<ServerState>, request: Request, next: Next) -> Response {
+    if let Some(token) = &state.token {
+        let provided = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if !provided.is_some_and(|p| token.matches(p)) {
+            return ApiError(StatusCode::UNAUTHORIZED, "缺少或错误的访问令牌".to_string()).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+async fn list_articles(State(state): State<ServerState>) -> ApiResult<Vec<Article>> {
+    Ok(Json(state.db()?.get_articles()?))
+}
+
+async fn create_article(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateArticleRequest>,
+) -> ApiResult<i64> {
+    let check = crate::text::language::check_article_language(&request.content);
+    if !check.supported {
+        return Err(ApiError(StatusCode::BAD_REQUEST, check.warning.unwrap_or_default()));
+    }
+    let db = state.db()?;
+    db.authorize(actor_from_headers(&headers).as_ref()).map_err(forbidden)?;
+    Ok(Json(db.create_article(&request.title, &request.content)?))
+}
+
+async fn get_article(State(state): State<ServerState>, Path(id): Path<i64>) -> ApiResult<Article> {
+    let article = state.db()?.get_article(id)?;
+    article.map(Json).ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "文章不存在".to_string()))
+}
+
+async fn get_segments(State(state): State<ServerState>, Path((id, segment_type)): Path<(i64, String)>) -> ApiResult<Vec<Segment>> {
+    Ok(Json(state.db()?.get_segments(id, &segment_type)?))
+}
+
+#[derive(Deserialize)]
+struct ScheduledWordsQuery {
+    user_name: String,
+    article_id: i64,
+    segment_type: String,
+    limit: i32,
+    order_mode: Option<String>,
+    seed: Option<u64>,
+    avoid_similar: Option<bool>,
+    include_answered: Option<bool>,
+}
+
+async fn scheduled_words(State(state): State<ServerState>, Query(q): Query<ScheduledWordsQuery>) -> ApiResult<ScheduledWordsResponse> {
+    let db = state.db()?;
+    let response = crate::commands::practice::scheduled_words(
+        &db,
+        &q.user_name,
+        q.article_id,
+        &q.segment_type,
+        q.limit,
+        q.order_mode.as_deref(),
+        q.seed,
+        q.avoid_similar.unwrap_or(false),
+        q.include_answered.unwrap_or(false),
+    )?;
+    Ok(Json(response))
+}
+
+async fn update_masteries(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(updates): Json<Vec<UpdateMasteryRequest>>,
+) -> ApiResult<Vec<WordMastery>> {
+    let db = state.db()?;
+    require_user_pins(&db, actor_from_headers(&headers).as_ref(), updates.iter().map(|u| u.user_name.as_str()))?;
+    Ok(Json(db.update_word_masteries_batch(&updates)?))
+}
+
+#[derive(Deserialize)]
+struct MistakesQuery {
+    user_name: String,
+    segment_type: Option<String>,
+}
+
+async fn list_mistakes(State(state): State<ServerState>, Query(q): Query<MistakesQuery>) -> ApiResult<Vec<Mistake>> {
+    Ok(Json(state.db()?.get_mistakes(&q.user_name, q.segment_type.as_deref())?))
+}
+
+async fn add_mistakes(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(mistakes): Json<Vec<AddMistakeRequest>>,
+) -> ApiResult<()> {
+    let db = state.db()?;
+    require_user_pins(&db, actor_from_headers(&headers).as_ref(), mistakes.iter().map(|m| m.user_name.as_str()))?;
+    db.add_mistakes_batch(&mistakes)?;
+    Ok(Json(()))
+}
+
+async fn save_record(State(state): State<ServerState>, headers: HeaderMap, Json(r): Json<SaveRecordRequest>) -> ApiResult<()> {
+    let db = state.db()?;
+    require_user_pins(&db, actor_from_headers(&headers).as_ref(), [r.user_name.as_str()])?;
+    db.save_record(&r.user_name, r.article_id, &r.segment_type, r.score, r.accuracy, r.wpm)?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+struct LeaderboardQuery {
+    article_id: Option<i64>,
+    segment_type: Option<String>,
+    limit: Option<i32>,
+}
+
+async fn leaderboard(State(state): State<ServerState>, Query(q): Query<LeaderboardQuery>) -> ApiResult<Vec<LeaderboardRecord>> {
+    Ok(Json(state.db()?.get_leaderboard(q.article_id, q.segment_type.as_deref(), q.limit.unwrap_or(10))?))
+}
+
+#[derive(Deserialize)]
+struct PracticeHistoryQuery {
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct WidaHistoryQuery {
+    test_type: Option<String>,
+    limit: Option<i32>,
+}
+
+async fn practice_history(
+    State(state): State<ServerState>,
+    Path(user_name): Path<String>,
+    Query(q): Query<PracticeHistoryQuery>,
+) -> ApiResult<Vec<PracticeHistory>> {
+    Ok(Json(state.db()?.get_practice_history(&user_name, q.limit.unwrap_or(20))?))
+}
+
+async fn wida_history(
+    State(state): State<ServerState>,
+    Path(user_name): Path<String>,
+    Query(q): Query<WidaHistoryQuery>,
+) -> ApiResult<Vec<WidaHistoryRecord>> {
+    Ok(Json(state.db()?.get_wida_history(&user_name, q.test_type.as_deref(), q.limit)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在随机端口上启动接口服务，返回地址
+    async fn spawn_server(db: DatabaseManager) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(Arc::new(Mutex::new(db)), None);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_quoted_parameters_are_not_sql() {
+        let path = std::env::temp_dir().join(format!("spelling-server-{}.db", uuid::Uuid::new_v4().simple()));
+        let db = DatabaseManager::new(&path).unwrap();
+        let article_id = db.create_article("Fruit", "apple banana").unwrap();
+        db.save_practice_history("alice", article_id, "word", 3, 1, 60).unwrap();
+        db.save_record("alice", article_id, "word", 90.0, 75.0, 20.0).unwrap();
+        let addr = spawn_server(db).await;
+        let client = reqwest::Client::new();
+        let get = |path: &str| client.get(format!("http://{}{}", addr, path)).send();
+
+        let history: Vec<serde_json::Value> = get("/api/users/alice/practice-history").await.unwrap().json().await.unwrap();
+        assert_eq!(history.len(), 1);
+
+        // 引号作为普通字符比较，不能拼进 SQL 取出其他用户的记录
+        let injected = "x'%20OR%20'1'='1";
+        let response = get(&format!("/api/users/{}/practice-history", injected)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.json::<Vec<serde_json::Value>>().await.unwrap().is_empty());
+
+        let response = get(&format!("/api/users/{}/wida-history?test_type={}", injected, injected)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.json::<Vec<serde_json::Value>>().await.unwrap().is_empty());
+
+        let response = get(&format!("/api/leaderboard?segment_type={}", injected)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.json::<Vec<serde_json::Value>>().await.unwrap().is_empty());
+        let leaderboard: Vec<serde_json::Value> = get("/api/leaderboard?segment_type=word").await.unwrap().json().await.unwrap();
+        assert_eq!(leaderboard.len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_write_endpoints_require_actor_and_pin() {
+        let db = DatabaseManager::new(":memory:").unwrap();
+        let article_id = db.create_article("Fruit", "apple banana").unwrap();
+        db.set_user_role(None, "teacher", "teacher").unwrap();
+        db.set_user_pin("teacher", None, Some("9999")).unwrap();
+        db.set_user_pin("alice", None, Some("1234")).unwrap();
+        let addr = spawn_server(db).await;
+        let client = reqwest::Client::new();
+        let post = |path: &str, body: serde_json::Value, actor: Option<(&str, &str)>| {
+            let mut request = client.post(format!("http://{}{}", addr, path)).json(&body);
+            if let Some((user, pin)) = actor {
+                request = request.header(ACTOR_HEADER, user).header(PIN_HEADER, pin);
+            }
+            request.send()
+        };
+
+        let article = serde_json::json!({ "title": "Fruit", "content": "cherry grape" });
+        let response = post("/api/articles", article.clone(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post("/api/articles", article.clone(), Some(("teacher", "0000"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post("/api/articles", article, Some(("teacher", "9999"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let record = serde_json::json!({
+            "user_name": "alice", "article_id": article_id, "segment_type": "word",
+            "score": 90.0, "accuracy": 75.0, "wpm": 20.0
+        });
+        let response = post("/api/records", record.clone(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        // 别人的 PIN 不能写入 alice 的数据
+        let response = post("/api/records", record.clone(), Some(("teacher", "9999"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post("/api/records", record, Some(("alice", "1234"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mistakes = serde_json::json!([
+            { "user_name": "bob", "segment_id": 1, "segment_content": "apple", "segment_type": "word" },
+            { "user_name": "alice", "segment_id": 1, "segment_content": "apple", "segment_type": "word" }
+        ]);
+        let response = post("/api/mistakes", mistakes, Some(("bob", ""))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_token_and_listen_address() {
+        let args = |list: &[&str]| ServerConfig::from_args(list.iter().map(|a| a.to_string()));
+        assert!(args(&["--listen", "0.0.0.0:8787"]).is_err());
+        assert!(args(&["--listen", "0.0.0.0:8787", "--token", "  "]).is_err());
+        assert!(args(&["--listen", "0.0.0.0:8787", "--token", "secret"]).is_ok());
+        assert!(args(&["--listen", "127.0.0.1:8787"]).is_ok());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(Arc::new(Mutex::new(DatabaseManager::new(":memory:").unwrap())), Some("secret".into()));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+        let get = |token: &str| client.get(format!("http://{}/api/articles", addr)).bearer_auth(token).send();
+        assert_eq!(get("secreT").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(get("secret-longer").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(get("secret").await.unwrap().status(), StatusCode::OK);
+    }
+}
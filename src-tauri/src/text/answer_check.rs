@@ -0,0 +1,125 @@
+//! 按可配置的严格程度检查答案，默写句子时按单词给部分分
+
+use super::spelling_variants::to_american;
+use crate::models::{AnswerCheckOptions, TokenMatch};
+
+/// 答案检查结果
+#[derive(Debug, Clone)]
+pub struct AnswerCheck {
+    pub correct: bool,
+    pub credit: f64,                    // 部分分（0-1）：对上的单词数 / 答案与输入中较多的单词数
+    pub tokens: Vec<TokenMatch>,        // 答案中每个单词的对照
+}
+
+/// 检查输入；默认忽略大小写、不忽略标点，`require_exact` 时要求完全一致（仅去掉首尾空白）
+///
+/// `accept_spelling_variants` 为 `Some(true)` 时美式和英式拼写都算对。
+pub fn check_answer(typed: &str, expected: &str, options: &AnswerCheckOptions) -> AnswerCheck {
+    let typed_tokens = tokens(typed, options);
+    let expected_tokens = tokens(expected, options);
+    let correct = if options.require_exact {
+        typed.trim() == expected.trim()
+    } else {
+        typed_tokens.iter().map(|t| &t.1).eq(expected_tokens.iter().map(|t| &t.1))
+    };
+
+    // 按单词求最长公共子序列，没对上的单词按位置与同一空隙中输入的单词对照
+    let (n, m) = (expected_tokens.len(), typed_tokens.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if expected_tokens[i].1 == typed_tokens[j].1 {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut matches = Vec::with_capacity(n);
+    let (mut i, mut j) = (0, 0);
+    while i < n {
+        if j < m && expected_tokens[i].1 == typed_tokens[j].1 {
+            matches.push(TokenMatch { expected: expected_tokens[i].0.clone(), typed: Some(typed_tokens[j].0.clone()), correct: true });
+            i += 1;
+            j += 1;
+        } else if j < m && lcs[i][j + 1] > lcs[i + 1][j] {
+            j += 1;
+        } else {
+            // 输入在这里也没对上时，把同一位置的单词作为写错的版本
+            let replaced = (j < m && lcs[i + 1][j + 1] == lcs[i][j]).then(|| typed_tokens[j].0.clone());
+            if replaced.is_some() {
+                j += 1;
+            }
+            matches.push(TokenMatch { expected: expected_tokens[i].0.clone(), typed: replaced, correct: false });
+            i += 1;
+        }
+    }
+
+    let matched = lcs[0][0];
+    let credit = if correct { 1.0 } else if n.max(m) == 0 { 0.0 } else { matched as f64 / n.max(m) as f64 };
+    AnswerCheck { correct, credit, tokens: matches }
+}
+
+/// 切分单词，返回（原样，用于比较的形式）；忽略标点时只剩标点的单词被去掉
+fn tokens(text: &str, options: &AnswerCheckOptions) -> Vec<(String, String)> {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let mut key = word.to_string();
+            if !options.require_exact && options.ignore_punctuation {
+                key.retain(char::is_alphanumeric);
+                if key.is_empty() {
+                    return None;
+                }
+            }
+            if !options.require_exact && options.accept_spelling_variants == Some(true) {
+                key = to_american(&key);
+            }
+            if !options.require_exact && options.ignore_case {
+                key = key.to_lowercase();
+            }
+            Some((word.to_string(), key))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 大小写、标点检查选项和逐词部分分
+    #[test]
+    fn test_check_answer_options() {
+        let expected = "The cat sat on the mat.";
+        let default = AnswerCheckOptions::default();
+        let loose = AnswerCheckOptions { ignore_punctuation: true, ..Default::default() };
+        let exact = AnswerCheckOptions { require_exact: true, ..Default::default() };
+
+        assert!(check_answer("the cat sat on the mat.", expected, &default).correct);
+        assert!(!check_answer("The cat sat on the mat", expected, &default).correct);
+        assert!(check_answer("the cat sat on the mat", expected, &loose).correct);
+        assert!(!check_answer("the cat sat on the mat.", expected, &exact).correct);
+        assert!(check_answer(" The cat sat on the mat. ", expected, &exact).correct);
+        let case_sensitive = AnswerCheckOptions { ignore_case: false, ..Default::default() };
+        assert!(!check_answer("the cat sat on the mat.", expected, &case_sensitive).correct);
+
+        // 漏写、写错的单词不得分，其余单词给部分分
+        let check = check_answer("The cat on the hat.", expected, &default);
+        assert!(!check.correct);
+        assert!((check.credit - 4.0 / 6.0).abs() < 1e-9);
+        let wrong: Vec<_> = check.tokens.iter().filter(|t| !t.correct).map(|t| (t.expected.as_str(), t.typed.as_deref())).collect();
+        assert_eq!(wrong, vec![("sat", None), ("mat.", Some("hat."))]);
+        // 多写的单词也会拉低得分
+        assert!((check_answer("The big cat sat on the mat.", expected, &default).credit - 6.0 / 7.0).abs() < 1e-9);
+        assert_eq!(check_answer("", expected, &default).credit, 0.0);
+    }
+
+    /// 开启后美式/英式拼写视为相同
+    #[test]
+    fn test_check_answer_spelling_variants() {
+        let strict = AnswerCheckOptions { accept_spelling_variants: Some(false), ..Default::default() };
+        let lenient = AnswerCheckOptions { accept_spelling_variants: Some(true), ..Default::default() };
+        assert!(!check_answer("colour", "color", &strict).correct);
+        assert!(check_answer("colour", "color", &lenient).correct);
+        assert!(check_answer("The theatre is grey.", "The theater is gray.", &lenient).correct);
+    }
+}
@@ -0,0 +1,126 @@
+//! 常用词表（约 1000 个高频英语单词，按词形归并），用于估计文本中生词的比例
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+const COMMON_WORDS: &str = "
+a able about above across act action add afraid after afternoon again against age ago agree air all allow almost
+alone along already also always am among an and angry animal another answer any anyone anything appear apple are
+area arm around arrive art as ask at aunt autumn away baby back bad bag ball banana band bank base basket bath be
+beach bear beautiful because become bed bedroom bee been before begin behind believe bell below beside best better
+between big bike bird birthday bit black blue board boat body book born both bottle bottom bowl box boy bread break
+breakfast bridge bright bring brother brown brush build building burn bus busy but butter buy by cake call camera
+camp can car card care careful carry case cat catch cause center chair chance change cheap check cheese chicken
+child children choose circle city class classroom clean clear climb clock close cloth clothes cloud coat cold color
+colour come common computer cook cool copy corner correct cost could count country course cousin cover cow cross cry
+cup cut dad dance danger dark date daughter day dead dear decide deep desk did die different difficult dinner
+dirty do doctor does dog dollar door down draw dream dress drink drive drop dry duck during each ear early earth
+easy eat egg eight either elephant else empty end enjoy enough enter even evening ever every everyone everything
+example except excited excuse eye face fact fall family famous far farm fast fat father favorite favourite feel
+few field fight fill find fine finger finish fire first fish five fix floor flower fly follow food foot for forest
+forget fork form four free fresh friend friendly from front fruit full fun funny game garden gas gate get gift girl
+give glad glass go gold good goodbye grandfather grandmother grass great green ground group grow guess guitar hair
+half hall hand happen happy hard has hat have he head hear heart heavy hello help her here high hill him his history
+hit hold hole holiday home homework hope horse hospital hot hotel hour house how however hundred hungry hurry hurt
+husband i ice idea if ill important in inside interest interesting into invite is island it its job join juice
+jump just keep key kick kid kill kind king kitchen kite knee knife know lake lamp land language large last late
+laugh lay lazy lead learn least leave left leg lesson let letter library lie life light like line lion list listen
+little live long look lose lot loud love low lucky lunch machine mad make man many map market matter may maybe me
+meal mean meat meet member milk mind minute miss mistake mom money monkey month moon more morning most mother
+mountain mouse mouth move movie much music must my name narrow near nearly neck need never new news next nice night
+nine no noise none noon nor north nose not note nothing notice now number nurse of off offer office often oh old on
+once one only open or orange order other our out outside over own page paint pair paper parent park part party pass
+past pay pen pencil people perhaps person pet phone photo piano pick picture piece pig pink place plan plant plate
+play please pocket point police poor popular possible post potato pour practice prepare present pretty price
+problem pull purple push put queen question quick quiet quite rabbit race rain rainbow reach read ready real really
+reason red remember rest restaurant rice rich ride right ring river road rock room rope round row rule run sad safe
+salt same sand save say school science sea season seat second see seem sell send sentence serve set seven several
+shape share she sheep shirt shoe shop short should shout show shut sick side sign simple sing sister sit six size
+skirt sky sleep slow small smell smile snake snow so soccer sock sofa soft some someone something sometimes son song
+soon sorry sound soup south space speak special spell spend sport spring square stair stand star start station stay
+step stick still stone stop store story straight strange street strong student study subject such sugar summer sun
+supper sure surprise sweet swim table tail take talk tall taste taxi tea teach teacher team tell ten tennis test
+than thank that the their them then there these they thing think third thirsty this those though thousand three
+through throw ticket tidy tiger time tired to today together tomorrow tonight too tooth top touch tower town toy
+traffic train travel tree trip trouble true try turn twice two umbrella uncle under understand until up upon us
+use useful usually vegetable very village visit voice wait wake walk wall want war warm wash watch water way we
+wear weather week weekend welcome well west wet what wheel when where which while white who whole why wide wife
+will win wind window winter wish with without woman wonderful wood word work world worry would write wrong yard
+year yellow yes yesterday yet you young your zero zoo
+ability above accept account activity actually address adult advice afford agency ahead aim airport alive amazing
+amount ancient announce annual appearance apply approach argue army arrange article artist attack attempt attend
+attention audience available avoid award aware balance basic battle beat beauty behave behavior belong benefit
+bill billion blood blow bone border borrow boss brain branch brave brief broad budget burst business button calm
+campaign capital captain career cell century certain chain challenge character charge chart chief choice church
+citizen claim climate club coach coast coin collect college comfortable command comment community company compare
+compete complete concern condition connect consider contain continue control conversation cotton council court
+crazy create crime crowd culture current customer damage deal debate degree deliver demand depend describe design
+detail develop device diet direction discover discuss disease distance divide double doubt dozen draft drama
+economy edge education effect effort election element energy engine enemy environment equal escape event evidence
+exactly exam excellent exercise exist expect expensive experience experiment explain express extra factory fail
+fair familiar fashion feature fee female figure final finance flat flight focus force foreign former fortune
+forward frame freedom fuel function future gain general generation gentle glove goal government grade grand
+guard guide habit handle hang health heat height hide highway hire hobby hole honest horror host huge human hunt
+ignore image imagine improve include increase indeed industry information injury insect instead instrument
+introduce investigate iron issue item journey judge jungle kilometer knowledge labor lack law layer leader level
+limit link liquid local lock lonely lucky magazine main major male manage manner mark marry material meaning
+measure medicine memory mention message metal method middle military million mirror mix model modern moment
+mystery national natural nature neighbor nervous net network normal novel object ocean offer officer oil opinion
+opportunity ordinary organize original pack pain pale passenger patient pattern peace perfect perform period
+permit personal physical pilot planet plastic pleasure plenty poem poet pole pollution population position
+positive powder power practical prefer president press prevent prince princess print prison private prize process
+produce product professor program progress project promise protect proud provide public purpose quality quarter
+quiz raise range rate rather receive recent record reduce region relax religion remain remove repeat reply report
+represent require research respect respond result return reveal review reward rise risk role rough rubbish
+sail sale sample scene scientist score screen search secret section secure select sense separate series serious
+service shadow shake shall sharp shelf shine shock shore sight silence silver similar single situation skill skin
+smart smoke social society soil soldier solid solve sort soul source speech speed spirit spot spread staff stage
+standard state statement steal steel storm stream stress structure style success suddenly suggest suit supply
+support suppose surface survive system target task tear technology temperature term terrible theater theory thick
+thin threat throughout tie tiny title tool total tour trade tradition treat trend trick tropical truck trust truth
+type typical unit universe unless usual valley value various vehicle version victim view violent volume vote wage
+warn waste wave weak wealth weapon weight whether wild wing wire wise within wonder worth youth
+";
+
+fn common_words() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| COMMON_WORDS.split_whitespace().collect())
+}
+
+/// 去掉常见词尾后的候选词形（复数、过去式、进行时、比较级、副词）
+fn base_forms(word: &str) -> Vec<String> {
+    let mut forms = vec![word.to_string()];
+    let word = word.trim_end_matches("'s");
+    for suffix in ["s", "es", "ed", "d", "ing", "er", "est", "ly"] {
+        if let Some(stem) = word.strip_suffix(suffix).filter(|stem| stem.len() >= 2) {
+            forms.push(stem.to_string());
+            forms.push(format!("{}e", stem));
+            // 双写辅音：running → run，stopped → stop
+            if stem.len() >= 3 && stem.as_bytes()[stem.len() - 1] == stem.as_bytes()[stem.len() - 2] {
+                forms.push(stem[..stem.len() - 1].to_string());
+            }
+            // studies → study，happily → happy
+            if let Some(root) = stem.strip_suffix('i') {
+                forms.push(format!("{}y", root));
+            }
+        }
+    }
+    forms
+}
+
+/// 单词（小写）是否在常用词表中；三个字母以内的词和缩写都算常用词
+pub fn is_common(word: &str) -> bool {
+    if word.len() <= 3 || word.contains('\'') {
+        return true;
+    }
+    if word.contains('-') {
+        return word.split('-').all(is_common);
+    }
+    in_word_list(word)
+}
+
+/// 单词（小写）或其去掉常见词尾后的词形是否在常用词表中，不把短词视为常用词
+pub fn in_word_list(word: &str) -> bool {
+    let words = common_words();
+    base_forms(word).iter().any(|form| words.contains(form.as_str()))
+}
@@ -0,0 +1,103 @@
+//! 按年级段检查听力、阅读文本的句长和生词比例，保存生成或导入的题目前使用
+
+use super::{frequency, tokenize};
+use crate::models::GradeLevelCheck;
+
+/// 各年级段的上限：（年级段，平均句长，最长句子，生词比例）
+const GRADE_LIMITS: [(&str, f64, usize, f64); 4] = [
+    ("grade_1_2", 10.0, 15, 0.10),
+    ("grade_3_5", 15.0, 22, 0.20),
+    ("grade_6_8", 20.0, 30, 0.30),
+    ("grade_9_12", 25.0, 40, 0.40),
+];
+
+/// 超过上限达到该倍数时拒绝保存，否则只提示
+const REJECT_FACTOR: f64 = 1.5;
+
+/// 每句的单词数，以句末标点或换行分句
+fn sentence_lengths(text: &str) -> Vec<usize> {
+    text.split(['.', '!', '?', '\n'])
+        .map(|sentence| tokenize(sentence).len())
+        .filter(|&len| len > 0)
+        .collect()
+}
+
+/// 检查文本是否符合年级段；未知的年级段返回 None
+pub fn check(text: &str, grade_level: &str) -> Option<GradeLevelCheck> {
+    let &(_, max_avg, max_longest, max_rare) = GRADE_LIMITS.iter().find(|(band, ..)| *band == grade_level)?;
+    let words = tokenize(text);
+    let lengths = sentence_lengths(text);
+    let avg_sentence_length = if lengths.is_empty() { 0.0 } else { words.len() as f64 / lengths.len() as f64 };
+    let longest_sentence = lengths.iter().copied().max().unwrap_or(0);
+    let mut rare_words: Vec<String> = words.iter().filter(|w| !frequency::is_common(w)).cloned().collect();
+    let rare_word_ratio = if words.is_empty() { 0.0 } else { rare_words.len() as f64 / words.len() as f64 };
+    rare_words.sort();
+    rare_words.dedup();
+
+    let mut issues = Vec::new();
+    let mut rejected = false;
+    let mut compare = |value: f64, limit: f64, message: String| {
+        if value > limit {
+            rejected |= value >= limit * REJECT_FACTOR;
+            issues.push(message);
+        }
+    };
+    compare(avg_sentence_length, max_avg, format!("平均句长 {:.1} 词，超过上限 {}", avg_sentence_length, max_avg));
+    compare(longest_sentence as f64, max_longest as f64, format!("最长句子 {} 词，超过上限 {}", longest_sentence, max_longest));
+    compare(
+        rare_word_ratio,
+        max_rare,
+        format!("生词比例 {:.0}%，超过上限 {:.0}%", rare_word_ratio * 100.0, max_rare * 100.0),
+    );
+
+    let status = if rejected {
+        "rejected"
+    } else if issues.is_empty() {
+        "ok"
+    } else {
+        "warning"
+    };
+    Some(GradeLevelCheck {
+        grade_level: grade_level.to_string(),
+        status: status.to_string(),
+        avg_sentence_length,
+        longest_sentence: longest_sentence as i32,
+        rare_word_ratio,
+        rare_words,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 句长和生词比例超出年级段上限时警告或拒绝
+    #[test]
+    fn test_check() {
+        let easy = "The cat is on the mat. My dog likes to play in the garden.";
+        let simple = check(easy, "grade_1_2").unwrap();
+        assert_eq!(simple.status, "ok");
+        assert_eq!(simple.longest_sentence, 8);
+        assert!(simple.rare_words.is_empty());
+        // 词形变化归并到常用词：studies、running、happily
+        assert!(check("She studies while running happily.", "grade_1_2").unwrap().rare_words.is_empty());
+
+        let academic = "Photosynthesis converts electromagnetic radiation into chemical energy, \
+                        sustaining heterotrophic organisms throughout terrestrial ecosystems.";
+        let hard = check(academic, "grade_1_2").unwrap();
+        assert_eq!(hard.status, "rejected");
+        assert!(hard.rare_words.contains(&"photosynthesis".to_string()));
+        // 14 词的一句话：平均句长和生词比例超限，最长句子未超
+        assert_eq!(hard.issues.len(), 2);
+        // 高年级段上限更宽，但生词仍然太多
+        assert_ne!(check(academic, "grade_9_12").unwrap().status, "ok");
+        assert!(check(easy, "grade_13").is_none());
+
+        // 平均句长未超，但最长的一句 16 词
+        let long_sentence = "We walk to the park and we play with the ball and then we eat cake. It is fun.";
+        let slightly_long = check(long_sentence, "grade_1_2").unwrap();
+        assert_eq!(slightly_long.status, "warning");
+        assert_eq!(slightly_long.longest_sentence, 16);
+    }
+}
@@ -0,0 +1,141 @@
+//! 常见同音词表（内置），用于识别把 their 写成 there 这类“拼对了另一个词”的错误
+
+/// 同音词组，组内单词读音相同、拼写不同
+const HOMOPHONE_GROUPS: &[&[&str]] = &[
+    &["their", "there", "they're"],
+    &["to", "too", "two"],
+    &["your", "you're"],
+    &["its", "it's"],
+    &["whose", "who's"],
+    &["hear", "here"],
+    &["know", "no"],
+    &["knew", "new"],
+    &["knight", "night"],
+    &["knot", "not"],
+    &["right", "write"],
+    &["see", "sea"],
+    &["son", "sun"],
+    &["buy", "by", "bye"],
+    &["for", "four"],
+    &["one", "won"],
+    &["eight", "ate"],
+    &["hour", "our"],
+    &["flower", "flour"],
+    &["weather", "whether"],
+    &["which", "witch"],
+    &["week", "weak"],
+    &["meet", "meat"],
+    &["pair", "pear"],
+    &["male", "mail"],
+    &["tail", "tale"],
+    &["blue", "blew"],
+    &["road", "rode"],
+    &["peace", "piece"],
+    &["plain", "plane"],
+    &["hole", "whole"],
+    &["wear", "where"],
+    &["bare", "bear"],
+    &["brake", "break"],
+    &["dear", "deer"],
+    &["aloud", "allowed"],
+    &["principal", "principle"],
+    &["stationary", "stationery"],
+    &["threw", "through"],
+    &["wait", "weight"],
+    &["way", "weigh"],
+    &["wood", "would"],
+    &["hair", "hare"],
+    &["heal", "heel"],
+    &["sale", "sail"],
+    &["steal", "steel"],
+    &["stair", "stare"],
+    &["made", "maid"],
+    &["be", "bee"],
+    &["red", "read"],
+    &["rain", "reign", "rein"],
+    &["cent", "scent", "sent"],
+    &["ant", "aunt"],
+    &["berry", "bury"],
+    &["cell", "sell"],
+    &["die", "dye"],
+    &["fair", "fare"],
+    &["flew", "flu"],
+    &["grate", "great"],
+    &["groan", "grown"],
+    &["guessed", "guest"],
+    &["higher", "hire"],
+    &["lead", "led"],
+    &["missed", "mist"],
+    &["passed", "past"],
+    &["pause", "paws"],
+    &["poor", "pour"],
+    &["role", "roll"],
+    &["sew", "so", "sow"],
+    &["some", "sum"],
+    &["tide", "tied"],
+    &["toe", "tow"],
+    &["waist", "waste"],
+];
+
+/// 单词的同音词（不含自身），不在表中时为空
+pub fn homophones_of(word: &str) -> Vec<&'static str> {
+    let word = normalize(word);
+    HOMOPHONE_GROUPS
+        .iter()
+        .filter(|group| group.contains(&word.as_str()))
+        .flat_map(|group| group.iter().copied())
+        .filter(|w| *w != word)
+        .collect()
+}
+
+/// 两个单词是否互为同音词
+pub fn are_homophones(a: &str, b: &str) -> bool {
+    let b = normalize(b);
+    homophones_of(a).contains(&b.as_str())
+}
+
+/// 输入只是把答案中的某些单词写成了同音词时，返回第一处（答案中的词，输入的词）
+pub fn homophone_swap(typed: &str, expected: &str) -> Option<(String, String)> {
+    let typed: Vec<&str> = typed.split_whitespace().collect();
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+    if typed.len() != expected.len() {
+        return None;
+    }
+    let mut swap = None;
+    for (t, e) in typed.iter().zip(&expected) {
+        if normalize(t) == normalize(e) {
+            continue;
+        }
+        if !are_homophones(e, t) {
+            return None;
+        }
+        swap.get_or_insert_with(|| (normalize(e), normalize(t)));
+    }
+    swap
+}
+
+/// 小写并统一弯撇号，去掉词尾标点
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| c.is_ascii_punctuation() && c != '\'').replace('’', "'").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 同音词查找，以及答案中只差一组同音词时识别为混淆
+    #[test]
+    fn test_homophone_swap() {
+        assert!(homophones_of("There").contains(&"their"));
+        assert!(homophones_of("there").contains(&"they're"));
+        assert!(are_homophones("your", "you’re"));
+        assert!(!are_homophones("there", "there"));
+        assert!(homophones_of("apple").is_empty());
+        assert_eq!(homophone_swap("there", "their"), Some(("their".to_string(), "there".to_string())));
+        assert_eq!(homophone_swap("I ate a pear", "I ate a pair"), Some(("pair".to_string(), "pear".to_string())));
+        assert_eq!(homophone_swap("I eight a pear", "I ate a pair").map(|s| s.0), Some("ate".to_string()));
+        // 还有其他拼写错误时不算同音词混淆
+        assert_eq!(homophone_swap("I ate a peer", "I ate a pair"), None);
+        assert_eq!(homophone_swap("thier", "their"), None);
+    }
+}
@@ -0,0 +1,56 @@
+/// 支持的键盘布局
+pub const KEYBOARD_LAYOUTS: &[&str] = &["qwerty", "azerty"];
+
+/// 未设置时使用的键盘布局
+pub const DEFAULT_KEYBOARD_LAYOUT: &str = "qwerty";
+
+const QWERTY_ROWS: [&str; 4] = ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"];
+const AZERTY_ROWS: [&str; 4] = ["&é\"'(-è_çà)=", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"];
+
+fn rows(layout: &str) -> &'static [&'static str; 4] {
+    match layout {
+        "azerty" => &AZERTY_ROWS,
+        _ => &QWERTY_ROWS,
+    }
+}
+
+fn key_position(layout: &str, key: char) -> Option<(i32, i32)> {
+    let key = key.to_lowercase().next()?;
+    rows(layout)
+        .iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.chars().position(|k| k == key).map(|col| (row as i32, col as i32)))
+}
+
+/// 两个键在该布局上是否相邻（同一行左右相邻，或上下行错开一格以内）
+pub fn is_adjacent(layout: &str, a: char, b: char) -> bool {
+    match (key_position(layout, a), key_position(layout, b)) {
+        (Some((ra, ca)), Some((rb, cb))) => (ra, ca) != (rb, cb) && (ra - rb).abs() <= 1 && (ca - cb).abs() <= 1,
+        _ => false,
+    }
+}
+
+/// 错误分类：按到相邻键算手滑（`slip`），否则算拼写错误（`spelling`）
+pub fn classify_error(layout: &str, expected: char, typed: char) -> &'static str {
+    if is_adjacent(layout, expected, typed) {
+        "slip"
+    } else {
+        "spelling"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按错相邻键算手滑，其余算拼写错误
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(classify_error("qwerty", 'a', 's'), "slip");
+        assert_eq!(classify_error("qwerty", 'a', 'q'), "slip");
+        assert_eq!(classify_error("qwerty", 'a', 'p'), "spelling");
+        assert_eq!(classify_error("azerty", 'a', 'z'), "slip");
+        assert_eq!(classify_error("azerty", 'm', 'l'), "slip");
+        assert_eq!(classify_error("qwerty", 'm', 'l'), "spelling");
+    }
+}
@@ -0,0 +1,77 @@
+//! 文章语言检测（whatlang），用于拒绝无法练习的文本，并按语言选择分词、朗读和评分方式
+
+use whatlang::{Lang, Script};
+
+use crate::models::LanguageCheck;
+
+/// 常见语言的 ISO 639-1 代码，其余语言使用 whatlang 的 ISO 639-3 代码
+const LANGUAGE_CODES: &[(Lang, &str)] = &[
+    (Lang::Eng, "en"),
+    (Lang::Cmn, "zh"),
+    (Lang::Jpn, "ja"),
+    (Lang::Kor, "ko"),
+    (Lang::Spa, "es"),
+    (Lang::Fra, "fr"),
+    (Lang::Deu, "de"),
+    (Lang::Ita, "it"),
+    (Lang::Por, "pt"),
+    (Lang::Rus, "ru"),
+    (Lang::Ara, "ar"),
+    (Lang::Vie, "vi"),
+    (Lang::Tha, "th"),
+    (Lang::Hin, "hi"),
+];
+
+/// 检测文本语言，没有可识别的文字时为空
+///
+/// 拉丁字母但可信度不高的文本（单词表、很短的句子）按英文处理。
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text)?;
+    if info.script() == Script::Latin && !info.is_reliable() {
+        return Some("en");
+    }
+    let lang = info.lang();
+    Some(LANGUAGE_CODES.iter().find(|(l, _)| *l == lang).map_or_else(|| lang.code(), |(_, code)| *code))
+}
+
+/// 按单词练习的外语（分词、朗读和掌握度都按语言区分）
+pub const PRACTICE_LANGUAGES: &[&str] = &["en", "es", "fr"];
+
+/// 检查文章语言：英文、西班牙语和法语完整支持；中文只能朗读和按句练习（给出提示）；其他语言不支持
+pub fn check_article_language(text: &str) -> LanguageCheck {
+    let language = detect_language(text);
+    let (supported, warning) = match language {
+        None => (true, None),
+        Some(code) if PRACTICE_LANGUAGES.contains(&code) => (true, None),
+        Some("zh") => (true, Some("检测到中文文章：可以朗读和按句练习，但不会按单词分词".to_string())),
+        Some(other) => (false, Some(format!("检测到的语言（{}）暂不支持，目前只能导入英文、西班牙语、法语或中文文章", other))),
+    };
+    LanguageCheck { language: language.map(str::to_string), supported, warning }
+}
+
+/// 该语言的文章是否按单词/短语分词（中文按空格切词没有意义）
+pub fn segments_words(language: Option<&str>) -> bool {
+    language.is_none_or(|code| PRACTICE_LANGUAGES.contains(&code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 检测文章语言，中文给出提示，不支持的语言被拒绝
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("The quick brown fox jumps over the lazy dog while the children watch from the garden."), Some("en"));
+        assert_eq!(detect_language("apple banana"), Some("en"));
+        assert_eq!(detect_language("今天天气很好，我们一起去公园散步吧。"), Some("zh"));
+        assert_eq!(detect_language("123 456"), None);
+
+        assert!(check_article_language("I like reading books with my friends every weekend.").warning.is_none());
+        let chinese = check_article_language("今天天气很好，我们一起去公园散步吧。");
+        assert!(chinese.supported && chinese.warning.is_some());
+        let german = check_article_language("Der Hund läuft jeden Tag mit seinem Besitzer und seinen Freunden durch den Park.");
+        assert_eq!(german.language.as_deref(), Some("de"));
+        assert!(!german.supported);
+        assert!(segments_words(Some("en")) && !segments_words(Some("zh")));
+    }
+}
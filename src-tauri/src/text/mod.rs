@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+pub mod answer_check;
+pub mod frequency;
+pub mod grade_check;
+pub mod homophones;
+pub mod keyboard;
+pub mod language;
+pub mod morphology;
+pub mod readability;
+pub mod speaking_rubric;
+pub mod spelling_variants;
+
+/// 常见虚词，提取关键词汇时忽略
+const STOP_WORDS: &[&str] = &[
+    "about", "above", "after", "again", "against", "also", "because", "been", "before", "being", "below", "between",
+    "both", "could", "does", "doing", "down", "during", "each", "even", "every", "from", "further", "have", "having",
+    "here", "into", "just", "like", "made", "make", "many", "more", "most", "much", "must", "only", "other", "over",
+    "same", "should", "some", "such", "than", "that", "their", "them", "then", "there", "these", "they", "this",
+    "those", "through", "under", "until", "very", "want", "were", "what", "when", "where", "which", "while", "will",
+    "with", "would", "your", "yours", "said", "says", "know", "think", "going", "answer", "question", "correct",
+];
+
+/// 参与关键词统计的最短单词长度
+const MIN_WORD_LEN: usize = 4;
+
+/// 按词频提取关键词汇：过滤虚词和短词，频次相同时较长的单词优先
+pub fn extract_key_vocabulary(texts: &[String], limit: usize) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut first_seen: Vec<String> = Vec::new();
+
+    for text in texts {
+        for word in tokenize(text) {
+            if word.len() < MIN_WORD_LEN || STOP_WORDS.contains(&word.as_str()) {
+                continue;
+            }
+            let count = counts.entry(word.clone()).or_insert(0);
+            if *count == 0 {
+                first_seen.push(word);
+            }
+            *count += 1;
+        }
+    }
+
+    // 稳定排序，保留首次出现的顺序作为最终的平局规则
+    first_seen.sort_by(|a, b| counts[b].cmp(&counts[a]).then(b.len().cmp(&a.len())));
+    first_seen.truncate(limit);
+    first_seen
+}
+
+/// 用 TF-IDF 挑出文章中最有区分度的单词，返回（单词，得分，出现次数），得分高的在前
+///
+/// `corpus` 为参与计算文档频率的全部文章（应包含 `document` 本身）。
+pub fn tf_idf_keywords(document: &str, corpus: &[String], limit: usize) -> Vec<(String, f64, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut total = 0;
+    for word in tokenize(document) {
+        total += 1;
+        if word.len() >= MIN_WORD_LEN && !STOP_WORDS.contains(&word.as_str()) {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    if counts.is_empty() {
+        return Vec::new();
+    }
+
+    let corpus_words: Vec<std::collections::HashSet<String>> =
+        corpus.iter().map(|text| tokenize(text).into_iter().collect()).collect();
+    let mut scored: Vec<(String, f64, usize)> = counts
+        .into_iter()
+        .map(|(word, count)| {
+            let document_frequency = corpus_words.iter().filter(|words| words.contains(&word)).count();
+            // 平滑后的 IDF，只出现在本文的单词得分最高
+            let idf = ((corpus.len() as f64 + 1.0) / (document_frequency as f64 + 1.0)).ln() + 1.0;
+            let score = count as f64 / total as f64 * idf;
+            (word, score, count)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.len().cmp(&a.0.len())).then(a.0.cmp(&b.0)));
+    scored.truncate(limit);
+    scored
+}
+
+/// 切分段落：有空行时以空行分段（段内换行合并为空格），否则每个非空行为一段
+pub fn split_paragraphs(text: &str) -> Vec<String> {
+    let text = text.trim();
+    if !text.lines().any(|line| line.trim().is_empty()) {
+        return text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect();
+    }
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join(" "));
+    }
+    paragraphs
+}
+
+/// 切分英文单词（小写），保留词内的撇号和连字符
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_ascii_alphabetic() || c == '\'' || c == '-'))
+        .map(|w| w.trim_matches(|c| c == '\'' || c == '-').to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// 按拼写规则粗略切分音节（无音标数据时的近似）
+///
+/// 以元音组为音节核心：VCV 切在辅音前（ti-ger），VCCV 切在两个辅音之间（rab-bit），
+/// ch/sh/th/ph/wh/ck/ng 等组合不拆开；词尾不发音的 e 并入前一音节，辅音 + le 单独成音节（ta-ble）。
+/// 这是启发式规则，分属两个音节的相邻元音（go-ing）不会拆开，只用于提示和分块练习。
+pub fn syllables(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let lower: Vec<char> = word.to_lowercase().chars().collect();
+    if chars.len() != lower.len() || chars.len() <= 3 {
+        return vec![word.to_string()];
+    }
+
+    let is_vowel = |i: usize| {
+        let c = lower[i];
+        matches!(c, 'a' | 'e' | 'i' | 'o' | 'u') || (c == 'y' && i > 0 && !matches!(lower[i - 1], 'a' | 'e' | 'i' | 'o' | 'u'))
+    };
+
+    // 词尾不发音的 e 不算元音（辅音 + le 结尾除外）
+    let len = lower.len();
+    let silent_e = lower[len - 1] == 'e'
+        && !is_vowel(len - 2)
+        && !(lower[len - 2] == 'l' && len >= 3 && !is_vowel(len - 3));
+    let vowel_at = |i: usize| is_vowel(i) && !(silent_e && i == len - 1);
+
+    // 每个元音组的起止位置
+    let mut nuclei: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < len {
+        if vowel_at(i) {
+            let start = i;
+            while i < len && vowel_at(i) {
+                i += 1;
+            }
+            nuclei.push((start, i));
+        } else {
+            i += 1;
+        }
+    }
+    if nuclei.len() <= 1 {
+        return vec![word.to_string()];
+    }
+
+    let mut breaks = Vec::new();
+    for pair in nuclei.windows(2) {
+        let (consonant_start, consonant_end) = (pair[0].1, pair[1].0);
+        let cluster = &lower[consonant_start..consonant_end];
+        let split = match cluster.len() {
+            0 | 1 => consonant_start,
+            _ => {
+                let keeps_together = |a: char, b: char| {
+                    matches!((a, b), ('c', 'h') | ('s', 'h') | ('t', 'h') | ('p', 'h') | ('w', 'h') | ('c', 'k') | ('n', 'g') | ('q', 'u'))
+                };
+                let last_two = (cluster[cluster.len() - 2], cluster[cluster.len() - 1]);
+                if keeps_together(last_two.0, last_two.1) && cluster.len() == 2 {
+                    // 双字母组合：ck/ng 归前一音节，其余归后一音节
+                    if matches!(last_two, ('c', 'k') | ('n', 'g')) { consonant_end } else { consonant_start }
+                } else if cluster.ends_with(&['l']) && consonant_end == len - 1 {
+                    // 辅音 + le：辅音随 le 成音节（ta-ble、lit-tle）
+                    consonant_end - 2
+                } else {
+                    consonant_start + 1
+                }
+            }
+        };
+        breaks.push(split);
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    for split in breaks {
+        if split > start && split < len {
+            result.push(chars[start..split].iter().collect());
+            start = split;
+        }
+    }
+    result.push(chars[start..].iter().collect());
+    result
+}
+
+/// 分块练习的音节块：逐词切分音节，按顺序连起来（不含空格）即为原内容
+pub fn syllable_chunks(content: &str) -> Vec<String> {
+    content.split_whitespace().flat_map(syllables).collect()
+}
+
+/// 输入中已按顺序正确拼完的音节块数（忽略空白和大小写），出错后的块不计
+pub fn completed_chunks(chunks: &[String], typed: &str) -> usize {
+    let mut typed = typed.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase);
+    chunks
+        .iter()
+        .take_while(|chunk| chunk.chars().flat_map(char::to_lowercase).all(|c| typed.next() == Some(c)))
+        .count()
+}
+
+/// 提示文本：1 = 第一个音节（多词时为第一个词），2 = 前一半，其余 = 完整内容
+pub fn hint_text(content: &str, hint_level: i32) -> String {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    if words.len() > 1 {
+        return match hint_level {
+            1 => words[0].to_string(),
+            2 => words[..words.len().div_ceil(2)].join(" "),
+            _ => content.trim().to_string(),
+        };
+    }
+
+    let word = content.trim();
+    let parts = syllables(word);
+    match hint_level {
+        1 => parts[0].clone(),
+        2 if parts.len() > 1 => parts[..parts.len().div_ceil(2)].concat(),
+        // 单音节词的“前一半”取前半个字母
+        2 => word.chars().take(word.chars().count().div_ceil(2)).collect(),
+        _ => word.to_string(),
+    }
+}
+
+/// 渐进提示：1 = 只显示长度，2 = 首字母，3 = 首字母并再读一遍，之后每次多显示一个字母
+///
+/// 返回提示类型、遮盖后的文本（未显示的字母用 `_`）和是否需要重新朗读。
+pub fn progressive_hint(content: &str, hint_level: i32) -> (&'static str, String, bool) {
+    let (kind, revealed, speak) = match hint_level {
+        ..=1 => ("length", 0, false),
+        2 => ("first_letter", 1, false),
+        3 => ("repeat_audio", 1, true),
+        n => ("letter", (n - 2) as usize, false),
+    };
+    let mut shown = 0;
+    let masked = content
+        .trim()
+        .chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                c
+            } else if shown < revealed {
+                shown += 1;
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    (kind, masked, speak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 音节切分：VCV / VCCV、双字母组合、词尾不发音的 e、辅音 + le 和元音组
+    #[test]
+    fn test_syllables() {
+        assert_eq!(syllables("tiger"), vec!["ti", "ger"]);
+        assert_eq!(syllables("rabbit"), vec!["rab", "bit"]);
+        assert_eq!(syllables("teacher"), vec!["tea", "cher"]);
+        assert_eq!(syllables("chicken"), vec!["chick", "en"]);
+        assert_eq!(syllables("singer"), vec!["sing", "er"]);
+        assert_eq!(syllables("Elephant"), vec!["E", "le", "phant"]);
+        // 词尾不发音的 e 不单独成音节
+        assert_eq!(syllables("cake"), vec!["cake"]);
+        assert_eq!(syllables("cupcake"), vec!["cup", "cake"]);
+        // 辅音 + le 单独成音节
+        assert_eq!(syllables("table"), vec!["ta", "ble"]);
+        assert_eq!(syllables("little"), vec!["lit", "tle"]);
+        // 相邻元音（含双元音）算一个音节核心
+        assert_eq!(syllables("rain"), vec!["rain"]);
+        assert_eq!(syllables("mountain"), vec!["moun", "tain"]);
+        assert_eq!(syllables("boy"), vec!["boy"]);
+        assert_eq!(syllables("happy"), vec!["hap", "py"]);
+        // 启发式的已知局限：元音相邻但分属两个音节时不拆开
+        assert_eq!(syllables("going"), vec!["going"]);
+    }
+
+    /// 提示文本：单词按音节，多词按单词
+    #[test]
+    fn test_hint_text() {
+        assert_eq!(hint_text("elephant", 1), "e");
+        assert_eq!(hint_text("elephant", 2), "ele");
+        assert_eq!(hint_text("elephant", 3), "elephant");
+        assert_eq!(hint_text("table", 1), "ta");
+        // 单音节词的“前一半”按字母取
+        assert_eq!(hint_text("cake", 1), "cake");
+        assert_eq!(hint_text("cake", 2), "ca");
+        assert_eq!(hint_text(" ice cream cone ", 1), "ice");
+        assert_eq!(hint_text("ice cream cone", 2), "ice cream");
+        assert_eq!(hint_text(" ice cream cone ", 3), "ice cream cone");
+    }
+
+    /// 分块练习按音节切块，逐块校验输入
+    #[test]
+    fn test_syllable_chunks() {
+        assert_eq!(syllable_chunks("rabbit"), vec!["rab", "bit"]);
+        assert_eq!(syllable_chunks("ice cream"), vec!["ice", "cream"]);
+        assert_eq!(syllable_chunks("Elephant").concat(), "Elephant");
+
+        let chunks = syllable_chunks("rabbit");
+        assert_eq!(completed_chunks(&chunks, ""), 0);
+        assert_eq!(completed_chunks(&chunks, "ra"), 0);
+        assert_eq!(completed_chunks(&chunks, "RAB"), 1);
+        assert_eq!(completed_chunks(&chunks, "rabbit"), 2);
+        // 出错后的块不计
+        assert_eq!(completed_chunks(&chunks, "rabbot"), 1);
+        assert_eq!(completed_chunks(&syllable_chunks("ice cream"), "ice cr"), 1);
+    }
+
+    /// 渐进提示逐级显示字母
+    #[test]
+    fn test_progressive_hint() {
+        assert_eq!(progressive_hint("apple", 1), ("length", "_____".to_string(), false));
+        assert_eq!(progressive_hint("apple", 2), ("first_letter", "a____".to_string(), false));
+        assert_eq!(progressive_hint("apple", 3), ("repeat_audio", "a____".to_string(), true));
+        assert_eq!(progressive_hint("ice cream", 5).1, "ice _____");
+        assert_eq!(progressive_hint("apple", 10).1, "apple");
+    }
+}
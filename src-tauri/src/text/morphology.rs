@@ -0,0 +1,233 @@
+//! 构词分析：把长单词拆成前缀、词根和后缀（内置常见词缀表），用于教构词规律
+//!
+//! 只有拆出的词干是常用词或已知词根时才接受拆分，避免把 reading 拆成 re-ading。
+
+use super::frequency::in_word_list;
+use crate::models::{MorphemePart, Morphology};
+
+/// 常见前缀及含义，按长度从长到短尝试
+const PREFIXES: &[(&str, &str)] = &[
+    ("counter", "反、对抗"),
+    ("extra", "超出"),
+    ("inter", "之间、相互"),
+    ("micro", "微小"),
+    ("multi", "多"),
+    ("super", "超级、在上"),
+    ("trans", "穿过、转移"),
+    ("under", "不足、在下"),
+    ("anti", "反对"),
+    ("over", "过度、在上"),
+    ("semi", "半"),
+    ("auto", "自己"),
+    ("fore", "预先"),
+    ("mono", "单一"),
+    ("post", "之后"),
+    ("tele", "远"),
+    ("com", "共同"),
+    ("con", "共同"),
+    ("dis", "不、相反"),
+    ("mid", "中间"),
+    ("mis", "错误"),
+    ("non", "非"),
+    ("pre", "之前"),
+    ("pro", "向前"),
+    ("sub", "在下"),
+    ("tri", "三"),
+    ("bi", "二"),
+    ("co", "共同"),
+    ("de", "去除、向下"),
+    ("em", "使"),
+    ("en", "使"),
+    ("ex", "向外、以前的"),
+    ("il", "不"),
+    ("im", "不"),
+    ("in", "不、向内"),
+    ("ir", "不"),
+    ("re", "再、回"),
+    ("un", "不"),
+];
+
+/// 常见后缀及含义，按长度从长到短尝试
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ation", "行为、结果"),
+    ("ship", "身份、关系"),
+    ("hood", "身份、时期"),
+    ("ible", "能够…的"),
+    ("able", "能够…的"),
+    ("ance", "状态、性质"),
+    ("ence", "状态、性质"),
+    ("ious", "具有…的"),
+    ("less", "没有"),
+    ("ment", "行为、结果"),
+    ("ness", "性质、状态"),
+    ("sion", "行为、状态"),
+    ("tion", "行为、状态"),
+    ("ward", "朝…方向"),
+    ("dom", "领域、状态"),
+    ("ful", "充满"),
+    ("ify", "使…化"),
+    ("ing", "正在、…的行为"),
+    ("ion", "行为、状态"),
+    ("ise", "使…化"),
+    ("ist", "…的人"),
+    ("ity", "性质"),
+    ("ive", "有…倾向的"),
+    ("ize", "使…化"),
+    ("ous", "具有…的"),
+    ("al", "与…有关的"),
+    ("ed", "过去式、被…的"),
+    ("er", "…的人、更"),
+    ("es", "复数、第三人称"),
+    ("ly", "…地"),
+    ("or", "…的人"),
+    ("s", "复数、第三人称"),
+    ("y", "有…的"),
+];
+
+/// 常见拉丁/希腊词根及含义
+const ROOTS: &[(&str, &str)] = &[
+    ("aud", "听"),
+    ("bio", "生命"),
+    ("cred", "相信"),
+    ("dict", "说"),
+    ("duct", "引导"),
+    ("form", "形状"),
+    ("geo", "地球"),
+    ("graph", "写、画"),
+    ("ject", "投掷"),
+    ("log", "言语、学问"),
+    ("meter", "测量"),
+    ("miss", "送"),
+    ("mit", "送"),
+    ("phon", "声音"),
+    ("photo", "光"),
+    ("port", "运送"),
+    ("rupt", "破裂"),
+    ("scrib", "写"),
+    ("script", "写"),
+    ("spect", "看"),
+    ("struct", "建造"),
+    ("tract", "拉"),
+    ("vent", "来"),
+    ("vid", "看"),
+    ("vis", "看"),
+    ("voc", "声音"),
+];
+
+/// 最多拆出的后缀数
+const MAX_SUFFIXES: usize = 3;
+/// 词干最少的字母数
+const MIN_STEM_LEN: usize = 3;
+
+/// 拆分单词；不是纯英文字母或拆不开时整个单词作为词根
+pub fn analyze(word: &str) -> Morphology {
+    let trimmed = word.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let whole = || Morphology { word: trimmed.to_string(), parts: vec![part(trimmed, "root", root_meaning(&lower))], base: None };
+    if lower.len() < 2 * MIN_STEM_LEN || !lower.bytes().all(|b| b.is_ascii_lowercase()) {
+        return whole();
+    }
+
+    // 前缀只在剩余部分可以识别时才接受；没有合适的前缀时只拆后缀
+    let prefixed = PREFIXES.iter().find_map(|(prefix, meaning)| {
+        let rest = lower.strip_prefix(prefix).filter(|rest| rest.len() >= MIN_STEM_LEN)?;
+        let split = split_suffixes(rest, 0).or_else(|| recognize(rest).map(|base| (rest.len(), Vec::new(), base)))?;
+        Some((prefix.len(), *meaning, split))
+    });
+    let (prefix_len, prefix_meaning, (stem_len, suffixes, base)) = match prefixed {
+        Some((len, meaning, split)) => (len, Some(meaning), split),
+        None => match split_suffixes(&lower, 0) {
+            Some(split) => (0, None, split),
+            None => return whole(),
+        },
+    };
+
+    let mut parts = Vec::new();
+    if let Some(meaning) = prefix_meaning {
+        parts.push(part(&trimmed[..prefix_len], "prefix", Some(meaning)));
+    }
+    let stem_end = prefix_len + stem_len;
+    let stem = &lower[prefix_len..stem_end];
+    parts.push(part(&trimmed[prefix_len..stem_end], "root", root_meaning(stem).or_else(|| root_meaning(&base))));
+    let mut start = stem_end;
+    for (suffix, meaning) in suffixes {
+        parts.push(part(&trimmed[start..start + suffix.len()], "suffix", Some(meaning)));
+        start += suffix.len();
+    }
+    Morphology { word: trimmed.to_string(), parts, base: (base != stem).then_some(base) }
+}
+
+fn part(text: &str, kind: &str, meaning: Option<&str>) -> MorphemePart {
+    MorphemePart { text: text.to_string(), kind: kind.to_string(), meaning: meaning.map(str::to_string) }
+}
+
+/// 后缀拆分结果：（词干长度, 从内到外的后缀及含义, 词干的原形）
+type SuffixSplit = (usize, Vec<(&'static str, &'static str)>, String);
+
+/// 从词尾拆后缀；优先拆得更细
+fn split_suffixes(word: &str, depth: usize) -> Option<SuffixSplit> {
+    if depth >= MAX_SUFFIXES {
+        return None;
+    }
+    SUFFIXES.iter().find_map(|&(suffix, meaning)| {
+        let stem = word.strip_suffix(suffix).filter(|stem| stem.len() >= MIN_STEM_LEN)?;
+        if let Some((stem_len, mut suffixes, base)) = split_suffixes(stem, depth + 1) {
+            suffixes.push((suffix, meaning));
+            return Some((stem_len, suffixes, base));
+        }
+        recognize(stem).map(|base| (stem.len(), vec![(suffix, meaning)], base))
+    })
+}
+
+/// 词干是常用词或已知词根时返回其原形（补回 e、去掉双写辅音、i 还原为 y）
+fn recognize(stem: &str) -> Option<String> {
+    let mut candidates = vec![stem.to_string(), format!("{}e", stem)];
+    let bytes = stem.as_bytes();
+    if bytes.len() > MIN_STEM_LEN && bytes[bytes.len() - 1] == bytes[bytes.len() - 2] {
+        candidates.push(stem[..stem.len() - 1].to_string());
+    }
+    if let Some(root) = stem.strip_suffix('i') {
+        candidates.push(format!("{}y", root));
+    }
+    candidates
+        .into_iter()
+        .find(|c| c.len() >= MIN_STEM_LEN && (in_word_list(c) || ROOTS.iter().any(|(root, _)| root == c)))
+}
+
+/// 词干中最长的已知词根的含义
+fn root_meaning(stem: &str) -> Option<&'static str> {
+    ROOTS
+        .iter()
+        .filter(|(root, _)| stem.contains(root))
+        .max_by_key(|(root, _)| root.len())
+        .map(|(_, meaning)| *meaning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 拆分前缀、词根、后缀，拆出的词干不认识时不拆
+    #[test]
+    fn test_analyze() {
+        let split = |word: &str| {
+            let m = analyze(word);
+            assert_eq!(m.parts.iter().map(|p| p.text.as_str()).collect::<String>(), m.word);
+            m.parts.iter().map(|p| format!("{}:{}", p.kind, p.text)).collect::<Vec<_>>().join(" ")
+        };
+        assert_eq!(split("unhappiness"), "prefix:un root:happi suffix:ness");
+        assert_eq!(analyze("unhappiness").base.as_deref(), Some("happy"));
+        assert_eq!(split("carefully"), "root:care suffix:ful suffix:ly");
+        assert_eq!(split("Transportation"), "prefix:Trans root:port suffix:ation");
+        assert_eq!(analyze("transportation").parts[1].meaning.as_deref(), Some("运送"));
+        assert_eq!(split("running"), "root:runn suffix:ing");
+        assert_eq!(analyze("running").base.as_deref(), Some("run"));
+        assert_eq!(split("teachers"), "root:teach suffix:er suffix:s");
+
+        assert_eq!(split("reading"), "root:read suffix:ing");
+        assert_eq!(split("string"), "root:string");
+        assert_eq!(split("interest"), "root:interest");
+        assert_eq!(split("cat"), "root:cat");
+        assert_eq!(split("e-mail"), "root:e-mail");
+    }
+}
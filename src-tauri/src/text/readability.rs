@@ -0,0 +1,55 @@
+use super::{syllables, tokenize};
+
+/// 英文文本的可读性指标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Readability {
+    /// Flesch-Kincaid 年级
+    pub grade: f64,
+    /// 平均句长（单词数）
+    pub avg_sentence_length: f64,
+    /// 不同单词数 / 总单词数
+    pub type_token_ratio: f64,
+}
+
+/// 计算 Flesch-Kincaid 年级、平均句长和词汇丰富度；没有英文单词时返回 None
+pub fn analyze(text: &str) -> Option<Readability> {
+    let words = tokenize(text);
+    if words.is_empty() {
+        return None;
+    }
+
+    let word_count = words.len() as f64;
+    let sentence_count = count_sentences(text).max(1) as f64;
+    let syllable_count: usize = words.iter().map(|w| syllables(w).len()).sum();
+    let distinct: std::collections::HashSet<&String> = words.iter().collect();
+
+    let avg_sentence_length = word_count / sentence_count;
+    let grade = 0.39 * avg_sentence_length + 11.8 * (syllable_count as f64 / word_count) - 15.59;
+    Some(Readability {
+        grade: grade.max(0.0),
+        avg_sentence_length,
+        type_token_ratio: distinct.len() as f64 / word_count,
+    })
+}
+
+/// 以句末标点（连续的算一个）或空行分句
+fn count_sentences(text: &str) -> usize {
+    text.split("\n\n")
+        .map(|paragraph| {
+            let mut count = 0;
+            let mut in_sentence = false;
+            for c in paragraph.chars() {
+                if matches!(c, '.' | '!' | '?' | '。' | '！' | '？') {
+                    if in_sentence {
+                        count += 1;
+                    }
+                    in_sentence = false;
+                } else if c.is_alphanumeric() {
+                    in_sentence = true;
+                }
+            }
+            // 段落末尾没有标点的最后一句
+            count + usize::from(in_sentence)
+        })
+        .sum()
+}
@@ -0,0 +1,41 @@
+//! 口语题按确认后的转写文本估算得分：覆盖示范回答中的实词，并达到一定长度
+
+use std::collections::HashSet;
+
+use super::{MIN_WORD_LEN, STOP_WORDS};
+
+/// 回答达到示范回答词数的该比例即视为长度足够
+const LENGTH_RATIO: f64 = 0.5;
+
+/// 得分比例（0-1）：示范回答实词的覆盖率与回答长度各占一半，转写为空时为 0
+pub fn speaking_credit(transcript: &str, sample_answer: &str) -> f64 {
+    let spoken = words(transcript);
+    if spoken.is_empty() {
+        return 0.0;
+    }
+    let sample = words(sample_answer);
+    let length = if sample.is_empty() {
+        1.0
+    } else {
+        (spoken.len() as f64 / (sample.len() as f64 * LENGTH_RATIO)).min(1.0)
+    };
+    let content: HashSet<&String> = sample
+        .iter()
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN && !STOP_WORDS.contains(&word.as_str()))
+        .collect();
+    let coverage = if content.is_empty() {
+        1.0
+    } else {
+        let spoken: HashSet<&String> = spoken.iter().collect();
+        content.iter().filter(|word| spoken.contains(*word)).count() as f64 / content.len() as f64
+    };
+    ((coverage + length) / 2.0 * 100.0).round() / 100.0
+}
+
+/// 小写单词列表，忽略标点
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
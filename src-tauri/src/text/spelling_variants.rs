@@ -0,0 +1,152 @@
+//! 美式/英式拼写对照表（内置），开启后 color 与 colour 视为同一个词
+
+/// （美式, 英式）
+const VARIANTS: &[(&str, &str)] = &[
+    ("color", "colour"),
+    ("favorite", "favourite"),
+    ("flavor", "flavour"),
+    ("honor", "honour"),
+    ("humor", "humour"),
+    ("labor", "labour"),
+    ("neighbor", "neighbour"),
+    ("behavior", "behaviour"),
+    ("harbor", "harbour"),
+    ("rumor", "rumour"),
+    ("vapor", "vapour"),
+    ("armor", "armour"),
+    ("odor", "odour"),
+    ("center", "centre"),
+    ("theater", "theatre"),
+    ("meter", "metre"),
+    ("kilometer", "kilometre"),
+    ("centimeter", "centimetre"),
+    ("liter", "litre"),
+    ("fiber", "fibre"),
+    ("gray", "grey"),
+    ("mom", "mum"),
+    ("program", "programme"),
+    ("catalog", "catalogue"),
+    ("dialog", "dialogue"),
+    ("analog", "analogue"),
+    ("defense", "defence"),
+    ("offense", "offence"),
+    ("license", "licence"),
+    ("practice", "practise"),
+    ("tire", "tyre"),
+    ("jewelry", "jewellery"),
+    ("pajamas", "pyjamas"),
+    ("aluminum", "aluminium"),
+    ("plow", "plough"),
+    ("mold", "mould"),
+    ("cozy", "cosy"),
+    ("donut", "doughnut"),
+    ("skeptical", "sceptical"),
+    ("aging", "ageing"),
+    ("airplane", "aeroplane"),
+    ("math", "maths"),
+    ("organize", "organise"),
+    ("realize", "realise"),
+    ("recognize", "recognise"),
+    ("apologize", "apologise"),
+    ("memorize", "memorise"),
+    ("organization", "organisation"),
+    ("analyze", "analyse"),
+    ("paralyze", "paralyse"),
+    ("traveler", "traveller"),
+    ("traveling", "travelling"),
+    ("traveled", "travelled"),
+    ("canceled", "cancelled"),
+    ("canceling", "cancelling"),
+    ("modeling", "modelling"),
+    ("labeled", "labelled"),
+    ("fulfill", "fulfil"),
+    ("enroll", "enrol"),
+    ("skillful", "skilful"),
+];
+
+/// 对照表查不到时去掉这些词尾再查（colours、coloured、organising）
+const INFLECTIONS: &[&str] = &["s", "ed", "ing", "er", "ers", "ful", "ly"];
+
+/// 把文本中的英式拼写统一为美式，保留首字母大写，其余字符不变
+pub fn to_american(text: &str) -> String {
+    map_words(text, |word| lookup(word, |(us, uk)| (uk, us)))
+}
+
+/// 把文本中的美式拼写统一为英式
+pub fn to_british(text: &str) -> String {
+    map_words(text, |word| lookup(word, |(us, uk)| (us, uk)))
+}
+
+/// 逐个替换文本中的英文单词
+fn map_words(text: &str, convert: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, result: &mut String| {
+        if !word.is_empty() {
+            match convert(&word.to_lowercase()) {
+                Some(converted) => result.push_str(&match_case(word, &converted)),
+                None => result.push_str(word),
+            }
+            word.clear();
+        }
+    };
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut result);
+            result.push(c);
+        }
+    }
+    flush(&mut word, &mut result);
+    result
+}
+
+/// 查对照表，`direction` 把（美式, 英式）映射为（源拼写, 目标拼写）
+fn lookup(word: &str, direction: impl Fn((&'static str, &'static str)) -> (&'static str, &'static str)) -> Option<String> {
+    let find = |w: &str| VARIANTS.iter().map(|&pair| direction(pair)).find(|(from, _)| *from == w).map(|(_, to)| to);
+    if let Some(to) = find(word) {
+        return Some(to.to_string());
+    }
+    // 已经是目标拼写（maths 不再变成 mathss）
+    if VARIANTS.iter().any(|&pair| direction(pair).1 == word) {
+        return None;
+    }
+    INFLECTIONS.iter().find_map(|suffix| {
+        let stem = word.strip_suffix(suffix)?;
+        if let Some(to) = find(stem) {
+            return Some(format!("{}{}", to, suffix));
+        }
+        // 元音开头的词尾前去掉了 e（organising = organise + ing），两种拼写都以 e 结尾时才还原
+        let to = find(&format!("{}e", stem)).filter(|_| suffix.starts_with(['e', 'i']))?;
+        to.strip_suffix('e').map(|to| format!("{}{}", to, suffix))
+    })
+}
+
+/// 按原词的大小写调整替换后的单词：全大写或首字母大写
+fn match_case(original: &str, converted: &str) -> String {
+    if original.len() > 1 && original.chars().all(|c| c.is_ascii_uppercase()) {
+        converted.to_uppercase()
+    } else if original.starts_with(|c: char| c.is_ascii_uppercase()) {
+        let mut chars = converted.chars();
+        chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+    } else {
+        converted.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 整词替换并保留大小写，不改动只是包含变体片段的单词
+    #[test]
+    fn test_convert_spelling() {
+        assert_eq!(to_american("My favourite Colours are grey."), "My favorite Colors are gray.");
+        assert_eq!(to_british("I organized the CENTER."), "I organised the CENTRE.");
+        assert_eq!(to_american("organising"), "organizing");
+        assert_eq!(to_british("maths"), "maths");
+        assert_eq!(to_american("colourful"), "colorful");
+        assert_eq!(to_american("collar"), "collar");
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{App, AppHandle, Emitter, Manager};
+
+use crate::database::DatabaseManager;
+
+const TRAY_ID: &str = "main-tray";
+const MAIN_WINDOW_LABEL: &str = "main";
+/// 托盘到期单词数的刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 创建系统托盘图标，并启动到期单词数的定时刷新
+pub fn setup_tray(app: &App) -> tauri::Result<()> {
+    let review_item = MenuItem::with_id(app, "start_review", "开始每日复习", true, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(app, &[&review_item, &separator, &show_item, &quit_item])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("单词拼写练习")
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "start_review" => {
+                if let Err(e) = show_main_window(app, true) {
+                    log::warn!("Failed to open daily review from tray: {}", e);
+                }
+            }
+            "show" => {
+                if let Err(e) = show_main_window(app, false) {
+                    log::warn!("Failed to show main window: {}", e);
+                }
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            refresh_due_badge(&handle, &review_item);
+        }
+    });
+
+    Ok(())
+}
+
+/// 显示并聚焦主窗口；`start_review` 为 true 时通知前端直接进入每日复习
+fn show_main_window(app: &AppHandle, start_review: bool) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return recreate_main_window(app, start_review);
+    };
+    window.show()?;
+    window.set_focus()?;
+    if start_review {
+        window.emit("tray-start-review", ())?;
+    }
+    Ok(())
+}
+
+/// 主窗口已关闭时按 `tauri.conf.json` 中的配置重新创建，页面加载完成后再通知前端
+fn recreate_main_window(app: &AppHandle, start_review: bool) -> tauri::Result<()> {
+    let Some(config) = app.config().app.windows.iter().find(|w| w.label == MAIN_WINDOW_LABEL) else {
+        log::warn!("No window config with label {}", MAIN_WINDOW_LABEL);
+        return Ok(());
+    };
+    let window = tauri::WebviewWindowBuilder::from_config(app, config)?
+        .on_page_load(move |window, payload| {
+            if start_review && matches!(payload.event(), tauri::webview::PageLoadEvent::Finished) {
+                let _ = window.emit("tray-start-review", ());
+            }
+        })
+        .build()?;
+    window.set_focus()
+}
+
+/// 刷新托盘上的到期单词数
+fn refresh_due_badge(app: &AppHandle, review_item: &MenuItem<tauri::Wry>) {
+    let due_count = {
+        let db = app.state::<Mutex<DatabaseManager>>();
+        let due = db.lock().ok().and_then(|db| db.count_due_words(None).ok());
+        due
+    };
+    let Some(due_count) = due_count else {
+        return;
+    };
+
+    let (label, tooltip) = if due_count > 0 {
+        (format!("开始每日复习（{} 个待复习）", due_count), format!("单词拼写练习 - {} 个单词待复习", due_count))
+    } else {
+        ("开始每日复习".to_string(), "单词拼写练习".to_string())
+    };
+    let _ = review_item.set_text(label);
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip));
+        #[cfg(target_os = "macos")]
+        let _ = tray.set_title(if due_count > 0 { Some(due_count.to_string()) } else { None });
+    }
+}
@@ -0,0 +1,127 @@
+//! 解码录音文件，计算时长和降采样后的波形包络，前端绘制播放进度条时不必在 JS 中解码
+//!
+//! 支持 wav、mp3、m4a/aac、ogg（Vorbis）；WebM 中的 Opus 无法解码，这类录音没有波形。
+
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use crate::models::RecordingAudio;
+
+/// 波形最多的点数
+pub const WAVEFORM_POINTS: usize = 200;
+/// 先按该时长（毫秒）分块求峰值，再合并为波形点，避免把整段采样留在内存中
+const BLOCK_MS: u64 = 10;
+
+/// 解码录音文件，返回时长、采样率、声道数和波形
+pub fn analyze_file(path: &Path) -> Result<RecordingAudio, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("无法识别录音格式: {}", e))?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("录音中没有音轨")?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("无法解码录音: {}", e))?;
+
+    let mut envelope = Envelope::default();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(Error::ResetRequired) => break,
+            Err(e) => return Err(e.to_string()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(Error::DecodeError(_)) => continue, // 跳过损坏的数据包
+            Err(e) => return Err(e.to_string()),
+        };
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        envelope.push(buffer.samples(), spec.channels.count(), spec.rate);
+    }
+    envelope.finish(WAVEFORM_POINTS).ok_or_else(|| "录音中没有音频数据".to_string())
+}
+
+/// 按块累计各声道的峰值
+#[derive(Default)]
+struct Envelope {
+    sample_rate: u32,
+    channels: usize,
+    frames: u64,
+    block_frames: u64,
+    block_peak: f32,
+    block_len: u64,
+    blocks: Vec<f32>,
+}
+
+impl Envelope {
+    /// 追加交错排列的采样
+    fn push(&mut self, samples: &[f32], channels: usize, sample_rate: u32) {
+        if channels == 0 || sample_rate == 0 {
+            return;
+        }
+        if self.sample_rate == 0 {
+            self.sample_rate = sample_rate;
+            self.channels = channels;
+            self.block_frames = (sample_rate as u64 * BLOCK_MS / 1000).max(1);
+        }
+        for frame in samples.chunks(channels) {
+            let peak = frame.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            self.block_peak = self.block_peak.max(peak);
+            self.block_len += 1;
+            self.frames += 1;
+            if self.block_len == self.block_frames {
+                self.blocks.push(self.block_peak);
+                self.block_peak = 0.0;
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// 合并为不超过 `points` 个波形点（每点为该段的峰值 0-1），没有采样时为空
+    fn finish(mut self, points: usize) -> Option<RecordingAudio> {
+        if self.frames == 0 {
+            return None;
+        }
+        if self.block_len > 0 {
+            self.blocks.push(self.block_peak);
+        }
+        let count = self.blocks.len();
+        let waveform = if count <= points {
+            self.blocks
+        } else {
+            (0..points)
+                .map(|i| self.blocks[i * count / points..(i + 1) * count / points].iter().copied().fold(0.0, f32::max))
+                .collect()
+        };
+        Some(RecordingAudio {
+            duration_ms: (self.frames * 1000 / self.sample_rate as u64) as i64,
+            sample_rate: self.sample_rate,
+            channels: self.channels as u16,
+            waveform: waveform.into_iter().map(|peak| (peak.min(1.0) * 1000.0).round() / 1000.0).collect(),
+        })
+    }
+}
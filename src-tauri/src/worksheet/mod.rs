@@ -0,0 +1,250 @@
+//! 可打印的练习纸（描红、选词填空、字母重排），生成 A4 PDF 供线下书写练习
+//!
+//! 只用 PDF 内置的 Helvetica 字体，不嵌入字体文件，所以只支持西文字符。
+
+/// 支持的练习纸样式
+pub const WORKSHEET_STYLES: &[&str] = &["trace", "fill_blanks", "scrambled"];
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 56.0;
+const BLANK: &str = "__________";
+
+/// 练习纸内容：单词表，选词填空还需要原文句子
+#[derive(Debug, Clone)]
+pub struct Worksheet {
+    pub title: String,
+    pub style: String,
+    pub words: Vec<String>,
+    pub sentences: Vec<String>,
+}
+
+/// 打乱字母顺序；同一个单词每次结果相同，且尽量不与原词相同
+pub fn scramble(word: &str) -> String {
+    let mut letters: Vec<char> = word.chars().collect();
+    let mut seed = word.bytes().fold(0x9e37_79b9_u64, |h, b| h.wrapping_mul(31).wrapping_add(b as u64));
+    for _ in 0..4 {
+        for i in (1..letters.len()).rev() {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            letters.swap(i, (seed >> 33) as usize % (i + 1));
+        }
+        if letters.iter().collect::<String>() != word {
+            break;
+        }
+    }
+    letters.into_iter().collect()
+}
+
+/// 把句子里第一个出现的单词表单词挖空，返回挖空后的句子；没有可挖的词时返回 None
+pub fn blank_sentence(sentence: &str, words: &[String]) -> Option<String> {
+    let lower = sentence.to_lowercase();
+    words.iter().find_map(|word| {
+        let word = word.to_lowercase();
+        lower.match_indices(&word).find_map(|(start, _)| {
+            let end = start + word.len();
+            let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+            if !(boundary(lower[..start].chars().next_back()) && boundary(lower[end..].chars().next())) {
+                return None;
+            }
+            Some(format!("{}{}{}", sentence.get(..start)?, BLANK, sentence.get(end..)?))
+        })
+    })
+}
+
+/// 一页上的绘制内容：(x, y, 字号, 灰度, 文字)
+type Line = (f64, f64, f64, f64, String);
+
+fn layout(sheet: &Worksheet) -> Result<Vec<Vec<Line>>, String> {
+    let mut pages: Vec<Vec<Line>> = vec![vec![]];
+    let mut y = PAGE_HEIGHT - MARGIN;
+    let push = |pages: &mut Vec<Vec<Line>>, y: &mut f64, height: f64, lines: Vec<Line>| {
+        if *y - height < MARGIN {
+            pages.push(vec![]);
+            *y = PAGE_HEIGHT - MARGIN;
+        }
+        let top = *y;
+        pages.last_mut().unwrap().extend(lines.into_iter().map(|(x, dy, size, gray, text)| (x, top - dy, size, gray, text)));
+        *y -= height;
+    };
+
+    push(&mut pages, &mut y, 36.0, vec![(MARGIN, 18.0, 18.0, 0.0, sheet.title.clone())]);
+    push(&mut pages, &mut y, 30.0, vec![(MARGIN, 10.0, 10.0, 0.4, "Name: ____________________    Date: ____________".to_string())]);
+
+    match sheet.style.as_str() {
+        "trace" => {
+            for word in &sheet.words {
+                let lines = vec![
+                    (MARGIN, 26.0, 26.0, 0.0, word.clone()),
+                    (MARGIN + 170.0, 26.0, 26.0, 0.75, format!("{}   {}", word, word)),
+                    (MARGIN, 36.0, 10.0, 0.6, "_".repeat(80)),
+                ];
+                push(&mut pages, &mut y, 48.0, lines);
+            }
+        }
+        "fill_blanks" => {
+            let bank = sheet.words.join("   ");
+            push(&mut pages, &mut y, 40.0, vec![(MARGIN, 14.0, 12.0, 0.0, format!("Word bank:   {}", bank))]);
+            let blanks: Vec<String> = sheet.sentences.iter().filter_map(|s| blank_sentence(s, &sheet.words)).collect();
+            if blanks.is_empty() {
+                return Err("原文中没有包含这些单词的句子，无法生成选词填空".to_string());
+            }
+            for (i, sentence) in blanks.iter().enumerate() {
+                for (j, line) in wrap(&format!("{}. {}", i + 1, sentence), 80).into_iter().enumerate() {
+                    push(&mut pages, &mut y, if j == 0 { 28.0 } else { 18.0 }, vec![(MARGIN, 14.0, 12.0, 0.0, line)]);
+                }
+            }
+        }
+        "scrambled" => {
+            for (i, word) in sheet.words.iter().enumerate() {
+                let lines = vec![
+                    (MARGIN, 18.0, 16.0, 0.0, format!("{}. {}", i + 1, scramble(word))),
+                    (MARGIN + 220.0, 18.0, 16.0, 0.6, BLANK.repeat(2)),
+                ];
+                push(&mut pages, &mut y, 36.0, lines);
+            }
+        }
+        other => return Err(format!("不支持的练习纸样式: {}，可选 {}", other, WORKSHEET_STYLES.join(" / "))),
+    }
+    Ok(pages)
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// PDF 字符串转义；Helvetica 用 WinAnsi 编码，Latin-1 以外的字符替换成 `?`
+fn pdf_string(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            c if (c as u32) >= 0xA0 && (c as u32) <= 0xFF => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// 生成练习纸 PDF
+pub fn render_pdf(sheet: &Worksheet) -> Result<Vec<u8>, String> {
+    if sheet.words.is_empty() {
+        return Err("单词表为空，无法生成练习纸".to_string());
+    }
+    let pages = layout(sheet)?;
+
+    // 对象编号：1 目录，2 页面树，3 字体，之后每页占两个（页面、内容流）
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len()).map(|i| format!("{} 0 R", 4 + i * 2)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    for (i, lines) in pages.iter().enumerate() {
+        let stream: String = lines
+            .iter()
+            .map(|(x, y, size, gray, text)| {
+                format!("BT {:.2} g /F1 {:.1} Tf {:.1} {:.1} Td ({}) Tj ET\n", gray, size, x, y, pdf_string(text))
+            })
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT, 5 + i * 2
+            )
+            .into_bytes(),
+        );
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", stream.len(), stream).into_bytes());
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+    Ok(pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 选词填空只按整词挖空
+    #[test]
+    fn test_blank_sentence() {
+        let words: Vec<String> = ["apple", "banana", "cherry"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(blank_sentence("I ate an Apple today.", &words).as_deref(), Some("I ate an __________ today."));
+        // pineapple 里的 apple 不算
+        assert_eq!(blank_sentence("Pineapple is sweet (very).", &words), None);
+    }
+
+    /// 字母重排打乱顺序，同一个词结果固定
+    #[test]
+    fn test_scramble() {
+        let scrambled = scramble("banana");
+        assert_ne!(scrambled, "banana");
+        assert_eq!(scrambled, scramble("banana"));
+        let mut letters: Vec<char> = scrambled.chars().collect();
+        letters.sort();
+        assert_eq!(letters.into_iter().collect::<String>(), "aaabnn");
+    }
+
+    /// 每种样式都能生成 PDF，超出一页时分页，缺少句子或未知样式报错
+    #[test]
+    fn test_render_pdf() {
+        for style in WORKSHEET_STYLES {
+            let sheet = Worksheet {
+                title: "Fruit (unit 1)".to_string(),
+                style: style.to_string(),
+                words: vec!["apple".to_string(), "banana".to_string()],
+                sentences: vec!["I ate an Apple today.".to_string(), "Pineapple is sweet (very).".to_string()],
+            };
+            let pdf = render_pdf(&sheet).unwrap();
+            assert!(pdf.starts_with(b"%PDF-1.4"));
+            assert!(pdf.ends_with(b"%%EOF\n"));
+            let text = String::from_utf8_lossy(&pdf);
+            assert!(text.contains("(Fruit \\(unit 1\\)) Tj"));
+        }
+
+        let many = Worksheet {
+            title: "Long".to_string(),
+            style: "trace".to_string(),
+            words: (0..40).map(|i| format!("word{}", i)).collect(),
+            sentences: vec![],
+        };
+        let pdf = String::from_utf8_lossy(&render_pdf(&many).unwrap()).to_string();
+        assert!(pdf.contains("/Count 3"));
+
+        let no_sentences = Worksheet { style: "fill_blanks".to_string(), sentences: vec![], ..many.clone() };
+        assert!(render_pdf(&no_sentences).is_err());
+        assert!(render_pdf(&Worksheet { style: "maze".to_string(), ..many }).is_err());
+    }
+}
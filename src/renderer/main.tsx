@@ -2,8 +2,12 @@ import React from 'react'
 import ReactDOM from 'react-dom/client'
 import { HashRouter } from 'react-router-dom'
 import App from './App'
+import { startTtsBridge } from './utils/ttsBridge'
 import './styles/global.css'
 
+// 移动端由 WebView 播放后端发来的朗读
+startTtsBridge().catch((e) => console.warn('Failed to start TTS bridge:', e))
+
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <React.StrictMode>
     <HashRouter>
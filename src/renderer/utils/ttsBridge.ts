@@ -0,0 +1,79 @@
+/**
+ * 移动端朗读桥接
+ * 后端在移动端没有原生朗读插件，发出 `tts-speak` / `tts-stop` 事件，
+ * 这里用 WebView 的 speechSynthesis 播放，结束后发出 `tts-complete`。
+ * 桌面端后端自己播放，不会发出这两个事件。
+ */
+import { emit, listen } from '@tauri-apps/api/event';
+
+/** `tts-speak` 事件（与后端 MobileSpeakPayload 一致） */
+interface TtsSpeakPayload {
+  text: string;
+  rate: number;       // 语速倍率，1.0 = 默认语速
+  lang: string;       // BCP 47 语言标签
+  interrupt: 'queue' | 'replace' | 'ignore';
+  id: number;
+}
+
+/** `tts-complete` 事件（与桌面端后端发出的格式一致） */
+interface TtsCompletePayload {
+  id: number;
+  completed: boolean;  // false 表示被忽略、被打断或播放失败
+  error: string | null;
+}
+
+// 已交给 speechSynthesis、还没有发出 tts-complete 的播放编号
+const pending = new Set<number>();
+
+function emitComplete(payload: TtsCompletePayload): void {
+  emit('tts-complete', payload).catch((e) => console.warn('Failed to emit tts-complete:', e));
+}
+
+/** 每个播放编号只发出一次 tts-complete（cancel 之后 onerror 还会再触发） */
+function complete(id: number, completed: boolean, error: string | null = null): void {
+  if (pending.delete(id)) {
+    emitComplete({ id, completed, error });
+  }
+}
+
+/** 取消正在播放和排队的朗读，它们都按未完成结束 */
+function cancelAll(): void {
+  const ids = [...pending];
+  window.speechSynthesis.cancel();
+  ids.forEach((id) => complete(id, false));
+}
+
+function speakPayload(payload: TtsSpeakPayload): void {
+  const synth = window.speechSynthesis;
+  if (payload.interrupt === 'ignore' && pending.size > 0) {
+    emitComplete({ id: payload.id, completed: false, error: null });
+    return;
+  }
+  if (payload.interrupt === 'replace') {
+    cancelAll();
+  }
+
+  const utterance = new SpeechSynthesisUtterance(payload.text);
+  utterance.rate = payload.rate;
+  utterance.lang = payload.lang;
+  utterance.onend = () => complete(payload.id, true);
+  utterance.onerror = (event) => {
+    const interrupted = event.error === 'interrupted' || event.error === 'canceled';
+    complete(payload.id, false, interrupted ? null : event.error);
+  };
+  pending.add(payload.id);
+  synth.speak(utterance);
+}
+
+/** 开始监听后端的朗读事件，返回取消监听的函数；WebView 不支持朗读时不监听 */
+export async function startTtsBridge(): Promise<() => void> {
+  if (!('speechSynthesis' in window)) {
+    return () => {};
+  }
+  const unlistenSpeak = await listen<TtsSpeakPayload>('tts-speak', (event) => speakPayload(event.payload));
+  const unlistenStop = await listen('tts-stop', () => cancelAll());
+  return () => {
+    unlistenSpeak();
+    unlistenStop();
+  };
+}